@@ -264,6 +264,13 @@ pub trait RequestExt: sealed::Sealed + Sized {
     /// [default limit](crate::extract::DefaultBodyLimit) is in place, or not wrapped if the
     /// default limit is disabled.
     fn into_limited_body(self) -> Body;
+
+    /// Get the [default body limit](crate::extract::DefaultBodyLimit) that applies to this
+    /// request.
+    ///
+    /// Returns `None` if the limit is disabled. This can be used by extractors to include the
+    /// limit in their rejections.
+    fn body_limit(&self) -> Option<usize>;
 }
 
 impl RequestExt for Request {
@@ -320,22 +327,19 @@ impl RequestExt for Request {
     }
 
     fn with_limited_body(self) -> Request {
-        // update docs in `axum-core/src/extract/default_body_limit.rs` and
-        // `axum/src/docs/extract.md` if this changes
-        const DEFAULT_LIMIT: usize = 2_097_152; // 2 mb
-
-        match self.extensions().get::<DefaultBodyLimitKind>().copied() {
-            Some(DefaultBodyLimitKind::Disable) => self,
-            Some(DefaultBodyLimitKind::Limit(limit)) => {
-                self.map(|b| Body::new(http_body_util::Limited::new(b, limit)))
-            }
-            None => self.map(|b| Body::new(http_body_util::Limited::new(b, DEFAULT_LIMIT))),
+        match self.body_limit() {
+            Some(limit) => self.map(|b| Body::new(http_body_util::Limited::new(b, limit))),
+            None => self,
         }
     }
 
     fn into_limited_body(self) -> Body {
         self.with_limited_body().into_body()
     }
+
+    fn body_limit(&self) -> Option<usize> {
+        DefaultBodyLimitKind::effective_limit(self.extensions())
+    }
 }
 
 #[cfg(test)]
@@ -406,6 +410,18 @@ mod tests {
         assert_eq!(req.headers()["x-foo"], "foo");
     }
 
+    #[test]
+    fn body_limit() {
+        let mut req = Request::new(Body::empty());
+        assert_eq!(req.body_limit(), Some(2_097_152));
+
+        req.extensions_mut().insert(DefaultBodyLimitKind::Limit(10));
+        assert_eq!(req.body_limit(), Some(10));
+
+        req.extensions_mut().insert(DefaultBodyLimitKind::Disable);
+        assert_eq!(req.body_limit(), None);
+    }
+
     // this stuff just needs to compile
     #[allow(dead_code)]
     struct WorksForCustomExtractor {
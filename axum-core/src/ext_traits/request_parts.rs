@@ -1,4 +1,4 @@
-use crate::extract::FromRequestParts;
+use crate::extract::{DefaultBodyLimitKind, FromRequestParts};
 use futures_util::future::BoxFuture;
 use http::request::Parts;
 
@@ -117,6 +117,12 @@ pub trait RequestPartsExt: sealed::Sealed + Sized {
     where
         E: FromRequestParts<S> + 'static,
         S: Send + Sync;
+
+    /// Get the [default body limit](crate::extract::DefaultBodyLimit) that applies to the
+    /// request.
+    ///
+    /// Returns `None` if the limit is disabled.
+    fn body_limit(&self) -> Option<usize>;
 }
 
 impl RequestPartsExt for Parts {
@@ -137,6 +143,10 @@ impl RequestPartsExt for Parts {
     {
         E::from_request_parts(self, state)
     }
+
+    fn body_limit(&self) -> Option<usize> {
+        DefaultBodyLimitKind::effective_limit(&self.extensions)
+    }
 }
 
 #[cfg(test)]
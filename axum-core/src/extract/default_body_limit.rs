@@ -84,6 +84,20 @@ pub(crate) enum DefaultBodyLimitKind {
     Limit(usize),
 }
 
+impl DefaultBodyLimitKind {
+    // update docs above and in `axum/src/docs/extract.md` if this changes
+    pub(crate) const DEFAULT_LIMIT: usize = 2_097_152; // 2 mb
+
+    /// The limit that applies to a request with the given extensions, or `None` if disabled.
+    pub(crate) fn effective_limit(extensions: &http::Extensions) -> Option<usize> {
+        match extensions.get::<Self>().copied() {
+            Some(Self::Disable) => None,
+            Some(Self::Limit(limit)) => Some(limit),
+            None => Some(Self::DEFAULT_LIMIT),
+        }
+    }
+}
+
 impl DefaultBodyLimit {
     /// Disable the default request body limit.
     ///
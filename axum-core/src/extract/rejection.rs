@@ -4,6 +4,47 @@ use crate::__composite_rejection as composite_rejection;
 use crate::__define_rejection as define_rejection;
 
 use crate::{BoxError, Error};
+use http::StatusCode;
+
+/// Details about a rejection from one of axum's built-in extractors.
+///
+/// Built-in rejections add this to the extensions of the responses they are converted into. This
+/// makes it possible to recognize those responses and replace them with responses in a custom
+/// format, for example with `Router::rejection_handler` in axum.
+///
+/// Custom rejections can opt in by inserting a `RejectionInfo` into their responses as well.
+#[derive(Debug, Clone)]
+pub struct RejectionInfo {
+    rejection_type: &'static str,
+    status: StatusCode,
+    body_text: String,
+}
+
+impl RejectionInfo {
+    /// Create a new `RejectionInfo` for the rejection type `T`.
+    pub fn new<T>(status: StatusCode, body_text: impl Into<String>) -> Self {
+        Self {
+            rejection_type: std::any::type_name::<T>(),
+            status,
+            body_text: body_text.into(),
+        }
+    }
+
+    /// The name of the rejection type, such as `axum::extract::rejection::JsonDataError`.
+    pub fn rejection_type(&self) -> &'static str {
+        self.rejection_type
+    }
+
+    /// The status code of the rejection's response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The body text of the rejection's response.
+    pub fn body_text(&self) -> &str {
+        &self.body_text
+    }
+}
 
 composite_rejection! {
     /// Rejection type for extractors that buffer the request body. Used if the
@@ -43,7 +43,11 @@ macro_rules! __define_rejection {
                     body_text = $body,
                     status = http::StatusCode::$status,
                 );
-                (self.status(), $body).into_response()
+                let mut res = (self.status(), $body).into_response();
+                res.extensions_mut().insert(
+                    $crate::extract::rejection::RejectionInfo::new::<$name>(self.status(), $body),
+                );
+                res
             }
         }
 
@@ -95,12 +99,20 @@ macro_rules! __define_rejection {
 
         impl $crate::response::IntoResponse for $name {
             fn into_response(self) -> $crate::response::Response {
+                let body_text = self.body_text();
                 $crate::__log_rejection!(
                     rejection_type = $name,
-                    body_text = self.body_text(),
+                    body_text = body_text,
                     status = http::StatusCode::$status,
                 );
-                (self.status(), self.body_text()).into_response()
+                let mut res = (self.status(), body_text.clone()).into_response();
+                res.extensions_mut().insert(
+                    $crate::extract::rejection::RejectionInfo::new::<$name>(
+                        self.status(),
+                        body_text,
+                    ),
+                );
+                res
             }
         }
 
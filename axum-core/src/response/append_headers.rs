@@ -57,7 +57,9 @@ where
     type Error = TryIntoHeaderError<K::Error, V::Error>;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
-        for (key, value) in self.0 {
+        let headers = self.0.into_iter();
+        res.reserve_headers(headers.size_hint().0);
+        for (key, value) in headers {
             let key = key.try_into().map_err(TryIntoHeaderError::key)?;
             let value = value.try_into().map_err(TryIntoHeaderError::value)?;
             res.headers_mut().append(key, value);
@@ -404,7 +404,8 @@ macro_rules! impl_into_response {
                 let ($($ty),*, res) = self;
 
                 let res = res.into_response();
-                let parts = ResponseParts { res };
+                let mut parts = ResponseParts { res };
+                parts.reserve_headers(0 $( + $ty.header_count_hint() )*);
 
                 $(
                     let parts = match $ty.into_response_parts(parts) {
@@ -429,7 +430,8 @@ macro_rules! impl_into_response {
                 let (status, $($ty),*, res) = self;
 
                 let res = res.into_response();
-                let parts = ResponseParts { res };
+                let mut parts = ResponseParts { res };
+                parts.reserve_headers(0 $( + $ty.header_count_hint() )*);
 
                 $(
                     let parts = match $ty.into_response_parts(parts) {
@@ -454,7 +456,9 @@ macro_rules! impl_into_response {
                 let (outer_parts, $($ty),*, res) = self;
 
                 let res = res.into_response();
-                let parts = ResponseParts { res };
+                let mut parts = ResponseParts { res };
+                parts.reserve_headers(0 $( + $ty.header_count_hint() )*);
+
                 $(
                     let parts = match $ty.into_response_parts(parts) {
                         Ok(parts) => parts,
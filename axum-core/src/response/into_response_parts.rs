@@ -78,6 +78,19 @@ pub trait IntoResponseParts {
 
     /// Set parts of the response
     fn into_response_parts(self, res: ResponseParts) -> Result<ResponseParts, Self::Error>;
+
+    /// Get an estimate of how many headers [`into_response_parts`] will add.
+    ///
+    /// When a response is built from several parts, such as a tuple of parts, the hints of all
+    /// parts are used to reserve room for their headers up front, instead of growing the header
+    /// map part by part. The hint is only used for that, so it's fine for it to be wrong.
+    ///
+    /// Defaults to `0`.
+    ///
+    /// [`into_response_parts`]: IntoResponseParts::into_response_parts
+    fn header_count_hint(&self) -> usize {
+        0
+    }
 }
 
 impl<T> IntoResponseParts for Option<T>
@@ -93,6 +106,10 @@ where
             Ok(res)
         }
     }
+
+    fn header_count_hint(&self) -> usize {
+        self.as_ref().map_or(0, T::header_count_hint)
+    }
 }
 
 /// Parts of a response.
@@ -114,6 +131,15 @@ impl ResponseParts {
         self.res.headers_mut()
     }
 
+    /// Reserve room for at least `additional` more headers.
+    ///
+    /// Use this in [`IntoResponseParts`] implementations that add many headers, so the header
+    /// map grows once instead of for every header. Since it's only an optimization, nothing is
+    /// reserved if that would exceed the maximum size of the header map.
+    pub fn reserve_headers(&mut self, additional: usize) {
+        let _ = self.res.headers_mut().try_reserve(additional);
+    }
+
     /// Gets a reference to the response extensions.
     pub fn extensions(&self) -> &Extensions {
         self.res.extensions()
@@ -132,6 +158,10 @@ impl IntoResponseParts for HeaderMap {
         res.headers_mut().extend(self);
         Ok(res)
     }
+
+    fn header_count_hint(&self) -> usize {
+        self.len()
+    }
 }
 
 impl<K, V, const N: usize> IntoResponseParts for [(K, V); N]
@@ -144,6 +174,7 @@ where
     type Error = TryIntoHeaderError<K::Error, V::Error>;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.reserve_headers(N);
         for (key, value) in self {
             let key = key.try_into().map_err(TryIntoHeaderError::key)?;
             let value = value.try_into().map_err(TryIntoHeaderError::value)?;
@@ -152,6 +183,10 @@ where
 
         Ok(res)
     }
+
+    fn header_count_hint(&self) -> usize {
+        N
+    }
 }
 
 /// Error returned if converting a value to a header fails.
@@ -230,7 +265,8 @@ macro_rules! impl_into_response_parts {
         {
             type Error = Response;
 
-            fn into_response_parts(self, res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+            fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+                res.reserve_headers(self.header_count_hint());
                 let ($($ty,)*) = self;
 
                 $(
@@ -244,6 +280,11 @@ macro_rules! impl_into_response_parts {
 
                 Ok(res)
             }
+
+            fn header_count_hint(&self) -> usize {
+                let ($($ty,)*) = self;
+                0 $( + $ty.header_count_hint() )*
+            }
         }
     }
 }
@@ -266,3 +307,25 @@ impl IntoResponseParts for () {
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuple_reserves_headers_for_all_parts() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-a", HeaderValue::from_static("a"));
+        let parts = (
+            headers,
+            [("x-b", "b"), ("x-c", "c")],
+            Some([("x-d", "d")]),
+            Extensions::new(),
+        );
+        assert_eq!(parts.header_count_hint(), 4);
+
+        let res = (parts, ()).into_response();
+        assert!(res.headers().capacity() >= 4);
+        assert_eq!(res.headers().len(), 4);
+    }
+}
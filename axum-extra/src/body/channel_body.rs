@@ -0,0 +1,238 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    response::{IntoResponse, Response},
+    Error,
+};
+use futures_util::task::AtomicWaker;
+use http::HeaderMap;
+use http_body::Frame;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// A body that streams the data sent with a [`BodySender`].
+///
+/// The channel is bounded, so [`BodySender::send`] waits while the client is slower than the
+/// producer. The body ends once all `BodySender`s have been dropped, or is aborted with
+/// [`BodySender::abort`], which the client sees as a truncated response.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::body::ChannelBody;
+///
+/// async fn numbers() -> ChannelBody {
+///     let (tx, body) = ChannelBody::new(16);
+///
+///     tokio::spawn(async move {
+///         for n in 0..1000 {
+///             if tx.send(format!("{n}\n")).await.is_err() {
+///                 // the client disconnected
+///                 return;
+///             }
+///         }
+///     });
+///
+///     body
+/// }
+///
+/// let app = Router::new().route("/numbers", get(numbers));
+/// # let _: Router = app;
+/// ```
+#[must_use]
+pub struct ChannelBody {
+    rx: mpsc::Receiver<Frame<Bytes>>,
+    shared: Arc<Shared>,
+    done: bool,
+}
+
+struct Shared {
+    aborted: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl ChannelBody {
+    /// Create a new `ChannelBody` that buffers up to `capacity` frames, and the [`BodySender`]
+    /// that writes to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> (BodySender, Self) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let shared = Arc::new(Shared {
+            aborted: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        });
+        let sender = BodySender {
+            tx,
+            shared: Arc::clone(&shared),
+        };
+        let body = Self {
+            rx,
+            shared,
+            done: false,
+        };
+        (sender, body)
+    }
+}
+
+impl HttpBody for ChannelBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        self.shared.waker.register(cx.waker());
+        if self.shared.aborted.load(Ordering::Acquire) {
+            self.done = true;
+            return Poll::Ready(Some(Err(Error::new("response body aborted"))));
+        }
+
+        let frame = std::task::ready!(self.rx.poll_recv(cx));
+        if frame.is_none() {
+            self.done = true;
+        }
+        Poll::Ready(frame.map(Ok))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+impl IntoResponse for ChannelBody {
+    fn into_response(self) -> Response {
+        Body::new(self).into_response()
+    }
+}
+
+impl fmt::Debug for ChannelBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelBody").finish_non_exhaustive()
+    }
+}
+
+/// Sends data to a [`ChannelBody`].
+///
+/// Cloning a `BodySender` creates another sender for the same body.
+#[derive(Clone)]
+pub struct BodySender {
+    tx: mpsc::Sender<Frame<Bytes>>,
+    shared: Arc<Shared>,
+}
+
+impl BodySender {
+    /// Send a chunk of data, waiting if the body's buffer is full.
+    ///
+    /// Returns an error if the body has been dropped, usually because the client disconnected.
+    pub async fn send(&self, data: impl Into<Bytes>) -> Result<(), BodyClosed> {
+        self.send_frame(Frame::data(data.into())).await
+    }
+
+    /// Send trailers, waiting if the body's buffer is full.
+    ///
+    /// Trailers must be sent last, and dropping all senders afterwards ends the body.
+    ///
+    /// Returns an error if the body has been dropped, usually because the client disconnected.
+    pub async fn send_trailers(&self, trailers: HeaderMap) -> Result<(), BodyClosed> {
+        self.send_frame(Frame::trailers(trailers)).await
+    }
+
+    async fn send_frame(&self, frame: Frame<Bytes>) -> Result<(), BodyClosed> {
+        self.tx
+            .send(frame)
+            .await
+            .map_err(|_| BodyClosed { _priv: () })
+    }
+
+    /// Abort the body.
+    ///
+    /// The body produces an error, which makes the server close the connection without
+    /// finishing the response. Data that is still buffered isn't sent.
+    pub fn abort(&self) {
+        self.shared.aborted.store(true, Ordering::Release);
+        self.shared.waker.wake();
+    }
+
+    /// Whether the body has been dropped, usually because the client disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl fmt::Debug for BodySender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodySender").finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`BodySender`] if the [`ChannelBody`] has been dropped.
+#[derive(Debug)]
+pub struct BodyClosed {
+    _priv: (),
+}
+
+impl fmt::Display for BodyClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The body has been dropped")
+    }
+}
+
+impl std::error::Error for BodyClosed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn streams_frames() {
+        let (tx, body) = ChannelBody::new(1);
+
+        let task = tokio::spawn(async move {
+            tx.send("hello ").await.unwrap();
+            tx.send(Bytes::from_static(b"world")).await.unwrap();
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-checksum", "abc".parse().unwrap());
+            tx.send_trailers(trailers).await.unwrap();
+        });
+
+        let collected = body.collect().await.unwrap();
+        task.await.unwrap();
+        assert_eq!(collected.trailers().unwrap()["x-checksum"], "abc");
+        assert_eq!(collected.to_bytes(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn abort() {
+        let (tx, body) = ChannelBody::new(1);
+        tx.send("hello").await.unwrap();
+        tx.abort();
+
+        assert!(body.collect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn closed() {
+        let (tx, body) = ChannelBody::new(1);
+        assert!(!tx.is_closed());
+        drop(body);
+        assert!(tx.is_closed());
+        assert!(tx.send("hello").await.is_err());
+    }
+}
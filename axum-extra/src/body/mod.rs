@@ -1,7 +1,17 @@
 //! Additional bodies.
 
+mod tee_body;
+
 #[cfg(feature = "async-read-body")]
 mod async_read_body;
 
+#[cfg(feature = "channel-body")]
+mod channel_body;
+
+pub use self::tee_body::{TeeBody, TeeSink, TeeSummary, TruncationPolicy};
+
 #[cfg(feature = "async-read-body")]
 pub use self::async_read_body::AsyncReadBody;
+
+#[cfg(feature = "channel-body")]
+pub use self::channel_body::{BodyClosed, BodySender, ChannelBody};
@@ -0,0 +1,344 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    Error,
+};
+use http_body::{Frame, SizeHint};
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Receives a copy of the data of a [`TeeBody`].
+pub trait TeeSink: Send + 'static {
+    /// Called with the data of the body as it's sent, up to the limit of the `TeeBody`.
+    fn write(&mut self, chunk: Bytes);
+
+    /// Called once the body has ended, failed, or been dropped.
+    ///
+    /// With [`TruncationPolicy::KeepEnd`] this is called after writing the end of the body.
+    fn finish(&mut self, summary: &TeeSummary) {
+        let _ = summary;
+    }
+}
+
+/// Summary of a [`TeeBody`], passed to [`TeeSink::finish`].
+#[derive(Debug, Clone)]
+pub struct TeeSummary {
+    bytes_sent: u64,
+    bytes_written: u64,
+    completed: bool,
+}
+
+impl TeeSummary {
+    /// The number of bytes of the body that were sent.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// The number of bytes that were written to the sink.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Whether the sink didn't receive all the data that was sent because of the limit.
+    pub fn truncated(&self) -> bool {
+        self.bytes_written < self.bytes_sent
+    }
+
+    /// Whether the whole body was sent, rather than failing or being dropped early.
+    pub fn completed(&self) -> bool {
+        self.completed
+    }
+}
+
+/// Which data a [`TeeBody`] writes to its sink once the body exceeds the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TruncationPolicy {
+    /// Write the start of the body, as it's sent, and nothing after the limit.
+    #[default]
+    KeepStart,
+    /// Write the end of the body once it has finished. Up to `limit` bytes are held in memory.
+    KeepEnd,
+}
+
+/// A body that copies its data into a [`TeeSink`] while it's sent to the client.
+///
+/// This allows logging response payloads, for example for auditing, without buffering the whole
+/// body first. At most [`limit`](Self::limit) bytes are written to the sink, chosen by the
+/// [`TruncationPolicy`]. The sink is told whether the data was truncated and whether the body
+/// was sent completely in [`TeeSink::finish`].
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get, body::{Body, Bytes}, middleware::map_response, response::Response};
+/// use axum_extra::body::{TeeBody, TeeSink, TeeSummary};
+///
+/// struct AuditLog {
+///     payload: Vec<u8>,
+/// }
+///
+/// impl TeeSink for AuditLog {
+///     fn write(&mut self, chunk: Bytes) {
+///         self.payload.extend_from_slice(&chunk);
+///     }
+///
+///     fn finish(&mut self, summary: &TeeSummary) {
+///         println!(
+///             "sent {} bytes (truncated: {}): {:?}",
+///             summary.bytes_sent(),
+///             summary.truncated(),
+///             String::from_utf8_lossy(&self.payload),
+///         );
+///     }
+/// }
+///
+/// async fn audit(res: Response) -> Response {
+///     res.map(|body| {
+///         let sink = AuditLog { payload: Vec::new() };
+///         Body::new(TeeBody::new(body, sink).limit(64 * 1024))
+///     })
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(|| async { "Hello, World!" }))
+///     .layer(map_response(audit));
+/// # let _: Router = app;
+/// ```
+pub struct TeeBody<S>
+where
+    S: TeeSink,
+{
+    inner: Body,
+    sink: S,
+    limit: u64,
+    policy: TruncationPolicy,
+    tail: VecDeque<Bytes>,
+    tail_len: u64,
+    bytes_sent: u64,
+    bytes_written: u64,
+    finished: bool,
+}
+
+impl<S> TeeBody<S>
+where
+    S: TeeSink,
+{
+    /// Create a new `TeeBody` that copies the data of `body` into `sink`, without a limit.
+    pub fn new(body: Body, sink: S) -> Self {
+        Self {
+            inner: body,
+            sink,
+            limit: u64::MAX,
+            policy: TruncationPolicy::default(),
+            tail: VecDeque::new(),
+            tail_len: 0,
+            bytes_sent: 0,
+            bytes_written: 0,
+            finished: false,
+        }
+    }
+
+    /// Set the maximum number of bytes written to the sink.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set which data is written to the sink once the body exceeds the limit.
+    ///
+    /// Defaults to [`TruncationPolicy::KeepStart`].
+    pub fn truncation_policy(mut self, policy: TruncationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn tee(&mut self, data: &Bytes) {
+        self.bytes_sent += data.len() as u64;
+
+        match self.policy {
+            TruncationPolicy::KeepStart => {
+                let remaining = self.limit - self.bytes_written;
+                let len = (data.len() as u64).min(remaining) as usize;
+                if len > 0 {
+                    self.bytes_written += len as u64;
+                    self.sink.write(data.slice(..len));
+                }
+            }
+            TruncationPolicy::KeepEnd => {
+                self.tail.push_back(data.clone());
+                self.tail_len += data.len() as u64;
+                while self.tail_len > self.limit {
+                    let excess = self.tail_len - self.limit;
+                    let front = self.tail.front_mut().expect("tail isn't empty");
+                    if front.len() as u64 <= excess {
+                        self.tail_len -= front.len() as u64;
+                        self.tail.pop_front();
+                    } else {
+                        *front = front.slice(excess as usize..);
+                        self.tail_len -= excess;
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, completed: bool) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+
+        for chunk in self.tail.drain(..) {
+            self.bytes_written += chunk.len() as u64;
+            self.sink.write(chunk);
+        }
+        self.sink.finish(&TeeSummary {
+            bytes_sent: self.bytes_sent,
+            bytes_written: self.bytes_written,
+            completed,
+        });
+    }
+}
+
+// the sink is never pinned
+impl<S> Unpin for TeeBody<S> where S: TeeSink {}
+
+impl<S> HttpBody for TeeBody<S>
+where
+    S: TeeSink,
+{
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    self.tee(data);
+                }
+            }
+            Some(Err(_)) => self.finish(false),
+            None => self.finish(true),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Drop for TeeBody<S>
+where
+    S: TeeSink,
+{
+    fn drop(&mut self) {
+        // servers stop polling once the body reports that it has ended
+        let completed = self.inner.is_end_stream();
+        self.finish(completed);
+    }
+}
+
+impl<S> fmt::Debug for TeeBody<S>
+where
+    S: TeeSink,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeBody")
+            .field("limit", &self.limit)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+    use http_body_util::BodyExt;
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Default, Clone)]
+    struct Captured(Arc<Mutex<(Vec<u8>, Option<TeeSummary>)>>);
+
+    impl TeeSink for Captured {
+        fn write(&mut self, chunk: Bytes) {
+            self.0.lock().unwrap().0.extend_from_slice(&chunk);
+        }
+
+        fn finish(&mut self, summary: &TeeSummary) {
+            self.0.lock().unwrap().1 = Some(summary.clone());
+        }
+    }
+
+    fn body() -> Body {
+        let chunks = ["hello", " ", "world"].map(Ok::<_, Infallible>);
+        Body::from_stream(stream::iter(chunks))
+    }
+
+    async fn tee(body: TeeBody<Captured>, captured: &Captured) -> (String, TeeSummary) {
+        let sent = body.collect().await.unwrap().to_bytes();
+        assert_eq!(sent, "hello world");
+        let (data, summary) = captured.0.lock().unwrap().clone();
+        (String::from_utf8(data).unwrap(), summary.unwrap())
+    }
+
+    #[tokio::test]
+    async fn unlimited() {
+        let captured = Captured::default();
+        let (data, summary) = tee(TeeBody::new(body(), captured.clone()), &captured).await;
+        assert_eq!(data, "hello world");
+        assert_eq!(summary.bytes_sent(), 11);
+        assert!(!summary.truncated());
+        assert!(summary.completed());
+    }
+
+    #[tokio::test]
+    async fn keep_start() {
+        let captured = Captured::default();
+        let body = TeeBody::new(body(), captured.clone()).limit(7);
+        let (data, summary) = tee(body, &captured).await;
+        assert_eq!(data, "hello w");
+        assert_eq!(summary.bytes_written(), 7);
+        assert!(summary.truncated());
+    }
+
+    #[tokio::test]
+    async fn keep_end() {
+        let captured = Captured::default();
+        let body = TeeBody::new(body(), captured.clone())
+            .limit(7)
+            .truncation_policy(TruncationPolicy::KeepEnd);
+        let (data, summary) = tee(body, &captured).await;
+        assert_eq!(data, "o world");
+        assert!(summary.truncated());
+        assert!(summary.completed());
+    }
+
+    #[tokio::test]
+    async fn dropped() {
+        let captured = Captured::default();
+        let mut body = TeeBody::new(body(), captured.clone());
+        body.frame().await.unwrap().unwrap();
+        drop(body);
+
+        let (data, summary) = captured.0.lock().unwrap().clone();
+        assert_eq!(data, b"hello");
+        assert!(!summary.unwrap().completed());
+    }
+}
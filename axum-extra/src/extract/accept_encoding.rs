@@ -0,0 +1,278 @@
+use axum::{async_trait, extract::FromRequestParts};
+use http::{header::ACCEPT_ENCODING, request::Parts, HeaderMap};
+use std::{convert::Infallible, fmt};
+
+/// Extractor for the content codings accepted by the client, from the `Accept-Encoding` header.
+///
+/// [`AcceptEncoding::choose`] picks the best of the encodings the server can produce, taking the
+/// quality values sent by the client into account. This is useful for serving assets that are
+/// compressed ahead of time, such as `.br` and `.gz` files on disk, without compressing responses
+/// on the fly.
+///
+/// If the request doesn't have an `Accept-Encoding` header only [`Encoding::Identity`] is
+/// considered acceptable. Responses that depend on the negotiated encoding should include
+/// `Vary: Accept-Encoding` so caches store each representation separately.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     Router,
+///     routing::get,
+///     http::{header::{CONTENT_ENCODING, VARY}, HeaderValue, StatusCode},
+///     response::{IntoResponse, Response},
+/// };
+/// use axum_extra::extract::{AcceptEncoding, Encoding};
+///
+/// async fn app_js(accept_encoding: AcceptEncoding) -> Response {
+///     let available = [Encoding::Br, Encoding::Gzip, Encoding::Identity];
+///     let Some(encoding) = accept_encoding.choose(&available) else {
+///         return StatusCode::NOT_ACCEPTABLE.into_response();
+///     };
+///
+///     let path = match encoding {
+///         Encoding::Br => "assets/app.js.br",
+///         Encoding::Gzip => "assets/app.js.gz",
+///         _ => "assets/app.js",
+///     };
+///     let Ok(contents) = tokio::fs::read(path).await else {
+///         return StatusCode::NOT_FOUND.into_response();
+///     };
+///
+///     let mut res = contents.into_response();
+///     res.headers_mut()
+///         .insert(VARY, HeaderValue::from_static("accept-encoding"));
+///     if encoding != Encoding::Identity {
+///         res.headers_mut()
+///             .insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+///     }
+///     res
+/// }
+///
+/// let app = Router::new().route("/app.js", get(app_js));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AcceptEncoding {
+    codings: Vec<(Coding, f32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Coding {
+    Encoding(Encoding),
+    Any,
+}
+
+/// A content coding that can be negotiated with [`AcceptEncoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// No encoding.
+    Identity,
+    /// `gzip`
+    Gzip,
+    /// `deflate`
+    Deflate,
+    /// `br`, Brotli.
+    Br,
+    /// `zstd`, Zstandard.
+    Zstd,
+}
+
+impl Encoding {
+    /// The name of the encoding as used in the `Content-Encoding` header.
+    ///
+    /// Note that `identity` shouldn't be sent in `Content-Encoding`. Omit the header instead.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Identity => "identity",
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+            Self::Br => "br",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let encoding = if s.eq_ignore_ascii_case("identity") {
+            Self::Identity
+        } else if s.eq_ignore_ascii_case("gzip") || s.eq_ignore_ascii_case("x-gzip") {
+            Self::Gzip
+        } else if s.eq_ignore_ascii_case("deflate") {
+            Self::Deflate
+        } else if s.eq_ignore_ascii_case("br") {
+            Self::Br
+        } else if s.eq_ignore_ascii_case("zstd") {
+            Self::Zstd
+        } else {
+            return None;
+        };
+        Some(encoding)
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AcceptEncoding {
+    /// Parse the `Accept-Encoding` header.
+    ///
+    /// Unknown content codings and codings with invalid quality values are ignored.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let codings = headers
+            .get_all(ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|item| {
+                let mut params = item.split(';');
+                let coding = params.next()?.trim();
+                let coding = if coding == "*" {
+                    Coding::Any
+                } else {
+                    Coding::Encoding(Encoding::parse(coding)?)
+                };
+
+                let mut quality = 1.0;
+                for param in params {
+                    let (key, value) = param.trim().split_once('=')?;
+                    if key.trim().eq_ignore_ascii_case("q") {
+                        quality = value.trim().parse::<f32>().ok()?;
+                    }
+                }
+                (0.0..=1.0).contains(&quality).then_some((coding, quality))
+            })
+            .collect();
+
+        Self { codings }
+    }
+
+    /// The quality value the client assigned to `encoding`, between `0` and `1`.
+    ///
+    /// An encoding with a quality of `0` isn't acceptable.
+    pub fn quality(&self, encoding: Encoding) -> f32 {
+        let mut any = None;
+        for &(coding, quality) in &self.codings {
+            match coding {
+                Coding::Encoding(coding) if coding == encoding => return quality,
+                Coding::Any => any = Some(quality),
+                Coding::Encoding(_) => {}
+            }
+        }
+        // identity is always acceptable unless explicitly excluded
+        any.unwrap_or(if encoding == Encoding::Identity {
+            1.0
+        } else {
+            0.0
+        })
+    }
+
+    /// Choose the encoding to use for the response.
+    ///
+    /// `available` are the encodings the server can produce, in order of preference. The
+    /// acceptable encoding with the highest quality value is returned, and ties are broken by the
+    /// order of `available`. Returns `None` if none of the encodings are acceptable, in which case
+    /// the server may respond with `406 Not Acceptable` or fall back to [`Encoding::Identity`].
+    pub fn choose(&self, available: &[Encoding]) -> Option<Encoding> {
+        let mut best: Option<(Encoding, f32)> = None;
+        for &encoding in available {
+            let quality = self.quality(encoding);
+            let better = match best {
+                Some((_, best)) => quality > best,
+                None => quality > 0.0,
+            };
+            if better {
+                best = Some((encoding, quality));
+            }
+        }
+        best.map(|(encoding, _)| encoding)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AcceptEncoding
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use http::HeaderValue;
+
+    const ALL: &[Encoding] = &[
+        Encoding::Zstd,
+        Encoding::Br,
+        Encoding::Gzip,
+        Encoding::Deflate,
+        Encoding::Identity,
+    ];
+
+    fn choose(value: Option<&'static str>, available: &[Encoding]) -> Option<Encoding> {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = value {
+            headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(value));
+        }
+        AcceptEncoding::from_headers(&headers).choose(available)
+    }
+
+    #[test]
+    fn choosing() {
+        assert_eq!(choose(None, ALL), Some(Encoding::Identity));
+        assert_eq!(choose(Some(""), ALL), Some(Encoding::Identity));
+        assert_eq!(choose(Some("gzip, br"), ALL), Some(Encoding::Br));
+        assert_eq!(
+            choose(Some("gzip;q=1.0, br;q=0.5"), ALL),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(choose(Some("X-GZIP"), ALL), Some(Encoding::Gzip));
+        assert_eq!(choose(Some("*"), ALL), Some(Encoding::Zstd));
+        assert_eq!(choose(Some("br;q=0, *;q=0.1"), ALL), Some(Encoding::Zstd));
+        assert_eq!(
+            choose(Some("gzip, compress, br;q=nope"), ALL),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            choose(Some("br"), &[Encoding::Gzip, Encoding::Identity]),
+            Some(Encoding::Identity)
+        );
+        assert_eq!(choose(Some("identity;q=0"), &[Encoding::Identity]), None);
+        assert_eq!(choose(Some("*;q=0"), &[Encoding::Identity]), None);
+        assert_eq!(
+            choose(Some("*;q=0, identity"), &[Encoding::Identity]),
+            Some(Encoding::Identity)
+        );
+    }
+
+    #[tokio::test]
+    async fn extracting() {
+        async fn handler(accept_encoding: AcceptEncoding) -> String {
+            accept_encoding
+                .choose(&[Encoding::Br, Encoding::Gzip, Encoding::Identity])
+                .map(|encoding| encoding.to_string())
+                .unwrap_or_default()
+        }
+
+        let client = TestClient::new(Router::new().route("/", get(handler)));
+
+        let res = client.get("/").header("accept-encoding", "gzip").await;
+        assert_eq!(res.text().await, "gzip");
+
+        let res = client
+            .get("/")
+            .header("accept-encoding", "br;q=0.8, identity;q=0.9")
+            .await;
+        assert_eq!(res.text().await, "identity");
+    }
+}
@@ -0,0 +1,221 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    response::{IntoResponse, Response},
+};
+use http::{request::Parts, StatusCode};
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+/// App state that holds values of any type, keyed by their type.
+///
+/// Usually the state of an app is a single struct, with a [`FromRef`] implementation for each
+/// part of it that handlers extract. That works poorly when the app is composed at runtime, for
+/// example from plugins that each need their own state. `AnyState` lets every part register its
+/// state independently and handlers extract it with [`FromState`].
+///
+/// Cloning `AnyState` is cheap since the values are reference counted.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::extract::{AnyState, FromState};
+///
+/// #[derive(Clone)]
+/// struct DbPool;
+///
+/// #[derive(Clone)]
+/// struct Metrics;
+///
+/// async fn handler(FromState(pool): FromState<DbPool>) {
+///     // ...
+/// }
+///
+/// let mut state = AnyState::new().with(DbPool);
+///
+/// // for example, only if a plugin is enabled
+/// state.insert(Metrics);
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .with_state(state);
+/// # let _: Router = app;
+/// ```
+#[derive(Clone, Default)]
+pub struct AnyState {
+    map: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl AnyState {
+    /// Create a new, empty `AnyState`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, replacing any previous value of the same type.
+    pub fn insert<T>(&mut self, value: T)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.map).insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Insert a value and return `self`, replacing any previous value of the same type.
+    pub fn with<T>(mut self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.insert(value);
+        self
+    }
+
+    /// Get a reference to the value of type `T`.
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.map.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// Remove the value of type `T`.
+    pub fn remove<T>(&mut self) -> bool
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.map)
+            .remove(&TypeId::of::<T>())
+            .is_some()
+    }
+
+    /// Whether a value of type `T` has been inserted.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+}
+
+impl fmt::Debug for AnyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyState")
+            .field("len", &self.map.len())
+            .finish()
+    }
+}
+
+/// Extractor for a value stored in [`AnyState`].
+///
+/// `AnyState` must be accessible from the state using [`FromRef`]. If no value of type `T` has
+/// been inserted the request is rejected with [`MissingState`], which is a bug in the app and
+/// responds with `500 Internal Server Error`.
+///
+/// See [`AnyState`] for an example.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FromState<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for FromState<T>
+where
+    T: Clone + Send + Sync + 'static,
+    AnyState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = MissingState;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        AnyState::from_ref(state)
+            .get::<T>()
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| MissingState {
+                type_name: type_name::<T>(),
+            })
+    }
+}
+
+axum_core::__impl_deref!(FromState);
+
+/// Rejection used for [`FromState`] if no value of the requested type was inserted into
+/// [`AnyState`].
+#[derive(Debug)]
+pub struct MissingState {
+    type_name: &'static str,
+}
+
+impl MissingState {
+    /// The name of the type that was missing.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl IntoResponse for MissingState {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for MissingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Missing state of type `{}`. Insert it into `AnyState`",
+            self.type_name
+        )
+    }
+}
+
+impl std::error::Error for MissingState {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    #[test]
+    fn type_map() {
+        let mut state = AnyState::new().with(1_u32);
+        let clone = state.clone();
+        state.insert(2_u32);
+        state.insert(String::from("hi"));
+
+        assert_eq!(state.get::<u32>(), Some(&2));
+        assert_eq!(clone.get::<u32>(), Some(&1));
+        assert_eq!(clone.get::<String>(), None);
+
+        assert!(state.remove::<String>());
+        assert!(!state.contains::<String>());
+    }
+
+    #[tokio::test]
+    async fn from_state() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|FromState(n): FromState<u32>| async move { n.to_string() }),
+            )
+            .route("/missing", get(|_: FromState<String>| async {}))
+            .with_state(AnyState::new().with(42_u32));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.text().await, "42");
+
+        let res = client.get("/missing").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            res.text().await,
+            "Missing state of type `alloc::string::String`. Insert it into `AnyState`"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use axum::{
+    async_trait,
+    body::{BodyDataStream, Bytes},
+    extract::{FromRequest, Request},
+};
+use futures_util::{stream::MapErr, TryStreamExt};
+use std::{
+    convert::Infallible,
+    fmt, io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+use tokio_util::io::StreamReader;
+
+/// Extractor that exposes the request body as an [`AsyncRead`] and [`AsyncBufRead`].
+///
+/// This makes it possible to stream the body into anything that reads from an `AsyncRead`, such
+/// as parsers, decompressors, or [`tokio::io::copy`], without buffering it in memory first.
+///
+/// Errors from the underlying body are returned as [`io::Error`]s.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{Router, routing::post, http::StatusCode};
+/// use axum_extra::extract::BodyReader;
+/// use tokio::fs::File;
+///
+/// async fn upload(mut body: BodyReader) -> Result<(), StatusCode> {
+///     let mut file = File::create("upload.bin")
+///         .await
+///         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+///
+///     tokio::io::copy(&mut body, &mut file)
+///         .await
+///         .map_err(|_| StatusCode::BAD_REQUEST)?;
+///
+///     Ok(())
+/// }
+///
+/// let app = Router::new().route("/upload", post(upload));
+/// # let _: Router = app;
+/// ```
+///
+/// Since this extractor consumes the request body it must be the last argument of the handler.
+pub struct BodyReader {
+    inner: StreamReader<MapErr<BodyDataStream, fn(axum::Error) -> io::Error>, Bytes>,
+}
+
+impl BodyReader {
+    /// Create a new `BodyReader` from a request body.
+    pub fn new(body: axum::body::Body) -> Self {
+        let stream = body
+            .into_data_stream()
+            .map_err(into_io_error as fn(axum::Error) -> io::Error);
+        Self {
+            inner: StreamReader::new(stream),
+        }
+    }
+}
+
+fn into_io_error(err: axum::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+impl fmt::Debug for BodyReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyReader").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for BodyReader
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::new(req.into_body()))
+    }
+}
+
+impl AsyncRead for BodyReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncBufRead for BodyReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().inner).poll_fill_buf(cx)
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        Pin::new(&mut self.inner).consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use tokio::io::AsyncBufReadExt;
+
+    #[tokio::test]
+    async fn reads_body() {
+        async fn handler(body: BodyReader) -> String {
+            let mut lines = body.lines();
+            let mut out = Vec::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                out.push(line);
+            }
+            out.join(",")
+        }
+
+        let client = TestClient::new(Router::new().route("/", post(handler)));
+        let res = client.post("/").body("foo\nbar\nbaz").await;
+        assert_eq!(res.text().await, "foo,bar,baz");
+    }
+}
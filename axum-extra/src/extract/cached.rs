@@ -1,8 +1,15 @@
 use axum::{
     async_trait,
-    extract::{Extension, FromRequestParts},
+    extract::{Extension, FromRef, FromRequestParts},
 };
 use http::request::Parts;
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 /// Cache results of other extractors.
 ///
@@ -12,7 +19,8 @@ use http::request::Parts;
 /// you only want to run once, perhaps because they're expensive.
 ///
 /// The cache purely type based so you can only cache one value of each type. The cache is also
-/// local to the current request and not reused across requests.
+/// local to the current request and not reused across requests. Use [`SharedCached`] to also
+/// reuse values across requests.
 ///
 /// # Example
 ///
@@ -108,6 +116,287 @@ where
 
 axum_core::__impl_deref!(Cached);
 
+/// Cache results of other extractors across requests.
+///
+/// `SharedCached` works like [`Cached`] but additionally stores values in a [`SharedCache`] so
+/// they can be reused by later requests with the same [key](CacheKey), until the cache's time to
+/// live expires or the entry is [invalidated](SharedCache::invalidate).
+///
+/// The [`SharedCache`] must be accessible from the state using [`FromRef`]. Within a request the
+/// value is also cached in the request extensions, so it's shared with [`Cached<T>`] extractors.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_extra::extract::{CacheKey, SharedCache, SharedCached};
+/// use axum::{
+///     async_trait,
+///     extract::{FromRef, FromRequestParts, State},
+///     http::{HeaderMap, StatusCode, request::Parts},
+///     routing::{get, post},
+///     Router,
+/// };
+/// use std::time::Duration;
+///
+/// #[derive(Clone)]
+/// struct CurrentUser { /* ... */ }
+///
+/// #[async_trait]
+/// impl<S> FromRequestParts<S> for CurrentUser
+/// where
+///     S: Send + Sync,
+/// {
+///     type Rejection = StatusCode;
+///
+///     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+///         // load the user from the database...
+///         # unimplemented!()
+///     }
+/// }
+///
+/// impl<S> CacheKey<S> for CurrentUser {
+///     type Key = String;
+///
+///     fn cache_key(parts: &Parts, _state: &S) -> Option<Self::Key> {
+///         // cache users by their session token
+///         let token = parts.headers.get("x-session-token")?.to_str().ok()?;
+///         Some(token.to_owned())
+///     }
+/// }
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     users: SharedCache<CurrentUser, String>,
+/// }
+///
+/// impl FromRef<AppState> for SharedCache<CurrentUser, String> {
+///     fn from_ref(state: &AppState) -> Self {
+///         state.users.clone()
+///     }
+/// }
+///
+/// async fn profile(SharedCached(user): SharedCached<CurrentUser>) {
+///     // ...
+/// }
+///
+/// async fn logout(State(users): State<SharedCache<CurrentUser, String>>, headers: HeaderMap) {
+///     if let Some(token) = headers.get("x-session-token") {
+///         users.invalidate(token.to_str().unwrap_or_default());
+///     }
+/// }
+///
+/// let state = AppState {
+///     users: SharedCache::new(Duration::from_secs(60)),
+/// };
+///
+/// let app = Router::new()
+///     .route("/profile", get(profile))
+///     .route("/logout", post(logout))
+///     .with_state(state);
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedCached<T>(pub T);
+
+/// Key used to look up values in a [`SharedCache`].
+///
+/// Implement this for extractors used with [`SharedCached`].
+pub trait CacheKey<S> {
+    /// The type of the key.
+    type Key: Hash + Eq + Send + Sync + 'static;
+
+    /// Compute the key for the current request.
+    ///
+    /// Returning `None` skips the shared cache, so the value is extracted and only cached for the
+    /// current request.
+    fn cache_key(parts: &Parts, state: &S) -> Option<Self::Key>;
+}
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for SharedCached<T>
+where
+    S: Send + Sync,
+    T: FromRequestParts<S> + CacheKey<S> + Clone + Send + Sync + 'static,
+    SharedCache<T, T::Key>: FromRef<S>,
+{
+    type Rejection = T::Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(CachedEntry(value)) = parts.extensions.get::<CachedEntry<T>>() {
+            return Ok(Self(value.clone()));
+        }
+
+        let cache = SharedCache::<T, T::Key>::from_ref(state);
+        let key = T::cache_key(parts, state);
+
+        let cached = key.as_ref().and_then(|key| cache.get(key));
+        let value = match cached {
+            Some(value) => value,
+            None => {
+                let value = T::from_request_parts(parts, state).await?;
+                if let Some(key) = key {
+                    cache.insert(key, value.clone());
+                }
+                value
+            }
+        };
+
+        parts.extensions.insert(CachedEntry(value.clone()));
+        Ok(Self(value))
+    }
+}
+
+axum_core::__impl_deref!(SharedCached);
+
+/// Cache of extracted values shared across requests, used by [`SharedCached`].
+///
+/// Entries expire after the time to live passed to [`SharedCache::new`]. The cache holds at most
+/// [`max_entries`](SharedCache::max_entries) values, once it's full the oldest values are evicted
+/// to make room for new ones. Cloning a `SharedCache` is cheap and all clones share the same
+/// entries.
+pub struct SharedCache<T, K> {
+    entries: Arc<Mutex<Entries<K, T>>>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+struct Entries<K, T> {
+    map: HashMap<K, (T, Instant)>,
+    next_sweep: usize,
+}
+
+impl<K, T> Default for Entries<K, T> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+            next_sweep: 0,
+        }
+    }
+}
+
+/// Expired values are only swept once there are at least this many.
+const MIN_SWEEP: usize = 1024;
+
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+impl<T, K> SharedCache<T, K>
+where
+    T: Clone,
+    K: Hash + Eq,
+{
+    /// Create a new empty `SharedCache` whose entries expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Default::default(),
+            ttl,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Set the maximum number of values the cache holds.
+    ///
+    /// When the cache is full, expired values are removed first and then the oldest values, in
+    /// batches of an eighth of `max_entries` so that inserting doesn't have to scan the cache
+    /// every time. Setting this to zero disables the shared cache.
+    ///
+    /// Defaults to 10,000.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Get the value cached for `key`, if it hasn't expired.
+    pub fn get<Q>(&self, key: &Q) -> Option<T>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entries = self.entries.lock().unwrap();
+        let (value, inserted_at) = entries.map.get(key)?;
+        (inserted_at.elapsed() < self.ttl).then(|| value.clone())
+    }
+
+    /// Cache `value` for `key`, replacing any previous value.
+    pub fn insert(&self, key: K, value: T) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.map.len() >= entries.next_sweep.max(MIN_SWEEP) {
+            entries.remove_expired(self.ttl, now);
+        }
+
+        if entries.map.len() >= self.max_entries && !entries.map.contains_key(&key) {
+            entries.remove_expired(self.ttl, now);
+            entries.remove_oldest(self.max_entries);
+        }
+
+        entries.map.insert(key, (value, now));
+    }
+
+    /// Remove the value cached for `key`, so the next request extracts it again.
+    pub fn invalidate<Q>(&self, key: &Q)
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.lock().unwrap().map.remove(key);
+    }
+
+    /// Remove all cached values.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().map.clear();
+    }
+}
+
+impl<K, T> Entries<K, T> {
+    fn remove_expired(&mut self, ttl: Duration, now: Instant) {
+        self.map
+            .retain(|_, (_, inserted_at)| now.duration_since(*inserted_at) < ttl);
+        self.next_sweep = self.map.len() * 2;
+    }
+
+    /// Make room for at least one new value, without going over `max_entries`.
+    fn remove_oldest(&mut self, max_entries: usize) {
+        if self.map.len() < max_entries {
+            return;
+        }
+
+        let batch = (max_entries / 8).max(1);
+        let count = (self.map.len() - max_entries + batch).min(self.map.len());
+        let mut inserted = self
+            .map
+            .values()
+            .map(|(_, inserted_at)| *inserted_at)
+            .collect::<Vec<_>>();
+        let (_, &mut cutoff, _) = inserted.select_nth_unstable(count - 1);
+        self.map.retain(|_, (_, inserted_at)| *inserted_at > cutoff);
+        self.next_sweep = self.map.len() * 2;
+    }
+}
+
+impl<T, K> Clone for SharedCache<T, K> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+            ttl: self.ttl,
+            max_entries: self.max_entries,
+        }
+    }
+}
+
+impl<T, K> fmt::Debug for SharedCache<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedCache")
+            .field("ttl", &self.ttl)
+            .field("max_entries", &self.max_entries)
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,6 +447,84 @@ mod tests {
         assert_eq!(first, second);
     }
 
+    #[tokio::test]
+    async fn shared_across_requests() {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Extractor(u32);
+
+        #[async_trait]
+        impl<S> FromRequestParts<S> for Extractor
+        where
+            S: Send + Sync,
+        {
+            type Rejection = Infallible;
+
+            async fn from_request_parts(
+                _parts: &mut Parts,
+                _state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                Ok(Self(COUNTER.fetch_add(1, Ordering::SeqCst)))
+            }
+        }
+
+        impl<S> CacheKey<S> for Extractor {
+            type Key = String;
+
+            fn cache_key(parts: &Parts, _state: &S) -> Option<Self::Key> {
+                Some(parts.uri.path().to_owned())
+            }
+        }
+
+        async fn extract(cache: &SharedCache<Extractor, String>, path: &str) -> u32 {
+            let (mut parts, _) = Request::get(path).body(()).unwrap().into_parts();
+            let SharedCached(Extractor(n)) = SharedCached::from_request_parts(&mut parts, cache)
+                .await
+                .unwrap();
+            n
+        }
+
+        let cache = SharedCache::new(Duration::from_secs(60));
+
+        assert_eq!(extract(&cache, "/a").await, 0);
+        assert_eq!(extract(&cache, "/a").await, 0);
+        assert_eq!(extract(&cache, "/b").await, 1);
+
+        cache.invalidate("/a");
+        assert_eq!(extract(&cache, "/a").await, 2);
+        assert_eq!(extract(&cache, "/b").await, 1);
+
+        let expired = SharedCache::new(Duration::ZERO);
+        assert_eq!(extract(&expired, "/a").await, 3);
+        assert_eq!(extract(&expired, "/a").await, 4);
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let cache = SharedCache::new(Duration::from_secs(60)).max_entries(16);
+
+        for n in 0..16 {
+            cache.insert(n, n);
+        }
+        assert_eq!(cache.entries.lock().unwrap().map.len(), 16);
+
+        cache.insert(16, 16);
+        assert_eq!(cache.get(&0), None);
+        assert_eq!(cache.get(&16), Some(16));
+        assert!(cache.entries.lock().unwrap().map.len() <= 16);
+
+        // replacing a value doesn't evict anything
+        let len = cache.entries.lock().unwrap().map.len();
+        cache.insert(16, 17);
+        assert_eq!(cache.get(&16), Some(17));
+        assert_eq!(cache.entries.lock().unwrap().map.len(), len);
+
+        let disabled = SharedCache::new(Duration::from_secs(60)).max_entries(0);
+        disabled.insert(0, 0);
+        assert_eq!(disabled.get(&0), None);
+    }
+
     // Not a #[test], we just want to know this compiles
     async fn _last_handler_argument() {
         async fn handler(_: http::Method, _: Cached<http::HeaderMap>) {}
@@ -0,0 +1,485 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    response::{IntoResponse, Response},
+};
+use http::{request::Parts, HeaderMap, StatusCode};
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+/// Extractor for the IP address of the client, taking trusted proxies into account.
+///
+/// Proxies and load balancers forward the address of the client they received a request from in
+/// headers. Those headers can be set by anyone though, so they must only be believed if they
+/// were set by a proxy you control. `ClientIp` walks the chain of addresses from the header set
+/// with [`TrustedProxies::header`], starting at the proxy closest to the server, and returns the
+/// first address that isn't a [trusted proxy](TrustedProxies).
+///
+/// Only the configured header is read, since clients can send any of the other ones themselves.
+/// If the peer of the TCP connection isn't a trusted proxy the header is ignored and the address
+/// of the peer is used.
+///
+/// This requires [`TrustedProxies`] to be accessible from the state using [`FromRef`], and the
+/// app to be served with [`into_make_service_with_connect_info`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{Router, routing::get};
+/// use axum_extra::extract::{ClientIp, TrustedProxies};
+/// use std::net::SocketAddr;
+///
+/// async fn handler(ClientIp(ip): ClientIp) -> String {
+///     format!("Hello {ip}")
+/// }
+///
+/// // trust the load balancer and everything in `10.0.0.0/8`, which set `X-Forwarded-For`
+/// let trusted_proxies = TrustedProxies::new()
+///     .trust("192.0.2.1".parse().unwrap())
+///     .trust_network("10.0.0.0".parse().unwrap(), 8);
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .with_state(trusted_proxies);
+///
+/// # async {
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+/// axum::serve(
+///     listener,
+///     app.into_make_service_with_connect_info::<SocketAddr>(),
+/// )
+/// .await
+/// .unwrap();
+/// # };
+/// ```
+///
+/// [`into_make_service_with_connect_info`]: axum::Router::into_make_service_with_connect_info
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientIp
+where
+    TrustedProxies: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ClientIpRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ConnectInfo(peer) = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .copied()
+            .ok_or(ClientIpRejection)?;
+
+        let trusted_proxies = TrustedProxies::from_ref(state);
+        Ok(Self(trusted_proxies.client_ip(peer.ip(), &parts.headers)))
+    }
+}
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Proxies whose forwarding headers are trusted by [`ClientIp`].
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Arc<Vec<(IpAddr, u8)>>,
+    header: ForwardedHeader,
+}
+
+/// The header trusted proxies use to forward the address of the client.
+///
+/// Set with [`TrustedProxies::header`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ForwardedHeader {
+    /// The `Forwarded` header from [RFC 7239].
+    ///
+    /// [RFC 7239]: https://www.rfc-editor.org/rfc/rfc7239
+    Forwarded,
+    /// The `X-Forwarded-For` header, and `X-Forwarded-Proto`, `X-Forwarded-Host`, and
+    /// `X-Forwarded-Port` for `ExternalUrl`.
+    #[default]
+    XForwardedFor,
+    /// The `X-Real-Ip` header, which contains a single address.
+    XRealIp,
+}
+
+impl TrustedProxies {
+    /// Create a new `TrustedProxies` that doesn't trust any proxies.
+    ///
+    /// The proxies are expected to set `X-Forwarded-For`. Use [`TrustedProxies::header`] to
+    /// change that.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the header the trusted proxies use to forward the address of the client.
+    ///
+    /// Only this header is read. The proxy closest to the server must set or append to it,
+    /// otherwise clients could set it to any address. Defaults to
+    /// [`ForwardedHeader::XForwardedFor`].
+    pub fn header(mut self, header: ForwardedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Get the header the trusted proxies use to forward the address of the client.
+    pub fn forwarded_header(&self) -> ForwardedHeader {
+        self.header
+    }
+
+    /// Trust a single proxy.
+    pub fn trust(self, ip: IpAddr) -> Self {
+        let prefix_len = if ip.is_ipv4() { 32 } else { 128 };
+        self.trust_network(ip, prefix_len)
+    }
+
+    /// Trust all proxies in a network, given in CIDR notation as an address and a prefix length.
+    ///
+    /// IPv4-mapped IPv6 networks, such as `::ffff:10.0.0.0/104`, also trust the IPv4 addresses
+    /// they contain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix length is longer than the address.
+    #[track_caller]
+    pub fn trust_network(mut self, ip: IpAddr, prefix_len: u8) -> Self {
+        let max_len = if ip.is_ipv4() { 32 } else { 128 };
+        assert!(
+            prefix_len <= max_len,
+            "prefix length {prefix_len} is too long for {ip}"
+        );
+        let network = match ip {
+            IpAddr::V6(v6) if prefix_len >= 96 => v6
+                .to_ipv4_mapped()
+                .map(|v4| (IpAddr::V4(v4), prefix_len - 96))
+                .unwrap_or((ip, prefix_len)),
+            _ => (ip, prefix_len),
+        };
+        Arc::make_mut(&mut self.networks).push(network);
+        self
+    }
+
+    /// Whether `ip` belongs to a trusted proxy.
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.networks
+            .iter()
+            .any(|&(network, prefix_len)| in_network(ip, network, prefix_len))
+    }
+
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let forwarded_for = match self.header {
+            ForwardedHeader::Forwarded => forwarded(headers),
+            ForwardedHeader::XForwardedFor => x_forwarded_for(headers),
+            ForwardedHeader::XRealIp => x_real_ip(headers),
+        };
+
+        let mut client = peer;
+        // start with the address added by the proxy closest to us
+        for hop in forwarded_for.into_iter().rev() {
+            let Some(ip) = hop else {
+                // an address we can't parse, so we can't trust anything before it
+                break;
+            };
+            client = ip;
+            if !self.is_trusted(ip) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix_len: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_len))
+                .unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_len))
+                .unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        // IPv6 networks that are too large to be stored as IPv4 networks can still contain
+        // IPv4-mapped addresses
+        (IpAddr::V4(ip), IpAddr::V6(_)) => {
+            in_network(IpAddr::V6(ip.to_ipv6_mapped()), network, prefix_len)
+        }
+        (IpAddr::V6(_), IpAddr::V4(_)) => false,
+    }
+}
+
+/// The `for` addresses of all `Forwarded` headers, in order.
+fn forwarded(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let mut addrs = Vec::new();
+    for value in headers.get_all(http::header::FORWARDED) {
+        let Ok(value) = value.to_str() else {
+            addrs.push(None);
+            continue;
+        };
+        for element in value.split(',') {
+            let Some(node) = element.split(';').find_map(|pair| {
+                let (key, value) = pair.trim().split_once('=')?;
+                key.eq_ignore_ascii_case("for").then_some(value)
+            }) else {
+                continue;
+            };
+            addrs.push(parse_forwarded_node(node.trim().trim_matches('"')));
+        }
+    }
+    addrs
+}
+
+/// Parse a node from the `Forwarded` header, such as `192.0.2.43:47011` or `[2001:db8::1]`.
+pub(super) fn parse_forwarded_node(node: &str) -> Option<IpAddr> {
+    if let Some(rest) = node.strip_prefix('[') {
+        let (ip, _port) = rest.split_once(']')?;
+        return ip.parse().ok();
+    }
+    let ip = node.split_once(':').map_or(node, |(ip, _port)| ip);
+    ip.parse().ok()
+}
+
+fn x_forwarded_for(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .flat_map(|value| {
+            let addrs: Vec<_> = match value.to_str() {
+                Ok(value) => value.split(',').map(|ip| ip.trim().parse().ok()).collect(),
+                Err(_) => vec![None],
+            };
+            addrs
+        })
+        .collect()
+}
+
+fn x_real_ip(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    headers
+        .get("x-real-ip")
+        .map(|value| vec![value.to_str().ok().and_then(|ip| ip.trim().parse().ok())])
+        .unwrap_or_default()
+}
+
+/// Rejection used for [`ClientIp`] if the app wasn't served with
+/// [`into_make_service_with_connect_info`].
+///
+/// [`into_make_service_with_connect_info`]: axum::Router::into_make_service_with_connect_info
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ClientIpRejection;
+
+impl IntoResponse for ClientIpRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for ClientIpRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "Missing `ConnectInfo<SocketAddr>`. Use `into_make_service_with_connect_info` to \
+             serve the app",
+        )
+    }
+}
+
+impl std::error::Error for ClientIpRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn client_ip(
+        header: ForwardedHeader,
+        peer: &str,
+        headers: &[(&'static str, &'static str)],
+    ) -> IpAddr {
+        let trusted_proxies = TrustedProxies::new()
+            .trust(ip("192.0.2.1"))
+            .trust_network(ip("10.0.0.0"), 8)
+            .trust_network(ip("fd00::"), 8)
+            .header(header);
+
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, HeaderValue::from_static(value));
+        }
+        trusted_proxies.client_ip(ip(peer), &map)
+    }
+
+    #[test]
+    fn ipv4_mapped_networks() {
+        let trusted_proxies = TrustedProxies::new()
+            .trust_network(ip("::ffff:10.0.0.0"), 104)
+            .trust(ip("::ffff:192.0.2.1"));
+
+        assert!(trusted_proxies.is_trusted(ip("10.1.2.3")));
+        assert!(trusted_proxies.is_trusted(ip("::ffff:10.1.2.3")));
+        assert!(trusted_proxies.is_trusted(ip("192.0.2.1")));
+        assert!(!trusted_proxies.is_trusted(ip("192.0.2.2")));
+        assert!(!trusted_proxies.is_trusted(ip("203.0.113.7")));
+        assert!(TrustedProxies::new()
+            .trust_network(ip("::"), 0)
+            .is_trusted(ip("203.0.113.7")));
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_headers() {
+        assert_eq!(
+            client_ip(
+                ForwardedHeader::XForwardedFor,
+                "203.0.113.7",
+                &[("x-forwarded-for", "198.51.100.1")]
+            ),
+            ip("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn x_forwarded_for() {
+        let client_ip = |peer, headers| client_ip(ForwardedHeader::XForwardedFor, peer, headers);
+
+        assert_eq!(
+            client_ip(
+                "192.0.2.1",
+                &[("x-forwarded-for", "1.1.1.1, 198.51.100.1, 10.1.2.3")]
+            ),
+            ip("198.51.100.1")
+        );
+        // all trusted, use the leftmost
+        assert_eq!(
+            client_ip("192.0.2.1", &[("x-forwarded-for", "10.0.0.2, 10.0.0.1")]),
+            ip("10.0.0.2")
+        );
+        // garbage stops the walk
+        assert_eq!(
+            client_ip(
+                "192.0.2.1",
+                &[("x-forwarded-for", "1.1.1.1, nope, 10.0.0.1")]
+            ),
+            ip("10.0.0.1")
+        );
+        // multiple headers are combined
+        assert_eq!(
+            client_ip(
+                "192.0.2.1",
+                &[
+                    ("x-forwarded-for", "198.51.100.1"),
+                    ("x-forwarded-for", "10.0.0.1"),
+                ]
+            ),
+            ip("198.51.100.1")
+        );
+        // no header, use the peer
+        assert_eq!(client_ip("192.0.2.1", &[]), ip("192.0.2.1"));
+    }
+
+    #[test]
+    fn client_sent_headers_are_ignored() {
+        // the proxy only appends to `X-Forwarded-For`, so `Forwarded` comes from the client
+        assert_eq!(
+            client_ip(
+                ForwardedHeader::XForwardedFor,
+                "192.0.2.1",
+                &[
+                    ("forwarded", "for=1.2.3.4"),
+                    ("x-real-ip", "1.2.3.4"),
+                    ("x-forwarded-for", "198.51.100.1"),
+                ]
+            ),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            client_ip(
+                ForwardedHeader::XForwardedFor,
+                "192.0.2.1",
+                &[("forwarded", "for=1.2.3.4")]
+            ),
+            ip("192.0.2.1")
+        );
+        assert_eq!(
+            client_ip(
+                ForwardedHeader::Forwarded,
+                "192.0.2.1",
+                &[
+                    ("x-forwarded-for", "1.2.3.4"),
+                    ("forwarded", "for=198.51.100.1")
+                ]
+            ),
+            ip("198.51.100.1")
+        );
+    }
+
+    #[test]
+    fn forwarded() {
+        let client_ip = |peer, headers| client_ip(ForwardedHeader::Forwarded, peer, headers);
+
+        assert_eq!(
+            client_ip(
+                "192.0.2.1",
+                &[(
+                    "forwarded",
+                    "for=\"[2001:db8::1]:4711\";proto=https, for=10.0.0.1:80"
+                )]
+            ),
+            ip("2001:db8::1")
+        );
+        assert_eq!(
+            client_ip("fd00::1", &[("forwarded", "For=198.51.100.1")]),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            client_ip("192.0.2.1", &[("forwarded", "for=unknown")]),
+            ip("192.0.2.1")
+        );
+    }
+
+    #[test]
+    fn x_real_ip() {
+        let client_ip = |peer, headers| client_ip(ForwardedHeader::XRealIp, peer, headers);
+
+        assert_eq!(
+            client_ip("10.0.0.1", &[("x-real-ip", "198.51.100.1")]),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            client_ip("::ffff:10.0.0.1", &[("x-real-ip", "198.51.100.1")]),
+            ip("198.51.100.1")
+        );
+    }
+}
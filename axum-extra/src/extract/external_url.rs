@@ -0,0 +1,406 @@
+use super::client_ip::{parse_forwarded_node, ForwardedHeader, TrustedProxies};
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts, OriginalUri},
+    response::{IntoResponse, Response},
+};
+use http::{
+    header::{FORWARDED, HOST},
+    request::Parts,
+    uri::{Authority, Scheme},
+    HeaderMap, StatusCode, Uri,
+};
+use std::{fmt, net::SocketAddr};
+
+/// Extractor for the URL of the request as seen by the client.
+///
+/// Behind a reverse proxy the URI of the request received by the server usually differs from the
+/// URL the client requested, for example because the proxy terminates TLS or listens on another
+/// host and port. `ExternalUrl` reconstructs the external URL, including scheme, host, port,
+/// path, and query, which is needed to build absolute links, OIDC redirect URIs, or canonical
+/// URLs.
+///
+/// Forwarding headers are only used if the peer of the connection is a
+/// [trusted proxy](TrustedProxies), and which ones are read depends on
+/// [`TrustedProxies::header`]:
+///
+/// - [`ForwardedHeader::XForwardedFor`]: the last value of `X-Forwarded-Proto`,
+///   `X-Forwarded-Host`, and `X-Forwarded-Port` is used, which is the one added by the proxy
+///   directly in front of the server.
+/// - [`ForwardedHeader::Forwarded`]: the `proto` and `host` of the `Forwarded` element added by
+///   the outermost trusted proxy are used. If the element doesn't have one of them,
+///   `X-Forwarded-Proto` or `X-Forwarded-Host` and `X-Forwarded-Port` are used instead.
+/// - [`ForwardedHeader::XRealIp`]: no headers are used, since `X-Real-Ip` doesn't describe the
+///   URL.
+///
+/// If the peer isn't trusted, or the headers don't say, the scheme and host come from the request
+/// URI and the `Host` header. The path is the path before any [nesting](axum::Router::nest).
+///
+/// Like [`ClientIp`](super::ClientIp) this requires [`TrustedProxies`] to be accessible from the
+/// state using [`FromRef`], and the app to be served with
+/// [`into_make_service_with_connect_info`].
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::extract::{ExternalUrl, TrustedProxies};
+///
+/// async fn handler(ExternalUrl(url): ExternalUrl) -> String {
+///     format!("{url}/callback")
+/// }
+///
+/// let app = Router::new()
+///     .route("/login", get(handler))
+///     .with_state(TrustedProxies::new().trust("10.0.0.1".parse().unwrap()));
+/// # let _: Router = app;
+/// ```
+///
+/// [`into_make_service_with_connect_info`]: axum::Router::into_make_service_with_connect_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalUrl(pub Uri);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ExternalUrl
+where
+    TrustedProxies: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ExternalUrlRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ConnectInfo(peer) = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .copied()
+            .ok_or(ExternalUrlRejection::MissingConnectInfo)?;
+
+        let uri = match parts.extensions.get::<OriginalUri>() {
+            Some(OriginalUri(uri)) => uri,
+            None => &parts.uri,
+        };
+
+        let trusted_proxies = TrustedProxies::from_ref(state);
+        let forwarded = if trusted_proxies.is_trusted(peer.ip()) {
+            match trusted_proxies.forwarded_header() {
+                ForwardedHeader::Forwarded => {
+                    let x_forwarded = x_forwarded(&parts.headers);
+                    let forwarded = forwarded(&parts.headers, &trusted_proxies);
+                    ForwardedUrl {
+                        proto: forwarded.proto.or(x_forwarded.proto),
+                        // the port is part of the host in `Forwarded`
+                        port: forwarded.host.map_or(x_forwarded.port, |_| None),
+                        host: forwarded.host.or(x_forwarded.host),
+                    }
+                }
+                ForwardedHeader::XForwardedFor => x_forwarded(&parts.headers),
+                ForwardedHeader::XRealIp => ForwardedUrl::default(),
+            }
+        } else {
+            ForwardedUrl::default()
+        };
+
+        let scheme = match forwarded.proto {
+            Some(proto) => proto
+                .parse()
+                .map_err(|_| ExternalUrlRejection::InvalidUrl)?,
+            None => uri.scheme().cloned().unwrap_or(Scheme::HTTP),
+        };
+
+        let host = forwarded
+            .host
+            .or_else(|| parts.headers.get(HOST)?.to_str().ok())
+            .or_else(|| uri.authority().map(Authority::as_str))
+            .ok_or(ExternalUrlRejection::InvalidUrl)?;
+        let mut authority = host
+            .parse::<Authority>()
+            .map_err(|_| ExternalUrlRejection::InvalidUrl)?;
+
+        if let Some(port) = forwarded.port {
+            let port = port
+                .parse::<u16>()
+                .map_err(|_| ExternalUrlRejection::InvalidUrl)?;
+            let default_port = if scheme == Scheme::HTTPS { 443 } else { 80 };
+            let host = authority.host();
+            authority = if port == default_port {
+                host.parse()
+            } else {
+                format!("{host}:{port}").parse()
+            }
+            .map_err(|_| ExternalUrlRejection::InvalidUrl)?;
+        }
+
+        let path_and_query = uri
+            .path_and_query()
+            .map_or("/", |path_and_query| path_and_query.as_str());
+
+        Uri::builder()
+            .scheme(scheme)
+            .authority(authority)
+            .path_and_query(path_and_query)
+            .build()
+            .map(Self)
+            .map_err(|_| ExternalUrlRejection::InvalidUrl)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ForwardedUrl<'a> {
+    proto: Option<&'a str>,
+    host: Option<&'a str>,
+    port: Option<&'a str>,
+}
+
+/// Get the `proto` and `host` of the `Forwarded` element added by the outermost trusted proxy.
+fn forwarded<'a>(headers: &'a HeaderMap, trusted_proxies: &TrustedProxies) -> ForwardedUrl<'a> {
+    let elements = headers
+        .get_all(FORWARDED)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+
+    let mut found = ForwardedUrl::default();
+    // start with the element added by the proxy closest to us
+    for element in elements.into_iter().rev() {
+        let mut url = ForwardedUrl::default();
+        let mut node = None;
+        for pair in element.split(';') {
+            let Some((key, value)) = pair.trim().split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+            if key.eq_ignore_ascii_case("proto") {
+                url.proto = Some(value);
+            } else if key.eq_ignore_ascii_case("host") {
+                url.host = Some(value);
+            } else if key.eq_ignore_ascii_case("for") {
+                node = Some(value);
+            }
+        }
+        found = url;
+
+        // if the element was added on behalf of another trusted proxy, that proxy's element
+        // describes the external request more accurately
+        match node.and_then(parse_forwarded_node) {
+            Some(ip) if trusted_proxies.is_trusted(ip) => {}
+            _ => break,
+        }
+    }
+    found
+}
+
+fn x_forwarded(headers: &HeaderMap) -> ForwardedUrl<'_> {
+    let last = |name: &str| {
+        let value = headers.get_all(name).iter().last()?.to_str().ok()?;
+        Some(value.rsplit(',').next()?.trim())
+    };
+
+    ForwardedUrl {
+        proto: last("x-forwarded-proto"),
+        host: last("x-forwarded-host"),
+        port: last("x-forwarded-port"),
+    }
+}
+
+/// Rejection used for [`ExternalUrl`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExternalUrlRejection {
+    /// The app wasn't served with [`into_make_service_with_connect_info`].
+    ///
+    /// [`into_make_service_with_connect_info`]: axum::Router::into_make_service_with_connect_info
+    MissingConnectInfo,
+    /// The request didn't contain a valid host, or a forwarding header contained an invalid
+    /// value.
+    InvalidUrl,
+}
+
+impl ExternalUrlRejection {
+    /// Get the status code used for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::MissingConnectInfo => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidUrl => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IntoResponse for ExternalUrlRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = self.status();
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for ExternalUrlRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingConnectInfo => f.write_str(
+                "Missing `ConnectInfo<SocketAddr>`. Use `into_make_service_with_connect_info` \
+                 to serve the app",
+            ),
+            Self::InvalidUrl => f.write_str("Failed to determine the URL of the request"),
+        }
+    }
+}
+
+impl std::error::Error for ExternalUrlRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Request;
+
+    async fn external_url(
+        header: ForwardedHeader,
+        peer: &str,
+        uri: &str,
+        headers: &[(&'static str, &'static str)],
+    ) -> String {
+        let mut req = Request::get(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let (mut parts, ()) = req.body(()).unwrap().into_parts();
+        parts
+            .extensions
+            .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+
+        let trusted_proxies = TrustedProxies::new()
+            .trust_network("10.0.0.0".parse().unwrap(), 8)
+            .header(header);
+        let ExternalUrl(url) = ExternalUrl::from_request_parts(&mut parts, &trusted_proxies)
+            .await
+            .unwrap();
+        url.to_string()
+    }
+
+    #[tokio::test]
+    async fn untrusted_peer() {
+        assert_eq!(
+            external_url(
+                ForwardedHeader::XForwardedFor,
+                "203.0.113.1:1234",
+                "/users?page=2",
+                &[("host", "example.com:8080"), ("x-forwarded-proto", "https")]
+            )
+            .await,
+            "http://example.com:8080/users?page=2"
+        );
+    }
+
+    #[tokio::test]
+    async fn x_forwarded_headers() {
+        assert_eq!(
+            external_url(
+                ForwardedHeader::XForwardedFor,
+                "10.0.0.1:1234",
+                "/users",
+                &[
+                    ("host", "backend:3000"),
+                    ("x-forwarded-proto", "https"),
+                    ("x-forwarded-host", "example.com"),
+                    ("x-forwarded-port", "443"),
+                ]
+            )
+            .await,
+            "https://example.com/users"
+        );
+        assert_eq!(
+            external_url(
+                ForwardedHeader::XForwardedFor,
+                "10.0.0.1:1234",
+                "/",
+                &[("host", "example.com"), ("x-forwarded-port", "8443")]
+            )
+            .await,
+            "http://example.com:8443/"
+        );
+        // the proxy doesn't set `Forwarded`, so it comes from the client
+        assert_eq!(
+            external_url(
+                ForwardedHeader::XForwardedFor,
+                "10.0.0.1:1234",
+                "/",
+                &[
+                    ("host", "example.com"),
+                    ("forwarded", "host=evil.com;proto=https"),
+                ]
+            )
+            .await,
+            "http://example.com/"
+        );
+    }
+
+    #[tokio::test]
+    async fn forwarded_header() {
+        // the client's own `Forwarded` element is ignored
+        assert_eq!(
+            external_url(
+                ForwardedHeader::Forwarded,
+                "10.0.0.1:1234",
+                "/",
+                &[
+                    ("host", "backend"),
+                    (
+                        "forwarded",
+                        "for=1.2.3.4;host=evil.com, for=203.0.113.1;proto=https;host=example.com, \
+                         for=10.0.0.2;proto=http;host=internal",
+                    ),
+                ]
+            )
+            .await,
+            "https://example.com/"
+        );
+    }
+
+    #[tokio::test]
+    async fn forwarded_header_falls_back_per_field() {
+        assert_eq!(
+            external_url(
+                ForwardedHeader::Forwarded,
+                "10.0.0.1:1234",
+                "/",
+                &[
+                    ("host", "backend"),
+                    ("forwarded", "for=203.0.113.1;host=example.com"),
+                    ("x-forwarded-proto", "https"),
+                    ("x-forwarded-port", "8443"),
+                ]
+            )
+            .await,
+            "https://example.com/"
+        );
+        assert_eq!(
+            external_url(
+                ForwardedHeader::Forwarded,
+                "10.0.0.1:1234",
+                "/",
+                &[
+                    ("host", "backend"),
+                    ("forwarded", "for=203.0.113.1;proto=https"),
+                    ("x-forwarded-host", "example.com"),
+                ]
+            )
+            .await,
+            "https://example.com/"
+        );
+    }
+
+    #[tokio::test]
+    async fn x_real_ip_uses_host() {
+        assert_eq!(
+            external_url(
+                ForwardedHeader::XRealIp,
+                "10.0.0.1:1234",
+                "/",
+                &[("host", "example.com"), ("x-forwarded-host", "evil.com")]
+            )
+            .await,
+            "http://example.com/"
+        );
+    }
+}
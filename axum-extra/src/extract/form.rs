@@ -37,7 +37,19 @@ use std::fmt;
 /// }
 /// ```
 ///
+/// # Legacy encodings
+///
+/// With the `form-encoding` feature, forms submitted in encodings other than UTF-8 are decoded
+/// with [`encoding_rs`] before being deserialized. The encoding is determined by, in order:
+///
+/// 1. The `charset` parameter of the `Content-Type` header.
+/// 2. A `_charset_` field in the form, which browsers fill in with the encoding they used.
+/// 3. The [`FormCharset`] request extension, if one was added.
+///
+/// Otherwise the form is expected to be UTF-8.
+///
 /// [`serde_html_form`]: https://crates.io/crates/serde_html_form
+/// [`encoding_rs`]: https://crates.io/crates/encoding_rs
 #[derive(Debug, Clone, Copy, Default)]
 #[cfg(feature = "form")]
 pub struct Form<T>(pub T);
@@ -53,17 +65,154 @@ where
     type Rejection = FormRejection;
 
     async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        #[cfg(feature = "form-encoding")]
+        let (header_charset, default_charset) = (
+            charset::from_content_type(req.headers()),
+            req.extensions().get::<FormCharset>().copied(),
+        );
+
         let RawForm(bytes) = req
             .extract()
             .await
             .map_err(FormRejection::RawFormRejection)?;
 
+        #[cfg(feature = "form-encoding")]
+        let bytes = charset::decode(&bytes, header_charset, default_charset);
+
         serde_html_form::from_bytes::<T>(&bytes)
             .map(Self)
             .map_err(|err| FormRejection::FailedToDeserializeForm(Error::new(err)))
     }
 }
 
+/// The encoding used to decode [`Form`]s that don't specify one.
+///
+/// Add it to requests with an [`Extension`](axum::Extension) layer. This is useful for legacy
+/// clients that submit forms in a fixed encoding without saying so.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, Extension, routing::post};
+/// use axum_extra::extract::{Form, FormCharset};
+/// use std::collections::HashMap;
+///
+/// async fn handler(Form(form): Form<HashMap<String, String>>) {
+///     // ...
+/// }
+///
+/// let app = Router::new()
+///     .route("/", post(handler))
+///     .layer(Extension(FormCharset::for_label("windows-1252").unwrap()));
+/// # let _: Router = app;
+/// ```
+#[cfg(feature = "form-encoding")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormCharset(&'static encoding_rs::Encoding);
+
+#[cfg(feature = "form-encoding")]
+impl FormCharset {
+    /// Get the encoding for a label such as `iso-8859-1` or `shift_jis`.
+    ///
+    /// Returns `None` if the label isn't a known [encoding label].
+    ///
+    /// [encoding label]: https://encoding.spec.whatwg.org/#names-and-labels
+    pub fn for_label(label: &str) -> Option<Self> {
+        encoding_rs::Encoding::for_label(label.trim().as_bytes()).map(Self)
+    }
+
+    /// Create a `FormCharset` from an [`encoding_rs::Encoding`].
+    pub fn new(encoding: &'static encoding_rs::Encoding) -> Self {
+        Self(encoding)
+    }
+
+    /// Get the underlying [`encoding_rs::Encoding`].
+    pub fn encoding(&self) -> &'static encoding_rs::Encoding {
+        self.0
+    }
+}
+
+#[cfg(feature = "form-encoding")]
+mod charset {
+    use super::FormCharset;
+    use http::{header::CONTENT_TYPE, HeaderMap};
+    use std::borrow::Cow;
+
+    pub(super) fn from_content_type(headers: &HeaderMap) -> Option<FormCharset> {
+        let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+        let mime = content_type.parse::<mime::Mime>().ok()?;
+        FormCharset::for_label(mime.get_param(mime::CHARSET)?.as_str())
+    }
+
+    /// Convert a form in a legacy encoding into an equivalent UTF-8 form.
+    pub(super) fn decode(
+        bytes: &[u8],
+        header_charset: Option<FormCharset>,
+        default_charset: Option<FormCharset>,
+    ) -> Cow<'_, [u8]> {
+        let charset = header_charset
+            .or_else(|| charset_field(bytes))
+            .or(default_charset);
+
+        let encoding = match charset {
+            Some(charset) if charset.0 != encoding_rs::UTF_8 => charset.0,
+            _ => return Cow::Borrowed(bytes),
+        };
+
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        for (key, value) in pairs(bytes) {
+            let (key, _, _) = encoding.decode(&key);
+            let (value, _, _) = encoding.decode(&value);
+            serializer.append_pair(&key, &value);
+        }
+        Cow::Owned(serializer.finish().into_bytes())
+    }
+
+    /// Find the `_charset_` field browsers add to forms that contain one.
+    fn charset_field(bytes: &[u8]) -> Option<FormCharset> {
+        pairs(bytes).find_map(|(key, value)| {
+            if &*key == b"_charset_" {
+                FormCharset::for_label(std::str::from_utf8(&value).ok()?)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Split a form into percent decoded key-value pairs without assuming any encoding.
+    fn pairs(bytes: &[u8]) -> impl Iterator<Item = (Cow<'_, [u8]>, Cow<'_, [u8]>)> {
+        bytes
+            .split(|&b| b == b'&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut parts = pair.splitn(2, |&b| b == b'=');
+                let key = parts.next().unwrap_or_default();
+                let value = parts.next().unwrap_or_default();
+                (unescape(key), unescape(value))
+            })
+    }
+
+    fn unescape(input: &[u8]) -> Cow<'_, [u8]> {
+        let input: Cow<'_, [u8]> = if input.contains(&b'+') {
+            Cow::Owned(
+                input
+                    .iter()
+                    .map(|&b| if b == b'+' { b' ' } else { b })
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(input)
+        };
+
+        match input {
+            Cow::Borrowed(input) => percent_encoding::percent_decode(input).into(),
+            Cow::Owned(input) => {
+                Cow::Owned(percent_encoding::percent_decode(&input).collect::<Vec<_>>())
+            }
+        }
+    }
+}
+
 /// Rejection used for [`Form`].
 ///
 /// Contains one variant for each way the [`Form`] extractor can fail.
@@ -145,4 +294,70 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
         assert_eq!(res.text().await, "one,two");
     }
+
+    #[cfg(feature = "form-encoding")]
+    #[tokio::test]
+    async fn legacy_encodings() {
+        use std::collections::BTreeMap;
+
+        let app = Router::new().route(
+            "/",
+            post(|Form(data): Form<BTreeMap<String, String>>| async move { format!("{data:?}") }),
+        );
+        let client = TestClient::new(app);
+
+        // "café" in windows-1252
+        let res = client
+            .post("/")
+            .header(
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=windows-1252",
+            )
+            .body("name=caf%E9+au+lait")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, r#"{"name": "café au lait"}"#);
+
+        // "日本" in shift_jis
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body("_charset_=shift_jis&name=%93%FA%96%7B")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.text().await,
+            r#"{"_charset_": "shift_jis", "name": "日本"}"#
+        );
+
+        let client = TestClient::new(
+            Router::new()
+                .route(
+                    "/",
+                    post(|Form(data): Form<BTreeMap<String, String>>| async move {
+                        format!("{data:?}")
+                    }),
+                )
+                .layer(axum::Extension(FormCharset::for_label("latin1").unwrap())),
+        );
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body("name=%E9")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, r#"{"name": "é"}"#);
+
+        // utf-8 is left alone
+        let res = client
+            .post("/")
+            .header(
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=utf-8",
+            )
+            .body("name=%C3%A9")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, r#"{"name": "é"}"#);
+    }
 }
@@ -0,0 +1,363 @@
+use super::json_deserializer::{
+    json_content_type, JsonDataError, JsonSyntaxError, MissingJsonContentType,
+};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use axum_core::__composite_rejection as composite_rejection;
+use axum_core::__define_rejection as define_rejection;
+use axum_core::extract::rejection::BytesRejection;
+use bytes::Bytes;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use serde::de::DeserializeOwned;
+use std::{fmt, marker::PhantomData};
+
+/// JSON Extractor with per-route configuration.
+///
+/// This works like [`Json`](axum::Json) but the maximum body size, whether unknown fields are
+/// rejected, and the maximum nesting depth are configured through a type implementing
+/// [`JsonConfig`]. This makes it possible to use stricter or more lenient settings for individual
+/// routes.
+///
+/// If deserializing fails the rejection includes the path to the field that caused the error,
+/// such as `items[2].price`, so clients can tell which part of their request was wrong.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{routing::post, Router};
+/// use axum_extra::extract::{JsonConfig, JsonWith};
+/// use serde::Deserialize;
+///
+/// struct Strict;
+///
+/// impl JsonConfig for Strict {
+///     const MAX_BODY_SIZE: Option<usize> = Some(16 * 1024);
+///     const DENY_UNKNOWN_FIELDS: bool = true;
+///     const MAX_DEPTH: usize = 8;
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+///     password: String,
+/// }
+///
+/// async fn create_user(JsonWith(payload, _): JsonWith<CreateUser, Strict>) {
+///     // payload is a `CreateUser`
+/// }
+///
+/// let app = Router::new().route("/users", post(create_user));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-with")))]
+pub struct JsonWith<T, C = DefaultJsonConfig>(pub T, pub PhantomData<C>);
+
+/// Configuration for [`JsonWith`].
+///
+/// All settings have defaults, so implementations only have to override the ones they want to
+/// change.
+pub trait JsonConfig {
+    /// The maximum size of the request body in bytes.
+    ///
+    /// If `None` the [default body limit](axum::extract::DefaultBodyLimit) is used.
+    const MAX_BODY_SIZE: Option<usize> = None;
+
+    /// Whether to reject bodies containing fields the target type doesn't have, even if it
+    /// doesn't use `#[serde(deny_unknown_fields)]`.
+    const DENY_UNKNOWN_FIELDS: bool = false;
+
+    /// The maximum nesting depth of arrays and objects.
+    ///
+    /// `serde_json` never parses values nested deeper than 128 levels, so larger values have no
+    /// effect.
+    const MAX_DEPTH: usize = 128;
+}
+
+/// The default [`JsonConfig`], which behaves like [`Json`](axum::Json).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultJsonConfig;
+
+impl JsonConfig for DefaultJsonConfig {}
+
+#[async_trait]
+impl<T, C, S> FromRequest<S> for JsonWith<T, C>
+where
+    T: DeserializeOwned,
+    C: JsonConfig,
+    S: Send + Sync,
+{
+    type Rejection = JsonWithRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !json_content_type(req.headers()) {
+            return Err(MissingJsonContentType.into());
+        }
+
+        let bytes = match C::MAX_BODY_SIZE {
+            Some(limit) => Limited::new(req.into_body(), limit)
+                .collect()
+                .await
+                .map_err(|err| {
+                    if err.is::<LengthLimitError>() {
+                        JsonWithRejection::from(JsonBodyTooLarge::from_err(err))
+                    } else {
+                        JsonWithRejection::from(FailedToBufferJsonBody::from_err(err))
+                    }
+                })?
+                .to_bytes(),
+            None => Bytes::from_request(req, state).await?,
+        };
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl<T, C> JsonWith<T, C>
+where
+    T: DeserializeOwned,
+    C: JsonConfig,
+{
+    /// Construct a `JsonWith<T, C>` from a byte slice, applying the nesting depth and unknown
+    /// field settings of `C`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, JsonWithRejection> {
+        if exceeds_depth(bytes, C::MAX_DEPTH) {
+            return Err(JsonTooDeep.into());
+        }
+
+        let mut unknown_field = None;
+        let deserializer = &mut serde_json::Deserializer::from_slice(bytes);
+        let mut on_ignored = |path: serde_ignored::Path<'_>| {
+            if unknown_field.is_none() {
+                unknown_field = Some(path.to_string());
+            }
+        };
+        let deserializer = serde_ignored::Deserializer::new(deserializer, &mut on_ignored);
+
+        let value = match serde_path_to_error::deserialize(deserializer) {
+            Ok(value) => value,
+            Err(err) => {
+                let rejection = match err.inner().classify() {
+                    serde_json::error::Category::Data => JsonDataError::from_err(err).into(),
+                    serde_json::error::Category::Syntax
+                    | serde_json::error::Category::Eof
+                    | serde_json::error::Category::Io => JsonSyntaxError::from_err(err).into(),
+                };
+                return Err(rejection);
+            }
+        };
+
+        if C::DENY_UNKNOWN_FIELDS {
+            if let Some(path) = unknown_field {
+                return Err(UnknownJsonField::from_err(UnknownField(path)).into());
+            }
+        }
+
+        Ok(Self(value, PhantomData))
+    }
+}
+
+/// Whether arrays and objects in `bytes` are nested deeper than `max_depth`.
+///
+/// This only tracks brackets outside of strings and doesn't validate the JSON otherwise.
+fn exceeds_depth(bytes: &[u8], max_depth: usize) -> bool {
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &byte in bytes {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    false
+}
+
+#[derive(Debug)]
+struct UnknownField(String);
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown field `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownField {}
+
+define_rejection! {
+    #[status = UNPROCESSABLE_ENTITY]
+    #[body = "Failed to deserialize the JSON body into the target type"]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-with")))]
+    /// Rejection type for [`JsonWith`].
+    ///
+    /// This rejection is used if the body contains a field the target type doesn't have and
+    /// [`JsonConfig::DENY_UNKNOWN_FIELDS`] is enabled.
+    pub struct UnknownJsonField(Error);
+}
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "JSON body is nested too deeply"]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-with")))]
+    /// Rejection type for [`JsonWith`].
+    ///
+    /// This rejection is used if the body is nested deeper than [`JsonConfig::MAX_DEPTH`].
+    pub struct JsonTooDeep;
+}
+
+define_rejection! {
+    #[status = PAYLOAD_TOO_LARGE]
+    #[body = "Failed to buffer the request body"]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-with")))]
+    /// Rejection type for [`JsonWith`].
+    ///
+    /// This rejection is used if the body is larger than [`JsonConfig::MAX_BODY_SIZE`].
+    pub struct JsonBodyTooLarge(Error);
+}
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Failed to buffer the request body"]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-with")))]
+    /// Rejection type for [`JsonWith`].
+    ///
+    /// This rejection is used if reading the body failed while [`JsonConfig::MAX_BODY_SIZE`] is
+    /// set.
+    pub struct FailedToBufferJsonBody(Error);
+}
+
+composite_rejection! {
+    /// Rejection used for [`JsonWith`].
+    ///
+    /// Contains one variant for each way the [`JsonWith`] extractor
+    /// can fail.
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-with")))]
+    pub enum JsonWithRejection {
+        JsonDataError,
+        JsonSyntaxError,
+        MissingJsonContentType,
+        UnknownJsonField,
+        JsonTooDeep,
+        JsonBodyTooLarge,
+        FailedToBufferJsonBody,
+        BytesRejection,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use http::StatusCode;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct Input {
+        items: Vec<Item>,
+    }
+
+    #[derive(Deserialize)]
+    struct Item {
+        price: u32,
+    }
+
+    struct Strict;
+
+    impl JsonConfig for Strict {
+        const MAX_BODY_SIZE: Option<usize> = Some(64);
+        const DENY_UNKNOWN_FIELDS: bool = true;
+        const MAX_DEPTH: usize = 3;
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/strict",
+                post(|JsonWith(input, _): JsonWith<Input, Strict>| async move {
+                    input.items.iter().map(|item| item.price).sum::<u32>().to_string()
+                }),
+            )
+            .route(
+                "/default",
+                post(|JsonWith(input, _): JsonWith<Input>| async move {
+                    input.items.len().to_string()
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn json_with() {
+        let client = TestClient::new(app());
+
+        let res = client
+            .post("/strict")
+            .json(&json!({ "items": [{ "price": 1 }, { "price": 2 }] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "3");
+
+        let res = client
+            .post("/strict")
+            .json(&json!({ "items": [{ "price": 1 }, { "price": "2" }] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(res.text().await.contains("items[1].price"));
+
+        let res = client
+            .post("/strict")
+            .json(&json!({ "items": [{ "price": 1, "extra": true }] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = res.text().await;
+        assert!(body.contains("unknown field"), "{body}");
+        assert!(body.contains("extra"), "{body}");
+
+        let res = client
+            .post("/strict")
+            .json(&json!({ "items": [{ "price": 1, "extra": [[1]] }] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "JSON body is nested too deeply");
+
+        let res = client
+            .post("/strict")
+            .json(&json!({ "items": vec![json!({ "price": 1 }); 10] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = client
+            .post("/default")
+            .json(&json!({ "items": [{ "price": 1, "extra": [[1]] }] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "1");
+    }
+
+    #[test]
+    fn depth() {
+        assert!(!exceeds_depth(br#"{"a": [1, {"b": "[[[["}]}"#, 3));
+        assert!(exceeds_depth(br#"{"a": [1, {"b": [2]}]}"#, 3));
+        assert!(!exceeds_depth(br#"{"a": "\"[[[["}"#, 1));
+    }
+}
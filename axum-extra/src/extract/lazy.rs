@@ -0,0 +1,176 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::{BoxFuture, FutureExt};
+use http::request::Parts;
+use std::{convert::Infallible, fmt};
+
+/// Extractor that defers running another extractor until it's needed.
+///
+/// Extracting `Lazy<T>` always succeeds and does no work. `T` is only extracted when
+/// [`Lazy::get`] is called, so handlers with conditional code paths don't pay for extractors,
+/// like reading the body or loading a user from a database, they might not use.
+///
+/// If `T` implements [`FromRequestParts`] it's extracted from a copy of the request parts.
+/// Changes `T` makes to the parts, such as inserting extensions, therefore aren't visible to other
+/// extractors. If `T` only implements [`FromRequest`], `Lazy<T>` takes ownership of the request
+/// and must be the last argument of the handler.
+///
+/// The state must implement `Clone` since it's kept until `T` is extracted.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     Router,
+///     Json,
+///     routing::post,
+///     http::{HeaderMap, StatusCode},
+///     response::{IntoResponse, Response},
+/// };
+/// use axum_extra::extract::Lazy;
+/// use serde_json::Value;
+///
+/// async fn handler(headers: HeaderMap, body: Lazy<Json<Value>>) -> Response {
+///     if !headers.contains_key("authorization") {
+///         // the body is never read
+///         return StatusCode::UNAUTHORIZED.into_response();
+///     }
+///
+///     let Json(body) = match body.get().await {
+///         Ok(body) => body,
+///         Err(rejection) => return rejection,
+///     };
+///
+///     // ...
+///     # let _ = body;
+///     StatusCode::OK.into_response()
+/// }
+///
+/// let app = Router::new().route("/", post(handler));
+/// # let _: Router = app;
+/// ```
+#[must_use]
+pub struct Lazy<T> {
+    extract: BoxFuture<'static, Result<T, Response>>,
+}
+
+impl<T> Lazy<T> {
+    /// Run the extractor.
+    ///
+    /// The rejection of the extractor is converted into a response.
+    pub async fn get(self) -> Result<T, Response> {
+        self.extract.await
+    }
+}
+
+impl<T> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for Lazy<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: FromRequestParts<S> + 'static,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let mut parts = clone_parts(parts);
+        let state = state.clone();
+        let extract = async move {
+            T::from_request_parts(&mut parts, &state)
+                .await
+                .map_err(IntoResponse::into_response)
+        };
+        Ok(Self {
+            extract: extract.boxed(),
+        })
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Lazy<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: FromRequest<S> + 'static,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let state = state.clone();
+        let extract = async move {
+            T::from_request(req, &state)
+                .await
+                .map_err(IntoResponse::into_response)
+        };
+        Ok(Self {
+            extract: extract.boxed(),
+        })
+    }
+}
+
+fn clone_parts(parts: &Parts) -> Parts {
+    let mut req = Request::new(());
+    *req.method_mut() = parts.method.clone();
+    *req.uri_mut() = parts.uri.clone();
+    *req.version_mut() = parts.version;
+    *req.headers_mut() = parts.headers.clone();
+    *req.extensions_mut() = parts.extensions.clone();
+    req.into_parts().0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use http::{HeaderMap, StatusCode};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    struct Counted;
+
+    #[async_trait]
+    impl<S> FromRequestParts<S> for Counted
+    where
+        S: Send + Sync,
+    {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(_: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+            COUNTER.fetch_add(1, Ordering::SeqCst);
+            Ok(Self)
+        }
+    }
+
+    #[tokio::test]
+    async fn only_extracts_when_used() {
+        async fn handler(headers: HeaderMap, counted: Lazy<Counted>, body: Lazy<String>) -> String {
+            if headers.contains_key("x-use") {
+                counted.get().await.unwrap();
+                body.get().await.unwrap()
+            } else {
+                String::new()
+            }
+        }
+
+        let client = TestClient::new(Router::new().route("/", post(handler)));
+
+        let res = client.post("/").body("hello").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "");
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 0);
+
+        let res = client.post("/").header("x-use", "1").body("hello").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "hello");
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 1);
+    }
+}
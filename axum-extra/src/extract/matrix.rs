@@ -0,0 +1,290 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    response::{IntoResponse, Response},
+    Error,
+};
+use http::{request::Parts, StatusCode, Uri};
+use serde::de::DeserializeOwned;
+use std::{
+    fmt,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Extractor that deserializes matrix parameters from the path into some type.
+///
+/// Matrix parameters are `;`-separated `key=value` pairs at the end of a path segment, such as
+/// `lat` and `lon` in `/map/point;lat=50;lon=20`. The parameters of all segments are combined,
+/// so a key that appears in several segments can be collected into a `Vec`.
+///
+/// By default the router sees matrix parameters as part of the path, so `/map/point` doesn't
+/// match the example above. Apply [`MatrixParamsLayer`] around the router to remove them from the
+/// path before routing. `Matrix` also works without the layer, for example with a route like
+/// `/map/:point` that captures the parameters as part of the segment.
+///
+/// `T` is expected to implement [`serde::Deserialize`]. Like `Query` from this crate this uses
+/// [`serde_html_form`] so multi-value items are supported.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{extract::Request, routing::get, Router, ServiceExt};
+/// use axum_extra::extract::{Matrix, MatrixParamsLayer};
+/// use serde::Deserialize;
+/// use tower_layer::Layer;
+///
+/// #[derive(Deserialize)]
+/// struct Point {
+///     lat: f64,
+///     lon: f64,
+/// }
+///
+/// // handles requests like `/map/point;lat=50;lon=20`
+/// async fn point(Matrix(point): Matrix<Point>) -> String {
+///     format!("{}, {}", point.lat, point.lon)
+/// }
+///
+/// let app = Router::new().route("/map/point", get(point));
+///
+/// // the layer must wrap the router to run before routing
+/// let app = MatrixParamsLayer::new().layer(app);
+///
+/// # async {
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+/// axum::serve(listener, ServiceExt::<Request>::into_make_service(app))
+///     .await
+///     .unwrap();
+/// # };
+/// ```
+///
+/// If the matrix parameters cannot be parsed it will reject the request with a
+/// `400 Bad Request` response.
+#[cfg_attr(docsrs, doc(cfg(feature = "matrix")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Matrix<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Matrix<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = MatrixRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let params = match parts.extensions.get::<MatrixParams>() {
+            Some(MatrixParams(params)) => params.clone(),
+            None => matrix_params(parts.uri.path()),
+        };
+        let value = serde_html_form::from_str(&params)
+            .map_err(|err| MatrixRejection::FailedToDeserializeMatrixParams(Error::new(err)))?;
+        Ok(Matrix(value))
+    }
+}
+
+axum_core::__impl_deref!(Matrix);
+
+/// Matrix parameters removed by [`MatrixParamsService`], encoded like a query string.
+#[derive(Clone)]
+struct MatrixParams(String);
+
+/// Collect the matrix parameters of all segments of `path` into a `&` separated string.
+fn matrix_params(path: &str) -> String {
+    let mut params = String::new();
+    for segment in path.split('/') {
+        let Some((_, segment_params)) = segment.split_once(';') else {
+            continue;
+        };
+        for param in segment_params.split(';').filter(|param| !param.is_empty()) {
+            if !params.is_empty() {
+                params.push('&');
+            }
+            // `&` and `+` aren't special in paths
+            params.push_str(&param.replace('&', "%26").replace('+', "%2B"));
+        }
+    }
+    params
+}
+
+/// Remove the matrix parameters from all segments of `path`.
+fn strip_matrix_params(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            segment
+                .split_once(';')
+                .map_or(segment, |(segment, _)| segment)
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Rejection used for [`Matrix`].
+///
+/// Contains one variant for each way the [`Matrix`] extractor can fail.
+#[derive(Debug)]
+#[non_exhaustive]
+#[cfg(feature = "matrix")]
+pub enum MatrixRejection {
+    #[allow(missing_docs)]
+    FailedToDeserializeMatrixParams(Error),
+}
+
+impl IntoResponse for MatrixRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::FailedToDeserializeMatrixParams(inner) => {
+                let body = format!("Failed to deserialize matrix parameters: {inner}");
+                let status = StatusCode::BAD_REQUEST;
+                axum_core::__log_rejection!(
+                    rejection_type = Self,
+                    body_text = body,
+                    status = status,
+                );
+                (status, body).into_response()
+            }
+        }
+    }
+}
+
+impl fmt::Display for MatrixRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FailedToDeserializeMatrixParams(inner) => inner.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MatrixRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::FailedToDeserializeMatrixParams(inner) => Some(inner),
+        }
+    }
+}
+
+/// Layer that removes matrix parameters from the path so routes match without them.
+///
+/// The removed parameters are still available to the [`Matrix`] extractor. The layer must wrap
+/// the [`Router`](axum::Router) rather than being added with
+/// [`Router::layer`](axum::Router::layer), since the latter runs after routing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatrixParamsLayer {
+    _priv: (),
+}
+
+impl MatrixParamsLayer {
+    /// Create a new `MatrixParamsLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for MatrixParamsLayer {
+    type Service = MatrixParamsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MatrixParamsService { inner }
+    }
+}
+
+/// Middleware that removes matrix parameters from the path so routes match without them.
+///
+/// Created with [`MatrixParamsLayer`].
+#[derive(Debug, Clone)]
+pub struct MatrixParamsService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for MatrixParamsService<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let path = req.uri().path();
+        if path.contains(';') {
+            let params = matrix_params(path);
+            let mut path_and_query = strip_matrix_params(path);
+            if let Some(query) = req.uri().query() {
+                path_and_query.push('?');
+                path_and_query.push_str(query);
+            }
+
+            let mut parts = req.uri().clone().into_parts();
+            // removing characters from a valid path always results in a valid path
+            if let Ok(path_and_query) = path_and_query.parse() {
+                parts.path_and_query = Some(path_and_query);
+                if let Ok(uri) = Uri::from_parts(parts) {
+                    *req.uri_mut() = uri;
+                    req.extensions_mut().insert(MatrixParams(params));
+                }
+            }
+        }
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Point {
+        lat: f64,
+        lon: f64,
+        #[serde(default)]
+        tags: Vec<String>,
+    }
+
+    async fn point(Matrix(point): Matrix<Point>) -> String {
+        format!("{} {} {:?}", point.lat, point.lon, point.tags)
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(matrix_params("/map/point;lat=50;lon=20"), "lat=50&lon=20");
+        assert_eq!(matrix_params("/a;x=1/b;;x=2/c"), "x=1&x=2");
+        assert_eq!(matrix_params("/a;x=1+1&y"), "x=1%2B1%26y");
+        assert_eq!(matrix_params("/a/b"), "");
+        assert_eq!(strip_matrix_params("/a;x=1/b;;x=2/c"), "/a/b/c");
+        assert_eq!(strip_matrix_params("/a/b/"), "/a/b/");
+    }
+
+    #[tokio::test]
+    async fn with_layer() {
+        let app = Router::new().route("/map/point", get(point));
+        let client = TestClient::new(MatrixParamsLayer::new().layer(app));
+
+        let res = client
+            .get("/map/point;lat=50;lon=20;tags=a;tags=b%20c?zoom=1")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, r#"50 20 ["a", "b c"]"#);
+
+        let res = client.get("/map/point;lat=north").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn without_layer() {
+        let app = Router::new().route("/map/:point", get(point));
+        let client = TestClient::new(app);
+
+        let res = client.get("/map/point;lat=50;lon=20").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "50 20 []");
+    }
+}
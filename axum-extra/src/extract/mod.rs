@@ -1,9 +1,23 @@
 //! Additional extractors.
 
+mod any_state;
 mod cached;
+mod lazy;
 mod optional_path;
 mod with_rejection;
 
+#[cfg(feature = "accept-encoding")]
+mod accept_encoding;
+
+#[cfg(feature = "body-reader")]
+mod body_reader;
+
+#[cfg(feature = "client-ip")]
+mod client_ip;
+
+#[cfg(feature = "external-url")]
+mod external_url;
+
 #[cfg(feature = "form")]
 mod form;
 
@@ -13,13 +27,61 @@ pub mod cookie;
 #[cfg(feature = "json-deserializer")]
 mod json_deserializer;
 
+#[cfg(feature = "json-with")]
+mod json_with;
+
+#[cfg(feature = "matrix")]
+mod matrix;
+
 #[cfg(feature = "query")]
 mod query;
 
 #[cfg(feature = "multipart")]
 pub mod multipart;
 
-pub use self::{cached::Cached, optional_path::OptionalPath, with_rejection::WithRejection};
+#[cfg(feature = "peer-certificates")]
+mod peer_certificates;
+
+#[cfg(feature = "preconditions")]
+mod preconditions;
+
+#[cfg(feature = "range")]
+mod range;
+
+#[cfg(feature = "request-id")]
+mod request_id;
+
+#[cfg(feature = "trailers")]
+mod trailers;
+
+#[cfg(feature = "spooled-body")]
+mod spooled_body;
+
+#[cfg(feature = "typed-multipart")]
+mod typed_multipart;
+
+#[cfg(feature = "validation")]
+pub mod valid;
+
+pub use self::{
+    any_state::{AnyState, FromState, MissingState},
+    cached::{CacheKey, Cached, SharedCache, SharedCached},
+    lazy::Lazy,
+    optional_path::OptionalPath,
+    with_rejection::WithRejection,
+};
+
+#[cfg(feature = "accept-encoding")]
+pub use self::accept_encoding::{AcceptEncoding, Encoding};
+
+#[cfg(feature = "body-reader")]
+pub use self::body_reader::BodyReader;
+
+#[cfg(feature = "client-ip")]
+pub use self::client_ip::{ClientIp, ClientIpRejection, ForwardedHeader, TrustedProxies};
+
+#[cfg(feature = "external-url")]
+pub use self::external_url::{ExternalUrl, ExternalUrlRejection};
 
 #[cfg(feature = "cookie")]
 pub use self::cookie::CookieJar;
@@ -33,22 +95,72 @@ pub use self::cookie::SignedCookieJar;
 #[cfg(feature = "form")]
 pub use self::form::{Form, FormRejection};
 
+#[cfg(feature = "form-encoding")]
+pub use self::form::FormCharset;
+
 #[cfg(feature = "query")]
-pub use self::query::{OptionalQuery, OptionalQueryRejection, Query, QueryRejection};
+pub use self::query::{
+    CommaSeparated, OptionalQuery, OptionalQueryRejection, Query, QueryRejection,
+};
+
+#[cfg(feature = "matrix")]
+pub use self::matrix::{Matrix, MatrixParamsLayer, MatrixParamsService, MatrixRejection};
 
 #[cfg(feature = "multipart")]
 pub use self::multipart::Multipart;
 
+#[cfg(feature = "peer-certificates")]
+pub use self::peer_certificates::{MissingPeerCertificates, PeerCertificates, SubjectAltName};
+
+#[cfg(feature = "preconditions")]
+pub use self::preconditions::{PreconditionError, Preconditions};
+
+#[cfg(feature = "range")]
+pub use self::range::{ByteRange, RangeHeader, RangeHeaderRejection, RangeNotSatisfiable};
+
+#[cfg(feature = "request-id")]
+pub use self::request_id::{
+    MakeRequestId, RandomRequestId, RequestId, RequestIdFuture, RequestIdLayer, RequestIdRejection,
+    RequestIdService,
+};
+
+#[cfg(feature = "spooled-body")]
+pub use self::spooled_body::{SpooledBody, SpooledBodyRejection, SpooledData, DEFAULT_MAX_MEMORY};
+
 #[cfg(feature = "json-deserializer")]
 pub use self::json_deserializer::{
     JsonDataError, JsonDeserializer, JsonDeserializerRejection, JsonSyntaxError,
     MissingJsonContentType,
 };
 
+#[cfg(feature = "json-with")]
+pub use self::json_with::{
+    DefaultJsonConfig, FailedToBufferJsonBody, JsonBodyTooLarge, JsonConfig, JsonTooDeep, JsonWith,
+    JsonWithRejection, UnknownJsonField,
+};
+
+#[cfg(feature = "trailers")]
+pub use self::trailers::{Trailers, TrailersLayer, TrailersRejection, TrailersService};
+
+#[cfg(feature = "typed-multipart")]
+pub use self::typed_multipart::{
+    FromMultipartField, TryFromMultipart, TypedMultipart, TypedMultipartError,
+    TypedMultipartRejection, UploadedFile,
+};
+#[cfg(feature = "typed-multipart")]
+pub use axum_macros::TryFromMultipart;
+
+#[cfg(feature = "validation")]
+pub use self::valid::{Valid, ValidRejection, ValidateRequest, ValidationErrors};
+
 #[cfg(feature = "json-lines")]
 #[doc(no_inline)]
 pub use crate::json_lines::JsonLines;
 
+#[cfg(feature = "sse")]
+#[doc(no_inline)]
+pub use crate::sse::LastEventId;
+
 #[cfg(feature = "typed-header")]
 #[doc(no_inline)]
 pub use crate::typed_header::TypedHeader;
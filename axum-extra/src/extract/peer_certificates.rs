@@ -0,0 +1,234 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    response::{IntoResponse, Response},
+    serve::{TlsConnectInfo, TlsInfo},
+};
+use bytes::Bytes;
+use http::{request::Parts, StatusCode};
+use std::{fmt, net::IpAddr, sync::Arc};
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+/// Extractor for the certificate chain a client presented during the TLS handshake.
+///
+/// This is useful for services using mutual TLS, where clients are authenticated by their
+/// certificates.
+///
+/// With [`serve_tls`](axum::serve_tls) and a [`RustlsConfig`](axum::serve::RustlsConfig) that
+/// asks clients for certificates, the certificates are read from the [`TlsInfo`] of the
+/// connection, when the app is served with [`TlsConnectInfo`]:
+///
+/// ```rust
+/// use axum::{Router, routing::get, serve::TlsConnectInfo};
+/// use axum_extra::extract::PeerCertificates;
+///
+/// async fn handler(certs: PeerCertificates) -> String {
+///     format!("Hello {:?}", certs.subject())
+/// }
+///
+/// let app = Router::new().route("/", get(handler));
+///
+/// # async {
+/// # let listener = tokio::net::TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// # let config: axum::serve::RustlsConfig = todo!();
+/// axum::serve_tls(
+///     listener,
+///     config,
+///     app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+/// )
+/// .await
+/// .unwrap();
+/// # };
+/// ```
+///
+/// If TLS is terminated elsewhere, such as by a proxy that forwards the client certificate, the
+/// code accepting the connections can insert `PeerCertificates` into the request extensions
+/// instead, for example by applying an [`Extension`](axum::Extension) layer per connection. It
+/// should only be inserted if the client presented at least one certificate. Certificates
+/// inserted this way take precedence over those of the connection.
+///
+/// If there are no certificates the request is rejected with `401 Unauthorized`. Use
+/// `Option<PeerCertificates>` if client certificates are optional.
+#[derive(Debug, Clone)]
+pub struct PeerCertificates(Arc<[Bytes]>);
+
+impl PeerCertificates {
+    /// Create a new `PeerCertificates` from DER encoded certificates, starting with the client's
+    /// own certificate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `certs` is empty.
+    pub fn new<I>(certs: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Bytes>,
+    {
+        let certs = certs.into_iter().map(Into::into).collect::<Arc<[Bytes]>>();
+        assert!(!certs.is_empty(), "`PeerCertificates` cannot be empty");
+        Self(certs)
+    }
+
+    /// The DER encoded certificate of the client.
+    pub fn leaf(&self) -> &Bytes {
+        &self.0[0]
+    }
+
+    /// The DER encoded certificate chain, starting with the client's own certificate.
+    pub fn chain(&self) -> &[Bytes] {
+        &self.0
+    }
+
+    /// The certificates the client presented on a TLS connection, if any.
+    pub fn from_tls_info(tls: &TlsInfo) -> Option<Self> {
+        let certs = tls.peer_certificates()?;
+        if certs.is_empty() {
+            return None;
+        }
+        Some(Self::new(
+            certs
+                .iter()
+                .map(|cert| Bytes::copy_from_slice(cert.as_ref())),
+        ))
+    }
+
+    /// The subject of the client's certificate, such as `CN=alice,O=Example`.
+    ///
+    /// Returns `None` if the certificate can't be parsed.
+    pub fn subject(&self) -> Option<String> {
+        let (_, cert) = X509Certificate::from_der(self.leaf()).ok()?;
+        Some(cert.subject().to_string())
+    }
+
+    /// The subject alternative names of the client's certificate.
+    ///
+    /// Names of other types than DNS names, email addresses, URIs, and IP addresses are skipped.
+    /// Returns an empty list if the certificate doesn't have any, or can't be parsed.
+    pub fn subject_alt_names(&self) -> Vec<SubjectAltName> {
+        let Ok((_, cert)) = X509Certificate::from_der(self.leaf()) else {
+            return Vec::new();
+        };
+        let Ok(Some(names)) = cert.subject_alternative_name() else {
+            return Vec::new();
+        };
+        names
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(name) => Some(SubjectAltName::Dns((*name).to_owned())),
+                GeneralName::RFC822Name(email) => Some(SubjectAltName::Email((*email).to_owned())),
+                GeneralName::URI(uri) => Some(SubjectAltName::Uri((*uri).to_owned())),
+                GeneralName::IPAddress(ip) => match ip.len() {
+                    4 => Some(SubjectAltName::Ip(IpAddr::from(
+                        <[u8; 4]>::try_from(*ip).ok()?,
+                    ))),
+                    16 => Some(SubjectAltName::Ip(IpAddr::from(
+                        <[u8; 16]>::try_from(*ip).ok()?,
+                    ))),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A subject alternative name of a certificate, returned by
+/// [`PeerCertificates::subject_alt_names`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubjectAltName {
+    /// A DNS name, such as `client.example.com`.
+    Dns(String),
+    /// An email address.
+    Email(String),
+    /// A URI, such as a SPIFFE ID.
+    Uri(String),
+    /// An IP address.
+    Ip(IpAddr),
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for PeerCertificates
+where
+    S: Send + Sync,
+{
+    type Rejection = MissingPeerCertificates;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(certs) = parts.extensions.get::<Self>() {
+            return Ok(certs.clone());
+        }
+        parts
+            .extensions
+            .get::<ConnectInfo<TlsConnectInfo>>()
+            .and_then(|ConnectInfo(info)| info.tls())
+            .and_then(Self::from_tls_info)
+            .ok_or(MissingPeerCertificates)
+    }
+}
+
+/// Rejection used for [`PeerCertificates`] if the client didn't present a certificate.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MissingPeerCertificates;
+
+impl IntoResponse for MissingPeerCertificates {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::UNAUTHORIZED;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for MissingPeerCertificates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("No client certificate was presented")
+    }
+}
+
+impl std::error::Error for MissingPeerCertificates {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Extension, Router};
+
+    #[tokio::test]
+    async fn extracts_certificates() {
+        async fn handler(certs: PeerCertificates) -> String {
+            format!("{} {:?}", certs.chain().len(), certs.leaf())
+        }
+
+        let client = TestClient::new(Router::new().route("/", get(handler)).layer(Extension(
+            PeerCertificates::new([b"leaf".as_slice(), b"intermediate".as_slice()]),
+        )));
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "2 b\"leaf\"");
+
+        let client = TestClient::new(Router::new().route("/", get(handler)));
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn subject_and_alt_names() {
+        const CERT: &[u8] = include_bytes!("../../../axum/src/test_helpers/certs/cert.pem");
+        let (_, pem) = x509_parser::pem::parse_x509_pem(CERT).unwrap();
+        let certs = PeerCertificates::new([pem.contents]);
+
+        assert_eq!(certs.subject().as_deref(), Some("CN=localhost"));
+        assert_eq!(
+            certs.subject_alt_names(),
+            [SubjectAltName::Dns("localhost".to_owned())]
+        );
+
+        let certs = PeerCertificates::new([b"not a certificate".as_slice()]);
+        assert_eq!(certs.subject(), None);
+        assert_eq!(certs.subject_alt_names(), []);
+    }
+}
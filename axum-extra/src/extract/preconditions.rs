@@ -0,0 +1,295 @@
+use crate::typed_header::{decode, TypedHeaderRejection};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use headers::{ETag, Header, IfMatch, IfUnmodifiedSince, LastModified};
+use http::{request::Parts, HeaderMap, StatusCode};
+use std::{fmt, time::SystemTime};
+
+/// Extractor for the preconditions of a conditional write, from the `If-Match` and
+/// `If-Unmodified-Since` headers.
+///
+/// Clients use preconditions to make sure the resource they're updating hasn't been changed by
+/// someone else since they last read it. [`Preconditions::check`] evaluates them against the
+/// current state of the resource and returns an error that responds with
+/// `412 Precondition Failed` if the resource has changed, or `428 Precondition Required` if the
+/// client didn't send any preconditions.
+///
+/// If both headers are sent only `If-Match` is evaluated. An invalid `If-Unmodified-Since`
+/// header is ignored, and an `If-Match` header that can't be decoded rejects the request with
+/// `400 Bad Request`.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::put, http::StatusCode, response::IntoResponse};
+/// use axum_extra::extract::Preconditions;
+/// use headers::ETag;
+///
+/// async fn update_document(preconditions: Preconditions, body: String) -> impl IntoResponse {
+///     let current_etag: ETag = "\"v1\"".parse().unwrap();
+///
+///     if let Err(err) = preconditions.check(true, Some(&current_etag), None) {
+///         return err.into_response();
+///     }
+///
+///     // update the document...
+///     # let _ = body;
+///     StatusCode::NO_CONTENT.into_response()
+/// }
+///
+/// let app = Router::new().route("/documents/:id", put(update_document));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Preconditions {
+    if_match: Option<IfMatch>,
+    if_unmodified_since: Option<IfUnmodifiedSince>,
+}
+
+impl Preconditions {
+    /// The `If-Match` header, if the request had one.
+    pub fn if_match(&self) -> Option<&IfMatch> {
+        self.if_match.as_ref()
+    }
+
+    /// The `If-Unmodified-Since` header, if the request had a valid one.
+    pub fn if_unmodified_since(&self) -> Option<&IfUnmodifiedSince> {
+        self.if_unmodified_since.as_ref()
+    }
+
+    /// Whether the request didn't have any preconditions.
+    pub fn is_empty(&self) -> bool {
+        self.if_match.is_none() && self.if_unmodified_since.is_none()
+    }
+
+    /// Evaluate the preconditions against the current state of the resource.
+    ///
+    /// `exists` is whether the resource currently exists. `If-Match: *` passes for any resource
+    /// that exists, even one without an entity tag. `etag` is the strong entity tag of the current
+    /// representation, if it has one. `last_modified` is the time the resource was last modified,
+    /// if known. It's compared with a precision of one second, like the `Last-Modified` header.
+    ///
+    /// Use [`is_empty`](Self::is_empty) to skip the check if preconditions are optional.
+    pub fn check(
+        &self,
+        exists: bool,
+        etag: Option<&ETag>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), PreconditionError> {
+        if let Some(if_match) = &self.if_match {
+            let passes = exists
+                && (if_match.is_any()
+                    || etag.map_or(false, |etag| if_match.precondition_passes(etag)));
+            return if passes {
+                Ok(())
+            } else {
+                Err(PreconditionError::Failed)
+            };
+        }
+
+        if let Some(if_unmodified_since) = &self.if_unmodified_since {
+            // the precondition is ignored if the modification time isn't known
+            let Some(last_modified) = last_modified else {
+                return Ok(());
+            };
+            // truncate to the precision of HTTP dates
+            let last_modified = SystemTime::from(LastModified::from(last_modified));
+            return if if_unmodified_since.precondition_passes(last_modified) {
+                Ok(())
+            } else {
+                Err(PreconditionError::Failed)
+            };
+        }
+
+        Err(PreconditionError::Required)
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Result<Self, TypedHeaderRejection> {
+        Ok(Self {
+            if_match: optional(headers)?,
+            if_unmodified_since: optional(headers).ok().flatten(),
+        })
+    }
+}
+
+fn optional<T>(headers: &HeaderMap) -> Result<Option<T>, TypedHeaderRejection>
+where
+    T: Header,
+{
+    // headers that are comma separated lists, like `If-Match`, decode successfully from zero
+    // values, so check that the header is there first
+    if !headers.contains_key(T::name()) {
+        return Ok(None);
+    }
+    match decode(headers) {
+        Ok(header) => Ok(Some(header)),
+        Err(rejection) if rejection.is_missing() => Ok(None),
+        Err(rejection) => Err(rejection),
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Preconditions
+where
+    S: Send + Sync,
+{
+    type Rejection = TypedHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Self::from_headers(&parts.headers)
+    }
+}
+
+/// Error returned by [`Preconditions::check`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PreconditionError {
+    /// The request didn't have any preconditions. Responds with `428 Precondition Required`.
+    Required,
+    /// The preconditions didn't match the current state of the resource. Responds with
+    /// `412 Precondition Failed`.
+    Failed,
+}
+
+impl PreconditionError {
+    /// Get the status code used for this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Required => StatusCode::PRECONDITION_REQUIRED,
+            Self::Failed => StatusCode::PRECONDITION_FAILED,
+        }
+    }
+}
+
+impl IntoResponse for PreconditionError {
+    fn into_response(self) -> Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
+
+impl fmt::Display for PreconditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Required => f.write_str(
+                "This request must be conditional. Use `If-Match` or `If-Unmodified-Since`",
+            ),
+            Self::Failed => f.write_str("The resource has been modified"),
+        }
+    }
+}
+
+impl std::error::Error for PreconditionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::put, Router};
+    use http::HeaderValue;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    // Wed, 21 Oct 2015 07:28:00 GMT
+    const DATE: u64 = 1445412480;
+
+    fn check(
+        headers: &[(&'static str, &'static str)],
+        etag: Option<&str>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), PreconditionError> {
+        let exists = etag.is_some() || last_modified.is_some();
+        check_exists(headers, exists, etag, last_modified)
+    }
+
+    fn check_exists(
+        headers: &[(&'static str, &'static str)],
+        exists: bool,
+        etag: Option<&str>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), PreconditionError> {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, HeaderValue::from_static(value));
+        }
+        let etag = etag.map(|etag| etag.parse::<ETag>().unwrap());
+        Preconditions::from_headers(&map)
+            .unwrap()
+            .check(exists, etag.as_ref(), last_modified)
+    }
+
+    #[test]
+    fn if_match() {
+        assert!(check(&[("if-match", "\"a\", \"b\"")], Some("\"b\""), None).is_ok());
+        assert!(matches!(
+            check(&[("if-match", "\"a\"")], Some("\"b\""), None),
+            Err(PreconditionError::Failed)
+        ));
+        // weak entity tags never match
+        assert!(check(&[("if-match", "W/\"a\"")], Some("W/\"a\""), None).is_err());
+        assert!(check(&[("if-match", "*")], Some("\"a\""), None).is_ok());
+        assert!(check(&[("if-match", "*")], None, None).is_err());
+        // `*` matches any resource that exists, even without an entity tag
+        assert!(check_exists(&[("if-match", "*")], true, None, None).is_ok());
+        assert!(check_exists(&[("if-match", "\"a\"")], true, None, None).is_err());
+        assert!(check_exists(&[("if-match", "*")], false, Some("\"a\""), None).is_err());
+        // `If-Unmodified-Since` is ignored if there is an `If-Match`
+        assert!(check(
+            &[
+                ("if-match", "\"a\""),
+                ("if-unmodified-since", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            ],
+            Some("\"a\""),
+            Some(UNIX_EPOCH + Duration::from_secs(DATE + 60)),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn if_unmodified_since() {
+        let headers = [("if-unmodified-since", "Wed, 21 Oct 2015 07:28:00 GMT")];
+        assert!(check(
+            &headers,
+            None,
+            Some(UNIX_EPOCH + Duration::from_millis(DATE * 1000 + 500))
+        )
+        .is_ok());
+        assert!(matches!(
+            check(
+                &headers,
+                None,
+                Some(UNIX_EPOCH + Duration::from_secs(DATE + 1))
+            ),
+            Err(PreconditionError::Failed)
+        ));
+        assert!(check(&headers, None, None).is_ok());
+        // invalid dates are ignored
+        assert!(matches!(
+            check(&[("if-unmodified-since", "yesterday")], None, None),
+            Err(PreconditionError::Required)
+        ));
+    }
+
+    #[tokio::test]
+    async fn responses() {
+        async fn handler(preconditions: Preconditions) -> Response {
+            let etag = "\"v1\"".parse::<ETag>().unwrap();
+            match preconditions.check(true, Some(&etag), None) {
+                Ok(()) => StatusCode::NO_CONTENT.into_response(),
+                Err(err) => err.into_response(),
+            }
+        }
+
+        let client = TestClient::new(Router::new().route("/", put(handler)));
+
+        let res = client.put("/").header("if-match", "\"v1\"").await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let res = client.put("/").header("if-match", "\"v0\"").await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let res = client.put("/").await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_REQUIRED);
+    }
+}
@@ -5,8 +5,11 @@ use axum::{
     Error,
 };
 use http::{request::Parts, StatusCode};
-use serde::de::DeserializeOwned;
-use std::fmt;
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{fmt, marker::PhantomData, str::FromStr};
 
 /// Extractor that deserializes query strings into some type.
 ///
@@ -262,6 +265,96 @@ impl std::error::Error for OptionalQueryRejection {
     }
 }
 
+/// A list of values separated by commas, such as `?ids=1,2,3`.
+///
+/// Use this as the type of a field in a struct extracted with [`Query`] or
+/// [`axum::extract::Query`]. Each value is parsed with [`FromStr`]. An empty value produces an
+/// empty list.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{routing::get, Router};
+/// use axum_extra::extract::{CommaSeparated, Query};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     ids: CommaSeparated<u64>,
+/// }
+///
+/// // This will parse `?ids=1,2,3` into `vec![1, 2, 3]`
+/// async fn list_things(Query(params): Query<Params>) {
+///     let ids: Vec<u64> = params.ids.0;
+///     // ...
+/// }
+///
+/// let app = Router::new().route("/list_things", get(list_things));
+/// # let _: Router = app;
+/// ```
+///
+/// [`FromStr`]: std::str::FromStr
+#[cfg_attr(docsrs, doc(cfg(feature = "query")))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommaSeparated<T>(pub Vec<T>);
+
+impl<'de, T> Deserialize<'de> for CommaSeparated<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CommaSeparatedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for CommaSeparatedVisitor<T>
+        where
+            T: FromStr,
+            T::Err: fmt::Display,
+        {
+            type Value = CommaSeparated<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a comma separated list")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v.is_empty() {
+                    return Ok(CommaSeparated(Vec::new()));
+                }
+
+                v.split(',')
+                    .map(|value| value.parse().map_err(E::custom))
+                    .collect::<Result<_, _>>()
+                    .map(CommaSeparated)
+            }
+        }
+
+        deserializer.deserialize_str(CommaSeparatedVisitor(PhantomData))
+    }
+}
+
+impl<T> std::ops::Deref for CommaSeparated<T> {
+    type Target = Vec<T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for CommaSeparated<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -375,4 +468,33 @@ mod tests {
 
         assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[tokio::test]
+    async fn comma_separated() {
+        #[derive(Deserialize)]
+        struct Params {
+            #[serde(default)]
+            ids: CommaSeparated<u64>,
+        }
+
+        let app = Router::new().route(
+            "/",
+            post(|Query(params): Query<Params>| async move { format!("{:?}", params.ids.0) }),
+        );
+
+        let client = TestClient::new(app);
+
+        let res = client.post("/?ids=1,2,3").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "[1, 2, 3]");
+
+        let res = client.post("/?ids=").await;
+        assert_eq!(res.text().await, "[]");
+
+        let res = client.post("/").await;
+        assert_eq!(res.text().await, "[]");
+
+        let res = client.post("/?ids=1,foo").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -0,0 +1,354 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use http::{
+    header::{CONTENT_RANGE, IF_RANGE, RANGE},
+    request::Parts,
+    HeaderMap, HeaderValue, StatusCode,
+};
+use std::{fmt, ops::Range};
+
+/// Extractor for the byte ranges requested with the `Range` header.
+///
+/// Ranges in the `Range` header are inclusive and may be open ended, or count from the end of the
+/// resource, which makes them easy to get wrong. [`RangeHeader::satisfiable_ranges`] resolves
+/// them against the length of the resource into half-open [`Range`]s that can be used to slice
+/// the data directly, and [`RangeHeader::content_range`] builds the matching `Content-Range`
+/// header.
+///
+/// The request is rejected if it doesn't have a `Range` header, or if the header isn't a valid
+/// byte range. Servers should ignore such headers and respond with the whole resource, so this is
+/// usually extracted as `Option<RangeHeader>`.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     Router,
+///     routing::get,
+///     http::{header::CONTENT_RANGE, StatusCode},
+///     response::{IntoResponse, Response},
+/// };
+/// use axum_extra::extract::RangeHeader;
+///
+/// static DATA: &[u8] = b"hello world";
+///
+/// async fn download(range: Option<RangeHeader>) -> Response {
+///     let Some(range) = range else {
+///         return DATA.into_response();
+///     };
+///
+///     let len = DATA.len() as u64;
+///     match range.satisfiable_ranges(len) {
+///         // only serve the first range to keep the example short
+///         Ok(ranges) => (
+///             StatusCode::PARTIAL_CONTENT,
+///             [(CONTENT_RANGE, RangeHeader::content_range(&ranges[0], len))],
+///             &DATA[ranges[0].start as usize..ranges[0].end as usize],
+///         )
+///             .into_response(),
+///         Err(not_satisfiable) => not_satisfiable.into_response(),
+///     }
+/// }
+///
+/// let app = Router::new().route("/download", get(download));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeHeader {
+    ranges: Vec<ByteRange>,
+    if_range: Option<HeaderValue>,
+}
+
+/// A single range from the `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `first-last`, from `first` to `last` inclusive.
+    FromTo(u64, u64),
+    /// `first-`, from `first` to the end of the resource.
+    From(u64),
+    /// `-length`, the last `length` bytes of the resource.
+    Last(u64),
+}
+
+impl ByteRange {
+    /// Resolve the range against the length of the resource.
+    ///
+    /// Ranges extending past the end of the resource are clamped. Returns `None` if the range
+    /// doesn't contain any bytes of the resource.
+    pub fn resolve(&self, len: u64) -> Option<Range<u64>> {
+        let range = match *self {
+            Self::FromTo(first, last) => first..last.min(len.checked_sub(1)?) + 1,
+            Self::From(first) => first..len,
+            Self::Last(length) => len.saturating_sub(length)..len,
+        };
+        (range.start < range.end).then_some(range)
+    }
+}
+
+impl RangeHeader {
+    /// Parse the `Range` header, and keep the `If-Range` header if there is one.
+    ///
+    /// Returns `None` if the `Range` header is missing, or isn't a valid byte range.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(RANGE)?.to_str().ok()?;
+        let (unit, ranges) = value.trim().split_once('=')?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+
+        let ranges = ranges
+            .split(',')
+            .map(str::trim)
+            // empty list elements are allowed
+            .filter(|range| !range.is_empty())
+            .map(parse_range)
+            .collect::<Option<Vec<_>>>()?;
+
+        (!ranges.is_empty()).then(|| Self {
+            ranges,
+            if_range: headers.get(IF_RANGE).cloned(),
+        })
+    }
+
+    /// The requested ranges, in the order they were requested.
+    pub fn ranges(&self) -> &[ByteRange] {
+        &self.ranges
+    }
+
+    /// The `If-Range` header of the request.
+    ///
+    /// If it's set, the ranges must only be served if it matches the `ETag` or `Last-Modified`
+    /// of the resource, and the whole resource must be served otherwise.
+    pub fn if_range(&self) -> Option<&HeaderValue> {
+        self.if_range.as_ref()
+    }
+
+    /// Resolve the requested ranges against the length of the resource.
+    ///
+    /// Ranges that don't contain any bytes of the resource are skipped, and ranges extending past
+    /// the end are clamped. The returned ranges are half-open and in the order they were
+    /// requested. If none of the ranges are satisfiable a [`RangeNotSatisfiable`] is returned,
+    /// which responds with `416 Range Not Satisfiable`.
+    pub fn satisfiable_ranges(&self, len: u64) -> Result<Vec<Range<u64>>, RangeNotSatisfiable> {
+        let ranges = self
+            .ranges
+            .iter()
+            .filter_map(|range| range.resolve(len))
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            Err(RangeNotSatisfiable { len })
+        } else {
+            Ok(ranges)
+        }
+    }
+
+    /// Build the `Content-Range` header for a range returned by
+    /// [`satisfiable_ranges`](Self::satisfiable_ranges), such as `bytes 0-499/1234`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    #[track_caller]
+    pub fn content_range(range: &Range<u64>, len: u64) -> HeaderValue {
+        assert!(range.start < range.end, "`range` cannot be empty");
+        let value = format!("bytes {}-{}/{len}", range.start, range.end - 1);
+        HeaderValue::try_from(value).expect("`Content-Range` is a valid header value")
+    }
+}
+
+fn parse_range(range: &str) -> Option<ByteRange> {
+    let (first, last) = range.split_once('-')?;
+    let (first, last) = (first.trim(), last.trim());
+
+    if first.is_empty() {
+        return Some(ByteRange::Last(parse_position(last)?));
+    }
+    let first = parse_position(first)?;
+    if last.is_empty() {
+        return Some(ByteRange::From(first));
+    }
+    let last = parse_position(last)?;
+    (first <= last).then_some(ByteRange::FromTo(first, last))
+}
+
+fn parse_position(position: &str) -> Option<u64> {
+    // `u64::from_str` accepts a leading `+`
+    if !position.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    position.parse().ok()
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RangeHeader
+where
+    S: Send + Sync,
+{
+    type Rejection = RangeHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(RANGE) {
+            return Err(RangeHeaderRejection::Missing);
+        }
+        Self::from_headers(&parts.headers).ok_or(RangeHeaderRejection::Invalid)
+    }
+}
+
+/// Rejection used for [`RangeHeader`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RangeHeaderRejection {
+    /// The request didn't have a `Range` header.
+    Missing,
+    /// The `Range` header wasn't a valid byte range.
+    Invalid,
+}
+
+impl IntoResponse for RangeHeaderRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::BAD_REQUEST;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for RangeHeaderRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => f.write_str("Header of type `range` was missing"),
+            Self::Invalid => f.write_str("Header of type `range` was not a valid byte range"),
+        }
+    }
+}
+
+impl std::error::Error for RangeHeaderRejection {}
+
+/// None of the ranges requested with the `Range` header are satisfiable.
+///
+/// Returned by [`RangeHeader::satisfiable_ranges`]. Responds with `416 Range Not Satisfiable` and
+/// a `Content-Range` header containing the length of the resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeNotSatisfiable {
+    len: u64,
+}
+
+impl RangeNotSatisfiable {
+    /// The length of the resource the ranges were resolved against.
+    pub fn complete_len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl IntoResponse for RangeNotSatisfiable {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(CONTENT_RANGE, format!("bytes */{}", self.len))],
+        )
+            .into_response()
+    }
+}
+
+impl fmt::Display for RangeNotSatisfiable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "None of the requested ranges are satisfiable for a length of {}",
+            self.len
+        )
+    }
+}
+
+impl std::error::Error for RangeNotSatisfiable {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    fn parse(value: &'static str) -> Option<Vec<ByteRange>> {
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, HeaderValue::from_static(value));
+        RangeHeader::from_headers(&headers).map(|range| range.ranges)
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(
+            parse("bytes=0-499, 500- ,-200,"),
+            Some(vec![
+                ByteRange::FromTo(0, 499),
+                ByteRange::From(500),
+                ByteRange::Last(200),
+            ])
+        );
+        assert_eq!(parse("Bytes=1-1"), Some(vec![ByteRange::FromTo(1, 1)]));
+        assert_eq!(parse("bytes=5-4"), None);
+        assert_eq!(parse("bytes=+1-2"), None);
+        assert_eq!(parse("bytes=-"), None);
+        assert_eq!(parse("bytes="), None);
+        assert_eq!(parse("items=0-1"), None);
+    }
+
+    #[test]
+    fn resolving() {
+        assert_eq!(ByteRange::FromTo(0, 499).resolve(1000), Some(0..500));
+        assert_eq!(ByteRange::FromTo(900, 1999).resolve(1000), Some(900..1000));
+        assert_eq!(
+            ByteRange::FromTo(0, u64::MAX).resolve(u64::MAX),
+            Some(0..u64::MAX)
+        );
+        assert_eq!(ByteRange::FromTo(1000, 1001).resolve(1000), None);
+        assert_eq!(ByteRange::From(999).resolve(1000), Some(999..1000));
+        assert_eq!(ByteRange::From(1000).resolve(1000), None);
+        assert_eq!(ByteRange::Last(200).resolve(1000), Some(800..1000));
+        assert_eq!(ByteRange::Last(2000).resolve(1000), Some(0..1000));
+        assert_eq!(ByteRange::Last(0).resolve(1000), None);
+        assert_eq!(ByteRange::Last(10).resolve(0), None);
+    }
+
+    #[test]
+    fn content_range() {
+        assert_eq!(
+            RangeHeader::content_range(&(0..500), 1234),
+            "bytes 0-499/1234"
+        );
+        assert_eq!(
+            RangeHeader::content_range(&(1233..1234), 1234),
+            "bytes 1233-1233/1234"
+        );
+    }
+
+    #[tokio::test]
+    async fn extracting() {
+        async fn handler(range: Option<RangeHeader>) -> Response {
+            let Some(range) = range else {
+                return "full".into_response();
+            };
+            match range.satisfiable_ranges(10) {
+                Ok(ranges) => format!("{ranges:?}").into_response(),
+                Err(err) => err.into_response(),
+            }
+        }
+
+        let client = TestClient::new(Router::new().route("/", get(handler)));
+
+        let res = client.get("/").await;
+        assert_eq!(res.text().await, "full");
+
+        let res = client.get("/").header("range", "bytes=nope").await;
+        assert_eq!(res.text().await, "full");
+
+        let res = client.get("/").header("range", "bytes=20-, -3").await;
+        assert_eq!(res.text().await, "[7..10]");
+
+        let res = client.get("/").header("range", "bytes=20-").await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(res.headers()["content-range"], "bytes */10");
+    }
+}
@@ -0,0 +1,405 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    response::{IntoResponse, Response},
+};
+use http::{request::Parts, HeaderName, HeaderValue, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Incoming ids longer than this are replaced, so clients can't fill logs with huge ids.
+const MAX_LEN: usize = 256;
+
+/// Extractor for the id of the request, set by [`RequestIdLayer`].
+///
+/// The id is read from the `X-Request-Id` header, or generated if the request doesn't have one,
+/// and sent back in the same header of the response. Use it to correlate logs of the same
+/// request across services.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::extract::{RequestId, RequestIdLayer};
+///
+/// async fn handler(request_id: RequestId) -> String {
+///     format!("your request id is {request_id}")
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(RequestIdLayer::new());
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// The id as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consume `self`, returning the id as a string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    fn header_value(&self) -> HeaderValue {
+        // only valid ids are created
+        HeaderValue::from_str(&self.0).unwrap()
+    }
+
+    fn parse(id: &str) -> Option<Self> {
+        let valid =
+            !id.is_empty() && id.len() <= MAX_LEN && id.bytes().all(|byte| byte.is_ascii_graphic());
+        valid.then(|| Self(id.to_owned()))
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = RequestIdRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or(RequestIdRejection::MissingRequestIdLayer)
+    }
+}
+
+/// Rejection used for [`RequestId`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RequestIdRejection {
+    /// [`RequestIdLayer`] wasn't applied to the route.
+    MissingRequestIdLayer,
+}
+
+impl IntoResponse for RequestIdRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for RequestIdRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRequestIdLayer => {
+                f.write_str("Missing `RequestIdLayer`. Add it to extract `RequestId`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RequestIdRejection {}
+
+/// Generates ids for requests that don't have one. See [`RequestIdLayer::make_request_id`].
+///
+/// This is implemented for closures taking the request and returning a `String`. Ids that
+/// contain characters other than visible ASCII, are empty, or are longer than 256 bytes are
+/// replaced with a [`RandomRequestId`].
+pub trait MakeRequestId: Clone + Send + Sync + 'static {
+    /// Generate an id for `req`.
+    fn make_request_id(&self, req: &Request) -> String;
+}
+
+impl<F> MakeRequestId for F
+where
+    F: Fn(&Request) -> String + Clone + Send + Sync + 'static,
+{
+    fn make_request_id(&self, req: &Request) -> String {
+        self(req)
+    }
+}
+
+/// Generates random version 4 UUIDs, such as `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+///
+/// This is the default [`MakeRequestId`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomRequestId {
+    _priv: (),
+}
+
+impl MakeRequestId for RandomRequestId {
+    fn make_request_id(&self, _req: &Request) -> String {
+        random_uuid()
+    }
+}
+
+fn random_uuid() -> String {
+    let mut n = fastrand::u128(..);
+    // set the version to 4 and the variant to RFC 4122
+    n = (n & !(0xf_u128 << 76)) | (0x4 << 76);
+    n = (n & !(0x3_u128 << 62)) | (0x2 << 62);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        n >> 96,
+        (n >> 80) & 0xffff,
+        (n >> 64) & 0xffff,
+        (n >> 48) & 0xffff,
+        n & 0xffff_ffff_ffff,
+    )
+}
+
+/// Layer that assigns an id to every request, for the [`RequestId`] extractor.
+///
+/// The id is read from the `X-Request-Id` header of the request, or generated with a
+/// [`MakeRequestId`] if the request doesn't have a valid one. It's inserted into the request
+/// extensions and headers, so inner services see the same id, and added to the response headers,
+/// unless the response already has the header.
+#[derive(Debug, Clone)]
+pub struct RequestIdLayer<M = RandomRequestId> {
+    header: HeaderName,
+    make_request_id: M,
+    trust_incoming: bool,
+}
+
+impl RequestIdLayer {
+    /// Create a new `RequestIdLayer` that uses the `X-Request-Id` header and random UUIDs.
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+            make_request_id: RandomRequestId::default(),
+            trust_incoming: true,
+        }
+    }
+}
+
+impl Default for RequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> RequestIdLayer<M> {
+    /// Set the header the id is read from and sent in.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Set how ids are generated for requests that don't have one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{Router, extract::Request};
+    /// use axum_extra::extract::RequestIdLayer;
+    /// use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
+    ///
+    /// let counter = Arc::new(AtomicU64::new(0));
+    /// let layer = RequestIdLayer::new().make_request_id(move |_: &Request| {
+    ///     format!("req-{}", counter.fetch_add(1, Ordering::Relaxed))
+    /// });
+    ///
+    /// let app = Router::new().layer(layer);
+    /// # let _: Router = app;
+    /// ```
+    pub fn make_request_id<T>(self, make_request_id: T) -> RequestIdLayer<T>
+    where
+        T: MakeRequestId,
+    {
+        RequestIdLayer {
+            header: self.header,
+            make_request_id,
+            trust_incoming: self.trust_incoming,
+        }
+    }
+
+    /// Set whether ids sent by clients are used.
+    ///
+    /// Defaults to `true`. Set this to `false` for services that are exposed directly to
+    /// untrusted clients, so every request gets a generated id.
+    pub fn trust_incoming(mut self, trust_incoming: bool) -> Self {
+        self.trust_incoming = trust_incoming;
+        self
+    }
+}
+
+impl<S, M> Layer<S> for RequestIdLayer<M>
+where
+    M: Clone,
+{
+    type Service = RequestIdService<S, M>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            header: self.header.clone(),
+            make_request_id: self.make_request_id.clone(),
+            trust_incoming: self.trust_incoming,
+        }
+    }
+}
+
+/// Middleware that assigns an id to every request, for the [`RequestId`] extractor.
+///
+/// Created with [`RequestIdLayer`].
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S, M = RandomRequestId> {
+    inner: S,
+    header: HeaderName,
+    make_request_id: M,
+    trust_incoming: bool,
+}
+
+impl<S, M> Service<Request> for RequestIdService<S, M>
+where
+    S: Service<Request, Response = Response>,
+    M: MakeRequestId,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = RequestIdFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let incoming = if self.trust_incoming {
+            req.headers()
+                .get(&self.header)
+                .and_then(|value| value.to_str().ok())
+                .and_then(RequestId::parse)
+        } else {
+            None
+        };
+        let request_id = incoming.unwrap_or_else(|| {
+            let id = self.make_request_id.make_request_id(&req);
+            RequestId::parse(&id).unwrap_or_else(|| RequestId(random_uuid()))
+        });
+
+        let value = request_id.header_value();
+        req.headers_mut().insert(self.header.clone(), value.clone());
+        req.extensions_mut().insert(request_id);
+
+        RequestIdFuture {
+            inner: self.inner.call(req),
+            header: Some((self.header.clone(), value)),
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`RequestIdService`].
+    pub struct RequestIdFuture<F> {
+        #[pin]
+        inner: F,
+        header: Option<(HeaderName, HeaderValue)>,
+    }
+}
+
+impl<F, E> Future for RequestIdFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.inner.poll(cx))?;
+        if let Some((name, value)) = this.header.take() {
+            if !res.headers().contains_key(&name) {
+                res.headers_mut().insert(name, value);
+            }
+        }
+        Poll::Ready(Ok(res))
+    }
+}
+
+impl<F> fmt::Debug for RequestIdFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestIdFuture").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    async fn handler(request_id: RequestId) -> String {
+        request_id.into_string()
+    }
+
+    #[tokio::test]
+    async fn generates_and_propagates() {
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(RequestIdLayer::new());
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        let header = res.headers()["x-request-id"].to_str().unwrap().to_owned();
+        let id = res.text().await;
+        assert_eq!(header, id);
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+
+        let res = client.get("/").header("x-request-id", "abc-123").await;
+        assert_eq!(res.headers()["x-request-id"], "abc-123");
+        assert_eq!(res.text().await, "abc-123");
+
+        let res = client
+            .get("/")
+            .header("x-request-id", "a".repeat(300))
+            .await;
+        assert_eq!(res.text().await.len(), 36);
+    }
+
+    #[tokio::test]
+    async fn configured() {
+        let app = Router::new().route("/", get(handler)).layer(
+            RequestIdLayer::new()
+                .header(HeaderName::from_static("x-correlation-id"))
+                .make_request_id(|req: &Request| format!("{}-1", req.method()))
+                .trust_incoming(false),
+        );
+        let client = TestClient::new(app);
+
+        let res = client.get("/").header("x-correlation-id", "abc").await;
+        assert_eq!(res.headers()["x-correlation-id"], "GET-1");
+        assert_eq!(res.text().await, "GET-1");
+    }
+
+    #[tokio::test]
+    async fn missing_layer() {
+        let client = TestClient::new(Router::new().route("/", get(handler)));
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn uuid_format() {
+        let id = random_uuid();
+        let groups = id.split('-').map(str::len).collect::<Vec<_>>();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+    }
+}
@@ -0,0 +1,294 @@
+use axum::{
+    async_trait,
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+    RequestExt,
+};
+use bytes::BytesMut;
+use http::StatusCode;
+use http_body_util::BodyExt;
+use sha2::{Digest, Sha256};
+use std::{fmt, io};
+use tokio::{
+    fs::File,
+    io::{AsyncSeekExt, AsyncWriteExt},
+};
+
+/// The default number of bytes [`SpooledBody`] keeps in memory, 1 MiB.
+pub const DEFAULT_MAX_MEMORY: usize = 1024 * 1024;
+
+/// Extractor that buffers the request body in memory, or in a temporary file if it is larger
+/// than `MAX_MEMORY` bytes.
+///
+/// This is meant for large raw uploads, such as `PUT` requests with a file as the body, that
+/// shouldn't be buffered in memory. For `multipart/form-data` uploads use
+/// [`Multipart`](crate::extract::Multipart).
+///
+/// The temporary file is deleted when it's closed. The length and SHA-256 digest of the body are
+/// computed while it's being read.
+///
+/// The [default body limit](axum::extract::DefaultBodyLimit) applies to this extractor, so it
+/// must be raised or disabled for bodies larger than 2 MB.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{
+///     Router,
+///     extract::DefaultBodyLimit,
+///     routing::put,
+///     http::StatusCode,
+/// };
+/// use axum_extra::extract::{SpooledBody, SpooledData};
+///
+/// async fn upload(body: SpooledBody) -> Result<String, StatusCode> {
+///     let len = body.len();
+///     let digest = body.sha256();
+///
+///     match body.into_data() {
+///         SpooledData::Memory(bytes) => {
+///             // ...
+///         }
+///         SpooledData::File(file) => {
+///             // ...
+///         }
+///     }
+///
+///     Ok(format!("received {len} bytes"))
+/// }
+///
+/// let app = Router::new()
+///     .route("/files/:name", put(upload))
+///     .layer(DefaultBodyLimit::max(1024 * 1024 * 1024));
+/// # let _: Router = app;
+/// ```
+///
+/// Since this extractor consumes the request body it must be the last argument of the handler.
+#[derive(Debug)]
+pub struct SpooledBody<const MAX_MEMORY: usize = DEFAULT_MAX_MEMORY> {
+    data: SpooledData,
+    len: u64,
+    sha256: [u8; 32],
+}
+
+/// The contents of a [`SpooledBody`].
+#[derive(Debug)]
+pub enum SpooledData {
+    /// The body was small enough to be kept in memory.
+    Memory(Bytes),
+    /// The body was written to a temporary file.
+    ///
+    /// The file is positioned at the start of the body.
+    File(File),
+}
+
+impl<const MAX_MEMORY: usize> SpooledBody<MAX_MEMORY> {
+    /// The length of the body in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the body is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The SHA-256 digest of the body.
+    pub fn sha256(&self) -> [u8; 32] {
+        self.sha256
+    }
+
+    /// Whether the body was kept in memory.
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self.data, SpooledData::Memory(_))
+    }
+
+    /// Get the contents of the body.
+    pub fn into_data(self) -> SpooledData {
+        self.data
+    }
+}
+
+#[async_trait]
+impl<S, const MAX_MEMORY: usize> FromRequest<S> for SpooledBody<MAX_MEMORY>
+where
+    S: Send + Sync,
+{
+    type Rejection = SpooledBodyRejection;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        spool(req.into_limited_body()).await
+    }
+}
+
+async fn spool<const MAX_MEMORY: usize>(
+    mut body: Body,
+) -> Result<SpooledBody<MAX_MEMORY>, SpooledBodyRejection> {
+    let mut hasher = Sha256::new();
+    let mut len = 0_u64;
+    let mut buf = BytesMut::new();
+    let mut file = None::<File>;
+
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(SpooledBodyRejection::FailedToReadBody)?;
+        let Ok(data) = frame.into_data() else {
+            continue;
+        };
+
+        hasher.update(&data);
+        len += data.len() as u64;
+
+        if let Some(file) = &mut file {
+            file.write_all(&data)
+                .await
+                .map_err(SpooledBodyRejection::Io)?;
+        } else if buf.len() + data.len() <= MAX_MEMORY {
+            buf.extend_from_slice(&data);
+        } else {
+            // too large to keep in memory, so move what we have so far to a file
+            let mut new_file = tempfile().await.map_err(SpooledBodyRejection::Io)?;
+            new_file
+                .write_all(&buf)
+                .await
+                .map_err(SpooledBodyRejection::Io)?;
+            new_file
+                .write_all(&data)
+                .await
+                .map_err(SpooledBodyRejection::Io)?;
+            buf = BytesMut::new();
+            file = Some(new_file);
+        }
+    }
+
+    let data = match file {
+        Some(mut file) => {
+            file.flush().await.map_err(SpooledBodyRejection::Io)?;
+            file.rewind().await.map_err(SpooledBodyRejection::Io)?;
+            SpooledData::File(file)
+        }
+        None => SpooledData::Memory(buf.freeze()),
+    };
+
+    Ok(SpooledBody {
+        data,
+        len,
+        sha256: hasher.finalize().into(),
+    })
+}
+
+async fn tempfile() -> io::Result<File> {
+    let file = tokio::task::spawn_blocking(tempfile::tempfile)
+        .await
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))??;
+    Ok(File::from_std(file))
+}
+
+/// Rejection used for [`SpooledBody`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SpooledBodyRejection {
+    /// Reading the request body failed, or it exceeded the
+    /// [body limit](axum::extract::DefaultBodyLimit).
+    FailedToReadBody(axum::Error),
+    /// Creating or writing the temporary file failed.
+    Io(io::Error),
+}
+
+impl SpooledBodyRejection {
+    /// Get the status code used for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::FailedToReadBody(err) if is_length_limit_error(err) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::FailedToReadBody(_) => StatusCode::BAD_REQUEST,
+            Self::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+fn is_length_limit_error(err: &axum::Error) -> bool {
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        if err.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+impl IntoResponse for SpooledBodyRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = self.status();
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for SpooledBodyRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FailedToReadBody(err) => write!(f, "Failed to read request body: {err}"),
+            Self::Io(err) => write!(f, "Failed to buffer request body: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SpooledBodyRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::FailedToReadBody(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{extract::DefaultBodyLimit, routing::put, Router};
+    use tokio::io::AsyncReadExt;
+
+    async fn handler(body: SpooledBody<4>) -> String {
+        let len = body.len();
+        let in_memory = body.is_in_memory();
+        let digest = body.sha256();
+        let contents = match body.into_data() {
+            SpooledData::Memory(bytes) => bytes.to_vec(),
+            SpooledData::File(mut file) => {
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents).await.unwrap();
+                contents
+            }
+        };
+        assert_eq!(digest, <[u8; 32]>::from(Sha256::digest(&contents)));
+        format!("{len} {in_memory} {}", String::from_utf8(contents).unwrap())
+    }
+
+    #[tokio::test]
+    async fn spools_large_bodies_to_file() {
+        let client = TestClient::new(Router::new().route("/", put(handler)));
+
+        let res = client.put("/").body("abc").await;
+        assert_eq!(res.text().await, "3 true abc");
+
+        let res = client.put("/").body("hello world").await;
+        assert_eq!(res.text().await, "11 false hello world");
+    }
+
+    #[tokio::test]
+    async fn respects_body_limit() {
+        let client = TestClient::new(
+            Router::new()
+                .route("/", put(handler))
+                .layer(DefaultBodyLimit::max(8)),
+        );
+
+        let res = client.put("/").body("hello world").await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
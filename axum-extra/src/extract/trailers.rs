@@ -0,0 +1,283 @@
+use axum::{
+    async_trait,
+    body::{Body, Bytes, HttpBody},
+    extract::{FromRequestParts, Request},
+    response::{IntoResponse, Response},
+    Error,
+};
+use http::{request::Parts, HeaderMap, StatusCode};
+use http_body::Frame;
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+use tokio::sync::oneshot;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Extractor for the trailers of the request body.
+///
+/// Trailers are headers sent after the body, using chunked transfer encoding in HTTP/1.1 or a
+/// final `HEADERS` frame in HTTP/2. They are used by gRPC, and for sending checksums of uploads
+/// that aren't known until the whole body has been sent.
+///
+/// `Trailers` is a future that resolves once the request body has been read to the end. It
+/// resolves to `None` if the request didn't have trailers, or if the body was dropped before it
+/// was read completely. **Awaiting it before reading the body never resolves**, so it must be
+/// awaited after the body has been consumed.
+///
+/// This requires [`TrailersLayer`] to be applied, which watches the request body for trailers.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, body::Bytes, routing::put};
+/// use axum_extra::extract::{Trailers, TrailersLayer};
+///
+/// async fn upload(trailers: Trailers, body: Bytes) -> String {
+///     // `body` is fully read by the time the handler runs
+///     let trailers = trailers.await.unwrap_or_default();
+///     match trailers.get("x-checksum") {
+///         Some(checksum) => format!("received {} bytes, checksum {checksum:?}", body.len()),
+///         None => format!("received {} bytes", body.len()),
+///     }
+/// }
+///
+/// let app = Router::new()
+///     .route("/upload", put(upload))
+///     .layer(TrailersLayer::new());
+/// # let _: Router = app;
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Trailers {
+    rx: oneshot::Receiver<HeaderMap>,
+}
+
+impl fmt::Debug for Trailers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trailers").finish_non_exhaustive()
+    }
+}
+
+impl Future for Trailers {
+    type Output = Option<HeaderMap>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // the sender is dropped without sending if there were no trailers
+        Poll::Ready(ready!(Pin::new(&mut self.rx).poll(cx)).ok())
+    }
+}
+
+/// The receiving end of the trailers, inserted into the request extensions by [`TrailersLayer`].
+#[derive(Clone)]
+struct TrailersSlot(Arc<Mutex<Option<oneshot::Receiver<HeaderMap>>>>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Trailers
+where
+    S: Send + Sync,
+{
+    type Rejection = TrailersRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let slot = parts
+            .extensions
+            .get::<TrailersSlot>()
+            .ok_or(TrailersRejection::MissingTrailersLayer)?;
+        let rx = slot
+            .0
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or(TrailersRejection::AlreadyExtracted)?;
+        Ok(Self { rx })
+    }
+}
+
+/// Rejection used for [`Trailers`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TrailersRejection {
+    /// [`TrailersLayer`] wasn't applied to the route.
+    MissingTrailersLayer,
+    /// `Trailers` was already extracted from this request.
+    AlreadyExtracted,
+}
+
+impl IntoResponse for TrailersRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for TrailersRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTrailersLayer => {
+                f.write_str("Missing `TrailersLayer`. Add it to extract `Trailers`")
+            }
+            Self::AlreadyExtracted => f.write_str("`Trailers` can only be extracted once"),
+        }
+    }
+}
+
+impl std::error::Error for TrailersRejection {}
+
+/// Layer that makes the trailers of request bodies available to the [`Trailers`] extractor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrailersLayer {
+    _priv: (),
+}
+
+impl TrailersLayer {
+    /// Create a new `TrailersLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for TrailersLayer {
+    type Service = TrailersService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TrailersService { inner }
+    }
+}
+
+/// Middleware that makes the trailers of request bodies available to the [`Trailers`] extractor.
+///
+/// Created with [`TrailersLayer`].
+#[derive(Debug, Clone)]
+pub struct TrailersService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for TrailersService<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let (tx, rx) = oneshot::channel();
+        let (mut parts, body) = req.into_parts();
+        parts
+            .extensions
+            .insert(TrailersSlot(Arc::new(Mutex::new(Some(rx)))));
+        let body = Body::new(TrailersBody {
+            inner: body,
+            tx: Some(tx),
+        });
+        self.inner.call(Request::from_parts(parts, body))
+    }
+}
+
+pin_project! {
+    struct TrailersBody {
+        #[pin]
+        inner: Body,
+        tx: Option<oneshot::Sender<HeaderMap>>,
+    }
+}
+
+impl HttpBody for TrailersBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(trailers) = frame.trailers_ref() {
+                    if let Some(tx) = this.tx.take() {
+                        let _ = tx.send(trailers.clone());
+                    }
+                }
+            }
+            // the body ended or failed, so there won't be any trailers
+            Some(Err(_)) | None => {
+                this.tx.take();
+            }
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::put, Router};
+    use futures_util::stream;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn extracts_trailers() {
+        async fn handler(trailers: Trailers, body: Bytes) -> String {
+            let trailers = trailers.await;
+            format!(
+                "{} {:?}",
+                String::from_utf8_lossy(&body),
+                trailers.as_ref().and_then(|t| t.get("x-checksum"))
+            )
+        }
+
+        let app = Router::new()
+            .route("/", put(handler))
+            .layer(TrailersLayer::new());
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", "abc".parse().unwrap());
+        let frames = vec![
+            Ok::<_, Error>(Frame::data(Bytes::from("hello"))),
+            Ok(Frame::trailers(trailers)),
+        ];
+        let body = Body::new(http_body_util::StreamBody::new(stream::iter(frames)));
+        let req = Request::put("/").body(body).unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, r#"hello Some("abc")"#);
+
+        let req = Request::put("/").body(Body::from("hello")).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "hello None");
+    }
+
+    #[tokio::test]
+    async fn missing_layer() {
+        let app = Router::new().route("/", put(|_: Trailers| async {}));
+        let req = Request::put("/").body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
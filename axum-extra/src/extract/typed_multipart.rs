@@ -0,0 +1,581 @@
+use super::multipart::{Field, Multipart, MultipartError, MultipartRejection};
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use http::StatusCode;
+use std::{fmt, str::FromStr};
+
+/// Extractor that parses `multipart/form-data` requests into a type implementing
+/// [`TryFromMultipart`].
+///
+/// ⚠️ Since parsing multipart form data from the request requires consuming the body, the
+/// `TypedMultipart` extractor must be *last* if there are multiple extractors in a handler.
+/// See ["the order of extractors"][order-of-extractors]
+///
+/// [order-of-extractors]: crate::extract#the-order-of-extractors
+///
+/// # Example
+///
+/// ```
+/// use axum::{routing::post, Router};
+/// use axum_extra::extract::{TryFromMultipart, TypedMultipart, UploadedFile};
+///
+/// #[derive(TryFromMultipart)]
+/// struct UploadForm {
+///     title: String,
+///     #[multipart(rename = "tag")]
+///     tags: Vec<String>,
+///     description: Option<String>,
+///     #[multipart(file, limit = 10_485_760)]
+///     image: UploadedFile,
+/// }
+///
+/// async fn upload(TypedMultipart(form): TypedMultipart<UploadForm>) {
+///     println!(
+///         "`{}` has {} tags and is {} bytes",
+///         form.title,
+///         form.tags.len(),
+///         form.image.bytes().len(),
+///     );
+/// }
+///
+/// let app = Router::new().route("/upload", post(upload));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypedMultipart<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for TypedMultipart<T>
+where
+    T: TryFromMultipart,
+    S: Send + Sync,
+{
+    type Rejection = TypedMultipartRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let mut multipart = Multipart::from_request(req, state).await?;
+        let value = T::try_from_multipart(&mut multipart).await?;
+        Ok(Self(value))
+    }
+}
+
+axum_core::__impl_deref!(TypedMultipart);
+
+/// Types that can be parsed from the fields of a [`Multipart`] stream.
+///
+/// This is usually implemented with `#[derive(TryFromMultipart)]`, on structs with named fields.
+/// Each field of the struct is parsed from the multipart field with the same name, with
+/// [`FromMultipartField`]. Multipart fields that don't match a field of the struct are ignored.
+///
+/// Fields of type `Option<T>` are optional, and fields of type `Vec<T>` collect every multipart
+/// field with their name. Fields of other types are required, and may only be sent once.
+///
+/// # Field attributes
+///
+/// Fields can be customized with `#[multipart(...)]`:
+///
+/// - `rename = "name"`: parse the field from the multipart field called `name`.
+/// - `limit = 1024`: reject the request if the field is larger than this many bytes.
+/// - `file`: reject the request if the multipart field isn't a file, that is it has no file
+///   name.
+/// - `text`: reject the request if the multipart field is a file.
+///
+/// # Example
+///
+/// ```
+/// use axum_extra::extract::{TryFromMultipart, UploadedFile};
+///
+/// #[derive(TryFromMultipart)]
+/// struct Profile {
+///     #[multipart(rename = "display-name", text, limit = 64)]
+///     display_name: String,
+///     age: Option<u8>,
+///     #[multipart(file)]
+///     avatar: UploadedFile,
+/// }
+/// ```
+///
+/// The multipart stream can also be parsed manually:
+///
+/// ```
+/// use axum::async_trait;
+/// use axum_extra::extract::{Multipart, TryFromMultipart, TypedMultipartError};
+///
+/// struct FileNames(Vec<String>);
+///
+/// #[async_trait]
+/// impl TryFromMultipart for FileNames {
+///     async fn try_from_multipart(
+///         multipart: &mut Multipart,
+///     ) -> Result<Self, TypedMultipartError> {
+///         let mut names = Vec::new();
+///         while let Some(field) = multipart.next_field().await? {
+///             if let Some(name) = field.file_name() {
+///                 names.push(name.to_owned());
+///             }
+///         }
+///         Ok(Self(names))
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait TryFromMultipart: Sized {
+    /// Parse `Self` from the fields of `multipart`.
+    async fn try_from_multipart(multipart: &mut Multipart) -> Result<Self, TypedMultipartError>;
+}
+
+/// Types that can be parsed from a single multipart [`Field`], for [`TryFromMultipart`].
+///
+/// This is implemented for text with [`String`], for raw bytes with [`Bytes`] and `Vec<u8>`, for
+/// files with [`UploadedFile`], and for numbers, `bool`, and `char`, which are parsed from text.
+#[async_trait]
+pub trait FromMultipartField: Sized {
+    /// Parse `Self` from `field`, failing if it's larger than `limit` bytes.
+    async fn from_multipart_field(
+        field: Field,
+        limit: Option<usize>,
+    ) -> Result<Self, TypedMultipartError>;
+}
+
+/// A file uploaded in a multipart field.
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    bytes: Bytes,
+}
+
+impl UploadedFile {
+    /// The file name sent by the client, if any.
+    ///
+    /// This is controlled by the client, so it mustn't be used as a path without being
+    /// sanitized.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The content type of the file, if any.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The contents of the file.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Consume the `UploadedFile`, returning its contents.
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
+#[async_trait]
+impl FromMultipartField for UploadedFile {
+    async fn from_multipart_field(
+        field: Field,
+        limit: Option<usize>,
+    ) -> Result<Self, TypedMultipartError> {
+        let file_name = field.file_name().map(ToOwned::to_owned);
+        let content_type = field.content_type().map(ToOwned::to_owned);
+        let bytes = read_field(field, limit).await?;
+        Ok(Self {
+            file_name,
+            content_type,
+            bytes,
+        })
+    }
+}
+
+#[async_trait]
+impl FromMultipartField for Bytes {
+    async fn from_multipart_field(
+        field: Field,
+        limit: Option<usize>,
+    ) -> Result<Self, TypedMultipartError> {
+        read_field(field, limit).await
+    }
+}
+
+#[async_trait]
+impl FromMultipartField for Vec<u8> {
+    async fn from_multipart_field(
+        field: Field,
+        limit: Option<usize>,
+    ) -> Result<Self, TypedMultipartError> {
+        Ok(read_field(field, limit).await?.into())
+    }
+}
+
+#[async_trait]
+impl FromMultipartField for String {
+    async fn from_multipart_field(
+        field: Field,
+        limit: Option<usize>,
+    ) -> Result<Self, TypedMultipartError> {
+        let name = field_name(&field);
+        let bytes = read_field(field, limit).await?;
+        String::from_utf8(bytes.into()).map_err(|err| TypedMultipartError::InvalidField {
+            field: name,
+            source: err.into(),
+        })
+    }
+}
+
+macro_rules! impl_from_multipart_field_from_str {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            #[async_trait]
+            impl FromMultipartField for $ty {
+                async fn from_multipart_field(
+                    field: Field,
+                    limit: Option<usize>,
+                ) -> Result<Self, TypedMultipartError> {
+                    parse_field(field, limit).await
+                }
+            }
+        )*
+    };
+}
+
+impl_from_multipart_field_from_str!(
+    bool, char, f32, f64, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize,
+);
+
+async fn parse_field<T>(field: Field, limit: Option<usize>) -> Result<T, TypedMultipartError>
+where
+    T: FromStr,
+    T::Err: Into<BoxError>,
+{
+    let name = field_name(&field);
+    let text = String::from_multipart_field(field, limit).await?;
+    text.trim()
+        .parse()
+        .map_err(|err: T::Err| TypedMultipartError::InvalidField {
+            field: name,
+            source: err.into(),
+        })
+}
+
+/// Read the contents of `field`, failing once they exceed `limit`.
+async fn read_field(mut field: Field, limit: Option<usize>) -> Result<Bytes, TypedMultipartError> {
+    let Some(limit) = limit else {
+        return Ok(field.bytes().await?);
+    };
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await? {
+        if bytes.len() + chunk.len() > limit {
+            return Err(TypedMultipartError::FieldTooLarge {
+                field: field_name(&field),
+                limit,
+            });
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes.into())
+}
+
+fn field_name(field: &Field) -> String {
+    field.name().unwrap_or_default().to_owned()
+}
+
+/// Errors that can happen when parsing a [`TryFromMultipart`] type.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TypedMultipartError {
+    /// The request isn't valid `multipart/form-data`.
+    Multipart(MultipartError),
+    /// A required field is missing.
+    MissingField {
+        /// The name of the field.
+        field: String,
+    },
+    /// A field that may only be sent once was sent more than once.
+    DuplicateField {
+        /// The name of the field.
+        field: String,
+    },
+    /// A field is larger than its limit.
+    FieldTooLarge {
+        /// The name of the field.
+        field: String,
+        /// The limit of the field, in bytes.
+        limit: usize,
+    },
+    /// A field that must be a file has no file name.
+    ExpectedFile {
+        /// The name of the field.
+        field: String,
+    },
+    /// A field that must be text is a file.
+    ExpectedText {
+        /// The name of the field.
+        field: String,
+    },
+    /// A field couldn't be parsed.
+    InvalidField {
+        /// The name of the field.
+        field: String,
+        /// Why the field couldn't be parsed.
+        source: BoxError,
+    },
+}
+
+impl TypedMultipartError {
+    /// Get the response body text used for this rejection.
+    pub fn body_text(&self) -> String {
+        match self {
+            Self::Multipart(inner) => inner.body_text(),
+            Self::MissingField { field } => format!("Missing field `{field}`"),
+            Self::DuplicateField { field } => format!("Field `{field}` was sent more than once"),
+            Self::FieldTooLarge { field, limit } => {
+                format!("Field `{field}` is larger than {limit} bytes")
+            }
+            Self::ExpectedFile { field } => format!("Field `{field}` must be a file"),
+            Self::ExpectedText { field } => format!("Field `{field}` must not be a file"),
+            Self::InvalidField { field, source } => format!("Invalid field `{field}`: {source}"),
+        }
+    }
+
+    /// Get the status code used for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Multipart(inner) => inner.status(),
+            Self::FieldTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::MissingField { .. }
+            | Self::DuplicateField { .. }
+            | Self::ExpectedFile { .. }
+            | Self::ExpectedText { .. }
+            | Self::InvalidField { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl From<MultipartError> for TypedMultipartError {
+    fn from(inner: MultipartError) -> Self {
+        Self::Multipart(inner)
+    }
+}
+
+impl IntoResponse for TypedMultipartError {
+    fn into_response(self) -> Response {
+        let body = self.body_text();
+        axum_core::__log_rejection!(
+            rejection_type = Self,
+            body_text = body,
+            status = self.status(),
+        );
+        (self.status(), body).into_response()
+    }
+}
+
+impl fmt::Display for TypedMultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.body_text())
+    }
+}
+
+impl std::error::Error for TypedMultipartError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Multipart(inner) => Some(inner),
+            Self::InvalidField { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
+
+/// Rejection used for [`TypedMultipart`].
+///
+/// Contains one variant for each way the [`TypedMultipart`] extractor can fail.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TypedMultipartRejection {
+    #[allow(missing_docs)]
+    MultipartRejection(MultipartRejection),
+    #[allow(missing_docs)]
+    TypedMultipartError(TypedMultipartError),
+}
+
+impl IntoResponse for TypedMultipartRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::MultipartRejection(inner) => inner.into_response(),
+            Self::TypedMultipartError(inner) => inner.into_response(),
+        }
+    }
+}
+
+impl TypedMultipartRejection {
+    /// Get the response body text used for this rejection.
+    pub fn body_text(&self) -> String {
+        match self {
+            Self::MultipartRejection(inner) => inner.body_text(),
+            Self::TypedMultipartError(inner) => inner.body_text(),
+        }
+    }
+
+    /// Get the status code used for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::MultipartRejection(inner) => inner.status(),
+            Self::TypedMultipartError(inner) => inner.status(),
+        }
+    }
+}
+
+impl From<MultipartRejection> for TypedMultipartRejection {
+    fn from(inner: MultipartRejection) -> Self {
+        Self::MultipartRejection(inner)
+    }
+}
+
+impl From<TypedMultipartError> for TypedMultipartRejection {
+    fn from(inner: TypedMultipartError) -> Self {
+        Self::TypedMultipartError(inner)
+    }
+}
+
+impl fmt::Display for TypedMultipartRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MultipartRejection(inner) => write!(f, "{inner}"),
+            Self::TypedMultipartError(inner) => write!(f, "{inner}"),
+        }
+    }
+}
+
+impl std::error::Error for TypedMultipartRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MultipartRejection(inner) => Some(inner),
+            Self::TypedMultipartError(inner) => Some(inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use reqwest::multipart::{Form, Part};
+
+    #[derive(Debug)]
+    struct Upload {
+        count: u32,
+        file: UploadedFile,
+    }
+
+    // what `#[derive(TryFromMultipart)]` generates, with `#[multipart(file, limit = 8)]` on `file`
+    #[async_trait]
+    impl TryFromMultipart for Upload {
+        async fn try_from_multipart(
+            multipart: &mut Multipart,
+        ) -> Result<Self, TypedMultipartError> {
+            let mut count = None;
+            let mut file = None;
+            while let Some(field) = multipart.next_field().await? {
+                let name = field.name().map(ToOwned::to_owned);
+                match name.as_deref() {
+                    Some("count") => {
+                        let value = u32::from_multipart_field(field, None).await?;
+                        if count.replace(value).is_some() {
+                            return Err(TypedMultipartError::DuplicateField {
+                                field: "count".to_owned(),
+                            });
+                        }
+                    }
+                    Some("file") => {
+                        if field.file_name().is_none() {
+                            return Err(TypedMultipartError::ExpectedFile {
+                                field: "file".to_owned(),
+                            });
+                        }
+                        let value = UploadedFile::from_multipart_field(field, Some(8)).await?;
+                        if file.replace(value).is_some() {
+                            return Err(TypedMultipartError::DuplicateField {
+                                field: "file".to_owned(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Self {
+                count: count.ok_or_else(|| TypedMultipartError::MissingField {
+                    field: "count".to_owned(),
+                })?,
+                file: file.ok_or_else(|| TypedMultipartError::MissingField {
+                    field: "file".to_owned(),
+                })?,
+            })
+        }
+    }
+
+    fn app() -> Router {
+        Router::new().route(
+            "/",
+            post(
+                |TypedMultipart(upload): TypedMultipart<Upload>| async move {
+                    format!(
+                        "{} {} {:?}",
+                        upload.count,
+                        upload.file.file_name().unwrap(),
+                        upload.file.bytes(),
+                    )
+                },
+            ),
+        )
+    }
+
+    fn file(bytes: &'static [u8]) -> Part {
+        Part::bytes(bytes).file_name("a.txt")
+    }
+
+    #[tokio::test]
+    async fn typed_multipart() {
+        let client = TestClient::new(app());
+
+        let form = Form::new()
+            .text("count", " 3 ")
+            .text("ignored", "")
+            .part("file", file(b"abc"));
+        let res = client.post("/").multipart(form).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, r#"3 a.txt b"abc""#);
+
+        let form = Form::new().part("file", file(b"abc"));
+        let res = client.post("/").multipart(form).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "Missing field `count`");
+
+        let form = Form::new()
+            .text("count", "three")
+            .part("file", file(b"abc"));
+        let res = client.post("/").multipart(form).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            res.text().await,
+            "Invalid field `count`: invalid digit found in string"
+        );
+
+        let form = Form::new().text("count", "1").text("file", "abc");
+        let res = client.post("/").multipart(form).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "Field `file` must be a file");
+
+        let form = Form::new()
+            .text("count", "1")
+            .part("file", file(b"too large"));
+        let res = client.post("/").multipart(form).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(res.text().await, "Field `file` is larger than 8 bytes");
+    }
+}
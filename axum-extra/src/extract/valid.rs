@@ -0,0 +1,606 @@
+//! Validating extracted values.
+//!
+//! See [`Valid`] for more details.
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Request},
+    response::{IntoResponse, Response},
+};
+use http::{header, request::Parts, HeaderValue, StatusCode};
+use serde::Serialize;
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/// Extractor that validates the value extracted by another extractor.
+///
+/// `T` is an extractor that dereferences to the value to validate, such as
+/// [`Json`](axum::Json), [`Query`](axum::extract::Query), or [`Form`](axum::Form). After `T` has
+/// been extracted the value is validated using [`ValidateRequest`], and if that fails the request
+/// is rejected with `422 Unprocessable Entity` and a JSON body listing every invalid field:
+///
+/// ```json
+/// {
+///     "errors": [
+///         { "field": "email", "code": "email", "message": "email" },
+///         { "field": "password", "message": "too short" }
+///     ]
+/// }
+/// ```
+///
+/// Fields of nested structs and lists are reported as `address.city` and `items[2].name`.
+///
+/// If `T` itself rejects the request its rejection is used unchanged.
+///
+/// # Choosing how to validate
+///
+/// `V` selects how the value is validated:
+///
+/// - [`Manual`], the default, uses a hand-written [`ValidateRequest`] implementation.
+/// - [`Validator`] uses [`validator::Validate`]. Requires the `validator` feature.
+/// - [`Garde`] uses [`garde::Validate`]. Requires the `garde` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{extract::Query, Router, routing::get};
+/// use axum_extra::extract::{Valid, ValidateRequest, ValidationErrors};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Pagination {
+///     page: u32,
+///     per_page: u32,
+/// }
+///
+/// impl ValidateRequest for Pagination {
+///     fn validate_request(&self) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if self.per_page > 100 {
+///             errors.add("per_page", "must be at most 100");
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// async fn list_users(Valid(Query(pagination), _): Valid<Query<Pagination>>) {
+///     // ...
+/// }
+///
+/// let app = Router::new().route("/users", get(list_users));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Valid<T, V = Manual>(pub T, pub PhantomData<V>);
+
+impl<T, V> Valid<T, V> {
+    /// Returns the wrapped extractor
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, V> Deref for Valid<T, V> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, V> DerefMut for Valid<T, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Validation of extracted values, used by [`Valid`].
+///
+/// `V` is a marker type selecting how the value is validated. Implement `ValidateRequest` without
+/// specifying `V` for hand-written rules, which are used by `Valid` by default. The `validator`
+/// and `garde` features implement it for all types implementing the respective `Validate`
+/// traits, with [`Validator`] and [`Garde`] as `V`.
+///
+/// See [`Valid`] for an example.
+pub trait ValidateRequest<V = Manual> {
+    /// Validate `self`.
+    fn validate_request(&self) -> Result<(), ValidationErrors>;
+}
+
+/// Marker for [`Valid`] to use a hand-written [`ValidateRequest`] implementation.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct Manual;
+
+/// Marker for [`Valid`] to use [`validator::Validate`].
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Json, Router, routing::post};
+/// use axum_extra::extract::{valid, Valid};
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct CreateUser {
+///     #[validate(email)]
+///     email: String,
+///     #[validate(length(min = 8, message = "too short"))]
+///     password: String,
+/// }
+///
+/// async fn create_user(
+///     Valid(Json(payload), _): Valid<Json<CreateUser>, valid::Validator>,
+/// ) {
+///     // `payload` has been validated
+/// }
+///
+/// let app = Router::new().route("/users", post(create_user));
+/// # let _: Router = app;
+/// ```
+#[cfg(feature = "validator")]
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct Validator;
+
+#[cfg(feature = "validator")]
+impl<T> ValidateRequest<Validator> for T
+where
+    T: validator::Validate,
+{
+    fn validate_request(&self) -> Result<(), ValidationErrors> {
+        self.validate().map_err(Into::into)
+    }
+}
+
+/// Marker for [`Valid`] to use [`garde::Validate`].
+///
+/// The value is validated with the default context.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Json, Router, routing::post};
+/// use axum_extra::extract::{valid, Valid};
+/// use garde::Validate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct CreateUser {
+///     #[garde(length(min = 1))]
+///     name: String,
+///     #[garde(length(min = 8))]
+///     password: String,
+/// }
+///
+/// async fn create_user(Valid(Json(payload), _): Valid<Json<CreateUser>, valid::Garde>) {
+///     // `payload` has been validated
+/// }
+///
+/// let app = Router::new().route("/users", post(create_user));
+/// # let _: Router = app;
+/// ```
+#[cfg(feature = "garde")]
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct Garde;
+
+#[cfg(feature = "garde")]
+impl<T> ValidateRequest<Garde> for T
+where
+    T: garde::Validate,
+    T::Context: Default,
+{
+    fn validate_request(&self) -> Result<(), ValidationErrors> {
+        self.validate(&T::Context::default()).map_err(Into::into)
+    }
+}
+
+#[async_trait]
+impl<T, V, S> FromRequest<S> for Valid<T, V>
+where
+    T: FromRequest<S> + Deref,
+    T::Target: ValidateRequest<V>,
+    S: Send + Sync,
+{
+    type Rejection = ValidRejection<T::Rejection>;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let value = T::from_request(req, state)
+            .await
+            .map_err(ValidRejection::Extractor)?;
+        ValidateRequest::<V>::validate_request(&*value).map_err(ValidRejection::Invalid)?;
+        Ok(Self(value, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<T, V, S> FromRequestParts<S> for Valid<T, V>
+where
+    T: FromRequestParts<S> + Deref,
+    T::Target: ValidateRequest<V>,
+    S: Send + Sync,
+{
+    type Rejection = ValidRejection<T::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let value = T::from_request_parts(parts, state)
+            .await
+            .map_err(ValidRejection::Extractor)?;
+        ValidateRequest::<V>::validate_request(&*value).map_err(ValidRejection::Invalid)?;
+        Ok(Self(value, PhantomData))
+    }
+}
+
+/// The fields that failed validation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    /// Create an empty `ValidationErrors`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an error for `field`.
+    ///
+    /// Nested fields should be named like `address.city` and `items[2].name`.
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) -> &mut Self {
+        self.push(FieldError {
+            field: field.into(),
+            code: None,
+            message: message.into(),
+        })
+    }
+
+    /// Add an error for `field` with a machine readable code, such as `length`.
+    pub fn add_with_code(
+        &mut self,
+        field: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        self.push(FieldError {
+            field: field.into(),
+            code: Some(code.into()),
+            message: message.into(),
+        })
+    }
+
+    fn push(&mut self, error: FieldError) -> &mut Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Whether no errors have been added.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The errors, in the order they were added.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// `Ok(())` if no errors have been added, otherwise `Err(self)`.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for ValidationErrors {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        fn collect(prefix: &str, errors: &validator::ValidationErrors, out: &mut ValidationErrors) {
+            use validator::ValidationErrorsKind;
+
+            for (field, kind) in errors.errors() {
+                let path = if prefix.is_empty() {
+                    (*field).to_owned()
+                } else {
+                    format!("{prefix}.{field}")
+                };
+                match kind {
+                    ValidationErrorsKind::Field(errors) => {
+                        for error in errors {
+                            let message = error.message.as_ref().unwrap_or(&error.code);
+                            out.add_with_code(path.clone(), error.code.clone(), message.clone());
+                        }
+                    }
+                    ValidationErrorsKind::Struct(errors) => collect(&path, errors, out),
+                    ValidationErrorsKind::List(errors) => {
+                        for (idx, errors) in errors {
+                            collect(&format!("{path}[{idx}]"), errors, out);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut out = Self::new();
+        collect("", &errors, &mut out);
+        // `validator::ValidationErrors` is backed by a `HashMap`
+        out.errors.sort_by(|a, b| a.field.cmp(&b.field));
+        out
+    }
+}
+
+#[cfg(feature = "garde")]
+impl From<garde::Report> for ValidationErrors {
+    fn from(report: garde::Report) -> Self {
+        let mut out = Self::new();
+        for (path, error) in report.iter() {
+            out.add(path.to_string(), error.message());
+        }
+        out
+    }
+}
+
+/// A field that failed validation.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    message: String,
+}
+
+impl FieldError {
+    /// The path to the field, such as `address.city`.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// The machine readable code of the error, if any.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// The message describing the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+/// Rejection used for [`Valid`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValidRejection<R> {
+    /// The inner extractor rejected the request.
+    Extractor(R),
+    /// The extracted value failed validation.
+    Invalid(ValidationErrors),
+}
+
+impl<R> IntoResponse for ValidRejection<R>
+where
+    R: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        match self {
+            Self::Extractor(rejection) => rejection.into_response(),
+            Self::Invalid(errors) => match serde_json::to_vec(&errors) {
+                Ok(body) => (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+                    )],
+                    body,
+                )
+                    .into_response(),
+                Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            },
+        }
+    }
+}
+
+impl<R> fmt::Display for ValidRejection<R>
+where
+    R: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Extractor(rejection) => rejection.fmt(f),
+            Self::Invalid(errors) => errors.fmt(f),
+        }
+    }
+}
+
+impl<R> std::error::Error for ValidRejection<R>
+where
+    R: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Extractor(rejection) => Some(rejection),
+            Self::Invalid(errors) => Some(errors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{extract::Query, routing::get, Router};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Deserialize)]
+    struct Pagination {
+        per_page: u32,
+    }
+
+    impl ValidateRequest for Pagination {
+        fn validate_request(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            if self.per_page > 100 {
+                errors.add("per_page", "must be at most 100");
+            }
+            errors.into_result()
+        }
+    }
+
+    #[tokio::test]
+    async fn manual() {
+        let app = Router::new().route(
+            "/users",
+            get(
+                |Valid(Query(page), _): Valid<Query<Pagination>>| async move {
+                    page.per_page.to_string()
+                },
+            ),
+        );
+        let client = TestClient::new(app);
+
+        let res = client.get("/users?per_page=10").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "10");
+
+        let res = client.get("/users?per_page=1000").await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            res.json::<Value>().await,
+            json!({
+                "errors": [{ "field": "per_page", "message": "must be at most 100" }]
+            })
+        );
+
+        // rejections of the inner extractor are unchanged
+        let res = client.get("/users?per_page=many").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn with_validator() {
+        use axum::{routing::post, Json};
+        use validator::Validate;
+
+        #[derive(Deserialize, Validate)]
+        struct CreateUser {
+            #[validate(length(min = 1))]
+            name: String,
+            #[validate(range(min = 18, message = "must be an adult"))]
+            age: u8,
+            #[validate]
+            address: Address,
+        }
+
+        #[derive(Deserialize, Validate)]
+        struct Address {
+            #[validate(length(min = 1))]
+            city: String,
+        }
+
+        async fn create_user(Valid(Json(user), _): Valid<Json<CreateUser>, Validator>) -> String {
+            user.name
+        }
+
+        let app = Router::new().route("/users", post(create_user));
+        let client = TestClient::new(app);
+
+        let res = client
+            .post("/users")
+            .json(&json!({ "name": "alice", "age": 30, "address": { "city": "Oslo" } }))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "alice");
+
+        let res = client
+            .post("/users")
+            .json(&json!({ "name": "", "age": 3, "address": { "city": "" } }))
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            res.json::<Value>().await,
+            json!({
+                "errors": [
+                    { "field": "address.city", "code": "length", "message": "length" },
+                    { "field": "age", "code": "range", "message": "must be an adult" },
+                    { "field": "name", "code": "length", "message": "length" },
+                ]
+            })
+        );
+
+        let res = client.post("/users").body("{").await;
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[cfg(feature = "garde")]
+    #[tokio::test]
+    async fn with_garde() {
+        use axum::{routing::post, Json};
+
+        #[derive(Deserialize, garde::Validate)]
+        struct CreateUser {
+            #[garde(length(min = 1))]
+            name: String,
+            #[garde(dive)]
+            address: Address,
+        }
+
+        #[derive(Deserialize, garde::Validate)]
+        struct Address {
+            #[garde(length(min = 1))]
+            city: String,
+        }
+
+        let app = Router::new().route(
+            "/users",
+            post(|Valid(Json(user), _): Valid<Json<CreateUser>, Garde>| async move { user.name }),
+        );
+        let client = TestClient::new(app);
+
+        let res = client
+            .post("/users")
+            .json(&json!({ "name": "alice", "address": { "city": "Oslo" } }))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "alice");
+
+        let res = client
+            .post("/users")
+            .json(&json!({ "name": "alice", "address": { "city": "" } }))
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = res.json::<Value>().await;
+        assert_eq!(body["errors"][0]["field"], "address.city");
+        assert_eq!(body["errors"].as_array().unwrap().len(), 1);
+    }
+}
@@ -9,21 +9,79 @@
 //!
 //! Name | Description | Default?
 //! ---|---|---
+//! `accept-encoding` | Enables the `AcceptEncoding` extractor | No
+//! `anyhow` | Enables the `AppError` response | No
+//! `api-key` | Enables `ApiKeyLayer` and the `ApiKey` extractor for API key authentication | No
 //! `async-read-body` | Enables the `AsyncReadBody` body | No
+//! `auto-etag` | Enables the `AutoEtag` response and `AutoEtagLayer` | No
+//! `body-digest` | Enables `BodyDigestLayer` for verifying request bodies against their digest and `WithDigest` for hashing bodies while they're extracted | No
+//! `body-logging` | Enables `BodyLoggingLayer` for logging request and response bodies | No
+//! `body-reader` | Enables the `BodyReader` extractor | No
+//! `cache` | Enables `CacheLayer` for caching responses in a `CacheStore` | No
+//! `catch-panic` | Enables `CatchPanicLayer` for converting panics into responses | No
+//! `channel-body` | Enables the `ChannelBody` body | No
+//! `circuit-breaker` | Enables `CircuitBreakerLayer` for shedding load from failing routes | No
+//! `client-ip` | Enables the `ClientIp` extractor | No
+//! `compression` | Enables the `Compressed` response, without any encodings | No
+//! `compression-br` | Enables Brotli compression in `Compressed` | No
+//! `compression-gzip` | Enables gzip compression in `Compressed` | No
+//! `compression-zstd` | Enables Zstandard compression in `Compressed` | No
+//! `concurrency-limit` | Enables `ConcurrencyLimitLayer` for limiting concurrent requests per route | No
 //! `cookie` | Enables the `CookieJar` extractor | No
 //! `cookie-private` | Enables the `PrivateCookieJar` extractor | No
 //! `cookie-signed` | Enables the `SignedCookieJar` extractor | No
 //! `cookie-key-expansion` | Enables the `Key::derive_from` method | No
 //! `erased-json` | Enables the `ErasedJson` response | No
+//! `external-url` | Enables the `ExternalUrl` extractor | No
 //! `form` | Enables the `Form` extractor | No
+//! `form-encoding` | Enables decoding `Form`s submitted in encodings other than UTF-8 | No
+//! `garde` | Enables validating with `garde` in `Valid` | No
+//! `health` | Enables `HealthChecks` for serving liveness and readiness probes | No
+//! `idempotency` | Enables `IdempotencyLayer` for replaying responses to retried requests | No
 //! `json-deserializer` | Enables the `JsonDeserializer` extractor | No
 //! `json-lines` | Enables the `JsonLines` extractor and response | No
+//! `json-stream` | Enables the `JsonStream` response | No
+//! `json-with` | Enables the `JsonWith` extractor | No
+//! `maintenance` | Enables `MaintenanceMode` for toggling maintenance mode at runtime | No
+//! `matrix` | Enables the `Matrix` extractor and `MatrixParamsLayer` | No
+//! `metrics` | Enables `MetricsLayer` for recording per-route request metrics | No
+//! `minijinja` | Enables rendering `Template`s with `minijinja` | No
+//! `multi-status` | Enables the `MultiStatus` response | No
 //! `multipart` | Enables the `Multipart` extractor | No
+//! `opentelemetry` | Enables `OtelTraceLayer` for OpenTelemetry request spans and trace propagation | No
+//! `peer-certificates` | Enables the `PeerCertificates` extractor for client certificates | No
+//! `preconditions` | Enables the `Preconditions` extractor | No
 //! `protobuf` | Enables the `Protobuf` extractor and response | No
 //! `query` | Enables the `Query` extractor | No
+//! `range` | Enables the `RangeHeader` extractor and `Ranged` response | No
+//! `rate-limit` | Enables `RateLimitLayer` for limiting requests per client | No
+//! `record` | Enables `RecordLayer` and `replay` for recording requests and replaying them in tests | No
+//! `request-id` | Enables the `RequestId` extractor and `RequestIdLayer` | No
+//! `security-headers` | Enables `SecurityHeadersLayer` and the `CspNonce` extractor | No
+//! `shadow` | Enables mirroring requests to a secondary service with `ShadowLayer` | No
+//! `single-flight` | Enables `SingleFlightLayer` for coalescing concurrent identical requests | No
+//! `slow-request` | Enables `SlowRequestLayer` for reporting requests that are still running after a threshold | No
+//! `sse` | Enables `SseBroadcaster`, `ReplayBuffer`, and the `LastEventId` extractor for server-sent events | No
+//! `spooled-body` | Enables the `SpooledBody` extractor | No
+//! `static-routes` | Enables building routes from configuration with `StaticRoutes` | No
+//! `template` | Enables the `Template` response and `TemplateLayer` | No
+//! `tera` | Enables rendering `Template`s with `tera` | No
+//! `test-utils` | Enables `TestClient` and `HandlerRequest` for calling a `Router` or handler in tests without running a server | No
+//! `test-utils-proptest` | Enables `arbitrary_request` for property testing handlers with `proptest` | No
+//! `test-utils-ws` | Enables `TestWebSocket` for testing WebSocket handlers with `TestClient` | No
+//! `timeout` | Enables per-route timeouts with `TimeoutLayer` and the `Deadline` extractor | No
 //! `tracing` | Log rejections from built-in extractors | Yes
-//! `typed-routing` | Enables the `TypedPath` routing utilities | No
+//! `trailers` | Enables the `Trailers` extractor and response trailers | No
+//! `typed-routing` | Enables the `TypedPath` routing utilities and `Created` response | No
 //! `typed-header` | Enables the `TypedHeader` extractor and response  | No
+//! `typed-multipart` | Enables the `TypedMultipart` extractor and `#[derive(TryFromMultipart)]` | No
+//! `validation` | Enables the `Valid` extractor | No
+//! `validator` | Enables validating with `validator` in `Valid` | No
+//! `ws` | Enables `TypedWebSocket` for sending and receiving typed WebSocket messages | No
+//! `ws-hub` | Enables `Hub` for managing WebSocket connections in rooms | No
+//! `ws-msgpack` | Enables `MessagePackCodec` for `TypedWebSocket` | No
+//! `ws-resume` | Enables `Resumption` for resuming WebSocket sessions after reconnecting | No
+//! `ws-send-queue` | Enables `SendQueue` for sending WebSocket messages through a bounded queue | No
 //!
 //! [`axum`]: https://crates.io/crates/axum
 
@@ -86,6 +144,15 @@ pub mod json_lines;
 #[cfg(feature = "multipart")]
 pub mod multipart_builder;
 
+#[cfg(feature = "sse")]
+pub mod sse;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+#[cfg(feature = "ws")]
+pub mod ws;
+
 #[cfg(feature = "typed-header")]
 pub mod typed_header;
 
@@ -104,6 +171,8 @@ pub mod protobuf;
 #[cfg(feature = "typed-routing")]
 #[doc(hidden)]
 pub mod __private {
+    use crate::routing::TypedPathRejection;
+    use axum::BoxError;
     use percent_encoding::{AsciiSet, CONTROLS};
 
     pub use percent_encoding::utf8_percent_encode;
@@ -112,6 +181,26 @@ pub mod __private {
     const FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
     const PATH: &AsciiSet = &FRAGMENT.add(b'#').add(b'?').add(b'{').add(b'}');
     pub const PATH_SEGMENT: &AsciiSet = &PATH.add(b'/').add(b'%');
+
+    pub fn decode_capture<T, E>(
+        captures: &[(String, String)],
+        name: &'static str,
+        decode: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<T, TypedPathRejection>
+    where
+        E: Into<BoxError>,
+    {
+        // captures from outer routers come first, so prefer the last match
+        let (_, value) = captures
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .ok_or(TypedPathRejection::MissingCapture { name })?;
+        decode(value).map_err(|err| TypedPathRejection::InvalidCapture {
+            name,
+            source: err.into(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -3,6 +3,142 @@
 use crate::either::Either;
 use tower_layer::Identity;
 
+#[cfg(feature = "api-key")]
+mod api_key;
+
+#[cfg(feature = "api-key")]
+pub use self::api_key::{
+    ApiKey, ApiKeyAuth, ApiKeyLayer, ApiKeyRejection, ApiKeyStore, MemoryApiKeyStore,
+};
+
+#[cfg(feature = "body-digest")]
+mod body_digest;
+
+#[cfg(feature = "body-digest")]
+pub use self::body_digest::{
+    BodyDigest, BodyDigestLayer, BodyDigestRejection, BodyDigestVerify, DigestAlgorithm, WithDigest,
+};
+
+#[cfg(feature = "body-logging")]
+mod body_logging;
+
+#[cfg(feature = "body-logging")]
+pub use self::body_logging::{
+    BodyLogging, BodyLoggingFuture, BodyLoggingLayer, NoRedaction, Redact,
+};
+
+#[cfg(feature = "cache")]
+mod cache;
+
+#[cfg(feature = "cache")]
+pub use self::cache::{Cache, CacheLayer, CacheStore, CachedResponse, MemoryCacheStore};
+
+#[cfg(feature = "catch-panic")]
+mod catch_panic;
+
+#[cfg(feature = "catch-panic")]
+pub use self::catch_panic::{CatchPanic, CatchPanicFuture, CatchPanicLayer, Panic};
+
+#[cfg(feature = "circuit-breaker")]
+mod circuit_breaker;
+
+#[cfg(feature = "circuit-breaker")]
+pub use self::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerLayer, ClassifyResponse, ServerErrors,
+};
+
+#[cfg(feature = "concurrency-limit")]
+mod concurrency_limit;
+
+#[cfg(feature = "concurrency-limit")]
+pub use self::concurrency_limit::{ConcurrencyLimit, ConcurrencyLimitLayer};
+
+#[cfg(feature = "health")]
+mod health;
+
+#[cfg(feature = "health")]
+pub use self::health::{HealthChecks, Probe, ProbeStatus};
+
+#[cfg(feature = "idempotency")]
+mod idempotency;
+
+#[cfg(feature = "idempotency")]
+pub use self::idempotency::{
+    Idempotency, IdempotencyLayer, IdempotencyState, IdempotencyStore, MemoryIdempotencyStore,
+    StoredResponse,
+};
+
+mod layer_if;
+
+pub use self::layer_if::{layer_if, Conditional, ConditionalLayer};
+
+#[cfg(feature = "maintenance")]
+mod maintenance;
+
+#[cfg(feature = "maintenance")]
+pub use self::maintenance::{
+    MaintenanceHandle, MaintenanceMode, MaintenanceModeFuture, MaintenanceModeService,
+};
+
+#[cfg(feature = "metrics")]
+mod metrics;
+
+#[cfg(feature = "metrics")]
+pub use self::metrics::{Metrics, MetricsFuture, MetricsLayer};
+
+#[cfg(feature = "opentelemetry")]
+mod opentelemetry;
+
+#[cfg(feature = "opentelemetry")]
+pub use self::opentelemetry::{OtelTrace, OtelTraceFuture, OtelTraceLayer};
+
+#[cfg(feature = "rate-limit")]
+mod rate_limit;
+
+#[cfg(feature = "rate-limit")]
+pub use self::rate_limit::{
+    Algorithm, MemoryStore, Quota, RateLimit, RateLimitDecision, RateLimitLayer, RateLimitStore,
+};
+
+#[cfg(feature = "record")]
+mod record;
+
+#[cfg(feature = "record")]
+pub use self::record::{
+    replay, Record, RecordFuture, RecordLayer, RecordedBody, RecordedRequest, RecordedResponse,
+    Recording, ReplayMismatch,
+};
+
+#[cfg(feature = "security-headers")]
+mod security_headers;
+
+#[cfg(feature = "security-headers")]
+pub use self::security_headers::{
+    ContentSecurityPolicy, CspNonce, CspNonceRejection, FrameOptions, PermissionsPolicy,
+    ReferrerPolicy, SecurityHeaders, SecurityHeadersFuture, SecurityHeadersLayer,
+    StrictTransportSecurity,
+};
+
+#[cfg(feature = "single-flight")]
+mod single_flight;
+
+#[cfg(feature = "single-flight")]
+pub use self::single_flight::{SingleFlight, SingleFlightLayer};
+
+#[cfg(feature = "slow-request")]
+mod slow_request;
+
+#[cfg(feature = "slow-request")]
+pub use self::slow_request::{
+    SlowRequest, SlowRequestFuture, SlowRequestLayer, SlowRequestWatchdog,
+};
+
+#[cfg(feature = "timeout")]
+mod timeout;
+
+#[cfg(feature = "timeout")]
+pub use self::timeout::{Deadline, DeadlineRejection, Timeout, TimeoutFuture, TimeoutLayer};
+
 /// Convert an `Option<Layer>` into a [`Layer`].
 ///
 /// If the layer is a `Some` it'll be applied, otherwise not.
@@ -0,0 +1,474 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::future::BoxFuture;
+use http::{header, request::Parts, HeaderName, StatusCode};
+use serde_json::json;
+use std::{
+    convert::Infallible,
+    fmt,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that authenticates requests with API keys.
+///
+/// The key is read from the `X-Api-Key` header, or another [header](Self::header), or
+/// optionally from a [query parameter](Self::query). It's looked up in an [`ApiKeyStore`], and
+/// the [`ApiKey`] found is available to handlers as an extractor.
+///
+/// Requests are rejected with a JSON body such as
+/// `{"error": "invalid_api_key", "message": "..."}`:
+///
+/// - `401 Unauthorized` with the error `missing_api_key` if the request has no key.
+/// - `401 Unauthorized` with the error `invalid_api_key` if the key isn't in the store.
+/// - `403 Forbidden` with the error `insufficient_scope` if the key doesn't have all the
+///   [required scopes](Self::require_scopes).
+/// - `500 Internal Server Error` if the store fails.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::{get, post}};
+/// use axum_extra::middleware::{ApiKey, ApiKeyLayer, MemoryApiKeyStore};
+///
+/// async fn whoami(key: ApiKey) -> String {
+///     format!("authenticated as {}", key.id())
+/// }
+///
+/// let store = MemoryApiKeyStore::new();
+/// store.insert("s3cr3t", ApiKey::new("ci").with_scopes(["read", "write"]));
+///
+/// let app = Router::new()
+///     .route(
+///         "/deploy",
+///         post(|| async { /* ... */ })
+///             // only keys with the `write` scope can deploy
+///             .layer(ApiKeyLayer::new(store.clone()).require_scopes(["write"])),
+///     )
+///     .route("/whoami", get(whoami))
+///     .layer(ApiKeyLayer::new(store));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiKeyLayer<T = MemoryApiKeyStore> {
+    store: T,
+    header: HeaderName,
+    query: Option<Arc<str>>,
+    required_scopes: Arc<[String]>,
+}
+
+impl<T> ApiKeyLayer<T>
+where
+    T: ApiKeyStore,
+{
+    /// Create a new `ApiKeyLayer` that looks up keys in `store`.
+    pub fn new(store: T) -> Self {
+        Self {
+            store,
+            header: HeaderName::from_static("x-api-key"),
+            query: None,
+            required_scopes: Arc::new([]),
+        }
+    }
+
+    /// Set the header the key is read from.
+    ///
+    /// Defaults to `X-Api-Key`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Also read the key from the query parameter `name`, if the request doesn't have the
+    /// header.
+    ///
+    /// Keys in URLs can end up in logs and browser histories, so prefer headers where possible.
+    pub fn query(mut self, name: &str) -> Self {
+        self.query = Some(name.into());
+        self
+    }
+
+    /// Require keys to have all of `scopes`.
+    pub fn require_scopes<I>(mut self, scopes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.required_scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl<S, T> Layer<S> for ApiKeyLayer<T>
+where
+    T: Clone,
+{
+    type Service = ApiKeyAuth<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyAuth {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Middleware that authenticates requests with API keys.
+///
+/// Created with [`ApiKeyLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth<S, T = MemoryApiKeyStore> {
+    inner: S,
+    layer: ApiKeyLayer<T>,
+}
+
+impl<S, T> Service<Request> for ApiKeyAuth<S, T>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    T: ApiKeyStore,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let Some(key) = read_key(&req, &layer) else {
+                return Ok(AuthError::MissingApiKey.into_response());
+            };
+
+            let api_key = match layer.store.lookup(&key).await {
+                Ok(Some(api_key)) => api_key,
+                Ok(None) => return Ok(AuthError::InvalidApiKey.into_response()),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to look up API key");
+
+                    return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                }
+            };
+
+            let missing = layer
+                .required_scopes
+                .iter()
+                .filter(|scope| !api_key.has_scope(scope))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return Ok(AuthError::InsufficientScope(missing).into_response());
+            }
+
+            req.extensions_mut().insert(api_key);
+            inner.call(req).await
+        })
+    }
+}
+
+fn read_key<T>(req: &Request, layer: &ApiKeyLayer<T>) -> Option<String> {
+    if let Some(key) = req
+        .headers()
+        .get(&layer.header)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(key.to_owned());
+    }
+
+    let name = layer.query.as_deref()?;
+    form_urlencoded::parse(req.uri().query()?.as_bytes())
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+enum AuthError {
+    MissingApiKey,
+    InvalidApiKey,
+    InsufficientScope(Vec<String>),
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::MissingApiKey => (
+                StatusCode::UNAUTHORIZED,
+                json!({
+                    "error": "missing_api_key",
+                    "message": "The request doesn't have an API key",
+                }),
+            ),
+            Self::InvalidApiKey => (
+                StatusCode::UNAUTHORIZED,
+                json!({
+                    "error": "invalid_api_key",
+                    "message": "The API key is invalid",
+                }),
+            ),
+            Self::InsufficientScope(missing) => (
+                StatusCode::FORBIDDEN,
+                json!({
+                    "error": "insufficient_scope",
+                    "message": "The API key doesn't have the required scopes",
+                    "missing_scopes": missing,
+                }),
+            ),
+        };
+        if status == StatusCode::UNAUTHORIZED {
+            (status, [(header::WWW_AUTHENTICATE, "ApiKey")], Json(body)).into_response()
+        } else {
+            (status, Json(body)).into_response()
+        }
+    }
+}
+
+/// An API key found by [`ApiKeyLayer`].
+///
+/// This is also an extractor for the key of the request. See [`ApiKeyLayer`] for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    id: String,
+    scopes: Vec<String>,
+}
+
+impl ApiKey {
+    /// Create a new `ApiKey` without any scopes.
+    ///
+    /// The `id` identifies the key, or its owner, to handlers. It should not be the key itself.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Set the scopes of the key.
+    pub fn with_scopes<I>(mut self, scopes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The id of the key.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The scopes of the key.
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    /// Whether the key has `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|existing| existing == scope)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ApiKey
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiKeyRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or(ApiKeyRejection::MissingApiKeyLayer)
+    }
+}
+
+/// Rejection used for [`ApiKey`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ApiKeyRejection {
+    /// [`ApiKeyLayer`] wasn't applied to the route.
+    MissingApiKeyLayer,
+}
+
+impl IntoResponse for ApiKeyRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for ApiKeyRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingApiKeyLayer => {
+                f.write_str("Missing `ApiKeyLayer`. Add it to extract `ApiKey`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiKeyRejection {}
+
+/// Looks up API keys for an [`ApiKeyLayer`].
+///
+/// Implementations should not reveal through their timing how much of a key matched. Comparing
+/// keys with `==` stops at the first difference, so prefer looking up keys by a hash of the
+/// key, or compare them in constant time.
+#[async_trait]
+pub trait ApiKeyStore: Clone + Send + Sync + 'static {
+    /// The error returned if looking up a key fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Look up `key`, returning `None` if it isn't valid.
+    async fn lookup(&self, key: &str) -> Result<Option<ApiKey>, Self::Error>;
+}
+
+/// [`ApiKeyStore`] that keeps keys in memory.
+///
+/// Keys are compared in constant time. The store can be cloned to share it between layers, and
+/// keys can be added and removed while the server is running.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryApiKeyStore {
+    keys: Arc<RwLock<Vec<(String, ApiKey)>>>,
+}
+
+impl MemoryApiKeyStore {
+    /// Create a new empty `MemoryApiKeyStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `key`, replacing it if it was added before.
+    pub fn insert(&self, key: impl Into<String>, api_key: ApiKey) {
+        let key = key.into();
+        let mut keys = self.keys.write().unwrap();
+        keys.retain(|(existing, _)| *existing != key);
+        keys.push((key, api_key));
+    }
+
+    /// Remove `key`, returning whether it was added before.
+    pub fn remove(&self, key: &str) -> bool {
+        let mut keys = self.keys.write().unwrap();
+        let len = keys.len();
+        keys.retain(|(existing, _)| existing != key);
+        keys.len() != len
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for MemoryApiKeyStore {
+    type Error = Infallible;
+
+    async fn lookup(&self, key: &str) -> Result<Option<ApiKey>, Self::Error> {
+        let keys = self.keys.read().unwrap();
+        // compare with every key, so the time doesn't depend on which key matched
+        let mut found = None;
+        for (candidate, api_key) in keys.iter() {
+            if constant_time_eq(candidate.as_bytes(), key.as_bytes()) {
+                found = Some(api_key.clone());
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Compare `a` and `b` in a time that only depends on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b));
+    std::hint::black_box(diff) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use serde_json::Value;
+
+    fn app() -> Router {
+        let store = MemoryApiKeyStore::new();
+        store.insert("reader-key", ApiKey::new("reader").with_scopes(["read"]));
+        store.insert(
+            "admin-key",
+            ApiKey::new("admin").with_scopes(["read", "admin"]),
+        );
+
+        Router::new()
+            .route(
+                "/admin",
+                get(|| async {}).layer(ApiKeyLayer::new(store.clone()).require_scopes(["admin"])),
+            )
+            .route(
+                "/whoami",
+                get(|key: ApiKey| async move { key.id().to_owned() }),
+            )
+            .layer(ApiKeyLayer::new(store).query("api_key"))
+    }
+
+    #[tokio::test]
+    async fn authenticates() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/whoami").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(res.headers()["www-authenticate"], "ApiKey");
+        assert_eq!(res.json::<Value>().await["error"], "missing_api_key");
+
+        let res = client.get("/whoami").header("x-api-key", "wrong").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(res.json::<Value>().await["error"], "invalid_api_key");
+
+        let res = client
+            .get("/whoami")
+            .header("x-api-key", "reader-key")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "reader");
+
+        let res = client.get("/whoami?api_key=admin-key").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "admin");
+    }
+
+    #[tokio::test]
+    async fn requires_scopes() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/admin").header("x-api-key", "reader-key").await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let body = res.json::<Value>().await;
+        assert_eq!(body["error"], "insufficient_scope");
+        assert_eq!(body["missing_scopes"], serde_json::json!(["admin"]));
+
+        let res = client.get("/admin").header("x-api-key", "admin-key").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn compares_keys() {
+        assert!(constant_time_eq(b"key", b"key"));
+        assert!(!constant_time_eq(b"key", b"kez"));
+        assert!(!constant_time_eq(b"key", b"keys"));
+    }
+}
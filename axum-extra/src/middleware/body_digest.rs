@@ -0,0 +1,642 @@
+use crate::body::{TeeBody, TeeSink};
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequest, FromRequestParts, Request},
+    response::{IntoResponse, Response},
+    RequestExt,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use bytes::{Bytes, BytesMut};
+use futures_util::future::BoxFuture;
+use http::{request::Parts, HeaderMap, HeaderName, StatusCode};
+use http_body_util::BodyExt;
+use md5::Md5;
+use sha2::{digest::Output, Digest, Sha256, Sha512};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that verifies request bodies against the digest sent with them.
+///
+/// The digest can be sent in these headers, with the MD5, SHA-256, and SHA-512 algorithms:
+///
+/// - `Content-MD5`, with the base64 MD5 digest of the body, as used by S3.
+/// - `Digest`, such as `Digest: sha-256=X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=`, from
+///   [RFC 3230].
+/// - `Content-Digest`, such as `Content-Digest: sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:`,
+///   from [RFC 9530].
+///
+/// The body is hashed as it's read and buffered, and requests whose body doesn't match every
+/// supported digest are rejected with `400 Bad Request`, without calling the handler. Requests
+/// with a digest header that only uses unsupported algorithms are rejected as well. The digests
+/// that were computed are available to handlers with the [`BodyDigest`] extractor.
+///
+/// Requests without a digest are passed through, unless digests are
+/// [required](Self::require).
+///
+/// The [default body limit](axum::extract::DefaultBodyLimit) applies while the body is buffered,
+/// and requests with larger bodies are rejected with `413 Payload Too Large`. Trailers of the
+/// body are dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::put, body::Bytes};
+/// use axum_extra::middleware::{BodyDigest, BodyDigestLayer, DigestAlgorithm};
+///
+/// async fn upload(digest: BodyDigest, body: Bytes) -> String {
+///     // the body matches the digest sent by the client, if it sent one
+///     let sha256 = digest.get(DigestAlgorithm::Sha256).unwrap();
+///     format!("stored {} bytes", body.len())
+/// }
+///
+/// let app = Router::new()
+///     .route("/objects/:key", put(upload))
+///     // always compute the SHA-256 digest, even if the client sent a different one
+///     .layer(BodyDigestLayer::new().compute(DigestAlgorithm::Sha256));
+/// # let _: Router = app;
+/// ```
+///
+/// [RFC 3230]: https://www.rfc-editor.org/rfc/rfc3230
+/// [RFC 9530]: https://www.rfc-editor.org/rfc/rfc9530
+#[derive(Debug, Clone, Default)]
+pub struct BodyDigestLayer {
+    config: Arc<Config>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Config {
+    required: bool,
+    compute: Vec<DigestAlgorithm>,
+}
+
+impl BodyDigestLayer {
+    /// Create a new `BodyDigestLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject requests without a digest with `400 Bad Request`.
+    pub fn require(mut self) -> Self {
+        Arc::make_mut(&mut self.config).required = true;
+        self
+    }
+
+    /// Compute the digest of every request body with `algorithm`, even if the request doesn't
+    /// have a digest with it.
+    ///
+    /// Can be called multiple times to compute several digests.
+    pub fn compute(mut self, algorithm: DigestAlgorithm) -> Self {
+        let config = Arc::make_mut(&mut self.config);
+        if !config.compute.contains(&algorithm) {
+            config.compute.push(algorithm);
+        }
+        self
+    }
+}
+
+impl<S> Layer<S> for BodyDigestLayer {
+    type Service = BodyDigestVerify<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyDigestVerify {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Middleware that verifies request bodies against the digest sent with them.
+///
+/// Created with [`BodyDigestLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct BodyDigestVerify<S> {
+    inner: S,
+    config: Arc<Config>,
+}
+
+impl<S> Service<Request> for BodyDigestVerify<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let expected = match expected_digests(req.headers()) {
+            Ok(expected) => expected,
+            Err(rejection) => return Box::pin(async move { Ok(rejection.into_response()) }),
+        };
+        if expected.is_none() && self.config.required {
+            let res = BodyDigestRejection::MissingDigest.into_response();
+            return Box::pin(async move { Ok(res) });
+        }
+
+        let mut algorithms = self.config.compute.clone();
+        for (algorithm, _) in expected.iter().flatten() {
+            if !algorithms.contains(algorithm) {
+                algorithms.push(*algorithm);
+            }
+        }
+        if algorithms.is_empty() {
+            req.extensions_mut().insert(BodyDigest::default());
+            return Box::pin(inner.call(req));
+        }
+
+        Box::pin(async move {
+            let (mut parts, mut body) = req.with_limited_body().into_parts();
+
+            let mut hasher = Hasher::new(&algorithms);
+            let mut buf = BytesMut::new();
+            while let Some(frame) = body.frame().await {
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        return Ok(BodyDigestRejection::FailedToReadBody(err).into_response());
+                    }
+                };
+                let Ok(data) = frame.into_data() else {
+                    continue;
+                };
+                hasher.update(&data);
+                buf.extend_from_slice(&data);
+            }
+
+            let digest = BodyDigest {
+                digests: hasher.finalize(),
+                verified: expected.is_some(),
+            };
+            for (algorithm, value) in expected.iter().flatten() {
+                if digest.get(*algorithm) != Some(value.as_slice()) {
+                    return Ok(BodyDigestRejection::DigestMismatch.into_response());
+                }
+            }
+
+            parts.extensions.insert(digest);
+            inner
+                .call(Request::from_parts(parts, Body::from(buf.freeze())))
+                .await
+        })
+    }
+}
+
+type Digests = Vec<(DigestAlgorithm, Vec<u8>)>;
+
+/// The digests sent with the request, or `None` if there are no digest headers.
+fn expected_digests(headers: &HeaderMap) -> Result<Option<Digests>, BodyDigestRejection> {
+    let mut found = false;
+    let mut expected = Vec::new();
+
+    for value in headers.get_all(HeaderName::from_static("content-md5")) {
+        found = true;
+        let value = value
+            .to_str()
+            .map_err(|_| BodyDigestRejection::InvalidDigest)?;
+        expected.push((DigestAlgorithm::Md5, decode(value)?));
+    }
+
+    for (name, colons) in [("digest", false), ("content-digest", true)] {
+        for value in headers.get_all(HeaderName::from_static(name)) {
+            found = true;
+            let value = value
+                .to_str()
+                .map_err(|_| BodyDigestRejection::InvalidDigest)?;
+            for item in value.split(',') {
+                let (algorithm, value) = item
+                    .split_once('=')
+                    .ok_or(BodyDigestRejection::InvalidDigest)?;
+                let Some(algorithm) = DigestAlgorithm::from_name(algorithm.trim()) else {
+                    continue;
+                };
+                let mut value = value.trim();
+                if colons {
+                    value = value
+                        .strip_prefix(':')
+                        .and_then(|value| value.strip_suffix(':'))
+                        .ok_or(BodyDigestRejection::InvalidDigest)?;
+                }
+                expected.push((algorithm, decode(value)?));
+            }
+        }
+    }
+
+    match (found, expected.is_empty()) {
+        (false, _) => Ok(None),
+        (true, true) => Err(BodyDigestRejection::UnsupportedAlgorithm),
+        (true, false) => Ok(Some(expected)),
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, BodyDigestRejection> {
+    STANDARD
+        .decode(value.trim())
+        .map_err(|_| BodyDigestRejection::InvalidDigest)
+}
+
+struct Hasher {
+    md5: Option<Md5>,
+    sha256: Option<Sha256>,
+    sha512: Option<Sha512>,
+}
+
+impl Hasher {
+    fn new(algorithms: &[DigestAlgorithm]) -> Self {
+        let uses = |algorithm| algorithms.contains(&algorithm);
+        Self {
+            md5: uses(DigestAlgorithm::Md5).then(Md5::new),
+            sha256: uses(DigestAlgorithm::Sha256).then(Sha256::new),
+            sha512: uses(DigestAlgorithm::Sha512).then(Sha512::new),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        if let Some(md5) = &mut self.md5 {
+            md5.update(data);
+        }
+        if let Some(sha256) = &mut self.sha256 {
+            sha256.update(data);
+        }
+        if let Some(sha512) = &mut self.sha512 {
+            sha512.update(data);
+        }
+    }
+
+    fn finalize(self) -> Digests {
+        let mut digests = Vec::new();
+        if let Some(md5) = self.md5 {
+            digests.push((DigestAlgorithm::Md5, md5.finalize().to_vec()));
+        }
+        if let Some(sha256) = self.sha256 {
+            digests.push((DigestAlgorithm::Sha256, sha256.finalize().to_vec()));
+        }
+        if let Some(sha512) = self.sha512 {
+            digests.push((DigestAlgorithm::Sha512, sha512.finalize().to_vec()));
+        }
+        digests
+    }
+}
+
+/// An algorithm supported by [`BodyDigestLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DigestAlgorithm {
+    /// MD5, from `Content-MD5` or the `md5` digest.
+    Md5,
+    /// SHA-256, from the `sha-256` digest.
+    Sha256,
+    /// SHA-512, from the `sha-512` digest.
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("md5") {
+            Some(Self::Md5)
+        } else if name.eq_ignore_ascii_case("sha-256") {
+            Some(Self::Sha256)
+        } else if name.eq_ignore_ascii_case("sha-512") {
+            Some(Self::Sha512)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extractor for the digests of the request body computed by [`BodyDigestLayer`].
+///
+/// See [`BodyDigestLayer`] for an example.
+#[derive(Debug, Clone, Default)]
+pub struct BodyDigest {
+    digests: Digests,
+    verified: bool,
+}
+
+impl BodyDigest {
+    /// The digest of the body computed with `algorithm`, if any.
+    ///
+    /// Digests are computed for the algorithms of the digests sent with the request, and the
+    /// algorithms set with [`BodyDigestLayer::compute`].
+    pub fn get(&self, algorithm: DigestAlgorithm) -> Option<&[u8]> {
+        self.digests
+            .iter()
+            .find(|(existing, _)| *existing == algorithm)
+            .map(|(_, digest)| digest.as_slice())
+    }
+
+    /// Whether the request had a digest, which the body was verified against.
+    pub fn is_verified(&self) -> bool {
+        self.verified
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for BodyDigest
+where
+    S: Send + Sync,
+{
+    type Rejection = BodyDigestRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or(BodyDigestRejection::MissingBodyDigestLayer)
+    }
+}
+
+/// Extractor that computes the digest of the request body while another extractor reads it.
+///
+/// Each chunk of the body is hashed as `E` reads it, so extractors that buffer the body, such
+/// as `Json`, `Form`, or `Bytes`, get their digest in the same pass, without a second copy of
+/// the body. The [default body limit](axum::extract::DefaultBodyLimit) is enforced by `E` while
+/// it reads the body, as usual, and rejections from `E` are returned unchanged.
+///
+/// `D` is the hash function, SHA-256 by default. Any [`Digest`] implementation can be used, such
+/// as `sha2::Sha512` or `md5::Md5`. If `E` doesn't read the whole body, the digest only covers
+/// the part it read.
+///
+/// Unlike [`BodyDigestLayer`], this doesn't verify the body against a digest sent by the client.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Json, Router, routing::post};
+/// use axum_extra::middleware::WithDigest;
+/// use serde_json::Value;
+///
+/// async fn create(WithDigest(Json(payload), sha256): WithDigest<Json<Value>>) -> String {
+///     format!("stored {payload} with digest {sha256:x}")
+/// }
+///
+/// let app = Router::new().route("/documents", post(create));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct WithDigest<E, D = Sha256>(pub E, pub Output<D>)
+where
+    D: Digest;
+
+#[async_trait]
+impl<E, D, S> FromRequest<S> for WithDigest<E, D>
+where
+    E: FromRequest<S>,
+    D: Digest + Send + 'static,
+    S: Send + Sync,
+{
+    type Rejection = E::Rejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let hasher = Arc::new(Mutex::new(D::new()));
+        let sink = DigestSink(Arc::clone(&hasher));
+        let req = req.map(|body| Body::new(TeeBody::new(body, sink)));
+
+        let value = E::from_request(req, state).await?;
+
+        // `E` might hold on to the body, so the hasher can't be unwrapped from the `Arc`
+        let hasher = std::mem::replace(&mut *hasher.lock().unwrap(), D::new());
+        Ok(Self(value, hasher.finalize()))
+    }
+}
+
+struct DigestSink<D>(Arc<Mutex<D>>);
+
+impl<D> TeeSink for DigestSink<D>
+where
+    D: Digest + Send + 'static,
+{
+    fn write(&mut self, chunk: Bytes) {
+        self.0.lock().unwrap().update(&chunk);
+    }
+}
+
+/// Rejection used by [`BodyDigestLayer`] and [`BodyDigest`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BodyDigestRejection {
+    /// The request didn't have a digest, but digests are [required](BodyDigestLayer::require).
+    MissingDigest,
+    /// A digest header couldn't be parsed.
+    InvalidDigest,
+    /// The digest headers only used unsupported algorithms.
+    UnsupportedAlgorithm,
+    /// The body didn't match its digest.
+    DigestMismatch,
+    /// Reading the request body failed, or it exceeded the
+    /// [body limit](axum::extract::DefaultBodyLimit).
+    FailedToReadBody(axum::Error),
+    /// [`BodyDigestLayer`] wasn't applied to the route.
+    MissingBodyDigestLayer,
+}
+
+impl BodyDigestRejection {
+    /// Get the status code used for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::FailedToReadBody(err) if is_length_limit_error(err) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::MissingBodyDigestLayer => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+fn is_length_limit_error(err: &axum::Error) -> bool {
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        if err.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+impl IntoResponse for BodyDigestRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = self.status();
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for BodyDigestRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingDigest => f.write_str("Request is missing a body digest"),
+            Self::InvalidDigest => f.write_str("Invalid body digest"),
+            Self::UnsupportedAlgorithm => f.write_str("Unsupported body digest algorithm"),
+            Self::DigestMismatch => f.write_str("Request body doesn't match its digest"),
+            Self::FailedToReadBody(err) => write!(f, "Failed to read request body: {err}"),
+            Self::MissingBodyDigestLayer => {
+                f.write_str("Missing `BodyDigestLayer`. Add it to extract `BodyDigest`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BodyDigestRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::FailedToReadBody(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{body::Bytes, extract::DefaultBodyLimit, routing::put, Json, Router};
+    use serde_json::Value;
+
+    // digests of "hello"
+    const MD5: &str = "XUFAKrxLKna5cZ2REBfFkg==";
+    const SHA256: &str = "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=";
+
+    fn app(layer: BodyDigestLayer) -> Router {
+        Router::new()
+            .route(
+                "/",
+                put(|digest: BodyDigest, body: Bytes| async move {
+                    let sha256 = digest
+                        .get(DigestAlgorithm::Sha256)
+                        .map(|d| STANDARD.encode(d));
+                    format!("{} {}", body.len(), sha256.unwrap_or_default())
+                }),
+            )
+            .layer(layer)
+    }
+
+    #[tokio::test]
+    async fn verifies_digests() {
+        let client = TestClient::new(app(BodyDigestLayer::new()));
+
+        let res = client
+            .put("/")
+            .header("content-md5", MD5)
+            .body("hello")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "5 ");
+
+        let res = client
+            .put("/")
+            .header("digest", format!("SHA-256={SHA256}, unknown=abc"))
+            .body("hello")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, format!("5 {SHA256}"));
+
+        let res = client
+            .put("/")
+            .header("content-digest", format!("sha-256=:{SHA256}:"))
+            .body("hello")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client
+            .put("/")
+            .header("content-md5", MD5)
+            .body("hellO")
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "Request body doesn't match its digest");
+
+        let res = client
+            .put("/")
+            .header("digest", "sha-1=abc")
+            .body("hello")
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "Unsupported body digest algorithm");
+
+        let res = client
+            .put("/")
+            .header("content-md5", "not base64!")
+            .body("hello")
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "Invalid body digest");
+
+        let res = client.put("/").body("hello").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "5 ");
+    }
+
+    #[tokio::test]
+    async fn computes_and_requires_digests() {
+        let layer = BodyDigestLayer::new()
+            .require()
+            .compute(DigestAlgorithm::Sha256);
+        let client = TestClient::new(app(layer));
+
+        let res = client.put("/").body("hello").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "Request is missing a body digest");
+
+        let res = client
+            .put("/")
+            .header("content-md5", MD5)
+            .body("hello")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, format!("5 {SHA256}"));
+    }
+
+    #[tokio::test]
+    async fn with_digest() {
+        let app = Router::new()
+            .route(
+                "/",
+                put(|WithDigest(body, sha256): WithDigest<Bytes>| async move {
+                    format!("{} {}", body.len(), STANDARD.encode(sha256))
+                }),
+            )
+            .route(
+                "/json",
+                put(
+                    |WithDigest(Json(value), md5): WithDigest<Json<Value>, Md5>| async move {
+                        format!("{value} {}", STANDARD.encode(md5))
+                    },
+                ),
+            )
+            .layer(DefaultBodyLimit::max(8));
+        let client = TestClient::new(app);
+
+        let res = client.put("/").body("hello").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, format!("5 {SHA256}"));
+
+        let res = client
+            .put("/json")
+            .header("content-type", "application/json")
+            .body("\"hello\"")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let md5 = STANDARD.encode(Md5::digest(b"\"hello\""));
+        assert_eq!(res.text().await, format!("\"hello\" {md5}"));
+
+        let res = client.put("/").body("hello world").await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::body::{TeeBody, TeeSink, TeeSummary};
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    response::Response,
+};
+use http::{header, HeaderMap, Method, StatusCode, Uri};
+use mime::Mime;
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::Span;
+
+/// Layer that logs the bodies of requests and responses with [`tracing`].
+///
+/// Bodies are logged as they are sent, without buffering them first, so streaming bodies keep
+/// streaming. Up to [`max_size`](Self::max_size) bytes of each body are logged, as text, once
+/// the body has been sent completely, has failed, or has been dropped. Events are emitted at the
+/// `DEBUG` level in the span of the request, with these fields:
+///
+/// - `body`: the start of the body, after [redaction](Self::redact).
+/// - `size`: the number of bytes sent.
+/// - `truncated`: whether `body` is only the start of the body.
+/// - `completed`: whether the whole body was sent.
+/// - `method` and `uri` for requests, and `status` for responses.
+///
+/// Only bodies whose `Content-Type` passes the [filter](Self::content_type_filter) are logged,
+/// by default textual types such as `text/*`, JSON, XML, and forms.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::post};
+/// use axum_extra::middleware::BodyLoggingLayer;
+///
+/// // mask API keys in logged bodies
+/// let layer = BodyLoggingLayer::new()
+///     .max_size(16 * 1024)
+///     .redact(|body: &str| body.replace("sk_live_", "sk_live_[redacted]"));
+///
+/// let app = Router::new()
+///     .route("/", post(|body: String| async move { body }))
+///     .layer(layer);
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BodyLoggingLayer<R = NoRedaction> {
+    redact: R,
+    max_size: u64,
+    content_type_filter: fn(&Mime) -> bool,
+}
+
+impl BodyLoggingLayer {
+    /// Create a new `BodyLoggingLayer` that logs up to 4 KiB of each body.
+    pub fn new() -> Self {
+        Self {
+            redact: NoRedaction::default(),
+            max_size: 4 * 1024,
+            content_type_filter: is_textual,
+        }
+    }
+}
+
+impl Default for BodyLoggingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> BodyLoggingLayer<R> {
+    /// Set the maximum number of bytes of each body that are logged.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Set which bodies are logged, by their `Content-Type`.
+    ///
+    /// Bodies without a valid `Content-Type` are never logged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_extra::middleware::BodyLoggingLayer;
+    ///
+    /// // only log JSON
+    /// let layer = BodyLoggingLayer::new().content_type_filter(|content_type| {
+    ///     content_type.subtype() == mime::JSON || content_type.suffix() == Some(mime::JSON)
+    /// });
+    /// ```
+    pub fn content_type_filter(mut self, content_type_filter: fn(&Mime) -> bool) -> Self {
+        self.content_type_filter = content_type_filter;
+        self
+    }
+
+    /// Set how secrets and personal data are removed from bodies before they are logged.
+    ///
+    /// The redactor gets the logged part of a body, which might be cut off in the middle of a
+    /// value if the body is larger than the [`max_size`](Self::max_size).
+    pub fn redact<T>(self, redact: T) -> BodyLoggingLayer<T>
+    where
+        T: Redact,
+    {
+        BodyLoggingLayer {
+            redact,
+            max_size: self.max_size,
+            content_type_filter: self.content_type_filter,
+        }
+    }
+}
+
+impl<S, R> Layer<S> for BodyLoggingLayer<R>
+where
+    R: Clone,
+{
+    type Service = BodyLogging<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyLogging {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Middleware that logs the bodies of requests and responses with [`tracing`].
+///
+/// Created with [`BodyLoggingLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct BodyLogging<S, R = NoRedaction> {
+    inner: S,
+    layer: BodyLoggingLayer<R>,
+}
+
+impl<S, R> Service<Request> for BodyLogging<S, R>
+where
+    S: Service<Request, Response = Response>,
+    R: Redact,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BodyLoggingFuture<S::Future, R>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let span = Span::current();
+
+        let req = if logged(req.headers(), self.layer.content_type_filter) {
+            let sink = LogSink {
+                data: Vec::new(),
+                redact: self.layer.redact.clone(),
+                kind: Kind::Request {
+                    method: req.method().clone(),
+                    uri: req.uri().clone(),
+                },
+                span: span.clone(),
+            };
+            let max_size = self.layer.max_size;
+            req.map(|body| Body::new(TeeBody::new(body, sink).limit(max_size)))
+        } else {
+            req
+        };
+
+        BodyLoggingFuture {
+            inner: self.inner.call(req),
+            layer: Some(self.layer.clone()),
+            span,
+        }
+    }
+}
+
+fn logged(headers: &HeaderMap, content_type_filter: fn(&Mime) -> bool) -> bool {
+    let Some(mime) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Mime>().ok())
+    else {
+        return false;
+    };
+    content_type_filter(&mime)
+}
+
+fn is_textual(mime: &Mime) -> bool {
+    if mime.type_() == mime::TEXT {
+        return true;
+    }
+    let textual = [
+        mime::JSON,
+        mime::XML,
+        mime::WWW_FORM_URLENCODED,
+        mime::JAVASCRIPT,
+    ];
+    mime.type_() == mime::APPLICATION
+        && (textual.contains(&mime.subtype())
+            || mime.suffix() == Some(mime::JSON)
+            || mime.suffix() == Some(mime::XML))
+}
+
+pin_project! {
+    /// Response future for [`BodyLogging`].
+    pub struct BodyLoggingFuture<F, R> {
+        #[pin]
+        inner: F,
+        layer: Option<BodyLoggingLayer<R>>,
+        span: Span,
+    }
+}
+
+impl<F, R, E> Future for BodyLoggingFuture<F, R>
+where
+    F: Future<Output = Result<Response, E>>,
+    R: Redact,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.inner.poll(cx))?;
+
+        let layer = this.layer.take().expect("future polled after completion");
+        if !logged(res.headers(), layer.content_type_filter) {
+            return Poll::Ready(Ok(res));
+        }
+
+        let sink = LogSink {
+            data: Vec::new(),
+            redact: layer.redact,
+            kind: Kind::Response {
+                status: res.status(),
+            },
+            span: this.span.clone(),
+        };
+        let res = res.map(|body| Body::new(TeeBody::new(body, sink).limit(layer.max_size)));
+        Poll::Ready(Ok(res))
+    }
+}
+
+impl<F, R> fmt::Debug for BodyLoggingFuture<F, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyLoggingFuture").finish_non_exhaustive()
+    }
+}
+
+enum Kind {
+    Request { method: Method, uri: Uri },
+    Response { status: StatusCode },
+}
+
+struct LogSink<R> {
+    data: Vec<u8>,
+    redact: R,
+    kind: Kind,
+    span: Span,
+}
+
+impl<R> TeeSink for LogSink<R>
+where
+    R: Redact,
+{
+    fn write(&mut self, chunk: Bytes) {
+        self.data.extend_from_slice(&chunk);
+    }
+
+    fn finish(&mut self, summary: &TeeSummary) {
+        let body = self.redact.redact(&String::from_utf8_lossy(&self.data));
+        let size = summary.bytes_sent();
+        let truncated = summary.truncated();
+        let completed = summary.completed();
+
+        let _guard = self.span.enter();
+        match &self.kind {
+            Kind::Request { method, uri } => tracing::debug!(
+                %method,
+                %uri,
+                body = %body,
+                size,
+                truncated,
+                completed,
+                "request body",
+            ),
+            Kind::Response { status } => tracing::debug!(
+                status = status.as_u16(),
+                body = %body,
+                size,
+                truncated,
+                completed,
+                "response body",
+            ),
+        }
+    }
+}
+
+/// Removes secrets and personal data from bodies logged by [`BodyLoggingLayer`].
+///
+/// This is implemented for closures taking the logged part of a body and returning the text to
+/// log instead.
+pub trait Redact: Clone + Send + Sync + 'static {
+    /// Return `body` with secrets and personal data removed.
+    fn redact(&self, body: &str) -> String;
+}
+
+impl<F> Redact for F
+where
+    F: Fn(&str) -> String + Clone + Send + Sync + 'static,
+{
+    fn redact(&self, body: &str) -> String {
+        self(body)
+    }
+}
+
+/// [`Redact`] that logs bodies as they are.
+///
+/// This is the default [`Redact`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRedaction {
+    _priv: (),
+}
+
+impl Redact for NoRedaction {
+    fn redact(&self, body: &str) -> String {
+        body.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn logs_redacted_bodies() {
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let layer = BodyLoggingLayer::new().max_size(64).redact({
+            let logged = logged.clone();
+            move |body: &str| {
+                let body = body.replace("secret", "******");
+                logged.lock().unwrap().push(body.clone());
+                body
+            }
+        });
+        let app = Router::new()
+            .route("/", post(|body: String| async move { body }))
+            .route("/binary", post(|| async { vec![0_u8; 4] }))
+            .layer(layer);
+        let client = TestClient::new(app);
+
+        let res = client
+            .post("/")
+            .header("content-type", "application/json")
+            .body("{\"token\":\"secret\"}")
+            .await;
+        assert_eq!(res.text().await, "{\"token\":\"secret\"}");
+        // the response body is logged once the server drops it
+        for _ in 0..100 {
+            if logged.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert_eq!(
+            *logged.lock().unwrap(),
+            ["{\"token\":\"******\"}", "{\"token\":\"******\"}"]
+        );
+
+        logged.lock().unwrap().clear();
+        let res = client.post("/binary").await;
+        assert_eq!(res.bytes().await.len(), 4);
+        assert!(logged.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn textual() {
+        for mime in ["text/plain", "application/json", "application/problem+json"] {
+            assert!(is_textual(&mime.parse().unwrap()), "{mime}");
+        }
+        for mime in ["image/png", "application/octet-stream"] {
+            assert!(!is_textual(&mime.parse().unwrap()), "{mime}");
+        }
+    }
+}
@@ -0,0 +1,723 @@
+use axum::{
+    async_trait,
+    body::{Body, Bytes, HttpBody},
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use http_body_util::BodyExt;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that caches responses to `GET` requests.
+///
+/// Responses are stored in a [`CacheStore`] and served from it, without calling the handler,
+/// until they expire. Like a shared HTTP cache, this honors the headers of the response:
+///
+/// - Responses are kept for the `s-maxage` or `max-age` of their `Cache-Control` header, minus
+///   their `Age`. Responses without either are only stored if a
+///   [`default_ttl`](Self::default_ttl) is set.
+/// - Responses with `no-store`, `no-cache`, or `private`, with a `Set-Cookie` header, or with
+///   `Vary: *` aren't stored.
+/// - Responses are stored separately for each value of the request headers listed in their
+///   `Vary` header.
+/// - Responses to requests with an `Authorization` header are only stored if they have `public`
+///   or `s-maxage`.
+/// - Only responses with a status that is cacheable by default, such as `200 OK` and
+///   `404 Not Found`, and whose body is known to fit in the
+///   [`max_body_size`](Self::max_body_size), are stored.
+///
+/// Requests with `Cache-Control: no-cache` skip the cache, but their response is stored, and
+/// requests with `Cache-Control: no-store` bypass the cache completely. Responses served from the
+/// cache have an `Age` header.
+///
+/// Responses are stored under the path and query of the request, which can be used to remove
+/// them when the resource changes, with [`CacheStore::invalidate`] and
+/// [`CacheStore::invalidate_prefix`].
+///
+/// If the store fails, the error is logged and the handler is called as if the cache was empty.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     extract::{Path, State},
+///     response::IntoResponse,
+///     routing::get,
+///     Router,
+/// };
+/// use axum_extra::middleware::{CacheLayer, CacheStore, MemoryCacheStore};
+/// use http::header::CACHE_CONTROL;
+///
+/// async fn get_user(Path(id): Path<u32>) -> impl IntoResponse {
+///     ([(CACHE_CONTROL, "public, max-age=60")], format!("user {id}"))
+/// }
+///
+/// async fn update_user(State(cache): State<MemoryCacheStore>, Path(id): Path<u32>) {
+///     // update the user, then remove the stale response
+///     cache.invalidate(&format!("/users/{id}")).await.unwrap();
+/// }
+///
+/// let cache = MemoryCacheStore::new(10_000);
+///
+/// let app = Router::new()
+///     .route("/users/:id", get(get_user).put(update_user))
+///     .layer(CacheLayer::new(cache.clone()))
+///     .with_state(cache);
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheLayer<T = MemoryCacheStore> {
+    store: T,
+    config: Config,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    default_ttl: Option<Duration>,
+    max_body_size: usize,
+}
+
+impl<T> CacheLayer<T>
+where
+    T: CacheStore,
+{
+    /// Create a new `CacheLayer` that stores responses in `store`.
+    pub fn new(store: T) -> Self {
+        Self {
+            store,
+            config: Config {
+                default_ttl: None,
+                max_body_size: 1024 * 1024,
+            },
+        }
+    }
+
+    /// Set how long responses whose `Cache-Control` header doesn't have a `max-age` or
+    /// `s-maxage` are kept for.
+    ///
+    /// By default, these responses aren't stored.
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.config.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Set the maximum size of the bodies of the responses that are stored.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl<S, T> Layer<S> for CacheLayer<T>
+where
+    T: Clone,
+{
+    type Service = Cache<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Cache {
+            inner,
+            store: self.store.clone(),
+            config: self.config,
+        }
+    }
+}
+
+/// Middleware that caches responses to `GET` requests.
+///
+/// Created with [`CacheLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct Cache<S, T = MemoryCacheStore> {
+    inner: S,
+    store: T,
+    config: Config,
+}
+
+impl<S, T> Service<Request> for Cache<S, T>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    T: CacheStore,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let request_directives = CacheControl::parse(req.headers());
+        if req.method() != Method::GET || request_directives.no_store {
+            return Box::pin(inner.call(req));
+        }
+
+        let key = req
+            .uri()
+            .path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or("/")
+            .to_owned();
+        let store = self.store.clone();
+        let config = self.config;
+
+        Box::pin(async move {
+            if !request_directives.no_cache {
+                match store.get(&key).await {
+                    Ok(responses) => {
+                        let now = SystemTime::now();
+                        if let Some(cached) = responses
+                            .into_iter()
+                            .find(|cached| cached.expires_at > now && cached.matches(&req))
+                        {
+                            return Ok(cached.into_response());
+                        }
+                    }
+                    Err(_err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(error = %_err, "failed to read response from cache");
+                    }
+                }
+            }
+
+            let authorized = req.headers().contains_key(header::AUTHORIZATION);
+            let request_headers = req.headers().clone();
+
+            let res = inner.call(req).await?;
+            let Some(ttl) = freshness(&res, authorized, config.default_ttl) else {
+                return Ok(res);
+            };
+            let Some(vary) = vary(res.headers(), &request_headers) else {
+                return Ok(res);
+            };
+            let fits = matches!(
+                res.body().size_hint().upper(),
+                Some(len) if len <= config.max_body_size as u64
+            );
+            if !fits {
+                return Ok(res);
+            }
+
+            let (parts, body) = res.into_parts();
+            let body = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to buffer response body");
+
+                    return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                }
+            };
+
+            let now = SystemTime::now();
+            let cached = CachedResponse::new(
+                parts.status,
+                parts.headers.clone(),
+                body.clone(),
+                vary,
+                now,
+                now + ttl,
+            );
+            if let Err(_err) = store.put(&key, cached).await {
+                #[cfg(feature = "tracing")]
+                tracing::error!(error = %_err, "failed to store response in cache");
+            }
+
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+/// How long the response can be stored for, if it can be stored at all.
+fn freshness(res: &Response, authorized: bool, default_ttl: Option<Duration>) -> Option<Duration> {
+    let cacheable_status = matches!(
+        res.status().as_u16(),
+        200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    );
+    if !cacheable_status || res.headers().contains_key(header::SET_COOKIE) {
+        return None;
+    }
+
+    let directives = CacheControl::parse(res.headers());
+    if directives.no_store || directives.no_cache || directives.private {
+        return None;
+    }
+    if authorized && !directives.public && directives.s_maxage.is_none() {
+        return None;
+    }
+
+    let ttl = directives
+        .s_maxage
+        .or(directives.max_age)
+        .map(Duration::from_secs)
+        .or(default_ttl)?;
+    let age = res
+        .headers()
+        .get(header::AGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default();
+    ttl.checked_sub(age).filter(|ttl| !ttl.is_zero())
+}
+
+/// The values of the request headers the response varies on, or `None` if it varies on
+/// everything.
+fn vary(
+    response_headers: &HeaderMap,
+    request_headers: &HeaderMap,
+) -> Option<Vec<(HeaderName, Vec<HeaderValue>)>> {
+    let mut vary = Vec::new();
+    for value in response_headers.get_all(header::VARY) {
+        for name in value.to_str().ok()?.split(',') {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            if name == "*" {
+                return None;
+            }
+            let name = HeaderName::from_bytes(name.as_bytes()).ok()?;
+            let values = request_headers.get_all(&name).iter().cloned().collect();
+            vary.push((name, values));
+        }
+    }
+    Some(vary)
+}
+
+#[derive(Debug, Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    public: bool,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = Self::default();
+        let values = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+        for directive in values.flat_map(|value| value.split(',')) {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            let seconds = || value.and_then(|value| value.parse().ok());
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "private" => directives.private = true,
+                "public" => directives.public = true,
+                "max-age" => directives.max_age = seconds(),
+                "s-maxage" => directives.s_maxage = seconds(),
+                _ => {}
+            }
+        }
+        directives
+    }
+}
+
+/// A response kept in a [`CacheStore`].
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    vary: Vec<(HeaderName, Vec<HeaderValue>)>,
+    stored_at: SystemTime,
+    expires_at: SystemTime,
+}
+
+impl CachedResponse {
+    /// Create a new `CachedResponse`.
+    ///
+    /// `vary` has the values of the request headers listed in the `Vary` header of the response.
+    pub fn new(
+        status: StatusCode,
+        headers: HeaderMap,
+        body: Bytes,
+        vary: Vec<(HeaderName, Vec<HeaderValue>)>,
+        stored_at: SystemTime,
+        expires_at: SystemTime,
+    ) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+            vary,
+            stored_at,
+            expires_at,
+        }
+    }
+
+    /// The status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body of the response.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The values of the request headers listed in the `Vary` header of the response.
+    pub fn vary(&self) -> &[(HeaderName, Vec<HeaderValue>)] {
+        &self.vary
+    }
+
+    /// When the response was stored.
+    pub fn stored_at(&self) -> SystemTime {
+        self.stored_at
+    }
+
+    /// When the response expires.
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// Whether `self` and `other` are responses to requests with the same values for the headers
+    /// they vary on, so one replaces the other.
+    pub fn same_variant(&self, other: &Self) -> bool {
+        self.vary == other.vary
+    }
+
+    fn matches(&self, req: &Request) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, values)| req.headers().get_all(name).iter().eq(values.iter()))
+    }
+}
+
+impl IntoResponse for CachedResponse {
+    fn into_response(self) -> Response {
+        let age = SystemTime::now()
+            .duration_since(self.stored_at)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut res = Response::new(Body::from(self.body));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers;
+        res.headers_mut()
+            .insert(header::AGE, HeaderValue::from(age));
+        res
+    }
+}
+
+/// Stores the responses of a [`CacheLayer`].
+///
+/// Responses are stored under the path and query of their request. Since a response can vary
+/// on request headers, there can be several responses for the same key, which are told apart
+/// with [`CachedResponse::same_variant`].
+///
+/// Expired responses are never served, but stores should still remove them, such as with the
+/// expiry time of a Redis key.
+#[async_trait]
+pub trait CacheStore: Clone + Send + Sync + 'static {
+    /// The error returned if the store fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Get the responses stored for `key`.
+    async fn get(&self, key: &str) -> Result<Vec<CachedResponse>, Self::Error>;
+
+    /// Store `response` for `key`, replacing the stored response for the same variant.
+    async fn put(&self, key: &str, response: CachedResponse) -> Result<(), Self::Error>;
+
+    /// Remove the responses stored for `key`.
+    async fn invalidate(&self, key: &str) -> Result<(), Self::Error>;
+
+    /// Remove the responses stored for keys starting with `prefix`.
+    async fn invalidate_prefix(&self, prefix: &str) -> Result<(), Self::Error>;
+}
+
+/// [`CacheStore`] that keeps responses in memory.
+///
+/// Once responses for more than `capacity` keys are stored, the least recently used key is
+/// removed. The store can be cloned to share it between layers and with handlers that
+/// invalidate responses.
+#[derive(Debug, Clone)]
+pub struct MemoryCacheStore {
+    inner: Arc<Mutex<Lru>>,
+}
+
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    entries: HashMap<String, LruEntry>,
+    /// Keys by the tick they were last used at, so the least recently used one is first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct LruEntry {
+    responses: Vec<CachedResponse>,
+    last_used: u64,
+}
+
+impl Lru {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = self.tick;
+            self.recency.insert(self.tick, key.to_owned());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+}
+
+impl MemoryCacheStore {
+    /// Create a new, empty `MemoryCacheStore` that stores responses for up to `capacity` keys.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Lru {
+                capacity,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            })),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryCacheStore {
+    type Error = Infallible;
+
+    async fn get(&self, key: &str) -> Result<Vec<CachedResponse>, Self::Error> {
+        let mut lru = self.inner.lock().unwrap();
+        let now = SystemTime::now();
+        let Some(entry) = lru.entries.get_mut(key) else {
+            return Ok(Vec::new());
+        };
+        entry.responses.retain(|response| response.expires_at > now);
+        if entry.responses.is_empty() {
+            lru.remove(key);
+            return Ok(Vec::new());
+        }
+        let responses = entry.responses.clone();
+        lru.touch(key);
+        Ok(responses)
+    }
+
+    async fn put(&self, key: &str, response: CachedResponse) -> Result<(), Self::Error> {
+        let mut lru = self.inner.lock().unwrap();
+        if lru.capacity == 0 {
+            return Ok(());
+        }
+
+        let entry = lru
+            .entries
+            .entry(key.to_owned())
+            .or_insert_with(|| LruEntry {
+                responses: Vec::new(),
+                last_used: 0,
+            });
+        entry
+            .responses
+            .retain(|existing| !existing.same_variant(&response));
+        entry.responses.push(response);
+        lru.touch(key);
+
+        while lru.entries.len() > lru.capacity {
+            let Some((_, oldest)) = lru.recency.pop_first() else {
+                break;
+            };
+            lru.entries.remove(&oldest);
+        }
+        Ok(())
+    }
+
+    async fn invalidate(&self, key: &str) -> Result<(), Self::Error> {
+        self.inner.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn invalidate_prefix(&self, prefix: &str) -> Result<(), Self::Error> {
+        let mut lru = self.inner.lock().unwrap();
+        let Lru {
+            entries, recency, ..
+        } = &mut *lru;
+        entries.retain(|key, entry| {
+            let keep = !key.starts_with(prefix);
+            if !keep {
+                recency.remove(&entry.last_used);
+            }
+            keep
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn app(store: MemoryCacheStore, calls: Arc<AtomicUsize>) -> Router {
+        let handler = |cache_control: &'static str| {
+            let calls = calls.clone();
+            move || {
+                let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    (
+                        [
+                            (header::CACHE_CONTROL, cache_control),
+                            (header::VARY, "accept-language"),
+                        ],
+                        call.to_string(),
+                    )
+                }
+            }
+        };
+        Router::new()
+            .route("/users/1", get(handler("public, max-age=60")))
+            .route("/users/2", get(handler("public, max-age=60")))
+            .route("/private", get(handler("private, max-age=60")))
+            .layer(CacheLayer::new(store))
+    }
+
+    #[tokio::test]
+    async fn caches_responses() {
+        let store = MemoryCacheStore::new(100);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = TestClient::new(app(store, calls.clone()));
+
+        let res = client.get("/users/1").await;
+        assert!(res.headers().get("age").is_none());
+        assert_eq!(res.text().await, "1");
+
+        let res = client.get("/users/1").await;
+        assert_eq!(res.headers()["age"], "0");
+        assert_eq!(res.headers()["cache-control"], "public, max-age=60");
+        assert_eq!(res.text().await, "1");
+
+        // a different variant
+        let res = client.get("/users/1").header("accept-language", "fr").await;
+        assert_eq!(res.text().await, "2");
+
+        // different query
+        let res = client.get("/users/1?page=2").await;
+        assert_eq!(res.text().await, "3");
+
+        let res = client
+            .get("/users/1")
+            .header("cache-control", "no-cache")
+            .await;
+        assert_eq!(res.text().await, "4");
+        let res = client.get("/users/1").await;
+        assert_eq!(res.text().await, "4");
+
+        let res = client.get("/private").await;
+        assert_eq!(res.text().await, "5");
+        let res = client.get("/private").await;
+        assert_eq!(res.text().await, "6");
+
+        let res = client
+            .get("/users/2")
+            .header("authorization", "Bearer token")
+            .await;
+        assert_eq!(res.text().await, "7");
+        let res = client.get("/users/2").await;
+        assert_eq!(res.text().await, "7");
+    }
+
+    #[tokio::test]
+    async fn invalidates_responses() {
+        let store = MemoryCacheStore::new(100);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = TestClient::new(app(store.clone(), calls.clone()));
+
+        client.get("/users/1").await;
+        client.get("/users/2").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        store.invalidate("/users/1").await.unwrap();
+        assert_eq!(client.get("/users/1").await.text().await, "3");
+        assert_eq!(client.get("/users/2").await.text().await, "2");
+
+        store.invalidate_prefix("/users/").await.unwrap();
+        assert_eq!(client.get("/users/1").await.text().await, "4");
+        assert_eq!(client.get("/users/2").await.text().await, "5");
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used() {
+        let store = MemoryCacheStore::new(2);
+        let response = || {
+            let now = SystemTime::now();
+            CachedResponse::new(
+                StatusCode::OK,
+                HeaderMap::new(),
+                Bytes::new(),
+                Vec::new(),
+                now,
+                now + Duration::from_secs(60),
+            )
+        };
+
+        store.put("/a", response()).await.unwrap();
+        store.put("/b", response()).await.unwrap();
+        store.get("/a").await.unwrap();
+        store.put("/c", response()).await.unwrap();
+
+        assert_eq!(store.get("/a").await.unwrap().len(), 1);
+        assert!(store.get("/b").await.unwrap().is_empty());
+        assert_eq!(store.get("/c").await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn parses_cache_control() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("Public, max-age=\"30\""),
+        );
+        headers.append(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("s-maxage=10"),
+        );
+
+        let directives = CacheControl::parse(&headers);
+        assert!(directives.public);
+        assert!(!directives.private);
+        assert_eq!(directives.max_age, Some(30));
+        assert_eq!(directives.s_maxage, Some(10));
+    }
+}
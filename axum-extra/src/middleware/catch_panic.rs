@@ -0,0 +1,356 @@
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use http::StatusCode;
+use pin_project_lite::pin_project;
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::RefCell,
+    fmt,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::Once,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that catches panics in handlers and extractors and converts them into responses.
+///
+/// Without this layer, a panic drops the connection without sending a response. With it, the
+/// panic is passed to the [`on_panic`](Self::on_panic) hook, together with the backtrace of where
+/// it happened, and the response returned by the hook is sent instead. By default, the panic is
+/// logged with [`tracing`] (if the `tracing` feature is enabled) and the response is an empty
+/// `500 Internal Server Error`.
+///
+/// Panics while the response body is being sent are not caught.
+///
+/// Backtraces are only captured after calling [`install_backtrace_hook`], which installs a
+/// process-wide [panic hook](std::panic::set_hook).
+///
+/// [`install_backtrace_hook`]: Self::install_backtrace_hook
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get, response::IntoResponse, http::StatusCode, Json};
+/// use axum_extra::middleware::{CatchPanicLayer, Panic};
+/// use serde_json::json;
+///
+/// CatchPanicLayer::install_backtrace_hook();
+///
+/// let layer = CatchPanicLayer::new().on_panic(|panic: Panic| {
+///     eprintln!(
+///         "handler panicked: {}\n{}",
+///         panic.message().unwrap_or("unknown"),
+///         panic.backtrace().map(ToString::to_string).unwrap_or_default(),
+///     );
+///     (
+///         StatusCode::INTERNAL_SERVER_ERROR,
+///         Json(json!({ "error": "internal server error" })),
+///     )
+///         .into_response()
+/// });
+///
+/// async fn handler() -> &'static str {
+///     panic!("oh no")
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(layer);
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CatchPanicLayer<T = fn(Panic) -> Response> {
+    on_panic: T,
+}
+
+impl CatchPanicLayer {
+    /// Create a new `CatchPanicLayer`.
+    pub fn new() -> Self {
+        Self {
+            on_panic: default_on_panic,
+        }
+    }
+
+    /// Install a [panic hook](std::panic::set_hook) that captures the backtraces of panics, so
+    /// they're available from [`Panic::backtrace`].
+    ///
+    /// The hook applies to the whole process, not just to panics caught by this layer. It records
+    /// the backtrace and then calls the hook that was installed before it, so panics are still
+    /// printed as usual. Calling this more than once has no effect.
+    pub fn install_backtrace_hook() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                // the thread local is gone if the thread panics while shutting down
+                let _ = BACKTRACE.try_with(|backtrace| {
+                    *backtrace.borrow_mut() = Some(Backtrace::force_capture());
+                });
+                previous(info);
+            }));
+        });
+    }
+}
+
+impl Default for CatchPanicLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn default_on_panic(_panic: Panic) -> Response {
+    #[cfg(feature = "tracing")]
+    tracing::error!(
+        message = _panic.message().unwrap_or("unknown"),
+        backtrace = %_panic
+            .backtrace()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+        "request handler panicked",
+    );
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+impl<T> CatchPanicLayer<T> {
+    /// Set the hook that is called with caught panics and returns the response to send.
+    ///
+    /// The response should generally be a `500 Internal Server Error`.
+    pub fn on_panic<U>(self, on_panic: U) -> CatchPanicLayer<U>
+    where
+        U: Fn(Panic) -> Response + Clone + Send + Sync + 'static,
+    {
+        CatchPanicLayer { on_panic }
+    }
+}
+
+impl<S, T> Layer<S> for CatchPanicLayer<T>
+where
+    T: Clone,
+{
+    type Service = CatchPanic<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CatchPanic {
+            inner,
+            on_panic: self.on_panic.clone(),
+        }
+    }
+}
+
+/// A panic caught by [`CatchPanicLayer`].
+pub struct Panic {
+    payload: Box<dyn Any + Send + 'static>,
+    backtrace: Option<Backtrace>,
+}
+
+impl Panic {
+    fn new(payload: Box<dyn Any + Send + 'static>) -> Self {
+        Self {
+            payload,
+            backtrace: BACKTRACE.with(|backtrace| backtrace.borrow_mut().take()),
+        }
+    }
+
+    /// The message the panic was created with, if it was a string.
+    ///
+    /// This is the case for panics from [`panic!`] with a message, [`unwrap`](Option::unwrap),
+    /// and [`expect`](Option::expect).
+    pub fn message(&self) -> Option<&str> {
+        if let Some(message) = self.payload.downcast_ref::<&str>() {
+            Some(message)
+        } else {
+            self.payload.downcast_ref::<String>().map(String::as_str)
+        }
+    }
+
+    /// The payload of the panic.
+    pub fn payload(&self) -> &(dyn Any + Send + 'static) {
+        &*self.payload
+    }
+
+    /// Consume the `Panic`, returning the payload.
+    ///
+    /// The payload can be passed to [`std::panic::resume_unwind`] to continue panicking.
+    pub fn into_payload(self) -> Box<dyn Any + Send + 'static> {
+        self.payload
+    }
+
+    /// The backtrace of where the panic happened.
+    ///
+    /// This is `None` unless [`CatchPanicLayer::install_backtrace_hook`] was called, or if the
+    /// hook it installed was replaced since.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+}
+
+impl fmt::Debug for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Panic")
+            .field("message", &self.message())
+            .field("backtrace", &self.backtrace)
+            .finish_non_exhaustive()
+    }
+}
+
+thread_local! {
+    static BACKTRACE: RefCell<Option<Backtrace>> = RefCell::new(None);
+}
+
+/// Middleware that catches panics in handlers and extractors and converts them into responses.
+///
+/// Created with [`CatchPanicLayer`]. See that type for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct CatchPanic<S, T = fn(Panic) -> Response> {
+    inner: S,
+    on_panic: T,
+}
+
+impl<S, T> Service<Request> for CatchPanic<S, T>
+where
+    S: Service<Request, Response = Response>,
+    T: Fn(Panic) -> Response + Clone,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = CatchPanicFuture<S::Future, T>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        match panic::catch_unwind(AssertUnwindSafe(|| self.inner.call(req))) {
+            Ok(inner) => CatchPanicFuture {
+                inner: Some(inner),
+                response: None,
+                on_panic: self.on_panic.clone(),
+            },
+            Err(payload) => CatchPanicFuture {
+                inner: None,
+                response: Some((self.on_panic)(Panic::new(payload))),
+                on_panic: self.on_panic.clone(),
+            },
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`CatchPanic`].
+    pub struct CatchPanicFuture<F, T> {
+        #[pin]
+        inner: Option<F>,
+        response: Option<Response>,
+        on_panic: T,
+    }
+}
+
+impl<F, T, E> Future for CatchPanicFuture<F, T>
+where
+    F: Future<Output = Result<Response, E>>,
+    T: Fn(Panic) -> Response,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        if let Some(res) = this.response.take() {
+            return Poll::Ready(Ok(res));
+        }
+
+        let inner = this
+            .inner
+            .as_mut()
+            .as_pin_mut()
+            .expect("future polled after completion");
+        match panic::catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                // the inner future can't be polled again after panicking
+                this.inner.set(None);
+                Poll::Ready(Ok((this.on_panic)(Panic::new(payload))))
+            }
+        }
+    }
+}
+
+impl<F, T> fmt::Debug for CatchPanicFuture<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanicFuture").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{async_trait, extract::FromRequestParts, routing::get, Router};
+    use http::request::Parts;
+
+    struct Panics;
+
+    #[async_trait]
+    impl<S> FromRequestParts<S> for Panics
+    where
+        S: Send + Sync,
+    {
+        type Rejection = Response;
+
+        async fn from_request_parts(_parts: &mut Parts, _state: &S) -> Result<Self, Response> {
+            panic!("extractor panicked")
+        }
+    }
+
+    async fn handler_panics() -> &'static str {
+        panic!("handler panicked")
+    }
+
+    async fn handler_panics_with_string() -> &'static str {
+        panic!("handler {}", "panicked")
+    }
+
+    #[tokio::test]
+    async fn default_response() {
+        let app = Router::new()
+            .route("/", get(handler_panics))
+            .layer(CatchPanicLayer::new());
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn custom_response() {
+        CatchPanicLayer::install_backtrace_hook();
+        let layer = CatchPanicLayer::new().on_panic(|panic: Panic| {
+            assert!(panic.backtrace().is_some());
+            let message = panic.message().unwrap_or_default().to_owned();
+            (StatusCode::INTERNAL_SERVER_ERROR, message).into_response()
+        });
+        let app = Router::new()
+            .route("/handler", get(handler_panics_with_string))
+            .route("/extractor", get(|_: Panics| async {}))
+            .route("/ok", get(|| async {}))
+            .layer(layer);
+        let client = TestClient::new(app);
+
+        let res = client.get("/handler").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.text().await, "handler panicked");
+
+        let res = client.get("/extractor").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.text().await, "extractor panicked");
+
+        let res = client.get("/ok").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,612 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{header, StatusCode};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that stops calling routes whose downstream dependencies are failing.
+///
+/// Every route has a circuit that starts closed, letting requests through. Responses are
+/// classified as failures with a [`ClassifyResponse`], by default [`ServerErrors`], or if they
+/// took longer than the [`slow_call_threshold`](Self::slow_call_threshold). Once a route has
+/// failed [`failure_threshold`](Self::failure_threshold) times in a row its circuit opens, and
+/// requests are answered immediately with the [`open_response`](Self::open_response), a
+/// `503 Service Unavailable` with a `Retry-After` header by default.
+///
+/// After [`open_duration`](Self::open_duration) the circuit is half-open, letting
+/// [`half_open_requests`](Self::half_open_requests) trial requests through. If they all succeed
+/// the circuit closes again, and if one fails it opens again.
+///
+/// This makes routes that depend on a failing service shed load, rather than piling up requests
+/// waiting for timeouts, and gives the service time to recover.
+///
+/// Routes are told apart by the route they matched, such as `/users/:id`. Requests that didn't
+/// match a route, such as those handled by the fallback, share a circuit. Clones of the layer
+/// share their circuits.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get, http::StatusCode};
+/// use axum_extra::middleware::CircuitBreakerLayer;
+/// use std::time::Duration;
+///
+/// async fn recommendations() -> Result<String, StatusCode> {
+///     // call a flaky recommendations service
+///     # Ok(String::new())
+/// }
+///
+/// let app = Router::new()
+///     .route("/recommendations", get(recommendations))
+///     .route_layer(
+///         CircuitBreakerLayer::new()
+///             .failure_threshold(3)
+///             .slow_call_threshold(Duration::from_secs(2))
+///             .open_duration(Duration::from_secs(10)),
+///     );
+/// # let _: Router = app;
+/// ```
+pub struct CircuitBreakerLayer<C = ServerErrors, R = fn(Duration) -> Response> {
+    classifier: C,
+    open_response: R,
+    config: Config,
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    failure_threshold: u32,
+    slow_call_threshold: Option<Duration>,
+    open_duration: Duration,
+    half_open_requests: u32,
+}
+
+impl CircuitBreakerLayer {
+    /// Create a new `CircuitBreakerLayer`.
+    ///
+    /// Circuits open after 5 failures in a row, and are half-open after 30 seconds, letting one
+    /// trial request through.
+    pub fn new() -> Self {
+        Self {
+            classifier: ServerErrors::default(),
+            open_response: default_open_response,
+            config: Config {
+                failure_threshold: 5,
+                slow_call_threshold: None,
+                open_duration: Duration::from_secs(30),
+                half_open_requests: 1,
+            },
+            circuits: Default::default(),
+        }
+    }
+}
+
+impl Default for CircuitBreakerLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn default_open_response(retry_after: Duration) -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, seconds(retry_after).to_string())],
+    )
+        .into_response()
+}
+
+fn seconds(duration: Duration) -> u64 {
+    let rounded_up = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    rounded_up.max(1)
+}
+
+impl<C, R> CircuitBreakerLayer<C, R> {
+    /// Set how many failures in a row open the circuit of a route.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure_threshold` is zero.
+    #[track_caller]
+    pub fn failure_threshold(mut self, failure_threshold: u32) -> Self {
+        assert!(
+            failure_threshold > 0,
+            "failure threshold must be greater than zero"
+        );
+        self.config.failure_threshold = failure_threshold;
+        self
+    }
+
+    /// Set the duration after which a response counts as a failure, regardless of its status.
+    ///
+    /// Defaults to none.
+    pub fn slow_call_threshold(mut self, slow_call_threshold: Duration) -> Self {
+        self.config.slow_call_threshold = Some(slow_call_threshold);
+        self
+    }
+
+    /// Set how long the circuit of a route stays open before trial requests are let through.
+    pub fn open_duration(mut self, open_duration: Duration) -> Self {
+        self.config.open_duration = open_duration;
+        self
+    }
+
+    /// Set how many trial requests are let through while the circuit is half-open.
+    ///
+    /// They must all succeed for the circuit to close. Other requests are answered with the
+    /// open response until then.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `half_open_requests` is zero.
+    #[track_caller]
+    pub fn half_open_requests(mut self, half_open_requests: u32) -> Self {
+        assert!(
+            half_open_requests > 0,
+            "half-open requests must be greater than zero"
+        );
+        self.config.half_open_requests = half_open_requests;
+        self
+    }
+
+    /// Set how responses are classified as failures.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{Router, response::Response, http::StatusCode};
+    /// use axum_extra::middleware::CircuitBreakerLayer;
+    ///
+    /// // rate limiting by the dependency is a failure too
+    /// let layer = CircuitBreakerLayer::new().classify(|res: &Response| {
+    ///     res.status().is_server_error() || res.status() == StatusCode::TOO_MANY_REQUESTS
+    /// });
+    ///
+    /// let app = Router::new().layer(layer);
+    /// # let _: Router = app;
+    /// ```
+    pub fn classify<T>(self, classifier: T) -> CircuitBreakerLayer<T, R>
+    where
+        T: ClassifyResponse,
+    {
+        CircuitBreakerLayer {
+            classifier,
+            open_response: self.open_response,
+            config: self.config,
+            circuits: self.circuits,
+        }
+    }
+
+    /// Set the response sent while the circuit of a route is open.
+    ///
+    /// The function is called with the time until trial requests are let through.
+    pub fn open_response<T>(self, open_response: T) -> CircuitBreakerLayer<C, T>
+    where
+        T: Fn(Duration) -> Response + Clone + Send + Sync + 'static,
+    {
+        CircuitBreakerLayer {
+            classifier: self.classifier,
+            open_response,
+            config: self.config,
+            circuits: self.circuits,
+        }
+    }
+}
+
+impl<C, R> Clone for CircuitBreakerLayer<C, R>
+where
+    C: Clone,
+    R: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            classifier: self.classifier.clone(),
+            open_response: self.open_response.clone(),
+            config: self.config,
+            circuits: self.circuits.clone(),
+        }
+    }
+}
+
+impl<C, R> fmt::Debug for CircuitBreakerLayer<C, R>
+where
+    C: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreakerLayer")
+            .field("classifier", &self.classifier)
+            .field("failure_threshold", &self.config.failure_threshold)
+            .field("slow_call_threshold", &self.config.slow_call_threshold)
+            .field("open_duration", &self.config.open_duration)
+            .field("half_open_requests", &self.config.half_open_requests)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, C, R> Layer<S> for CircuitBreakerLayer<C, R>
+where
+    C: Clone,
+    R: Clone,
+{
+    type Service = CircuitBreaker<S, C, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreaker {
+            inner,
+            classifier: self.classifier.clone(),
+            open_response: self.open_response.clone(),
+            config: self.config,
+            circuits: self.circuits.clone(),
+        }
+    }
+}
+
+/// Middleware that stops calling routes whose downstream dependencies are failing.
+///
+/// Created with [`CircuitBreakerLayer`]. See that type for more details.
+pub struct CircuitBreaker<S, C = ServerErrors, R = fn(Duration) -> Response> {
+    inner: S,
+    classifier: C,
+    open_response: R,
+    config: Config,
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+}
+
+impl<S, C, R> Clone for CircuitBreaker<S, C, R>
+where
+    S: Clone,
+    C: Clone,
+    R: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            classifier: self.classifier.clone(),
+            open_response: self.open_response.clone(),
+            config: self.config,
+            circuits: self.circuits.clone(),
+        }
+    }
+}
+
+impl<S, C, R> fmt::Debug for CircuitBreaker<S, C, R>
+where
+    S: fmt::Debug,
+    C: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("inner", &self.inner)
+            .field("classifier", &self.classifier)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, C, R> Service<Request> for CircuitBreaker<S, C, R>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    C: ClassifyResponse,
+    R: Fn(Duration) -> Response + Clone + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_owned())
+            .unwrap_or_default();
+
+        let now = Instant::now();
+        let trial = {
+            let mut circuits = self.circuits.lock().unwrap();
+            let circuit = circuits.entry(route.clone()).or_default();
+            match circuit.acquire(&self.config, now) {
+                Ok(trial) => trial,
+                Err(retry_after) => {
+                    let res = (self.open_response)(retry_after);
+                    return Box::pin(async move { Ok(res) });
+                }
+            }
+        };
+
+        let mut guard = Guard {
+            circuits: self.circuits.clone(),
+            route,
+            trial,
+            done: false,
+        };
+        let future = self.inner.call(req);
+        let classifier = self.classifier.clone();
+        let config = self.config;
+
+        Box::pin(async move {
+            let result = future.await;
+
+            let slow = matches!(
+                config.slow_call_threshold,
+                Some(threshold) if now.elapsed() > threshold
+            );
+            let failed = match &result {
+                Ok(res) => slow || classifier.is_failure(res),
+                Err(_) => true,
+            };
+
+            let mut circuits = guard.circuits.lock().unwrap();
+            if let Some(circuit) = circuits.get_mut(&guard.route) {
+                circuit.record(&config, &guard.route, guard.trial, failed, Instant::now());
+            }
+            drop(circuits);
+            guard.disarm();
+
+            result
+        })
+    }
+}
+
+/// Releases the permit of a trial request whose future was dropped.
+struct Guard {
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+    route: String,
+    trial: bool,
+    done: bool,
+}
+
+impl Guard {
+    /// Keep the permit, since the outcome of the request has been recorded.
+    fn disarm(&mut self) {
+        self.done = true;
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if self.done || !self.trial {
+            return;
+        }
+        if let Ok(mut circuits) = self.circuits.lock() {
+            if let Some(Circuit::HalfOpen { in_flight, .. }) = circuits.get_mut(&self.route) {
+                *in_flight = in_flight.saturating_sub(1);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { in_flight: u32, successes: u32 },
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self::Closed { failures: 0 }
+    }
+}
+
+impl Circuit {
+    /// Returns whether the request is a trial, or how long until trial requests are let through.
+    fn acquire(&mut self, config: &Config, now: Instant) -> Result<bool, Duration> {
+        match self {
+            Self::Closed { .. } => Ok(false),
+            Self::Open { until } if now < *until => Err(*until - now),
+            Self::Open { .. } => {
+                *self = Self::HalfOpen {
+                    in_flight: 1,
+                    successes: 0,
+                };
+                Ok(true)
+            }
+            Self::HalfOpen {
+                in_flight,
+                successes,
+            } => {
+                if *in_flight + *successes < config.half_open_requests {
+                    *in_flight += 1;
+                    Ok(true)
+                } else {
+                    Err(Duration::ZERO)
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, config: &Config, _route: &str, trial: bool, failed: bool, now: Instant) {
+        match self {
+            Self::Closed { failures } if failed => {
+                *failures += 1;
+                if *failures >= config.failure_threshold {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(route = _route, "circuit opened");
+
+                    *self = Self::Open {
+                        until: now + config.open_duration,
+                    };
+                }
+            }
+            Self::Closed { failures } => *failures = 0,
+            Self::HalfOpen { .. } if trial && failed => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    route = _route,
+                    "circuit opened again after failed trial request"
+                );
+
+                *self = Self::Open {
+                    until: now + config.open_duration,
+                };
+            }
+            Self::HalfOpen {
+                in_flight,
+                successes,
+            } if trial => {
+                *in_flight -= 1;
+                *successes += 1;
+                if *successes >= config.half_open_requests {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(route = _route, "circuit closed");
+
+                    *self = Self::default();
+                }
+            }
+            // responses to requests let through before the circuit changed state
+            Self::HalfOpen { .. } | Self::Open { .. } => {}
+        }
+    }
+}
+
+/// Classifies responses as failures for a [`CircuitBreakerLayer`].
+///
+/// This is implemented for closures taking the response and returning whether it's a failure.
+pub trait ClassifyResponse: Clone + Send + Sync + 'static {
+    /// Whether `res` is a failure.
+    fn is_failure(&self, res: &Response) -> bool;
+}
+
+impl<F> ClassifyResponse for F
+where
+    F: Fn(&Response) -> bool + Clone + Send + Sync + 'static,
+{
+    fn is_failure(&self, res: &Response) -> bool {
+        self(res)
+    }
+}
+
+/// Classifies `5xx Server Error` responses as failures.
+///
+/// This is the default [`ClassifyResponse`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerErrors {
+    _priv: (),
+}
+
+impl ClassifyResponse for ServerErrors {
+    fn is_failure(&self, res: &Response) -> bool {
+        res.status().is_server_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const OPEN: Duration = Duration::from_millis(50);
+
+    fn app(failing: Arc<AtomicBool>, layer: CircuitBreakerLayer) -> Router {
+        Router::new()
+            .route(
+                "/flaky",
+                get(move || {
+                    let failing = failing.load(Ordering::SeqCst);
+                    async move {
+                        if failing {
+                            StatusCode::BAD_GATEWAY
+                        } else {
+                            StatusCode::OK
+                        }
+                    }
+                }),
+            )
+            .route("/ok", get(|| async {}))
+            .layer(layer)
+    }
+
+    #[tokio::test]
+    async fn opens_and_recovers() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let layer = CircuitBreakerLayer::new()
+            .failure_threshold(2)
+            .open_duration(OPEN);
+        let client = TestClient::new(app(failing.clone(), layer));
+
+        for _ in 0..2 {
+            let res = client.get("/flaky").await;
+            assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+        }
+
+        let res = client.get("/flaky").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers()["retry-after"], "1");
+
+        // other routes have their own circuit
+        let res = client.get("/ok").await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // a failed trial request opens the circuit again
+        tokio::time::sleep(OPEN).await;
+        let res = client.get("/flaky").await;
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+        let res = client.get("/flaky").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        failing.store(false, Ordering::SeqCst);
+        tokio::time::sleep(OPEN).await;
+        for _ in 0..3 {
+            let res = client.get("/flaky").await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn successes_reset_failures() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let layer = CircuitBreakerLayer::new().failure_threshold(2);
+        let client = TestClient::new(app(failing.clone(), layer));
+
+        client.get("/flaky").await;
+        failing.store(false, Ordering::SeqCst);
+        client.get("/flaky").await;
+        failing.store(true, Ordering::SeqCst);
+        client.get("/flaky").await;
+
+        let res = client.get("/flaky").await;
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn custom_classifier_and_response() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }),
+            )
+            .layer(
+                CircuitBreakerLayer::new()
+                    .failure_threshold(1)
+                    .slow_call_threshold(Duration::from_millis(10))
+                    .classify(|_: &Response| false)
+                    .open_response(|_| (StatusCode::OK, "fallback").into_response()),
+            );
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.text().await, "");
+        let res = client.get("/").await;
+        assert_eq!(res.text().await, "fallback");
+    }
+}
@@ -0,0 +1,281 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{header, StatusCode};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that limits how many requests to a route are processed at the same time.
+///
+/// This protects expensive routes, such as ones generating reports, from using up all the
+/// resources of the server. Requests over the limit can wait in a [queue](Self::queue) for up
+/// to the [`max_wait`](Self::max_wait), in the order they arrived. Requests that don't fit in
+/// the queue, or time out waiting, get a `503 Service Unavailable` response with a
+/// [`Retry-After`](Self::retry_after) header.
+///
+/// Routes are told apart by the route they matched, such as `/users/:id`, so each route gets
+/// its own limit even if the layer is added to a whole router. Requests that didn't match a
+/// route, such as those handled by the fallback, share a limit. Clones of the layer share their
+/// limits.
+///
+/// Unlike tower's `ConcurrencyLimitLayer`, requests over the limit are rejected rather than
+/// waiting for as long as it takes.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::middleware::ConcurrencyLimitLayer;
+/// use std::time::Duration;
+///
+/// async fn generate_report() -> String {
+///     // expensive work
+///     # String::new()
+/// }
+///
+/// let app = Router::new()
+///     .route("/report", get(generate_report))
+///     .route_layer(
+///         ConcurrencyLimitLayer::new(4)
+///             .queue(16)
+///             .max_wait(Duration::from_secs(5))
+///             .retry_after(Duration::from_secs(30)),
+///     );
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitLayer {
+    config: Config,
+    limits: Arc<Mutex<HashMap<String, Arc<Limit>>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    max: usize,
+    queue: usize,
+    max_wait: Duration,
+    retry_after: Duration,
+}
+
+#[derive(Debug)]
+struct Limit {
+    semaphore: Arc<Semaphore>,
+    waiting: AtomicUsize,
+}
+
+impl ConcurrencyLimitLayer {
+    /// Create a new `ConcurrencyLimitLayer` that lets up to `max` requests to each route be
+    /// processed at the same time, without a queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    #[track_caller]
+    pub fn new(max: usize) -> Self {
+        assert!(max > 0, "concurrency limit must be greater than zero");
+        Self {
+            config: Config {
+                max,
+                queue: 0,
+                max_wait: Duration::from_secs(30),
+                retry_after: Duration::from_secs(1),
+            },
+            limits: Default::default(),
+        }
+    }
+
+    /// Set how many requests to each route can wait for others to complete.
+    ///
+    /// Defaults to zero.
+    pub fn queue(mut self, queue: usize) -> Self {
+        self.config.queue = queue;
+        self
+    }
+
+    /// Set how long requests wait in the queue before they are rejected.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.config.max_wait = max_wait;
+        self
+    }
+
+    /// Set the value of the `Retry-After` header of rejected requests.
+    ///
+    /// Defaults to 1 second.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.config.retry_after = retry_after;
+        self
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimit {
+            inner,
+            config: self.config,
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+/// Middleware that limits how many requests to a route are processed at the same time.
+///
+/// Created with [`ConcurrencyLimitLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit<S> {
+    inner: S,
+    config: Config,
+    limits: Arc<Mutex<HashMap<String, Arc<Limit>>>>,
+}
+
+impl<S> Service<Request> for ConcurrencyLimit<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str())
+            .unwrap_or_default();
+        let limit = self
+            .limits
+            .lock()
+            .unwrap()
+            .entry(route.to_owned())
+            .or_insert_with(|| {
+                Arc::new(Limit {
+                    semaphore: Arc::new(Semaphore::new(self.config.max)),
+                    waiting: AtomicUsize::new(0),
+                })
+            })
+            .clone();
+        let config = self.config;
+
+        Box::pin(async move {
+            let permit = match limit.semaphore.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    if limit.waiting.fetch_add(1, Ordering::SeqCst) >= config.queue {
+                        limit.waiting.fetch_sub(1, Ordering::SeqCst);
+                        return Ok(saturated(config.retry_after));
+                    }
+                    let _waiting = Waiting(&limit.waiting);
+                    let acquire = limit.semaphore.clone().acquire_owned();
+                    match tokio::time::timeout(config.max_wait, acquire).await {
+                        Ok(Ok(permit)) => permit,
+                        _ => return Ok(saturated(config.retry_after)),
+                    }
+                }
+            };
+
+            let res = inner.call(req).await;
+            drop(permit);
+            res
+        })
+    }
+}
+
+/// Removes a request from the queue once it stops waiting, even if its future is dropped.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn saturated(retry_after: Duration) -> Response {
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, seconds.to_string())],
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    fn app(layer: ConcurrencyLimitLayer) -> Router {
+        Router::new()
+            .route(
+                "/slow",
+                get(|| async { tokio::time::sleep(Duration::from_millis(100)).await }),
+            )
+            .route("/other", get(|| async {}))
+            .layer(layer)
+    }
+
+    async fn concurrently(client: &TestClient, uri: &str) -> (StatusCode, StatusCode) {
+        let (first, second) = tokio::join!(async { client.get("/slow").await }, async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            client.get(uri).await
+        });
+        (first.status(), second.status())
+    }
+
+    #[tokio::test]
+    async fn rejects_over_limit() {
+        let client = TestClient::new(app(
+            ConcurrencyLimitLayer::new(1).retry_after(Duration::from_secs(10))
+        ));
+
+        let (first, second) = tokio::join!(async { client.get("/slow").await }, async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            client.get("/slow").await
+        });
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(second.headers()["retry-after"], "10");
+
+        // routes have separate limits
+        let statuses = concurrently(&client, "/other").await;
+        assert_eq!(statuses, (StatusCode::OK, StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn queues() {
+        let client = TestClient::new(app(ConcurrencyLimitLayer::new(1).queue(1)));
+        let statuses = concurrently(&client, "/slow").await;
+        assert_eq!(statuses, (StatusCode::OK, StatusCode::OK));
+
+        let client = TestClient::new(app(ConcurrencyLimitLayer::new(1)
+            .queue(1)
+            .max_wait(Duration::from_millis(10))));
+        let statuses = concurrently(&client, "/slow").await;
+        assert_eq!(statuses, (StatusCode::OK, StatusCode::SERVICE_UNAVAILABLE));
+    }
+}
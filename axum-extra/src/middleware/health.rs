@@ -0,0 +1,387 @@
+use axum::{
+    async_trait,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use http::StatusCode;
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    future::Future,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Registry of health probes, served as liveness and readiness endpoints.
+///
+/// [`router`](Self::router) returns a [`Router`] with two routes:
+///
+/// - `GET /healthz`, for liveness: whether the server is running and should not be restarted.
+/// - `GET /readyz`, for readiness: whether the server can handle traffic, for example because its
+///   database is reachable.
+///
+/// Each route runs its [probes](Probe) concurrently, and responds with `200 OK` if none of them
+/// are [unhealthy](ProbeStatus::Unhealthy), or `503 Service Unavailable` otherwise. Probes that
+/// take longer than the [timeout](Self::timeout) are unhealthy. The body of the response is a
+/// JSON object with the result of each probe:
+///
+/// ```json
+/// {
+///   "status": "degraded",
+///   "checks": {
+///     "database": { "status": "healthy", "duration_ms": 3 },
+///     "queue": { "status": "degraded", "message": "1200 jobs queued", "duration_ms": 1 }
+///   }
+/// }
+/// ```
+///
+/// Probes can be registered at any time, also after the router has been built. Clones of a
+/// `HealthChecks` share their probes.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::{
+///     middleware::{HealthChecks, ProbeStatus},
+///     routing::RouterExt,
+/// };
+/// use std::time::Duration;
+///
+/// # struct Database;
+/// # impl Database { async fn ping(&self) -> Result<(), std::io::Error> { Ok(()) } }
+/// # async fn queue_depth() -> usize { 0 }
+/// let db = std::sync::Arc::new(Database);
+///
+/// let health = HealthChecks::new().timeout(Duration::from_secs(2));
+/// health.readiness("database", move || {
+///     let db = db.clone();
+///     async move { db.ping().await }
+/// });
+/// health.readiness("queue", || async {
+///     match queue_depth().await {
+///         depth if depth > 1000 => ProbeStatus::Degraded(format!("{depth} jobs queued")),
+///         _ => ProbeStatus::Healthy,
+///     }
+/// });
+///
+/// let app = Router::new()
+///     .route("/", get(|| async { "Hello, World!" }))
+///     .health_checks(health);
+/// # let _: Router = app;
+/// ```
+#[derive(Clone)]
+pub struct HealthChecks {
+    probes: Arc<RwLock<Probes>>,
+    timeout: Duration,
+}
+
+#[derive(Default)]
+struct Probes {
+    liveness: Vec<(String, Arc<dyn Probe>)>,
+    readiness: Vec<(String, Arc<dyn Probe>)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Liveness,
+    Readiness,
+}
+
+impl HealthChecks {
+    /// Create a new `HealthChecks` without any probes.
+    pub fn new() -> Self {
+        Self {
+            probes: Default::default(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Set how long probes can take before they are unhealthy.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Register a probe that is run for `/healthz`.
+    ///
+    /// A probe registered earlier with the same name is replaced.
+    pub fn liveness<P>(&self, name: impl Into<String>, probe: P)
+    where
+        P: Probe,
+    {
+        self.register(Kind::Liveness, name.into(), Arc::new(probe));
+    }
+
+    /// Register a probe that is run for `/readyz`.
+    ///
+    /// A probe registered earlier with the same name is replaced.
+    pub fn readiness<P>(&self, name: impl Into<String>, probe: P)
+    where
+        P: Probe,
+    {
+        self.register(Kind::Readiness, name.into(), Arc::new(probe));
+    }
+
+    fn register(&self, kind: Kind, name: String, probe: Arc<dyn Probe>) {
+        let mut probes = self.probes.write().unwrap();
+        let probes = match kind {
+            Kind::Liveness => &mut probes.liveness,
+            Kind::Readiness => &mut probes.readiness,
+        };
+        match probes.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = probe,
+            None => probes.push((name, probe)),
+        }
+    }
+
+    /// Create a [`Router`] that serves `/healthz` and `/readyz`.
+    ///
+    /// The router can be [merged](Router::merge) into the application, or added with
+    /// [`RouterExt::health_checks`](crate::routing::RouterExt::health_checks).
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let liveness = self.clone();
+        let readiness = self.clone();
+        Router::new()
+            .route(
+                "/healthz",
+                get(move || {
+                    let checks = liveness.clone();
+                    async move { checks.report(Kind::Liveness).await }
+                }),
+            )
+            .route(
+                "/readyz",
+                get(move || {
+                    let checks = readiness.clone();
+                    async move { checks.report(Kind::Readiness).await }
+                }),
+            )
+    }
+
+    async fn report(&self, kind: Kind) -> Response {
+        let probes = {
+            let probes = self.probes.read().unwrap();
+            match kind {
+                Kind::Liveness => probes.liveness.clone(),
+                Kind::Readiness => probes.readiness.clone(),
+            }
+        };
+
+        let timeout = self.timeout;
+        let results =
+            futures_util::future::join_all(probes.into_iter().map(|(name, probe)| async move {
+                let start = Instant::now();
+                let status = tokio::time::timeout(timeout, probe.check())
+                    .await
+                    .unwrap_or_else(|_| {
+                        ProbeStatus::Unhealthy(format!("timed out after {timeout:?}"))
+                    });
+                (name, status, start.elapsed())
+            }))
+            .await;
+
+        let mut overall = ProbeStatus::Healthy;
+        let mut checks = Map::new();
+        for (name, status, duration) in results {
+            let mut check = Map::new();
+            check.insert("status".to_owned(), status.name().into());
+            if let Some(message) = status.message() {
+                check.insert("message".to_owned(), message.into());
+            }
+            check.insert(
+                "duration_ms".to_owned(),
+                u64::try_from(duration.as_millis())
+                    .unwrap_or(u64::MAX)
+                    .into(),
+            );
+            checks.insert(name, check.into());
+
+            if status.severity() > overall.severity() {
+                overall = status;
+            }
+        }
+
+        let status = if matches!(overall, ProbeStatus::Unhealthy(_)) {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::OK
+        };
+        let mut body = Map::new();
+        body.insert("status".to_owned(), overall.name().into());
+        body.insert("checks".to_owned(), checks.into());
+        (status, Json(Value::Object(body))).into_response()
+    }
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for HealthChecks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let probes = self.probes.read().unwrap();
+        let names = |probes: &[(String, Arc<dyn Probe>)]| {
+            probes
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>()
+        };
+        f.debug_struct("HealthChecks")
+            .field("liveness", &names(&probes.liveness))
+            .field("readiness", &names(&probes.readiness))
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// A health check registered with [`HealthChecks`].
+///
+/// This is implemented for async closures returning a [`ProbeStatus`], or a `Result<(), E>`
+/// where `E` is the reason the probe is unhealthy.
+#[async_trait]
+pub trait Probe: Send + Sync + 'static {
+    /// Check the health of the component.
+    async fn check(&self) -> ProbeStatus;
+}
+
+#[async_trait]
+impl<F, Fut, T> Probe for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = T> + Send,
+    T: Into<ProbeStatus>,
+{
+    async fn check(&self) -> ProbeStatus {
+        self().await.into()
+    }
+}
+
+/// The result of a [`Probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProbeStatus {
+    /// The component works.
+    Healthy,
+    /// The component works, but not as well as it should, for example because a queue is
+    /// backing up. The endpoint still responds with `200 OK`.
+    Degraded(String),
+    /// The component doesn't work. The endpoint responds with `503 Service Unavailable`.
+    Unhealthy(String),
+}
+
+impl ProbeStatus {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded(_) => "degraded",
+            Self::Unhealthy(_) => "unhealthy",
+        }
+    }
+
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Healthy => None,
+            Self::Degraded(message) | Self::Unhealthy(message) => Some(message),
+        }
+    }
+
+    fn severity(&self) -> u8 {
+        match self {
+            Self::Healthy => 0,
+            Self::Degraded(_) => 1,
+            Self::Unhealthy(_) => 2,
+        }
+    }
+}
+
+impl<E> From<Result<(), E>> for ProbeStatus
+where
+    E: fmt::Display,
+{
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::Healthy,
+            Err(err) => Self::Unhealthy(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::RouterExt, test_helpers::*};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn aggregates_probes() {
+        let depth = Arc::new(AtomicUsize::new(0));
+        let health = HealthChecks::new();
+        health.readiness("database", || async { Ok::<_, String>(()) });
+        health.readiness("queue", {
+            let depth = depth.clone();
+            move || {
+                let depth = depth.load(Ordering::SeqCst);
+                async move {
+                    if depth > 10 {
+                        ProbeStatus::Degraded(format!("{depth} jobs queued"))
+                    } else {
+                        ProbeStatus::Healthy
+                    }
+                }
+            }
+        });
+        let client = TestClient::new(Router::new().health_checks(health.clone()));
+
+        let res = client.get("/healthz").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.json::<Value>().await,
+            json!({ "status": "healthy", "checks": {} })
+        );
+
+        let res = client.get("/readyz").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.json::<Value>().await["status"], "healthy");
+
+        depth.store(20, Ordering::SeqCst);
+        let res = client.get("/readyz").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.json::<Value>().await;
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["checks"]["queue"]["message"], "20 jobs queued");
+        assert_eq!(body["checks"]["database"]["status"], "healthy");
+
+        // probes can be registered after the router is built
+        health.readiness("database", || async { Err::<(), _>("connection refused") });
+        let res = client.get("/readyz").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = res.json::<Value>().await;
+        assert_eq!(body["status"], "unhealthy");
+        assert_eq!(body["checks"]["database"]["message"], "connection refused");
+    }
+
+    #[tokio::test]
+    async fn times_out() {
+        let health = HealthChecks::new().timeout(Duration::from_millis(10));
+        health.liveness("slow", || async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            ProbeStatus::Healthy
+        });
+        let client = TestClient::new(health.router::<()>());
+
+        let res = client.get("/healthz").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = res.json::<Value>().await;
+        assert_eq!(body["checks"]["slow"]["status"], "unhealthy");
+    }
+}
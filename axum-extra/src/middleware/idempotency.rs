@@ -0,0 +1,742 @@
+use axum::{
+    async_trait,
+    body::{Body, Bytes, HttpBody},
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use http_body_util::BodyExt;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Keys longer than this are rejected, so clients can't fill the store with huge keys.
+const MAX_KEY_LEN: usize = 255;
+
+/// How long to wait between checks whether a request with the same key has completed.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Layer that replays the response of a request when it's retried with the same
+/// `Idempotency-Key`.
+///
+/// Clients of APIs that must not perform an operation twice, such as taking a payment, send a
+/// unique key with every operation. If a response gets lost, the client can safely retry with the
+/// same key: the response to the first request is stored in an [`IdempotencyStore`], and sent
+/// again with an `Idempotent-Replayed: true` header, without calling the handler.
+///
+/// Only `POST` and `PATCH` requests are handled, since other methods are idempotent already.
+/// Requests without the header are passed through.
+///
+/// - A retry that arrives while the first request is still being processed waits for its
+///   response, for up to the [`wait_timeout`](Self::wait_timeout), after which it's rejected
+///   with `409 Conflict`.
+/// - A key reused for a different method, URI, or request body is rejected with
+///   `422 Unprocessable Entity`. Request bodies are buffered to hash them, and rejected with
+///   `413 Payload Too Large` if they're larger than the [`max_body_size`](Self::max_body_size).
+/// - A key that is empty, longer than 255 bytes, or contains characters other than visible ASCII
+///   is rejected with `400 Bad Request`.
+/// - Responses with a `5xx Server Error` status, and responses whose body isn't known to fit in
+///   the [`max_body_size`](Self::max_body_size), such as streams, aren't stored, so retries call
+///   the handler again.
+/// - If the store fails the request is rejected with `500 Internal Server Error`, since it
+///   can't be known whether the operation was performed already.
+///
+/// Keys are global unless a [`scope`](Self::scope) is set, so one client could replay the
+/// response to another client's request by guessing its key. Scope keys to the authenticated user
+/// if clients share the layer.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::post, Json};
+/// use axum_extra::middleware::IdempotencyLayer;
+/// use std::time::Duration;
+///
+/// async fn create_payment(Json(_amount): Json<u64>) -> Json<String> {
+///     // charge the customer
+///     # Json(String::new())
+/// }
+///
+/// let app = Router::new()
+///     .route("/payments", post(create_payment))
+///     .layer(IdempotencyLayer::new().ttl(Duration::from_secs(60 * 60)));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct IdempotencyLayer<T = MemoryIdempotencyStore> {
+    store: T,
+    config: Config,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    header: HeaderName,
+    scope: Option<Scope>,
+    ttl: Duration,
+    wait_timeout: Duration,
+    max_body_size: usize,
+}
+
+#[derive(Clone)]
+struct Scope(Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>);
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+impl IdempotencyLayer {
+    /// Create a new `IdempotencyLayer` that uses the `Idempotency-Key` header and a
+    /// [`MemoryIdempotencyStore`].
+    pub fn new() -> Self {
+        Self {
+            store: MemoryIdempotencyStore::new(),
+            config: Config {
+                header: HeaderName::from_static("idempotency-key"),
+                scope: None,
+                ttl: Duration::from_secs(24 * 60 * 60),
+                wait_timeout: Duration::from_secs(10),
+                max_body_size: 1024 * 1024,
+            },
+        }
+    }
+}
+
+impl Default for IdempotencyLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IdempotencyLayer<T> {
+    /// Set the store the responses are kept in.
+    pub fn store<U>(self, store: U) -> IdempotencyLayer<U>
+    where
+        U: IdempotencyStore,
+    {
+        IdempotencyLayer {
+            store,
+            config: self.config,
+        }
+    }
+
+    /// Set the header the key is read from.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.config.header = header;
+        self
+    }
+
+    /// Set the scope keys are unique in, such as the authenticated user.
+    ///
+    /// `scope` is called with every request that has a key, and requests only replay responses
+    /// to requests with the same scope. Requests for which `scope` returns `None` share the
+    /// global scope.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{Router, routing::post, http::header::AUTHORIZATION};
+    /// use axum_extra::middleware::IdempotencyLayer;
+    ///
+    /// let layer = IdempotencyLayer::new().scope(|req| {
+    ///     let credentials = req.headers().get(AUTHORIZATION)?;
+    ///     Some(String::from_utf8_lossy(credentials.as_bytes()).into_owned())
+    /// });
+    ///
+    /// let app = Router::new()
+    ///     .route("/payments", post(|| async {}))
+    ///     .layer(layer);
+    /// # let _: Router = app;
+    /// ```
+    pub fn scope<F>(mut self, scope: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.config.scope = Some(Scope(Arc::new(scope)));
+        self
+    }
+
+    /// Set how long responses are kept for.
+    ///
+    /// Defaults to 24 hours.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.config.ttl = ttl;
+        self
+    }
+
+    /// Set how long retries wait for a request with the same key to complete.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn wait_timeout(mut self, wait_timeout: Duration) -> Self {
+        self.config.wait_timeout = wait_timeout;
+        self
+    }
+
+    /// Set the maximum size of request bodies, which are hashed into the fingerprint, and of the
+    /// bodies of the responses that are stored.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl<S, T> Layer<S> for IdempotencyLayer<T>
+where
+    T: Clone,
+{
+    type Service = Idempotency<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Idempotency {
+            inner,
+            store: self.store.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Middleware that replays the response of a request when it's retried with the same
+/// `Idempotency-Key`.
+///
+/// Created with [`IdempotencyLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct Idempotency<S, T = MemoryIdempotencyStore> {
+    inner: S,
+    store: T,
+    config: Config,
+}
+
+impl<S, T> Service<Request> for Idempotency<S, T>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    T: IdempotencyStore,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let key = match req.headers().get(&self.config.header) {
+            Some(key) if matches!(*req.method(), Method::POST | Method::PATCH) => key,
+            _ => return Box::pin(inner.call(req)),
+        };
+        let Some(key) = key
+            .to_str()
+            .ok()
+            .filter(|key| valid_key(key))
+            .map(str::to_owned)
+        else {
+            let res = (StatusCode::BAD_REQUEST, "Invalid idempotency key").into_response();
+            return Box::pin(async move { Ok(res) });
+        };
+        // scopes are hashed so credentials used as scopes aren't kept in the store
+        let key = match self.config.scope.as_ref().and_then(|scope| (scope.0)(&req)) {
+            Some(scope) => format!("{:x} {key}", Sha256::digest(scope)),
+            None => key,
+        };
+
+        let store = self.store.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = match axum::body::to_bytes(body, config.max_body_size).await {
+                Ok(body) => body,
+                Err(_) => {
+                    return Ok((
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "Request body too large for an idempotent request",
+                    )
+                        .into_response());
+                }
+            };
+            let fingerprint = format!("{} {} {:x}", parts.method, parts.uri, Sha256::digest(&body));
+            let req = Request::from_parts(parts, Body::from(body));
+
+            let started = Instant::now();
+            loop {
+                match store.begin(&key, &fingerprint, config.ttl).await {
+                    Ok(IdempotencyState::Started) => break,
+                    Ok(
+                        IdempotencyState::InFlight { fingerprint: other }
+                        | IdempotencyState::Completed {
+                            fingerprint: other, ..
+                        },
+                    ) if other != fingerprint => {
+                        return Ok(key_reused());
+                    }
+                    Ok(IdempotencyState::InFlight { .. }) => {
+                        if started.elapsed() >= config.wait_timeout {
+                            return Ok((
+                                StatusCode::CONFLICT,
+                                "A request with this idempotency key is still being processed",
+                            )
+                                .into_response());
+                        }
+                        store.wait(&key).await;
+                    }
+                    Ok(IdempotencyState::Completed { response, .. }) => {
+                        return Ok(response.replay());
+                    }
+                    Err(err) => return Ok(store_failed(err)),
+                }
+            }
+
+            let guard = AbandonGuard {
+                store: Some(store.clone()),
+                key: key.clone(),
+            };
+
+            let res = inner.call(req).await?;
+            let fits = matches!(
+                res.body().size_hint().upper(),
+                Some(len) if len <= config.max_body_size as u64
+            );
+            if res.status().is_server_error() || !fits {
+                return Ok(res);
+            }
+
+            let (parts, body) = res.into_parts();
+            let body = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to buffer response body");
+
+                    return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                }
+            };
+
+            let response = StoredResponse::new(parts.status, parts.headers.clone(), body.clone());
+            if let Err(err) = store.complete(&key, response, config.ttl).await {
+                return Ok(store_failed(err));
+            }
+            guard.disarm();
+
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LEN && key.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+fn key_reused() -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "The idempotency key was already used for a different request",
+    )
+        .into_response()
+}
+
+fn store_failed<E>(_err: E) -> Response
+where
+    E: std::error::Error,
+{
+    #[cfg(feature = "tracing")]
+    tracing::error!(error = %_err, "idempotency store failed");
+
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+/// Releases the key if the response isn't stored, so retries call the handler again.
+struct AbandonGuard<T>
+where
+    T: IdempotencyStore,
+{
+    store: Option<T>,
+    key: String,
+}
+
+impl<T> AbandonGuard<T>
+where
+    T: IdempotencyStore,
+{
+    fn disarm(mut self) {
+        self.store = None;
+    }
+}
+
+impl<T> Drop for AbandonGuard<T>
+where
+    T: IdempotencyStore,
+{
+    fn drop(&mut self) {
+        if let Some(store) = self.store.take() {
+            let key = std::mem::take(&mut self.key);
+            tokio::spawn(async move {
+                if let Err(_err) = store.abandon(&key).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to release idempotency key");
+                }
+            });
+        }
+    }
+}
+
+/// A response kept in an [`IdempotencyStore`].
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl StoredResponse {
+    /// Create a new `StoredResponse`.
+    pub fn new(status: StatusCode, headers: HeaderMap, body: Bytes) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    /// The status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body of the response.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn replay(self) -> Response {
+        let mut res = Response::new(Body::from(self.body));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers;
+        res.headers_mut().insert(
+            HeaderName::from_static("idempotent-replayed"),
+            HeaderValue::from_static("true"),
+        );
+        res
+    }
+}
+
+/// The state of a key, returned by [`IdempotencyStore::begin`].
+#[derive(Debug, Clone)]
+pub enum IdempotencyState {
+    /// The key wasn't used before, and is now reserved for the request.
+    Started,
+    /// Another request with the key is being processed.
+    InFlight {
+        /// The fingerprint of the other request.
+        fingerprint: String,
+    },
+    /// A request with the key has completed.
+    Completed {
+        /// The fingerprint of the completed request.
+        fingerprint: String,
+        /// The response to the completed request.
+        response: StoredResponse,
+    },
+}
+
+/// Stores the responses of an [`IdempotencyLayer`].
+///
+/// Requests are identified by a fingerprint, made of their method, URI, and a hash of their body,
+/// so keys reused for different requests can be rejected.
+///
+/// [`begin`](Self::begin) must reserve keys atomically, so concurrent requests with the same key
+/// can't both start. For Redis this can be done with `SET key value NX PX ttl`.
+#[async_trait]
+pub trait IdempotencyStore: Clone + Send + Sync + 'static {
+    /// The error returned if the store fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Reserve `key` for a request, unless it's in use or has a stored response.
+    ///
+    /// Reserved keys and stored responses expire after `ttl`.
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        ttl: Duration,
+    ) -> Result<IdempotencyState, Self::Error>;
+
+    /// Store the response to the request `key` was reserved for.
+    async fn complete(
+        &self,
+        key: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Result<(), Self::Error>;
+
+    /// Release `key` without storing a response, so it can be used again.
+    async fn abandon(&self, key: &str) -> Result<(), Self::Error>;
+
+    /// Wait for a request with `key` to complete or be abandoned.
+    ///
+    /// This is called before checking `key` again with [`begin`](Self::begin). The default
+    /// implementation waits for 100 milliseconds.
+    async fn wait(&self, key: &str) {
+        let _ = key;
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// [`IdempotencyStore`] that keeps responses in memory.
+///
+/// Retries waiting for a request with the same key are woken up as soon as it completes. The
+/// store can be cloned to share it between layers.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryIdempotencyStore {
+    inner: Arc<MemoryInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryInner {
+    entries: Mutex<Entries>,
+    completed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    next_sweep: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    fingerprint: String,
+    response: Option<StoredResponse>,
+    expires: Instant,
+}
+
+/// Expired keys are only swept once there are at least this many.
+const MIN_SWEEP: usize = 1024;
+
+impl MemoryIdempotencyStore {
+    /// Create a new, empty `MemoryIdempotencyStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    type Error = std::convert::Infallible;
+
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        ttl: Duration,
+    ) -> Result<IdempotencyState, Self::Error> {
+        let now = Instant::now();
+        let mut entries = self.inner.entries.lock().unwrap();
+
+        if entries.map.len() >= entries.next_sweep.max(MIN_SWEEP) {
+            entries.map.retain(|_, entry| entry.expires > now);
+            entries.next_sweep = entries.map.len() * 2;
+        }
+
+        match entries.map.get(key) {
+            Some(entry) if entry.expires > now => {
+                let fingerprint = entry.fingerprint.clone();
+                Ok(match &entry.response {
+                    Some(response) => IdempotencyState::Completed {
+                        fingerprint,
+                        response: response.clone(),
+                    },
+                    None => IdempotencyState::InFlight { fingerprint },
+                })
+            }
+            _ => {
+                entries.map.insert(
+                    key.to_owned(),
+                    Entry {
+                        fingerprint: fingerprint.to_owned(),
+                        response: None,
+                        expires: now + ttl,
+                    },
+                );
+                Ok(IdempotencyState::Started)
+            }
+        }
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Result<(), Self::Error> {
+        if let Some(entry) = self.inner.entries.lock().unwrap().map.get_mut(key) {
+            entry.response = Some(response);
+            entry.expires = Instant::now() + ttl;
+        }
+        self.inner.completed.notify_waiters();
+        Ok(())
+    }
+
+    async fn abandon(&self, key: &str) -> Result<(), Self::Error> {
+        self.inner.entries.lock().unwrap().map.remove(key);
+        self.inner.completed.notify_waiters();
+        Ok(())
+    }
+
+    async fn wait(&self, _key: &str) {
+        // notifications sent between `begin` and here are missed, so don't wait forever
+        let _ = tokio::time::timeout(POLL_INTERVAL, self.inner.completed.notified()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn app(calls: Arc<AtomicUsize>, layer: IdempotencyLayer) -> Router {
+        let handler = move || {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                if call == 1 {
+                    (StatusCode::SERVICE_UNAVAILABLE, "try again").into_response()
+                } else {
+                    (StatusCode::CREATED, [("x-call", call.to_string())]).into_response()
+                }
+            }
+        };
+        Router::new()
+            .route("/a", post(handler.clone()))
+            .route("/b", post(handler))
+            .layer(layer)
+    }
+
+    #[tokio::test]
+    async fn replays_responses() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = TestClient::new(app(calls.clone(), IdempotencyLayer::new()));
+
+        // server errors aren't stored
+        let res = client.post("/a").header("idempotency-key", "abc").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let res = client.post("/a").header("idempotency-key", "abc").await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers()["x-call"], "2");
+        assert!(!res.headers().contains_key("idempotent-replayed"));
+
+        let res = client.post("/a").header("idempotency-key", "abc").await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers()["x-call"], "2");
+        assert_eq!(res.headers()["idempotent-replayed"], "true");
+
+        let res = client.post("/b").header("idempotency-key", "abc").await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = client.post("/a").header("idempotency-key", "").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client.post("/a").await;
+        assert_eq!(res.headers()["x-call"], "3");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn rejects_keys_reused_with_different_bodies() {
+        let calls = Arc::new(AtomicUsize::new(1));
+        let client = TestClient::new(app(calls.clone(), IdempotencyLayer::new()));
+
+        let res = client
+            .post("/a")
+            .header("idempotency-key", "abc")
+            .body("one")
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = client
+            .post("/a")
+            .header("idempotency-key", "abc")
+            .body("one")
+            .await;
+        assert_eq!(res.headers()["idempotent-replayed"], "true");
+
+        let res = client
+            .post("/a")
+            .header("idempotency-key", "abc")
+            .body("two")
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn scopes_keys() {
+        let calls = Arc::new(AtomicUsize::new(1));
+        let layer = IdempotencyLayer::new().scope(|req| {
+            let user = req.headers().get("x-user")?;
+            Some(user.to_str().ok()?.to_owned())
+        });
+        let client = TestClient::new(app(calls, layer));
+
+        for user in ["alice", "bob"] {
+            let res = client
+                .post("/a")
+                .header("idempotency-key", "abc")
+                .header("x-user", user)
+                .await;
+            assert_eq!(res.status(), StatusCode::CREATED);
+            assert!(!res.headers().contains_key("idempotent-replayed"));
+        }
+
+        let res = client
+            .post("/a")
+            .header("idempotency-key", "abc")
+            .header("x-user", "bob")
+            .await;
+        assert_eq!(res.headers()["idempotent-replayed"], "true");
+    }
+
+    #[tokio::test]
+    async fn coalesces_in_flight_requests() {
+        let calls = Arc::new(AtomicUsize::new(1));
+        let client = TestClient::new(app(calls.clone(), IdempotencyLayer::new()));
+
+        let (first, second) = tokio::join!(
+            async { client.post("/a").header("idempotency-key", "abc").await },
+            async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                client.post("/a").header("idempotency-key", "abc").await
+            },
+        );
+        assert_eq!(first.headers()["x-call"], "2");
+        assert_eq!(second.headers()["x-call"], "2");
+        assert_eq!(second.headers()["idempotent-replayed"], "true");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
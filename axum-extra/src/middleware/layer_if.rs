@@ -0,0 +1,159 @@
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::{Either, MapOk, TryFutureExt};
+use std::task::{ready, Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Apply a [`Layer`] only to requests that match a predicate.
+///
+/// Requests for which `predicate` returns `true` go through the middleware created by `layer`,
+/// others skip it and go straight to the inner service. This makes it possible to scope
+/// middleware by path prefix, header, method, etc, without splitting the router.
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, routing::get, extract::Request};
+/// use axum_extra::middleware::layer_if;
+/// use std::time::Duration;
+/// use tower_http::timeout::TimeoutLayer;
+///
+/// let app = Router::new()
+///     .route("/", get(|| async { /* ... */ }))
+///     .route("/api/users", get(|| async { /* ... */ }))
+///     // only time out API requests
+///     .layer(layer_if(
+///         |req: &Request| req.uri().path().starts_with("/api/"),
+///         TimeoutLayer::new(Duration::from_secs(10)),
+///     ));
+/// # let _: Router = app;
+/// ```
+///
+/// # Readiness
+///
+/// The service is only ready once both the middleware and the inner service are ready, since it
+/// isn't known yet which one the next request will go to.
+pub fn layer_if<P, L>(predicate: P, layer: L) -> ConditionalLayer<P, L>
+where
+    P: Fn(&Request) -> bool + Clone,
+{
+    ConditionalLayer { predicate, layer }
+}
+
+/// A [`Layer`] that applies another layer only to requests that match a predicate.
+///
+/// Created with [`layer_if`]. See that function for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct ConditionalLayer<P, L> {
+    predicate: P,
+    layer: L,
+}
+
+impl<S, P, L> Layer<S> for ConditionalLayer<P, L>
+where
+    S: Clone,
+    P: Clone,
+    L: Layer<S>,
+{
+    type Service = Conditional<P, S, L::Service>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Conditional {
+            predicate: self.predicate.clone(),
+            layered: self.layer.layer(inner.clone()),
+            inner,
+        }
+    }
+}
+
+/// Middleware that applies another middleware only to requests that match a predicate.
+///
+/// Created with [`layer_if`]. See that function for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct Conditional<P, S, T> {
+    predicate: P,
+    inner: S,
+    layered: T,
+}
+
+impl<P, S, T> Service<Request> for Conditional<P, S, T>
+where
+    P: Fn(&Request) -> bool,
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    T: Service<Request, Error = S::Error>,
+    T::Response: IntoResponse,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Either<
+        MapOk<T::Future, fn(T::Response) -> Response>,
+        MapOk<S::Future, fn(S::Response) -> Response>,
+    >;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.layered.poll_ready(cx))?;
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if (self.predicate)(&req) {
+            Either::Left(
+                self.layered
+                    .call(req)
+                    .map_ok(IntoResponse::into_response as _),
+            )
+        } else {
+            Either::Right(
+                self.inner
+                    .call(req)
+                    .map_ok(IntoResponse::into_response as _),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use http::{HeaderValue, Method};
+    use tower_http::set_header::SetResponseHeaderLayer;
+
+    #[tokio::test]
+    async fn applies_to_matching_requests() {
+        let app = Router::new()
+            .route("/", get(|| async {}).post(|| async {}))
+            .route("/admin", get(|| async {}))
+            .layer(layer_if(
+                |req: &Request| req.uri().path().starts_with("/admin"),
+                SetResponseHeaderLayer::overriding(
+                    http::header::CACHE_CONTROL,
+                    HeaderValue::from_static("no-store"),
+                ),
+            ))
+            .layer(layer_if(
+                |req: &Request| req.method() == Method::POST,
+                SetResponseHeaderLayer::overriding(
+                    http::header::HeaderName::from_static("x-post"),
+                    HeaderValue::from_static("1"),
+                ),
+            ));
+        let client = TestClient::new(app);
+
+        let res = client.get("/admin").await;
+        assert_eq!(res.headers()["cache-control"], "no-store");
+        assert!(res.headers().get("x-post").is_none());
+
+        let res = client.get("/").await;
+        assert!(res.headers().get("cache-control").is_none());
+
+        let res = client.post("/").await;
+        assert_eq!(res.headers()["x-post"], "1");
+        assert!(res.headers().get("cache-control").is_none());
+    }
+}
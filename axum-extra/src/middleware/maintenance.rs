@@ -0,0 +1,321 @@
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use http::{header, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that answers requests with `503 Service Unavailable` while maintenance mode is enabled.
+///
+/// Maintenance mode is toggled at runtime with the [`MaintenanceHandle`] returned by
+/// [`handle`](Self::handle), for example from an admin route or a signal handler, without
+/// restarting the server. Requests to [allowed](Self::allow) paths, such as health checks and
+/// admin routes, are always let through.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::{get, post}, Extension};
+/// use axum_extra::middleware::{MaintenanceHandle, MaintenanceMode};
+///
+/// async fn toggle(Extension(handle): Extension<MaintenanceHandle>, enable: String) {
+///     if enable == "true" {
+///         handle.enable();
+///     } else {
+///         handle.disable();
+///     }
+/// }
+///
+/// let maintenance = MaintenanceMode::new().allow("/health").allow("/admin");
+/// let handle = maintenance.handle();
+///
+/// let app = Router::new()
+///     .route("/", get(|| async { "Hello, World!" }))
+///     .route("/health", get(|| async {}))
+///     .route("/admin/maintenance", post(toggle))
+///     .layer(maintenance)
+///     .layer(Extension(handle));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode<R = fn() -> Response> {
+    handle: MaintenanceHandle,
+    allowed: Arc<[String]>,
+    response: R,
+    retry_after: Option<Duration>,
+}
+
+impl MaintenanceMode {
+    /// Create a new `MaintenanceMode`, initially disabled.
+    pub fn new() -> Self {
+        Self {
+            handle: MaintenanceHandle::default(),
+            allowed: Arc::new([]),
+            response: default_response,
+            retry_after: None,
+        }
+    }
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn default_response() -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        "The service is down for maintenance",
+    )
+        .into_response()
+}
+
+impl<R> MaintenanceMode<R> {
+    /// The handle that toggles maintenance mode.
+    ///
+    /// All clones of the layer and its handles toggle the same maintenance mode.
+    pub fn handle(&self) -> MaintenanceHandle {
+        self.handle.clone()
+    }
+
+    /// Let requests to `path`, and paths below it, through while maintenance mode is enabled.
+    ///
+    /// For example `/admin` allows `/admin` and `/admin/users`, but not `/administrator`.
+    pub fn allow(mut self, path: impl Into<String>) -> Self {
+        let mut path = path.into();
+        if path.len() > 1 && path.ends_with('/') {
+            path.pop();
+        }
+        self.allowed = self.allowed.iter().cloned().chain([path]).collect();
+        self
+    }
+
+    /// Set the response sent while maintenance mode is enabled.
+    ///
+    /// The status of the response is set to `503 Service Unavailable`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::response::{Html, IntoResponse};
+    /// use axum_extra::middleware::MaintenanceMode;
+    ///
+    /// let layer = MaintenanceMode::new().response(|| {
+    ///     Html("<h1>We'll be back soon!</h1>").into_response()
+    /// });
+    /// ```
+    pub fn response<T>(self, response: T) -> MaintenanceMode<T>
+    where
+        T: Fn() -> Response + Clone + Send + Sync + 'static,
+    {
+        MaintenanceMode {
+            handle: self.handle,
+            allowed: self.allowed,
+            response,
+            retry_after: self.retry_after,
+        }
+    }
+
+    /// Set the `Retry-After` header of the response sent while maintenance mode is enabled.
+    ///
+    /// Defaults to none.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+}
+
+impl<S, R> Layer<S> for MaintenanceMode<R>
+where
+    R: Clone,
+{
+    type Service = MaintenanceModeService<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaintenanceModeService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Handle that toggles the maintenance mode of a [`MaintenanceMode`] layer.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceHandle {
+    enabled: Arc<AtomicBool>,
+}
+
+impl MaintenanceHandle {
+    /// Enable maintenance mode.
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Disable maintenance mode.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether maintenance mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+/// Middleware that answers requests with `503 Service Unavailable` while maintenance mode is
+/// enabled.
+///
+/// Created with [`MaintenanceMode`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct MaintenanceModeService<S, R = fn() -> Response> {
+    inner: S,
+    layer: MaintenanceMode<R>,
+}
+
+impl<S, R> Service<Request> for MaintenanceModeService<S, R>
+where
+    S: Service<Request, Response = Response>,
+    R: Fn() -> Response,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = MaintenanceModeFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let path = req.uri().path();
+        let allowed = self.layer.allowed.iter().any(|allowed| {
+            allowed == "/"
+                || matches!(
+                    path.strip_prefix(allowed.as_str()),
+                    Some(rest) if rest.is_empty() || rest.starts_with('/')
+                )
+        });
+
+        if !self.layer.handle.is_enabled() || allowed {
+            return MaintenanceModeFuture {
+                inner: Some(self.inner.call(req)),
+                response: None,
+            };
+        }
+
+        let mut res = (self.layer.response)();
+        *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        if let Some(retry_after) = self.layer.retry_after {
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, seconds.into());
+        }
+        MaintenanceModeFuture {
+            inner: None,
+            response: Some(res),
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`MaintenanceModeService`].
+    pub struct MaintenanceModeFuture<F> {
+        #[pin]
+        inner: Option<F>,
+        response: Option<Response>,
+    }
+}
+
+impl<F, E> Future for MaintenanceModeFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Some(res) = this.response.take() {
+            return Poll::Ready(Ok(res));
+        }
+        this.inner
+            .as_pin_mut()
+            .expect("future polled after completion")
+            .poll(cx)
+    }
+}
+
+impl<F> fmt::Debug for MaintenanceModeFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaintenanceModeFuture")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    #[tokio::test]
+    async fn toggles() {
+        let maintenance = MaintenanceMode::new()
+            .allow("/health")
+            .allow("/admin/")
+            .retry_after(Duration::from_secs(60));
+        let handle = maintenance.handle();
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .route("/health", get(|| async {}))
+            .route("/admin/users", get(|| async {}))
+            .route("/administrator", get(|| async {}))
+            .layer(maintenance);
+        let client = TestClient::new(app);
+
+        for uri in ["/", "/administrator"] {
+            assert_eq!(client.get(uri).await.status(), StatusCode::OK);
+        }
+
+        handle.enable();
+        for uri in ["/", "/administrator"] {
+            let res = client.get(uri).await;
+            assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(res.headers()["retry-after"], "60");
+            assert_eq!(res.text().await, "The service is down for maintenance");
+        }
+        for uri in ["/health", "/admin/users"] {
+            assert_eq!(client.get(uri).await.status(), StatusCode::OK);
+        }
+
+        handle.disable();
+        assert_eq!(client.get("/").await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn custom_response() {
+        let maintenance = MaintenanceMode::new().response(|| "back soon".into_response());
+        maintenance.handle().enable();
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .layer(maintenance);
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.text().await, "back soon");
+    }
+}
@@ -0,0 +1,262 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    response::Response,
+};
+use http::Method;
+use metrics::Label;
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Instant,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+const REQUESTS_TOTAL: &str = "http_requests_total";
+const REQUESTS_IN_FLIGHT: &str = "http_requests_in_flight";
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+
+/// Layer that records metrics about requests with the [`metrics`] crate.
+///
+/// These metrics are recorded, labeled by the `method` and the matched `route`, such as
+/// `/users/:id`:
+///
+/// - `http_requests_total`: counter of completed requests, also labeled by the `status` class,
+///   such as `2xx`.
+/// - `http_requests_in_flight`: gauge of requests being processed.
+/// - `http_request_duration_seconds`: histogram of the time until the response is returned,
+///   also labeled by the `status` class. This doesn't include the time it takes to send the
+///   response body.
+///
+/// Requests that didn't match a route, such as those handled by the fallback, are labeled with
+/// the route `unmatched`, and requests with non-standard methods with the method `OTHER`, so the
+/// number of labels stays bounded.
+///
+/// The metrics are sent to the recorder installed with the [`metrics`] crate, such as the one
+/// from `metrics-exporter-prometheus`. Without a recorder, this layer does nothing.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::middleware::MetricsLayer;
+///
+/// let app = Router::new()
+///     .route("/users/:id", get(|| async {}))
+///     .layer(MetricsLayer::new());
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsLayer {
+    _priv: (),
+}
+
+impl MetricsLayer {
+    /// Create a new `MetricsLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = Metrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Metrics { inner }
+    }
+}
+
+/// Middleware that records metrics about requests with the [`metrics`] crate.
+///
+/// Created with [`MetricsLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct Metrics<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for Metrics<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = MetricsFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_owned())
+            .unwrap_or_else(|| "unmatched".to_owned());
+        let labels = vec![
+            Label::new("method", method_label(req.method())),
+            Label::new("route", route),
+        ];
+
+        MetricsFuture {
+            inner: self.inner.call(req),
+            in_flight: Some(InFlight::new(labels)),
+            start: Instant::now(),
+        }
+    }
+}
+
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        Method::CONNECT => "CONNECT",
+        Method::PATCH => "PATCH",
+        Method::TRACE => "TRACE",
+        _ => "OTHER",
+    }
+}
+
+/// Counts a request as in flight until it is dropped, even if its future is dropped early.
+struct InFlight {
+    labels: Vec<Label>,
+}
+
+impl InFlight {
+    fn new(labels: Vec<Label>) -> Self {
+        ::metrics::increment_gauge!(REQUESTS_IN_FLIGHT, 1.0, labels.clone());
+        Self { labels }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        ::metrics::decrement_gauge!(REQUESTS_IN_FLIGHT, 1.0, self.labels.clone());
+    }
+}
+
+pin_project! {
+    /// Response future for [`Metrics`].
+    pub struct MetricsFuture<F> {
+        #[pin]
+        inner: F,
+        in_flight: Option<InFlight>,
+        start: Instant,
+    }
+}
+
+impl<F, E> Future for MetricsFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.inner.poll(cx));
+
+        let in_flight = this
+            .in_flight
+            .take()
+            .expect("future polled after completion");
+        if let Ok(res) = &result {
+            let status = match res.status().as_u16() {
+                100..=199 => "1xx",
+                200..=299 => "2xx",
+                300..=399 => "3xx",
+                400..=499 => "4xx",
+                _ => "5xx",
+            };
+            let mut labels = in_flight.labels.clone();
+            labels.push(Label::new("status", status));
+            ::metrics::histogram!(
+                REQUEST_DURATION,
+                this.start.elapsed().as_secs_f64(),
+                labels.clone()
+            );
+            ::metrics::increment_counter!(REQUESTS_TOTAL, labels);
+        }
+
+        Poll::Ready(result)
+    }
+}
+
+impl<F> fmt::Debug for MetricsFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsFuture").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use http::StatusCode;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+    #[tokio::test]
+    async fn records_metrics() {
+        DebuggingRecorder::per_thread().install().unwrap();
+
+        let app = Router::new()
+            .route("/users/:id", get(|| async {}))
+            .layer(MetricsLayer::new());
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/users/1").await.status(), StatusCode::OK);
+        assert_eq!(client.get("/users/2").await.status(), StatusCode::OK);
+        assert_eq!(client.get("/nope").await.status(), StatusCode::NOT_FOUND);
+
+        // the server runs on the same thread, since `tokio::test` uses a single threaded runtime
+        let metrics = Snapshotter::current_thread_snapshot()
+            .unwrap()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let mut labels = key
+                    .labels()
+                    .map(|label| format!("{}={}", label.key(), label.value()))
+                    .collect::<Vec<_>>();
+                labels.sort();
+                (format!("{}{{{}}}", key.name(), labels.join(",")), value)
+            })
+            .collect::<Vec<_>>();
+        let metric = |name: &str| {
+            metrics
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value)
+                .unwrap_or_else(|| panic!("missing {name} in {metrics:?}"))
+        };
+
+        assert_eq!(
+            metric("http_requests_total{method=GET,route=/users/:id,status=2xx}"),
+            &DebugValue::Counter(2)
+        );
+        assert_eq!(
+            metric("http_requests_total{method=GET,route=unmatched,status=4xx}"),
+            &DebugValue::Counter(1)
+        );
+        let DebugValue::Histogram(durations) =
+            metric("http_request_duration_seconds{method=GET,route=/users/:id,status=2xx}")
+        else {
+            panic!("expected a histogram");
+        };
+        assert_eq!(durations.len(), 2);
+        let DebugValue::Gauge(in_flight) =
+            metric("http_requests_in_flight{method=GET,route=/users/:id}")
+        else {
+            panic!("expected a gauge");
+        };
+        assert_eq!(in_flight.into_inner(), 0.0);
+    }
+}
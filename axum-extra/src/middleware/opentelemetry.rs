@@ -0,0 +1,306 @@
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request},
+    response::Response,
+};
+use http::{header, HeaderMap, HeaderName, HeaderValue, Method, Version};
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector},
+};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{field::Empty, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Layer that creates a [`tracing`] span for each request, following the OpenTelemetry semantic
+/// conventions for HTTP servers.
+///
+/// The span has these attributes, which `tracing-opentelemetry` exports to OpenTelemetry:
+///
+/// - `http.request.method`, `http.route` (from [`MatchedPath`]), `url.path`, `url.query`,
+///   `url.scheme`, `network.protocol.version`, `server.address`, and `user_agent.original`.
+/// - `client.address`, `network.peer.address`, and `network.peer.port`, if the server was
+///   started with [`into_make_service_with_connect_info`], which provides [`ConnectInfo`].
+/// - `http.response.status_code`, once the response is returned.
+/// - `error.type` and `otel.status_code`, if the response is a server error or the inner service
+///   failed.
+///
+/// The span is named after the method and the route, such as `GET /users/:id`, and is the
+/// current span while the request is handled.
+///
+/// The parent of the span is extracted from the headers of the request, such as the W3C
+/// `traceparent` header, and the context of the span is injected into the headers of the
+/// response, with the propagator set with
+/// [`opentelemetry::global::set_text_map_propagator`]. The default propagator doesn't propagate
+/// anything.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::middleware::OtelTraceLayer;
+///
+/// // with the `tracing-opentelemetry` layer added to the `tracing` subscriber, and a
+/// // propagator such as `opentelemetry_sdk::propagation::TraceContextPropagator` set
+///
+/// let app = Router::new()
+///     .route("/users/:id", get(|| async {}))
+///     .layer(OtelTraceLayer::new());
+/// # let _: Router = app;
+/// ```
+///
+/// [`into_make_service_with_connect_info`]: axum::Router::into_make_service_with_connect_info
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OtelTraceLayer {
+    _priv: (),
+}
+
+impl OtelTraceLayer {
+    /// Create a new `OtelTraceLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for OtelTraceLayer {
+    type Service = OtelTrace<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OtelTrace { inner }
+    }
+}
+
+/// Middleware that creates a [`tracing`] span for each request, following the OpenTelemetry
+/// semantic conventions for HTTP servers.
+///
+/// Created with [`OtelTraceLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct OtelTrace<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for OtelTrace<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = OtelTraceFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let span = make_span(&req);
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(req.headers()))
+        });
+        span.set_parent(parent);
+
+        let inner = {
+            let _guard = span.enter();
+            self.inner.call(req)
+        };
+        OtelTraceFuture { inner, span }
+    }
+}
+
+fn make_span(req: &Request) -> Span {
+    let method = method_attribute(req.method());
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+    let name = match route {
+        Some(route) => format!("{method} {route}"),
+        None => method.to_owned(),
+    };
+    let header_value = |name: HeaderName| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+
+    let span = tracing::info_span!(
+        "HTTP request",
+        otel.name = %name,
+        otel.kind = "server",
+        otel.status_code = Empty,
+        http.request.method = method,
+        http.route = Empty,
+        http.response.status_code = Empty,
+        url.path = req.uri().path(),
+        url.query = Empty,
+        url.scheme = req.uri().scheme_str().unwrap_or("http"),
+        network.protocol.version = version_attribute(req.version()),
+        server.address = Empty,
+        user_agent.original = Empty,
+        client.address = Empty,
+        network.peer.address = Empty,
+        network.peer.port = Empty,
+        error.r#type = Empty,
+    );
+
+    if let Some(route) = route {
+        span.record("http.route", route);
+    }
+    if let Some(query) = req.uri().query() {
+        span.record("url.query", query);
+    }
+    if let Some(host) = req.uri().host().or_else(|| header_value(header::HOST)) {
+        span.record("server.address", host);
+    }
+    if let Some(user_agent) = header_value(header::USER_AGENT) {
+        span.record("user_agent.original", user_agent);
+    }
+    if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        let ip = addr.ip().to_string();
+        span.record("client.address", ip.as_str());
+        span.record("network.peer.address", ip.as_str());
+        span.record("network.peer.port", addr.port());
+    }
+
+    span
+}
+
+fn method_attribute(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        Method::CONNECT => "CONNECT",
+        Method::PATCH => "PATCH",
+        Method::TRACE => "TRACE",
+        _ => "_OTHER",
+    }
+}
+
+fn version_attribute(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "0.9",
+        Version::HTTP_10 => "1.0",
+        Version::HTTP_11 => "1.1",
+        Version::HTTP_2 => "2",
+        Version::HTTP_3 => "3",
+        _ => "",
+    }
+}
+
+pin_project! {
+    /// Response future for [`OtelTrace`].
+    pub struct OtelTraceFuture<F> {
+        #[pin]
+        inner: F,
+        span: Span,
+    }
+}
+
+impl<F, E> Future for OtelTraceFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.span.enter();
+        let mut result = ready!(this.inner.poll(cx));
+
+        match &mut result {
+            Ok(res) => {
+                let status = res.status();
+                this.span
+                    .record("http.response.status_code", status.as_u16());
+                if status.is_server_error() {
+                    this.span.record("otel.status_code", "ERROR");
+                    this.span.record("error.type", status.as_str());
+                }
+
+                let context = this.span.context();
+                global::get_text_map_propagator(|propagator| {
+                    propagator.inject_context(&context, &mut HeaderInjector(res.headers_mut()))
+                });
+            }
+            Err(_) => {
+                this.span.record("otel.status_code", "ERROR");
+                this.span.record("error.type", "_OTHER");
+            }
+        }
+
+        Poll::Ready(result)
+    }
+}
+
+impl<F> fmt::Debug for OtelTraceFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtelTraceFuture").finish_non_exhaustive()
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::try_from(value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn passes_requests_through() {
+        let app = Router::new()
+            .route("/users/:id", get(|| async {}))
+            .layer(OtelTraceLayer::new());
+        let client = TestClient::new(app);
+
+        let res = client.get("/users/1").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn headers() {
+        let mut headers = HeaderMap::new();
+        HeaderInjector(&mut headers).set("traceparent", "00-abc-def-01".to_owned());
+        HeaderInjector(&mut headers).set("invalid header", "value".to_owned());
+
+        let extractor = HeaderExtractor(&headers);
+        assert_eq!(extractor.get("traceparent"), Some("00-abc-def-01"));
+        assert_eq!(extractor.keys(), ["traceparent"]);
+    }
+}
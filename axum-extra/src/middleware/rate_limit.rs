@@ -0,0 +1,795 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, MatchedPath, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that limits how many requests a client can make.
+///
+/// Clients are identified by a key extracted from the request with `K`, which can be any
+/// [`FromRequestParts`] extractor that implements [`Display`](fmt::Display), such as `ClientIp`
+/// or extractors for an API key or the authenticated user. If the extractor rejects the request
+/// its rejection is returned and the request isn't counted.
+///
+/// Every key gets the same [`Quota`], tracked in a [`RateLimitStore`]. By default the
+/// [`MemoryStore`] is used, which only works for a single server. Implement [`RateLimitStore`]
+/// for a shared database, such as Redis, to limit requests across several servers.
+///
+/// Requests over the limit get a `429 Too Many Requests` response with a `Retry-After` header.
+/// All responses get the `RateLimit-Limit`, `RateLimit-Remaining`, `RateLimit-Reset`, and
+/// `RateLimit-Policy` headers from the [IETF draft], with times in seconds.
+///
+/// If the store fails the request is allowed, so an outage of the store doesn't take down the
+/// app.
+///
+/// [IETF draft]: https://datatracker.ietf.org/doc/draft-ietf-httpapi-ratelimit-headers/
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     async_trait,
+///     extract::FromRequestParts,
+///     http::{request::Parts, StatusCode},
+///     routing::{get, post},
+///     Router,
+/// };
+/// use axum_extra::middleware::{Quota, RateLimitLayer};
+/// use std::{fmt, time::Duration};
+///
+/// struct ApiKey(String);
+///
+/// #[async_trait]
+/// impl<S> FromRequestParts<S> for ApiKey
+/// where
+///     S: Send + Sync,
+/// {
+///     type Rejection = StatusCode;
+///
+///     async fn from_request_parts(
+///         parts: &mut Parts,
+///         _state: &S,
+///     ) -> Result<Self, Self::Rejection> {
+///         parts
+///             .headers
+///             .get("x-api-key")
+///             .and_then(|value| value.to_str().ok())
+///             .map(|key| Self(key.to_owned()))
+///             .ok_or(StatusCode::UNAUTHORIZED)
+///     }
+/// }
+///
+/// impl fmt::Display for ApiKey {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str(&self.0)
+///     }
+/// }
+///
+/// let app = Router::new()
+///     .route("/search", get(|| async {}))
+///     .route_layer(RateLimitLayer::<ApiKey>::new(Quota::sliding_window(
+///         10,
+///         Duration::from_secs(1),
+///     )))
+///     .route("/users", get(|| async {}).post(|| async {}))
+///     .route("/orders", post(|| async {}))
+///     // every route of an API key gets its own bucket of 100 requests per minute
+///     .layer(
+///         RateLimitLayer::<ApiKey>::new(Quota::token_bucket(100, Duration::from_secs(60)))
+///             .per_route(true),
+///     );
+/// # let _: Router = app;
+/// ```
+///
+/// # Route-aware limits
+///
+/// With [`per_route`](Self::per_route) every route is limited separately, using the route it
+/// matched such as `/users/:id`, rather than the exact path. Requests that didn't match a route,
+/// such as those handled by the fallback, share a quota. Use [`Router::route_layer`] to give some
+/// routes a stricter quota than the rest of the app, as above.
+///
+/// [`Router::route_layer`]: axum::Router::route_layer
+pub struct RateLimitLayer<K, T = MemoryStore, S = ()> {
+    quota: Quota,
+    store: T,
+    state: S,
+    per_route: bool,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<K> RateLimitLayer<K> {
+    /// Create a new `RateLimitLayer` that limits each key to `quota`, using a [`MemoryStore`].
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            store: MemoryStore::new(),
+            state: (),
+            per_route: false,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<K, T, S> RateLimitLayer<K, T, S> {
+    /// Set the store that tracks the requests of each key.
+    ///
+    /// Layers sharing a store should use different quotas only with
+    /// [`per_route`](Self::per_route), or the quotas are applied to the same key.
+    pub fn store<U>(self, store: U) -> RateLimitLayer<K, U, S>
+    where
+        U: RateLimitStore,
+    {
+        RateLimitLayer {
+            quota: self.quota,
+            store,
+            state: self.state,
+            per_route: self.per_route,
+            _key: PhantomData,
+        }
+    }
+
+    /// Set the state the key is extracted with.
+    ///
+    /// This is required for extractors that need state, such as `ClientIp`.
+    pub fn with_state<S2>(self, state: S2) -> RateLimitLayer<K, T, S2> {
+        RateLimitLayer {
+            quota: self.quota,
+            store: self.store,
+            state,
+            per_route: self.per_route,
+            _key: PhantomData,
+        }
+    }
+
+    /// Set whether every route is limited separately.
+    ///
+    /// Defaults to `false`, so all routes behind the layer share the quota of a key.
+    pub fn per_route(mut self, per_route: bool) -> Self {
+        self.per_route = per_route;
+        self
+    }
+}
+
+impl<K, T, S> Clone for RateLimitLayer<K, T, S>
+where
+    T: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            quota: self.quota,
+            store: self.store.clone(),
+            state: self.state.clone(),
+            per_route: self.per_route,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<K, T, S> fmt::Debug for RateLimitLayer<K, T, S>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitLayer")
+            .field("quota", &self.quota)
+            .field("store", &self.store)
+            .field("per_route", &self.per_route)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, K, T, S> Layer<I> for RateLimitLayer<K, T, S>
+where
+    T: Clone,
+    S: Clone,
+{
+    type Service = RateLimit<I, K, T, S>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        RateLimit {
+            inner,
+            quota: self.quota,
+            store: self.store.clone(),
+            state: self.state.clone(),
+            per_route: self.per_route,
+            _key: PhantomData,
+        }
+    }
+}
+
+/// Middleware that limits how many requests a client can make.
+///
+/// Created with [`RateLimitLayer`]. See that type for more details.
+pub struct RateLimit<I, K, T = MemoryStore, S = ()> {
+    inner: I,
+    quota: Quota,
+    store: T,
+    state: S,
+    per_route: bool,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<I, K, T, S> Clone for RateLimit<I, K, T, S>
+where
+    I: Clone,
+    T: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            quota: self.quota,
+            store: self.store.clone(),
+            state: self.state.clone(),
+            per_route: self.per_route,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<I, K, T, S> fmt::Debug for RateLimit<I, K, T, S>
+where
+    I: fmt::Debug,
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimit")
+            .field("inner", &self.inner)
+            .field("quota", &self.quota)
+            .field("store", &self.store)
+            .field("per_route", &self.per_route)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, K, T, S> Service<Request> for RateLimit<I, K, T, S>
+where
+    I: Service<Request, Response = Response> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+    K: FromRequestParts<S> + fmt::Display + 'static,
+    T: RateLimitStore,
+    S: Clone + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = I::Error;
+    type Future = BoxFuture<'static, Result<Response, I::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let quota = self.quota;
+        let store = self.store.clone();
+        let state = self.state.clone();
+        let per_route = self.per_route;
+
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+            let key = match K::from_request_parts(&mut parts, &state).await {
+                Ok(key) => key.to_string(),
+                Err(rejection) => return Ok(rejection.into_response()),
+            };
+            let key = match parts.extensions.get::<MatchedPath>() {
+                Some(path) if per_route => format!("{}:{key}", path.as_str()),
+                _ => key,
+            };
+
+            let decision = match store.check(&key, &quota).await {
+                Ok(decision) => Some(decision),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to check rate limit");
+
+                    None
+                }
+            };
+
+            if let Some(decision) = decision.filter(|decision| !decision.is_allowed()) {
+                let mut res = StatusCode::TOO_MANY_REQUESTS.into_response();
+                insert_headers(res.headers_mut(), &quota, &decision);
+                return Ok(res);
+            }
+
+            let mut res = inner.call(Request::from_parts(parts, body)).await?;
+            if let Some(decision) = decision {
+                insert_headers(res.headers_mut(), &quota, &decision);
+            }
+            Ok(res)
+        })
+    }
+}
+
+fn insert_headers(headers: &mut HeaderMap, quota: &Quota, decision: &RateLimitDecision) {
+    fn seconds(duration: Duration) -> HeaderValue {
+        let rounded_up = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+        rounded_up.into()
+    }
+
+    headers.insert(
+        HeaderName::from_static("ratelimit-limit"),
+        decision.limit.into(),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-remaining"),
+        decision.remaining.into(),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-reset"),
+        seconds(decision.reset),
+    );
+    let policy = format!("{};w={}", quota.limit, quota.window.as_secs());
+    headers.insert(
+        HeaderName::from_static("ratelimit-policy"),
+        HeaderValue::try_from(policy).expect("policy is a valid header value"),
+    );
+    if let Some(retry_after) = decision.retry_after {
+        headers.insert(header::RETRY_AFTER, seconds(retry_after));
+    }
+}
+
+/// How many requests a key can make, and the algorithm used to limit them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    limit: u32,
+    window: Duration,
+    algorithm: Algorithm,
+}
+
+impl Quota {
+    /// Allow bursts of up to `limit` requests, refilled at a rate of `limit` requests per
+    /// `window`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero or `window` is shorter than a second.
+    #[track_caller]
+    pub fn token_bucket(limit: u32, window: Duration) -> Self {
+        Self::new(limit, window, Algorithm::TokenBucket)
+    }
+
+    /// Allow up to `limit` requests in any `window`.
+    ///
+    /// The count is approximated from the number of requests in the current and previous
+    /// windows, so only two counters are stored per key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero or `window` is shorter than a second.
+    #[track_caller]
+    pub fn sliding_window(limit: u32, window: Duration) -> Self {
+        Self::new(limit, window, Algorithm::SlidingWindow)
+    }
+
+    #[track_caller]
+    fn new(limit: u32, window: Duration, algorithm: Algorithm) -> Self {
+        assert!(limit > 0, "rate limit must be greater than zero");
+        assert!(
+            window >= Duration::from_secs(1),
+            "rate limit window must be at least one second"
+        );
+        Self {
+            limit,
+            window,
+            algorithm,
+        }
+    }
+
+    /// The number of requests allowed per window.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// The duration of the window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The algorithm used to limit requests.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+/// Algorithm used to limit requests. See [`Quota`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// See [`Quota::token_bucket`].
+    TokenBucket,
+    /// See [`Quota::sliding_window`].
+    SlidingWindow,
+}
+
+/// The outcome of checking a request against a [`Quota`], returned by a [`RateLimitStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    limit: u32,
+    remaining: u32,
+    reset: Duration,
+    retry_after: Option<Duration>,
+}
+
+impl RateLimitDecision {
+    /// The request is allowed, and `remaining` more requests are allowed before the limit is
+    /// reached.
+    ///
+    /// `reset` is the time until the quota is restored.
+    pub fn allowed(limit: u32, remaining: u32, reset: Duration) -> Self {
+        Self {
+            limit,
+            remaining,
+            reset,
+            retry_after: None,
+        }
+    }
+
+    /// The request is over the limit, and the client should retry after `retry_after`.
+    ///
+    /// `reset` is the time until the quota is restored.
+    pub fn denied(limit: u32, reset: Duration, retry_after: Duration) -> Self {
+        Self {
+            limit,
+            remaining: 0,
+            reset,
+            retry_after: Some(retry_after),
+        }
+    }
+
+    /// Whether the request is allowed.
+    pub fn is_allowed(&self) -> bool {
+        self.retry_after.is_none()
+    }
+
+    /// The number of requests allowed per window.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// The number of requests allowed before the limit is reached.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// The time until the quota is restored.
+    pub fn reset(&self) -> Duration {
+        self.reset
+    }
+
+    /// The time the client should wait before retrying, if the request is over the limit.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+}
+
+/// Tracks the requests of each key for a [`RateLimitLayer`].
+///
+/// Checking a key must count the request, if it's allowed, atomically, so concurrent requests
+/// can't exceed the limit. For Redis this can be done with a Lua script implementing the
+/// [`Algorithm`] of the quota.
+#[async_trait]
+pub trait RateLimitStore: Clone + Send + Sync + 'static {
+    /// The error returned if checking a key fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Check whether a request with `key` is allowed by `quota`, and count it if it is.
+    async fn check(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, Self::Error>;
+}
+
+/// [`RateLimitStore`] that keeps the requests of each key in memory.
+///
+/// Keys that haven't made requests for a while are removed, to bound memory usage. The store can
+/// be cloned to share it between layers.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    inner: Arc<Mutex<MemoryStoreInner>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryStoreInner {
+    entries: HashMap<String, Entry>,
+    next_sweep: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    state: State,
+    // the time after which the state is the same as a fresh one
+    expires: Instant,
+}
+
+#[derive(Debug)]
+enum State {
+    TokenBucket {
+        tokens: f64,
+        updated: Instant,
+    },
+    SlidingWindow {
+        start: Instant,
+        current: u32,
+        previous: u32,
+    },
+}
+
+/// Keys are only swept once there are at least this many.
+const MIN_SWEEP: usize = 1024;
+
+impl MemoryStore {
+    /// Create a new, empty `MemoryStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn check_at(&self, key: &str, quota: &Quota, now: Instant) -> RateLimitDecision {
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.entries.len() >= inner.next_sweep.max(MIN_SWEEP) {
+            inner.entries.retain(|_, entry| entry.expires > now);
+            inner.next_sweep = inner.entries.len() * 2;
+        }
+
+        let entry = inner
+            .entries
+            .entry(key.to_owned())
+            .or_insert_with(|| Entry::new(quota, now));
+        if !entry.state.matches(quota.algorithm) {
+            *entry = Entry::new(quota, now);
+        }
+        entry.check(quota, now)
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    type Error = Infallible;
+
+    async fn check(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, Self::Error> {
+        Ok(self.check_at(key, quota, Instant::now()))
+    }
+}
+
+impl Entry {
+    fn new(quota: &Quota, now: Instant) -> Self {
+        let state = match quota.algorithm {
+            Algorithm::TokenBucket => State::TokenBucket {
+                tokens: f64::from(quota.limit),
+                updated: now,
+            },
+            Algorithm::SlidingWindow => State::SlidingWindow {
+                start: now,
+                current: 0,
+                previous: 0,
+            },
+        };
+        Self {
+            state,
+            expires: now,
+        }
+    }
+
+    fn check(&mut self, quota: &Quota, now: Instant) -> RateLimitDecision {
+        let limit = f64::from(quota.limit);
+        let window = quota.window;
+
+        match &mut self.state {
+            State::TokenBucket { tokens, updated } => {
+                // `limit` tokens are added per window
+                let elapsed = now.saturating_duration_since(*updated);
+                let refilled = elapsed.as_secs_f64() / window.as_secs_f64() * limit;
+                *tokens = (*tokens + refilled).min(limit);
+                *updated = now;
+
+                let decision = if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    let reset = window.mul_f64((limit - *tokens) / limit);
+                    RateLimitDecision::allowed(quota.limit, *tokens as u32, reset)
+                } else {
+                    let reset = window.mul_f64((limit - *tokens) / limit);
+                    let retry_after = window.mul_f64((1.0 - *tokens) / limit);
+                    RateLimitDecision::denied(quota.limit, reset, retry_after)
+                };
+                self.expires = now + decision.reset;
+                decision
+            }
+            State::SlidingWindow {
+                start,
+                current,
+                previous,
+            } => {
+                let elapsed = now.saturating_duration_since(*start);
+                if elapsed >= window * 2 {
+                    *start = now;
+                    *current = 0;
+                    *previous = 0;
+                } else if elapsed >= window {
+                    *start += window;
+                    *previous = *current;
+                    *current = 0;
+                }
+                self.expires = *start + window * 2;
+
+                let elapsed = now.saturating_duration_since(*start);
+                let reset = window - elapsed;
+                let weight = 1.0 - elapsed.as_secs_f64() / window.as_secs_f64();
+                let estimate = f64::from(*previous) * weight + f64::from(*current);
+
+                if estimate + 1.0 <= limit {
+                    *current += 1;
+                    let remaining = (limit - estimate - 1.0).max(0.0) as u32;
+                    return RateLimitDecision::allowed(quota.limit, remaining, reset);
+                }
+
+                let excess = estimate + 1.0 - limit;
+                let retry_after = if *previous > 0 && excess <= f64::from(*previous) * weight {
+                    // wait for enough of the previous window to slide out
+                    window.mul_f64(excess / f64::from(*previous))
+                } else {
+                    // wait for the current window to end and enough of it to slide out
+                    let allowed = (limit - 1.0) / f64::from((*current).max(1));
+                    reset + window.mul_f64((1.0 - allowed).max(0.0))
+                };
+                RateLimitDecision::denied(quota.limit, reset, retry_after)
+            }
+        }
+    }
+}
+
+impl State {
+    fn matches(&self, algorithm: Algorithm) -> bool {
+        matches!(
+            (self, algorithm),
+            (Self::TokenBucket { .. }, Algorithm::TokenBucket)
+                | (Self::SlidingWindow { .. }, Algorithm::SlidingWindow)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use http::request::Parts;
+
+    struct Key(String);
+
+    #[async_trait]
+    impl<S> FromRequestParts<S> for Key
+    where
+        S: Send + Sync,
+    {
+        type Rejection = StatusCode;
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            parts
+                .headers
+                .get("x-key")
+                .and_then(|value| value.to_str().ok())
+                .map(|key| Self(key.to_owned()))
+                .ok_or(StatusCode::UNAUTHORIZED)
+        }
+    }
+
+    impl fmt::Display for Key {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn token_bucket() {
+        let store = MemoryStore::new();
+        let quota = Quota::token_bucket(2, MINUTE);
+        let now = Instant::now();
+
+        let decision = store.check_at("a", &quota, now);
+        assert_eq!(decision, RateLimitDecision::allowed(2, 1, MINUTE / 2));
+        assert!(store.check_at("a", &quota, now).is_allowed());
+
+        let decision = store.check_at("a", &quota, now);
+        assert_eq!(decision.retry_after(), Some(MINUTE / 2));
+        assert_eq!(decision.reset(), MINUTE);
+        assert!(store.check_at("b", &quota, now).is_allowed());
+
+        let later = now + MINUTE / 2;
+        assert!(store.check_at("a", &quota, later).is_allowed());
+        assert!(!store.check_at("a", &quota, later).is_allowed());
+    }
+
+    #[test]
+    fn sliding_window() {
+        let store = MemoryStore::new();
+        let quota = Quota::sliding_window(4, MINUTE);
+        let now = Instant::now();
+
+        for remaining in (0..4).rev() {
+            let decision = store.check_at("a", &quota, now);
+            assert_eq!(decision, RateLimitDecision::allowed(4, remaining, MINUTE));
+        }
+        let decision = store.check_at("a", &quota, now);
+        assert_eq!(decision.retry_after(), Some(MINUTE + MINUTE / 4));
+
+        // three quarters of the previous window still count
+        let later = now + MINUTE + MINUTE / 4;
+        assert!(store.check_at("a", &quota, later).is_allowed());
+        let decision = store.check_at("a", &quota, later);
+        assert_eq!(decision.retry_after(), Some(MINUTE / 4));
+
+        assert!(store.check_at("a", &quota, now + MINUTE * 2).is_allowed());
+        let decision = store.check_at("a", &quota, now + MINUTE * 3);
+        assert_eq!(decision.remaining(), 2);
+    }
+
+    #[tokio::test]
+    async fn layer() {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .layer(RateLimitLayer::<Key>::new(Quota::token_bucket(2, MINUTE)));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").header("x-key", "a").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["ratelimit-limit"], "2");
+        assert_eq!(res.headers()["ratelimit-remaining"], "1");
+        assert_eq!(res.headers()["ratelimit-reset"], "30");
+        assert_eq!(res.headers()["ratelimit-policy"], "2;w=60");
+
+        let res = client.get("/").header("x-key", "a").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["ratelimit-remaining"], "0");
+
+        let res = client.get("/").header("x-key", "a").await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()["retry-after"], "30");
+        assert_eq!(res.headers()["ratelimit-remaining"], "0");
+
+        let res = client.get("/").header("x-key", "b").await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert!(!res.headers().contains_key("ratelimit-limit"));
+    }
+
+    #[tokio::test]
+    async fn per_route() {
+        let app = Router::new()
+            .route("/users/:id", get(|| async {}))
+            .route("/orders", get(|| async {}))
+            .route_layer(
+                RateLimitLayer::<Key>::new(Quota::sliding_window(1, MINUTE)).per_route(true),
+            );
+        let client = TestClient::new(app);
+
+        let res = client.get("/users/1").header("x-key", "a").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = client.get("/users/2").header("x-key", "a").await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let res = client.get("/orders").header("x-key", "a").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
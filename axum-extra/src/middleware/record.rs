@@ -0,0 +1,735 @@
+use crate::body::{TeeBody, TeeSink, TeeSummary};
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    response::Response,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use http::{header, HeaderMap, HeaderName, StatusCode};
+use http_body_util::BodyExt;
+use pin_project_lite::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    future::Future,
+    io::{self, BufRead, Write},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+use tower::ServiceExt;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that records requests and their responses, so they can be [replayed](replay) in
+/// regression tests.
+///
+/// Each exchange is written to the writer as one line of JSON, once both bodies have been sent.
+/// Read the lines back with [`Recording::read_all`].
+///
+/// Bodies are copied as they are sent, without buffering them first, so streaming bodies keep
+/// streaming. Only the parts of bodies that were read are recorded, and at most
+/// [`max_body_size`](Self::max_body_size) bytes of each. Requests whose body wasn't recorded
+/// completely are skipped when replaying.
+///
+/// `Authorization`, `Proxy-Authorization`, `Cookie`, and `Set-Cookie` headers aren't recorded.
+/// Use [`skip_header`](Self::skip_header) to leave out other headers with secrets. Bodies are
+/// recorded as they are, so only record routes whose bodies don't contain secrets.
+///
+/// Errors writing recordings are ignored.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{Router, routing::post};
+/// use axum_extra::middleware::RecordLayer;
+/// use std::fs::File;
+///
+/// let file = File::create("recordings.jsonl").unwrap();
+///
+/// let app = Router::new()
+///     .route("/", post(|body: String| async move { body }))
+///     .layer(RecordLayer::new(file).skip_header("x-api-key"));
+/// # let _: Router = app;
+/// ```
+#[derive(Clone)]
+pub struct RecordLayer {
+    writer: Arc<Mutex<dyn Write + Send>>,
+    max_body_size: u64,
+    skipped_headers: Vec<HeaderName>,
+}
+
+impl RecordLayer {
+    /// Create a new `RecordLayer` that writes recordings to `writer` and records up to 1 MiB of
+    /// each body.
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+            max_body_size: 1024 * 1024,
+            skipped_headers: vec![
+                header::AUTHORIZATION,
+                header::PROXY_AUTHORIZATION,
+                header::COOKIE,
+                header::SET_COOKIE,
+            ],
+        }
+    }
+
+    /// Set the maximum number of bytes of each body that are recorded.
+    pub fn max_body_size(mut self, max_body_size: u64) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Don't record the header `name` of requests and responses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name.
+    pub fn skip_header(mut self, name: &str) -> Self {
+        let name = name.parse().expect("invalid header name");
+        self.skipped_headers.push(name);
+        self
+    }
+}
+
+impl fmt::Debug for RecordLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordLayer")
+            .field("max_body_size", &self.max_body_size)
+            .field("skipped_headers", &self.skipped_headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for RecordLayer {
+    type Service = Record<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Record {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Middleware that records requests and their responses.
+///
+/// Created with [`RecordLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct Record<S> {
+    inner: S,
+    layer: RecordLayer,
+}
+
+impl<S> Service<Request> for Record<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = RecordFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let pending = Arc::new(Pending {
+            writer: self.layer.writer.clone(),
+            request: Mutex::new(RecordedRequest {
+                method: req.method().to_string(),
+                uri: req.uri().to_string(),
+                headers: recorded_headers(req.headers(), &self.layer.skipped_headers),
+                body: RecordedBody::default(),
+            }),
+            response: Mutex::new(None),
+        });
+
+        let sink = BodySink {
+            pending: pending.clone(),
+            data: Vec::new(),
+            kind: BodyKind::Request,
+        };
+        let max_body_size = self.layer.max_body_size;
+        let req = req.map(|body| Body::new(TeeBody::new(body, sink).limit(max_body_size)));
+
+        RecordFuture {
+            inner: self.inner.call(req),
+            pending: Some(pending),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`Record`].
+    pub struct RecordFuture<F> {
+        #[pin]
+        inner: F,
+        pending: Option<Arc<Pending>>,
+        layer: RecordLayer,
+    }
+}
+
+impl<F, E> Future for RecordFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.inner.poll(cx))?;
+
+        let pending = this.pending.take().expect("future polled after completion");
+        *pending.response.lock().unwrap() = Some(RecordedResponse {
+            status: res.status().as_u16(),
+            headers: recorded_headers(res.headers(), &this.layer.skipped_headers),
+            body: RecordedBody::default(),
+        });
+
+        let sink = BodySink {
+            pending,
+            data: Vec::new(),
+            kind: BodyKind::Response,
+        };
+        let max_body_size = this.layer.max_body_size;
+        let res = res.map(|body| Body::new(TeeBody::new(body, sink).limit(max_body_size)));
+        Poll::Ready(Ok(res))
+    }
+}
+
+impl<F> fmt::Debug for RecordFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordFuture").finish_non_exhaustive()
+    }
+}
+
+fn recorded_headers(headers: &HeaderMap, skipped: &[HeaderName]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| !skipped.contains(name))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
+
+/// An exchange that is written once the request, the response, and both bodies are done.
+struct Pending {
+    writer: Arc<Mutex<dyn Write + Send>>,
+    request: Mutex<RecordedRequest>,
+    response: Mutex<Option<RecordedResponse>>,
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        // the inner service failed, so there is nothing to compare a replay with
+        let Some(response) = self.response.get_mut().unwrap().take() else {
+            return;
+        };
+        let recording = Recording {
+            request: self.request.get_mut().unwrap().clone(),
+            response,
+        };
+
+        let Ok(mut line) = serde_json::to_vec(&recording) else {
+            return;
+        };
+        line.push(b'\n');
+        let mut writer = self.writer.lock().unwrap();
+        let _ = writer.write_all(&line).and_then(|()| writer.flush());
+    }
+}
+
+enum BodyKind {
+    Request,
+    Response,
+}
+
+struct BodySink {
+    pending: Arc<Pending>,
+    data: Vec<u8>,
+    kind: BodyKind,
+}
+
+impl TeeSink for BodySink {
+    fn write(&mut self, chunk: Bytes) {
+        self.data.extend_from_slice(&chunk);
+    }
+
+    fn finish(&mut self, summary: &TeeSummary) {
+        let body = RecordedBody {
+            data: Bytes::from(std::mem::take(&mut self.data)),
+            truncated: summary.truncated() || !summary.completed(),
+        };
+        match self.kind {
+            BodyKind::Request => self.pending.request.lock().unwrap().body = body,
+            BodyKind::Response => {
+                if let Some(response) = &mut *self.pending.response.lock().unwrap() {
+                    response.body = body;
+                }
+            }
+        }
+    }
+}
+
+/// A request and its response, recorded by [`RecordLayer`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+impl Recording {
+    /// Read the recordings written by a [`RecordLayer`], one per line.
+    ///
+    /// Empty lines are skipped.
+    pub fn read_all<R>(reader: R) -> io::Result<Vec<Self>>
+    where
+        R: BufRead,
+    {
+        let mut recordings = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recording = serde_json::from_str(&line)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            recordings.push(recording);
+        }
+        Ok(recordings)
+    }
+
+    /// The recorded request.
+    pub fn request(&self) -> &RecordedRequest {
+        &self.request
+    }
+
+    /// The recorded response.
+    pub fn response(&self) -> &RecordedResponse {
+        &self.response
+    }
+}
+
+/// A request in a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    method: String,
+    uri: String,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+impl RecordedRequest {
+    /// The method of the request.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The URI of the request, as the router saw it.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The recorded headers of the request.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The recorded body of the request.
+    pub fn body(&self) -> &RecordedBody {
+        &self.body
+    }
+}
+
+/// A response in a [`Recording`], or a response to a replayed request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+impl RecordedResponse {
+    /// The status code of the response.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// The recorded headers of the response.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The recorded body of the response.
+    pub fn body(&self) -> &RecordedBody {
+        &self.body
+    }
+}
+
+/// A body in a [`Recording`].
+///
+/// Bodies that are valid UTF-8 are stored as text, and other bodies as base64.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "RawBody", try_from = "RawBody")]
+pub struct RecordedBody {
+    data: Bytes,
+    truncated: bool,
+}
+
+impl RecordedBody {
+    /// The recorded data.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Whether only part of the body was recorded, because it was larger than the maximum size
+    /// or wasn't read completely.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base64: Option<String>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+impl From<RecordedBody> for RawBody {
+    fn from(body: RecordedBody) -> Self {
+        let (text, base64) = match std::str::from_utf8(&body.data) {
+            Ok(text) => (Some(text.to_owned()), None),
+            Err(_) => (None, Some(STANDARD.encode(&body.data))),
+        };
+        Self {
+            text,
+            base64,
+            truncated: body.truncated,
+        }
+    }
+}
+
+impl TryFrom<RawBody> for RecordedBody {
+    type Error = base64::DecodeError;
+
+    fn try_from(raw: RawBody) -> Result<Self, Self::Error> {
+        let data = match (raw.text, raw.base64) {
+            (_, Some(base64)) => Bytes::from(STANDARD.decode(base64)?),
+            (Some(text), None) => Bytes::from(text),
+            (None, None) => Bytes::new(),
+        };
+        Ok(Self {
+            data,
+            truncated: raw.truncated,
+        })
+    }
+}
+
+/// Replay `recordings` against `router` and return the responses that differ from the recorded
+/// ones.
+///
+/// Responses are compared by their status code and body. JSON bodies are compared as values, so
+/// the order of object keys doesn't matter. Recorded responses whose body was truncated are only
+/// compared by their status code, and recordings whose request body was truncated are skipped.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{Router, routing::post};
+/// use axum_extra::middleware::{replay, Recording};
+/// use std::{fs::File, io::BufReader};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let app = Router::new().route("/", post(|body: String| async move { body }));
+///
+/// let file = File::open("recordings.jsonl").unwrap();
+/// let recordings = Recording::read_all(BufReader::new(file)).unwrap();
+///
+/// let mismatches = replay(app, &recordings).await;
+/// for mismatch in &mismatches {
+///     eprintln!("{mismatch}");
+/// }
+/// assert!(mismatches.is_empty());
+/// # }
+/// ```
+pub async fn replay(router: Router, recordings: &[Recording]) -> Vec<ReplayMismatch> {
+    let mut mismatches = Vec::new();
+
+    for recording in recordings {
+        let request = &recording.request;
+        if request.body.truncated {
+            continue;
+        }
+
+        let mut builder = Request::builder()
+            .method(request.method.as_str())
+            .uri(request.uri.as_str());
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let Ok(req) = builder.body(Body::from(request.body.data.clone())) else {
+            continue;
+        };
+
+        let res = router
+            .clone()
+            .oneshot(req)
+            .await
+            .unwrap_or_else(|err| match err {});
+        let status = res.status();
+        let headers = recorded_headers(res.headers(), &[]);
+        let (data, truncated) = match res.into_body().collect().await {
+            Ok(collected) => (collected.to_bytes(), false),
+            Err(_) => (Bytes::new(), true),
+        };
+        let actual = RecordedResponse {
+            status: status.as_u16(),
+            headers,
+            body: RecordedBody { data, truncated },
+        };
+
+        let expected = &recording.response;
+        let status_matches = expected.status == actual.status;
+        let body_matches = expected.body.truncated || bodies_match(&expected.body, &actual.body);
+        if !status_matches || !body_matches {
+            mismatches.push(ReplayMismatch {
+                recording: recording.clone(),
+                actual,
+            });
+        }
+    }
+
+    mismatches
+}
+
+fn bodies_match(expected: &RecordedBody, actual: &RecordedBody) -> bool {
+    if expected.data == actual.data {
+        return true;
+    }
+    match (
+        serde_json::from_slice::<serde_json::Value>(&expected.data),
+        serde_json::from_slice::<serde_json::Value>(&actual.data),
+    ) {
+        (Ok(expected), Ok(actual)) => expected == actual,
+        _ => false,
+    }
+}
+
+/// A replayed request whose response differs from the recorded one, returned by [`replay`].
+///
+/// The [`Display`](fmt::Display) implementation describes the differences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayMismatch {
+    recording: Recording,
+    actual: RecordedResponse,
+}
+
+impl ReplayMismatch {
+    /// The recording that was replayed.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// The response to the replayed request.
+    pub fn actual(&self) -> &RecordedResponse {
+        &self.actual
+    }
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let request = &self.recording.request;
+        let expected = &self.recording.response;
+        write!(f, "{} {}", request.method, request.uri)?;
+
+        if expected.status != self.actual.status {
+            write!(
+                f,
+                "\n  status: expected {}, got {}",
+                display_status(expected.status),
+                display_status(self.actual.status),
+            )?;
+        }
+        if !expected.body.truncated && !bodies_match(&expected.body, &self.actual.body) {
+            write!(
+                f,
+                "\n  body: expected {:?}, got {:?}",
+                String::from_utf8_lossy(&expected.body.data),
+                String::from_utf8_lossy(&self.actual.body.data),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn display_status(status: u16) -> String {
+    match StatusCode::from_u16(status) {
+        Ok(status) => status.to_string(),
+        Err(_) => status.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{
+        extract::Path,
+        routing::{get, post},
+        Json,
+    };
+    use serde_json::json;
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let writer = SharedWriter::default();
+        let app = Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .route("/bytes", get(|| async { vec![0xff_u8, 0, 1] }))
+            .route(
+                "/users/:id",
+                get(
+                    |Path(id): Path<u32>| async move { Json(json!({ "id": id, "name": "alice" })) },
+                ),
+            )
+            .layer(RecordLayer::new(writer.clone()).skip_header("x-secret"));
+        let client = TestClient::new(app);
+
+        client
+            .post("/echo")
+            .header("x-secret", "hunter2")
+            .header("authorization", "Bearer token")
+            .header("x-trace", "1")
+            .body("hello")
+            .await
+            .text()
+            .await;
+        client.get("/bytes").await.bytes().await;
+        client.get("/users/1").await.bytes().await;
+
+        // recordings are written once the server drops the bodies
+        for _ in 0..100 {
+            if writer
+                .0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+                == 3
+            {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let mut recordings = Recording::read_all(&**writer.0.lock().unwrap()).unwrap();
+        recordings.sort_by(|a, b| a.request().uri().cmp(b.request().uri()));
+        assert_eq!(recordings.len(), 3);
+
+        let echo = &recordings[1];
+        assert_eq!(echo.request().method(), "POST");
+        assert_eq!(echo.request().uri(), "/echo");
+        assert!(echo
+            .request()
+            .headers()
+            .iter()
+            .all(|(name, _)| name != "x-secret" && name != "authorization"));
+        assert!(echo
+            .request()
+            .headers()
+            .contains(&("x-trace".to_owned(), "1".to_owned())));
+        assert_eq!(echo.request().body().data(), "hello");
+        assert_eq!(echo.response().status(), 200);
+        assert_eq!(echo.response().body().data(), "hello");
+        assert_eq!(
+            serde_json::to_value(recordings[0].response().body()).unwrap(),
+            json!({ "base64": "/wAB", "truncated": false })
+        );
+
+        let same = Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .route("/bytes", get(|| async { vec![0xff_u8, 0, 1] }))
+            .route(
+                "/users/:id",
+                get(
+                    |Path(id): Path<u32>| async move { Json(json!({ "name": "alice", "id": id })) },
+                ),
+            );
+        assert!(replay(same, &recordings).await.is_empty());
+
+        let changed = Router::new()
+            .route(
+                "/echo",
+                post(|body: String| async move { body.to_uppercase() }),
+            )
+            .route(
+                "/users/:id",
+                get(
+                    |Path(id): Path<u32>| async move { Json(json!({ "id": id, "name": "alice" })) },
+                ),
+            );
+        let mismatches = replay(changed, &recordings).await;
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].actual().status(), 404);
+        assert!(mismatches[0]
+            .to_string()
+            .starts_with("GET /bytes\n  status: expected 200 OK, got 404 Not Found"));
+        assert_eq!(
+            mismatches[1].to_string(),
+            "POST /echo\n  body: expected \"hello\", got \"HELLO\""
+        );
+    }
+
+    #[test]
+    fn read_all_skips_empty_lines() {
+        let line = serde_json::to_string(&json!({
+            "request": {
+                "method": "GET",
+                "uri": "/",
+                "headers": [["accept", "*/*"]],
+                "body": {},
+            },
+            "response": {
+                "status": 204,
+                "headers": [],
+                "body": { "text": "" },
+            },
+        }))
+        .unwrap();
+        let input = format!("{line}\n\n{line}\n");
+
+        let recordings = Recording::read_all(input.as_bytes()).unwrap();
+        assert_eq!(recordings.len(), 2);
+        assert_eq!(recordings[0].request().headers()[0].1, "*/*");
+        assert!(recordings[0].response().body().data().is_empty());
+
+        let err = Recording::read_all(&b"{"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
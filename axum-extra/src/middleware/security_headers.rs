@@ -0,0 +1,839 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    response::{IntoResponse, Response},
+};
+use http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that adds security headers to responses.
+///
+/// By default these headers are added:
+///
+/// - `Strict-Transport-Security: max-age=31536000; includeSubDomains`
+/// - `Content-Security-Policy: default-src 'self'; base-uri 'self'; form-action 'self';
+///   frame-ancestors 'self'; object-src 'none'`
+/// - `X-Content-Type-Options: nosniff`
+/// - `X-Frame-Options: SAMEORIGIN`
+/// - `Referrer-Policy: no-referrer`
+///
+/// Each header can be changed or removed with the builder methods, and a `Permissions-Policy`
+/// can be added.
+///
+/// Headers that the response already has, for example because the handler set them, are not
+/// replaced.
+///
+/// # Overriding headers for some routes
+///
+/// If a request goes through several `SecurityHeadersLayer`s, only the innermost one adds
+/// headers. This means a route can use different headers than the rest of the router by adding
+/// its own layer:
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::middleware::{ContentSecurityPolicy, SecurityHeadersLayer};
+///
+/// let app = Router::new()
+///     .route("/", get(|| async { /* ... */ }))
+///     .route(
+///         "/embed",
+///         get(|| async { /* ... */ }).layer(
+///             SecurityHeadersLayer::new()
+///                 .frame_options(None)
+///                 .content_security_policy(
+///                     ContentSecurityPolicy::new()
+///                         .default_src(["'self'"])
+///                         .frame_ancestors(["https://partner.example.com"]),
+///                 ),
+///         ),
+///     )
+///     .layer(SecurityHeadersLayer::new());
+/// # let _: Router = app;
+/// ```
+///
+/// # Nonces
+///
+/// A [content security policy](ContentSecurityPolicy::nonce) can require inline scripts and
+/// styles to have a nonce. A new nonce is generated for each request, and handlers get it with
+/// the [`CspNonce`] extractor:
+///
+/// ```rust
+/// use axum::{Router, routing::get, response::Html};
+/// use axum_extra::middleware::{ContentSecurityPolicy, CspNonce, SecurityHeadersLayer};
+///
+/// async fn page(nonce: CspNonce) -> Html<String> {
+///     Html(format!("<script nonce=\"{nonce}\">console.log('hi')</script>"))
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(page))
+///     .layer(SecurityHeadersLayer::new().content_security_policy(
+///         ContentSecurityPolicy::new()
+///             .default_src(["'self'"])
+///             .nonce("script-src"),
+///     ));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersLayer {
+    config: Arc<Config>,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    strict_transport_security: Option<StrictTransportSecurity>,
+    content_security_policy: Option<ContentSecurityPolicy>,
+    content_type_options: bool,
+    frame_options: Option<FrameOptions>,
+    referrer_policy: Option<ReferrerPolicy>,
+    permissions_policy: Option<PermissionsPolicy>,
+}
+
+impl SecurityHeadersLayer {
+    /// Create a new `SecurityHeadersLayer` with the default headers.
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Config {
+                strict_transport_security: Some(StrictTransportSecurity::default()),
+                content_security_policy: Some(
+                    ContentSecurityPolicy::new()
+                        .default_src(["'self'"])
+                        .base_uri(["'self'"])
+                        .form_action(["'self'"])
+                        .frame_ancestors(["'self'"])
+                        .object_src(["'none'"]),
+                ),
+                content_type_options: true,
+                frame_options: Some(FrameOptions::SameOrigin),
+                referrer_policy: Some(ReferrerPolicy::NoReferrer),
+                permissions_policy: None,
+            }),
+        }
+    }
+
+    /// Set the `Strict-Transport-Security` header, or don't send it with `None`.
+    pub fn strict_transport_security(
+        mut self,
+        strict_transport_security: impl Into<Option<StrictTransportSecurity>>,
+    ) -> Self {
+        Arc::make_mut(&mut self.config).strict_transport_security =
+            strict_transport_security.into();
+        self
+    }
+
+    /// Set the `Content-Security-Policy` header, or don't send it with `None`.
+    pub fn content_security_policy(
+        mut self,
+        content_security_policy: impl Into<Option<ContentSecurityPolicy>>,
+    ) -> Self {
+        Arc::make_mut(&mut self.config).content_security_policy = content_security_policy.into();
+        self
+    }
+
+    /// Set whether to send `X-Content-Type-Options: nosniff`.
+    pub fn content_type_options(mut self, content_type_options: bool) -> Self {
+        Arc::make_mut(&mut self.config).content_type_options = content_type_options;
+        self
+    }
+
+    /// Set the `X-Frame-Options` header, or don't send it with `None`.
+    pub fn frame_options(mut self, frame_options: impl Into<Option<FrameOptions>>) -> Self {
+        Arc::make_mut(&mut self.config).frame_options = frame_options.into();
+        self
+    }
+
+    /// Set the `Referrer-Policy` header, or don't send it with `None`.
+    pub fn referrer_policy(mut self, referrer_policy: impl Into<Option<ReferrerPolicy>>) -> Self {
+        Arc::make_mut(&mut self.config).referrer_policy = referrer_policy.into();
+        self
+    }
+
+    /// Set the `Permissions-Policy` header, or don't send it with `None`.
+    ///
+    /// Defaults to none.
+    pub fn permissions_policy(
+        mut self,
+        permissions_policy: impl Into<Option<PermissionsPolicy>>,
+    ) -> Self {
+        Arc::make_mut(&mut self.config).permissions_policy = permissions_policy.into();
+        self
+    }
+}
+
+impl Default for SecurityHeadersLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for SecurityHeadersLayer {
+    type Service = SecurityHeaders<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SecurityHeaders {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// The `Strict-Transport-Security` header, telling browsers to only connect over HTTPS.
+///
+/// Defaults to a `max-age` of one year and `includeSubDomains`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrictTransportSecurity {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl StrictTransportSecurity {
+    /// Create a new `StrictTransportSecurity` with the given `max-age`, without
+    /// `includeSubDomains`.
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+
+    /// Set whether the header also applies to subdomains.
+    pub fn include_subdomains(mut self, include_subdomains: bool) -> Self {
+        self.include_subdomains = include_subdomains;
+        self
+    }
+
+    /// Set whether to add the `preload` directive, for submitting the domain to browsers'
+    /// preload lists.
+    pub fn preload(mut self, preload: bool) -> Self {
+        self.preload = preload;
+        self
+    }
+
+    fn header_value(&self) -> String {
+        let mut value = format!("max-age={}", self.max_age.as_secs());
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+        value
+    }
+}
+
+impl Default for StrictTransportSecurity {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(365 * 24 * 60 * 60)).include_subdomains(true)
+    }
+}
+
+/// The `Content-Security-Policy` header, restricting what resources a page can load.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_extra::middleware::ContentSecurityPolicy;
+///
+/// let policy = ContentSecurityPolicy::new()
+///     .default_src(["'self'"])
+///     .img_src(["'self'", "data:", "https://images.example.com"])
+///     .upgrade_insecure_requests();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+    report_only: bool,
+}
+
+macro_rules! directives {
+    ($($method:ident => $name:literal,)*) => {
+        $(
+            #[doc = concat!("Set the `", $name, "` directive.")]
+            pub fn $method<I>(self, sources: I) -> Self
+            where
+                I: IntoIterator,
+                I::Item: Into<String>,
+            {
+                self.directive($name, sources)
+            }
+        )*
+    };
+}
+
+impl ContentSecurityPolicy {
+    /// Create a new `ContentSecurityPolicy` without any directives.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a directive, replacing it if it was set before.
+    ///
+    /// `sources` must be valid in a header value, and sources that are keywords must be quoted,
+    /// such as `'self'`.
+    pub fn directive<I>(mut self, name: &str, sources: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let sources = sources.into_iter().map(Into::into).collect::<Vec<_>>();
+        let nonce = self
+            .sources(name)
+            .map(|existing| existing.iter().any(|source| source == NONCE_PLACEHOLDER))
+            .unwrap_or_default();
+        let directive = self.directive_mut(name);
+        *directive = sources;
+        if nonce {
+            directive.push(NONCE_PLACEHOLDER.to_owned());
+        }
+        self
+    }
+
+    directives! {
+        default_src => "default-src",
+        script_src => "script-src",
+        style_src => "style-src",
+        img_src => "img-src",
+        connect_src => "connect-src",
+        font_src => "font-src",
+        media_src => "media-src",
+        frame_src => "frame-src",
+        worker_src => "worker-src",
+        object_src => "object-src",
+        base_uri => "base-uri",
+        form_action => "form-action",
+        frame_ancestors => "frame-ancestors",
+    }
+
+    /// Add the `upgrade-insecure-requests` directive, making browsers load `http` resources over
+    /// `https`.
+    pub fn upgrade_insecure_requests(self) -> Self {
+        self.directive("upgrade-insecure-requests", Vec::<String>::new())
+    }
+
+    /// Require a nonce for the given directive, such as `script-src` or `style-src`.
+    ///
+    /// A new nonce is generated for each request and added to the directive as
+    /// `'nonce-<nonce>'`. Handlers get it with the [`CspNonce`] extractor, to add it to inline
+    /// scripts and styles.
+    pub fn nonce(mut self, name: &str) -> Self {
+        let directive = self.directive_mut(name);
+        if !directive.iter().any(|source| source == NONCE_PLACEHOLDER) {
+            directive.push(NONCE_PLACEHOLDER.to_owned());
+        }
+        self
+    }
+
+    /// Set whether to send the policy as `Content-Security-Policy-Report-Only`, which reports
+    /// violations without blocking anything.
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    fn sources(&self, name: &str) -> Option<&[String]> {
+        self.directives
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, sources)| &**sources)
+    }
+
+    fn directive_mut(&mut self, name: &str) -> &mut Vec<String> {
+        let index = match self
+            .directives
+            .iter()
+            .position(|(existing, _)| existing == name)
+        {
+            Some(index) => index,
+            None => {
+                self.directives.push((name.to_owned(), Vec::new()));
+                self.directives.len() - 1
+            }
+        };
+        &mut self.directives[index].1
+    }
+
+    fn uses_nonce(&self) -> bool {
+        self.directives
+            .iter()
+            .any(|(_, sources)| sources.iter().any(|source| source == NONCE_PLACEHOLDER))
+    }
+
+    fn header_name(&self) -> HeaderName {
+        if self.report_only {
+            header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+        } else {
+            header::CONTENT_SECURITY_POLICY
+        }
+    }
+
+    fn header_value(&self, nonce: Option<&CspNonce>) -> String {
+        let mut value = String::new();
+        for (name, sources) in &self.directives {
+            if !value.is_empty() {
+                value.push_str("; ");
+            }
+            value.push_str(name);
+            for source in sources {
+                value.push(' ');
+                match nonce {
+                    Some(nonce) if source == NONCE_PLACEHOLDER => {
+                        value.push_str(&format!("'nonce-{nonce}'"));
+                    }
+                    _ => value.push_str(source),
+                }
+            }
+        }
+        value
+    }
+}
+
+/// Stands for the nonce of the request in directives, replaced when the header is sent.
+const NONCE_PLACEHOLDER: &str = "'nonce'";
+
+/// The `X-Frame-Options` header, controlling whether the page can be shown in a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrameOptions {
+    /// `DENY`: the page can't be shown in frames.
+    Deny,
+    /// `SAMEORIGIN`: the page can only be shown in frames on the same origin.
+    SameOrigin,
+}
+
+impl FrameOptions {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Deny => "DENY",
+            Self::SameOrigin => "SAMEORIGIN",
+        }
+    }
+}
+
+/// The `Referrer-Policy` header, controlling how much of the URL is sent in the `Referer`
+/// header of requests from the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReferrerPolicy {
+    /// `no-referrer`
+    NoReferrer,
+    /// `no-referrer-when-downgrade`
+    NoReferrerWhenDowngrade,
+    /// `origin`
+    Origin,
+    /// `origin-when-cross-origin`
+    OriginWhenCrossOrigin,
+    /// `same-origin`
+    SameOrigin,
+    /// `strict-origin`
+    StrictOrigin,
+    /// `strict-origin-when-cross-origin`
+    StrictOriginWhenCrossOrigin,
+    /// `unsafe-url`
+    UnsafeUrl,
+}
+
+impl ReferrerPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::NoReferrer => "no-referrer",
+            Self::NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
+            Self::Origin => "origin",
+            Self::OriginWhenCrossOrigin => "origin-when-cross-origin",
+            Self::SameOrigin => "same-origin",
+            Self::StrictOrigin => "strict-origin",
+            Self::StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
+            Self::UnsafeUrl => "unsafe-url",
+        }
+    }
+}
+
+/// The `Permissions-Policy` header, controlling which browser features the page can use.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_extra::middleware::PermissionsPolicy;
+///
+/// // camera=(), fullscreen=(self), geolocation=(self "https://maps.example.com")
+/// let policy = PermissionsPolicy::new()
+///     .deny("camera")
+///     .allow("fullscreen", ["self"])
+///     .allow("geolocation", ["self", "https://maps.example.com"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionsPolicy {
+    features: Vec<(String, Vec<String>)>,
+}
+
+impl PermissionsPolicy {
+    /// Create a new `PermissionsPolicy` without any features.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Don't allow the page to use `feature`.
+    pub fn deny(self, feature: &str) -> Self {
+        self.allow(feature, Vec::<String>::new())
+    }
+
+    /// Allow the page to use `feature` if it comes from one of the `origins`.
+    ///
+    /// Origins are `self`, `*`, or URLs such as `https://example.com`.
+    pub fn allow<I>(mut self, feature: &str, origins: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let origins = origins.into_iter().map(Into::into).collect();
+        match self
+            .features
+            .iter_mut()
+            .find(|(existing, _)| existing == feature)
+        {
+            Some((_, existing)) => *existing = origins,
+            None => self.features.push((feature.to_owned(), origins)),
+        }
+        self
+    }
+
+    fn header_value(&self) -> String {
+        let features = self.features.iter().map(|(feature, origins)| {
+            let origins = origins
+                .iter()
+                .map(|origin| match origin.as_str() {
+                    "self" | "*" => origin.clone(),
+                    _ => format!("\"{origin}\""),
+                })
+                .collect::<Vec<_>>();
+            format!("{feature}=({})", origins.join(" "))
+        });
+        features.collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Extractor for the nonce of the request, set by [`SecurityHeadersLayer`].
+///
+/// See [`SecurityHeadersLayer`] for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(String);
+
+impl CspNonce {
+    fn generate() -> Self {
+        let mut bytes = [0; 16];
+        getrandom::getrandom(&mut bytes).expect("failed to generate a CSP nonce");
+        Self(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
+    /// The nonce as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CspNonce
+where
+    S: Send + Sync,
+{
+    type Rejection = CspNonceRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or(CspNonceRejection::MissingNonce)
+    }
+}
+
+/// Rejection used for [`CspNonce`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CspNonceRejection {
+    /// [`SecurityHeadersLayer`] wasn't applied to the route, or its content security policy
+    /// doesn't use a nonce.
+    MissingNonce,
+}
+
+impl IntoResponse for CspNonceRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for CspNonceRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingNonce => f.write_str(
+                "No CSP nonce. Add `SecurityHeadersLayer` with a content security policy that \
+                 uses a nonce to extract `CspNonce`",
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CspNonceRejection {}
+
+/// Middleware that adds security headers to responses.
+///
+/// Created with [`SecurityHeadersLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders<S> {
+    inner: S,
+    config: Arc<Config>,
+}
+
+impl<S> Service<Request> for SecurityHeaders<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = SecurityHeadersFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // the innermost layer decides the headers, so it also decides the nonce
+        let nonce = match &self.config.content_security_policy {
+            Some(policy) if policy.uses_nonce() => {
+                let nonce = CspNonce::generate();
+                req.extensions_mut().insert(nonce.clone());
+                Some(nonce)
+            }
+            _ => {
+                req.extensions_mut().remove::<CspNonce>();
+                None
+            }
+        };
+
+        SecurityHeadersFuture {
+            inner: self.inner.call(req),
+            config: self.config.clone(),
+            nonce,
+        }
+    }
+}
+
+/// Marks responses that already got headers from an inner [`SecurityHeadersLayer`].
+#[derive(Debug, Clone, Copy)]
+struct Applied;
+
+pin_project! {
+    /// Response future for [`SecurityHeaders`].
+    pub struct SecurityHeadersFuture<F> {
+        #[pin]
+        inner: F,
+        config: Arc<Config>,
+        nonce: Option<CspNonce>,
+    }
+}
+
+impl<F, E> Future for SecurityHeadersFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.inner.poll(cx))?;
+
+        if res.extensions().get::<Applied>().is_some() {
+            return Poll::Ready(Ok(res));
+        }
+        res.extensions_mut().insert(Applied);
+
+        let config = &**this.config;
+        let headers = res.headers_mut();
+        if let Some(hsts) = &config.strict_transport_security {
+            set_if_absent(
+                headers,
+                header::STRICT_TRANSPORT_SECURITY,
+                hsts.header_value(),
+            );
+        }
+        if let Some(policy) = &config.content_security_policy {
+            set_if_absent(
+                headers,
+                policy.header_name(),
+                policy.header_value(this.nonce.as_ref()),
+            );
+        }
+        if config.content_type_options {
+            set_if_absent(headers, header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+        }
+        if let Some(frame_options) = config.frame_options {
+            set_if_absent(headers, header::X_FRAME_OPTIONS, frame_options.as_str());
+        }
+        if let Some(referrer_policy) = config.referrer_policy {
+            set_if_absent(headers, header::REFERRER_POLICY, referrer_policy.as_str());
+        }
+        if let Some(permissions_policy) = &config.permissions_policy {
+            set_if_absent(
+                headers,
+                HeaderName::from_static("permissions-policy"),
+                permissions_policy.header_value(),
+            );
+        }
+
+        Poll::Ready(Ok(res))
+    }
+}
+
+impl<F> fmt::Debug for SecurityHeadersFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecurityHeadersFuture")
+            .finish_non_exhaustive()
+    }
+}
+
+fn set_if_absent<V>(headers: &mut HeaderMap, name: HeaderName, value: V)
+where
+    V: TryInto<HeaderValue>,
+{
+    if headers.contains_key(&name) {
+        return;
+    }
+    // values built from invalid user input are skipped rather than failing the request
+    if let Ok(value) = value.try_into() {
+        headers.insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    #[tokio::test]
+    async fn default_headers() {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .route(
+                "/custom",
+                get(|| async { ([(header::REFERRER_POLICY, "origin")], "") }),
+            )
+            .layer(SecurityHeadersLayer::new());
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(
+            res.headers()["strict-transport-security"],
+            "max-age=31536000; includeSubDomains"
+        );
+        assert_eq!(
+            res.headers()["content-security-policy"],
+            "default-src 'self'; base-uri 'self'; form-action 'self'; frame-ancestors 'self'; \
+             object-src 'none'"
+        );
+        assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+        assert_eq!(res.headers()["x-frame-options"], "SAMEORIGIN");
+        assert_eq!(res.headers()["referrer-policy"], "no-referrer");
+        assert!(res.headers().get("permissions-policy").is_none());
+
+        // headers set by handlers are kept
+        let res = client.get("/custom").await;
+        assert_eq!(res.headers()["referrer-policy"], "origin");
+    }
+
+    #[tokio::test]
+    async fn per_route_override() {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .route(
+                "/embed",
+                get(|| async {}).layer(
+                    SecurityHeadersLayer::new()
+                        .frame_options(None)
+                        .strict_transport_security(
+                            StrictTransportSecurity::new(Duration::from_secs(60)).preload(true),
+                        )
+                        .permissions_policy(
+                            PermissionsPolicy::new()
+                                .deny("camera")
+                                .allow("geolocation", ["self", "https://example.com"]),
+                        ),
+                ),
+            )
+            .layer(SecurityHeadersLayer::new());
+        let client = TestClient::new(app);
+
+        let res = client.get("/embed").await;
+        assert!(res.headers().get("x-frame-options").is_none());
+        assert_eq!(
+            res.headers()["strict-transport-security"],
+            "max-age=60; preload"
+        );
+        assert_eq!(
+            res.headers()["permissions-policy"],
+            "camera=(), geolocation=(self \"https://example.com\")"
+        );
+
+        let res = client.get("/").await;
+        assert_eq!(res.headers()["x-frame-options"], "SAMEORIGIN");
+    }
+
+    #[tokio::test]
+    async fn nonce() {
+        let app = Router::new()
+            .route("/", get(|nonce: CspNonce| async move { nonce.to_string() }))
+            .layer(
+                SecurityHeadersLayer::new().content_security_policy(
+                    ContentSecurityPolicy::new()
+                        .nonce("script-src")
+                        .script_src(["'self'"])
+                        .report_only(true),
+                ),
+            );
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        let policy = res.headers()["content-security-policy-report-only"]
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let nonce = res.text().await;
+        assert_eq!(nonce.len(), 32);
+        assert_eq!(policy, format!("script-src 'self' 'nonce-{nonce}'"));
+
+        let res = client.get("/").await;
+        assert_ne!(res.text().await, nonce);
+    }
+
+    #[tokio::test]
+    async fn missing_nonce() {
+        let app = Router::new()
+            .route("/", get(|_: CspNonce| async {}))
+            .layer(SecurityHeadersLayer::new());
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
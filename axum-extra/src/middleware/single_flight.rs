@@ -0,0 +1,387 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+use http_body_util::BodyExt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::watch;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that coalesces concurrent identical `GET` and `HEAD` requests.
+///
+/// When a burst of identical requests arrives, such as after a cache entry expired, only the
+/// first one is passed to the handler. The others wait for its response, which is buffered and
+/// sent to all of them. Requests that arrive after the response was sent call the handler again,
+/// so this doesn't cache anything.
+///
+/// Requests are identical if they have the same method, URI, and values for the headers set
+/// with [`vary`](Self::vary). Requests with other methods are passed through.
+///
+/// If the first request fails, is cancelled, or its response body isn't known to fit in the
+/// [`max_body_size`](Self::max_body_size), such as streams, the waiting requests call the
+/// handler themselves.
+///
+/// # Responses are shared
+///
+/// The response is sent to every waiting request, so like `CacheLayer`, this
+/// follows the rules of a shared HTTP cache:
+///
+/// - Requests with an `Authorization` header are passed through.
+/// - Responses with a `Set-Cookie` header, or with `private` or `no-store` in their
+///   `Cache-Control` header, aren't shared, and the waiting requests call the handler themselves.
+///
+/// If responses depend on other headers identifying who sends the request, such as `Cookie`, add
+/// them with [`vary`](Self::vary).
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::middleware::SingleFlightLayer;
+/// use http::header::ACCEPT_LANGUAGE;
+///
+/// async fn expensive_report() -> String {
+///     // query the database
+///     # String::new()
+/// }
+///
+/// let app = Router::new()
+///     .route("/report", get(expensive_report))
+///     .layer(SingleFlightLayer::new().vary(ACCEPT_LANGUAGE));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SingleFlightLayer {
+    config: Arc<Config>,
+    in_flight: InFlight,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    vary: Vec<HeaderName>,
+    max_body_size: usize,
+}
+
+type InFlight = Arc<Mutex<HashMap<Key, watch::Receiver<Option<SharedResponse>>>>>;
+
+impl SingleFlightLayer {
+    /// Create a new `SingleFlightLayer`.
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Config {
+                vary: Vec::new(),
+                max_body_size: 1024 * 1024,
+            }),
+            in_flight: Default::default(),
+        }
+    }
+
+    /// Only coalesce requests that also have the same values for `header`.
+    ///
+    /// Can be called multiple times to add more headers.
+    pub fn vary(mut self, header: HeaderName) -> Self {
+        Arc::make_mut(&mut self.config).vary.push(header);
+        self
+    }
+
+    /// Set the maximum size of the response bodies that are shared.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_body_size = max_body_size;
+        self
+    }
+}
+
+impl Default for SingleFlightLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for SingleFlightLayer {
+    type Service = SingleFlight<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SingleFlight {
+            inner,
+            config: self.config.clone(),
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+/// Middleware that coalesces concurrent identical `GET` and `HEAD` requests.
+///
+/// Created with [`SingleFlightLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct SingleFlight<S> {
+    inner: S,
+    config: Arc<Config>,
+    in_flight: InFlight,
+}
+
+impl<S> Service<Request> for SingleFlight<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if !matches!(*req.method(), Method::GET | Method::HEAD)
+            || req.headers().contains_key(header::AUTHORIZATION)
+        {
+            return Box::pin(inner.call(req));
+        }
+
+        let key = Key {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            headers: self
+                .config
+                .vary
+                .iter()
+                .map(|name| req.headers().get_all(name).iter().cloned().collect())
+                .collect(),
+        };
+
+        let leader = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(key.clone(), receiver);
+                    Ok(sender)
+                }
+            }
+        };
+
+        let sender = match leader {
+            Ok(sender) => sender,
+            Err(mut receiver) => {
+                return Box::pin(async move {
+                    if receiver.changed().await.is_ok() {
+                        let shared = receiver.borrow().clone();
+                        if let Some(shared) = shared {
+                            return Ok(shared.into_response());
+                        }
+                    }
+                    // the first request didn't produce a response that can be shared
+                    inner.call(req).await
+                });
+            }
+        };
+
+        let guard = LeaderGuard {
+            in_flight: self.in_flight.clone(),
+            key,
+        };
+        let max_body_size = self.config.max_body_size;
+
+        Box::pin(async move {
+            let res = inner.call(req).await?;
+            let fits = matches!(
+                res.body().size_hint().upper(),
+                Some(len) if len <= max_body_size as u64
+            );
+            if !fits || is_private(res.headers()) {
+                return Ok(res);
+            }
+
+            let (parts, body) = res.into_parts();
+            let body = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to buffer response body");
+
+                    return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                }
+            };
+
+            // requests arriving from now on call the handler again
+            drop(guard);
+            let _ = sender.send(Some(SharedResponse {
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+            }));
+
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+/// Whether the response is meant for a single user and must not be shared.
+fn is_private(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::SET_COOKIE) {
+        return true;
+    }
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.split('=').next().unwrap_or_default().trim())
+        .any(|name| name.eq_ignore_ascii_case("private") || name.eq_ignore_ascii_case("no-store"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    method: Method,
+    uri: Uri,
+    headers: Vec<Vec<HeaderValue>>,
+}
+
+/// Removes the key once the first request completes or is cancelled.
+struct LeaderGuard {
+    in_flight: InFlight,
+    key: Key,
+}
+
+impl Drop for LeaderGuard {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(&self.key);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SharedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl IntoResponse for SharedResponse {
+    fn into_response(self) -> Response {
+        let mut res = Response::new(Body::from(self.body));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    fn app(calls: Arc<AtomicUsize>) -> Router {
+        let handler = move || {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                call.to_string()
+            }
+        };
+        Router::new()
+            .route("/", get(handler.clone()).post(handler))
+            .layer(SingleFlightLayer::new().vary(HeaderName::from_static("x-tenant")))
+    }
+
+    #[tokio::test]
+    async fn coalesces_concurrent_requests() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = TestClient::new(app(calls.clone()));
+
+        let (first, second, third) = tokio::join!(
+            async { client.get("/").await.text().await },
+            async { client.get("/").await.text().await },
+            async { client.get("/").await.text().await },
+        );
+        assert_eq!(first, "1");
+        assert_eq!(second, "1");
+        assert_eq!(third, "1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // completed responses aren't cached
+        let res = client.get("/").await;
+        assert_eq!(res.text().await, "2");
+    }
+
+    #[tokio::test]
+    async fn different_requests() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = TestClient::new(app(calls.clone()));
+
+        tokio::join!(
+            async { client.get("/").await },
+            async { client.get("/?page=2").await },
+            async { client.get("/").header("x-tenant", "a").await },
+            async { client.post("/").await },
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn passes_through_authorized_requests() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = TestClient::new(app(calls.clone()));
+
+        let (first, second) = tokio::join!(
+            async { client.get("/").header("authorization", "Bearer a").await },
+            async { client.get("/").header("authorization", "Bearer a").await },
+        );
+        assert_ne!(first.text().await, second.text().await);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn doesnt_share_private_responses() {
+        async fn check(headers: [(HeaderName, &'static str); 1]) {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let handler = {
+                let calls = calls.clone();
+                move || {
+                    let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        (headers, call.to_string())
+                    }
+                }
+            };
+            let app = Router::new()
+                .route("/", get(handler))
+                .layer(SingleFlightLayer::new());
+            let client = TestClient::new(app);
+
+            let (first, second) =
+                tokio::join!(async { client.get("/").await.text().await }, async {
+                    client.get("/").await.text().await
+                },);
+            assert_ne!(first, second);
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+        }
+
+        check([(header::SET_COOKIE, "session=a")]).await;
+        check([(header::CACHE_CONTROL, "max-age=60, Private")]).await;
+        check([(header::CACHE_CONTROL, "no-store")]).await;
+    }
+}
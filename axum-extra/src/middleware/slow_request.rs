@@ -0,0 +1,269 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    response::Response,
+};
+use http::{Method, Uri};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::time::Sleep;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that reports requests that are still running after a threshold.
+///
+/// When a request has been running for longer than the threshold, the
+/// [`on_slow`](Self::on_slow) callback is called once with a [`SlowRequest`], while the request
+/// is still being handled. This makes it possible to spot stuck handlers as they happen, rather
+/// than once they complete, if they ever do. By default, a warning with the method, URI, matched
+/// route, and elapsed time is logged with [`tracing`] (if the `tracing` feature is enabled).
+///
+/// The request isn't cancelled. Use a timeout for that.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::middleware::{SlowRequest, SlowRequestLayer};
+/// use std::time::Duration;
+///
+/// let layer = SlowRequestLayer::new(Duration::from_secs(5)).on_slow(|req: &SlowRequest| {
+///     eprintln!(
+///         "{} {} has been running for {:?}",
+///         req.method(),
+///         req.route().unwrap_or("<unmatched>"),
+///         req.elapsed(),
+///     );
+/// });
+///
+/// let app = Router::new()
+///     .route("/reports/:id", get(|| async { /* ... */ }))
+///     .route_layer(layer);
+/// # let _: Router = app;
+/// ```
+///
+/// The route is only known if the layer is added with [`Router::route_layer`] or to individual
+/// routes.
+///
+/// [`Router::route_layer`]: axum::Router::route_layer
+#[derive(Debug, Clone, Copy)]
+pub struct SlowRequestLayer<T = fn(&SlowRequest)> {
+    threshold: Duration,
+    on_slow: T,
+}
+
+impl SlowRequestLayer {
+    /// Create a new `SlowRequestLayer` that reports requests running for longer than
+    /// `threshold`.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            on_slow: default_on_slow,
+        }
+    }
+}
+
+fn default_on_slow(_req: &SlowRequest) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        method = %_req.method(),
+        uri = %_req.uri(),
+        route = _req.route(),
+        elapsed = ?_req.elapsed(),
+        "request is still running after {:?}",
+        _req.threshold(),
+    );
+}
+
+impl<T> SlowRequestLayer<T> {
+    /// Set the callback that is called with requests that are still running after the threshold.
+    pub fn on_slow<U>(self, on_slow: U) -> SlowRequestLayer<U>
+    where
+        U: Fn(&SlowRequest) + Clone,
+    {
+        SlowRequestLayer {
+            threshold: self.threshold,
+            on_slow,
+        }
+    }
+}
+
+impl<S, T> Layer<S> for SlowRequestLayer<T>
+where
+    T: Clone,
+{
+    type Service = SlowRequestWatchdog<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SlowRequestWatchdog {
+            inner,
+            threshold: self.threshold,
+            on_slow: self.on_slow.clone(),
+        }
+    }
+}
+
+/// A request that is still running after the threshold of a [`SlowRequestLayer`].
+#[derive(Debug, Clone)]
+pub struct SlowRequest {
+    method: Method,
+    uri: Uri,
+    route: Option<MatchedPath>,
+    started: Instant,
+    threshold: Duration,
+}
+
+impl SlowRequest {
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The URI of the request.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// The route that matched the request, if known.
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_ref().map(MatchedPath::as_str)
+    }
+
+    /// How long the request has been running for.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// The threshold of the layer.
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+}
+
+/// Middleware that reports requests that are still running after a threshold.
+///
+/// Created with [`SlowRequestLayer`]. See that type for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct SlowRequestWatchdog<S, T = fn(&SlowRequest)> {
+    inner: S,
+    threshold: Duration,
+    on_slow: T,
+}
+
+impl<S, T> Service<Request> for SlowRequestWatchdog<S, T>
+where
+    S: Service<Request, Response = Response>,
+    T: Fn(&SlowRequest) + Clone,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = SlowRequestFuture<S::Future, T>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let request = SlowRequest {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            route: req.extensions().get::<MatchedPath>().cloned(),
+            started: Instant::now(),
+            threshold: self.threshold,
+        };
+        SlowRequestFuture {
+            inner: self.inner.call(req),
+            sleep: Some(Box::pin(tokio::time::sleep(self.threshold))),
+            request,
+            on_slow: self.on_slow.clone(),
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`SlowRequestWatchdog`].
+    pub struct SlowRequestFuture<F, T> {
+        #[pin]
+        inner: F,
+        sleep: Option<Pin<Box<Sleep>>>,
+        request: SlowRequest,
+        on_slow: T,
+    }
+}
+
+impl<F, T, E> Future for SlowRequestFuture<F, T>
+where
+    F: Future<Output = Result<Response, E>>,
+    T: Fn(&SlowRequest),
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Poll::Ready(result) = this.inner.poll(cx) {
+            return Poll::Ready(result);
+        }
+
+        if let Some(sleep) = this.sleep {
+            if sleep.as_mut().poll(cx).is_ready() {
+                *this.sleep = None;
+                (this.on_slow)(this.request);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl<F, T> fmt::Debug for SlowRequestFuture<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestFuture")
+            .field("request", &self.request)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn reports_slow_requests() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let layer = SlowRequestLayer::new(Duration::from_millis(20)).on_slow({
+            let reported = reported.clone();
+            move |req: &SlowRequest| {
+                assert!(req.elapsed() >= req.threshold());
+                reported
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", req.route().unwrap(), req.uri()));
+            }
+        });
+        let app = Router::new()
+            .route(
+                "/slow/:id",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }),
+            )
+            .route("/fast", get(|| async {}))
+            .route_layer(layer);
+        let client = TestClient::new(app);
+
+        client.get("/fast").await;
+        assert!(reported.lock().unwrap().is_empty());
+
+        let res = client.get("/slow/1").await;
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(*reported.lock().unwrap(), ["/slow/:id /slow/1"]);
+    }
+}
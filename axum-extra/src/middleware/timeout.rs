@@ -0,0 +1,303 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    response::{IntoResponse, Response},
+};
+use http::{request::Parts, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that fails requests that take longer than a timeout.
+///
+/// Requests that take too long are answered with `408 Request Timeout`, or the response set with
+/// [`on_timeout`](Self::on_timeout), and the handler is dropped. Unlike the `TimeoutLayer` from
+/// `tower-http`, handlers can read the time they have left with the [`Deadline`] extractor, for
+/// example to set timeouts on calls to other services.
+///
+/// Timeouts can be set for single routes with [`MethodRouterExt`], or for all routes of a router
+/// with [`RouterExt`]. If timeouts are nested, the [`Deadline`] is the earliest one.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get, response::IntoResponse, http::StatusCode};
+/// use axum_extra::{
+///     middleware::Deadline,
+///     routing::{MethodRouterExt, RouterExt},
+/// };
+/// use std::time::Duration;
+///
+/// async fn report(deadline: Deadline) -> String {
+///     format!("{:?} left to build the report", deadline.remaining())
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(|| async {}))
+///     .route(
+///         "/report",
+///         get(report).timeout_with(Duration::from_secs(30), || {
+///             (StatusCode::SERVICE_UNAVAILABLE, "The report took too long").into_response()
+///         }),
+///     )
+///     // the default for all routes
+///     .timeout(Duration::from_secs(60));
+/// # let _: Router = app;
+/// ```
+///
+/// [`MethodRouterExt`]: crate::routing::MethodRouterExt
+/// [`RouterExt`]: crate::routing::RouterExt
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutLayer<T = fn() -> Response> {
+    duration: Duration,
+    on_timeout: T,
+}
+
+impl TimeoutLayer {
+    /// Create a new `TimeoutLayer` that fails requests that take longer than `duration`.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            on_timeout: default_timeout_response,
+        }
+    }
+}
+
+fn default_timeout_response() -> Response {
+    StatusCode::REQUEST_TIMEOUT.into_response()
+}
+
+impl<T> TimeoutLayer<T> {
+    /// Set the response sent for requests that time out.
+    pub fn on_timeout<U>(self, on_timeout: U) -> TimeoutLayer<U>
+    where
+        U: Fn() -> Response + Clone + Send + Sync + 'static,
+    {
+        TimeoutLayer {
+            duration: self.duration,
+            on_timeout,
+        }
+    }
+}
+
+impl<S, T> Layer<S> for TimeoutLayer<T>
+where
+    T: Clone,
+{
+    type Service = Timeout<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Timeout {
+            inner,
+            duration: self.duration,
+            on_timeout: self.on_timeout.clone(),
+        }
+    }
+}
+
+/// Middleware that fails requests that take longer than a timeout.
+///
+/// Created with [`TimeoutLayer`]. See that type for more details.
+#[derive(Debug, Clone)]
+pub struct Timeout<S, T = fn() -> Response> {
+    inner: S,
+    duration: Duration,
+    on_timeout: T,
+}
+
+impl<S, T> Service<Request> for Timeout<S, T>
+where
+    S: Service<Request, Response = Response>,
+    T: Fn() -> Response + Clone,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = TimeoutFuture<S::Future, T>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let mut deadline = Instant::now() + self.duration;
+        if let Some(Deadline(outer)) = req.extensions().get::<Deadline>() {
+            deadline = deadline.min(*outer);
+        }
+        req.extensions_mut().insert(Deadline(deadline));
+
+        TimeoutFuture {
+            inner: self.inner.call(req),
+            sleep: tokio::time::sleep_until(deadline),
+            on_timeout: self.on_timeout.clone(),
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`Timeout`].
+    pub struct TimeoutFuture<F, T> {
+        #[pin]
+        inner: F,
+        #[pin]
+        sleep: Sleep,
+        on_timeout: T,
+    }
+}
+
+impl<F, T, E> Future for TimeoutFuture<F, T>
+where
+    F: Future<Output = Result<Response, E>>,
+    T: Fn() -> Response,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Poll::Ready(result) = this.inner.poll(cx) {
+            return Poll::Ready(result);
+        }
+        match this.sleep.poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Ok((this.on_timeout)())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<F, T> fmt::Debug for TimeoutFuture<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutFuture").finish_non_exhaustive()
+    }
+}
+
+/// Extractor for the time by which the request must be answered, set by [`TimeoutLayer`].
+///
+/// See [`TimeoutLayer`] for an example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// The time by which the request must be answered.
+    pub fn instant(&self) -> std::time::Instant {
+        self.0.into_std()
+    }
+
+    /// The time left until the deadline, or zero if it has passed.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Deadline
+where
+    S: Send + Sync,
+{
+    type Rejection = DeadlineRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .copied()
+            .ok_or(DeadlineRejection::MissingTimeoutLayer)
+    }
+}
+
+/// Rejection used for [`Deadline`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeadlineRejection {
+    /// [`TimeoutLayer`] wasn't applied to the route.
+    MissingTimeoutLayer,
+}
+
+impl IntoResponse for DeadlineRejection {
+    fn into_response(self) -> Response {
+        let body = self.to_string();
+        let status = StatusCode::INTERNAL_SERVER_ERROR;
+        axum_core::__log_rejection!(rejection_type = Self, body_text = body, status = status,);
+        (status, body).into_response()
+    }
+}
+
+impl fmt::Display for DeadlineRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTimeoutLayer => {
+                f.write_str("Missing `TimeoutLayer`. Add it to extract `Deadline`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeadlineRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routing::{MethodRouterExt, RouterExt},
+        test_helpers::*,
+    };
+    use axum::{routing::get, Router};
+
+    async fn slow(deadline: Deadline) -> String {
+        let remaining = deadline.remaining();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        remaining.as_millis().to_string()
+    }
+
+    #[tokio::test]
+    async fn per_route() {
+        let app = Router::new()
+            .route("/default", get(slow).timeout(Duration::from_millis(10)))
+            .route(
+                "/custom",
+                get(slow).timeout_with(Duration::from_millis(10), || {
+                    (StatusCode::SERVICE_UNAVAILABLE, "too slow").into_response()
+                }),
+            )
+            .route("/fast", get(slow).timeout(Duration::from_secs(10)));
+        let client = TestClient::new(app);
+
+        let res = client.get("/default").await;
+        assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+
+        let res = client.get("/custom").await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.text().await, "too slow");
+
+        let res = client.get("/fast").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let remaining = res.text().await.parse::<u64>().unwrap();
+        assert!(remaining > 9_000 && remaining <= 10_000);
+    }
+
+    #[tokio::test]
+    async fn nested() {
+        let app = Router::new()
+            .route("/", get(slow).timeout(Duration::from_secs(20)))
+            .timeout(Duration::from_secs(10));
+        let client = TestClient::new(app);
+
+        let remaining = client.get("/").await.text().await.parse::<u64>().unwrap();
+        assert!(remaining > 9_000 && remaining <= 10_000);
+    }
+
+    #[tokio::test]
+    async fn missing_layer() {
+        let client = TestClient::new(Router::new().route("/", get(slow)));
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
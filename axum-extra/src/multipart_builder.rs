@@ -1,6 +1,7 @@
 //! Generate forms to use in responses. You're probably looking for `MultipartForm``.
 
 use axum::response::{IntoResponse, Response};
+use bytes::{BufMut, BytesMut};
 use fastrand;
 use http::{header, HeaderMap, StatusCode};
 use mime::Mime;
@@ -14,6 +15,16 @@ pub enum TransferEncoding {
     Binary,
 }
 
+impl TransferEncoding {
+    /// The value of the `Content-Transfer-Encoding` header, if one is sent.
+    fn header_value(&self) -> Option<&'static str> {
+        match self {
+            Self::TextUTF8 => None,
+            Self::Binary => Some("binary"),
+        }
+    }
+}
+
 /// Create multipart forms to be used in API responses.
 /// This struct implements [IntoResponse], and so it can be returned from a handler.
 #[derive(Debug)]
@@ -61,14 +72,30 @@ impl IntoResponse for MultipartForm {
         };
         // The use of unwrap is safe here because mime types are inherently string representable
         headers.insert(header::CONTENT_TYPE, mime_type.to_string().parse().unwrap());
-        let mut serialized_form: Vec<u8> = Vec::new();
-        for part in self.parts {
-            // for each part, the boundary is preceded by two dashes
-            serialized_form.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
-            serialized_form.extend_from_slice(&part.serialize());
+
+        // each part is preceded by two dashes, the boundary, and a line break, and the form ends
+        // with two dashes, the boundary, and two more dashes
+        let len = self
+            .parts
+            .iter()
+            .map(|part| 2 + boundary.len() + 2 + part.serialized_len())
+            .sum::<usize>()
+            + 2
+            + boundary.len()
+            + 2;
+        let mut serialized_form = BytesMut::with_capacity(len);
+        for part in &self.parts {
+            serialized_form.put_slice(b"--");
+            serialized_form.put_slice(boundary.as_bytes());
+            serialized_form.put_slice(b"\r\n");
+            part.serialize_into(&mut serialized_form);
         }
-        serialized_form.extend_from_slice(format!("--{}--", boundary).as_bytes());
-        (headers, serialized_form).into_response()
+        serialized_form.put_slice(b"--");
+        serialized_form.put_slice(boundary.as_bytes());
+        serialized_form.put_slice(b"--");
+        debug_assert_eq!(serialized_form.len(), len);
+
+        (headers, serialized_form.freeze()).into_response()
     }
 }
 
@@ -190,8 +217,22 @@ impl Part {
         }
     }
 
-    /// Serialize this part into a chunk that can be easily inserted into a larger form
-    pub(super) fn serialize(&self) -> Vec<u8> {
+    /// The number of bytes [`Part::serialize_into`] writes.
+    pub(super) fn serialized_len(&self) -> usize {
+        let mut len = CONTENT_DISPOSITION.len() + self.name.len() + 1;
+        if let Some(filename) = &self.filename {
+            len += FILENAME.len() + filename.len() + 1;
+        }
+        len += 2 + CONTENT_TYPE.len() + self.mime_type.as_ref().len() + 2;
+        if let Some(encoding) = self.encoding.header_value() {
+            len += CONTENT_TRANSFER_ENCODING.len() + encoding.len() + 2;
+        }
+        len + 2 + self.contents.len() + 2
+    }
+
+    /// Serialize this part into a chunk of a larger form, without allocating if `buf` has
+    /// [`Part::serialized_len`] bytes of spare capacity.
+    pub(super) fn serialize_into(&self, buf: &mut BytesMut) {
         // A part is serialized in this general format:
         // // the filename is optional
         // Content-Disposition: form-data; name="FIELD_NAME"; filename="FILENAME"\r\n
@@ -203,34 +244,37 @@ impl Part {
         // // a blank line, then the contents of the file start
         // \r\n
         // CONTENTS\r\n
-
-        // Format what we can as a string, then handle the rest at a byte level
-        let mut serialized_part = format!("Content-Disposition: form-data; name=\"{}\"", self.name);
+        buf.put_slice(CONTENT_DISPOSITION.as_bytes());
+        buf.put_slice(self.name.as_bytes());
+        buf.put_u8(b'"');
         // specify a filename if one was set
         if let Some(filename) = &self.filename {
-            serialized_part += &format!("; filename=\"{}\"", filename);
+            buf.put_slice(FILENAME.as_bytes());
+            buf.put_slice(filename.as_bytes());
+            buf.put_u8(b'"');
         }
-        serialized_part += "\r\n";
+        buf.put_slice(b"\r\n");
         // specify the MIME type
-        serialized_part += &format!("Content-Type: {}\r\n", self.mime_type);
-        // if an encoding was set, add that
-        // determine what encoding to label the body of the field with
-        let encoding: Option<&str> = match self.encoding {
-            TransferEncoding::TextUTF8 => None,
-            TransferEncoding::Binary => Some("binary"),
-        };
-        if let Some(encoding) = encoding {
-            serialized_part += &format!("Content-Transfer-Encoding: {}\r\n", encoding);
+        buf.put_slice(CONTENT_TYPE.as_bytes());
+        buf.put_slice(self.mime_type.as_ref().as_bytes());
+        buf.put_slice(b"\r\n");
+        // if the part isn't UTF-8 text, label the encoding of its body
+        if let Some(encoding) = self.encoding.header_value() {
+            buf.put_slice(CONTENT_TRANSFER_ENCODING.as_bytes());
+            buf.put_slice(encoding.as_bytes());
+            buf.put_slice(b"\r\n");
         }
-        serialized_part += "\r\n";
-        let mut part_bytes = serialized_part.as_bytes().to_vec();
-        part_bytes.extend_from_slice(&self.contents);
-        part_bytes.extend_from_slice(b"\r\n");
-
-        part_bytes
+        buf.put_slice(b"\r\n");
+        buf.put_slice(&self.contents);
+        buf.put_slice(b"\r\n");
     }
 }
 
+const CONTENT_DISPOSITION: &str = "Content-Disposition: form-data; name=\"";
+const FILENAME: &str = "; filename=\"";
+const CONTENT_TYPE: &str = "Content-Type: ";
+const CONTENT_TRANSFER_ENCODING: &str = "Content-Transfer-Encoding: ";
+
 /// A boundary is defined as a user defined (arbitrary) value that does not occur in any of the data.
 /// Because the specification does not clearly define a methodology for generating boundaries, this implementation
 /// follow's Reqwest's, and generates a boundary in the format of `XXXXXXXX-XXXXXXXX-XXXXXXXX-XXXXXXXX` where `XXXXXXXX`
@@ -245,13 +289,12 @@ fn generate_boundary() -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{generate_boundary, MultipartForm, Part};
-    use axum::{body::Body, http};
+    use super::{generate_boundary, MultipartForm, Part, TransferEncoding};
+    use crate::test_utils::TestClient;
     use axum::{routing::get, Router};
-    use http::{Request, Response};
-    use http_body_util::BodyExt;
+    use bytes::BytesMut;
+    use http::StatusCode;
     use mime::Mime;
-    use tower::ServiceExt;
 
     #[tokio::test]
     async fn process_form() -> Result<(), Box<dyn std::error::Error>> {
@@ -276,16 +319,14 @@ mod tests {
         }
 
         // make a request to that handle
-        let app = Router::new().route("/", get(handle));
-        let response: Response<_> = app
-            .oneshot(Request::builder().uri("/").body(Body::empty())?)
-            .await?;
+        let client = TestClient::new(Router::new().route("/", get(handle)));
+        let response = client.get("/").await;
+        response.assert_status(StatusCode::OK);
         // content_type header
         let ct_header = response.headers().get("content-type").unwrap().to_str()?;
         let boundary = ct_header.split("boundary=").nth(1).unwrap().to_owned();
-        let body: &[u8] = &response.into_body().collect().await?.to_bytes();
         assert_eq!(
-            std::str::from_utf8(body)?,
+            response.text(),
             &format!(
                 "--{boundary}\r\n\
                 Content-Disposition: form-data; name=\"part1\"\r\n\
@@ -311,6 +352,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn serialized_len_is_exact() {
+        let parts = [
+            Part::text("text".to_owned(), "abc"),
+            Part::file("file", "file.txt", vec![0xff; 16]),
+            Part::raw_part(
+                "raw",
+                mime::APPLICATION_JSON,
+                b"{}".to_vec(),
+                Some("data.json"),
+                TransferEncoding::TextUTF8,
+            ),
+        ];
+        for part in parts {
+            let mut buf = BytesMut::new();
+            part.serialize_into(&mut buf);
+            assert_eq!(buf.len(), part.serialized_len());
+        }
+    }
+
     #[test]
     fn valid_boundary_generation() {
         for _ in 0..256 {
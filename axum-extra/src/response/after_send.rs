@@ -0,0 +1,318 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::Request,
+    response::{IntoResponseParts, Response, ResponseParts},
+    Error,
+};
+use futures_util::future::{MapOk, TryFutureExt};
+use http_body::Frame;
+use std::{
+    convert::Infallible,
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+type Callback = Box<dyn FnOnce(&SendOutcome) + Send>;
+
+/// Runs a callback after the response has been sent.
+///
+/// Middleware created with `map_response` runs before the body has been sent, so it can't tell
+/// how much of the body was sent or whether the client received all of it. `AfterSend`
+/// callbacks run once the body has ended, failed, or been dropped, which makes them suitable for
+/// access logs, accounting, and cleanup that must wait until the response is done.
+///
+/// `AfterSend` is returned as part of the response, like headers, and requires
+/// [`AfterSendLayer`] which watches the response body. A response can have multiple callbacks,
+/// which run in the order they were added.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::response::{AfterSend, AfterSendLayer};
+///
+/// async fn download() -> (AfterSend, &'static str) {
+///     let after_send = AfterSend::new(|outcome| {
+///         println!("sent {} bytes: {:?}", outcome.bytes_sent(), outcome.status());
+///     });
+///
+///     (after_send, "Hello, World!")
+/// }
+///
+/// let app = Router::new()
+///     .route("/download", get(download))
+///     .layer(AfterSendLayer::new());
+/// # let _: Router = app;
+/// ```
+pub struct AfterSend {
+    callback: Callback,
+}
+
+impl AfterSend {
+    /// Create a new `AfterSend` that calls `f` after the response has been sent.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: FnOnce(&SendOutcome) + Send + 'static,
+    {
+        Self {
+            callback: Box::new(f),
+        }
+    }
+}
+
+impl fmt::Debug for AfterSend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AfterSend").finish_non_exhaustive()
+    }
+}
+
+impl IntoResponseParts for AfterSend {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Some(Callbacks(callbacks)) = res.extensions_mut().get_mut::<Callbacks>() {
+            callbacks.lock().unwrap().push(self.callback);
+        } else {
+            res.extensions_mut()
+                .insert(Callbacks(Arc::new(Mutex::new(vec![self.callback]))));
+        }
+        Ok(res)
+    }
+}
+
+/// The callbacks of a response, inserted into the response extensions by [`AfterSend`].
+#[derive(Clone)]
+struct Callbacks(Arc<Mutex<Vec<Callback>>>);
+
+/// How sending a response went, passed to [`AfterSend`] callbacks.
+#[derive(Debug, Clone)]
+pub struct SendOutcome {
+    status: SendStatus,
+    bytes_sent: u64,
+}
+
+impl SendOutcome {
+    /// Whether the whole body was sent.
+    pub fn status(&self) -> SendStatus {
+        self.status
+    }
+
+    /// The number of body bytes that were sent, not including headers.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+}
+
+/// Whether the whole body of a response was sent. See [`SendOutcome::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SendStatus {
+    /// The whole body was handed to the server.
+    Completed,
+    /// The body produced an error.
+    Failed,
+    /// The body was dropped before it ended, usually because the client disconnected.
+    Aborted,
+}
+
+/// Layer that runs the [`AfterSend`] callbacks of responses after they have been sent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AfterSendLayer {
+    _priv: (),
+}
+
+impl AfterSendLayer {
+    /// Create a new `AfterSendLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for AfterSendLayer {
+    type Service = AfterSendService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AfterSendService { inner }
+    }
+}
+
+/// Middleware that runs the [`AfterSend`] callbacks of responses after they have been sent.
+///
+/// Created with [`AfterSendLayer`].
+#[derive(Debug, Clone)]
+pub struct AfterSendService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for AfterSendService<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = MapOk<S::Future, fn(Response) -> Response>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner
+            .call(req)
+            .map_ok(watch_body as fn(Response) -> Response)
+    }
+}
+
+fn watch_body(res: Response) -> Response {
+    let (mut parts, body) = res.into_parts();
+    let callbacks = parts.extensions.remove::<Callbacks>();
+    let callbacks = callbacks
+        .as_ref()
+        .map(|Callbacks(callbacks)| std::mem::take(&mut *callbacks.lock().unwrap()))
+        .unwrap_or_default();
+    if callbacks.is_empty() {
+        return Response::from_parts(parts, body);
+    }
+
+    let body = AfterSendBody {
+        inner: body,
+        bytes_sent: 0,
+        callbacks,
+    };
+    Response::from_parts(parts, Body::new(body))
+}
+
+struct AfterSendBody {
+    inner: Body,
+    bytes_sent: u64,
+    callbacks: Vec<Callback>,
+}
+
+impl AfterSendBody {
+    fn finish(&mut self, status: SendStatus) {
+        let outcome = SendOutcome {
+            status,
+            bytes_sent: self.bytes_sent,
+        };
+        for callback in self.callbacks.drain(..) {
+            callback(&outcome);
+        }
+    }
+}
+
+impl HttpBody for AfterSendBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    self.bytes_sent += data.len() as u64;
+                }
+            }
+            Some(Err(_)) => self.finish(SendStatus::Failed),
+            None => self.finish(SendStatus::Completed),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for AfterSendBody {
+    fn drop(&mut self) {
+        // servers stop polling once the body reports that it has ended
+        let status = if self.inner.is_end_stream() {
+            SendStatus::Completed
+        } else {
+            SendStatus::Aborted
+        };
+        self.finish(status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use http_body_util::BodyExt;
+    use tokio::sync::mpsc;
+    use tower::ServiceExt;
+
+    fn app(tx: mpsc::UnboundedSender<(&'static str, SendStatus, u64)>) -> Router {
+        let callback = move |name: &'static str| {
+            let tx = tx.clone();
+            AfterSend::new(move |outcome| {
+                tx.send((name, outcome.status(), outcome.bytes_sent()))
+                    .unwrap();
+            })
+        };
+        Router::new()
+            .route(
+                "/",
+                get(move || async move { (callback("first"), callback("second"), "body") }),
+            )
+            .route("/without", get(|| async { "body" }))
+            .layer(AfterSendLayer::new())
+    }
+
+    fn request(uri: &str) -> Request {
+        http::Request::builder()
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn completed() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = app(tx);
+
+        let res = app.clone().oneshot(request("/")).await.unwrap();
+        assert!(rx.try_recv().is_err());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "body");
+        drop(app);
+
+        assert_eq!(rx.recv().await, Some(("first", SendStatus::Completed, 4)));
+        assert_eq!(rx.recv().await, Some(("second", SendStatus::Completed, 4)));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn aborted() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = app(tx);
+
+        let res = app.oneshot(request("/")).await.unwrap();
+        drop(res);
+
+        assert_eq!(rx.recv().await, Some(("first", SendStatus::Aborted, 0)));
+    }
+
+    #[tokio::test]
+    async fn without_callbacks() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = app(tx);
+
+        let res = app.oneshot(request("/without")).await.unwrap();
+        res.into_body().collect().await.unwrap();
+        assert_eq!(rx.recv().await, None);
+    }
+}
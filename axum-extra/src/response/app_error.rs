@@ -0,0 +1,148 @@
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use std::{fmt, sync::RwLock};
+
+type Hook = Box<dyn Fn(&anyhow::Error) -> Response + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// An error type for handlers that can be created from any error, using [`anyhow`].
+///
+/// `AppError` implements `From` for every type that can be converted into an [`anyhow::Error`],
+/// so `?` works on most errors in handlers returning `Result<T, AppError>`.
+///
+/// By default the response is `500 Internal Server Error` without any details about the error,
+/// and the error is logged, including its backtrace if one was captured, when the `tracing`
+/// feature is enabled. Use [`AppError::set_hook`] to customize the response.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::response::AppError;
+///
+/// async fn handler() -> Result<String, AppError> {
+///     let contents = tokio::fs::read_to_string("config.toml").await?;
+///     Ok(contents)
+/// }
+///
+/// let app = Router::new().route("/", get(handler));
+/// # let _: Router = app;
+/// ```
+pub struct AppError(anyhow::Error);
+
+impl AppError {
+    /// Create an `AppError` from an [`anyhow::Error`].
+    pub fn new(err: anyhow::Error) -> Self {
+        Self(err)
+    }
+
+    /// Get a reference to the underlying [`anyhow::Error`].
+    pub fn inner(&self) -> &anyhow::Error {
+        &self.0
+    }
+
+    /// Consume `self`, returning the underlying [`anyhow::Error`].
+    pub fn into_inner(self) -> anyhow::Error {
+        self.0
+    }
+
+    /// Set the function that turns all `AppError`s into responses.
+    ///
+    /// This replaces the default response and logging, and any previously set hook. Downcast the
+    /// error to choose the status code for specific errors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{http::StatusCode, response::IntoResponse};
+    /// use axum_extra::response::AppError;
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    ///
+    /// impl std::fmt::Display for NotFound {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         f.write_str("not found")
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for NotFound {}
+    ///
+    /// AppError::set_hook(|err| {
+    ///     if err.downcast_ref::<NotFound>().is_some() {
+    ///         return StatusCode::NOT_FOUND.into_response();
+    ///     }
+    ///     eprintln!("{err:?}");
+    ///     StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    /// });
+    /// ```
+    pub fn set_hook<F>(hook: F)
+    where
+        F: Fn(&anyhow::Error) -> Response + Send + Sync + 'static,
+    {
+        *HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Box::new(hook));
+    }
+}
+
+impl<E> From<E> for AppError
+where
+    E: Into<anyhow::Error>,
+{
+    fn from(err: E) -> Self {
+        Self(err.into())
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let hook = HOOK.read().unwrap_or_else(|err| err.into_inner());
+        if let Some(hook) = &*hook {
+            return hook(&self.0);
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::error!(error = ?self.0, "handler failed");
+
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    }
+}
+
+impl fmt::Debug for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    #[tokio::test]
+    async fn responses() {
+        async fn handler() -> Result<(), AppError> {
+            "nope".parse::<u32>()?;
+            Ok(())
+        }
+
+        let client = TestClient::new(Router::new().route("/", get(handler)));
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.text().await, "");
+
+        AppError::set_hook(|err| (StatusCode::BAD_REQUEST, err.to_string()).into_response());
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.text().await, "invalid digit found in string");
+    }
+}
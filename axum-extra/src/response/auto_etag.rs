@@ -0,0 +1,245 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use headers::{ETag, HeaderMapExt, IfNoneMatch};
+use http::{header, response::Parts, Method, StatusCode};
+use http_body_util::BodyExt;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A response that gets a strong `ETag` computed from its body.
+///
+/// [`AutoEtagLayer`] buffers the body of `200 OK` responses wrapped in `AutoEtag`, sets an `ETag`
+/// derived from its SHA-256 hash, and responds with `304 Not Modified` without a body if the
+/// `If-None-Match` header of a `GET` or `HEAD` request matches it. **Without the layer the
+/// response doesn't get an `ETag`.**
+///
+/// This is meant for small responses that are generated on every request, where computing a
+/// hash is cheap compared to clients downloading the response again. Responses that already
+/// have an `ETag` are left alone.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, Json, routing::get};
+/// use axum_extra::response::{AutoEtag, AutoEtagLayer};
+/// use serde_json::{json, Value};
+///
+/// async fn settings() -> AutoEtag<Json<Value>> {
+///     AutoEtag(Json(json!({ "theme": "dark" })))
+/// }
+///
+/// let app = Router::new()
+///     .route("/settings", get(settings))
+///     .layer(AutoEtagLayer::new());
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct AutoEtag<T>(pub T);
+
+impl<T> IntoResponse for AutoEtag<T>
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let mut res = self.0.into_response();
+        res.extensions_mut().insert(Marker);
+        res
+    }
+}
+
+/// Marks responses that should get an `ETag`, inserted into the response extensions by
+/// [`AutoEtag`].
+#[derive(Clone, Copy)]
+struct Marker;
+
+/// Layer that sets the `ETag` of [`AutoEtag`] responses and responds with `304 Not Modified`
+/// if the `If-None-Match` header matches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoEtagLayer {
+    _priv: (),
+}
+
+impl AutoEtagLayer {
+    /// Create a new `AutoEtagLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for AutoEtagLayer {
+    type Service = AutoEtagService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AutoEtagService { inner }
+    }
+}
+
+/// Middleware that sets the `ETag` of [`AutoEtag`] responses and responds with
+/// `304 Not Modified` if the `If-None-Match` header matches.
+///
+/// Created with [`AutoEtagLayer`].
+#[derive(Debug, Clone)]
+pub struct AutoEtagService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for AutoEtagService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // `If-None-Match` on other methods requires `412 Precondition Failed` instead, which
+        // handlers changing state should decide on themselves
+        let if_none_match = match *req.method() {
+            Method::GET | Method::HEAD => req.headers().typed_get::<IfNoneMatch>(),
+            _ => None,
+        };
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let res = future.await?;
+            Ok(tag(res, if_none_match).await)
+        })
+    }
+}
+
+async fn tag(res: Response, if_none_match: Option<IfNoneMatch>) -> Response {
+    let (mut parts, body) = res.into_parts();
+    if parts.extensions.remove::<Marker>().is_none()
+        || parts.status != StatusCode::OK
+        || parts.headers.contains_key(header::ETAG)
+    {
+        return Response::from_parts(parts, body);
+    }
+
+    let bytes = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(_err) => {
+            #[cfg(feature = "tracing")]
+            tracing::error!(error = %_err, "failed to buffer response body for `ETag`");
+
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let etag = etag(&bytes);
+    parts.headers.typed_insert(etag.clone());
+
+    match if_none_match {
+        Some(if_none_match) if !if_none_match.precondition_passes(&etag) => not_modified(parts),
+        _ => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+fn etag(bytes: &[u8]) -> ETag {
+    let hash = Sha256::digest(bytes);
+    let mut etag = String::with_capacity(34);
+    etag.push('"');
+    for byte in &hash[..16] {
+        write!(etag, "{byte:02x}").unwrap();
+    }
+    etag.push('"');
+    etag.parse().unwrap()
+}
+
+fn not_modified(parts: Parts) -> Response {
+    let mut res = StatusCode::NOT_MODIFIED.into_response();
+    // the headers a `304` response must include if they would have been sent with `200 OK`
+    for name in [
+        header::CACHE_CONTROL,
+        header::CONTENT_LOCATION,
+        header::DATE,
+        header::ETAG,
+        header::EXPIRES,
+        header::VARY,
+    ] {
+        for value in parts.headers.get_all(&name) {
+            res.headers_mut().append(name.clone(), value.clone());
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    AutoEtag(([(header::CACHE_CONTROL, "no-cache")], "Hello, World!"))
+                }),
+            )
+            .route("/plain", get(|| async { "Hello, World!" }))
+            .route(
+                "/not-found",
+                get(|| async { AutoEtag((StatusCode::NOT_FOUND, "Not Found")) }),
+            )
+            .layer(AutoEtagLayer::new())
+    }
+
+    #[tokio::test]
+    async fn sets_etag() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let etag = res.headers()["etag"].to_str().unwrap().to_owned();
+        assert_eq!(etag, "\"dffd6021bb2bd5b0af676290809ec3a5\"");
+        assert_eq!(res.text().await, "Hello, World!");
+
+        let res = client.get("/plain").await;
+        assert!(res.headers().get("etag").is_none());
+
+        let res = client.get("/not-found").await;
+        assert!(res.headers().get("etag").is_none());
+    }
+
+    #[tokio::test]
+    async fn if_none_match() {
+        let client = TestClient::new(app());
+
+        let etag = "\"dffd6021bb2bd5b0af676290809ec3a5\"";
+        for if_none_match in [
+            etag,
+            "\"other\", W/\"dffd6021bb2bd5b0af676290809ec3a5\"",
+            "*",
+        ] {
+            let res = client.get("/").header("if-none-match", if_none_match).await;
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(res.headers()["etag"], etag);
+            assert_eq!(res.headers()["cache-control"], "no-cache");
+            assert!(res.headers().get("content-type").is_none());
+            assert_eq!(res.text().await, "");
+        }
+
+        let res = client.get("/").header("if-none-match", "\"other\"").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "Hello, World!");
+    }
+}
@@ -0,0 +1,294 @@
+use crate::extract::{AcceptEncoding, Encoding};
+use axum::{
+    body::Body,
+    response::{IntoResponse, Response},
+};
+use futures_util::TryStreamExt;
+use http::{
+    header::{self, HeaderValue},
+    HeaderMap, StatusCode,
+};
+use http_body::Body as _;
+use std::io;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+/// Bodies smaller than this aren't compressed, as the overhead outweighs the savings.
+const MIN_SIZE: u64 = 32;
+
+/// A response that is compressed with the best encoding accepted by the client.
+///
+/// This is useful for compressing the responses of a few large endpoints, without compressing
+/// every response with a middleware like [`tower_http::compression`]. The encodings are chosen
+/// with [`AcceptEncoding::choose`], preferring Zstandard, then Brotli, then gzip, among those
+/// enabled with the `compression-zstd`, `compression-br`, and `compression-gzip` features. The
+/// body is compressed as it's streamed.
+///
+/// `Vary: Accept-Encoding` is always added. The response isn't compressed if it already has a
+/// `Content-Encoding` or `Content-Range` header, has no body, is smaller than 32 bytes, or is an
+/// image or server-sent event stream. Compressed responses don't have `Content-Length` or
+/// `Accept-Ranges` headers, since those refer to the uncompressed body. A strong `ETag` is made
+/// weak, since the compressed bytes differ from the ones it was computed for.
+///
+/// [`tower_http::compression`]: https://docs.rs/tower-http/latest/tower_http/compression/index.html
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::{extract::AcceptEncoding, response::Compressed};
+///
+/// async fn report(accept_encoding: AcceptEncoding) -> Compressed<String> {
+///     let report = "lots of text".repeat(1000);
+///     Compressed::new(report, accept_encoding)
+/// }
+///
+/// let app = Router::new().route("/report", get(report));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Compressed<T> {
+    body: T,
+    accept_encoding: AcceptEncoding,
+}
+
+impl<T> Compressed<T> {
+    /// Create a new `Compressed` from a response and the encodings accepted by the client.
+    pub fn new(body: T, accept_encoding: AcceptEncoding) -> Self {
+        Self {
+            body,
+            accept_encoding,
+        }
+    }
+}
+
+impl<T> IntoResponse for Compressed<T>
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let mut res = self.body.into_response();
+        add_vary(res.headers_mut());
+
+        if !should_compress(&res) {
+            return res;
+        }
+
+        let available = [
+            #[cfg(feature = "compression-zstd")]
+            Encoding::Zstd,
+            #[cfg(feature = "compression-br")]
+            Encoding::Br,
+            #[cfg(feature = "compression-gzip")]
+            Encoding::Gzip,
+            Encoding::Identity,
+        ];
+        let encoding = match self.accept_encoding.choose(&available) {
+            Some(Encoding::Identity) | None => return res,
+            Some(encoding) => encoding,
+        };
+
+        let (mut parts, body) = res.into_parts();
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::ACCEPT_RANGES);
+        weaken_etag(&mut parts.headers);
+        parts.headers.insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static(encoding.as_str()),
+        );
+        Response::from_parts(parts, compress(body, encoding))
+    }
+}
+
+fn add_vary(headers: &mut HeaderMap) {
+    let varies = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| {
+            let value = value.trim();
+            value == "*" || value.eq_ignore_ascii_case("accept-encoding")
+        });
+    if !varies {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+fn weaken_etag(headers: &mut HeaderMap) {
+    let Some(etag) = headers.get(header::ETAG) else {
+        return;
+    };
+    if etag.as_bytes().starts_with(b"W/") {
+        return;
+    }
+    let mut weak = b"W/".to_vec();
+    weak.extend_from_slice(etag.as_bytes());
+    match HeaderValue::from_bytes(&weak) {
+        Ok(weak) => {
+            headers.insert(header::ETAG, weak);
+        }
+        Err(_) => {
+            headers.remove(header::ETAG);
+        }
+    }
+}
+
+fn should_compress(res: &Response) -> bool {
+    let status = res.status();
+    if status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+    {
+        return false;
+    }
+
+    let headers = res.headers();
+    if headers.contains_key(header::CONTENT_ENCODING) || headers.contains_key(header::CONTENT_RANGE)
+    {
+        return false;
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    // images other than SVG are already compressed
+    if (content_type.starts_with("image/") && !content_type.starts_with("image/svg+xml"))
+        || content_type.starts_with("text/event-stream")
+    {
+        return false;
+    }
+
+    let size_hint = res.body().size_hint();
+    !matches!(size_hint.upper(), Some(upper) if upper < MIN_SIZE)
+}
+
+fn compress(body: Body, encoding: Encoding) -> Body {
+    let reader = StreamReader::new(
+        body.into_data_stream()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err)),
+    );
+
+    match encoding {
+        #[cfg(feature = "compression-zstd")]
+        Encoding::Zstd => Body::from_stream(ReaderStream::new(
+            async_compression::tokio::bufread::ZstdEncoder::new(reader),
+        )),
+        #[cfg(feature = "compression-br")]
+        Encoding::Br => Body::from_stream(ReaderStream::new(
+            async_compression::tokio::bufread::BrotliEncoder::new(reader),
+        )),
+        #[cfg(feature = "compression-gzip")]
+        Encoding::Gzip => Body::from_stream(ReaderStream::new(
+            async_compression::tokio::bufread::GzipEncoder::new(reader),
+        )),
+        // only encodings that are enabled are chosen
+        _ => Body::from_stream(ReaderStream::new(reader)),
+    }
+}
+
+#[cfg(all(test, feature = "compression-gzip"))]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use tokio::io::AsyncReadExt;
+
+    fn body() -> String {
+        "hello world ".repeat(100)
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|accept_encoding: AcceptEncoding| async move {
+                    Compressed::new(body(), accept_encoding)
+                }),
+            )
+            .route(
+                "/small",
+                get(|accept_encoding: AcceptEncoding| async move {
+                    Compressed::new("hello", accept_encoding)
+                }),
+            )
+            .route(
+                "/etag",
+                get(|accept_encoding: AcceptEncoding| async move {
+                    Compressed::new(([(header::ETAG, "\"v1\"")], body()), accept_encoding)
+                }),
+            )
+            .route(
+                "/weak-etag",
+                get(|accept_encoding: AcceptEncoding| async move {
+                    Compressed::new(([(header::ETAG, "W/\"v1\"")], body()), accept_encoding)
+                }),
+            )
+            .route(
+                "/image",
+                get(|accept_encoding: AcceptEncoding| async move {
+                    Compressed::new(
+                        ([(header::CONTENT_TYPE, "image/png")], body()),
+                        accept_encoding,
+                    )
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn gzip() {
+        let client = TestClient::new(app());
+
+        let res = client
+            .get("/")
+            .header("accept-encoding", "gzip, identity;q=0.5")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-encoding"], "gzip");
+        assert_eq!(res.headers()["vary"], "accept-encoding");
+
+        let compressed = res.bytes().await;
+        assert!(compressed.len() < body().len());
+        let mut decompressed = String::new();
+        async_compression::tokio::bufread::GzipDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .await
+            .unwrap();
+        assert_eq!(decompressed, body());
+    }
+
+    #[tokio::test]
+    async fn weakens_etag() {
+        let client = TestClient::new(app());
+
+        for uri in ["/etag", "/weak-etag"] {
+            let res = client.get(uri).header("accept-encoding", "gzip").await;
+            assert_eq!(res.headers()["content-encoding"], "gzip");
+            assert_eq!(res.headers()["etag"], "W/\"v1\"");
+        }
+
+        // uncompressed responses keep the strong entity tag
+        let res = client.get("/etag").await;
+        assert_eq!(res.headers()["etag"], "\"v1\"");
+    }
+
+    #[tokio::test]
+    async fn not_compressed() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/").await;
+        assert!(res.headers().get("content-encoding").is_none());
+        assert_eq!(res.headers()["vary"], "accept-encoding");
+        assert_eq!(res.text().await, body());
+
+        let res = client.get("/").header("accept-encoding", "gzip;q=0").await;
+        assert!(res.headers().get("content-encoding").is_none());
+
+        for uri in ["/small", "/image"] {
+            let res = client.get(uri).header("accept-encoding", "gzip").await;
+            assert!(res.headers().get("content-encoding").is_none());
+            assert_eq!(res.headers()["vary"], "accept-encoding");
+        }
+    }
+}
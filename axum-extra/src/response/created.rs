@@ -0,0 +1,130 @@
+use crate::routing::TypedPath;
+use axum::response::{IntoResponse, Response};
+use http::{header::LOCATION, HeaderValue, StatusCode};
+
+/// A `201 Created` response with a `Location` header built from a [`TypedPath`].
+///
+/// Building the location from the same type that routes the created resource means the two can't
+/// drift apart, and the derived [`TypedPath`] percent-encodes captures. Use
+/// [`with_body`](Self::with_body) to also respond with the created resource.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, Json, routing::post};
+/// use axum_extra::{response::Created, routing::{RouterExt, TypedPath}};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(TypedPath, Deserialize)]
+/// #[typed_path("/users/:id")]
+/// struct UserPath {
+///     id: u64,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// // responds with `Location: /users/1`
+/// async fn create_user(Json(name): Json<String>) -> Created<UserPath, Json<User>> {
+///     let user = User { id: 1, name };
+///     Created::new(UserPath { id: user.id }).with_body(Json(user))
+/// }
+///
+/// let app = Router::new().route("/users", post(create_user));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy)]
+#[must_use]
+pub struct Created<L, T = ()> {
+    location: L,
+    body: T,
+}
+
+impl<L> Created<L> {
+    /// Create a new `Created` response without a body.
+    pub fn new(location: L) -> Self {
+        Self { location, body: () }
+    }
+}
+
+impl<L, T> Created<L, T> {
+    /// Respond with `body`.
+    pub fn with_body<U>(self, body: U) -> Created<L, U> {
+        Created {
+            location: self.location,
+            body,
+        }
+    }
+
+    /// The location of the created resource.
+    pub fn location(&self) -> &L {
+        &self.location
+    }
+}
+
+impl<L, T> IntoResponse for Created<L, T>
+where
+    L: TypedPath,
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let location = HeaderValue::try_from(self.location.to_uri().to_string())
+            .expect("URI isn't a valid header value");
+        (StatusCode::CREATED, [(LOCATION, location)], self.body).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::post, Router};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/files/:name")]
+    struct FilePath {
+        name: String,
+    }
+
+    #[derive(Serialize)]
+    struct Params {
+        version: u32,
+    }
+
+    #[tokio::test]
+    async fn location() {
+        let app = Router::new()
+            .route(
+                "/files",
+                post(|| async {
+                    Created::new(FilePath {
+                        name: "a b/c".to_owned(),
+                    })
+                }),
+            )
+            .route(
+                "/files-with-body",
+                post(|| async {
+                    let path = FilePath {
+                        name: "report".to_owned(),
+                    };
+                    Created::new(path.with_query_params(Params { version: 2 })).with_body("created")
+                }),
+            );
+        let client = TestClient::new(app);
+
+        let res = client.post("/files").await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers()["location"], "/files/a%20b%2Fc");
+        assert_eq!(res.text().await, "");
+
+        let res = client.post("/files-with-body").await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers()["location"], "/files/report?&version=2");
+        assert_eq!(res.text().await, "created");
+    }
+}
@@ -0,0 +1,335 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use http::{header, request::Parts, HeaderValue};
+use mime::Mime;
+use std::{convert::Infallible, fmt::Write};
+
+/// Whether a response should be displayed by the browser or downloaded.
+///
+/// Sent with the `Content-Disposition` header. See [`InlineOrAttachment`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposition {
+    /// Display the response in the browser.
+    Inline,
+    /// Download the response as a file.
+    Attachment,
+}
+
+impl Disposition {
+    /// Choose the disposition for a MIME type, based on whether browsers can safely display it.
+    ///
+    /// Images, audio, video, PDFs, plain text, and JSON are displayed inline, anything else is
+    /// downloaded. Types that can run scripts, like HTML and SVG, are always downloaded.
+    pub fn for_mime(mime: &Mime) -> Self {
+        if is_displayable(mime) {
+            Self::Inline
+        } else {
+            Self::Attachment
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Attachment => "attachment",
+        }
+    }
+}
+
+fn is_displayable(mime: &Mime) -> bool {
+    let type_ = mime.type_();
+    let subtype = mime.subtype();
+
+    (type_ == mime::IMAGE && subtype != mime::SVG)
+        || type_ == mime::AUDIO
+        || type_ == mime::VIDEO
+        || (type_ == mime::TEXT && subtype == mime::PLAIN)
+        || (type_ == mime::APPLICATION && (subtype == mime::PDF || subtype == mime::JSON))
+}
+
+fn can_run_scripts(mime: &Mime) -> bool {
+    let type_ = mime.type_();
+    let subtype = mime.subtype();
+    let suffix = mime.suffix();
+
+    subtype == mime::HTML
+        || subtype == mime::XML
+        || subtype == mime::JAVASCRIPT
+        || subtype == "ecmascript"
+        || subtype == "xhtml"
+        || suffix == Some(mime::XML)
+        || (type_ == mime::IMAGE && subtype == mime::SVG)
+}
+
+/// Extractor for the disposition requested with the `download` query parameter.
+///
+/// `?download`, `?download=1`, and `?download=true` request [`Disposition::Attachment`], while
+/// `?download=0` and `?download=false` request [`Disposition::Inline`]. It's `None` if the
+/// parameter is missing or has any other value. Pass it to [`InlineOrAttachment::requested`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestedDisposition(pub Option<Disposition>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestedDisposition
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let requested = parts
+            .uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter_map(|pair| match pair.split_once('=') {
+                Some(("download", value)) => Some(value),
+                None if pair == "download" => Some(""),
+                _ => None,
+            })
+            .next_back()
+            .and_then(|value| match value {
+                "" | "1" | "true" => Some(Disposition::Attachment),
+                "0" | "false" => Some(Disposition::Inline),
+                _ => None,
+            });
+
+        Ok(Self(requested))
+    }
+}
+
+/// A response that is either displayed by the browser or downloaded, for "view or download"
+/// endpoints.
+///
+/// The disposition is chosen from the [`RequestedDisposition`] if one is set, or else from the
+/// MIME type with [`Disposition::for_mime`]. Types that can run scripts, like HTML and SVG, are
+/// always downloaded, even if displaying them was requested, so user uploads can't run scripts
+/// on your origin. `X-Content-Type-Options: nosniff` is set so browsers don't guess a different
+/// type.
+///
+/// The filename is stripped of any directories and control characters, and sent both as a
+/// quoted ASCII fallback and, if it contains other characters, encoded according to
+/// [RFC 8187](https://www.rfc-editor.org/rfc/rfc8187).
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::response::{InlineOrAttachment, RequestedDisposition};
+///
+/// // `/report` is displayed in the browser, `/report?download` is downloaded
+/// async fn report(requested: RequestedDisposition) -> InlineOrAttachment<Vec<u8>> {
+///     let pdf = Vec::new();
+///
+///     InlineOrAttachment::new(mime::APPLICATION_PDF, pdf)
+///         .filename("report.pdf")
+///         .requested(requested)
+/// }
+///
+/// let app = Router::new().route("/report", get(report));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct InlineOrAttachment<T> {
+    content_type: Mime,
+    body: T,
+    filename: Option<String>,
+    requested: Option<Disposition>,
+}
+
+impl<T> InlineOrAttachment<T> {
+    /// Create a new `InlineOrAttachment` with the given content type and body.
+    pub fn new(content_type: Mime, body: T) -> Self {
+        Self {
+            content_type,
+            body,
+            filename: None,
+            requested: None,
+        }
+    }
+
+    /// Set the name of the file, used by browsers when saving the response.
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Use the disposition requested by the client, if any.
+    pub fn requested(mut self, requested: RequestedDisposition) -> Self {
+        self.requested = requested.0;
+        self
+    }
+
+    /// The disposition the response will be sent with.
+    pub fn disposition(&self) -> Disposition {
+        if can_run_scripts(&self.content_type) {
+            return Disposition::Attachment;
+        }
+        self.requested
+            .unwrap_or_else(|| Disposition::for_mime(&self.content_type))
+    }
+}
+
+impl<T> IntoResponse for InlineOrAttachment<T>
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let disposition = content_disposition(self.disposition(), self.filename.as_deref());
+        let content_type = HeaderValue::from_str(self.content_type.as_ref())
+            .unwrap_or_else(|_| HeaderValue::from_static(mime::APPLICATION_OCTET_STREAM.as_ref()));
+
+        (
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::CONTENT_DISPOSITION, disposition),
+                (
+                    header::X_CONTENT_TYPE_OPTIONS,
+                    HeaderValue::from_static("nosniff"),
+                ),
+            ],
+            self.body,
+        )
+            .into_response()
+    }
+}
+
+fn content_disposition(disposition: Disposition, filename: Option<&str>) -> HeaderValue {
+    let mut value = disposition.as_str().to_owned();
+
+    if let Some(filename) = filename.and_then(sanitize_filename) {
+        let fallback: String = filename
+            .chars()
+            .map(|c| match c {
+                ' '..='~' if !matches!(c, '"' | '\\' | '%') => c,
+                _ => '_',
+            })
+            .collect();
+        write!(value, "; filename=\"{fallback}\"").unwrap();
+
+        if fallback != filename {
+            value.push_str("; filename*=UTF-8''");
+            for byte in filename.bytes() {
+                if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                    value.push(byte as char);
+                } else {
+                    write!(value, "%{byte:02X}").unwrap();
+                }
+            }
+        }
+    }
+
+    // only contains visible ASCII characters
+    HeaderValue::from_str(&value).unwrap()
+}
+
+fn sanitize_filename(filename: &str) -> Option<String> {
+    let filename = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let filename: String = filename.chars().filter(|c| !c.is_control()).collect();
+    let filename = filename.trim().trim_start_matches('.');
+
+    if filename.is_empty() {
+        None
+    } else {
+        Some(filename.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    #[test]
+    fn disposition_for_mime() {
+        assert_eq!(Disposition::for_mime(&mime::IMAGE_PNG), Disposition::Inline);
+        assert_eq!(
+            Disposition::for_mime(&mime::APPLICATION_PDF),
+            Disposition::Inline
+        );
+        assert_eq!(
+            Disposition::for_mime(&mime::TEXT_PLAIN_UTF_8),
+            Disposition::Inline
+        );
+        assert_eq!(
+            Disposition::for_mime(&mime::IMAGE_SVG),
+            Disposition::Attachment
+        );
+        assert_eq!(
+            Disposition::for_mime(&mime::TEXT_HTML),
+            Disposition::Attachment
+        );
+        assert_eq!(
+            Disposition::for_mime(&mime::APPLICATION_OCTET_STREAM),
+            Disposition::Attachment
+        );
+    }
+
+    #[test]
+    fn filenames() {
+        let header = |filename| content_disposition(Disposition::Attachment, Some(filename));
+
+        assert_eq!(header("report.pdf"), "attachment; filename=\"report.pdf\"");
+        assert_eq!(
+            header("../../etc/passwd"),
+            "attachment; filename=\"passwd\""
+        );
+        assert_eq!(
+            header("C:\\Users\\evil\".exe"),
+            "attachment; filename=\"evil_.exe\"; filename*=UTF-8''evil%22.exe"
+        );
+        assert_eq!(
+            header("résumé 2024.pdf"),
+            "attachment; filename=\"r_sum_ 2024.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9%202024.pdf"
+        );
+        assert_eq!(header("a\r\nb.txt"), "attachment; filename=\"ab.txt\"");
+        assert_eq!(header(".."), "attachment");
+        assert_eq!(content_disposition(Disposition::Inline, None), "inline");
+    }
+
+    #[tokio::test]
+    async fn negotiation() {
+        let app = Router::new()
+            .route(
+                "/image",
+                get(|requested: RequestedDisposition| async move {
+                    InlineOrAttachment::new(mime::IMAGE_PNG, "png")
+                        .filename("cat.png")
+                        .requested(requested)
+                }),
+            )
+            .route(
+                "/html",
+                get(|requested: RequestedDisposition| async move {
+                    InlineOrAttachment::new(mime::TEXT_HTML_UTF_8, "<script></script>")
+                        .requested(requested)
+                }),
+            );
+        let client = TestClient::new(app);
+
+        let res = client.get("/image").await;
+        assert_eq!(res.headers()["content-type"], "image/png");
+        assert_eq!(
+            res.headers()["content-disposition"],
+            "inline; filename=\"cat.png\""
+        );
+        assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+        assert_eq!(res.text().await, "png");
+
+        for query in ["download", "download=1", "size=2&download=true"] {
+            let res = client.get(&format!("/image?{query}")).await;
+            assert_eq!(
+                res.headers()["content-disposition"],
+                "attachment; filename=\"cat.png\""
+            );
+        }
+
+        let res = client.get("/html?download=0").await;
+        assert_eq!(res.headers()["content-disposition"], "attachment");
+    }
+}
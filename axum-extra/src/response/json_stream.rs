@@ -0,0 +1,182 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{ready, stream::TryStream, Stream};
+use pin_project_lite::pin_project;
+use serde::Serialize;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pin_project! {
+    /// A response that serializes a stream of items as a JSON array.
+    ///
+    /// Each item is serialized and sent as soon as the stream produces it, so large responses
+    /// don't have to be buffered in memory. Unlike `JsonLines`, which sends newline delimited JSON,
+    /// the response is a single, standard JSON document.
+    ///
+    /// If the stream produces an error the response is aborted, which clients see as a truncated
+    /// response. The status code has already been sent at that point so it can't be changed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{Router, routing::get, BoxError};
+    /// use axum_extra::response::JsonStream;
+    /// use futures_util::stream::{self, Stream};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     id: u64,
+    /// }
+    ///
+    /// fn all_users() -> impl Stream<Item = Result<User, BoxError>> {
+    ///     // for example rows streamed from a database
+    ///     stream::iter((0..1_000_000).map(|id| Ok(User { id })))
+    /// }
+    ///
+    /// // responds with `[{"id":0},{"id":1},...]`
+    /// async fn users() -> JsonStream<impl Stream<Item = Result<User, BoxError>>> {
+    ///     JsonStream::new(all_users())
+    /// }
+    ///
+    /// let app = Router::new().route("/users", get(users));
+    /// # let _: Router = app;
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-stream")))]
+    #[derive(Debug)]
+    #[must_use]
+    pub struct JsonStream<S> {
+        #[pin]
+        stream: S,
+        state: State,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Start,
+    Items,
+    Done,
+}
+
+impl<S> JsonStream<S> {
+    /// Create a new `JsonStream` from a stream of items.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            state: State::Start,
+        }
+    }
+}
+
+impl<S> Stream for JsonStream<S>
+where
+    S: TryStream,
+    S::Ok: Serialize,
+    S::Error: Into<BoxError>,
+{
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if *this.state == State::Done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(this.stream.try_poll_next(cx)) {
+            Some(Ok(value)) => {
+                let mut buf = BytesMut::new();
+                buf.put_u8(if *this.state == State::Start {
+                    b'['
+                } else {
+                    b','
+                });
+                *this.state = State::Items;
+
+                let mut writer = buf.writer();
+                if let Err(err) = serde_json::to_writer(&mut writer, &value) {
+                    *this.state = State::Done;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                Poll::Ready(Some(Ok(writer.into_inner().freeze())))
+            }
+            Some(Err(err)) => {
+                *this.state = State::Done;
+                Poll::Ready(Some(Err(err.into())))
+            }
+            None => {
+                let end: &'static [u8] = if *this.state == State::Start {
+                    b"[]"
+                } else {
+                    b"]"
+                };
+                *this.state = State::Done;
+                Poll::Ready(Some(Ok(Bytes::from_static(end))))
+            }
+        }
+    }
+}
+
+impl<S> IntoResponse for JsonStream<S>
+where
+    S: TryStream + Send + 'static,
+    S::Ok: Serialize,
+    S::Error: Into<BoxError>,
+{
+    fn into_response(self) -> Response {
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+            )],
+            Body::from_stream(self),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use futures_util::stream;
+    use std::convert::Infallible;
+
+    #[derive(Serialize)]
+    struct User {
+        id: i32,
+    }
+
+    #[tokio::test]
+    async fn response() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    let values = (1..=3).map(|id| Ok::<_, Infallible>(User { id }));
+                    JsonStream::new(stream::iter(values))
+                }),
+            )
+            .route(
+                "/empty",
+                get(|| async { JsonStream::new(stream::empty::<Result<User, Infallible>>()) }),
+            );
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert_eq!(res.text().await, r#"[{"id":1},{"id":2},{"id":3}]"#);
+
+        let res = client.get("/empty").await;
+        assert_eq!(res.text().await, "[]");
+    }
+}
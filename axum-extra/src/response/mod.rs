@@ -1,11 +1,77 @@
 //! Additional types for generating responses.
 
+mod after_send;
+mod disposition;
+mod pretty_json;
+
+#[cfg(feature = "anyhow")]
+mod app_error;
+
+#[cfg(feature = "auto-etag")]
+mod auto_etag;
+
+#[cfg(feature = "compression")]
+mod compressed;
+
+#[cfg(feature = "typed-routing")]
+mod created;
+
 #[cfg(feature = "erased-json")]
 mod erased_json;
 
+#[cfg(feature = "json-stream")]
+mod json_stream;
+
+#[cfg(feature = "multi-status")]
+mod multi_status;
+
+#[cfg(feature = "range")]
+mod ranged;
+
+#[cfg(feature = "template")]
+mod template;
+
+#[cfg(feature = "trailers")]
+mod trailers;
+
+pub use after_send::{AfterSend, AfterSendLayer, AfterSendService, SendOutcome, SendStatus};
+pub use disposition::{Disposition, InlineOrAttachment, RequestedDisposition};
+pub use pretty_json::{PrettyJsonLayer, PrettyJsonService};
+
+#[cfg(feature = "anyhow")]
+pub use app_error::AppError;
+
+#[cfg(feature = "auto-etag")]
+pub use auto_etag::{AutoEtag, AutoEtagLayer, AutoEtagService};
+
+#[cfg(feature = "compression")]
+pub use compressed::Compressed;
+
+#[cfg(feature = "typed-routing")]
+pub use created::Created;
+
 #[cfg(feature = "erased-json")]
 pub use erased_json::ErasedJson;
 
+#[cfg(feature = "json-stream")]
+pub use json_stream::JsonStream;
+
+#[cfg(feature = "multi-status")]
+pub use multi_status::{MultiStatus, MultiStatusEntry};
+
+#[cfg(feature = "range")]
+pub use ranged::Ranged;
+
+#[cfg(feature = "template")]
+pub use template::{
+    Template, TemplateEngine, TemplateError, TemplateFuture, TemplateLayer, TemplateService,
+};
+
+#[cfg(feature = "trailers")]
+pub use trailers::{
+    ResponseTrailers, ResponseTrailersLayer, ResponseTrailersService, TrailersSender, WithTrailers,
+};
+
 #[cfg(feature = "json-lines")]
 #[doc(no_inline)]
 pub use crate::json_lines::JsonLines;
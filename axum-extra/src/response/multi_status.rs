@@ -0,0 +1,280 @@
+use axum::response::{IntoResponse, Response};
+use http::{header, HeaderValue, StatusCode};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+/// A `207 Multi-Status` response, reporting the outcome of an operation on multiple resources.
+///
+/// This lets batch endpoints report partial successes, with a status for every resource. The
+/// response is serialized as the XML defined by WebDAV ([RFC 4918]), or as JSON with
+/// [`json`](Self::json).
+///
+/// The JSON profile looks like this, where `description` and `body` are only included if set:
+///
+/// ```json
+/// {
+///   "responses": [
+///     { "href": "/items/1", "status": 200 },
+///     { "href": "/items/2", "status": 404, "description": "No such item", "body": {} }
+///   ]
+/// }
+/// ```
+///
+/// [RFC 4918]: https://www.rfc-editor.org/rfc/rfc4918#section-13
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, Json, routing::post, http::StatusCode};
+/// use axum_extra::response::{MultiStatus, MultiStatusEntry};
+///
+/// async fn delete_items(Json(ids): Json<Vec<u64>>) -> MultiStatus {
+///     let mut multi_status = MultiStatus::new();
+///     for id in ids {
+///         let href = format!("/items/{id}");
+///         let entry = if id % 2 == 0 {
+///             MultiStatusEntry::new(href, StatusCode::NO_CONTENT)
+///         } else {
+///             MultiStatusEntry::new(href, StatusCode::NOT_FOUND).description("No such item")
+///         };
+///         multi_status.push(entry);
+///     }
+///     multi_status
+/// }
+///
+/// let app = Router::new().route("/items/delete", post(delete_items));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct MultiStatus {
+    entries: Vec<MultiStatusEntry>,
+    json: bool,
+}
+
+impl MultiStatus {
+    /// Create a new, empty `MultiStatus`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the status of a resource.
+    pub fn entry(mut self, entry: MultiStatusEntry) -> Self {
+        self.push(entry);
+        self
+    }
+
+    /// Add the status of a resource.
+    pub fn push(&mut self, entry: MultiStatusEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Serialize the response as JSON instead of XML.
+    pub fn json(mut self) -> Self {
+        self.json = true;
+        self
+    }
+
+    /// The statuses of the resources, in the order they were added.
+    pub fn entries(&self) -> &[MultiStatusEntry] {
+        &self.entries
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"DAV:\">\n",
+        );
+        for entry in &self.entries {
+            xml.push_str("  <D:response>\n");
+            writeln!(xml, "    <D:href>{}</D:href>", escape(&entry.href)).unwrap();
+            writeln!(xml, "    <D:status>HTTP/1.1 {}</D:status>", entry.status).unwrap();
+            if let Some(description) = &entry.description {
+                writeln!(
+                    xml,
+                    "    <D:responsedescription>{}</D:responsedescription>",
+                    escape(description)
+                )
+                .unwrap();
+            }
+            xml.push_str("  </D:response>\n");
+        }
+        xml.push_str("</D:multistatus>\n");
+        xml
+    }
+
+    fn to_json(&self) -> Value {
+        let responses = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut response = Map::new();
+                response.insert("href".to_owned(), entry.href.clone().into());
+                response.insert("status".to_owned(), entry.status.as_u16().into());
+                if let Some(description) = &entry.description {
+                    response.insert("description".to_owned(), description.clone().into());
+                }
+                if let Some(body) = &entry.body {
+                    response.insert("body".to_owned(), body.clone());
+                }
+                Value::Object(response)
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "responses": responses })
+    }
+}
+
+impl IntoResponse for MultiStatus {
+    fn into_response(self) -> Response {
+        if self.json {
+            (
+                StatusCode::MULTI_STATUS,
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+                )],
+                self.to_json().to_string(),
+            )
+                .into_response()
+        } else {
+            (
+                StatusCode::MULTI_STATUS,
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/xml; charset=utf-8"),
+                )],
+                self.to_xml(),
+            )
+                .into_response()
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The status of a single resource in a [`MultiStatus`] response.
+#[derive(Debug, Clone)]
+pub struct MultiStatusEntry {
+    href: String,
+    status: StatusCode,
+    description: Option<String>,
+    body: Option<Value>,
+}
+
+impl MultiStatusEntry {
+    /// Create a new `MultiStatusEntry` for the resource at `href`.
+    pub fn new(href: impl Into<String>, status: StatusCode) -> Self {
+        Self {
+            href: href.into(),
+            status,
+            description: None,
+            body: None,
+        }
+    }
+
+    /// Set a human readable description of the status.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set a body with details about the resource.
+    ///
+    /// Only included in JSON responses, since WebDAV doesn't define an element for it.
+    pub fn body(mut self, body: Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// The location of the resource.
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    /// The status of the resource.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use serde_json::json;
+
+    fn multi_status() -> MultiStatus {
+        MultiStatus::new()
+            .entry(MultiStatusEntry::new("/items/1", StatusCode::OK))
+            .entry(
+                MultiStatusEntry::new("/items/a&b", StatusCode::NOT_FOUND)
+                    .description("No <such> item")
+                    .body(json!({ "id": "a&b" })),
+            )
+    }
+
+    #[tokio::test]
+    async fn xml() {
+        let app = Router::new().route("/", get(|| async { multi_status() }));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::MULTI_STATUS);
+        assert_eq!(
+            res.headers()["content-type"],
+            "application/xml; charset=utf-8"
+        );
+        assert_eq!(
+            res.text().await,
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <D:multistatus xmlns:D=\"DAV:\">\n  \
+               <D:response>\n    \
+                 <D:href>/items/1</D:href>\n    \
+                 <D:status>HTTP/1.1 200 OK</D:status>\n  \
+               </D:response>\n  \
+               <D:response>\n    \
+                 <D:href>/items/a&amp;b</D:href>\n    \
+                 <D:status>HTTP/1.1 404 Not Found</D:status>\n    \
+                 <D:responsedescription>No &lt;such&gt; item</D:responsedescription>\n  \
+               </D:response>\n\
+             </D:multistatus>\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn json() {
+        let app = Router::new().route("/", get(|| async { multi_status().json() }));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::MULTI_STATUS);
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert_eq!(
+            res.json::<Value>().await,
+            json!({
+                "responses": [
+                    { "href": "/items/1", "status": 200 },
+                    {
+                        "href": "/items/a&b",
+                        "status": 404,
+                        "description": "No <such> item",
+                        "body": { "id": "a&b" },
+                    },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use http::{header, HeaderMap, StatusCode};
+use http_body_util::BodyExt;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that pretty-prints JSON responses if the client asks for it.
+///
+/// Clients ask for pretty-printed JSON with a `pretty` query parameter, such as `?pretty` or
+/// `?pretty=1`, or with a `pretty=true` parameter in the `Accept` header, such as
+/// `Accept: application/json; pretty=true`. Responses with a `Content-Type` of
+/// `application/json`, or a type ending in `+json`, are then reformatted with two space
+/// indentation, keeping the order of keys. Other responses, including compressed ones, are left
+/// alone.
+///
+/// This makes public APIs easier to debug without handlers needing to check whether to
+/// pretty-print, and works with [`Json`](axum::Json) and any other JSON response. Responses are
+/// buffered when pretty-printing, so only bodies whose exact size is known and fits in the
+/// [`max_body_size`](Self::max_body_size) are reformatted. Larger bodies and streams are passed
+/// through unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, Json, routing::get};
+/// use axum_extra::response::PrettyJsonLayer;
+/// use serde_json::{json, Value};
+///
+/// async fn user() -> Json<Value> {
+///     Json(json!({ "id": 1, "name": "Alice" }))
+/// }
+///
+/// // `GET /user?pretty` responds with indented JSON
+/// let app = Router::new()
+///     .route("/user", get(user))
+///     .layer(PrettyJsonLayer::new());
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PrettyJsonLayer {
+    max_body_size: usize,
+}
+
+impl PrettyJsonLayer {
+    /// Create a new `PrettyJsonLayer`.
+    pub fn new() -> Self {
+        Self {
+            max_body_size: 1024 * 1024,
+        }
+    }
+
+    /// Set the maximum size of the response bodies that are pretty-printed.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl Default for PrettyJsonLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for PrettyJsonLayer {
+    type Service = PrettyJsonService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PrettyJsonService {
+            inner,
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+/// Middleware that pretty-prints JSON responses if the client asks for it.
+///
+/// Created with [`PrettyJsonLayer`].
+#[derive(Debug, Clone)]
+pub struct PrettyJsonService<S> {
+    inner: S,
+    max_body_size: usize,
+}
+
+impl<S> Service<Request> for PrettyJsonService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let pretty = pretty_requested(&req);
+        let future = self.inner.call(req);
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            let res = future.await?;
+            if !pretty || !is_json(res.headers()) {
+                return Ok(res);
+            }
+            let fits = matches!(
+                res.body().size_hint().exact(),
+                Some(len) if len <= max_body_size as u64
+            );
+            if !fits {
+                return Ok(res);
+            }
+
+            let (mut parts, body) = res.into_parts();
+            let json = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %_err, "failed to buffer JSON response body");
+
+                    return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+                }
+            };
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Ok(Response::from_parts(parts, Body::from(prettify(&json))))
+        })
+    }
+}
+
+fn pretty_requested(req: &Request) -> bool {
+    let query = req
+        .uri()
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .any(|pair| matches!(pair, "pretty" | "pretty=1" | "pretty=true"));
+
+    let accept = req
+        .headers()
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .flat_map(|media_range| media_range.split(';').skip(1))
+        .any(|param| {
+            let param = param.trim();
+            param.eq_ignore_ascii_case("pretty=true")
+                || param.eq_ignore_ascii_case("pretty=\"true\"")
+        });
+
+    query || accept
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::CONTENT_ENCODING) {
+        return false;
+    }
+
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+    else {
+        return false;
+    };
+
+    content_type.type_() == mime::APPLICATION
+        && (content_type.subtype() == mime::JSON || content_type.suffix() == Some(mime::JSON))
+}
+
+/// Reformat compact JSON with two space indentation, like `serde_json::to_string_pretty`.
+fn prettify(json: &[u8]) -> Vec<u8> {
+    fn newline(out: &mut Vec<u8>, indent: usize) {
+        out.push(b'\n');
+        out.resize(out.len() + indent * 2, b' ');
+    }
+
+    let mut out = Vec::with_capacity(json.len() * 2);
+    let mut indent = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    let mut i = 0;
+    while i < json.len() {
+        let byte = json[i];
+        i += 1;
+
+        if in_string {
+            out.push(byte);
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => {
+                in_string = true;
+                out.push(byte);
+            }
+            b'{' | b'[' => {
+                let close = if byte == b'{' { b'}' } else { b']' };
+                let next = json[i..]
+                    .iter()
+                    .position(|byte| !byte.is_ascii_whitespace())
+                    .map(|offset| i + offset);
+                out.push(byte);
+                match next {
+                    // keep empty objects and arrays on one line
+                    Some(next) if json[next] == close => {
+                        out.push(close);
+                        i = next + 1;
+                    }
+                    _ => {
+                        indent += 1;
+                        newline(&mut out, indent);
+                    }
+                }
+            }
+            b'}' | b']' => {
+                indent = indent.saturating_sub(1);
+                newline(&mut out, indent);
+                out.push(byte);
+            }
+            b',' => {
+                out.push(byte);
+                newline(&mut out, indent);
+            }
+            b':' => out.extend_from_slice(b": "),
+            byte if byte.is_ascii_whitespace() => {}
+            _ => out.push(byte),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Json, Router};
+    use serde_json::{json, Value};
+
+    fn value() -> Value {
+        json!({
+            "name": "a \"quoted\" {string}, with: punctuation \\",
+            "tags": ["x", "y"],
+            "empty": {},
+            "none": [],
+            "nested": { "n": 1.5, "ok": true },
+        })
+    }
+
+    #[test]
+    fn matches_serde_json() {
+        let compact = serde_json::to_vec(&value()).unwrap();
+        let pretty = serde_json::to_vec_pretty(&value()).unwrap();
+        assert_eq!(
+            String::from_utf8(prettify(&compact)).unwrap(),
+            String::from_utf8(pretty).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn layer() {
+        let app = Router::new()
+            .route("/", get(|| async { Json(value()) }))
+            .route("/text", get(|| async { "{\"a\":1}" }))
+            .layer(PrettyJsonLayer::new());
+        let client = TestClient::new(app);
+        let pretty = serde_json::to_string_pretty(&value()).unwrap();
+        let compact = serde_json::to_string(&value()).unwrap();
+
+        let res = client.get("/").await;
+        assert_eq!(res.text().await, compact);
+
+        for uri in ["/?pretty", "/?a=b&pretty=1", "/?pretty=true"] {
+            let res = client.get(uri).await;
+            assert_eq!(res.text().await, pretty);
+        }
+
+        let res = client
+            .get("/")
+            .header("accept", "text/html, application/json; pretty=true")
+            .await;
+        assert_eq!(res.text().await, pretty);
+
+        let res = client.get("/text?pretty").await;
+        assert_eq!(res.text().await, "{\"a\":1}");
+    }
+
+    #[tokio::test]
+    async fn passes_through_large_bodies() {
+        let app = Router::new()
+            .route("/", get(|| async { Json(value()) }))
+            .route(
+                "/stream",
+                get(|| async {
+                    let chunks = [Ok::<_, std::io::Error>("{\"a\":1}")];
+                    (
+                        [(header::CONTENT_TYPE, "application/json")],
+                        Body::from_stream(futures_util::stream::iter(chunks)),
+                    )
+                }),
+            )
+            .layer(PrettyJsonLayer::new().max_body_size(16));
+        let client = TestClient::new(app);
+
+        let res = client.get("/?pretty").await;
+        assert_eq!(res.text().await, serde_json::to_string(&value()).unwrap());
+
+        // the size of streams isn't known
+        let res = client.get("/stream?pretty").await;
+        assert_eq!(res.text().await, "{\"a\":1}");
+    }
+}
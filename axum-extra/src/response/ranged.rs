@@ -0,0 +1,335 @@
+use crate::extract::RangeHeader;
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    response::{IntoResponse, Response},
+    Error,
+};
+use http::{
+    header::{ACCEPT_RANGES, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, ETAG, LAST_MODIFIED},
+    HeaderMap, HeaderValue, StatusCode,
+};
+use http_body::{Frame, SizeHint};
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// A response that serves the byte range requested with the `Range` header from a complete body.
+///
+/// If the request has a `Range` header with a single satisfiable range, the response is changed to
+/// `206 Partial Content` with a `Content-Range` header, and the body is cut down to the range as
+/// it's streamed, so it doesn't have to be buffered. If none of the ranges are satisfiable the
+/// response is `416 Range Not Satisfiable`. `Accept-Ranges: bytes` is always set.
+///
+/// The length of the body must be known, either from the body itself or a `Content-Length`
+/// header, like with proxied responses. Otherwise, and if multiple ranges are requested, the
+/// response isn't `200 OK`, or it has a `Content-Encoding`, the whole response is sent.
+///
+/// If the request has an `If-Range` header, the range is only served if it matches the `ETag`
+/// of the response, which must be strong, or its `Last-Modified` date exactly. Otherwise the
+/// resource changed since the client got the rest of it, so the whole response is sent.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::{extract::RangeHeader, response::Ranged};
+///
+/// async fn video(range: Option<RangeHeader>) -> Ranged<Vec<u8>> {
+///     let video = Vec::new();
+///     Ranged::new(video, range)
+/// }
+///
+/// let app = Router::new().route("/video", get(video));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Ranged<T> {
+    body: T,
+    range: Option<RangeHeader>,
+}
+
+impl<T> Ranged<T> {
+    /// Create a new `Ranged` from a complete body and the requested range, if any.
+    pub fn new(body: T, range: Option<RangeHeader>) -> Self {
+        Self { body, range }
+    }
+}
+
+impl<T> IntoResponse for Ranged<T>
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let mut res = self.body.into_response();
+        res.headers_mut()
+            .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+        let Some(range) = self.range else {
+            return res;
+        };
+        if res.status() != StatusCode::OK || res.headers().contains_key(CONTENT_ENCODING) {
+            return res;
+        }
+        if let Some(if_range) = range.if_range() {
+            if !if_range_matches(if_range, res.headers()) {
+                return res;
+            }
+        }
+        let Some(len) = body_len(&res) else {
+            return res;
+        };
+
+        let range = match range.satisfiable_ranges(len) {
+            Ok(ranges) if ranges.len() == 1 => ranges[0].clone(),
+            // multipart responses aren't supported, respond with the whole body instead
+            Ok(_) => return res,
+            Err(not_satisfiable) => {
+                let mut not_satisfiable = not_satisfiable.into_response();
+                not_satisfiable
+                    .headers_mut()
+                    .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+                return not_satisfiable;
+            }
+        };
+
+        let (mut parts, body) = res.into_parts();
+        parts.status = StatusCode::PARTIAL_CONTENT;
+        parts
+            .headers
+            .insert(CONTENT_RANGE, RangeHeader::content_range(&range, len));
+        parts
+            .headers
+            .insert(CONTENT_LENGTH, HeaderValue::from(range.end - range.start));
+
+        let body = RangeBody {
+            inner: body,
+            skip: range.start,
+            remaining: range.end - range.start,
+        };
+        Response::from_parts(parts, Body::new(body))
+    }
+}
+
+/// Whether the `If-Range` validator matches the response, using the strong comparison.
+fn if_range_matches(if_range: &HeaderValue, headers: &HeaderMap) -> bool {
+    let if_range = if_range.as_bytes();
+    if if_range.starts_with(b"\"") {
+        headers.get(ETAG).map(HeaderValue::as_bytes) == Some(if_range)
+    } else if if_range.starts_with(b"W/") {
+        // weak entity tags never match
+        false
+    } else {
+        headers.get(LAST_MODIFIED).map(HeaderValue::as_bytes) == Some(if_range)
+    }
+}
+
+fn body_len(res: &Response) -> Option<u64> {
+    res.body().size_hint().exact().or_else(|| {
+        res.headers()
+            .get(CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    })
+}
+
+/// Body that skips `skip` bytes of the inner body and then ends after `remaining` bytes.
+struct RangeBody {
+    inner: Body,
+    skip: u64,
+    remaining: u64,
+}
+
+impl HttpBody for RangeBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        loop {
+            if self.remaining == 0 {
+                return Poll::Ready(None);
+            }
+
+            let frame = match ready!(Pin::new(&mut self.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            };
+            // trailers describe the whole body so they're dropped
+            let Ok(mut data) = frame.into_data() else {
+                continue;
+            };
+
+            let len = data.len() as u64;
+            if self.skip >= len {
+                self.skip -= len;
+                continue;
+            }
+            let start = self.skip as usize;
+            let end = (self.skip + self.remaining).min(len) as usize;
+            self.skip = 0;
+            self.remaining -= (end - start) as u64;
+            data = data.slice(start..end);
+
+            if !data.is_empty() {
+                return Poll::Ready(Some(Ok(Frame::data(data))));
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+    use futures_util::stream;
+    use std::convert::Infallible;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|range: Option<RangeHeader>| async move { Ranged::new("hello world", range) }),
+            )
+            .route(
+                "/stream",
+                get(|range: Option<RangeHeader>| async move {
+                    let chunks = ["hel", "lo w", "orld"].map(Ok::<_, Infallible>);
+                    let body = Body::from_stream(stream::iter(chunks));
+                    Ranged::new(([(CONTENT_LENGTH, "11")], body), range)
+                }),
+            )
+            .route(
+                "/validators",
+                get(|range: Option<RangeHeader>| async move {
+                    let headers = [
+                        (ETAG, "\"v1\""),
+                        (LAST_MODIFIED, "Wed, 21 Oct 2015 07:28:00 GMT"),
+                    ];
+                    Ranged::new((headers, "hello world"), range)
+                }),
+            )
+            .route(
+                "/compressed",
+                get(|range: Option<RangeHeader>| async move {
+                    Ranged::new(([(CONTENT_ENCODING, "gzip")], "hello world"), range)
+                }),
+            )
+            .route(
+                "/unknown-length",
+                get(|range: Option<RangeHeader>| async move {
+                    let chunks = ["hello", " world"].map(Ok::<_, Infallible>);
+                    Ranged::new(Body::from_stream(stream::iter(chunks)), range)
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn single_range() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/").header("range", "bytes=0-4").await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(res.headers()["content-range"], "bytes 0-4/11");
+        assert_eq!(res.headers()["content-length"], "5");
+        assert_eq!(res.headers()["accept-ranges"], "bytes");
+        assert_eq!(res.text().await, "hello");
+
+        let res = client.get("/").header("range", "bytes=-5").await;
+        assert_eq!(res.headers()["content-range"], "bytes 6-10/11");
+        assert_eq!(res.text().await, "world");
+
+        let res = client.get("/stream").header("range", "bytes=2-7").await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(res.headers()["content-range"], "bytes 2-7/11");
+        assert_eq!(res.text().await, "llo wo");
+    }
+
+    #[tokio::test]
+    async fn whole_body() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["accept-ranges"], "bytes");
+        assert_eq!(res.text().await, "hello world");
+
+        let res = client.get("/").header("range", "bytes=0-1, 4-5").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "hello world");
+
+        let res = client
+            .get("/unknown-length")
+            .header("range", "bytes=0-4")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "hello world");
+    }
+
+    #[tokio::test]
+    async fn if_range() {
+        let client = TestClient::new(app());
+
+        for if_range in ["\"v1\"", "Wed, 21 Oct 2015 07:28:00 GMT"] {
+            let res = client
+                .get("/validators")
+                .header("range", "bytes=0-4")
+                .header("if-range", if_range)
+                .await;
+            assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+            assert_eq!(res.text().await, "hello");
+        }
+
+        for if_range in ["\"v2\"", "W/\"v1\"", "Thu, 22 Oct 2015 07:28:00 GMT"] {
+            let res = client
+                .get("/validators")
+                .header("range", "bytes=0-4")
+                .header("if-range", if_range)
+                .await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.text().await, "hello world");
+        }
+
+        // without validators `If-Range` can't match
+        let res = client
+            .get("/")
+            .header("range", "bytes=0-4")
+            .header("if-range", "\"v1\"")
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn content_encoding() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/compressed").header("range", "bytes=0-4").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "hello world");
+    }
+
+    #[tokio::test]
+    async fn not_satisfiable() {
+        let client = TestClient::new(app());
+
+        let res = client.get("/").header("range", "bytes=20-").await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(res.headers()["content-range"], "bytes */11");
+        assert_eq!(res.headers()["accept-ranges"], "bytes");
+    }
+}
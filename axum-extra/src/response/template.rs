@@ -0,0 +1,515 @@
+use axum::{
+    extract::Request,
+    response::{Html, IntoResponse, Response},
+};
+use http::{header, HeaderValue, StatusCode};
+use pin_project_lite::pin_project;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    convert::Infallible,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A template engine that renders templates by name, used by [`Template`].
+///
+/// This is implemented for [`minijinja::Environment`] and [`tera::Tera`] with the `minijinja`
+/// and `tera` features, and for `Arc`s of engines.
+///
+/// There is no implementation for `askama`, and none is planned: its templates are compiled into
+/// the binary and don't need an engine. Render them directly and return them with [`Html`].
+pub trait TemplateEngine: Send + Sync + 'static {
+    /// The error returned if rendering fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Render the template called `name` with `context`.
+    fn render(&self, name: &str, context: &Value) -> Result<String, Self::Error>;
+}
+
+impl<E> TemplateEngine for Arc<E>
+where
+    E: TemplateEngine,
+{
+    type Error = E::Error;
+
+    fn render(&self, name: &str, context: &Value) -> Result<String, Self::Error> {
+        E::render(self, name, context)
+    }
+}
+
+#[cfg(feature = "minijinja")]
+impl TemplateEngine for minijinja::Environment<'static> {
+    type Error = minijinja::Error;
+
+    fn render(&self, name: &str, context: &Value) -> Result<String, Self::Error> {
+        self.get_template(name)?.render(context)
+    }
+}
+
+#[cfg(feature = "tera")]
+impl TemplateEngine for tera::Tera {
+    type Error = tera::Error;
+
+    fn render(&self, name: &str, context: &Value) -> Result<String, Self::Error> {
+        let context = tera::Context::from_value(context.clone())?;
+        tera::Tera::render(self, name, &context)
+    }
+}
+
+/// An HTML response rendered from a template.
+///
+/// The template is rendered by [`TemplateLayer`], which holds the [`TemplateEngine`], so
+/// handlers don't need access to it. **Without the layer the response is a
+/// `500 Internal Server Error`** and an error is logged. Use [`Template::render`] to render a
+/// template without the layer.
+///
+/// The layer has to see the `Template` being returned, so it must be returned by a handler or
+/// service wrapped by the layer, and not from another task.
+///
+/// The response has `Content-Type: text/html; charset=utf-8`. If rendering fails the response is
+/// replaced with a [`TemplateError`].
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{Router, routing::get};
+/// use axum_extra::response::{Template, TemplateLayer};
+/// use serde::Serialize;
+/// # use axum_extra::response::TemplateEngine;
+/// # #[derive(Clone)]
+/// # struct MyEngine;
+/// # impl TemplateEngine for MyEngine {
+/// #     type Error = std::fmt::Error;
+/// #     fn render(&self, _: &str, _: &serde_json::Value) -> Result<String, Self::Error> {
+/// #         Ok(String::new())
+/// #     }
+/// # }
+///
+/// #[derive(Serialize)]
+/// struct Profile {
+///     name: String,
+/// }
+///
+/// async fn profile() -> Template<Profile> {
+///     let profile = Profile { name: "Alice".to_owned() };
+///
+///     // `base.html` is rendered with the rendered page as `content`
+///     Template::new("profile.html", profile).layout("base.html")
+/// }
+///
+/// // for example a `minijinja::Environment` or `tera::Tera`
+/// let engine = MyEngine;
+///
+/// let app = Router::new()
+///     .route("/profile", get(profile))
+///     .layer(TemplateLayer::new(engine));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Template<T> {
+    name: Cow<'static, str>,
+    layout: Option<Cow<'static, str>>,
+    context: T,
+}
+
+impl<T> Template<T> {
+    /// Create a new `Template` that renders the template called `name` with `context`.
+    pub fn new(name: impl Into<Cow<'static, str>>, context: T) -> Self {
+        Self {
+            name: name.into(),
+            layout: None,
+            context,
+        }
+    }
+
+    /// Render the page inside a layout template.
+    ///
+    /// The layout is rendered with the same context, and the rendered page as `content`. If the
+    /// context isn't a map it's replaced with one that only contains `content`. Engines that
+    /// escape HTML need to be told that `content` is safe, for example with `{{ content|safe }}`.
+    pub fn layout(mut self, layout: impl Into<Cow<'static, str>>) -> Self {
+        self.layout = Some(layout.into());
+        self
+    }
+}
+
+impl<T> Template<T>
+where
+    T: Serialize,
+{
+    /// Render the template with `engine`.
+    pub fn render<E>(&self, engine: &E) -> Result<Html<String>, TemplateError<E::Error>>
+    where
+        E: TemplateEngine,
+    {
+        let context = serde_json::to_value(&self.context).map_err(TemplateError::Serialize)?;
+        render(engine, &self.name, self.layout.as_deref(), context).map(Html)
+    }
+}
+
+fn render<E>(
+    engine: &E,
+    name: &str,
+    layout: Option<&str>,
+    context: Value,
+) -> Result<String, TemplateError<E::Error>>
+where
+    E: TemplateEngine,
+{
+    let content = engine
+        .render(name, &context)
+        .map_err(TemplateError::Render)?;
+    let Some(layout) = layout else {
+        return Ok(content);
+    };
+
+    let context = match context {
+        Value::Object(mut map) => {
+            map.insert("content".to_owned(), Value::String(content));
+            Value::Object(map)
+        }
+        _ => serde_json::json!({ "content": content }),
+    };
+    engine
+        .render(layout, &context)
+        .map_err(TemplateError::Render)
+}
+
+impl<T> IntoResponse for Template<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        if LAYERS.with(Cell::get) == 0 {
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                template = %self.name,
+                "`Template` returned without a `TemplateLayer`, the template isn't rendered",
+            );
+
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to render template",
+            )
+                .into_response();
+        }
+
+        let context = match serde_json::to_value(&self.context) {
+            Ok(context) => context,
+            Err(err) => return TemplateError::<Infallible>::Serialize(err).into_response(),
+        };
+        let pending = PendingTemplate {
+            name: self.name,
+            layout: self.layout,
+            context,
+        };
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(mime::TEXT_HTML_UTF_8.as_ref()),
+            )],
+            axum::Extension(pending),
+        )
+            .into_response()
+    }
+}
+
+thread_local! {
+    // How many `TemplateFuture`s are polling their inner future on this thread, so `Template` can
+    // tell whether it's returned to a `TemplateLayer`
+    static LAYERS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Marks the thread as polling a [`TemplateFuture`] until dropped.
+struct LayerGuard;
+
+impl LayerGuard {
+    fn enter() -> Self {
+        LAYERS.with(|layers| layers.set(layers.get() + 1));
+        Self
+    }
+}
+
+impl Drop for LayerGuard {
+    fn drop(&mut self) {
+        LAYERS.with(|layers| layers.set(layers.get() - 1));
+    }
+}
+
+/// A template that hasn't been rendered yet, inserted into the response extensions by
+/// [`Template`].
+#[derive(Clone)]
+struct PendingTemplate {
+    name: Cow<'static, str>,
+    layout: Option<Cow<'static, str>>,
+    context: Value,
+}
+
+/// Error returned if rendering a [`Template`] fails.
+///
+/// Responds with `500 Internal Server Error` without details about the error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TemplateError<E> {
+    /// The context couldn't be serialized.
+    Serialize(serde_json::Error),
+    /// The template engine failed to render the template.
+    Render(E),
+}
+
+impl<E> IntoResponse for TemplateError<E>
+where
+    E: std::error::Error,
+{
+    fn into_response(self) -> Response {
+        #[cfg(feature = "tracing")]
+        tracing::error!(error = %self, "failed to render template");
+
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to render template",
+        )
+            .into_response()
+    }
+}
+
+impl<E> fmt::Display for TemplateError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(err) => write!(f, "Failed to serialize template context: {err}"),
+            Self::Render(err) => write!(f, "Failed to render template: {err}"),
+        }
+    }
+}
+
+impl<E> std::error::Error for TemplateError<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialize(err) => Some(err),
+            Self::Render(err) => Some(err),
+        }
+    }
+}
+
+/// Layer that renders the [`Template`]s returned by handlers.
+#[derive(Debug, Clone)]
+pub struct TemplateLayer<E> {
+    engine: E,
+}
+
+impl<E> TemplateLayer<E> {
+    /// Create a new `TemplateLayer` that renders templates with `engine`.
+    pub fn new(engine: E) -> Self {
+        Self { engine }
+    }
+}
+
+impl<S, E> Layer<S> for TemplateLayer<E>
+where
+    E: Clone,
+{
+    type Service = TemplateService<S, E>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TemplateService {
+            inner,
+            engine: self.engine.clone(),
+        }
+    }
+}
+
+/// Middleware that renders the [`Template`]s returned by handlers.
+///
+/// Created with [`TemplateLayer`].
+#[derive(Debug, Clone)]
+pub struct TemplateService<S, E> {
+    inner: S,
+    engine: E,
+}
+
+impl<S, E> Service<Request> for TemplateService<S, E>
+where
+    S: Service<Request, Response = Response>,
+    E: TemplateEngine + Clone,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = TemplateFuture<S::Future, E>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        TemplateFuture {
+            inner: self.inner.call(req),
+            engine: self.engine.clone(),
+        }
+    }
+}
+
+pin_project! {
+    /// Response future for [`TemplateService`].
+    pub struct TemplateFuture<F, E> {
+        #[pin]
+        inner: F,
+        engine: E,
+    }
+}
+
+impl<F, E, Error> Future for TemplateFuture<F, E>
+where
+    F: Future<Output = Result<Response, Error>>,
+    E: TemplateEngine,
+{
+    type Output = Result<Response, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = {
+            let _guard = LayerGuard::enter();
+            this.inner.poll(cx)
+        };
+        let mut res = ready!(res)?;
+
+        let Some(pending) = res.extensions_mut().remove::<PendingTemplate>() else {
+            return Poll::Ready(Ok(res));
+        };
+        let rendered = render(
+            &*this.engine,
+            &pending.name,
+            pending.layout.as_deref(),
+            pending.context,
+        );
+        match rendered {
+            Ok(html) => {
+                // the length of the placeholder body doesn't apply to the rendered one
+                res.headers_mut().remove(header::CONTENT_LENGTH);
+                *res.body_mut() = html.into();
+                Poll::Ready(Ok(res))
+            }
+            Err(err) => Poll::Ready(Ok(err.into_response())),
+        }
+    }
+}
+
+impl<F, E> fmt::Debug for TemplateFuture<F, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemplateFuture").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Router};
+
+    /// Replaces `{{ key }}` with the value of `key` in the context.
+    #[derive(Clone)]
+    struct ReplaceEngine;
+
+    #[derive(Debug)]
+    struct NotFound;
+
+    impl fmt::Display for NotFound {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("template not found")
+        }
+    }
+
+    impl std::error::Error for NotFound {}
+
+    impl TemplateEngine for ReplaceEngine {
+        type Error = NotFound;
+
+        fn render(&self, name: &str, context: &Value) -> Result<String, Self::Error> {
+            let mut template = match name {
+                "page.html" => "<p>{{ name }}</p>".to_owned(),
+                "base.html" => "<main>{{ content }}</main>".to_owned(),
+                _ => return Err(NotFound),
+            };
+            if let Value::Object(map) = context {
+                for (key, value) in map {
+                    let value = value.as_str().unwrap_or_default();
+                    template = template.replace(&format!("{{{{ {key} }}}}"), value);
+                }
+            }
+            Ok(template)
+        }
+    }
+
+    fn context() -> Value {
+        serde_json::json!({ "name": "Alice" })
+    }
+
+    #[tokio::test]
+    async fn layer() {
+        let app = Router::new()
+            .route("/", get(|| async { Template::new("page.html", context()) }))
+            .route(
+                "/layout",
+                get(|| async { Template::new("page.html", context()).layout("base.html") }),
+            )
+            .route(
+                "/status",
+                get(|| async { (StatusCode::NOT_FOUND, Template::new("page.html", context())) }),
+            )
+            .route(
+                "/missing",
+                get(|| async { Template::new("missing.html", context()) }),
+            )
+            .layer(TemplateLayer::new(ReplaceEngine));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+        assert_eq!(res.text().await, "<p>Alice</p>");
+
+        let res = client.get("/layout").await;
+        assert_eq!(res.text().await, "<main><p>Alice</p></main>");
+
+        let res = client.get("/status").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.text().await, "<p>Alice</p>");
+
+        let res = client.get("/missing").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn missing_layer() {
+        let app = Router::new().route("/", get(|| async { Template::new("page.html", context()) }));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.text().await, "Failed to render template");
+    }
+
+    #[test]
+    fn render_without_layer() {
+        let Html(html) = Template::new("page.html", context())
+            .layout("base.html")
+            .render(&ReplaceEngine)
+            .unwrap();
+        assert_eq!(html, "<main><p>Alice</p></main>");
+    }
+}
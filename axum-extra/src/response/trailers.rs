@@ -0,0 +1,389 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::Request,
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+    BoxError, Error,
+};
+use futures_util::future::{MapOk, TryFutureExt};
+use http::HeaderMap;
+use http_body::Frame;
+use pin_project_lite::pin_project;
+use std::{
+    convert::Infallible,
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+use tokio::sync::oneshot;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Trailers to send after the response body.
+///
+/// Trailers are headers sent after the body, using chunked transfer encoding in HTTP/1.1 or a
+/// final `HEADERS` frame in HTTP/2. They're used by gRPC for `grpc-status`, and for checksums
+/// that are computed while the body is streamed.
+///
+/// The trailers are either known up front, with [`ResponseTrailers::new`], or sent once the body
+/// has been produced, with [`ResponseTrailers::deferred`]. They can be added to a response in
+/// two ways:
+///
+/// - Wrapping the body in [`WithTrailers`].
+/// - Returning `ResponseTrailers` as part of the response, like headers. This requires
+///   [`ResponseTrailersLayer`], which wraps the body of the response.
+///
+/// Note that trailers are only sent if the client and any proxies in between support them.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     Router,
+///     http::{HeaderMap, HeaderValue},
+///     routing::get,
+/// };
+/// use axum_extra::response::{ResponseTrailers, ResponseTrailersLayer};
+///
+/// async fn handler() -> (ResponseTrailers, &'static str) {
+///     let (trailers, sender) = ResponseTrailers::deferred();
+///
+///     tokio::spawn(async move {
+///         // compute the checksum while the body is being sent...
+///         let mut trailers = HeaderMap::new();
+///         trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+///         sender.send(trailers);
+///     });
+///
+///     (trailers, "Hello, World!")
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(ResponseTrailersLayer::new());
+/// # let _: Router = app;
+/// ```
+pub struct ResponseTrailers {
+    source: Source,
+}
+
+enum Source {
+    Ready(HeaderMap),
+    Deferred(oneshot::Receiver<HeaderMap>),
+}
+
+impl ResponseTrailers {
+    /// Create `ResponseTrailers` that are known up front.
+    pub fn new(trailers: HeaderMap) -> Self {
+        Self {
+            source: Source::Ready(trailers),
+        }
+    }
+
+    /// Create `ResponseTrailers` that are sent later with the returned [`TrailersSender`].
+    ///
+    /// After the body has been sent the response waits for the trailers. No trailers are sent
+    /// if the `TrailersSender` is dropped without sending any.
+    pub fn deferred() -> (Self, TrailersSender) {
+        let (tx, rx) = oneshot::channel();
+        let trailers = Self {
+            source: Source::Deferred(rx),
+        };
+        (trailers, TrailersSender { tx })
+    }
+}
+
+impl From<HeaderMap> for ResponseTrailers {
+    fn from(trailers: HeaderMap) -> Self {
+        Self::new(trailers)
+    }
+}
+
+impl fmt::Debug for ResponseTrailers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Source::Ready(trailers) => f.debug_tuple("ResponseTrailers").field(trailers).finish(),
+            Source::Deferred(_) => f.debug_struct("ResponseTrailers").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl IntoResponseParts for ResponseTrailers {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.extensions_mut()
+            .insert(TrailersSlot(Arc::new(Mutex::new(Some(self.source)))));
+        Ok(res)
+    }
+}
+
+/// The trailers of a response, inserted into the response extensions by [`ResponseTrailers`].
+#[derive(Clone)]
+struct TrailersSlot(Arc<Mutex<Option<Source>>>);
+
+/// Sends the trailers created with [`ResponseTrailers::deferred`].
+pub struct TrailersSender {
+    tx: oneshot::Sender<HeaderMap>,
+}
+
+impl TrailersSender {
+    /// Send the trailers.
+    ///
+    /// Does nothing if the response has been dropped.
+    pub fn send(self, trailers: HeaderMap) {
+        let _ = self.tx.send(trailers);
+    }
+}
+
+impl fmt::Debug for TrailersSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrailersSender").finish_non_exhaustive()
+    }
+}
+
+pin_project! {
+    /// A body that sends trailers after another body.
+    ///
+    /// This can be used as a response. See [`ResponseTrailers`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::{HeaderMap, HeaderValue};
+    /// use axum_extra::response::{ResponseTrailers, WithTrailers};
+    ///
+    /// async fn handler() -> WithTrailers<&'static str> {
+    ///     let mut trailers = HeaderMap::new();
+    ///     trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    ///
+    ///     WithTrailers::new("Hello, World!", ResponseTrailers::new(trailers))
+    /// }
+    /// ```
+    pub struct WithTrailers<B = Body> {
+        #[pin]
+        body: B,
+        body_done: bool,
+        trailers: Option<Source>,
+    }
+}
+
+impl<B> WithTrailers<B> {
+    /// Create a new `WithTrailers` that sends `trailers` after `body`.
+    pub fn new(body: B, trailers: ResponseTrailers) -> Self {
+        Self {
+            body,
+            body_done: false,
+            trailers: Some(trailers.source),
+        }
+    }
+}
+
+impl<B> HttpBody for WithTrailers<B>
+where
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+
+        if !*this.body_done {
+            match ready!(this.body.poll_frame(cx)) {
+                Some(frame) => return Poll::Ready(Some(frame.map_err(Error::new))),
+                None => *this.body_done = true,
+            }
+        }
+
+        let trailers = match this.trailers {
+            None => return Poll::Ready(None),
+            Some(Source::Ready(trailers)) => mem::take(trailers),
+            // the sender was dropped without sending trailers
+            Some(Source::Deferred(rx)) => ready!(Pin::new(rx).poll(cx)).unwrap_or_default(),
+        };
+        *this.trailers = None;
+
+        if trailers.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.trailers.is_none() && (self.body_done || self.body.is_end_stream())
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        // an unknown size makes HTTP/1.1 use chunked transfer encoding, which trailers require
+        if self.trailers.is_some() {
+            http_body::SizeHint::default()
+        } else {
+            self.body.size_hint()
+        }
+    }
+}
+
+impl<B> IntoResponse for WithTrailers<B>
+where
+    B: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let Self { body, trailers, .. } = self;
+        let (parts, body) = body.into_response().into_parts();
+        let body = WithTrailers {
+            body,
+            body_done: false,
+            trailers,
+        };
+        Response::from_parts(parts, Body::new(body))
+    }
+}
+
+impl<B> fmt::Debug for WithTrailers<B>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithTrailers")
+            .field("body", &self.body)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Layer that sends the [`ResponseTrailers`] returned by handlers after the response body.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResponseTrailersLayer {
+    _priv: (),
+}
+
+impl ResponseTrailersLayer {
+    /// Create a new `ResponseTrailersLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for ResponseTrailersLayer {
+    type Service = ResponseTrailersService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseTrailersService { inner }
+    }
+}
+
+/// Middleware that sends the [`ResponseTrailers`] returned by handlers after the response body.
+///
+/// Created with [`ResponseTrailersLayer`].
+#[derive(Debug, Clone)]
+pub struct ResponseTrailersService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for ResponseTrailersService<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = MapOk<S::Future, fn(Response) -> Response>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner
+            .call(req)
+            .map_ok(add_trailers as fn(Response) -> Response)
+    }
+}
+
+fn add_trailers(res: Response) -> Response {
+    let (mut parts, body) = res.into_parts();
+    let slot = parts.extensions.remove::<TrailersSlot>();
+    let source = slot
+        .as_ref()
+        .and_then(|TrailersSlot(slot)| slot.lock().unwrap().take());
+    match source {
+        Some(source) => {
+            let body = WithTrailers {
+                body,
+                body_done: false,
+                trailers: Some(source),
+            };
+            Response::from_parts(parts, Body::new(body))
+        }
+        None => Response::from_parts(parts, body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use http::HeaderValue;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn trailers() -> HeaderMap {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+        trailers
+    }
+
+    async fn collect(res: Response) -> (Bytes, Option<HeaderMap>) {
+        let collected = res.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().cloned();
+        (collected.to_bytes(), trailers)
+    }
+
+    #[tokio::test]
+    async fn with_trailers() {
+        let res = WithTrailers::new("body", ResponseTrailers::new(trailers())).into_response();
+        assert_eq!(collect(res).await, (Bytes::from("body"), Some(trailers())));
+
+        let (response_trailers, sender) = ResponseTrailers::deferred();
+        let res = WithTrailers::new("body", response_trailers).into_response();
+        sender.send(trailers());
+        assert_eq!(collect(res).await, (Bytes::from("body"), Some(trailers())));
+
+        let (response_trailers, sender) = ResponseTrailers::deferred();
+        let res = WithTrailers::new("body", response_trailers).into_response();
+        drop(sender);
+        assert_eq!(collect(res).await, (Bytes::from("body"), None));
+    }
+
+    #[tokio::test]
+    async fn response_parts() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { (ResponseTrailers::new(trailers()), "body") }),
+            )
+            .route("/without", get(|| async { "body" }))
+            .layer(ResponseTrailersLayer::new());
+
+        let req = http::Request::builder()
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(collect(res).await, (Bytes::from("body"), Some(trailers())));
+
+        let req = http::Request::builder()
+            .uri("/without")
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(collect(res).await, (Bytes::from("body"), None));
+    }
+}
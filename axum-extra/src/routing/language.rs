@@ -0,0 +1,234 @@
+use axum::{
+    extract::Request,
+    http::{header::ACCEPT_LANGUAGE, HeaderMap},
+    routing::future::RouteFuture,
+    Router,
+};
+use std::{
+    convert::Infallible,
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Service that dispatches requests to language specific routers based on the
+/// `Accept-Language` header.
+///
+/// The languages requested by the client are tried in order of their quality values. A requested
+/// language matches a configured language if they're equal, or if the configured language is a
+/// prefix of the requested one. So a request for `en-US` will be handled by the router for `en`
+/// if there is no router for `en-US`. If nothing matches, the default router is used.
+///
+/// The negotiated language is inserted into the request extensions as a [`LanguageTag`], which
+/// can be extracted with [`Extension`].
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, Extension, routing::get};
+/// use axum_extra::routing::{LanguageRouter, LanguageTag};
+///
+/// async fn index(Extension(language): Extension<LanguageTag>) -> String {
+///     format!("content in {language}")
+/// }
+///
+/// let english = Router::new().route("/", get(index));
+/// let german = Router::new().route("/", get(|| async { "Inhalt auf Deutsch" }));
+///
+/// let docs = LanguageRouter::new("en", english).language("de", german);
+///
+/// let app = Router::new().nest_service("/docs", docs);
+/// # let _: Router = app;
+/// ```
+///
+/// [`Extension`]: axum::Extension
+#[derive(Clone)]
+#[must_use]
+pub struct LanguageRouter {
+    default: (LanguageTag, Router),
+    languages: Arc<Vec<(LanguageTag, Router)>>,
+}
+
+impl LanguageRouter {
+    /// Create a new `LanguageRouter` that uses `router` for requests that don't match any other
+    /// language.
+    ///
+    /// `language` is the [`LanguageTag`] inserted for those requests.
+    pub fn new(language: &str, router: Router) -> Self {
+        Self {
+            default: (LanguageTag::new(language), router),
+            languages: Default::default(),
+        }
+    }
+
+    /// Add a router for the given language.
+    pub fn language(mut self, language: &str, router: Router) -> Self {
+        Arc::make_mut(&mut self.languages).push((LanguageTag::new(language), router));
+        self
+    }
+
+    fn negotiate(&self, headers: &HeaderMap) -> &(LanguageTag, Router) {
+        for requested in accepted_languages(headers) {
+            if requested == "*" {
+                break;
+            }
+
+            if let Some(found) = self
+                .languages
+                .iter()
+                .find(|(tag, _)| tag.matches(requested))
+            {
+                return found;
+            }
+
+            if self.default.0.matches(requested) {
+                break;
+            }
+        }
+
+        &self.default
+    }
+}
+
+impl fmt::Debug for LanguageRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LanguageRouter")
+            .field("default", &self.default)
+            .field("languages", &self.languages)
+            .finish()
+    }
+}
+
+impl Service<Request> for LanguageRouter {
+    type Response = axum::response::Response;
+    type Error = Infallible;
+    type Future = RouteFuture<Infallible>;
+
+    #[inline]
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let (tag, router) = self.negotiate(req.headers());
+        let mut router = router.clone();
+        req.extensions_mut().insert(tag.clone());
+        router.call(req)
+    }
+}
+
+/// Parse the `Accept-Language` header and return the requested languages, highest quality
+/// first.
+///
+/// Languages with a quality of `0` are excluded.
+fn accepted_languages(headers: &HeaderMap) -> Vec<&str> {
+    let mut languages = headers
+        .get_all(ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim();
+            if tag.is_empty() {
+                return None;
+            }
+
+            let mut quality = 1.0;
+            for param in parts {
+                if let Some(q) = param.trim().strip_prefix("q=") {
+                    quality = q.trim().parse::<f32>().ok()?;
+                }
+            }
+
+            (quality > 0.0).then_some((tag, quality))
+        })
+        .collect::<Vec<_>>();
+
+    // stable sort so languages with the same quality keep the client's order
+    languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    languages.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// A language negotiated by [`LanguageRouter`], such as `en` or `de-AT`.
+///
+/// Extract it with [`Extension`](axum::Extension).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LanguageTag(Arc<str>);
+
+impl LanguageTag {
+    fn new(tag: &str) -> Self {
+        Self(tag.into())
+    }
+
+    /// Get the language tag as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `requested` is this language or a more specific variant of it.
+    fn matches(&self, requested: &str) -> bool {
+        let tag = self.as_str();
+        match requested.get(..tag.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(tag) => {
+                matches!(requested.as_bytes().get(tag.len()), None | Some(b'-'))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{routing::get, Extension};
+    use http::StatusCode;
+
+    fn app() -> Router {
+        async fn handler(Extension(language): Extension<LanguageTag>) -> String {
+            language.to_string()
+        }
+
+        let router = || Router::new().route("/", get(handler));
+        let languages = LanguageRouter::new("en", router())
+            .language("de", router())
+            .language("pt-BR", router());
+
+        Router::new().nest_service("/docs", languages)
+    }
+
+    #[tokio::test]
+    async fn negotiation() {
+        let client = TestClient::new(app());
+
+        for (accept_language, expected) in [
+            (None, "en"),
+            (Some("de"), "de"),
+            (Some("de-AT"), "de"),
+            (Some("DE"), "de"),
+            (Some("fr, de;q=0.5"), "de"),
+            (Some("de;q=0.5, pt-BR;q=0.8"), "pt-BR"),
+            (Some("pt"), "en"),
+            (Some("en, de"), "en"),
+            (Some("de;q=0"), "en"),
+            (Some("*"), "en"),
+            (Some("deu"), "en"),
+        ] {
+            let mut req = client.get("/docs");
+            if let Some(accept_language) = accept_language {
+                req = req.header("accept-language", accept_language);
+            }
+            let res = req.await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.text().await, expected, "{accept_language:?}");
+        }
+    }
+}
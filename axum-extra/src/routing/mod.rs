@@ -10,12 +10,36 @@ use http::{uri::PathAndQuery, StatusCode, Uri};
 use std::{borrow::Cow, convert::Infallible};
 use tower_service::Service;
 
+#[cfg(feature = "health")]
+use crate::middleware::HealthChecks;
+#[cfg(feature = "timeout")]
+use crate::middleware::TimeoutLayer;
+
+mod language;
+
 mod resource;
 
+#[cfg(feature = "shadow")]
+mod shadow;
+
+#[cfg(feature = "static-routes")]
+mod static_routes;
+
 #[cfg(feature = "typed-routing")]
 mod typed;
 
-pub use self::resource::Resource;
+pub use self::{
+    language::{LanguageRouter, LanguageTag},
+    resource::Resource,
+};
+
+#[cfg(feature = "shadow")]
+pub use self::shadow::{Shadow, ShadowLayer};
+
+#[cfg(feature = "static-routes")]
+pub use self::static_routes::{
+    FileRoute, ProxyRoute, RedirectRoute, ResponseRoute, StaticRoutes, StaticRoutesError,
+};
 
 #[cfg(feature = "typed-routing")]
 pub use self::typed::WithQueryParams;
@@ -23,7 +47,7 @@ pub use self::typed::WithQueryParams;
 pub use axum_macros::TypedPath;
 
 #[cfg(feature = "typed-routing")]
-pub use self::typed::{SecondElementIs, TypedPath};
+pub use self::typed::{SecondElementIs, SegmentEncoding, TypedPath, TypedPathRejection};
 
 /// Extension trait that adds additional methods to [`Router`].
 pub trait RouterExt<S>: sealed::Sealed {
@@ -169,6 +193,33 @@ pub trait RouterExt<S>: sealed::Sealed {
         T::Response: IntoResponse,
         T::Future: Send + 'static,
         Self: Sized;
+
+    /// Fail requests to all routes that take longer than `duration` with
+    /// `408 Request Timeout`.
+    ///
+    /// See [`TimeoutLayer`] for more details.
+    #[cfg(feature = "timeout")]
+    fn timeout(self, duration: std::time::Duration) -> Self
+    where
+        Self: Sized;
+
+    /// Fail requests to all routes that take longer than `duration` with the response returned
+    /// by `on_timeout`.
+    ///
+    /// See [`TimeoutLayer`] for more details.
+    #[cfg(feature = "timeout")]
+    fn timeout_with<T>(self, duration: std::time::Duration, on_timeout: T) -> Self
+    where
+        T: Fn() -> Response + Clone + Send + Sync + 'static,
+        Self: Sized;
+
+    /// Serve the liveness and readiness endpoints of `health_checks`.
+    ///
+    /// See [`HealthChecks`] for more details.
+    #[cfg(feature = "health")]
+    fn health_checks(self, health_checks: HealthChecks) -> Self
+    where
+        Self: Sized;
 }
 
 impl<S> RouterExt<S> for Router<S>
@@ -277,6 +328,65 @@ where
         self = self.route_service(path, service);
         add_tsr_redirect_route(self, path)
     }
+
+    #[cfg(feature = "timeout")]
+    fn timeout(self, duration: std::time::Duration) -> Self
+    where
+        Self: Sized,
+    {
+        self.layer(TimeoutLayer::new(duration))
+    }
+
+    #[cfg(feature = "timeout")]
+    fn timeout_with<T>(self, duration: std::time::Duration, on_timeout: T) -> Self
+    where
+        T: Fn() -> Response + Clone + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.layer(TimeoutLayer::new(duration).on_timeout(on_timeout))
+    }
+
+    #[cfg(feature = "health")]
+    fn health_checks(self, health_checks: HealthChecks) -> Self
+    where
+        Self: Sized,
+    {
+        self.merge(health_checks.router())
+    }
+}
+
+/// Extension trait that adds additional methods to [`MethodRouter`].
+#[cfg(feature = "timeout")]
+pub trait MethodRouterExt<S>: sealed::Sealed {
+    /// Fail requests that take longer than `duration` with `408 Request Timeout`.
+    ///
+    /// See [`TimeoutLayer`] for more details.
+    fn timeout(self, duration: std::time::Duration) -> Self;
+
+    /// Fail requests that take longer than `duration` with the response returned by
+    /// `on_timeout`.
+    ///
+    /// See [`TimeoutLayer`] for more details.
+    fn timeout_with<T>(self, duration: std::time::Duration, on_timeout: T) -> Self
+    where
+        T: Fn() -> Response + Clone + Send + Sync + 'static;
+}
+
+#[cfg(feature = "timeout")]
+impl<S> MethodRouterExt<S> for MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn timeout(self, duration: std::time::Duration) -> Self {
+        self.layer(TimeoutLayer::new(duration))
+    }
+
+    fn timeout_with<T>(self, duration: std::time::Duration, on_timeout: T) -> Self
+    where
+        T: Fn() -> Response + Clone + Send + Sync + 'static,
+    {
+        self.layer(TimeoutLayer::new(duration).on_timeout(on_timeout))
+    }
 }
 
 #[track_caller]
@@ -336,6 +446,7 @@ where
 mod sealed {
     pub trait Sealed {}
     impl<S> Sealed for axum::Router<S> {}
+    impl<S> Sealed for axum::routing::MethodRouter<S> {}
 }
 
 #[cfg(test)]
@@ -0,0 +1,234 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use std::{
+    convert::Infallible,
+    fmt,
+    task::{Context, Poll},
+};
+use tower::ServiceExt;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that mirrors requests to a secondary service.
+///
+/// This can be used for dark launching a new implementation of a route in production. A copy of
+/// a sample of the requests is sent to the secondary service in a background task, while the
+/// client only ever sees the response of the primary service. Responses and errors from the
+/// secondary service are discarded.
+///
+/// Mirrored requests have their bodies buffered so they can be sent to both services. The
+/// [`DefaultBodyLimit`] applies to the buffered bodies.
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, routing::{get, post}, handler::HandlerWithoutStateExt};
+/// use axum_extra::routing::ShadowLayer;
+///
+/// async fn create_user() {}
+///
+/// async fn create_user_v2() {}
+///
+/// let app = Router::new()
+///     .route("/users", post(create_user))
+///     // mirror 5% of requests to `/users` to the new implementation
+///     .route_layer(ShadowLayer::new(create_user_v2.into_service()).sample_rate(0.05))
+///     // routes added after `route_layer` are not mirrored
+///     .route("/health", get(|| async {}));
+/// # let _: Router = app;
+/// ```
+///
+/// [`DefaultBodyLimit`]: axum::extract::DefaultBodyLimit
+#[derive(Clone)]
+pub struct ShadowLayer<T> {
+    shadow: T,
+    sample_rate: f64,
+}
+
+impl<T> ShadowLayer<T> {
+    /// Create a new `ShadowLayer` that mirrors all requests to `shadow`.
+    pub fn new(shadow: T) -> Self {
+        Self {
+            shadow,
+            sample_rate: 1.0,
+        }
+    }
+
+    /// Set the fraction of requests that are mirrored, between `0.0` and `1.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_rate` is not between `0.0` and `1.0`.
+    #[track_caller]
+    pub fn sample_rate(mut self, sample_rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&sample_rate),
+            "sample rate must be between 0.0 and 1.0"
+        );
+        self.sample_rate = sample_rate;
+        self
+    }
+}
+
+impl<T> fmt::Debug for ShadowLayer<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShadowLayer")
+            .field("shadow", &self.shadow)
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+impl<S, T> Layer<S> for ShadowLayer<T>
+where
+    T: Clone,
+{
+    type Service = Shadow<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Shadow {
+            inner,
+            shadow: self.shadow.clone(),
+            sample_rate: self.sample_rate,
+        }
+    }
+}
+
+/// Middleware that mirrors requests to a secondary service.
+///
+/// Created with [`ShadowLayer`]. See that type for more details.
+#[derive(Clone)]
+pub struct Shadow<S, T> {
+    inner: S,
+    shadow: T,
+    sample_rate: f64,
+}
+
+impl<S, T> fmt::Debug for Shadow<S, T>
+where
+    S: fmt::Debug,
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shadow")
+            .field("inner", &self.inner)
+            .field("shadow", &self.shadow)
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+impl<S, T> Service<Request> for Shadow<S, T>
+where
+    S: Service<Request, Error = Infallible> + Clone + Send + 'static,
+    S::Response: IntoResponse,
+    S::Future: Send + 'static,
+    T: Service<Request> + Clone + Send + 'static,
+    T::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let inner = self.inner.clone();
+
+        if self.sample_rate < 1.0 && fastrand::f64() >= self.sample_rate {
+            return Box::pin(async move {
+                let res = inner.oneshot(req).await?;
+                Ok(res.into_response())
+            });
+        }
+
+        let shadow = self.shadow.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let bytes =
+                match Bytes::from_request(Request::from_parts(parts.clone(), body), &()).await {
+                    Ok(bytes) => bytes,
+                    Err(rejection) => return Ok(rejection.into_response()),
+                };
+
+            let shadow_req = Request::from_parts(parts.clone(), Body::from(bytes.clone()));
+            tokio::spawn(async move {
+                let _ = shadow.oneshot(shadow_req).await;
+            });
+
+            let res = inner
+                .oneshot(Request::from_parts(parts, Body::from(bytes)))
+                .await?;
+            Ok(res.into_response())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{
+        handler::HandlerWithoutStateExt,
+        http::StatusCode,
+        routing::{get, post},
+        Router,
+    };
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn mirrors_requests() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let shadow = (move |body: String| async move {
+            tx.send(body).unwrap();
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+        .into_service();
+
+        let app = Router::new()
+            .route("/", post(|body: String| async move { body }))
+            .route_layer(ShadowLayer::new(shadow))
+            .route("/not-mirrored", get(|| async {}));
+
+        let client = TestClient::new(app);
+
+        let res = client.post("/").body("foo").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "foo");
+        assert_eq!(rx.recv().await.unwrap(), "foo");
+
+        let res = client.get("/not-mirrored").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn sample_rate_zero_mirrors_nothing() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let shadow = (move || async move {
+            tx.send(()).unwrap();
+        })
+        .into_service();
+
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .route_layer(ShadowLayer::new(shadow).sample_rate(0.0));
+
+        let client = TestClient::new(app);
+
+        for _ in 0..10 {
+            let res = client.get("/").await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+        assert!(rx.try_recv().is_err());
+    }
+}
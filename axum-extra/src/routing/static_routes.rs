@@ -0,0 +1,441 @@
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Redirect},
+    routing::{any, get},
+    Router,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Uri};
+use serde::Deserialize;
+use std::{borrow::Cow, collections::BTreeMap, convert::Infallible, fmt, path::PathBuf};
+use tower_http::services::ServeDir;
+use tower_service::Service;
+
+/// Routes built from configuration rather than code.
+///
+/// `StaticRoutes` can be deserialized with [serde] from any format, allowing redirects, static
+/// file mounts, fixed responses, and proxy targets to be changed without recompiling the app.
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, routing::get};
+/// use axum_extra::routing::StaticRoutes;
+///
+/// let config = r#"{
+///     "redirects": [
+///         { "path": "/old-blog", "to": "/blog", "permanent": true }
+///     ],
+///     "files": [
+///         { "path": "/assets", "dir": "./assets" }
+///     ],
+///     "responses": [
+///         { "path": "/robots.txt", "body": "User-agent: *\nDisallow: /" },
+///         {
+///             "path": "/maintenance",
+///             "status": 503,
+///             "headers": { "retry-after": "120" }
+///         }
+///     ]
+/// }"#;
+///
+/// let static_routes: StaticRoutes = serde_json::from_str(config).unwrap();
+///
+/// let app = Router::new()
+///     .route("/blog", get(|| async { "blog" }))
+///     .merge(static_routes.into_router().unwrap());
+/// # let _: Router = app;
+/// ```
+///
+/// The paths are checked when building the router, so invalid paths, and paths that are
+/// configured twice or conflict with each other, such as `/:id` and `/:name`, are returned as
+/// errors. Paths that conflict with routes of the router they are merged into still cause a
+/// panic.
+///
+/// [serde]: https://crates.io/crates/serde
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct StaticRoutes {
+    /// Routes that redirect to another location.
+    #[serde(default)]
+    pub redirects: Vec<RedirectRoute>,
+    /// Directories to serve files from.
+    #[serde(default)]
+    pub files: Vec<FileRoute>,
+    /// Routes that always respond with the same response.
+    #[serde(default)]
+    pub responses: Vec<ResponseRoute>,
+    /// Routes that are forwarded to another server.
+    ///
+    /// These require a proxy implementation and therefore only work with
+    /// [`StaticRoutes::into_router_with_proxy`].
+    #[serde(default)]
+    pub proxies: Vec<ProxyRoute>,
+}
+
+/// A route that redirects to another location.
+///
+/// See [`StaticRoutes`] for more details.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct RedirectRoute {
+    /// The path to redirect from.
+    pub path: String,
+    /// The location to redirect to.
+    pub to: String,
+    /// Whether to use [`Redirect::permanent`] rather than [`Redirect::temporary`].
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+/// A route that serves files from a directory.
+///
+/// See [`StaticRoutes`] for more details.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct FileRoute {
+    /// The path to serve the files at.
+    pub path: String,
+    /// The directory to serve the files from.
+    pub dir: PathBuf,
+}
+
+/// A route that always responds with the same response.
+///
+/// See [`StaticRoutes`] for more details.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ResponseRoute {
+    /// The path of the route.
+    pub path: String,
+    /// The status code of the response. Defaults to `200 OK`.
+    #[serde(default = "default_status")]
+    pub status: u16,
+    /// Headers to add to the response.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The response body. Defaults to an empty body.
+    #[serde(default)]
+    pub body: String,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+/// A route that forwards requests to another server.
+///
+/// See [`StaticRoutes`] for more details.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ProxyRoute {
+    /// The path to forward requests from.
+    pub path: String,
+    /// The URI of the server to forward requests to.
+    pub target: String,
+}
+
+impl StaticRoutes {
+    /// Build a [`Router`] from the configured routes.
+    ///
+    /// Returns an error if any of the routes is invalid or if any proxy routes are configured.
+    /// Use [`StaticRoutes::into_router_with_proxy`] to support proxy routes.
+    pub fn into_router<S>(self) -> Result<Router<S>, StaticRoutesError>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        if let Some(proxy) = self.proxies.first() {
+            return Err(StaticRoutesError::new(
+                &proxy.path,
+                "proxy routes require `StaticRoutes::into_router_with_proxy`",
+            ));
+        }
+
+        self.into_router_with_proxy(|_| -> Router {
+            unreachable!("no proxy routes are configured")
+        })
+    }
+
+    /// Build a [`Router`] from the configured routes, using `make_proxy` to create the services
+    /// that handle proxy routes.
+    ///
+    /// `make_proxy` is called once per proxy route with the parsed target URI. The returned
+    /// service is [nested](Router::nest_service) at the route's path, so it sees request URIs
+    /// with that path stripped and is responsible for forwarding them to the target.
+    ///
+    /// Returns an error if any of the routes is invalid.
+    pub fn into_router_with_proxy<S, F, T>(
+        self,
+        mut make_proxy: F,
+    ) -> Result<Router<S>, StaticRoutesError>
+    where
+        S: Clone + Send + Sync + 'static,
+        F: FnMut(Uri) -> T,
+        T: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        T::Response: IntoResponse,
+        T::Future: Send + 'static,
+    {
+        let mut router = Router::new();
+        let mut paths = Paths::default();
+
+        for redirect in self.redirects {
+            paths.route(&redirect.path)?;
+            let to = redirect.to;
+            let permanent = redirect.permanent;
+            router = router.route(
+                &redirect.path,
+                any(move || async move {
+                    if permanent {
+                        Redirect::permanent(&to)
+                    } else {
+                        Redirect::temporary(&to)
+                    }
+                }),
+            );
+        }
+
+        for file in self.files {
+            paths.nest(&file.path)?;
+            router = router.nest_service(&file.path, ServeDir::new(file.dir));
+        }
+
+        for response in self.responses {
+            paths.route(&response.path)?;
+            let status = StatusCode::from_u16(response.status)
+                .map_err(|_| StaticRoutesError::new(&response.path, "invalid status code"))?;
+            let mut headers = HeaderMap::with_capacity(response.headers.len());
+            for (name, value) in response.headers {
+                let name = HeaderName::try_from(name)
+                    .map_err(|_| StaticRoutesError::new(&response.path, "invalid header name"))?;
+                let value = HeaderValue::try_from(value)
+                    .map_err(|_| StaticRoutesError::new(&response.path, "invalid header value"))?;
+                headers.append(name, value);
+            }
+            let body = response.body;
+            router = router.route(
+                &response.path,
+                get(move || async move { (status, headers, body).into_response() }),
+            );
+        }
+
+        for proxy in self.proxies {
+            paths.nest(&proxy.path)?;
+            let target = proxy
+                .target
+                .parse::<Uri>()
+                .map_err(|_| StaticRoutesError::new(&proxy.path, "invalid proxy target"))?;
+            router = router.nest_service(&proxy.path, make_proxy(target));
+        }
+
+        Ok(router)
+    }
+}
+
+/// Checks the paths of the routes the same way the router does, which panics instead of
+/// returning an error.
+#[derive(Default)]
+struct Paths(matchit::Router<()>);
+
+impl Paths {
+    fn route(&mut self, path: &str) -> Result<(), StaticRoutesError> {
+        if !path.starts_with('/') {
+            return Err(StaticRoutesError::new(path, "paths must start with a `/`"));
+        }
+        self.insert(path, path)
+    }
+
+    /// Like [`Router::nest_service`], which also routes everything below `path`.
+    fn nest(&mut self, path: &str) -> Result<(), StaticRoutesError> {
+        if path.contains('*') {
+            return Err(StaticRoutesError::new(
+                path,
+                "nested paths cannot contain wildcards",
+            ));
+        }
+        self.route(path)?;
+        if !path.ends_with('/') {
+            self.insert(path, &format!("{path}/"))?;
+        }
+        self.insert(path, &format!("{}/*rest", path.trim_end_matches('/')))
+    }
+
+    fn insert(&mut self, path: &str, route: &str) -> Result<(), StaticRoutesError> {
+        self.0
+            .insert(route, ())
+            .map_err(|err| StaticRoutesError::new(path, err.to_string()))
+    }
+}
+
+/// Error returned by [`StaticRoutes::into_router`] and [`StaticRoutes::into_router_with_proxy`]
+/// if a route is invalid.
+#[derive(Debug)]
+pub struct StaticRoutesError {
+    path: String,
+    reason: Cow<'static, str>,
+}
+
+impl StaticRoutesError {
+    fn new(path: &str, reason: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            path: path.to_owned(),
+            reason: reason.into(),
+        }
+    }
+
+    /// The path of the invalid route.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for StaticRoutesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid static route {:?}: {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for StaticRoutesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::routing::get;
+    use tower::service_fn;
+
+    #[tokio::test]
+    async fn builds_routes_from_config() {
+        let config = serde_json::json!({
+            "redirects": [
+                { "path": "/old", "to": "/new", "permanent": true },
+                { "path": "/tmp", "to": "/new" },
+            ],
+            "files": [
+                { "path": "/files", "dir": "test_files" },
+            ],
+            "responses": [
+                { "path": "/robots.txt", "body": "User-agent: *" },
+                { "path": "/gone", "status": 410, "headers": { "x-reason": "removed" } },
+            ],
+            "proxies": [
+                { "path": "/upstream", "target": "http://example.com" },
+            ],
+        });
+        let static_routes: StaticRoutes = serde_json::from_value(config).unwrap();
+
+        let app = Router::new().route("/new", get(|| async {})).merge(
+            static_routes
+                .into_router_with_proxy(|target| {
+                    service_fn(move |req: Request| {
+                        let target = target.clone();
+                        async move { Ok::<_, Infallible>(format!("{target} {}", req.uri().path())) }
+                    })
+                })
+                .unwrap(),
+        );
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/old").await;
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers()["location"], "/new");
+
+        let res = client.post("/tmp").await;
+        assert_eq!(res.status(), StatusCode::TEMPORARY_REDIRECT);
+
+        let res = client.get("/files/script.js").await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client.get("/robots.txt").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "User-agent: *");
+
+        let res = client.get("/gone").await;
+        assert_eq!(res.status(), StatusCode::GONE);
+        assert_eq!(res.headers()["x-reason"], "removed");
+
+        let res = client.get("/upstream/foo").await;
+        assert_eq!(res.text().await, "http://example.com/ /foo");
+    }
+
+    #[test]
+    fn invalid_routes() {
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "responses": [{ "path": "/foo", "status": 1000 }],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid static route \"/foo\": invalid status code"
+        );
+
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "redirects": [{ "path": "foo", "to": "/bar" }],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(err.path(), "foo");
+
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "proxies": [{ "path": "/foo", "target": "http://example.com" }],
+        }))
+        .unwrap();
+        assert!(static_routes.into_router::<()>().is_err());
+    }
+
+    #[test]
+    fn duplicate_routes() {
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "redirects": [{ "path": "/foo", "to": "/bar" }],
+            "responses": [{ "path": "/foo" }],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(err.path(), "/foo");
+
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "files": [
+                { "path": "/assets", "dir": "assets" },
+                { "path": "/assets", "dir": "static" },
+            ],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(err.path(), "/assets");
+    }
+
+    #[test]
+    fn conflicting_routes() {
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "redirects": [
+                { "path": "/users/:id", "to": "/" },
+                { "path": "/users/:name", "to": "/" },
+            ],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(err.path(), "/users/:name");
+
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "responses": [{ "path": "/foo/:" }],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(err.path(), "/foo/:");
+
+        let static_routes: StaticRoutes = serde_json::from_value(serde_json::json!({
+            "files": [{ "path": "/assets/*path", "dir": "assets" }],
+        }))
+        .unwrap();
+        let err = static_routes.into_router::<()>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid static route \"/assets/*path\": nested paths cannot contain wildcards"
+        );
+    }
+}
@@ -1,7 +1,12 @@
 use std::{any::type_name, fmt};
 
 use super::sealed::Sealed;
-use http::Uri;
+use axum::{
+    extract::rejection::PathRejection,
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use http::{StatusCode, Uri};
 use serde::Serialize;
 
 /// A type safe path.
@@ -207,7 +212,43 @@ use serde::Serialize;
 /// }
 /// ```
 ///
+/// ## Renaming captures
+///
+/// By default the captures must be named like the fields. To match existing URL conventions, the
+/// captures can instead be named by converting the field names with
+/// `#[typed_path("...", rename_all = "...")]`. The supported conversions are `"lowercase"`,
+/// `"UPPERCASE"`, `"PascalCase"`, `"camelCase"`, `"snake_case"`, `"SCREAMING_SNAKE_CASE"`,
+/// `"kebab-case"` and `"SCREAMING-KEBAB-CASE"`:
+///
+/// ```
+/// use axum_extra::routing::TypedPath;
+///
+/// #[derive(TypedPath)]
+/// #[typed_path("/users/:userId/teams/:teamId", rename_all = "camelCase")]
+/// struct UsersTeam {
+///     user_id: u32,
+///     team_id: u32,
+/// }
+///
+/// assert_eq!(
+///     UsersTeam { user_id: 1, team_id: 2 }.to_string(),
+///     "/users/1/teams/2",
+/// );
+/// ```
+///
+/// ## Custom encoding
+///
+/// Fields can be encoded with a [`SegmentEncoding`] rather than their [`Display`] and
+/// [`Deserialize`] implementations using `#[typed_path(encoding(YourEncoding))]`. See
+/// [`SegmentEncoding`] for an example.
+///
+/// Typed paths that use `rename_all` or `encoding` don't use [`Path`] and so don't need to
+/// implement [`Deserialize`]. Instead each field is parsed from its capture with its
+/// [`FromStr`] implementation, or its encoding, and the rejection is [`TypedPathRejection`]. A
+/// custom rejection must then implement `From<TypedPathRejection>`.
+///
 /// [`FromRequest`]: axum::extract::FromRequest
+/// [`FromStr`]: std::str::FromStr
 /// [`RouterExt::typed_get`]: super::RouterExt::typed_get
 /// [`RouterExt::typed_post`]: super::RouterExt::typed_post
 /// [`Path`]: axum::extract::Path
@@ -318,6 +359,159 @@ where
     const PATH: &'static str = P::PATH;
 }
 
+/// Custom encoding of a path segment for `#[derive(TypedPath)]`.
+///
+/// Used with `#[typed_path(encoding(...))]` on a field of a [`TypedPath`] to encode the field
+/// with something other than its [`Display`] and [`FromStr`] implementations.
+///
+/// The encoded segment is percent-encoded, so it can contain any characters.
+///
+/// # Example
+///
+/// ```
+/// use axum_extra::routing::{SegmentEncoding, TypedPath};
+///
+/// // Encodes ids in base 62, such that `/users/4c92` refers to user 1000000
+/// struct Base62;
+///
+/// const ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+///
+/// #[derive(Debug)]
+/// struct InvalidBase62;
+///
+/// impl std::fmt::Display for InvalidBase62 {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         f.write_str("invalid base 62 number")
+///     }
+/// }
+///
+/// impl std::error::Error for InvalidBase62 {}
+///
+/// impl SegmentEncoding<u64> for Base62 {
+///     type Error = InvalidBase62;
+///
+///     fn encode(value: &u64) -> String {
+///         let mut value = *value;
+///         let mut digits = Vec::new();
+///         loop {
+///             digits.push(ALPHABET[(value % 62) as usize]);
+///             value /= 62;
+///             if value == 0 {
+///                 break;
+///             }
+///         }
+///         digits.reverse();
+///         String::from_utf8(digits).unwrap()
+///     }
+///
+///     fn decode(segment: &str) -> Result<u64, Self::Error> {
+///         if segment.is_empty() {
+///             return Err(InvalidBase62);
+///         }
+///         segment.bytes().try_fold(0u64, |value, byte| {
+///             let digit = ALPHABET.iter().position(|&c| c == byte).ok_or(InvalidBase62)?;
+///             value
+///                 .checked_mul(62)
+///                 .and_then(|value| value.checked_add(digit as u64))
+///                 .ok_or(InvalidBase62)
+///         })
+///     }
+/// }
+///
+/// #[derive(TypedPath)]
+/// #[typed_path("/users/:id")]
+/// struct UsersMember {
+///     #[typed_path(encoding(Base62))]
+///     id: u64,
+/// }
+///
+/// assert_eq!(UsersMember { id: 1000000 }.to_string(), "/users/4c92");
+/// ```
+///
+/// [`Display`]: std::fmt::Display
+/// [`FromStr`]: std::str::FromStr
+pub trait SegmentEncoding<T> {
+    /// The error returned if a segment can't be decoded.
+    type Error: Into<BoxError>;
+
+    /// Encode `value` as a path segment.
+    fn encode(value: &T) -> String;
+
+    /// Decode a percent-decoded path segment.
+    fn decode(segment: &str) -> Result<T, Self::Error>;
+}
+
+/// Rejection used for [`TypedPath`]s that use `rename_all` or `encoding`.
+///
+/// See [`TypedPath`] for more details.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TypedPathRejection {
+    /// The captures couldn't be extracted from the request.
+    Path(PathRejection),
+    /// The matched route doesn't have a capture the typed path needs.
+    ///
+    /// This is most likely caused by adding the handler with `Router::route` rather than
+    /// [`RouterExt::typed_get`] and friends.
+    ///
+    /// [`RouterExt::typed_get`]: super::RouterExt::typed_get
+    MissingCapture {
+        /// The name of the capture.
+        name: &'static str,
+    },
+    /// A capture couldn't be decoded.
+    InvalidCapture {
+        /// The name of the capture.
+        name: &'static str,
+        /// The error returned by `FromStr` or the [`SegmentEncoding`].
+        source: BoxError,
+    },
+}
+
+impl IntoResponse for TypedPathRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Path(rejection) => rejection.into_response(),
+            Self::MissingCapture { .. } => {
+                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+            }
+            Self::InvalidCapture { .. } => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
+        }
+    }
+}
+
+impl From<PathRejection> for TypedPathRejection {
+    fn from(rejection: PathRejection) -> Self {
+        Self::Path(rejection)
+    }
+}
+
+impl fmt::Display for TypedPathRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(rejection) => write!(f, "{rejection}"),
+            Self::MissingCapture { name } => {
+                write!(f, "No capture named `{name}` found for matched route")
+            }
+            Self::InvalidCapture { name, source } => {
+                write!(f, "Invalid URL: Cannot parse `{name}`: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TypedPathRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Path(rejection) => Some(rejection),
+            Self::MissingCapture { .. } => None,
+            Self::InvalidCapture { source, .. } => Some(&**source),
+        }
+    }
+}
+
 /// Utility trait used with [`RouterExt`] to ensure the second element of a tuple type is a
 /// given type.
 ///
@@ -389,12 +583,9 @@ mod tests {
     use crate::{
         extract::WithRejection,
         routing::{RouterExt, TypedPath},
+        test_helpers::*,
     };
-    use axum::{
-        extract::rejection::PathRejection,
-        response::{IntoResponse, Response},
-        Router,
-    };
+    use axum::Router;
     use serde::Deserialize;
 
     #[derive(TypedPath, Deserialize)]
@@ -443,6 +634,58 @@ mod tests {
         assert_eq!(uri, "/users/1?&foo=foo&bar=123&baz=true&qux=1337");
     }
 
+    struct Hex;
+
+    impl SegmentEncoding<u32> for Hex {
+        type Error = std::num::ParseIntError;
+
+        fn encode(value: &u32) -> String {
+            format!("{value:x}")
+        }
+
+        fn decode(segment: &str) -> Result<u32, Self::Error> {
+            u32::from_str_radix(segment, 16)
+        }
+    }
+
+    #[derive(TypedPath)]
+    #[typed_path("/users/:userId/files/*filePath", rename_all = "camelCase")]
+    struct UsersFile {
+        #[typed_path(encoding(Hex))]
+        user_id: u32,
+        file_path: String,
+    }
+
+    #[test]
+    fn rename_all_and_encoding_display() {
+        let path = UsersFile {
+            user_id: 255,
+            file_path: "a b".to_owned(),
+        };
+        assert_eq!(path.to_string(), "/users/ff/files/a%20b");
+    }
+
+    #[crate::test]
+    async fn rename_all_and_encoding_extract() {
+        async fn handler(path: UsersFile) -> String {
+            format!("{} {}", path.user_id, path.file_path)
+        }
+
+        let app = Router::new().typed_get(handler);
+        let client = TestClient::new(app);
+
+        let res = client.get("/users/ff/files/a%20b").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "255 a b");
+
+        let res = client.get("/users/zz/files/a").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            res.text().await,
+            "Invalid URL: Cannot parse `userId`: invalid digit found in string"
+        );
+    }
+
     #[allow(dead_code)] // just needs to compile
     fn supports_with_rejection() {
         async fn handler(_: WithRejection<UsersShow, MyRejection>) {}
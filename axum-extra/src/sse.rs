@@ -0,0 +1,552 @@
+//! Utilities for [server-sent events](axum::response::sse).
+//!
+//! [`SseBroadcaster`] sends events to many subscribers, grouped by topic.
+//!
+//! # Resuming after reconnecting
+//!
+//! Browsers automatically reconnect to an [`EventSource`] when the connection is lost, and
+//! send the id of the last event they received in the `Last-Event-ID` header. [`ReplayBuffer`]
+//! keeps the most recent events so the ones that were sent while the client was disconnected
+//! can be sent again.
+//!
+//! # Example
+//!
+//! ```rust
+//! use axum::{
+//!     extract::State,
+//!     response::sse::{Event, Sse},
+//!     routing::get,
+//!     Router,
+//! };
+//! use axum_extra::sse::{LastEventId, ReplayBuffer, ReplayStream};
+//!
+//! async fn events(
+//!     State(buffer): State<ReplayBuffer>,
+//!     last_event_id: LastEventId,
+//! ) -> Sse<ReplayStream> {
+//!     Sse::new(buffer.subscribe(&last_event_id))
+//! }
+//!
+//! let buffer = ReplayBuffer::new(100);
+//!
+//! // somewhere else in the app
+//! buffer.send(Event::default().data("hello"));
+//!
+//! let app = Router::new()
+//!     .route("/events", get(events))
+//!     .with_state(buffer);
+//! # let _: Router = app;
+//! ```
+//!
+//! [`EventSource`]: https://developer.mozilla.org/en-US/docs/Web/API/EventSource
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    response::sse::{Event, KeepAlive, Sse, TypedEvent},
+};
+use futures_util::{ready, stream::Stream};
+use http::request::Parts;
+use pin_project_lite::pin_project;
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    vec,
+};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+/// Extractor for the `Last-Event-ID` header.
+///
+/// Browsers send the id of the last event they received in this header when reconnecting to an
+/// `EventSource`. It's `None` if the client hasn't received any events with an id, or if the
+/// header isn't valid UTF-8.
+///
+/// See the [module docs](self) for an example.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LastEventId(pub Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for LastEventId
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let id = parts
+            .headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .map(ToOwned::to_owned);
+        Ok(Self(id))
+    }
+}
+
+axum_core::__impl_deref!(LastEventId: Option<String>);
+
+/// A broadcast channel for server-sent events that keeps the most recent events so clients can
+/// resume after reconnecting.
+///
+/// Every event sent with [`ReplayBuffer::send`] is given an increasing id and delivered to all
+/// streams created with [`ReplayBuffer::subscribe`]. A client that reconnects with
+/// [`LastEventId`] first receives the buffered events it missed, then new events as they're sent.
+///
+/// Cloning `ReplayBuffer` is cheap and all clones share the same buffer.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct ReplayBuffer {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    capacity: usize,
+    next_id: u64,
+    events: VecDeque<(u64, Event)>,
+    sender: broadcast::Sender<Event>,
+}
+
+impl ReplayBuffer {
+    /// Create a new `ReplayBuffer` that keeps the last `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "`ReplayBuffer` capacity must be greater than zero"
+        );
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                capacity,
+                next_id: 0,
+                events: VecDeque::with_capacity(capacity),
+                sender,
+            })),
+        }
+    }
+
+    /// Send an event to all subscribers and store it in the buffer.
+    ///
+    /// The event's id field is set by the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event already has an id.
+    pub fn send(&self, event: Event) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let id = inner.next_id;
+        inner.next_id += 1;
+        let event = event.id(id.to_string());
+
+        if inner.events.len() == inner.capacity {
+            inner.events.pop_front();
+        }
+        inner.events.push_back((id, event.clone()));
+
+        // there might not be any subscribers
+        let _ = inner.sender.send(event);
+    }
+
+    /// Subscribe to the events, starting after `last_event_id`.
+    ///
+    /// The stream first yields the buffered events that were sent after `last_event_id`, then
+    /// all new events. If the id is missing or wasn't created by this buffer only new events are
+    /// yielded.
+    pub fn subscribe(&self, last_event_id: &LastEventId) -> ReplayStream {
+        let inner = self.inner.lock().unwrap();
+
+        let missed = match last_event_id
+            .0
+            .as_deref()
+            .and_then(|id| id.parse::<u64>().ok())
+        {
+            Some(last_id) => inner
+                .events
+                .iter()
+                .filter(|(id, _)| *id > last_id)
+                .map(|(_, event)| event.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // subscribe while holding the lock so no events are missed or yielded twice
+        ReplayStream {
+            missed: missed.into_iter(),
+            live: BroadcastStream::new(inner.sender.subscribe()),
+        }
+    }
+}
+
+impl fmt::Debug for ReplayBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("ReplayBuffer")
+            .field("capacity", &inner.capacity)
+            .field("len", &inner.events.len())
+            .finish()
+    }
+}
+
+pin_project! {
+    /// Stream of events created with [`ReplayBuffer::subscribe`].
+    ///
+    /// If the client falls so far behind that events are dropped before it receives them the
+    /// stream ends. The client then reconnects and receives the missed events from the buffer.
+    pub struct ReplayStream {
+        missed: vec::IntoIter<Event>,
+        #[pin]
+        live: BroadcastStream<Event>,
+    }
+}
+
+impl Stream for ReplayStream {
+    type Item = Result<Event, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if let Some(event) = this.missed.next() {
+            return Poll::Ready(Some(Ok(event)));
+        }
+
+        match ready!(this.live.poll_next(cx)) {
+            Some(Ok(event)) => Poll::Ready(Some(Ok(event))),
+            Some(Err(_)) | None => Poll::Ready(None),
+        }
+    }
+}
+
+impl fmt::Debug for ReplayStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplayStream")
+            .field("missed", &self.missed.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Topic based fan-out of server-sent events to many subscribers.
+///
+/// Publishers send events to a topic with [`SseBroadcaster::publish`] or
+/// [`SseBroadcaster::publish_typed`], and every client subscribed to that topic receives them.
+/// [`SseBroadcaster::sse`] creates the response for a subscription, with keep-alive messages
+/// enabled.
+///
+/// A topic only exists while it has subscribers. Events published to a topic without
+/// subscribers are dropped, and the topic is removed once its last subscriber disconnects.
+///
+/// Each subscriber can fall `capacity` events behind. Events a subscriber falls further behind
+/// than that are skipped for that subscriber so one slow client can't hold back the others.
+///
+/// Cloning `SseBroadcaster` is cheap and all clones share the same topics.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     extract::{Path, State},
+///     response::sse::{Sse, TypedEvent},
+///     routing::{get, post},
+///     Router,
+/// };
+/// use axum_extra::sse::{SseBroadcaster, Subscription};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Message {
+///     text: String,
+/// }
+///
+/// impl TypedEvent for Message {
+///     const EVENT: &'static str = "message";
+/// }
+///
+/// async fn subscribe(
+///     State(broadcaster): State<SseBroadcaster>,
+///     Path(room): Path<String>,
+/// ) -> Sse<Subscription> {
+///     broadcaster.sse(room)
+/// }
+///
+/// async fn send_message(
+///     State(broadcaster): State<SseBroadcaster>,
+///     Path(room): Path<String>,
+///     text: String,
+/// ) {
+///     broadcaster.publish_typed(&room, &Message { text }).unwrap();
+/// }
+///
+/// let app = Router::new()
+///     .route("/rooms/:room/events", get(subscribe))
+///     .route("/rooms/:room/messages", post(send_message))
+///     .with_state(SseBroadcaster::new(16));
+/// # let _: Router = app;
+/// ```
+#[derive(Clone)]
+pub struct SseBroadcaster {
+    topics: Arc<Mutex<HashMap<String, broadcast::Sender<Event>>>>,
+    capacity: usize,
+}
+
+impl SseBroadcaster {
+    /// Create a new `SseBroadcaster` where each subscriber can fall `capacity` events behind.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "`SseBroadcaster` capacity must be greater than zero"
+        );
+        Self {
+            topics: Default::default(),
+            capacity,
+        }
+    }
+
+    /// Send an event to all subscribers of `topic`.
+    ///
+    /// Returns the number of subscribers the event was sent to.
+    pub fn publish(&self, topic: &str, event: Event) -> usize {
+        let topics = self.topics.lock().unwrap();
+        match topics.get(topic) {
+            Some(sender) => sender.send(event).unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// Send an event created with [`Event::typed`] to all subscribers of `topic`.
+    ///
+    /// Returns the number of subscribers the event was sent to, or an error if `data` couldn't be
+    /// serialized.
+    pub fn publish_typed<T>(&self, topic: &str, data: &T) -> Result<usize, axum::Error>
+    where
+        T: TypedEvent,
+    {
+        Ok(self.publish(topic, Event::typed(data)?))
+    }
+
+    /// Subscribe to the events published to `topic` from now on.
+    pub fn subscribe(&self, topic: impl Into<String>) -> Subscription {
+        let topic = topic.into();
+        let mut topics = self.topics.lock().unwrap();
+        let receiver = topics
+            .entry(topic.clone())
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .subscribe();
+
+        Subscription {
+            live: BroadcastStream::new(receiver),
+            guard: TopicGuard {
+                topics: Arc::clone(&self.topics),
+                topic,
+            },
+        }
+    }
+
+    /// Subscribe to `topic` and create a response that sends its events to the client.
+    pub fn sse(&self, topic: impl Into<String>) -> Sse<Subscription> {
+        Sse::new(self.subscribe(topic)).keep_alive(KeepAlive::default())
+    }
+
+    /// The number of subscribers of `topic`.
+    pub fn subscriber_count(&self, topic: &str) -> usize {
+        let topics = self.topics.lock().unwrap();
+        topics
+            .get(topic)
+            .map_or(0, broadcast::Sender::receiver_count)
+    }
+}
+
+impl fmt::Debug for SseBroadcaster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let topics = self.topics.lock().unwrap();
+        f.debug_struct("SseBroadcaster")
+            .field("capacity", &self.capacity)
+            .field("topics", &topics.len())
+            .finish()
+    }
+}
+
+pin_project! {
+    /// Stream of events created with [`SseBroadcaster::subscribe`].
+    pub struct Subscription {
+        #[pin]
+        live: BroadcastStream<Event>,
+        // dropped after `live` so the receiver has already been removed from the topic
+        guard: TopicGuard,
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Result<Event, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            match ready!(this.live.as_mut().poll_next(cx)) {
+                Some(Ok(event)) => return Poll::Ready(Some(Ok(event))),
+                // the subscriber lagged behind, skip the events it missed
+                Some(Err(_)) => continue,
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("topic", &self.guard.topic)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Removes the topic when its last subscriber is dropped.
+struct TopicGuard {
+    topics: Arc<Mutex<HashMap<String, broadcast::Sender<Event>>>>,
+    topic: String,
+}
+
+impl Drop for TopicGuard {
+    fn drop(&mut self) {
+        let Ok(mut topics) = self.topics.lock() else {
+            return;
+        };
+        if matches!(topics.get(&self.topic), Some(sender) if sender.receiver_count() == 0) {
+            topics.remove(&self.topic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use axum::{extract::State, response::sse::Sse, routing::get, Router};
+
+    fn app(buffer: ReplayBuffer) -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(
+                    |State(buffer): State<ReplayBuffer>, last_event_id: LastEventId| async move {
+                        Sse::new(buffer.subscribe(&last_event_id))
+                    },
+                ),
+            )
+            .with_state(buffer)
+    }
+
+    #[tokio::test]
+    async fn replay() {
+        let buffer = ReplayBuffer::new(2);
+        for data in ["a", "b", "c"] {
+            buffer.send(Event::default().data(data));
+        }
+        let client = TestClient::new(app(buffer.clone()));
+
+        // "a" has been dropped from the buffer
+        let mut res = client.get("/").header("last-event-id", "0").await;
+        assert_eq!(res.chunk_text().await.unwrap(), "data: b\nid: 1\n\n");
+        assert_eq!(res.chunk_text().await.unwrap(), "data: c\nid: 2\n\n");
+
+        buffer.send(Event::default().data("d"));
+        assert_eq!(res.chunk_text().await.unwrap(), "data: d\nid: 3\n\n");
+
+        let mut res = client.get("/").header("last-event-id", "2").await;
+        buffer.send(Event::default().data("e"));
+        assert_eq!(res.chunk_text().await.unwrap(), "data: d\nid: 3\n\n");
+        assert_eq!(res.chunk_text().await.unwrap(), "data: e\nid: 4\n\n");
+    }
+
+    #[tokio::test]
+    async fn without_last_event_id() {
+        let buffer = ReplayBuffer::new(2);
+        buffer.send(Event::default().data("a"));
+        let client = TestClient::new(app(buffer.clone()));
+
+        let mut res = client.get("/").await;
+        buffer.send(Event::default().data("b"));
+        assert_eq!(res.chunk_text().await.unwrap(), "data: b\nid: 1\n\n");
+    }
+
+    #[tokio::test]
+    async fn broadcaster() {
+        use futures_util::StreamExt;
+
+        let broadcaster = SseBroadcaster::new(2);
+        assert_eq!(broadcaster.publish("a", Event::default().data("0")), 0);
+
+        let mut first = broadcaster.subscribe("a");
+        let mut second = broadcaster.subscribe("a");
+        let mut other = broadcaster.subscribe("b");
+        assert_eq!(broadcaster.subscriber_count("a"), 2);
+
+        for data in ["1", "2", "3"] {
+            assert_eq!(broadcaster.publish("a", Event::default().data(data)), 2);
+        }
+        broadcaster.publish("b", Event::default().data("4"));
+
+        // both lagged behind and skip "1"
+        let expected = format!("{:?}", Event::default().data("2"));
+        let event = first.next().await.unwrap().unwrap();
+        assert_eq!(format!("{event:?}"), expected);
+        let event = second.next().await.unwrap().unwrap();
+        assert_eq!(format!("{event:?}"), expected);
+
+        let expected = format!("{:?}", Event::default().data("4"));
+        let event = other.next().await.unwrap().unwrap();
+        assert_eq!(format!("{event:?}"), expected);
+
+        drop(first);
+        assert_eq!(broadcaster.subscriber_count("a"), 1);
+        drop(second);
+        assert_eq!(broadcaster.subscriber_count("a"), 0);
+        assert!(!broadcaster.topics.lock().unwrap().contains_key("a"));
+        assert!(broadcaster.topics.lock().unwrap().contains_key("b"));
+    }
+
+    #[tokio::test]
+    async fn broadcaster_response() {
+        #[derive(serde::Serialize)]
+        struct Message {
+            text: &'static str,
+        }
+
+        impl TypedEvent for Message {
+            const EVENT: &'static str = "message";
+        }
+
+        let broadcaster = SseBroadcaster::new(16);
+        let app =
+            Router::new()
+                .route(
+                    "/",
+                    get(|State(broadcaster): State<SseBroadcaster>| async move {
+                        broadcaster.sse("topic")
+                    }),
+                )
+                .with_state(broadcaster.clone());
+        let client = TestClient::new(app);
+
+        let mut res = client.get("/").await;
+        assert_eq!(res.headers()["content-type"], "text/event-stream");
+        broadcaster
+            .publish_typed("topic", &Message { text: "hi" })
+            .unwrap();
+        assert_eq!(
+            res.chunk_text().await.unwrap(),
+            "event: message\ndata: {\"text\":\"hi\"}\n\n"
+        );
+    }
+}
@@ -0,0 +1,810 @@
+//! Calling a [`Router`] or a handler in tests without running a server.
+//!
+//! Use [`TestClient`] to test a whole `Router`, and [`HandlerRequest`] to test a single handler.
+//!
+//! # Example
+//!
+//! ```
+//! use axum::{Router, routing::post, Json, http::StatusCode};
+//! use axum_extra::test_utils::TestClient;
+//! use serde_json::{json, Value};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! async fn echo(Json(value): Json<Value>) -> Json<Value> {
+//!     Json(value)
+//! }
+//!
+//! let app = Router::new().route("/echo", post(echo));
+//! let client = TestClient::new(app);
+//!
+//! let response = client.post("/echo").json(&json!({ "hello": "world" })).await;
+//! response.assert_status(StatusCode::OK);
+//! assert_eq!(response.json::<Value>(), json!({ "hello": "world" }));
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    future::IntoFuture,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    handler::Handler,
+    response::Response,
+    routing::any,
+    Router,
+};
+use futures_util::future::BoxFuture;
+use http::{
+    header::{self, HeaderName, HeaderValue},
+    HeaderMap, Method, StatusCode,
+};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+mod sse;
+
+pub use self::sse::{parse_events, ParsedEvent};
+
+#[cfg(feature = "test-utils-proptest")]
+mod arbitrary;
+
+#[cfg(feature = "test-utils-proptest")]
+pub use self::arbitrary::{arbitrary_request, ArbitraryRequest};
+
+#[cfg(feature = "test-utils-ws")]
+mod ws;
+
+#[cfg(feature = "test-utils-ws")]
+pub use self::ws::TestWebSocket;
+
+/// A client that sends requests straight to a [`Router`].
+///
+/// Cookies set by responses are sent with later requests, like a browser would. Clones share
+/// the same cookies.
+#[derive(Debug, Clone)]
+pub struct TestClient {
+    router: Router,
+    cookies: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl TestClient {
+    /// Create a client for `router`.
+    pub fn new(router: Router) -> Self {
+        Self {
+            router,
+            cookies: Default::default(),
+        }
+    }
+
+    /// Start building a request with the given method and URI, such as `/users?page=2`.
+    pub fn request(&self, method: Method, uri: &str) -> RequestBuilder {
+        RequestBuilder {
+            client: self.clone(),
+            builder: Request::builder().method(method).uri(uri),
+            body: Body::empty(),
+        }
+    }
+
+    /// Start building a `GET` request.
+    pub fn get(&self, uri: &str) -> RequestBuilder {
+        self.request(Method::GET, uri)
+    }
+
+    /// Start building a `HEAD` request.
+    pub fn head(&self, uri: &str) -> RequestBuilder {
+        self.request(Method::HEAD, uri)
+    }
+
+    /// Start building a `POST` request.
+    pub fn post(&self, uri: &str) -> RequestBuilder {
+        self.request(Method::POST, uri)
+    }
+
+    /// Start building a `PUT` request.
+    pub fn put(&self, uri: &str) -> RequestBuilder {
+        self.request(Method::PUT, uri)
+    }
+
+    /// Start building a `PATCH` request.
+    pub fn patch(&self, uri: &str) -> RequestBuilder {
+        self.request(Method::PATCH, uri)
+    }
+
+    /// Start building a `DELETE` request.
+    pub fn delete(&self, uri: &str) -> RequestBuilder {
+        self.request(Method::DELETE, uri)
+    }
+
+    /// Start sending an already built `request`, such as one from
+    /// [`ArbitraryRequest::into_request`](crate::test_utils::ArbitraryRequest::into_request).
+    ///
+    /// Like the other methods, the request is only sent once the returned builder is awaited.
+    pub fn send(&self, request: Request) -> RequestBuilder {
+        let (parts, body) = request.into_parts();
+        let mut builder = Request::builder()
+            .method(parts.method)
+            .uri(parts.uri)
+            .version(parts.version);
+        if let Some(headers) = builder.headers_mut() {
+            *headers = parts.headers;
+        }
+        if let Some(extensions) = builder.extensions_mut() {
+            *extensions = parts.extensions;
+        }
+
+        RequestBuilder {
+            client: self.clone(),
+            builder,
+            body,
+        }
+    }
+
+    /// Get the value of a cookie that will be sent with the next request.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies.lock().unwrap().get(name).cloned()
+    }
+
+    fn add_cookies(&self, headers: &mut HeaderMap) {
+        let cookies = self.cookies.lock().unwrap();
+        if cookies.is_empty() || headers.contains_key(header::COOKIE) {
+            return;
+        }
+
+        let cookie = cookies
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        headers.insert(header::COOKIE, cookie.parse().unwrap());
+    }
+
+    fn store_cookies(&self, headers: &HeaderMap) {
+        let mut cookies = self.cookies.lock().unwrap();
+        for set_cookie in headers.get_all(header::SET_COOKIE) {
+            let Ok(set_cookie) = set_cookie.to_str() else {
+                continue;
+            };
+            let mut attributes = set_cookie.split(';').map(str::trim);
+            let Some((name, value)) = attributes.next().and_then(|pair| pair.split_once('='))
+            else {
+                continue;
+            };
+
+            // cookies are removed by setting them to expire immediately
+            let removed = attributes.any(|attribute| {
+                attribute.split_once('=').map_or(false, |(key, value)| {
+                    key.eq_ignore_ascii_case("max-age") && value.trim().starts_with(['0', '-'])
+                })
+            });
+
+            if removed {
+                cookies.remove(name.trim());
+            } else {
+                cookies.insert(name.trim().to_owned(), value.trim().to_owned());
+            }
+        }
+    }
+}
+
+/// A request being built by a [`TestClient`].
+///
+/// Send it by awaiting it.
+///
+/// # Panics
+///
+/// Awaiting the request panics if the method, URI, or a header is invalid.
+#[derive(Debug)]
+#[must_use = "requests are only sent when awaited"]
+pub struct RequestBuilder {
+    client: TestClient,
+    builder: http::request::Builder,
+    body: Body,
+}
+
+impl RequestBuilder {
+    /// Add a header to the request.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    /// Set the body of the request.
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Set the body of the request to `json` and the `Content-Type` to `application/json`.
+    pub fn json<T>(self, json: &T) -> Self
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        self.header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(json).expect("failed to serialize JSON body"))
+    }
+
+    /// Set the body of the request to `form` and the `Content-Type` to `multipart/form-data`
+    /// with the form's boundary.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::post, http::StatusCode};
+    /// use axum_extra::{
+    ///     extract::Multipart,
+    ///     multipart_builder::{MultipartForm, Part},
+    ///     test_utils::TestClient,
+    /// };
+    ///
+    /// async fn upload(mut multipart: Multipart) -> String {
+    ///     let field = multipart.next_field().await.unwrap().unwrap();
+    ///     field.file_name().unwrap().to_owned()
+    /// }
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let client = TestClient::new(Router::new().route("/upload", post(upload)));
+    ///
+    /// let form = MultipartForm::with_parts(vec![Part::file("file", "notes.txt", b"hi".to_vec())]);
+    /// client
+    ///     .post("/upload")
+    ///     .multipart(form)
+    ///     .await
+    ///     .assert_status(StatusCode::OK)
+    ///     .assert_text("notes.txt");
+    /// # }
+    /// ```
+    #[cfg(feature = "multipart")]
+    pub fn multipart(self, form: crate::multipart_builder::MultipartForm) -> Self {
+        use axum::response::IntoResponse;
+
+        // the form generates its boundary when it's converted into a response
+        let (parts, body) = form.into_response().into_parts();
+        let content_type = parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .expect("multipart form without content type")
+            .clone();
+        self.header(header::CONTENT_TYPE, content_type).body(body)
+    }
+}
+
+impl RequestBuilder {
+    /// Send the request and read the first `count` [server-sent events](ParsedEvent) of the
+    /// response.
+    ///
+    /// Unlike awaiting the request, this doesn't wait for the body to end, so it works with event
+    /// streams that never end. Fewer events are returned if the body ends first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     Router,
+    ///     routing::get,
+    ///     response::sse::{Event, Sse},
+    /// };
+    /// use axum_extra::test_utils::TestClient;
+    /// use futures_util::stream::{self, Stream, StreamExt};
+    /// use std::convert::Infallible;
+    ///
+    /// async fn ticks() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    ///     let events = stream::iter(0..).map(|tick| Ok(Event::default().json_data(tick).unwrap()));
+    ///     Sse::new(events)
+    /// }
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let client = TestClient::new(Router::new().route("/ticks", get(ticks)));
+    ///
+    /// let events = client.get("/ticks").events(3).await;
+    /// let ticks = events.iter().map(|event| event.json()).collect::<Vec<u32>>();
+    /// assert_eq!(ticks, [0, 1, 2]);
+    /// # }
+    /// ```
+    pub async fn events(self, count: usize) -> Vec<ParsedEvent> {
+        let response = self.send().await;
+        sse::collect_events(response.into_body(), count).await
+    }
+
+    async fn send(self) -> Response {
+        let Self {
+            client,
+            builder,
+            body,
+        } = self;
+
+        let mut request = builder.body(body).expect("invalid request");
+        client.add_cookies(request.headers_mut());
+
+        let response = client
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .unwrap_or_else(|err| match err {});
+        client.store_cookies(response.headers());
+        response
+    }
+}
+
+impl IntoFuture for RequestBuilder {
+    type Output = TestResponse;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let (parts, body) = self.send().await.into_parts();
+            let body = body
+                .collect()
+                .await
+                .expect("failed to read response body")
+                .to_bytes();
+
+            TestResponse {
+                status: parts.status,
+                headers: parts.headers,
+                body,
+            }
+        })
+    }
+}
+
+/// The response to a request sent with a [`TestClient`].
+///
+/// The whole body is read before the response is returned, so responses with bodies that never
+/// end, such as endless server-sent event streams, can't be tested with it. Use
+/// [`RequestBuilder::events`] for those.
+#[derive(Debug)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    /// The status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body of the response.
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The body of the response as text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the body isn't valid UTF-8.
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.body).expect("response body is not valid UTF-8")
+    }
+
+    /// Parse the body of the response as a `text/event-stream` of server-sent events.
+    ///
+    /// See [`parse_events`] for details.
+    pub fn events(&self) -> Vec<ParsedEvent> {
+        parse_events(&String::from_utf8_lossy(&self.body))
+    }
+
+    /// Deserialize the body of the response from JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the body can't be deserialized into `T`.
+    pub fn json<T>(&self) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body).unwrap_or_else(|err| {
+            panic!(
+                "failed to deserialize response body: {err}\nbody: {}",
+                String::from_utf8_lossy(&self.body)
+            )
+        })
+    }
+
+    /// Assert that the response has the status code `expected`.
+    ///
+    /// The body is included in the panic message, which usually explains why a request failed.
+    #[track_caller]
+    pub fn assert_status(&self, expected: StatusCode) -> &Self {
+        assert_eq!(
+            self.status,
+            expected,
+            "unexpected status code\nbody: {}",
+            String::from_utf8_lossy(&self.body)
+        );
+        self
+    }
+
+    /// Assert that the response has a header `name` with the value `expected`.
+    #[track_caller]
+    pub fn assert_header(&self, name: impl header::AsHeaderName, expected: &str) -> &Self {
+        let value = self
+            .headers
+            .get(name)
+            .map(|value| value.to_str().unwrap_or_default());
+        assert_eq!(value, Some(expected), "unexpected header value");
+        self
+    }
+
+    /// Assert that the body of the response is the text `expected`.
+    #[track_caller]
+    pub fn assert_text(&self, expected: &str) -> &Self {
+        assert_eq!(self.text(), expected);
+        self
+    }
+}
+
+/// A request for calling a single handler directly.
+///
+/// The state, path parameters, headers, and body that the handler's extractors read are set on
+/// the request, which is then passed to the handler with [`HandlerRequest::call`]. This makes it
+/// possible to unit test a handler without building the [`Router`] it is used in.
+///
+/// # Example
+///
+/// ```
+/// use axum::{extract::{Path, State}, http::StatusCode, Json};
+/// use axum_extra::test_utils::HandlerRequest;
+/// use serde_json::{json, Value};
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     admin_id: u32,
+/// }
+///
+/// async fn update_user(
+///     State(state): State<AppState>,
+///     Path(id): Path<u32>,
+///     Json(_user): Json<Value>,
+/// ) -> StatusCode {
+///     if id == state.admin_id {
+///         StatusCode::FORBIDDEN
+///     } else {
+///         StatusCode::NO_CONTENT
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let response = HandlerRequest::new()
+///     .state(AppState { admin_id: 1 })
+///     .path_param("id", "1")
+///     .json(&json!({ "name": "alice" }))
+///     .call(update_user)
+///     .await;
+///
+/// assert_eq!(response.status(), StatusCode::FORBIDDEN);
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Calling the handler panics if the method, a path parameter name, or a header is invalid.
+#[derive(Debug)]
+#[must_use = "handlers are only called with `HandlerRequest::call`"]
+pub struct HandlerRequest<S = ()> {
+    state: S,
+    builder: http::request::Builder,
+    path_params: Vec<(String, String)>,
+    query: Option<String>,
+    body: Body,
+}
+
+impl HandlerRequest<()> {
+    /// Create a `GET` request without state, path parameters, headers, or body.
+    pub fn new() -> Self {
+        Self {
+            state: (),
+            builder: Request::builder(),
+            path_params: Vec::new(),
+            query: None,
+            body: Body::empty(),
+        }
+    }
+}
+
+impl Default for HandlerRequest<()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> HandlerRequest<S> {
+    /// Set the state extracted with [`State`](axum::extract::State).
+    pub fn state<S2>(self, state: S2) -> HandlerRequest<S2> {
+        HandlerRequest {
+            state,
+            builder: self.builder,
+            path_params: self.path_params,
+            query: self.query,
+            body: self.body,
+        }
+    }
+
+    /// Set the method of the request.
+    pub fn method(mut self, method: Method) -> Self {
+        self.builder = self.builder.method(method);
+        self
+    }
+
+    /// Add a path parameter extracted with [`Path`](axum::extract::Path).
+    ///
+    /// Parameters are extracted in the order they were added, which matters when extracting them
+    /// into a tuple.
+    pub fn path_param(mut self, name: &str, value: &str) -> Self {
+        self.path_params.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Set the query string extracted with `Query`, such as `page=2&per_page=10`.
+    pub fn query(mut self, query: &str) -> Self {
+        self.query = Some(query.to_owned());
+        self
+    }
+
+    /// Add a header to the request.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    /// Add an extension extracted with [`Extension`](axum::Extension).
+    pub fn extension<T>(mut self, extension: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.builder = self.builder.extension(extension);
+        self
+    }
+
+    /// Set the body of the request.
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Set the body of the request to `json` and the `Content-Type` to `application/json`.
+    pub fn json<T>(self, json: &T) -> Self
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        self.header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(json).expect("failed to serialize JSON body"))
+    }
+
+    /// Call `handler` with the request and return its response.
+    pub async fn call<H, T>(self, handler: H) -> Response
+    where
+        H: Handler<T, S>,
+        T: 'static,
+        S: Clone + Send + Sync + 'static,
+    {
+        let Self {
+            state,
+            builder,
+            path_params,
+            query,
+            body,
+        } = self;
+
+        // the handler is routed at a path that captures each parameter, so they are extracted
+        // the same way as in a real router
+        let mut path = String::new();
+        let mut uri = String::new();
+        for (name, value) in &path_params {
+            path.push_str("/:");
+            path.push_str(name);
+            uri.push('/');
+            uri.push_str(&percent_encode(value));
+        }
+        if path.is_empty() {
+            path.push('/');
+            uri.push('/');
+        }
+        if let Some(query) = query {
+            uri.push('?');
+            uri.push_str(&query);
+        }
+
+        let request = builder.uri(uri).body(body).expect("invalid request");
+        Router::new()
+            .route(&path, any(handler))
+            .with_state(state)
+            .oneshot(request)
+            .await
+            .unwrap_or_else(|err| match err {})
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::{Path, Query, State},
+        response::AppendHeaders,
+        routing::{get, post},
+        Extension,
+    };
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn json_and_headers() {
+        let app = Router::new().route(
+            "/",
+            post(|headers: HeaderMap, body: String| async move {
+                let content_type = headers[header::CONTENT_TYPE].to_str().unwrap().to_owned();
+                let custom = headers["x-custom"].to_str().unwrap().to_owned();
+                ([("x-echo", custom)], format!("{content_type} {body}"))
+            }),
+        );
+        let client = TestClient::new(app);
+
+        client
+            .post("/")
+            .header("x-custom", "value")
+            .json(&serde_json::json!({ "a": 1 }))
+            .await
+            .assert_status(StatusCode::OK)
+            .assert_header("x-echo", "value")
+            .assert_text(r#"application/json {"a":1}"#);
+
+        client
+            .get("/missing")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn cookies() {
+        let app = Router::new()
+            .route(
+                "/login",
+                post(|| async {
+                    AppendHeaders([
+                        (header::SET_COOKIE, "session=abc; Path=/; HttpOnly"),
+                        (header::SET_COOKIE, "theme=dark"),
+                    ])
+                }),
+            )
+            .route(
+                "/logout",
+                post(|| async { AppendHeaders([(header::SET_COOKIE, "session=; Max-Age=0")]) }),
+            )
+            .route(
+                "/",
+                get(|headers: HeaderMap| async move {
+                    headers
+                        .get(header::COOKIE)
+                        .map(|cookie| cookie.to_str().unwrap().to_owned())
+                        .unwrap_or_default()
+                }),
+            );
+        let client = TestClient::new(app);
+
+        client.get("/").await.assert_text("");
+
+        client.post("/login").await;
+        assert_eq!(client.cookie("session").as_deref(), Some("abc"));
+        client.get("/").await.assert_text("session=abc; theme=dark");
+
+        client.post("/logout").await;
+        assert_eq!(client.cookie("session"), None);
+        client.get("/").await.assert_text("theme=dark");
+    }
+
+    #[cfg(feature = "multipart")]
+    #[tokio::test]
+    async fn multipart() {
+        use crate::{
+            extract::Multipart,
+            multipart_builder::{MultipartForm, Part},
+        };
+
+        let app = Router::new().route(
+            "/",
+            post(|mut multipart: Multipart| async move {
+                let mut fields = Vec::new();
+                while let Some(field) = multipart.next_field().await.unwrap() {
+                    let name = field.name().unwrap().to_owned();
+                    let file_name = field.file_name().map(ToOwned::to_owned);
+                    let text = field.text().await.unwrap();
+                    fields.push(format!("{name}:{file_name:?}:{text}"));
+                }
+                fields.join(",")
+            }),
+        );
+        let client = TestClient::new(app);
+
+        let form = MultipartForm::with_parts(vec![
+            Part::text("name".to_owned(), "alice"),
+            Part::file("avatar", "avatar.txt", b"pixels".to_vec()),
+        ]);
+        client
+            .post("/")
+            .multipart(form)
+            .await
+            .assert_status(StatusCode::OK)
+            .assert_text(r#"name:None:alice,avatar:Some("avatar.txt"):pixels"#);
+    }
+
+    #[tokio::test]
+    async fn call_handler() {
+        async fn handler(
+            State(prefix): State<&'static str>,
+            Path((a, b)): Path<(String, u32)>,
+            Query(query): Query<HashMap<String, String>>,
+            Extension(user): Extension<String>,
+            headers: HeaderMap,
+            method: Method,
+            body: String,
+        ) -> String {
+            format!(
+                "{prefix} {method} {a} {b} {} {user} {} {body}",
+                query["page"],
+                headers["x-custom"].to_str().unwrap(),
+            )
+        }
+
+        let response = HandlerRequest::new()
+            .state("called")
+            .method(Method::PUT)
+            .path_param("a", "with space/slash")
+            .path_param("b", "2")
+            .query("page=3")
+            .extension("alice".to_owned())
+            .header("x-custom", "value")
+            .body("body")
+            .call(handler)
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "called PUT with space/slash 2 3 alice value body");
+
+        let response = HandlerRequest::new()
+            .path_param("id", "not a number")
+            .call(|Path(_): Path<u32>| async {})
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
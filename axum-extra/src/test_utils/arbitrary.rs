@@ -0,0 +1,310 @@
+use super::percent_encode;
+use axum::{body::Body, extract::Request};
+use http::{
+    header::{self, HeaderName, HeaderValue},
+    Method,
+};
+use proptest::{
+    collection::vec,
+    option,
+    prelude::{any, prop_oneof, Just, Strategy},
+    sample::select,
+    strategy::BoxedStrategy,
+};
+
+/// A request generated by [`arbitrary_request`].
+///
+/// Convert it with [`ArbitraryRequest::into_request`] or send it with
+/// [`TestClient::send`](super::TestClient::send).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitraryRequest {
+    method: Method,
+    uri: String,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Vec<u8>,
+}
+
+impl ArbitraryRequest {
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path and query of the request.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The headers of the request.
+    pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.headers
+    }
+
+    /// The body of the request.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Convert the generated request into a [`Request`].
+    pub fn into_request(self) -> Request {
+        let mut builder = Request::builder().method(self.method).uri(self.uri);
+        for (name, value) in self.headers {
+            builder = builder.header(name, value);
+        }
+        builder
+            .body(Body::from(self.body))
+            .expect("generated an invalid request")
+    }
+}
+
+/// A [proptest] strategy that generates requests to the given route paths.
+///
+/// Paths use the same syntax as [`Router::route`](axum::Router::route). Captures such as `:id`
+/// and wildcards such as `*rest` are filled with arbitrary, percent-encoded segments, and a query
+/// string is added to some requests.
+///
+/// The requests are valid HTTP, but their contents are meant to trip up extractors: methods
+/// include non-standard ones, query strings and percent-encodings can be malformed, headers
+/// such as `Content-Type` have wrong or garbled values, and bodies are arbitrary bytes, almost
+/// valid JSON, or forms. Use it to check that handlers reject such requests gracefully, for
+/// example that they never respond with a server error.
+///
+/// # Panics
+///
+/// Panics if `paths` is empty.
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, routing::post, extract::Path, Json};
+/// use axum_extra::test_utils::{arbitrary_request, TestClient};
+/// use proptest::prelude::*;
+///
+/// async fn update_user(Path(_id): Path<u32>, Json(_user): Json<serde_json::Value>) {}
+///
+/// proptest! {
+///     fn never_a_server_error(request in arbitrary_request(&["/users/:id"])) {
+///         let client = TestClient::new(Router::new().route("/users/:id", post(update_user)));
+///         let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+///
+///         let response = runtime.block_on(async { client.send(request.into_request()).await });
+///         prop_assert!(!response.status().is_server_error());
+///     }
+/// }
+/// # fn main() { never_a_server_error(); }
+/// ```
+///
+/// [proptest]: https://docs.rs/proptest
+pub fn arbitrary_request(paths: &[&str]) -> BoxedStrategy<ArbitraryRequest> {
+    assert!(!paths.is_empty(), "no paths to generate requests for");
+    let paths = paths
+        .iter()
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>();
+
+    let uri = select(paths)
+        .prop_flat_map(|path| {
+            let segments = path
+                .split('/')
+                .map(|segment| {
+                    if segment.starts_with(':') {
+                        vec(any_segment(), 1..=1).boxed()
+                    } else if segment.starts_with('*') {
+                        vec(any_segment(), 1..4).boxed()
+                    } else {
+                        Just(vec![segment.to_owned()]).boxed()
+                    }
+                })
+                .collect::<Vec<_>>();
+            (segments, option::of(any_query()))
+        })
+        .prop_map(|(segments, query)| {
+            let path = segments
+                .into_iter()
+                .map(|segment| segment.join("/"))
+                .collect::<Vec<_>>()
+                .join("/");
+            let path = if path.is_empty() {
+                "/".to_owned()
+            } else {
+                path
+            };
+            match query {
+                Some(query) => format!("{path}?{query}"),
+                None => path,
+            }
+        });
+
+    (uri, any_method(), vec(any_header(), 0..6), any_body())
+        .prop_map(|(uri, method, headers, body)| ArbitraryRequest {
+            method,
+            uri,
+            headers,
+            body,
+        })
+        .boxed()
+}
+
+fn any_method() -> impl Strategy<Value = Method> {
+    prop_oneof![
+        4 => select(vec![
+            Method::GET,
+            Method::HEAD,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+            Method::TRACE,
+        ]),
+        1 => "[A-Z]{1,10}".prop_map(|method| method.parse().expect("valid method")),
+    ]
+}
+
+fn any_segment() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z0-9_-]{1,12}",
+        "-?[0-9]{1,20}",
+        any::<String>().prop_map(|segment| percent_encode(&segment)),
+        // malformed percent-encodings
+        Just("%".to_owned()),
+        Just("%zz".to_owned()),
+        Just("%FF%FE".to_owned()),
+    ]
+}
+
+fn any_query() -> impl Strategy<Value = String> {
+    let pair = (
+        "[a-z_]{1,8}",
+        prop_oneof![
+            "[a-zA-Z0-9]{0,12}",
+            Just("%zz".to_owned()),
+            Just("a=b=c".to_owned())
+        ],
+    )
+        .prop_map(|(key, value)| format!("{key}={value}"));
+    prop_oneof![
+        vec(pair, 0..5).prop_map(|pairs| pairs.join("&")),
+        "[a-zA-Z0-9&=%]{0,20}",
+    ]
+}
+
+fn any_header() -> impl Strategy<Value = (HeaderName, HeaderValue)> {
+    let content_type = select(vec![
+        "application/json",
+        "application/json; charset=utf-8",
+        "application/problem+json",
+        "application/x-www-form-urlencoded",
+        "multipart/form-data",
+        "multipart/form-data; boundary=boundary",
+        "text/plain",
+        "application/octet-stream",
+        "application/json; charset=",
+        "json",
+        "",
+    ])
+    .prop_map(|value| (header::CONTENT_TYPE, HeaderValue::from_static(value)));
+
+    let known = (
+        select(vec![
+            header::ACCEPT,
+            header::ACCEPT_ENCODING,
+            header::AUTHORIZATION,
+            header::CONTENT_ENCODING,
+            header::CONTENT_LENGTH,
+            header::COOKIE,
+            header::HOST,
+            header::IF_NONE_MATCH,
+            header::RANGE,
+            header::USER_AGENT,
+        ]),
+        any_header_value(),
+    );
+
+    let custom = ("x-[a-z]{1,10}", any_header_value()).prop_map(|(name, value)| {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("valid header name");
+        (name, value)
+    });
+
+    prop_oneof![2 => content_type, 2 => known, 1 => custom]
+}
+
+fn any_header_value() -> impl Strategy<Value = HeaderValue> {
+    // visible ASCII, spaces, tabs, and opaque bytes are allowed in header values
+    vec(
+        prop_oneof![8 => 0x20_u8..0x7f, 1 => Just(b'\t'), 1 => 0x80_u8..=0xff],
+        0..40,
+    )
+    .prop_map(|bytes| HeaderValue::from_bytes(&bytes).expect("valid header value"))
+}
+
+fn any_body() -> impl Strategy<Value = Vec<u8>> {
+    let json = any_json().prop_map(|json| json.to_string().into_bytes());
+    let truncated_json = (any_json(), any::<usize>()).prop_map(|(json, cut)| {
+        let json = json.to_string().into_bytes();
+        let cut = cut % json.len().max(1);
+        json[..cut].to_vec()
+    });
+    let form = vec(("[a-z]{1,8}", "[a-zA-Z0-9%+]{0,12}"), 0..5).prop_map(|pairs| {
+        pairs
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("&")
+            .into_bytes()
+    });
+
+    prop_oneof![
+        Just(Vec::new()),
+        vec(any::<u8>(), 0..256),
+        json,
+        truncated_json,
+        form,
+    ]
+}
+
+fn any_json() -> impl Strategy<Value = serde_json::Value> {
+    use serde_json::Value;
+
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        any::<String>().prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 24, 6, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..6).prop_map(Value::Array),
+            vec(("[a-z_]{1,8}", inner), 0..6)
+                .prop_map(|fields| Value::Object(fields.into_iter().collect())),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn generates_requests_for_paths(
+            request in arbitrary_request(&["/users/:id/posts", "/files/*path"]),
+        ) {
+            let uri = request.uri().to_owned();
+            let path = uri.split('?').next().unwrap();
+            prop_assert!(
+                path.starts_with("/users/") && path.ends_with("/posts")
+                    || path.starts_with("/files/"),
+                "{}",
+                path,
+            );
+
+            let method = request.method().clone();
+            let request = request.into_request();
+            prop_assert_eq!(request.uri().to_string(), uri);
+            prop_assert_eq!(request.method(), &method);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use axum::body::Body;
+use http_body_util::BodyExt;
+use std::time::Duration;
+
+/// A server-sent event parsed from a `text/event-stream` body.
+///
+/// Returned by [`parse_events`], [`TestResponse::events`](super::TestResponse::events), and
+/// [`RequestBuilder::events`](super::RequestBuilder::events).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl ParsedEvent {
+    /// The `id` field of the event.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// The `event` field of the event.
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    /// The `data` fields of the event, joined with newlines.
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+
+    /// The `retry` field of the event.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Deserialize the data of the event from JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event has no data or it can't be deserialized into `T`.
+    #[track_caller]
+    pub fn json<T>(&self) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let data = self.data.as_deref().expect("event has no data");
+        serde_json::from_str(data)
+            .unwrap_or_else(|err| panic!("failed to deserialize event data: {err}\ndata: {data}"))
+    }
+}
+
+/// Parse a `text/event-stream` body into its events.
+///
+/// Comments are skipped. Unlike browsers, events that only set `id` or `retry` are returned too,
+/// so they can be asserted on. An event that isn't terminated by a blank line is ignored.
+///
+/// # Example
+///
+/// ```
+/// use axum_extra::test_utils::parse_events;
+///
+/// let events = parse_events("event: greeting\ndata: hello\ndata: world\n\n: comment\n\n");
+///
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].event(), Some("greeting"));
+/// assert_eq!(events[0].data(), Some("hello\nworld"));
+/// ```
+pub fn parse_events(text: &str) -> Vec<ParsedEvent> {
+    let mut parser = EventParser::default();
+    parser.feed(text.as_bytes());
+    parser.events
+}
+
+/// Read events from `body` until `count` events have been parsed or the body has ended.
+pub(super) async fn collect_events(mut body: Body, count: usize) -> Vec<ParsedEvent> {
+    let mut parser = EventParser::default();
+    while parser.events.len() < count {
+        let Some(frame) = body.frame().await else {
+            break;
+        };
+        let frame = frame.expect("failed to read response body");
+        if let Some(data) = frame.data_ref() {
+            parser.feed(data);
+        }
+    }
+    parser.events.truncate(count);
+    parser.events
+}
+
+#[derive(Default)]
+struct EventParser {
+    buf: Vec<u8>,
+    current: Option<ParsedEvent>,
+    events: Vec<ParsedEvent>,
+}
+
+impl EventParser {
+    fn feed(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+
+        // lines can be split across chunks, so only complete lines are parsed
+        while let Some(end) = self.buf.iter().position(|&byte| byte == b'\n') {
+            let line = self.buf.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line[..end]);
+            self.line(line.strip_suffix('\r').unwrap_or(&line));
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        if line.is_empty() {
+            if let Some(event) = self.current.take() {
+                self.events.push(event);
+            }
+            return;
+        }
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value).to_owned();
+        let event = self.current.get_or_insert_with(Default::default);
+        match field {
+            "id" => event.id = Some(value),
+            "event" => event.event = Some(value),
+            "data" => match &mut event.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(&value);
+                }
+                None => event.data = Some(value),
+            },
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    event.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let events = parse_events(
+            "id: 1\r\nevent: update\r\ndata: {\"a\":1}\r\n\r\n\
+             :keep-alive\n\n\
+             retry: 3000\n\n\
+             data:no space\ndata\n\n\
+             data: unterminated\n",
+        );
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].id(), Some("1"));
+        assert_eq!(events[0].event(), Some("update"));
+        assert_eq!(
+            events[0].json::<serde_json::Value>(),
+            serde_json::json!({ "a": 1 })
+        );
+        assert_eq!(events[1].retry(), Some(Duration::from_secs(3)));
+        assert_eq!(events[1].data(), None);
+        assert_eq!(events[2].data(), Some("no space\n"));
+    }
+
+    #[tokio::test]
+    async fn collect_from_chunks() {
+        let chunks = ["da", "ta: one\n", "\ndata: two\n\ndata: three\n\n"];
+        let body = Body::from_stream(futures_util::stream::iter(
+            chunks.map(Ok::<_, std::convert::Infallible>),
+        ));
+
+        let events = collect_events(body, 2).await;
+        let data = events.iter().map(|event| event.data()).collect::<Vec<_>>();
+        assert_eq!(data, [Some("one"), Some("two")]);
+    }
+}
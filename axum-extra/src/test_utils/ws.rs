@@ -0,0 +1,292 @@
+use axum::extract::ws::{CloseFrame, Message};
+use futures_util::{SinkExt, StreamExt};
+use hyper_util::{rt::TokioIo, service::TowerToHyperService};
+use std::fmt;
+use tokio::io::DuplexStream;
+use tokio_tungstenite::{
+    tungstenite::{self as ts, client::IntoClientRequest, protocol::frame::coding::CloseCode},
+    WebSocketStream,
+};
+
+use super::RequestBuilder;
+
+/// The client side of a WebSocket connected to a [`Router`](axum::Router), returned by
+/// [`RequestBuilder::websocket`].
+///
+/// The connection runs over an in-memory pipe, so no port is bound.
+pub struct TestWebSocket {
+    inner: WebSocketStream<DuplexStream>,
+}
+
+impl fmt::Debug for TestWebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestWebSocket").finish_non_exhaustive()
+    }
+}
+
+impl TestWebSocket {
+    /// Send a message to the server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the connection is closed.
+    pub async fn send(&mut self, message: Message) {
+        self.inner
+            .send(into_tungstenite(message))
+            .await
+            .expect("failed to send WebSocket message");
+    }
+
+    /// Send a text message to the server.
+    pub async fn send_text(&mut self, text: impl Into<String>) {
+        self.send(Message::Text(text.into())).await;
+    }
+
+    /// Send a text message with `json` serialized as JSON to the server.
+    pub async fn send_json<T>(&mut self, json: &T)
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let text = serde_json::to_string(json).expect("failed to serialize JSON message");
+        self.send_text(text).await;
+    }
+
+    /// Receive the next message from the server.
+    ///
+    /// Returns `None` once the connection is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the connection fails.
+    pub async fn recv(&mut self) -> Option<Message> {
+        loop {
+            let message = self
+                .inner
+                .next()
+                .await?
+                .expect("failed to receive WebSocket message");
+            if let Some(message) = from_tungstenite(message) {
+                return Some(message);
+            }
+        }
+    }
+
+    /// Receive the next message from the server, which must be a text message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the next message isn't a text message.
+    pub async fn recv_text(&mut self) -> String {
+        match self.recv().await {
+            Some(Message::Text(text)) => text,
+            message => panic!("expected a text message, got {message:?}"),
+        }
+    }
+
+    /// Receive the next message from the server and deserialize it from JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the next message isn't a text or binary message, or can't be deserialized into
+    /// `T`.
+    pub async fn recv_json<T>(&mut self) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let data = match self.recv().await {
+            Some(Message::Text(text)) => text.into_bytes(),
+            Some(Message::Binary(data)) => data,
+            message => panic!("expected a text or binary message, got {message:?}"),
+        };
+        serde_json::from_slice(&data).unwrap_or_else(|err| {
+            panic!(
+                "failed to deserialize WebSocket message: {err}\nmessage: {}",
+                String::from_utf8_lossy(&data)
+            )
+        })
+    }
+
+    /// Close the connection with an optional close frame and wait for the server to close it
+    /// too.
+    pub async fn close(mut self, frame: Option<CloseFrame<'static>>) {
+        let frame = frame.map(|frame| ts::protocol::CloseFrame {
+            code: CloseCode::from(frame.code),
+            reason: frame.reason,
+        });
+        // the server might have closed the connection already
+        let _ = self.inner.close(frame).await;
+        while let Some(Ok(_)) = self.inner.next().await {}
+    }
+}
+
+impl RequestBuilder {
+    /// Send the request as a WebSocket handshake and return the client side of the connection.
+    ///
+    /// The handshake headers are added to the request, and the request and the connection are
+    /// served by the router over an in-memory pipe.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handshake fails, for example because the route doesn't exist or didn't
+    /// accept the upgrade.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     Router,
+    ///     routing::get,
+    ///     extract::ws::{WebSocket, WebSocketUpgrade},
+    ///     response::Response,
+    /// };
+    /// use axum_extra::test_utils::TestClient;
+    ///
+    /// async fn echo(ws: WebSocketUpgrade) -> Response {
+    ///     ws.on_upgrade(|mut socket: WebSocket| async move {
+    ///         while let Some(Ok(message)) = socket.recv().await {
+    ///             if socket.send(message).await.is_err() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     })
+    /// }
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let client = TestClient::new(Router::new().route("/echo", get(echo)));
+    ///
+    /// let mut socket = client.get("/echo").websocket().await;
+    /// socket.send_text("hello").await;
+    /// assert_eq!(socket.recv_text().await, "hello");
+    /// socket.close(None).await;
+    /// # }
+    /// ```
+    pub async fn websocket(self) -> TestWebSocket {
+        let Self {
+            client,
+            builder,
+            body: _,
+        } = self;
+
+        let mut request = builder.body(()).expect("invalid request");
+        client.add_cookies(request.headers_mut());
+
+        // tungstenite requires an absolute URI but only sends the path
+        let path_and_query = request
+            .uri()
+            .path_and_query()
+            .map_or("/", |path_and_query| path_and_query.as_str());
+        let mut handshake = format!("ws://localhost{path_and_query}")
+            .into_client_request()
+            .expect("invalid WebSocket request");
+        for (name, value) in request.headers() {
+            handshake.headers_mut().append(name, value.clone());
+        }
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let router = client.router.clone();
+        tokio::spawn(async move {
+            let _ = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(server_io), TowerToHyperService::new(router))
+                .with_upgrades()
+                .await;
+        });
+
+        match tokio_tungstenite::client_async(handshake, client_io).await {
+            Ok((inner, response)) => {
+                client.store_cookies(response.headers());
+                TestWebSocket { inner }
+            }
+            Err(ts::Error::Http(response)) => panic!(
+                "WebSocket handshake failed with status {}\nbody: {}",
+                response.status(),
+                String::from_utf8_lossy(response.body().as_deref().unwrap_or_default())
+            ),
+            Err(err) => panic!("WebSocket handshake failed: {err}"),
+        }
+    }
+}
+
+fn into_tungstenite(message: Message) -> ts::Message {
+    match message {
+        Message::Text(text) => ts::Message::Text(text),
+        Message::Binary(binary) => ts::Message::Binary(binary),
+        Message::Ping(ping) => ts::Message::Ping(ping),
+        Message::Pong(pong) => ts::Message::Pong(pong),
+        Message::Close(Some(close)) => ts::Message::Close(Some(ts::protocol::CloseFrame {
+            code: CloseCode::from(close.code),
+            reason: close.reason,
+        })),
+        Message::Close(None) => ts::Message::Close(None),
+    }
+}
+
+fn from_tungstenite(message: ts::Message) -> Option<Message> {
+    match message {
+        ts::Message::Text(text) => Some(Message::Text(text)),
+        ts::Message::Binary(binary) => Some(Message::Binary(binary)),
+        ts::Message::Ping(ping) => Some(Message::Ping(ping)),
+        ts::Message::Pong(pong) => Some(Message::Pong(pong)),
+        ts::Message::Close(Some(close)) => Some(Message::Close(Some(CloseFrame {
+            code: close.code.into(),
+            reason: close.reason,
+        }))),
+        ts::Message::Close(None) => Some(Message::Close(None)),
+        // raw frames are never returned when reading
+        ts::Message::Frame(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TestClient;
+    use super::*;
+    use axum::{
+        extract::ws::{WebSocket, WebSocketUpgrade},
+        http::HeaderMap,
+        response::Response,
+        routing::get,
+        Router,
+    };
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn echo() {
+        async fn handler(ws: WebSocketUpgrade, headers: HeaderMap) -> Response {
+            let token = headers["x-token"].to_str().unwrap().to_owned();
+            ws.on_upgrade(|mut socket: WebSocket| async move {
+                socket.send(Message::Text(token)).await.unwrap();
+                while let Some(Ok(message)) = socket.recv().await {
+                    if let Message::Close(_) = message {
+                        break;
+                    }
+                    socket.send(message).await.unwrap();
+                }
+            })
+        }
+
+        let client = TestClient::new(Router::new().route("/ws", get(handler)));
+        let mut socket = client
+            .get("/ws?room=1")
+            .header("x-token", "secret")
+            .websocket()
+            .await;
+
+        assert_eq!(socket.recv_text().await, "secret");
+
+        socket.send_json(&json!({ "a": 1 })).await;
+        assert_eq!(socket.recv_json::<Value>().await, json!({ "a": 1 }));
+
+        socket.send(Message::Binary(vec![1, 2, 3])).await;
+        assert_eq!(socket.recv().await, Some(Message::Binary(vec![1, 2, 3])));
+
+        socket.close(None).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "WebSocket handshake failed with status 404 Not Found")]
+    async fn missing_route() {
+        let client = TestClient::new(Router::new());
+        client.get("/ws").websocket().await;
+    }
+}
@@ -6,7 +6,7 @@ use axum::{
     response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
 };
 use headers::{Header, HeaderMapExt};
-use http::request::Parts;
+use http::{request::Parts, HeaderMap};
 use std::convert::Infallible;
 
 /// Extractor and response that works with typed header values from [`headers`].
@@ -64,24 +64,113 @@ where
     type Rejection = TypedHeaderRejection;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let mut values = parts.headers.get_all(T::name()).iter();
-        let is_missing = values.size_hint() == (0, Some(0));
-        T::decode(&mut values)
-            .map(Self)
-            .map_err(|err| TypedHeaderRejection {
-                name: T::name(),
-                reason: if is_missing {
-                    // Report a more precise rejection for the missing header case.
-                    TypedHeaderRejectionReason::Missing
-                } else {
-                    TypedHeaderRejectionReason::Error(err)
-                },
-            })
+        decode(&parts.headers).map(Self)
     }
 }
 
+pub(crate) fn decode<T>(headers: &HeaderMap) -> Result<T, TypedHeaderRejection>
+where
+    T: Header,
+{
+    let mut values = headers.get_all(T::name()).iter();
+    let is_missing = values.size_hint() == (0, Some(0));
+    T::decode(&mut values).map_err(|err| TypedHeaderRejection {
+        name: T::name(),
+        reason: if is_missing {
+            // Report a more precise rejection for the missing header case.
+            TypedHeaderRejectionReason::Missing
+        } else {
+            TypedHeaderRejectionReason::Error(err)
+        },
+    })
+}
+
 axum_core::__impl_deref!(TypedHeader);
 
+/// Extractor for several typed headers at once.
+///
+/// `T` is a tuple of [`Header`]s. Unlike using a separate [`TypedHeader`] for each header, all
+/// headers are decoded before rejecting, so the [`TypedHeadersRejection`] reports every missing
+/// or invalid header rather than only the first one.
+///
+/// ```rust,no_run
+/// use axum::{
+///     routing::get,
+///     Router,
+/// };
+/// use headers::{IfNoneMatch, Range, UserAgent};
+/// use axum_extra::typed_header::TypedHeaders;
+///
+/// async fn download(
+///     TypedHeaders((user_agent, if_none_match, range)): TypedHeaders<(
+///         UserAgent,
+///         IfNoneMatch,
+///         Range,
+///     )>,
+/// ) {
+///     // ...
+/// }
+///
+/// let app = Router::new().route("/download", get(download));
+/// # let _: Router = app;
+/// ```
+///
+/// Use [`TypedHeader`] with `Option` for headers that aren't required.
+#[cfg(feature = "typed-header")]
+#[derive(Debug, Clone, Copy)]
+pub struct TypedHeaders<T>(pub T);
+
+macro_rules! impl_from_request_parts_for_typed_headers {
+    ( $($ty:ident),* $(,)? ) => {
+        #[async_trait]
+        #[allow(non_snake_case)]
+        impl<S, $($ty,)*> FromRequestParts<S> for TypedHeaders<($($ty,)*)>
+        where
+            $( $ty: Header, )*
+            S: Send + Sync,
+        {
+            type Rejection = TypedHeadersRejection;
+
+            async fn from_request_parts(
+                parts: &mut Parts,
+                _state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                let mut rejections = Vec::new();
+
+                $(
+                    let $ty = match decode::<$ty>(&parts.headers) {
+                        Ok(value) => Some(value),
+                        Err(rejection) => {
+                            rejections.push(rejection);
+                            None
+                        }
+                    };
+                )*
+
+                match ($($ty,)*) {
+                    ($(Some($ty),)*) => Ok(Self(($($ty,)*))),
+                    _ => Err(TypedHeadersRejection { rejections }),
+                }
+            }
+        }
+    };
+}
+
+impl_from_request_parts_for_typed_headers!(T1);
+impl_from_request_parts_for_typed_headers!(T1, T2);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6, T7);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_from_request_parts_for_typed_headers!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+
+axum_core::__impl_deref!(TypedHeaders);
+
 impl<T> IntoResponseParts for TypedHeader<T>
 where
     T: Header,
@@ -182,6 +271,47 @@ impl std::error::Error for TypedHeaderRejection {
     }
 }
 
+/// Rejection used for [`TypedHeaders`].
+///
+/// Contains a [`TypedHeaderRejection`] for every header that was missing or invalid.
+#[cfg(feature = "typed-header")]
+#[derive(Debug)]
+pub struct TypedHeadersRejection {
+    rejections: Vec<TypedHeaderRejection>,
+}
+
+impl TypedHeadersRejection {
+    /// The rejections for each header that failed to decode, in the order of the tuple.
+    pub fn rejections(&self) -> &[TypedHeaderRejection] {
+        &self.rejections
+    }
+
+    /// Consume `self` and return the rejections for each header that failed to decode.
+    pub fn into_rejections(self) -> Vec<TypedHeaderRejection> {
+        self.rejections
+    }
+}
+
+impl IntoResponse for TypedHeadersRejection {
+    fn into_response(self) -> Response {
+        (http::StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+impl std::fmt::Display for TypedHeadersRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, rejection) in self.rejections.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{rejection}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TypedHeadersRejection {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +353,34 @@ mod tests {
         let body = res.text().await;
         assert_eq!(body, "Header of type `user-agent` was missing");
     }
+
+    #[tokio::test]
+    async fn typed_headers() {
+        async fn handle(
+            TypedHeaders((user_agent, content_type)): TypedHeaders<(
+                headers::UserAgent,
+                headers::ContentType,
+            )>,
+        ) -> String {
+            format!("{} {}", user_agent.as_str(), content_type)
+        }
+
+        let app = Router::new().route("/", get(handle));
+
+        let client = TestClient::new(app);
+
+        let res = client
+            .get("/")
+            .header("user-agent", "foobar")
+            .header("content-type", "text/plain")
+            .await;
+        assert_eq!(res.text().await, "foobar text/plain");
+
+        let res = client.get("/").header("content-type", "nope").await;
+        assert_eq!(res.status(), http::StatusCode::BAD_REQUEST);
+        assert_eq!(
+            res.text().await,
+            "Header of type `user-agent` was missing, invalid HTTP header (content-type)"
+        );
+    }
 }
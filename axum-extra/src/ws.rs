@@ -0,0 +1,335 @@
+//! Utilities for [WebSockets](axum::extract::ws).
+//!
+//! [`TypedWebSocket`] sends and receives values of serde types, encoded with a [`Codec`] such as
+//! [`JsonCodec`], instead of raw [`Message`]s. MessagePack is supported with `MessagePackCodec`,
+//! which requires the `ws-msgpack` feature.
+//!
+//! [`Hub`] keeps track of connected WebSockets and the rooms they have joined, and broadcasts
+//! messages to them. It requires the `ws-hub` feature.
+//!
+//! [`Resumption`] lets clients resume a WebSocket session after reconnecting, with a signed token.
+//! It requires the `ws-resume` feature.
+//!
+//! [`SendQueue`] sends messages through a bounded queue, with an [`OverflowPolicy`] for slow
+//! clients. It requires the `ws-send-queue` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use axum::{
+//!     extract::ws::WebSocketUpgrade,
+//!     response::Response,
+//!     routing::get,
+//!     Router,
+//! };
+//! use axum_extra::ws::{TypedWebSocket, TypedWebSocketError};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize)]
+//! #[serde(tag = "type")]
+//! enum ClientMessage {
+//!     Subscribe { topic: String },
+//!     Unsubscribe { topic: String },
+//! }
+//!
+//! #[derive(Serialize)]
+//! #[serde(tag = "type")]
+//! enum ServerMessage {
+//!     Subscribed { topic: String },
+//!     Error { message: String },
+//! }
+//!
+//! async fn handler(ws: WebSocketUpgrade) -> Response {
+//!     ws.on_upgrade(|socket| async move {
+//!         let mut socket = TypedWebSocket::<ServerMessage, ClientMessage>::new(socket);
+//!
+//!         while let Some(msg) = socket.recv().await {
+//!             let reply = match msg {
+//!                 Ok(ClientMessage::Subscribe { topic }) => ServerMessage::Subscribed { topic },
+//!                 Ok(ClientMessage::Unsubscribe { .. }) => continue,
+//!                 // the client sent something that isn't a `ClientMessage`
+//!                 Err(TypedWebSocketError::Decode(err)) => ServerMessage::Error {
+//!                     message: err.to_string(),
+//!                 },
+//!                 // the client disconnected
+//!                 Err(_) => return,
+//!             };
+//!             if socket.send(&reply).await.is_err() {
+//!                 return;
+//!             }
+//!         }
+//!     })
+//! }
+//!
+//! let app = Router::new().route("/ws", get(handler));
+//! # let _: Router = app;
+//! ```
+
+use axum::extract::ws::{Message, WebSocket};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt, marker::PhantomData};
+
+#[cfg(feature = "ws-hub")]
+mod hub;
+
+#[cfg(feature = "ws-hub")]
+pub use self::hub::{ConnectionId, Hub, HubConnection, LagPolicy};
+
+#[cfg(feature = "ws-resume")]
+mod resume;
+
+#[cfg(feature = "ws-resume")]
+pub use self::resume::{MemorySessionStore, Resumption, SessionId, SessionStore};
+
+#[cfg(feature = "ws-send-queue")]
+mod send_queue;
+
+#[cfg(feature = "ws-send-queue")]
+pub use self::send_queue::{OverflowPolicy, SendQueue, SendQueueError};
+
+/// A [`WebSocket`] that sends values of type `Tx` and receives values of type `Rx`, encoded with
+/// the codec `C`.
+///
+/// See the [module docs](self) for an example.
+pub struct TypedWebSocket<Tx, Rx, C = JsonCodec> {
+    socket: WebSocket,
+    codec: C,
+    _marker: PhantomData<fn(Tx) -> Rx>,
+}
+
+impl<Tx, Rx> TypedWebSocket<Tx, Rx> {
+    /// Wrap `socket`, encoding messages as JSON.
+    pub fn new(socket: WebSocket) -> Self {
+        Self::with_codec(socket, JsonCodec)
+    }
+}
+
+impl<Tx, Rx, C> TypedWebSocket<Tx, Rx, C> {
+    /// Wrap `socket`, encoding messages with `codec`.
+    pub fn with_codec(socket: WebSocket, codec: C) -> Self {
+        Self {
+            socket,
+            codec,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get a reference to the underlying socket.
+    pub fn get_ref(&self) -> &WebSocket {
+        &self.socket
+    }
+
+    /// Get a mutable reference to the underlying socket.
+    ///
+    /// This can be used to send and receive messages that aren't encoded with the codec.
+    pub fn get_mut(&mut self) -> &mut WebSocket {
+        &mut self.socket
+    }
+
+    /// Consume the `TypedWebSocket`, returning the underlying socket.
+    pub fn into_inner(self) -> WebSocket {
+        self.socket
+    }
+}
+
+impl<Tx, Rx, C> TypedWebSocket<Tx, Rx, C>
+where
+    Tx: Serialize,
+    Rx: DeserializeOwned,
+    C: Codec,
+{
+    /// Encode and send a message.
+    pub async fn send(&mut self, msg: &Tx) -> Result<(), TypedWebSocketError> {
+        let msg = self
+            .codec
+            .encode(msg)
+            .map_err(TypedWebSocketError::Encode)?;
+        self.socket
+            .send(msg)
+            .await
+            .map_err(TypedWebSocketError::Socket)
+    }
+
+    /// Receive and decode another message.
+    ///
+    /// Pings and pongs are skipped. Returns `None` once the client has closed the connection.
+    ///
+    /// If a message can't be decoded, [`TypedWebSocketError::Decode`] is returned, and the
+    /// socket can still be used to receive the next message.
+    pub async fn recv(&mut self) -> Option<Result<Rx, TypedWebSocketError>> {
+        loop {
+            let msg = match self.socket.recv().await? {
+                Ok(msg) => msg,
+                Err(err) => return Some(Err(TypedWebSocketError::Socket(err))),
+            };
+            match msg {
+                Message::Text(_) | Message::Binary(_) => {
+                    return Some(self.codec.decode(msg).map_err(TypedWebSocketError::Decode));
+                }
+                Message::Ping(_) | Message::Pong(_) => {}
+                Message::Close(_) => return None,
+            }
+        }
+    }
+
+    /// Gracefully close the connection.
+    pub async fn close(self) -> Result<(), TypedWebSocketError> {
+        self.socket
+            .close()
+            .await
+            .map_err(TypedWebSocketError::Socket)
+    }
+}
+
+impl<Tx, Rx, C> fmt::Debug for TypedWebSocket<Tx, Rx, C>
+where
+    C: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedWebSocket")
+            .field("socket", &self.socket)
+            .field("codec", &self.codec)
+            .finish()
+    }
+}
+
+/// Encodes and decodes the messages of a [`TypedWebSocket`].
+pub trait Codec {
+    /// Encode `value` into a message.
+    fn encode<T>(&self, value: &T) -> Result<Message, axum::Error>
+    where
+        T: Serialize;
+
+    /// Decode a text or binary message.
+    fn decode<T>(&self, msg: Message) -> Result<T, axum::Error>
+    where
+        T: DeserializeOwned;
+}
+
+/// [`Codec`] that encodes messages as JSON, in text messages.
+///
+/// Both text and binary messages are decoded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode<T>(&self, value: &T) -> Result<Message, axum::Error>
+    where
+        T: Serialize,
+    {
+        serde_json::to_string(value)
+            .map(Message::Text)
+            .map_err(axum::Error::new)
+    }
+
+    fn decode<T>(&self, msg: Message) -> Result<T, axum::Error>
+    where
+        T: DeserializeOwned,
+    {
+        match msg {
+            Message::Text(text) => serde_json::from_str(&text).map_err(axum::Error::new),
+            Message::Binary(bytes) => serde_json::from_slice(&bytes).map_err(axum::Error::new),
+            _ => Err(axum::Error::new("expected a text or binary message")),
+        }
+    }
+}
+
+/// [`Codec`] that encodes messages as [MessagePack], in binary messages.
+///
+/// Structs are encoded as maps with field names, so they can be decoded by other
+/// implementations.
+///
+/// [MessagePack]: https://msgpack.org
+#[cfg(feature = "ws-msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackCodec;
+
+#[cfg(feature = "ws-msgpack")]
+impl Codec for MessagePackCodec {
+    fn encode<T>(&self, value: &T) -> Result<Message, axum::Error>
+    where
+        T: Serialize,
+    {
+        rmp_serde::to_vec_named(value)
+            .map(Message::Binary)
+            .map_err(axum::Error::new)
+    }
+
+    fn decode<T>(&self, msg: Message) -> Result<T, axum::Error>
+    where
+        T: DeserializeOwned,
+    {
+        match msg {
+            Message::Binary(bytes) => rmp_serde::from_slice(&bytes).map_err(axum::Error::new),
+            _ => Err(axum::Error::new("expected a binary message")),
+        }
+    }
+}
+
+/// Error returned by [`TypedWebSocket`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TypedWebSocketError {
+    /// Sending or receiving a message failed.
+    Socket(axum::Error),
+    /// Encoding a message failed.
+    Encode(axum::Error),
+    /// Decoding a received message failed.
+    Decode(axum::Error),
+}
+
+impl fmt::Display for TypedWebSocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Socket(err) => write!(f, "WebSocket error: {err}"),
+            Self::Encode(err) => write!(f, "Failed to encode message: {err}"),
+            Self::Decode(err) => write!(f, "Failed to decode message: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TypedWebSocketError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Socket(err) | Self::Encode(err) | Self::Decode(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Ping {
+        id: u32,
+    }
+
+    #[test]
+    fn json() {
+        let msg = JsonCodec.encode(&Ping { id: 1 }).unwrap();
+        assert_eq!(msg, Message::Text(r#"{"id":1}"#.to_owned()));
+        assert_eq!(JsonCodec.decode::<Ping>(msg).unwrap(), Ping { id: 1 });
+
+        let binary = Message::Binary(br#"{"id":2}"#.to_vec());
+        assert_eq!(JsonCodec.decode::<Ping>(binary).unwrap(), Ping { id: 2 });
+
+        let invalid = Message::Text(r#"{"id":"x"}"#.to_owned());
+        assert!(JsonCodec.decode::<Ping>(invalid).is_err());
+    }
+
+    #[cfg(feature = "ws-msgpack")]
+    #[test]
+    fn msgpack() {
+        let msg = MessagePackCodec.encode(&Ping { id: 1 }).unwrap();
+        assert!(matches!(msg, Message::Binary(_)));
+        assert_eq!(
+            MessagePackCodec.decode::<Ping>(msg).unwrap(),
+            Ping { id: 1 }
+        );
+
+        let text = Message::Text("{}".to_owned());
+        assert!(MessagePackCodec.decode::<Ping>(text).is_err());
+    }
+}
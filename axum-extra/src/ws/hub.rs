@@ -0,0 +1,625 @@
+use axum::extract::ws::Message;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Keeps track of connected WebSockets and the rooms they have joined, and sends messages to
+/// them.
+///
+/// Each connection gets a [`HubConnection`] from [`connect`](Self::connect), which receives the
+/// messages sent to it and must be forwarded to the socket. Connections can join any number of
+/// named rooms, and have metadata of type `M`, such as the name of the user, that can be read and
+/// updated through the hub.
+///
+/// Messages are queued for each connection, up to the [`capacity`](Self::capacity). What happens
+/// when the queue of a slow client is full is set with [`on_lag`](Self::on_lag).
+///
+/// `Hub` is cheap to clone, and clones share the same connections and rooms, so it can be used as
+/// state.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     extract::{
+///         ws::{Message, WebSocket, WebSocketUpgrade},
+///         State,
+///     },
+///     response::Response,
+///     routing::get,
+///     Router,
+/// };
+/// use axum_extra::ws::Hub;
+/// use futures_util::{SinkExt, StreamExt};
+///
+/// async fn handler(ws: WebSocketUpgrade, State(hub): State<Hub<String>>) -> Response {
+///     ws.on_upgrade(|socket| chat(socket, hub))
+/// }
+///
+/// async fn chat(socket: WebSocket, hub: Hub<String>) {
+///     let (mut sink, mut stream) = socket.split();
+///     let mut conn = hub.connect("anonymous".to_owned());
+///     let id = conn.id();
+///     conn.join("lobby");
+///
+///     // forward the messages sent to this connection to the client
+///     let forward = tokio::spawn(async move {
+///         while let Some(msg) = conn.recv().await {
+///             if sink.send(msg).await.is_err() {
+///                 break;
+///             }
+///         }
+///     });
+///
+///     while let Some(Ok(Message::Text(text))) = stream.next().await {
+///         if let Some(name) = text.strip_prefix("/name ") {
+///             hub.update_metadata(id, |current| *current = name.to_owned());
+///         } else {
+///             let name = hub.metadata(id).unwrap_or_default();
+///             hub.broadcast("lobby", Message::Text(format!("{name}: {text}")));
+///         }
+///     }
+///
+///     // dropping the `HubConnection` removes it from the hub
+///     forward.abort();
+/// }
+///
+/// let app = Router::new()
+///     .route("/chat", get(handler))
+///     .with_state(Hub::new());
+/// # let _: Router = app;
+/// ```
+pub struct Hub<M = ()> {
+    state: Arc<Mutex<State<M>>>,
+}
+
+struct State<M> {
+    next_id: u64,
+    capacity: usize,
+    on_lag: LagPolicy,
+    connections: HashMap<ConnectionId, Connection<M>>,
+    rooms: HashMap<String, HashSet<ConnectionId>>,
+}
+
+struct Connection<M> {
+    metadata: M,
+    rooms: HashSet<String>,
+    sender: mpsc::Sender<Message>,
+    suspended: Option<Suspended>,
+}
+
+/// A connection kept after its socket closed, with [`HubConnection::suspend`].
+struct Suspended {
+    receiver: mpsc::Receiver<Message>,
+    until: Instant,
+}
+
+impl<M> Hub<M> {
+    /// Create a new empty `Hub`.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                next_id: 0,
+                capacity: 32,
+                on_lag: LagPolicy::default(),
+                connections: HashMap::new(),
+                rooms: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Set how many messages can be queued for each connection.
+    ///
+    /// Only applies to connections added afterwards. Defaults to 32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn capacity(self, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        self.state.lock().unwrap().capacity = capacity;
+        self
+    }
+
+    /// Set what happens when a message is sent to a connection whose queue is full.
+    ///
+    /// Defaults to [`LagPolicy::DropMessage`].
+    pub fn on_lag(self, on_lag: LagPolicy) -> Self {
+        self.state.lock().unwrap().on_lag = on_lag;
+        self
+    }
+
+    /// Add a connection with the given metadata.
+    ///
+    /// The connection is removed when the returned [`HubConnection`] is dropped.
+    pub fn connect(&self, metadata: M) -> HubConnection<M> {
+        let mut state = self.state.lock().unwrap();
+        state.remove_expired();
+        let id = ConnectionId(state.next_id);
+        state.next_id += 1;
+
+        let (sender, receiver) = mpsc::channel(state.capacity);
+        state.connections.insert(
+            id,
+            Connection {
+                metadata,
+                rooms: HashSet::new(),
+                sender,
+                suspended: None,
+            },
+        );
+
+        HubConnection {
+            id,
+            hub: self.clone(),
+            receiver: Some(receiver),
+        }
+    }
+
+    /// Resume a connection suspended with [`HubConnection::suspend`].
+    ///
+    /// The returned [`HubConnection`] receives the messages sent to the connection while it was
+    /// suspended, and is still in the same rooms, with the same metadata.
+    ///
+    /// Returns `None` if there was no such suspended connection, or it expired.
+    pub fn resume(&self, id: ConnectionId) -> Option<HubConnection<M>> {
+        let mut state = self.state.lock().unwrap();
+        state.remove_expired();
+        let suspended = state.connections.get_mut(&id)?.suspended.take()?;
+        Some(HubConnection {
+            id,
+            hub: self.clone(),
+            receiver: Some(suspended.receiver),
+        })
+    }
+
+    /// Remove a connection.
+    ///
+    /// Its [`HubConnection`] receives the messages that are already queued, and then `None`.
+    ///
+    /// Returns `false` if there was no such connection.
+    pub fn disconnect(&self, id: ConnectionId) -> bool {
+        self.state.lock().unwrap().remove(id)
+    }
+
+    /// Add a connection to a room.
+    ///
+    /// Rooms are created when the first connection joins them, and removed once the last one
+    /// leaves.
+    ///
+    /// Returns `false` if there was no such connection.
+    pub fn join(&self, id: ConnectionId, room: impl Into<String>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let Some(conn) = state.connections.get_mut(&id) else {
+            return false;
+        };
+
+        let room = room.into();
+        state.rooms.entry(room.clone()).or_default().insert(id);
+        conn.rooms.insert(room);
+        true
+    }
+
+    /// Remove a connection from a room.
+    ///
+    /// Returns `false` if the connection wasn't in the room.
+    pub fn leave(&self, id: ConnectionId, room: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(conn) = state.connections.get_mut(&id) else {
+            return false;
+        };
+        if !conn.rooms.remove(room) {
+            return false;
+        }
+
+        state.leave_room(id, room);
+        true
+    }
+
+    /// Send a message to a single connection.
+    ///
+    /// Returns `false` if there was no such connection or the message couldn't be queued.
+    pub fn send(&self, id: ConnectionId, msg: Message) -> bool {
+        self.state.lock().unwrap().deliver(vec![id], msg) == 1
+    }
+
+    /// Send a message to every connection in a room.
+    ///
+    /// Returns the number of connections the message was queued for.
+    pub fn broadcast(&self, room: &str, msg: Message) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let ids = state
+            .rooms
+            .get(room)
+            .map(|members| members.iter().copied().collect())
+            .unwrap_or_default();
+        state.deliver(ids, msg)
+    }
+
+    /// Send a message to every connection.
+    ///
+    /// Returns the number of connections the message was queued for.
+    pub fn broadcast_all(&self, msg: Message) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let ids = state.connections.keys().copied().collect();
+        state.deliver(ids, msg)
+    }
+
+    /// Get the connections in a room, in no particular order.
+    pub fn members(&self, room: &str) -> Vec<ConnectionId> {
+        self.state
+            .lock()
+            .unwrap()
+            .rooms
+            .get(room)
+            .map(|members| members.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the rooms a connection has joined, in no particular order.
+    pub fn joined_rooms(&self, id: ConnectionId) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .connections
+            .get(&id)
+            .map(|conn| conn.rooms.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the names of all rooms, in no particular order.
+    pub fn rooms(&self) -> Vec<String> {
+        self.state.lock().unwrap().rooms.keys().cloned().collect()
+    }
+
+    /// Get the metadata of a connection.
+    pub fn metadata(&self, id: ConnectionId) -> Option<M>
+    where
+        M: Clone,
+    {
+        self.state
+            .lock()
+            .unwrap()
+            .connections
+            .get(&id)
+            .map(|conn| conn.metadata.clone())
+    }
+
+    /// Update the metadata of a connection.
+    ///
+    /// The hub is locked while `f` runs, so it must not call methods on the hub.
+    ///
+    /// Returns `false` if there was no such connection.
+    pub fn update_metadata<F>(&self, id: ConnectionId, f: F) -> bool
+    where
+        F: FnOnce(&mut M),
+    {
+        match self.state.lock().unwrap().connections.get_mut(&id) {
+            Some(conn) => {
+                f(&mut conn.metadata);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the number of connections.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().connections.len()
+    }
+
+    /// Returns `true` if there are no connections.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<M> State<M> {
+    fn remove(&mut self, id: ConnectionId) -> bool {
+        let Some(conn) = self.connections.remove(&id) else {
+            return false;
+        };
+        for room in &conn.rooms {
+            self.leave_room(id, room);
+        }
+        true
+    }
+
+    fn remove_expired(&mut self) {
+        let now = Instant::now();
+        let expired = self
+            .connections
+            .iter()
+            .filter(
+                |(_, conn)| matches!(&conn.suspended, Some(suspended) if suspended.until <= now),
+            )
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in expired {
+            self.remove(id);
+        }
+    }
+
+    fn leave_room(&mut self, id: ConnectionId, room: &str) {
+        if let Some(members) = self.rooms.get_mut(room) {
+            members.remove(&id);
+            if members.is_empty() {
+                self.rooms.remove(room);
+            }
+        }
+    }
+
+    fn deliver(&mut self, ids: Vec<ConnectionId>, msg: Message) -> usize {
+        self.remove_expired();
+        let mut delivered = 0;
+        let mut lagging = Vec::new();
+        for id in ids {
+            let Some(conn) = self.connections.get(&id) else {
+                continue;
+            };
+            match conn.sender.try_send(msg.clone()) {
+                Ok(()) => delivered += 1,
+                Err(TrySendError::Full(_)) => match self.on_lag {
+                    LagPolicy::DropMessage => {}
+                    LagPolicy::Disconnect => lagging.push(id),
+                },
+                // the `HubConnection` is being dropped
+                Err(TrySendError::Closed(_)) => lagging.push(id),
+            }
+        }
+
+        for id in lagging {
+            self.remove(id);
+        }
+        delivered
+    }
+}
+
+impl<M> Clone for Hub<M> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<M> Default for Hub<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> fmt::Debug for Hub<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Hub")
+            .field("connections", &state.connections.len())
+            .field("rooms", &state.rooms.len())
+            .field("capacity", &state.capacity)
+            .field("on_lag", &state.on_lag)
+            .finish()
+    }
+}
+
+/// What a [`Hub`] does when a message is sent to a connection whose queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum LagPolicy {
+    /// Drop the message for that connection. The connection misses it, but stays connected.
+    #[default]
+    DropMessage,
+    /// Remove the connection from the hub. Its [`HubConnection`] receives the messages that are
+    /// already queued, and then `None`, so the socket can be closed.
+    Disconnect,
+}
+
+/// Identifies a connection in a [`Hub`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(u64);
+
+impl fmt::Display for ConnectionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A connection in a [`Hub`].
+///
+/// Created with [`Hub::connect`]. The messages sent to the connection must be received with
+/// [`recv`](Self::recv) and sent to the socket. The connection is removed from the hub when this
+/// is dropped.
+pub struct HubConnection<M = ()> {
+    id: ConnectionId,
+    hub: Hub<M>,
+    /// `None` once the connection was suspended.
+    receiver: Option<mpsc::Receiver<Message>>,
+}
+
+impl<M> HubConnection<M> {
+    /// Get the id of the connection.
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+
+    /// Get the hub the connection belongs to.
+    pub fn hub(&self) -> &Hub<M> {
+        &self.hub
+    }
+
+    /// Join a room.
+    ///
+    /// See [`Hub::join`] for more details.
+    pub fn join(&self, room: impl Into<String>) -> bool {
+        self.hub.join(self.id, room)
+    }
+
+    /// Leave a room.
+    ///
+    /// See [`Hub::leave`] for more details.
+    pub fn leave(&self, room: &str) -> bool {
+        self.hub.leave(self.id, room)
+    }
+
+    /// Receive the next message sent to this connection.
+    ///
+    /// Returns `None` once the connection has been removed from the hub, with
+    /// [`Hub::disconnect`] or because of [`LagPolicy::Disconnect`], and the queued messages have
+    /// been received.
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.receiver.as_mut()?.recv().await
+    }
+
+    /// Keep the connection in the hub after its socket closed, so it can be resumed with
+    /// [`Hub::resume`] when the client reconnects.
+    ///
+    /// The connection stays in its rooms, and messages sent to it are queued until it's resumed,
+    /// up to the [capacity](Hub::capacity) of the hub. It's removed if it isn't resumed within
+    /// `ttl`.
+    pub fn suspend(mut self, ttl: Duration) -> ConnectionId {
+        if let Some(receiver) = self.receiver.take() {
+            let mut state = self.hub.state.lock().unwrap();
+            if let Some(conn) = state.connections.get_mut(&self.id) {
+                conn.suspended = Some(Suspended {
+                    receiver,
+                    until: Instant::now() + ttl,
+                });
+            }
+        }
+        self.id
+    }
+}
+
+impl<M> Drop for HubConnection<M> {
+    fn drop(&mut self) {
+        // suspended connections are kept
+        if self.receiver.is_some() {
+            self.hub.disconnect(self.id);
+        }
+    }
+}
+
+impl<M> fmt::Debug for HubConnection<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HubConnection")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Message {
+        Message::Text(text.to_owned())
+    }
+
+    #[tokio::test]
+    async fn rooms() {
+        let hub = Hub::new();
+        let mut a = hub.connect("a");
+        let mut b = hub.connect("b");
+        let mut c = hub.connect("c");
+        assert!(a.join("x"));
+        assert!(b.join("x"));
+        assert!(b.join("y"));
+
+        let mut members = hub.members("x");
+        members.sort();
+        assert_eq!(members, [a.id(), b.id()]);
+        let mut joined = hub.joined_rooms(b.id());
+        joined.sort();
+        assert_eq!(joined, ["x", "y"]);
+
+        assert_eq!(hub.broadcast("x", text("to x")), 2);
+        assert_eq!(hub.broadcast("z", text("to z")), 0);
+        assert!(hub.send(c.id(), text("to c")));
+        assert_eq!(hub.broadcast_all(text("to all")), 3);
+
+        assert_eq!(a.recv().await.unwrap(), text("to x"));
+        assert_eq!(a.recv().await.unwrap(), text("to all"));
+        assert_eq!(b.recv().await.unwrap(), text("to x"));
+        assert_eq!(b.recv().await.unwrap(), text("to all"));
+        assert_eq!(c.recv().await.unwrap(), text("to c"));
+        assert_eq!(c.recv().await.unwrap(), text("to all"));
+
+        assert!(b.leave("y"));
+        assert!(!b.leave("y"));
+        assert!(hub.members("y").is_empty());
+        assert!(!hub.rooms().contains(&"y".to_owned()));
+
+        drop(a);
+        assert_eq!(hub.members("x"), [b.id()]);
+        assert_eq!(hub.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn metadata() {
+        let hub = Hub::new();
+        let conn = hub.connect("anonymous".to_owned());
+
+        assert_eq!(hub.metadata(conn.id()).unwrap(), "anonymous");
+        assert!(hub.update_metadata(conn.id(), |name| *name = "alice".to_owned()));
+        assert_eq!(hub.metadata(conn.id()).unwrap(), "alice");
+
+        let id = conn.id();
+        drop(conn);
+        assert_eq!(hub.metadata(id), None);
+        assert!(!hub.update_metadata(id, |_| {}));
+    }
+
+    #[tokio::test]
+    async fn suspend_and_resume() {
+        let hub = Hub::new();
+        let conn = hub.connect("alice");
+        assert!(conn.join("room"));
+        assert_eq!(hub.broadcast("room", text("before")), 1);
+
+        let id = conn.suspend(Duration::from_secs(60));
+        assert_eq!(hub.broadcast("room", text("while suspended")), 1);
+        assert_eq!(hub.metadata(id), Some("alice"));
+
+        let mut conn = hub.resume(id).unwrap();
+        assert!(hub.resume(id).is_none());
+        assert_eq!(conn.recv().await.unwrap(), text("before"));
+        assert_eq!(conn.recv().await.unwrap(), text("while suspended"));
+        assert_eq!(hub.members("room"), [id]);
+
+        let id = conn.suspend(Duration::ZERO);
+        assert!(hub.resume(id).is_none());
+        assert!(hub.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drop_message_when_lagging() {
+        let hub = Hub::new().capacity(1);
+        let mut conn = hub.connect(());
+
+        assert_eq!(hub.broadcast_all(text("first")), 1);
+        assert_eq!(hub.broadcast_all(text("second")), 0);
+        assert_eq!(conn.recv().await.unwrap(), text("first"));
+
+        assert_eq!(hub.broadcast_all(text("third")), 1);
+        assert_eq!(conn.recv().await.unwrap(), text("third"));
+    }
+
+    #[tokio::test]
+    async fn disconnect_when_lagging() {
+        let hub = Hub::new().capacity(1).on_lag(LagPolicy::Disconnect);
+        let mut conn = hub.connect(());
+        assert!(conn.join("room"));
+
+        assert_eq!(hub.broadcast("room", text("first")), 1);
+        assert_eq!(hub.broadcast("room", text("second")), 0);
+        assert!(hub.is_empty());
+        assert!(hub.rooms().is_empty());
+
+        assert_eq!(conn.recv().await.unwrap(), text("first"));
+        assert_eq!(conn.recv().await, None);
+    }
+}
@@ -0,0 +1,384 @@
+use axum::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SESSION_ID_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+
+/// Lets clients resume a WebSocket session after reconnecting.
+///
+/// When a client connects, start a session with [`new_session`](Self::new_session) and send it
+/// the signed [`token`](Self::token) for the session. When its socket closes, store the state of
+/// the connection with [`suspend`](Self::suspend). If the client reconnects with the token before
+/// the [`ttl`](Self::ttl) expires, [`resume`](Self::resume) returns the state again.
+///
+/// Tokens are signed with HMAC-SHA256, so clients can't forge tokens for sessions they weren't
+/// given. A session can be resumed once each time it's suspended.
+///
+/// Combined with [`Hub::resume`](super::Hub::resume), the messages sent to a connection while its
+/// client was away are replayed when it reconnects.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     extract::{ws::{Message, WebSocketUpgrade}, Query, State},
+///     response::Response,
+///     routing::get,
+///     Router,
+/// };
+/// use axum_extra::ws::{ConnectionId, Hub, Resumption};
+/// use serde::Deserialize;
+/// use std::time::Duration;
+///
+/// const TTL: Duration = Duration::from_secs(60);
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     hub: Hub,
+///     resumption: Resumption<ConnectionId>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     token: Option<String>,
+/// }
+///
+/// async fn handler(
+///     ws: WebSocketUpgrade,
+///     Query(params): Query<Params>,
+///     State(state): State<AppState>,
+/// ) -> Response {
+///     ws.on_upgrade(move |mut socket| async move {
+///         let resumed = match params.token {
+///             Some(token) => state.resumption.resume(&token).await.unwrap(),
+///             None => None,
+///         };
+///         // the connection receives the messages it missed while the client was away
+///         let (session, mut conn) = match resumed
+///             .and_then(|(session, id)| Some((session, state.hub.resume(id)?)))
+///         {
+///             Some(resumed) => resumed,
+///             None => (state.resumption.new_session(), state.hub.connect(())),
+///         };
+///
+///         let token = state.resumption.token(session);
+///         if socket.send(Message::Text(token)).await.is_err() {
+///             return;
+///         }
+///
+///         loop {
+///             tokio::select! {
+///                 Some(msg) = conn.recv() => {
+///                     if socket.send(msg).await.is_err() {
+///                         break;
+///                     }
+///                 }
+///                 msg = socket.recv() => match msg {
+///                     Some(Ok(_)) => {}
+///                     _ => break,
+///                 },
+///             }
+///         }
+///
+///         let id = conn.suspend(TTL);
+///         state.resumption.suspend(session, id).await.unwrap();
+///     })
+/// }
+///
+/// # std::env::set_var("RESUMPTION_SECRET", "0123456789abcdef0123456789abcdef");
+/// let secret = std::env::var("RESUMPTION_SECRET").unwrap();
+/// let state = AppState {
+///     hub: Hub::new(),
+///     resumption: Resumption::new(secret, Default::default()).ttl(TTL),
+/// };
+///
+/// let app = Router::new().route("/ws", get(handler)).with_state(state);
+/// # let _: Router = app;
+/// ```
+pub struct Resumption<T, S = MemorySessionStore<T>> {
+    secret: Arc<[u8]>,
+    store: S,
+    ttl: Duration,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, S> Resumption<T, S>
+where
+    T: Send + 'static,
+    S: SessionStore<T>,
+{
+    /// Create a new `Resumption` that signs tokens with `secret` and stores suspended sessions in
+    /// `store`.
+    ///
+    /// # Panics
+    ///
+    /// If `secret` is shorter than 32 bytes.
+    pub fn new(secret: impl AsRef<[u8]>, store: S) -> Self {
+        let secret = secret.as_ref();
+        assert!(
+            secret.len() >= 32,
+            "resumption secret must be at least 32 bytes"
+        );
+        Self {
+            secret: secret.into(),
+            store,
+            ttl: Duration::from_secs(60),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set how long suspended sessions can be resumed for.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Start a new session.
+    pub fn new_session(&self) -> SessionId {
+        let mut bytes = [0; SESSION_ID_LEN];
+        getrandom::getrandom(&mut bytes).expect("failed to generate a session id");
+        SessionId(bytes)
+    }
+
+    /// The token the client sends to resume `session`.
+    ///
+    /// The token is URL safe, so it can be sent in the query string.
+    pub fn token(&self, session: SessionId) -> String {
+        let mut token = Vec::with_capacity(SESSION_ID_LEN + TAG_LEN);
+        token.extend_from_slice(&session.0);
+        token.extend_from_slice(&self.mac(session).finalize().into_bytes());
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// Check the signature of `token`, and return the session it was issued for.
+    pub fn verify(&self, token: &str) -> Option<SessionId> {
+        let token = URL_SAFE_NO_PAD.decode(token).ok()?;
+        if token.len() != SESSION_ID_LEN + TAG_LEN {
+            return None;
+        }
+        let (id, tag) = token.split_at(SESSION_ID_LEN);
+        let session = SessionId(id.try_into().ok()?);
+        self.mac(session).verify_slice(tag).ok()?;
+        Some(session)
+    }
+
+    /// Store the state of `session` after its socket closed, so it can be resumed.
+    pub async fn suspend(&self, session: SessionId, state: T) -> Result<(), S::Error> {
+        self.store.save(session, state, self.ttl).await
+    }
+
+    /// Resume the session `token` was issued for, returning the state it was suspended with.
+    ///
+    /// Returns `None` if the token is invalid, or the session isn't suspended or has expired.
+    pub async fn resume(&self, token: &str) -> Result<Option<(SessionId, T)>, S::Error> {
+        let Some(session) = self.verify(token) else {
+            return Ok(None);
+        };
+        Ok(self
+            .store
+            .take(session)
+            .await?
+            .map(|state| (session, state)))
+    }
+
+    fn mac(&self, session: SessionId) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC can take keys of any size");
+        mac.update(&session.0);
+        mac
+    }
+}
+
+impl<T, S> Clone for Resumption<T, S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            secret: self.secret.clone(),
+            store: self.store.clone(),
+            ttl: self.ttl,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, S> fmt::Debug for Resumption<T, S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resumption")
+            .field("store", &self.store)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Identifies a session of a [`Resumption`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SessionId([u8; SESSION_ID_LEN]);
+
+impl SessionId {
+    /// The bytes of the id.
+    pub fn as_bytes(&self) -> &[u8; SESSION_ID_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Stores the state of suspended sessions for a [`Resumption`].
+#[async_trait]
+pub trait SessionStore<T: Send + 'static>: Clone + Send + Sync + 'static {
+    /// The error returned if the store fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Store the state of `session`, replacing any previous state.
+    ///
+    /// The state expires after `ttl`.
+    async fn save(&self, session: SessionId, state: T, ttl: Duration) -> Result<(), Self::Error>;
+
+    /// Remove and return the state of `session`, unless it has expired.
+    async fn take(&self, session: SessionId) -> Result<Option<T>, Self::Error>;
+}
+
+/// [`SessionStore`] that keeps suspended sessions in memory.
+///
+/// The store can be cloned to share it between [`Resumption`]s.
+pub struct MemorySessionStore<T> {
+    sessions: Arc<Mutex<HashMap<SessionId, (T, Instant)>>>,
+}
+
+impl<T> MemorySessionStore<T> {
+    /// Create a new, empty `MemorySessionStore`.
+    pub fn new() -> Self {
+        Self {
+            sessions: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> SessionStore<T> for MemorySessionStore<T>
+where
+    T: Send + 'static,
+{
+    type Error = Infallible;
+
+    async fn save(&self, session: SessionId, state: T, ttl: Duration) -> Result<(), Self::Error> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, (_, expires)| *expires > now);
+        sessions.insert(session, (state, now + ttl));
+        Ok(())
+    }
+
+    async fn take(&self, session: SessionId) -> Result<Option<T>, Self::Error> {
+        let mut sessions = self.sessions.lock().unwrap();
+        Ok(match sessions.remove(&session) {
+            Some((state, expires)) if expires > Instant::now() => Some(state),
+            _ => None,
+        })
+    }
+}
+
+impl<T> Clone for MemorySessionStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sessions: self.sessions.clone(),
+        }
+    }
+}
+
+impl<T> Default for MemorySessionStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for MemorySessionStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemorySessionStore")
+            .field("len", &self.sessions.lock().unwrap().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn resumption() -> Resumption<&'static str> {
+        Resumption::new(SECRET, MemorySessionStore::new())
+    }
+
+    #[test]
+    fn tokens() {
+        let resumption = resumption();
+        let session = resumption.new_session();
+        assert_ne!(session, resumption.new_session());
+
+        let token = resumption.token(session);
+        assert_eq!(resumption.verify(&token), Some(session));
+
+        let mut tampered = token.clone().into_bytes();
+        tampered[0] = if tampered[0] == b'A' { b'B' } else { b'A' };
+        let tampered = String::from_utf8(tampered).unwrap();
+        assert_eq!(resumption.verify(&tampered), None);
+        assert_eq!(resumption.verify(&token[1..]), None);
+        assert_eq!(resumption.verify("not a token"), None);
+
+        let other = Resumption::<&str>::new(SECRET.repeat(2), MemorySessionStore::new());
+        assert_eq!(other.verify(&token), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "resumption secret must be at least 32 bytes")]
+    fn short_secret() {
+        Resumption::<()>::new("secret", MemorySessionStore::new());
+    }
+
+    #[tokio::test]
+    async fn suspend_and_resume() {
+        let resumption = resumption();
+        let session = resumption.new_session();
+        let token = resumption.token(session);
+        assert!(resumption.resume(&token).await.unwrap().is_none());
+
+        resumption.suspend(session, "state").await.unwrap();
+        assert_eq!(
+            resumption.resume(&token).await.unwrap(),
+            Some((session, "state"))
+        );
+        // a session can only be resumed once per suspension
+        assert!(resumption.resume(&token).await.unwrap().is_none());
+
+        let resumption = resumption.ttl(Duration::ZERO);
+        resumption.suspend(session, "state").await.unwrap();
+        assert!(resumption.resume(&token).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,432 @@
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
+use futures_util::{
+    future::poll_fn,
+    sink::{Sink, SinkExt},
+    stream::{SplitStream, StreamExt},
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+};
+
+/// A bounded queue of messages to send to a [`WebSocket`].
+///
+/// Messages are queued and sent by a background task, so sending doesn't wait for the client.
+/// When a slow client falls behind and the queue is full, the [`OverflowPolicy`] decides what
+/// happens, so a slow client can't make the server buffer an unbounded number of messages.
+///
+/// `SendQueue` is cheap to clone, and clones send to the same socket, so it can be shared with
+/// other tasks, such as ones broadcasting to many sockets. Once every clone has been dropped, or
+/// [`close`](Self::close) is called, the queued messages are sent and the connection is closed.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{
+///     extract::ws::{Message, WebSocketUpgrade},
+///     response::Response,
+/// };
+/// use axum_extra::ws::{OverflowPolicy, SendQueue};
+/// use futures_util::StreamExt;
+/// use std::time::Duration;
+///
+/// async fn handler(ws: WebSocketUpgrade) -> Response {
+///     ws.on_upgrade(|socket| async {
+///         let (queue, mut receiver) = SendQueue::new(socket, 64, OverflowPolicy::DropOldest);
+///
+///         // pass the queue to the tasks producing messages, which never wait for the client
+///         let producer = queue.clone();
+///         tokio::spawn(async move {
+///             while producer.send(Message::Text("tick".to_owned())).await.is_ok() {
+///                 tokio::time::sleep(Duration::from_secs(1)).await;
+///             }
+///         });
+///
+///         while let Some(Ok(msg)) = receiver.next().await {
+///             // ...
+///         }
+///
+///         queue.close();
+///     })
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SendQueue {
+    shared: Arc<Shared>,
+    _sender: Arc<SenderGuard>,
+}
+
+struct Shared {
+    capacity: usize,
+    on_overflow: OverflowPolicy,
+    state: Mutex<State>,
+}
+
+struct State {
+    queue: VecDeque<Message>,
+    /// Whether new messages are accepted.
+    open: bool,
+    /// Whether the connection was closed because the queue overflowed.
+    overflowed: bool,
+    dropped: u64,
+    writer: Option<Waker>,
+    /// Wakers of senders waiting for space in the queue.
+    blocked: Vec<Waker>,
+}
+
+/// Lets the writer finish once every [`SendQueue`] has been dropped.
+struct SenderGuard {
+    shared: Arc<Shared>,
+}
+
+impl SendQueue {
+    /// Split `socket`, and spawn a task sending the messages queued with the returned
+    /// `SendQueue`.
+    ///
+    /// The returned stream receives the messages from the client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero, or if called outside of a Tokio runtime.
+    pub fn new(
+        socket: WebSocket,
+        capacity: usize,
+        on_overflow: OverflowPolicy,
+    ) -> (Self, SplitStream<WebSocket>) {
+        let (sink, stream) = socket.split();
+        (Self::spawn(sink, capacity, on_overflow), stream)
+    }
+
+    fn spawn<S>(sink: S, capacity: usize, on_overflow: OverflowPolicy) -> Self
+    where
+        S: Sink<Message, Error = axum::Error> + Send + Unpin + 'static,
+    {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        let shared = Arc::new(Shared {
+            capacity,
+            on_overflow,
+            state: Mutex::new(State {
+                queue: VecDeque::with_capacity(capacity),
+                open: true,
+                overflowed: false,
+                dropped: 0,
+                writer: None,
+                blocked: Vec::new(),
+            }),
+        });
+        tokio::spawn(write(shared.clone(), sink));
+
+        Self {
+            _sender: Arc::new(SenderGuard {
+                shared: shared.clone(),
+            }),
+            shared,
+        }
+    }
+
+    /// Queue a message.
+    ///
+    /// This only waits if the queue is full and the policy is [`OverflowPolicy::Block`].
+    pub async fn send(&self, msg: Message) -> Result<(), SendQueueError> {
+        let mut msg = Some(msg);
+        poll_fn(|cx| {
+            let mut state = self.shared.state.lock().unwrap();
+            if !state.open {
+                return Poll::Ready(Err(SendQueueError::Closed));
+            }
+
+            if state.queue.len() >= self.shared.capacity {
+                match self.shared.on_overflow {
+                    OverflowPolicy::Block => {
+                        state.blocked.push(cx.waker().clone());
+                        return Poll::Pending;
+                    }
+                    OverflowPolicy::DropOldest => {
+                        state.queue.pop_front();
+                        state.dropped += 1;
+                    }
+                    OverflowPolicy::DropNewest => {
+                        state.dropped += 1;
+                        return Poll::Ready(Err(SendQueueError::Dropped));
+                    }
+                    OverflowPolicy::CloseConnection => {
+                        state.open = false;
+                        state.overflowed = true;
+                        state.dropped += state.queue.len() as u64 + 1;
+                        state.queue.clear();
+                        state.wake_all();
+                        return Poll::Ready(Err(SendQueueError::Closed));
+                    }
+                }
+            }
+
+            state.queue.extend(msg.take());
+            if let Some(waker) = state.writer.take() {
+                waker.wake();
+            }
+            Poll::Ready(Ok(()))
+        })
+        .await
+    }
+
+    /// Stop accepting messages, and close the connection once the queued messages have been
+    /// sent.
+    pub fn close(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.open = false;
+        state.wake_all();
+    }
+
+    /// Get the number of queued messages.
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().queue.len()
+    }
+
+    /// Returns `true` if no messages are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the maximum number of queued messages.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Get the number of messages that were dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.shared.state.lock().unwrap().dropped
+    }
+
+    /// Returns `true` if messages are no longer accepted.
+    ///
+    /// This is the case after [`close`](Self::close), after the queue overflowed with
+    /// [`OverflowPolicy::CloseConnection`], or after sending a message failed.
+    pub fn is_closed(&self) -> bool {
+        !self.shared.state.lock().unwrap().open
+    }
+}
+
+impl fmt::Debug for SendQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.shared.state.lock().unwrap();
+        f.debug_struct("SendQueue")
+            .field("len", &state.queue.len())
+            .field("capacity", &self.shared.capacity)
+            .field("on_overflow", &self.shared.on_overflow)
+            .field("dropped", &state.dropped)
+            .field("closed", &!state.open)
+            .finish()
+    }
+}
+
+impl State {
+    /// Wake the writer and the blocked senders, after the queue was closed.
+    fn wake_all(&mut self) {
+        self.writer.take().into_iter().for_each(Waker::wake);
+        self.blocked.drain(..).for_each(Waker::wake);
+    }
+}
+
+impl Drop for SenderGuard {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.open = false;
+        state.wake_all();
+    }
+}
+
+async fn write<S>(shared: Arc<Shared>, mut sink: S)
+where
+    S: Sink<Message, Error = axum::Error> + Unpin,
+{
+    loop {
+        let next = poll_fn(|cx| {
+            let mut state = shared.state.lock().unwrap();
+            match state.queue.pop_front() {
+                Some(msg) => {
+                    state.blocked.drain(..).for_each(Waker::wake);
+                    Poll::Ready(Some((msg, state.queue.is_empty())))
+                }
+                None if !state.open => Poll::Ready(None),
+                None => {
+                    state.writer = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await;
+
+        let Some((msg, flush)) = next else {
+            break;
+        };
+        // buffer messages while more are queued, and flush once the queue is empty
+        let result = if flush {
+            sink.send(msg).await
+        } else {
+            sink.feed(msg).await
+        };
+        if result.is_err() {
+            let mut state = shared.state.lock().unwrap();
+            state.open = false;
+            state.queue.clear();
+            state.wake_all();
+            return;
+        }
+    }
+
+    if shared.state.lock().unwrap().overflowed {
+        let _ = sink
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::POLICY,
+                reason: "client is too slow".into(),
+            })))
+            .await;
+    }
+    let _ = sink.close().await;
+}
+
+/// What a [`SendQueue`] does when a message is sent while the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+    /// Wait until there is space in the queue.
+    Block,
+    /// Drop the oldest queued message to make space for the new one.
+    DropOldest,
+    /// Drop the new message, and return [`SendQueueError::Dropped`].
+    DropNewest,
+    /// Drop the queued messages, and close the connection with
+    /// [`close_code::POLICY`](axum::extract::ws::close_code::POLICY).
+    CloseConnection,
+}
+
+/// Error returned by [`SendQueue::send`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SendQueueError {
+    /// The queue was full, and the message was dropped because of
+    /// [`OverflowPolicy::DropNewest`].
+    Dropped,
+    /// The queue no longer accepts messages.
+    Closed,
+}
+
+impl fmt::Display for SendQueueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dropped => f.write_str("Send queue is full, message was dropped"),
+            Self::Closed => f.write_str("Send queue is closed"),
+        }
+    }
+}
+
+impl std::error::Error for SendQueueError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    type Sent = Arc<Mutex<Vec<Message>>>;
+
+    fn queue(capacity: usize, on_overflow: OverflowPolicy) -> (SendQueue, Sent) {
+        let sent = Sent::default();
+        let sink =
+            futures_util::sink::unfold(sent.clone(), |sent: Sent, msg: Message| async move {
+                sent.lock().unwrap().push(msg);
+                Ok::<_, axum::Error>(sent)
+            });
+        (
+            SendQueue::spawn(Box::pin(sink), capacity, on_overflow),
+            sent,
+        )
+    }
+
+    fn text(text: &str) -> Message {
+        Message::Text(text.to_owned())
+    }
+
+    // the writer doesn't run until the test yields, since tests use a single threaded runtime
+    async fn send_all(queue: &SendQueue) -> Vec<Result<(), SendQueueError>> {
+        let mut results = Vec::new();
+        for msg in ["1", "2", "3", "4"] {
+            results.push(queue.send(text(msg)).await);
+        }
+        results
+    }
+
+    async fn sent(queue: SendQueue, sent: Sent) -> Vec<Message> {
+        drop(queue);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let sent = sent.lock().unwrap();
+        sent.clone()
+    }
+
+    #[tokio::test]
+    async fn drop_oldest() {
+        let (queue, sent_msgs) = queue(2, OverflowPolicy::DropOldest);
+
+        assert!(send_all(&queue).await.iter().all(Result::is_ok));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.dropped(), 2);
+
+        assert_eq!(sent(queue, sent_msgs).await, [text("3"), text("4")]);
+    }
+
+    #[tokio::test]
+    async fn drop_newest() {
+        let (queue, sent_msgs) = queue(2, OverflowPolicy::DropNewest);
+
+        let results = send_all(&queue).await;
+        assert_eq!(
+            results,
+            [
+                Ok(()),
+                Ok(()),
+                Err(SendQueueError::Dropped),
+                Err(SendQueueError::Dropped)
+            ]
+        );
+        assert_eq!(queue.dropped(), 2);
+
+        assert_eq!(sent(queue, sent_msgs).await, [text("1"), text("2")]);
+    }
+
+    #[tokio::test]
+    async fn close_connection() {
+        let (queue, sent_msgs) = queue(2, OverflowPolicy::CloseConnection);
+
+        let results = send_all(&queue).await;
+        assert_eq!(
+            results,
+            [
+                Ok(()),
+                Ok(()),
+                Err(SendQueueError::Closed),
+                Err(SendQueueError::Closed)
+            ]
+        );
+        assert!(queue.is_closed());
+
+        let sent = sent(queue, sent_msgs).await;
+        assert!(
+            matches!(&sent[..], [Message::Close(Some(frame))] if frame.code == close_code::POLICY)
+        );
+    }
+
+    #[tokio::test]
+    async fn block() {
+        let (queue, sent_msgs) = queue(1, OverflowPolicy::Block);
+
+        // waits for the writer to take the previous message
+        assert!(send_all(&queue).await.iter().all(Result::is_ok));
+        assert_eq!(queue.dropped(), 0);
+
+        assert_eq!(
+            sent(queue, sent_msgs).await,
+            [text("1"), text("2"), text("3"), text("4")]
+        );
+    }
+}
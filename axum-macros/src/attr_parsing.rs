@@ -100,3 +100,34 @@ where
 pub(crate) fn second<T, K>(tuple: (T, K)) -> K {
     tuple.1
 }
+
+/// The text of the doc comments in `attrs`, with the leading space of each line removed.
+pub(crate) fn parse_doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| {
+            let syn::Meta::NameValue(meta) = &attr.meta else {
+                return None;
+            };
+            if !meta.path.is_ident("doc") {
+                return None;
+            }
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) = &meta.value
+            else {
+                return None;
+            };
+            let line = lit.value();
+            Some(
+                line.strip_prefix(' ')
+                    .map(ToOwned::to_owned)
+                    .unwrap_or(line),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n").trim().to_owned();
+    (!doc.is_empty()).then_some(doc)
+}
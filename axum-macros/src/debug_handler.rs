@@ -48,6 +48,13 @@ pub(crate) fn expand(attr: Attrs, item_fn: ItemFn, kind: FunctionKind) -> TokenS
         }
 
         err.unwrap_or_else(|| {
+            let check_middleware_state = match (kind, &state_ty) {
+                (FunctionKind::Middleware, Some(state_ty)) => {
+                    check_middleware_state(&item_fn, state_ty)
+                }
+                _ => quote! {},
+            };
+
             let state_ty = state_ty.unwrap_or_else(|| syn::parse_quote!(()));
 
             let check_future_send = check_future_send(&item_fn, kind);
@@ -56,6 +63,7 @@ pub(crate) fn expand(attr: Attrs, item_fn: ItemFn, kind: FunctionKind) -> TokenS
                 quote! {
                     #check_input_order
                     #check_future_send
+                    #check_middleware_state
                 }
             } else {
                 let check_inputs_impls_from_request =
@@ -64,6 +72,7 @@ pub(crate) fn expand(attr: Attrs, item_fn: ItemFn, kind: FunctionKind) -> TokenS
                 quote! {
                     #check_inputs_impls_from_request
                     #check_future_send
+                    #check_middleware_state
                 }
             }
         })
@@ -76,7 +85,7 @@ pub(crate) fn expand(attr: Attrs, item_fn: ItemFn, kind: FunctionKind) -> TokenS
     };
 
     let middleware_takes_next_as_last_arg =
-        matches!(kind, FunctionKind::Middleware).then(|| next_is_last_input(&item_fn));
+        matches!(kind, FunctionKind::Middleware).then(|| check_next_input(&item_fn));
 
     quote! {
         #item_fn
@@ -287,6 +296,37 @@ fn check_inputs_impls_from_request(
             span = span,
         );
 
+        // `State<T>` only fails to extract if `T` can't be created from the handler's state, so
+        // name the missing `FromRef` impl instead of the `FromRequestParts` impl
+        if let Some(sub_state) = crate::infer_state_types(std::iter::once(&*ty)).next() {
+            let span = sub_state.span();
+            let call_check_fn_body = if takes_self {
+                quote_spanned! {span=>
+                    Self::#check_fn::<#sub_state>();
+                }
+            } else {
+                quote_spanned! {span=>
+                    #check_fn::<#sub_state>();
+                }
+            };
+
+            return quote_spanned! {span=>
+                #[allow(warnings)]
+                #[doc(hidden)]
+                fn #check_fn<T>()
+                where
+                    T: ::axum::extract::FromRef<#state_ty>,
+                {}
+
+                #[allow(warnings)]
+                #[doc(hidden)]
+                fn #call_check_fn()
+                {
+                    #call_check_fn_body
+                }
+            };
+        }
+
         let call_check_fn_body = if takes_self {
             quote_spanned! {span=>
                 Self::#check_fn();
@@ -529,9 +569,13 @@ fn check_input_order(item_fn: &ItemFn, kind: FunctionKind) -> Option<TokenStream
         // and that is not the last
         if types_that_consume_the_request[0].0 != number_of_inputs - 1 {
             let (_idx, type_name, span) = &types_that_consume_the_request[0];
+            let last_argument = match kind {
+                FunctionKind::Handler => "the last argument to the handler function",
+                FunctionKind::Middleware => "the last argument before `next: Next`",
+            };
             let error = format!(
                 "`{type_name}` consumes the request body and thus must be \
-                the last argument to the handler function"
+                {last_argument}"
             );
             return Some(quote_spanned! {*span=>
                 compile_error!(#error);
@@ -821,7 +865,7 @@ fn state_types_from_args(item_fn: &ItemFn) -> HashSet<Type> {
     crate::infer_state_types(types).collect()
 }
 
-fn next_is_last_input(item_fn: &ItemFn) -> TokenStream {
+fn check_next_input(item_fn: &ItemFn) -> TokenStream {
     let next_args = item_fn
         .sig
         .inputs
@@ -855,16 +899,81 @@ fn next_is_last_input(item_fn: &ItemFn) -> TokenStream {
         };
     }
 
+    let (_, arg) = next_args[0];
+    let FnArg::Typed(pat_type) = arg else {
+        return quote! {};
+    };
+
+    match &*pat_type.ty {
+        Type::Reference(_) => {
+            return syn::Error::new_spanned(
+                &pat_type.ty,
+                "`axum::middleware::Next` must be taken by value, as in `next: Next`",
+            )
+            .into_compile_error();
+        }
+        Type::Path(type_path) => {
+            let arguments = &type_path.path.segments.last().unwrap().arguments;
+            if !arguments.is_empty() {
+                return syn::Error::new_spanned(
+                    arguments,
+                    "`axum::middleware::Next` doesn't take a request body type, \
+                    requests always have an `axum::body::Body`",
+                )
+                .into_compile_error();
+            }
+        }
+        _ => {}
+    }
+
+    // `from_fn` requires an extractor for the request, which is needed to call `Next::run`
+    let takes_request =
+        item_fn.sig.inputs.iter().any(|arg| {
+            matches!(arg, FnArg::Typed(_)) && skip_next_arg(arg, FunctionKind::Middleware)
+        });
+    if !takes_request {
+        return syn::Error::new_spanned(
+            arg,
+            "Middleware functions must take the request before `axum::middleware::Next`, \
+            as in `request: axum::extract::Request`",
+        )
+        .into_compile_error();
+    }
+
     quote! {}
 }
 
+/// `from_fn_with_state` requires the state to be `Clone + Send + Sync + 'static`.
+fn check_middleware_state(item_fn: &ItemFn, state_ty: &Type) -> TokenStream {
+    let span = state_ty.span();
+    let name = format_ident!("__axum_macros_check_{}_state", item_fn.sig.ident);
+
+    quote_spanned! {span=>
+        #[allow(warnings)]
+        #[doc(hidden)]
+        fn #name() {
+            fn check<S>()
+            where
+                S: ::std::clone::Clone + ::std::marker::Send + ::std::marker::Sync + 'static,
+            {}
+
+            check::<#state_ty>();
+        }
+    }
+}
+
 fn skip_next_arg(arg: &FnArg, kind: FunctionKind) -> bool {
     match kind {
         FunctionKind::Handler => true,
         FunctionKind::Middleware => match arg {
             FnArg::Receiver(_) => true,
             FnArg::Typed(pat_type) => {
-                if let Type::Path(type_path) = &*pat_type.ty {
+                // also match references so `&mut Next` gets a targeted error
+                let ty = match &*pat_type.ty {
+                    Type::Reference(reference) => &*reference.elem,
+                    ty => ty,
+                };
+                if let Type::Path(type_path) = ty {
                     type_path
                         .path
                         .segments
@@ -0,0 +1,182 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt, parse::Parse, Fields, ItemStruct, LitStr, Token};
+
+use crate::attr_parsing::{
+    combine_attribute, combine_unary_attribute, parse_assignment_attribute, parse_attrs,
+    parse_doc_comment, Combine,
+};
+
+pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
+    let ItemStruct {
+        attrs,
+        ident,
+        generics,
+        fields,
+        ..
+    } = &item;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let ContainerAttrs {
+        path,
+        query,
+        header,
+    } = parse_attrs("parameters", attrs)?;
+    let constructor = match (path, query, header) {
+        (Some(_), None, None) => quote! { path },
+        (None, Some(_) | None, None) => quote! { query },
+        (None, None, Some(_)) => quote! { header },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "only one of `path`, `query`, and `header` can be used",
+            ))
+        }
+    };
+
+    let Fields::Named(fields) = fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`#[derive(DescribeParameters)]` only supports structs with named fields",
+        ));
+    };
+
+    let parameters = fields
+        .named
+        .iter()
+        .map(|field| {
+            let FieldAttrs { rename } = parse_attrs("parameters", &field.attrs)?;
+            let name = match rename {
+                Some((_, rename)) => rename.value(),
+                None => field.ident.as_ref().unwrap().unraw().to_string(),
+            };
+
+            let mut parameter = quote! {
+                ::axum::routing::ParameterDocs::#constructor(#name)
+            };
+            if let Some(description) = parse_doc_comment(&field.attrs) {
+                parameter = quote! { #parameter.description(#description) };
+            }
+            if !is_option(&field.ty) {
+                parameter = quote! { #parameter.required(true) };
+            }
+            Ok(parameter)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::axum::routing::DescribeParameters for #ident #ty_generics
+        #where_clause
+        {
+            fn parameters() -> ::std::vec::Vec<::axum::routing::ParameterDocs> {
+                ::std::vec![#(#parameters),*]
+            }
+        }
+    })
+}
+
+/// Whether `ty` looks like an `Option`, in which case the parameter isn't required.
+fn is_option(ty: &syn::Type) -> bool {
+    let syn::Type::Path(ty) = ty else {
+        return false;
+    };
+    ty.path
+        .segments
+        .last()
+        .map_or(false, |segment| segment.ident == "Option")
+}
+
+mod kw {
+    syn::custom_keyword!(path);
+    syn::custom_keyword!(query);
+    syn::custom_keyword!(header);
+    syn::custom_keyword!(rename);
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    path: Option<kw::path>,
+    query: Option<kw::query>,
+    header: Option<kw::header>,
+}
+
+impl Parse for ContainerAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut path = None;
+        let mut query = None;
+        let mut header = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::path) {
+                combine_unary_attribute(&mut path, Some(input.parse()?))?;
+            } else if lh.peek(kw::query) {
+                combine_unary_attribute(&mut query, Some(input.parse()?))?;
+            } else if lh.peek(kw::header) {
+                combine_unary_attribute(&mut header, Some(input.parse()?))?;
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self {
+            path,
+            query,
+            header,
+        })
+    }
+}
+
+impl Combine for ContainerAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            path,
+            query,
+            header,
+        } = other;
+        combine_unary_attribute(&mut self.path, path)?;
+        combine_unary_attribute(&mut self.query, query)?;
+        combine_unary_attribute(&mut self.header, header)?;
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<(kw::rename, LitStr)>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut rename = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::rename) {
+                parse_assignment_attribute(input, &mut rename)?;
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self { rename })
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self { rename } = other;
+        combine_attribute(&mut self.rename, rename)?;
+        Ok(self)
+    }
+}
+
+#[test]
+fn ui() {
+    crate::run_ui_tests("describe_parameters");
+}
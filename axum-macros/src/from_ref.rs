@@ -2,11 +2,14 @@ use proc_macro2::{Ident, TokenStream};
 use quote::quote_spanned;
 use syn::{
     parse::{Parse, ParseStream},
+    punctuated::Punctuated,
     spanned::Spanned,
-    Field, ItemStruct, Token, Type,
+    Field, GenericArgument, ItemStruct, PathArguments, Token, Type,
 };
 
-use crate::attr_parsing::{combine_unary_attribute, parse_attrs, Combine};
+use crate::attr_parsing::{
+    combine_attribute, combine_unary_attribute, parse_attrs, parse_parenthesized_attribute, Combine,
+};
 
 pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
     if !item.generics.params.is_empty() {
@@ -27,35 +30,78 @@ pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
 }
 
 fn expand_field(state: &Ident, idx: usize, field: &Field) -> TokenStream {
-    let FieldAttrs { skip } = match parse_attrs("from_ref", &field.attrs) {
+    let FieldAttrs {
+        skip,
+        deref,
+        nested,
+    } = match parse_attrs("from_ref", &field.attrs) {
         Ok(attrs) => attrs,
         Err(err) => return err.into_compile_error(),
     };
 
-    if skip.is_some() {
+    if let Some(skip) = skip {
+        if deref.is_some() || nested.is_some() {
+            return syn::Error::new_spanned(skip, "`skip` can't be combined with other options")
+                .into_compile_error();
+        }
         return TokenStream::default();
     }
 
     let field_ty = &field.ty;
     let span = field.ty.span();
 
-    let body = if let Some(field_ident) = &field.ident {
-        if matches!(field_ty, Type::Reference(_)) {
-            quote_spanned! {span=> state.#field_ident }
-        } else {
-            quote_spanned! {span=> state.#field_ident.clone() }
-        }
+    let member = if let Some(field_ident) = &field.ident {
+        quote_spanned! {span=> state.#field_ident }
     } else {
         let idx = syn::Index {
             index: idx as _,
             span: field.span(),
         };
-        quote_spanned! {span=> state.#idx.clone() }
+        quote_spanned! {span=> state.#idx }
+    };
+
+    let body = if matches!(field_ty, Type::Reference(_)) {
+        member.clone()
+    } else {
+        quote_spanned! {span=> #member.clone() }
     };
 
-    quote_spanned! {span=>
+    let mut impls = impl_from_ref(state, field_ty, &body);
+
+    // the type the sub-states are extracted from, `T` for `#[from_ref(deref)]` fields
+    let (inner_ty, inner) = match deref {
+        Some(deref) => {
+            let Some(inner_ty) = deref_target(field_ty) else {
+                return syn::Error::new_spanned(
+                    deref,
+                    "`deref` requires a field with a type such as `Arc<T>`, `Box<T>`, or \
+                     `&'static T`",
+                )
+                .into_compile_error();
+            };
+            let body = quote_spanned! {span=>
+                <#inner_ty as ::std::clone::Clone>::clone(&#member)
+            };
+            impls.extend(impl_from_ref(state, inner_ty, &body));
+            (inner_ty, quote_spanned! {span=> &*#member })
+        }
+        None => (field_ty, quote_spanned! {span=> &#member }),
+    };
+
+    for sub_state in nested.into_iter().flat_map(|(_, types)| types.0) {
+        let body = quote_spanned! {sub_state.span()=>
+            <#sub_state as ::axum::extract::FromRef<#inner_ty>>::from_ref(#inner)
+        };
+        impls.extend(impl_from_ref(state, &sub_state, &body));
+    }
+
+    impls
+}
+
+fn impl_from_ref(state: &Ident, ty: &Type, body: &TokenStream) -> TokenStream {
+    quote_spanned! {ty.span()=>
         #[allow(clippy::clone_on_copy)]
-        impl ::axum::extract::FromRef<#state> for #field_ty {
+        impl ::axum::extract::FromRef<#state> for #ty {
             fn from_ref(state: &#state) -> Self {
                 #body
             }
@@ -63,23 +109,59 @@ fn expand_field(state: &Ident, idx: usize, field: &Field) -> TokenStream {
     }
 }
 
+/// `T` if `ty` is `&T`, or looks like `Arc<T>`, `Box<T>`, or another type with a single generic
+/// type.
+fn deref_target(ty: &Type) -> Option<&Type> {
+    let ty = match ty {
+        Type::Reference(reference) => return Some(&reference.elem),
+        Type::Path(ty) => ty,
+        _ => return None,
+    };
+    let PathArguments::AngleBracketed(args) = &ty.path.segments.last()?.arguments else {
+        return None;
+    };
+    let mut args = args.args.iter();
+    match (args.next(), args.next()) {
+        (Some(GenericArgument::Type(inner)), None) => Some(inner),
+        _ => None,
+    }
+}
+
 mod kw {
     syn::custom_keyword!(skip);
+    syn::custom_keyword!(deref);
+    syn::custom_keyword!(nested);
 }
 
 #[derive(Default)]
 pub(super) struct FieldAttrs {
     pub(super) skip: Option<kw::skip>,
+    pub(super) deref: Option<kw::deref>,
+    pub(super) nested: Option<(kw::nested, Types)>,
+}
+
+pub(super) struct Types(Punctuated<Type, Token![,]>);
+
+impl Parse for Types {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Punctuated::parse_terminated(input).map(Self)
+    }
 }
 
 impl Parse for FieldAttrs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut skip = None;
+        let mut deref = None;
+        let mut nested = None;
 
         while !input.is_empty() {
             let lh = input.lookahead1();
             if lh.peek(kw::skip) {
                 skip = Some(input.parse()?);
+            } else if lh.peek(kw::deref) {
+                combine_unary_attribute(&mut deref, Some(input.parse()?))?;
+            } else if lh.peek(kw::nested) {
+                parse_parenthesized_attribute(input, &mut nested)?;
             } else {
                 return Err(lh.error());
             }
@@ -87,14 +169,24 @@ impl Parse for FieldAttrs {
             let _ = input.parse::<Token![,]>();
         }
 
-        Ok(Self { skip })
+        Ok(Self {
+            skip,
+            deref,
+            nested,
+        })
     }
 }
 
 impl Combine for FieldAttrs {
     fn combine(mut self, other: Self) -> syn::Result<Self> {
-        let Self { skip } = other;
+        let Self {
+            skip,
+            deref,
+            nested,
+        } = other;
         combine_unary_attribute(&mut self.skip, skip)?;
+        combine_unary_attribute(&mut self.deref, deref)?;
+        combine_attribute(&mut self.nested, nested)?;
         Ok(self)
     }
 }
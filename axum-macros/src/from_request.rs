@@ -4,7 +4,7 @@ use crate::{
     from_request::attr::FromRequestFieldAttrs,
 };
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned, ToTokens};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use std::{collections::HashSet, fmt, iter};
 use syn::{
     parse_quote, punctuated::Punctuated, spanned::Spanned, Fields, Ident, Path, Token, Type,
@@ -343,14 +343,7 @@ fn impl_struct_by_extracting_each_field(
         State::CannotInfer => quote! {
             ::std::unimplemented!()
         },
-        _ => {
-            let extract_fields = extract_fields(&fields, &rejection, tr)?;
-            quote! {
-                ::std::result::Result::Ok(Self {
-                    #(#extract_fields)*
-                })
-            }
-        }
+        _ => extract_fields(&fields, &rejection, tr)?,
     };
 
     let rejection_ident = if let Some(rejection) = rejection {
@@ -370,6 +363,7 @@ fn impl_struct_by_extracting_each_field(
         .collect::<Punctuated<Type, Token![,]>>();
 
     let state_bounds = state.bounds();
+    let flattened_field_bounds = flattened_field_bounds(&fields, state)?;
 
     Ok(match tr {
         Trait::FromRequest => quote! {
@@ -378,6 +372,7 @@ fn impl_struct_by_extracting_each_field(
             impl<#impl_generics> ::axum::extract::FromRequest<#trait_generics> for #ident
             where
                 #state_bounds
+                #flattened_field_bounds
             {
                 type Rejection = #rejection_ident;
 
@@ -395,6 +390,7 @@ fn impl_struct_by_extracting_each_field(
             impl<#impl_generics> ::axum::extract::FromRequestParts<#trait_generics> for #ident
             where
                 #state_bounds
+                #flattened_field_bounds
             {
                 type Rejection = #rejection_ident;
 
@@ -421,7 +417,7 @@ fn extract_fields(
     fields: &syn::Fields,
     rejection: &Option<syn::Path>,
     tr: Trait,
-) -> syn::Result<Vec<TokenStream>> {
+) -> syn::Result<TokenStream> {
     fn member(field: &syn::Field, index: usize) -> TokenStream {
         match &field.ident {
             Some(ident) => quote! { #ident },
@@ -448,158 +444,23 @@ fn extract_fields(
         }
     }
 
-    let mut fields_iter = fields.iter();
-
-    let last = match tr {
-        // Use FromRequestParts for all elements except the last
-        Trait::FromRequest => fields_iter.next_back(),
-        // Use FromRequestParts for all elements
-        Trait::FromRequestParts => None,
-    };
-
-    let mut res: Vec<_> = fields_iter
-        .enumerate()
-        .map(|(index, field)| {
-            let FromRequestFieldAttrs { via } = parse_attrs("from_request", &field.attrs)?;
-
-            let member = member(field, index);
-            let ty_span = field.ty.span();
-            let into_inner = into_inner(via, ty_span);
-
-            if peel_option(&field.ty).is_some() {
-                let tokens = match tr {
-                    Trait::FromRequest => {
-                        quote_spanned! {ty_span=>
-                            #member: {
-                                let (mut parts, body) = req.into_parts();
-                                let value =
-                                    ::axum::extract::FromRequestParts::from_request_parts(
-                                        &mut parts,
-                                        state,
-                                    )
-                                    .await
-                                    .ok()
-                                    .map(#into_inner);
-                                req = ::axum::http::Request::from_parts(parts, body);
-                                value
-                            },
-                        }
-                    }
-                    Trait::FromRequestParts => {
-                        quote_spanned! {ty_span=>
-                            #member: {
-                                ::axum::extract::FromRequestParts::from_request_parts(
-                                    parts,
-                                    state,
-                                )
-                                .await
-                                .ok()
-                                .map(#into_inner)
-                            },
-                        }
-                    }
-                };
-                Ok(tokens)
-            } else if peel_result_ok(&field.ty).is_some() {
-                let tokens = match tr {
-                    Trait::FromRequest => {
-                        quote_spanned! {ty_span=>
-                            #member: {
-                                let (mut parts, body) = req.into_parts();
-                                let value =
-                                    ::axum::extract::FromRequestParts::from_request_parts(
-                                        &mut parts,
-                                        state,
-                                    )
-                                    .await
-                                    .map(#into_inner);
-                                req = ::axum::http::Request::from_parts(parts, body);
-                                value
-                            },
-                        }
-                    }
-                    Trait::FromRequestParts => {
-                        quote_spanned! {ty_span=>
-                            #member: {
-                                ::axum::extract::FromRequestParts::from_request_parts(
-                                    parts,
-                                    state,
-                                )
-                                .await
-                                .map(#into_inner)
-                            },
-                        }
-                    }
-                };
-                Ok(tokens)
-            } else {
-                let map_err = if let Some(rejection) = rejection {
-                    quote! { <#rejection as ::std::convert::From<_>>::from }
-                } else {
-                    quote! { ::axum::response::IntoResponse::into_response }
-                };
-
-                let tokens = match tr {
-                    Trait::FromRequest => {
-                        quote_spanned! {ty_span=>
-                            #member: {
-                                let (mut parts, body) = req.into_parts();
-                                let value =
-                                    ::axum::extract::FromRequestParts::from_request_parts(
-                                        &mut parts,
-                                        state,
-                                    )
-                                    .await
-                                    .map(#into_inner)
-                                    .map_err(#map_err)?;
-                                req = ::axum::http::Request::from_parts(parts, body);
-                                value
-                            },
-                        }
-                    }
-                    Trait::FromRequestParts => {
-                        quote_spanned! {ty_span=>
-                            #member: {
-                                ::axum::extract::FromRequestParts::from_request_parts(
-                                    parts,
-                                    state,
-                                )
-                                .await
-                                .map(#into_inner)
-                                .map_err(#map_err)?
-                            },
-                        }
-                    }
-                };
-                Ok(tokens)
-            }
-        })
-        .collect::<syn::Result<_>>()?;
-
-    // Handle the last element, if deriving FromRequest
-    if let Some(field) = last {
-        let FromRequestFieldAttrs { via } = parse_attrs("from_request", &field.attrs)?;
-
-        let member = member(field, fields.len() - 1);
+    // `extract` is the awaited call to `from_request` or `from_request_parts`
+    fn field_value(
+        field: &syn::Field,
+        via: Option<(attr::kw::via, syn::Path)>,
+        extract: TokenStream,
+        rejection: &Option<syn::Path>,
+    ) -> TokenStream {
         let ty_span = field.ty.span();
         let into_inner = into_inner(via, ty_span);
 
-        let item = if peel_option(&field.ty).is_some() {
+        if peel_option(&field.ty).is_some() {
             quote_spanned! {ty_span=>
-                #member: {
-                    ::axum::extract::FromRequest::from_request(req, state)
-                        .await
-                        .ok()
-                        .map(#into_inner)
-                },
+                #extract.ok().map(#into_inner)
             }
         } else if peel_result_ok(&field.ty).is_some() {
             quote_spanned! {ty_span=>
-                #member: {
-                    ::axum::extract::FromRequest::from_request(req, state)
-                        .await
-                        .map(#into_inner)
-                },
+                #extract.map(#into_inner)
             }
         } else {
             let map_err = if let Some(rejection) = rejection {
@@ -609,19 +470,176 @@ fn extract_fields(
             };
 
             quote_spanned! {ty_span=>
-                #member: {
-                    ::axum::extract::FromRequest::from_request(req, state)
-                        .await
-                        .map(#into_inner)
-                        .map_err(#map_err)?
-                },
+                #extract.map(#into_inner).map_err(#map_err)?
+            }
+        }
+    }
+
+    let fields = fields
+        .iter()
+        .map(|field| {
+            let attrs = parse_attrs::<FromRequestFieldAttrs>("from_request", &field.attrs)?;
+            if let (Some(flatten), Some(_)) = (&attrs.flatten, &attrs.via) {
+                return Err(syn::Error::new_spanned(
+                    flatten,
+                    "`flatten` can't be combined with `via`",
+                ));
+            }
+            Ok((field, attrs))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let body_index = match tr {
+        Trait::FromRequest => body_field_index(&fields)?,
+        // `FromRequestParts` can't extract the body
+        Trait::FromRequestParts => None,
+    };
+
+    let mut members = Vec::with_capacity(fields.len());
+    let mut extract_parts = Vec::with_capacity(fields.len());
+    let mut extract_body = None;
+
+    for (index, (field, FromRequestFieldAttrs { via, .. })) in fields.into_iter().enumerate() {
+        let member = member(field, index);
+        let binding = format_ident!("__field{}", index);
+        members.push(quote! { #member: #binding });
+
+        let ty_span = field.ty.span();
+
+        if Some(index) == body_index {
+            let extract = quote_spanned! {ty_span=>
+                ::axum::extract::FromRequest::from_request(req, state).await
+            };
+            let value = field_value(field, via, extract, rejection);
+            extract_body = Some(quote_spanned! {ty_span=>
+                let #binding = #value;
+            });
+            continue;
+        }
+
+        let tokens = match tr {
+            Trait::FromRequest => {
+                let extract = quote_spanned! {ty_span=>
+                    ::axum::extract::FromRequestParts::from_request_parts(&mut parts, state).await
+                };
+                let value = field_value(field, via, extract, rejection);
+                quote_spanned! {ty_span=>
+                    let #binding = {
+                        let (mut parts, body) = req.into_parts();
+                        let value = #value;
+                        req = ::axum::http::Request::from_parts(parts, body);
+                        value
+                    };
+                }
+            }
+            Trait::FromRequestParts => {
+                let extract = quote_spanned! {ty_span=>
+                    ::axum::extract::FromRequestParts::from_request_parts(parts, state).await
+                };
+                let value = field_value(field, via, extract, rejection);
+                quote_spanned! {ty_span=>
+                    let #binding = #value;
+                }
             }
         };
+        extract_parts.push(tokens);
+    }
 
-        res.push(item);
+    // The body is extracted last, after all the other fields have had access to the parts
+    let extract_body = match (tr, extract_body) {
+        (_, Some(extract_body)) => extract_body,
+        (Trait::FromRequest, None) if !members.is_empty() => quote! {
+            ::std::mem::drop(req);
+        },
+        _ => quote! {},
+    };
+
+    Ok(quote! {
+        #(#extract_parts)*
+        #extract_body
+        ::std::result::Result::Ok(Self {
+            #(#members,)*
+        })
+    })
+}
+
+/// Types that extract the request body, and so have to be extracted after every other field.
+const BODY_EXTRACTORS: &[&str] = &[
+    "Body",
+    "Bytes",
+    "Form",
+    "Json",
+    "JsonLines",
+    "Multipart",
+    "Protobuf",
+    "RawBody",
+    "RawForm",
+    "Request",
+    "String",
+    "TypedMultipart",
+];
+
+/// Find the field that extracts the request body with `FromRequest`.
+///
+/// That is the field whose type, or `via` extractor, is a well known body extractor. If there is
+/// no such field, the last field is used unless it is flattened.
+fn body_field_index(fields: &[(&syn::Field, FromRequestFieldAttrs)]) -> syn::Result<Option<usize>> {
+    let mut body_fields = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, (field, attrs))| attrs.flatten.is_none() && extracts_body(field, attrs));
+
+    match (body_fields.next(), body_fields.next()) {
+        (Some(_), Some((_, (field, _)))) => Err(syn::Error::new_spanned(
+            &field.ty,
+            "only one field can extract the request body",
+        )),
+        (Some((index, _)), None) => Ok(Some(index)),
+        (None, _) => Ok(fields
+            .len()
+            .checked_sub(1)
+            .filter(|&last| fields[last].1.flatten.is_none())),
     }
+}
+
+fn extracts_body(field: &syn::Field, attrs: &FromRequestFieldAttrs) -> bool {
+    let extractor = match &attrs.via {
+        Some((_, via)) => via.segments.last(),
+        None => {
+            let ty = peel_option(&field.ty)
+                .or_else(|| peel_result_ok(&field.ty))
+                .unwrap_or(&field.ty);
+            match ty {
+                syn::Type::Path(type_path) => type_path.path.segments.last(),
+                _ => None,
+            }
+        }
+    };
+
+    matches!(
+        extractor,
+        Some(segment) if BODY_EXTRACTORS.iter().any(|name| segment.ident == name)
+    )
+}
+
+/// Flattened fields are often extractors derived for a concrete state, so a generic
+/// implementation has to require that they can be extracted with its state.
+fn flattened_field_bounds(fields: &syn::Fields, state: &State) -> syn::Result<TokenStream> {
+    let State::Default(state) = state else {
+        return Ok(quote! {});
+    };
 
-    Ok(res)
+    let mut bounds = TokenStream::new();
+    for field in fields {
+        let FromRequestFieldAttrs { flatten, .. } = parse_attrs("from_request", &field.attrs)?;
+        if flatten.is_some() {
+            let ty = &field.ty;
+            bounds.extend(quote_spanned! {ty.span()=>
+                #ty: ::axum::extract::FromRequestParts<#state>,
+            });
+        }
+    }
+    Ok(bounds)
 }
 
 fn peel_option(ty: &syn::Type) -> Option<&syn::Type> {
@@ -702,7 +720,7 @@ fn impl_struct_by_extracting_all_at_once(
     };
 
     for field in fields {
-        let FromRequestFieldAttrs { via } = parse_attrs("from_request", &field.attrs)?;
+        let FromRequestFieldAttrs { via, flatten } = parse_attrs("from_request", &field.attrs)?;
 
         if let Some((via, _)) = via {
             return Err(syn::Error::new_spanned(
@@ -711,6 +729,14 @@ fn impl_struct_by_extracting_all_at_once(
                 together with `#[from_request(...)]` on the container",
             ));
         }
+
+        if let Some(flatten) = flatten {
+            return Err(syn::Error::new_spanned(
+                flatten,
+                "`#[from_request(flatten)]` cannot be used \
+                together with `#[from_request(via(...))]` on the container",
+            ));
+        }
     }
 
     let path_span = via_path.span();
@@ -867,7 +893,7 @@ fn impl_enum_by_extracting_all_at_once(
     tr: Trait,
 ) -> syn::Result<TokenStream> {
     for variant in variants {
-        let FromRequestFieldAttrs { via } = parse_attrs("from_request", &variant.attrs)?;
+        let FromRequestFieldAttrs { via, flatten } = parse_attrs("from_request", &variant.attrs)?;
 
         if let Some((via, _)) = via {
             return Err(syn::Error::new_spanned(
@@ -876,6 +902,13 @@ fn impl_enum_by_extracting_all_at_once(
             ));
         }
 
+        if let Some(flatten) = flatten {
+            return Err(syn::Error::new_spanned(
+                flatten,
+                "`#[from_request(flatten)]` cannot be used on variants",
+            ));
+        }
+
         let fields = match variant.fields {
             syn::Fields::Named(fields) => fields.named.into_iter(),
             syn::Fields::Unnamed(fields) => fields.unnamed.into_iter(),
@@ -883,13 +916,19 @@ fn impl_enum_by_extracting_all_at_once(
         };
 
         for field in fields {
-            let FromRequestFieldAttrs { via } = parse_attrs("from_request", &field.attrs)?;
+            let FromRequestFieldAttrs { via, flatten } = parse_attrs("from_request", &field.attrs)?;
             if let Some((via, _)) = via {
                 return Err(syn::Error::new_spanned(
                     via,
                     "`#[from_request(via(...))]` cannot be used inside variants",
                 ));
             }
+            if let Some(flatten) = flatten {
+                return Err(syn::Error::new_spanned(
+                    flatten,
+                    "`#[from_request(flatten)]` cannot be used inside variants",
+                ));
+            }
         }
     }
 
@@ -1005,7 +1044,7 @@ fn infer_state_type_from_field_attributes(fields: &Fields) -> impl Iterator<Item
             Box::new(fields_named.named.iter().filter_map(|field| {
                 // TODO(david): it's a little wasteful to parse the attributes again here
                 // ideally we should parse things once and pass the data down
-                let FromRequestFieldAttrs { via } =
+                let FromRequestFieldAttrs { via, .. } =
                     parse_attrs("from_request", &field.attrs).ok()?;
                 let (_, via_path) = via?;
                 path_ident_is_state(&via_path).then(|| field.ty.clone())
@@ -1015,7 +1054,7 @@ fn infer_state_type_from_field_attributes(fields: &Fields) -> impl Iterator<Item
             Box::new(fields_unnamed.unnamed.iter().filter_map(|field| {
                 // TODO(david): it's a little wasteful to parse the attributes again here
                 // ideally we should parse things once and pass the data down
-                let FromRequestFieldAttrs { via } =
+                let FromRequestFieldAttrs { via, .. } =
                     parse_attrs("from_request", &field.attrs).ok()?;
                 let (_, via_path) = via?;
                 path_ident_is_state(&via_path).then(|| field.ty.clone())
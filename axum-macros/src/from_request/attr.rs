@@ -1,4 +1,6 @@
-use crate::attr_parsing::{combine_attribute, parse_parenthesized_attribute, Combine};
+use crate::attr_parsing::{
+    combine_attribute, combine_unary_attribute, parse_parenthesized_attribute, Combine,
+};
 use syn::{
     parse::{Parse, ParseStream},
     Token,
@@ -8,6 +10,7 @@ pub(crate) mod kw {
     syn::custom_keyword!(via);
     syn::custom_keyword!(rejection);
     syn::custom_keyword!(state);
+    syn::custom_keyword!(flatten);
 }
 
 #[derive(Default)]
@@ -63,16 +66,20 @@ impl Combine for FromRequestContainerAttrs {
 #[derive(Default)]
 pub(super) struct FromRequestFieldAttrs {
     pub(super) via: Option<(kw::via, syn::Path)>,
+    pub(super) flatten: Option<kw::flatten>,
 }
 
 impl Parse for FromRequestFieldAttrs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut via = None;
+        let mut flatten = None;
 
         while !input.is_empty() {
             let lh = input.lookahead1();
             if lh.peek(kw::via) {
                 parse_parenthesized_attribute(input, &mut via)?;
+            } else if lh.peek(kw::flatten) {
+                combine_unary_attribute(&mut flatten, Some(input.parse()?))?;
             } else {
                 return Err(lh.error());
             }
@@ -80,14 +87,15 @@ impl Parse for FromRequestFieldAttrs {
             let _ = input.parse::<Token![,]>();
         }
 
-        Ok(Self { via })
+        Ok(Self { via, flatten })
     }
 }
 
 impl Combine for FromRequestFieldAttrs {
     fn combine(mut self, other: Self) -> syn::Result<Self> {
-        let Self { via } = other;
+        let Self { via, flatten } = other;
         combine_attribute(&mut self.via, via)?;
+        combine_unary_attribute(&mut self.flatten, flatten)?;
         Ok(self)
     }
 }
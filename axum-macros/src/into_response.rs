@@ -0,0 +1,189 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse::Parse, spanned::Spanned, Fields, ItemEnum, LitInt, LitStr, Token, Variant};
+
+use crate::attr_parsing::{
+    combine_attribute, combine_unary_attribute, parse_assignment_attribute, parse_attrs, Combine,
+};
+
+pub(crate) fn expand(item: ItemEnum) -> syn::Result<TokenStream> {
+    let ItemEnum {
+        ident,
+        generics,
+        variants,
+        ..
+    } = &item;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = variants
+        .iter()
+        .map(expand_variant)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::axum::response::IntoResponse for #ident #ty_generics #where_clause {
+            fn into_response(self) -> ::axum::response::Response {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+mod kw {
+    syn::custom_keyword!(status);
+    syn::custom_keyword!(message);
+    syn::custom_keyword!(transparent);
+}
+
+#[derive(Default)]
+struct VariantAttrs {
+    status: Option<(kw::status, LitInt)>,
+    message: Option<(kw::message, LitStr)>,
+    transparent: Option<kw::transparent>,
+}
+
+impl Parse for VariantAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut status = None;
+        let mut message = None;
+        let mut transparent = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::status) {
+                parse_assignment_attribute(input, &mut status)?;
+            } else if lh.peek(kw::message) {
+                parse_assignment_attribute(input, &mut message)?;
+            } else if lh.peek(kw::transparent) {
+                combine_unary_attribute(&mut transparent, Some(input.parse()?))?;
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self {
+            status,
+            message,
+            transparent,
+        })
+    }
+}
+
+impl Combine for VariantAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            status,
+            message,
+            transparent,
+        } = other;
+        combine_attribute(&mut self.status, status)?;
+        combine_attribute(&mut self.message, message)?;
+        combine_unary_attribute(&mut self.transparent, transparent)?;
+        Ok(self)
+    }
+}
+
+fn expand_variant(variant: &Variant) -> syn::Result<TokenStream> {
+    let VariantAttrs {
+        status,
+        message,
+        transparent,
+    } = parse_attrs("response", &variant.attrs)?;
+
+    let ident = &variant.ident;
+    let bindings = (0..variant.fields.len())
+        .map(|idx| format_ident!("__field{}", idx))
+        .collect::<Vec<_>>();
+    let pattern = match &variant.fields {
+        Fields::Unit => quote! { Self::#ident },
+        Fields::Unnamed(_) => quote! { Self::#ident(#(#bindings),*) },
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { Self::#ident { #(#names: #bindings),* } }
+        }
+    };
+
+    if let Some(transparent) = transparent {
+        if let Some((kw, _)) = &status {
+            return Err(syn::Error::new_spanned(
+                kw,
+                "`status` can't be used with `transparent`",
+            ));
+        }
+        if let Some((kw, _)) = &message {
+            return Err(syn::Error::new_spanned(
+                kw,
+                "`message` can't be used with `transparent`",
+            ));
+        }
+        let [field] = &bindings[..] else {
+            return Err(syn::Error::new_spanned(
+                transparent,
+                "`transparent` variants must have exactly one field",
+            ));
+        };
+        let ty = &variant.fields.iter().next().unwrap().ty;
+        let into_response = quote_spanned! {ty.span()=>
+            ::axum::response::IntoResponse::into_response(#field)
+        };
+        return Ok(quote! {
+            #pattern => #into_response,
+        });
+    }
+
+    let Some((_, status)) = status else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "missing `#[response(status = ...)]` or `#[response(transparent)]`",
+        ));
+    };
+    let code = status.base10_parse::<u16>()?;
+    if !(100..1000).contains(&code) {
+        return Err(syn::Error::new_spanned(
+            status,
+            "status codes must be between 100 and 999",
+        ));
+    }
+    let status = quote! {
+        ::axum::http::StatusCode::from_u16(#code).unwrap()
+    };
+
+    let body = match (message, &bindings[..]) {
+        (Some((_, message)), _) => {
+            return Ok(quote! {
+                Self::#ident { .. } => {
+                    ::axum::response::IntoResponse::into_response((#status, #message))
+                }
+            });
+        }
+        (None, []) => quote! {
+            ::axum::response::IntoResponse::into_response(#status)
+        },
+        (None, [field]) => {
+            let ty = &variant.fields.iter().next().unwrap().ty;
+            quote_spanned! {ty.span()=>
+                ::axum::response::IntoResponse::into_response((#status, #field))
+            }
+        }
+        (None, _) => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "variants with more than one field must have a `message`",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #pattern => #body,
+    })
+}
+
+#[test]
+fn ui() {
+    crate::run_ui_tests("into_response");
+}
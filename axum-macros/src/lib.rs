@@ -53,8 +53,13 @@ mod attr_parsing;
 #[cfg(feature = "__private")]
 mod axum_test;
 mod debug_handler;
+mod describe_parameters;
 mod from_ref;
 mod from_request;
+mod into_response;
+mod query_params;
+mod route;
+mod try_from_multipart;
 mod typed_path;
 mod with_position;
 
@@ -98,7 +103,11 @@ use from_request::Trait::{FromRequest, FromRequestParts};
 ///
 /// This requires that each field is an extractor (i.e. implements [`FromRequest`]).
 ///
-/// Note that only the last field can consume the request body. Therefore this doesn't compile:
+/// Only one field can consume the request body. If a field is one of axum's well known body
+/// extractors, such as `Json`, `Form`, `Bytes` or `String`, it is extracted with
+/// [`FromRequest`] after all the other fields, regardless of where it's declared. Otherwise the
+/// last field is extracted with [`FromRequest`]. All other fields are extracted with
+/// [`FromRequestParts`], so they must not consume the body. Therefore this doesn't compile:
 ///
 /// ```compile_fail
 /// use axum_macros::FromRequest;
@@ -106,13 +115,60 @@ use from_request::Trait::{FromRequest, FromRequestParts};
 ///
 /// #[derive(FromRequest)]
 /// struct MyExtractor {
-///     // only the last field can implement `FromRequest`
-///     // other fields must only implement `FromRequestParts`
+///     // only one field can consume the request body
 ///     bytes: Bytes,
 ///     string: String,
 /// }
 /// ```
 ///
+/// ## Flattening other extractors
+///
+/// Extractors derived with `#[derive(FromRequestParts)]` can be reused in other extractors with
+/// `#[from_request(flatten)]`:
+///
+/// ```
+/// use axum_macros::{FromRequest, FromRequestParts};
+/// use axum::{
+///     extract::{Path, State},
+///     http::HeaderMap,
+///     Json,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(FromRequestParts)]
+/// struct Common {
+///     headers: HeaderMap,
+///     state: State<AppState>,
+/// }
+///
+/// #[derive(FromRequest)]
+/// struct CreateUser {
+///     #[from_request(flatten)]
+///     common: Common,
+///     path: Path<u32>,
+///     payload: Json<NewUser>,
+/// }
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     // ...
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct NewUser {
+///     // ...
+/// }
+///
+/// async fn handler(extractor: CreateUser) {}
+/// #
+/// # let _: axum::Router = axum::Router::new()
+/// #     .route("/users/:id", axum::routing::post(handler))
+/// #     .with_state(AppState {});
+/// ```
+///
+/// Flattened fields are always extracted with [`FromRequestParts`] and the state they require
+/// is carried over to the outer extractor. `flatten` can't be combined with `via`.
+///
 /// ## Extracting via another extractor
 ///
 /// You can use `#[from_request(via(...))]` to extract a field via another extractor, meaning the
@@ -409,6 +465,7 @@ use from_request::Trait::{FromRequest, FromRequestParts};
 /// ```
 ///
 /// [`FromRequest`]: https://docs.rs/axum/0.7/axum/extract/trait.FromRequest.html
+/// [`FromRequestParts`]: https://docs.rs/axum/0.7/axum/extract/trait.FromRequestParts.html
 /// [`axum::response::Response`]: https://docs.rs/axum/0.7/axum/response/type.Response.html
 /// [`axum::extract::rejection::ExtensionRejection`]: https://docs.rs/axum/0.7/axum/extract/rejection/enum.ExtensionRejection.html
 #[proc_macro_derive(FromRequest, attributes(from_request))]
@@ -578,6 +635,12 @@ pub fn derive_from_request_parts(item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// Without the [`FromRef`] implementation, `#[debug_handler]` reports that
+/// `InnerState: FromRef<AppState>` isn't satisfied, pointing at `InnerState`, rather than failing
+/// with an error about the whole handler.
+///
+/// [`FromRef`]: https://docs.rs/axum/0.7/axum/extract/trait.FromRef.html
+///
 /// # Limitations
 ///
 /// This macro does not work for functions in an `impl` block that don't have a `self` parameter:
@@ -625,7 +688,18 @@ pub fn debug_handler(_attr: TokenStream, input: TokenStream) -> TokenStream {
 /// Generates better error messages when applied to middleware functions.
 ///
 /// This works similarly to [`#[debug_handler]`](macro@debug_handler) except for middleware using
-/// [`axum::middleware::from_fn`].
+/// [`axum::middleware::from_fn`] or [`axum::middleware::from_fn_with_state`].
+///
+/// In addition to the checks done by `#[debug_handler]`, this verifies that:
+///
+/// - The function takes exactly one [`Next`] as its last argument, by value.
+/// - The function takes the request, or another extractor that implements `FromRequest`, right
+///   before [`Next`].
+/// - The state, if any, is `Clone + Send + Sync + 'static`, as required by
+///   [`axum::middleware::from_fn_with_state`].
+///
+/// The state type is inferred from `State` arguments like with `#[debug_handler]`, or can be set
+/// with `#[debug_middleware(state = MyStateType)]`.
 ///
 /// # Example
 ///
@@ -666,6 +740,8 @@ pub fn debug_handler(_attr: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// [`axum`]: https://docs.rs/axum/latest
 /// [`axum::middleware::from_fn`]: https://docs.rs/axum/0.7/axum/middleware/fn.from_fn.html
+/// [`axum::middleware::from_fn_with_state`]: https://docs.rs/axum/0.7/axum/middleware/fn.from_fn_with_state.html
+/// [`Next`]: https://docs.rs/axum/0.7/axum/middleware/struct.Next.html
 /// [`debug_middleware`]: macro@debug_middleware
 #[proc_macro_attribute]
 pub fn debug_middleware(_attr: TokenStream, input: TokenStream) -> TokenStream {
@@ -678,6 +754,158 @@ pub fn debug_middleware(_attr: TokenStream, input: TokenStream) -> TokenStream {
     });
 }
 
+/// Register a handler for a route, to be collected into a router with [`collect_routes!`].
+///
+/// This is useful in large codebases where keeping all routes in one central router becomes
+/// unwieldy. Instead each handler declares its own method and path:
+///
+/// ```
+/// use axum::{extract::Path, routing::get, Router};
+/// use axum_macros::{collect_routes, route};
+///
+/// #[route(get, "/users")]
+/// async fn list_users() {}
+///
+/// #[route(get, "/users/{id}")]
+/// async fn show_user(Path(id): Path<u32>) {}
+///
+/// mod admin {
+///     use super::AppState;
+///     use axum::extract::State;
+///     use axum_macros::route;
+///
+///     #[route(post, "/admin/users/:id/ban")]
+///     pub(crate) async fn ban_user(State(state): State<AppState>) {}
+/// }
+///
+/// #[derive(Clone)]
+/// struct AppState {}
+///
+/// fn main() {
+///     let app: Router = collect_routes![list_users, show_user, admin::ban_user]
+///         // the result is a regular `Router`
+///         .route("/health", get(|| async {}))
+///         .with_state(AppState {});
+///     # let _ = app;
+/// }
+/// ```
+///
+/// The method is one of `get`, `post`, `put`, `delete`, `patch`, `head`, `options` or `trace`.
+/// Captures can be written as `:id` and `*rest`, or as `{id}` and `{*rest}`.
+///
+/// Routes with the same path and different methods are combined, like when calling
+/// [`Router::route`] multiple times with the same path.
+///
+/// # State
+///
+/// The state type is inferred from `State` arguments like with
+/// [`#[debug_handler]`](macro@debug_handler). Handlers without `State` arguments can be used
+/// with any state. If the state can't be inferred, for example because the handler uses an
+/// extractor that requires a specific state, it can be set with
+/// `#[route(get, "/", state = MyStateType)]`.
+///
+/// # Documentation
+///
+/// `#[route]` attaches [`OperationDocs`] to the route, which are included in
+/// [`Router::export_routes`] for generating OpenAPI documents. The first paragraph of the
+/// handler's doc comment is the summary and the rest is the description. Captures in the path are
+/// documented as path parameters. More can be added in the attribute:
+///
+/// ```
+/// use axum::{extract::{Path, Query}, Json};
+/// use axum_macros::{route, DescribeParameters};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, DescribeParameters)]
+/// struct Pagination {
+///     /// The page to return.
+///     page: Option<u32>,
+/// }
+///
+/// /// List the files of a user.
+/// #[route(
+///     get,
+///     "/users/{id}/files",
+///     // parameters described by types that implement `DescribeParameters`
+///     params(Pagination),
+///     // descriptions of individual parameters. Names that aren't captures are query parameters
+///     param(id = "The id of the user"),
+///     // the possible responses. `description` and `body` are optional
+///     response(status = 200, description = "The files", body = Json<Vec<String>>),
+///     response(status = 404, description = "No such user"),
+/// )]
+/// async fn list_files(Path(id): Path<u32>, Query(pagination): Query<Pagination>) {}
+/// ```
+///
+/// `summary = "..."` and `description = "..."` override the doc comment.
+///
+/// # Limitations
+///
+/// `#[route]` only supports free functions, not methods or generic functions.
+///
+/// [`Router::route`]: https://docs.rs/axum/0.7/axum/struct.Router.html#method.route
+/// [`OperationDocs`]: https://docs.rs/axum/0.7/axum/routing/struct.OperationDocs.html
+/// [`Router::export_routes`]: https://docs.rs/axum/0.7/axum/struct.Router.html#method.export_routes
+#[proc_macro_attribute]
+pub fn route(attr: TokenStream, input: TokenStream) -> TokenStream {
+    expand_attr_with(attr, input, route::expand)
+}
+
+/// Build a `Router` from handlers annotated with [`#[route]`](macro@route).
+///
+/// Takes a comma separated list of paths to handlers. See [`#[route]`](macro@route) for an
+/// example.
+///
+/// Handlers that aren't annotated with `#[route]` result in an error about a missing
+/// `__axum_macros_route_*` function.
+#[proc_macro]
+pub fn collect_routes(input: TokenStream) -> TokenStream {
+    expand_with(input, route::expand_collect_routes)
+}
+
+/// Define a struct for query parameters, with defaults and bounds.
+///
+/// ```
+/// use axum::{extract::Query, routing::get, Router};
+/// use axum_macros::query_params;
+///
+/// query_params! {
+///     #[derive(Debug)]
+///     pub struct ListUsers {
+///         /// The page to return.
+///         pub page: u32 = 1 (min 1),
+///         /// The number of users per page.
+///         pub per_page: u32 = 20 (min 1, max 100),
+///         /// Only return users whose name contains this.
+///         pub q: Option<String>,
+///     }
+/// }
+///
+/// async fn list_users(Query(params): Query<ListUsers>) {
+///     // `?per_page=1000` results in `per_page` being `100`, and without `page` it's `1`
+/// }
+///
+/// let app = Router::new().route("/users", get(list_users));
+/// # let _: Router = app;
+/// ```
+///
+/// The struct implements `serde::Deserialize`, so the crate using the macro must depend on
+/// `serde` with the `derive` feature. Fields with a default (`= expr`) use it when the
+/// parameter is missing. Values outside of the bounds (`(min a, max b)`) are clamped to the
+/// nearest bound rather than rejected. Both are optional. Fields without a default that aren't
+/// an `Option` are required.
+///
+/// `#[serde(...)]` attributes on the struct and its fields are supported.
+///
+/// The struct also implements [`DescribeParameters`] so the parameters, including their doc
+/// comments, defaults, and bounds, can be documented with `#[route(..., params(ListUsers))]`.
+///
+/// [`DescribeParameters`]: https://docs.rs/axum/0.7/axum/routing/trait.DescribeParameters.html
+#[proc_macro]
+pub fn query_params(input: TokenStream) -> TokenStream {
+    expand_with(input, query_params::expand)
+}
+
 /// Private API: Do no use this!
 ///
 /// Attribute macro to be placed on test functions that'll generate two functions:
@@ -704,6 +932,104 @@ pub fn derive_typed_path(input: TokenStream) -> TokenStream {
     expand_with(input, typed_path::expand)
 }
 
+/// Derive an implementation of [`axum_extra::extract::TryFromMultipart`].
+///
+/// See that trait for more details.
+///
+/// [`axum_extra::extract::TryFromMultipart`]: https://docs.rs/axum-extra/latest/axum_extra/extract/trait.TryFromMultipart.html
+#[proc_macro_derive(TryFromMultipart, attributes(multipart))]
+pub fn derive_try_from_multipart(input: TokenStream) -> TokenStream {
+    expand_with(input, try_from_multipart::expand)
+}
+
+/// Derive an implementation of [`DescribeParameters`] for a struct, usually one that is
+/// extracted with `Query` or `Path`.
+///
+/// Each field is a parameter named after the field and described by its doc comment. Fields that
+/// aren't an `Option` are required.
+///
+/// # Example
+///
+/// ```
+/// use axum::routing::DescribeParameters;
+///
+/// #[derive(DescribeParameters)]
+/// struct Pagination {
+///     /// The page to return.
+///     page: u32,
+///     /// The number of items per page.
+///     #[parameters(rename = "per-page")]
+///     per_page: Option<u32>,
+/// }
+///
+/// let parameters = Pagination::parameters();
+/// assert_eq!(parameters[1].name(), "per-page");
+/// ```
+///
+/// The parameters are query parameters by default. Use `#[parameters(path)]` or
+/// `#[parameters(header)]` on the struct for path parameters or headers.
+///
+/// [`DescribeParameters`]: https://docs.rs/axum/0.7/axum/routing/trait.DescribeParameters.html
+#[proc_macro_derive(DescribeParameters, attributes(parameters))]
+pub fn derive_describe_parameters(item: TokenStream) -> TokenStream {
+    expand_with(item, describe_parameters::expand)
+}
+
+/// Derive an implementation of [`IntoResponse`] for an enum, usually an error type.
+///
+/// Each variant is converted into a response according to its `#[response(...)]` attribute:
+///
+/// - `status = 404`: respond with that status code. The body is the `message`, if there is one,
+///   or else the variant's only field, which must implement [`IntoResponse`]. Unit variants without
+///   a `message` have an empty body.
+/// - `message = "..."`: the body of the response, as `text/plain`. The fields of the variant are
+///   ignored.
+/// - `transparent`: the variant must have a single field, which is converted into the response
+///   with its own [`IntoResponse`] implementation.
+///
+/// # Example
+///
+/// ```
+/// use axum_macros::IntoResponse;
+/// use axum::{
+///     extract::rejection::JsonRejection,
+///     routing::post,
+///     Json, Router,
+/// };
+///
+/// #[derive(IntoResponse)]
+/// enum ApiError {
+///     #[response(status = 404, message = "not found")]
+///     NotFound,
+///     // the body is the `String`
+///     #[response(status = 409)]
+///     Conflict(String),
+///     // logged by the handler, but not sent to the client
+///     #[response(status = 500, message = "something went wrong")]
+///     Internal { source: std::io::Error },
+///     // responds with the rejection's own status code and body
+///     #[response(transparent)]
+///     Json(JsonRejection),
+/// }
+///
+/// async fn handler(payload: Result<Json<String>, JsonRejection>) -> Result<(), ApiError> {
+///     let Json(name) = payload.map_err(ApiError::Json)?;
+///     if name.is_empty() {
+///         return Err(ApiError::NotFound);
+///     }
+///     Err(ApiError::Conflict(format!("`{name}` already exists")))
+/// }
+///
+/// let app = Router::new().route("/", post(handler));
+/// # let _: Router = app;
+/// ```
+///
+/// [`IntoResponse`]: https://docs.rs/axum/0.7/axum/response/trait.IntoResponse.html
+#[proc_macro_derive(IntoResponse, attributes(response))]
+pub fn derive_into_response(item: TokenStream) -> TokenStream {
+    expand_with(item, into_response::expand)
+}
+
 /// Derive an implementation of [`FromRef`] for each field in a struct.
 ///
 /// # Example
@@ -748,6 +1074,40 @@ pub fn derive_typed_path(input: TokenStream) -> TokenStream {
 /// # let _: axum::Router = app;
 /// ```
 ///
+/// # Nested states
+///
+/// `#[from_ref(deref)]` on a field of type `Arc<T>`, `Box<T>`, `&'static T`, or a similar wrapper
+/// additionally implements `FromRef` for `T` by cloning it.
+///
+/// `#[from_ref(nested(A, B))]` implements `FromRef` for `A` and `B` by going through the field,
+/// which is useful when the field is itself a state that derives `FromRef`. Combined with `deref`
+/// the types are extracted from `T` rather than from the wrapper:
+///
+/// ```
+/// use axum::extract::FromRef;
+/// use std::sync::Arc;
+///
+/// #[derive(Clone, FromRef)]
+/// struct AppState {
+///     #[from_ref(deref, nested(DatabasePool))]
+///     inner: Arc<InnerState>,
+/// }
+///
+/// #[derive(Clone, FromRef)]
+/// struct InnerState {
+///     pool: DatabasePool,
+/// }
+///
+/// #[derive(Clone)]
+/// struct DatabasePool;
+///
+/// // `AppState` implements `FromRef` for `Arc<InnerState>`, `InnerState`, and `DatabasePool`
+/// fn assert_from_ref<T: FromRef<AppState>>() {}
+/// assert_from_ref::<Arc<InnerState>>();
+/// assert_from_ref::<InnerState>();
+/// assert_from_ref::<DatabasePool>();
+/// ```
+///
 /// [`FromRef`]: https://docs.rs/axum/0.7/axum/extract/trait.FromRef.html
 #[proc_macro_derive(FromRef, attributes(from_ref))]
 pub fn derive_from_ref(item: TokenStream) -> TokenStream {
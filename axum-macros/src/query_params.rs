@@ -0,0 +1,382 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    braced,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Expr, Ident, Lit, LitStr, Token, Type, Visibility,
+};
+
+use crate::attr_parsing::parse_doc_comment;
+
+pub(crate) fn expand(item: QueryParams) -> syn::Result<TokenStream> {
+    let QueryParams {
+        attrs,
+        vis,
+        ident,
+        fields,
+    } = item;
+
+    let raw_ident = format_ident!("__AxumQueryParams{}", ident);
+    let raw_ident_str = raw_ident.to_string();
+    let (serde_attrs, attrs) = split_serde_attrs(attrs);
+
+    let struct_fields = fields.iter().map(|field| {
+        let Field {
+            attrs,
+            vis,
+            ident,
+            ty,
+            ..
+        } = field;
+        let (_, attrs) = split_serde_attrs(attrs.clone());
+        quote! {
+            #(#attrs)*
+            #vis #ident: #ty,
+        }
+    });
+
+    let raw_fields = fields.iter().map(|field| {
+        let Field {
+            attrs,
+            ident,
+            ty,
+            default,
+            ..
+        } = field;
+        let (serde_attrs, _) = split_serde_attrs(attrs.clone());
+        let ty = if default.is_some() {
+            quote_spanned! {ty.span()=> ::std::option::Option<#ty> }
+        } else {
+            quote! { #ty }
+        };
+        quote! {
+            #(#serde_attrs)*
+            #ident: #ty,
+        }
+    });
+
+    let conversions = fields.iter().map(expand_conversion);
+
+    let parameters = fields
+        .iter()
+        .map(expand_parameter)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(::serde::Deserialize)]
+        #[serde(from = #raw_ident_str)]
+        #vis struct #ident {
+            #(#struct_fields)*
+        }
+
+        #[doc(hidden)]
+        #[derive(::serde::Deserialize)]
+        #(#serde_attrs)*
+        #vis struct #raw_ident {
+            #(#raw_fields)*
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::From<#raw_ident> for #ident {
+            fn from(raw: #raw_ident) -> Self {
+                Self {
+                    #(#conversions)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl ::axum::routing::DescribeParameters for #ident {
+            fn parameters() -> ::std::vec::Vec<::axum::routing::ParameterDocs> {
+                ::std::vec![#(#parameters),*]
+            }
+        }
+    })
+}
+
+/// Separate `#[serde(...)]` attributes, which belong on the struct that is deserialized, from
+/// the rest.
+fn split_serde_attrs(attrs: Vec<Attribute>) -> (Vec<Attribute>, Vec<Attribute>) {
+    attrs
+        .into_iter()
+        .partition(|attr| attr.path().is_ident("serde"))
+}
+
+fn expand_conversion(field: &Field) -> TokenStream {
+    let Field {
+        ident,
+        ty,
+        default,
+        min,
+        max,
+        ..
+    } = field;
+
+    let clamp = {
+        let min = min.iter();
+        let max = max.iter();
+        quote! {
+            #( let value = if value < #min { #min } else { value }; )*
+            #( let value = if value > #max { #max } else { value }; )*
+            value
+        }
+    };
+
+    let value = match default {
+        Some(default) => quote_spanned! {default.span()=>
+            raw.#ident.unwrap_or_else(|| #default)
+        },
+        None => quote! { raw.#ident },
+    };
+
+    let value = if min.is_none() && max.is_none() {
+        value
+    } else if default.is_none() && is_option(ty) {
+        quote! { #value.map(|value| { #clamp }) }
+    } else {
+        quote! {
+            {
+                let value = #value;
+                #clamp
+            }
+        }
+    };
+
+    quote! {
+        #ident: #value,
+    }
+}
+
+fn expand_parameter(field: &Field) -> syn::Result<TokenStream> {
+    let Field {
+        attrs,
+        ident,
+        ty,
+        default,
+        min,
+        max,
+        ..
+    } = field;
+
+    let name = serde_rename(attrs)?.unwrap_or_else(|| ident.unraw().to_string());
+    let required = default.is_none() && !is_option(ty);
+
+    let mut parameter = quote! {
+        ::axum::routing::ParameterDocs::query(#name).required(#required)
+    };
+    if let Some(description) = parse_doc_comment(attrs) {
+        parameter = quote! { #parameter.description(#description) };
+    }
+    if let Some(default) = default {
+        let default = display_expr(default);
+        parameter = quote! { #parameter.default_value(#default) };
+    }
+    if let Some(min) = min {
+        let min = display_expr(min);
+        parameter = quote! { #parameter.minimum(#min) };
+    }
+    if let Some(max) = max {
+        let max = display_expr(max);
+        parameter = quote! { #parameter.maximum(#max) };
+    }
+
+    Ok(parameter)
+}
+
+/// The name of the field set with `#[serde(rename = "...")]`, if any.
+fn serde_rename(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let _content;
+                parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(rename)
+}
+
+/// How a default or bound is shown in the parameter docs.
+fn display_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(lit) => lit.value(),
+            Lit::Int(lit) => lit.base10_digits().to_owned(),
+            Lit::Float(lit) => lit.base10_digits().to_owned(),
+            Lit::Bool(lit) => lit.value.to_string(),
+            lit => lit.to_token_stream().to_string(),
+        },
+        expr => expr.to_token_stream().to_string(),
+    }
+}
+
+/// Whether `ty` looks like an `Option`.
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(ty) = ty else {
+        return false;
+    };
+    ty.path
+        .segments
+        .last()
+        .map_or(false, |segment| segment.ident == "Option")
+}
+
+pub(crate) struct QueryParams {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    fields: Punctuated<Field, Token![,]>,
+}
+
+impl Parse for QueryParams {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let fields = content.parse_terminated(Field::parse, Token![,])?;
+
+        Ok(Self {
+            attrs,
+            vis,
+            ident,
+            fields,
+        })
+    }
+}
+
+/// `name: Type = default (min a, max b)`, where the default and the bounds are optional.
+struct Field {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    ty: Type,
+    default: Option<Expr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+}
+
+mod kw {
+    syn::custom_keyword!(min);
+    syn::custom_keyword!(max);
+}
+
+impl Parse for Field {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+
+        // the type and the default are parsed from the tokens up to the next `=`, `,`, or bounds
+        // since otherwise `u32 (max 100)` would be parsed as a function call
+        let ty = syn::parse2(collect_until(input, true)?)?;
+
+        let default = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(syn::parse2(collect_until(input, false)?)?)
+        } else {
+            None
+        };
+
+        let mut min = None;
+        let mut max = None;
+        if peek_bounds(input) {
+            let content;
+            parenthesized!(content in input);
+            while !content.is_empty() {
+                let lh = content.lookahead1();
+                if lh.peek(kw::min) {
+                    let kw = content.parse::<kw::min>()?;
+                    if min.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            kw,
+                            "`min` specified more than once",
+                        ));
+                    }
+                    min = Some(content.parse()?);
+                } else if lh.peek(kw::max) {
+                    let kw = content.parse::<kw::max>()?;
+                    if max.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            kw,
+                            "`max` specified more than once",
+                        ));
+                    }
+                    max = Some(content.parse()?);
+                } else {
+                    return Err(lh.error());
+                }
+
+                let _ = content.parse::<Token![,]>();
+            }
+        }
+
+        Ok(Self {
+            attrs,
+            vis,
+            ident,
+            ty,
+            default,
+            min,
+            max,
+        })
+    }
+}
+
+/// Collect tokens until the next top level `,`, bounds, or, if `stop_at_eq` is set, `=`.
+fn collect_until(input: ParseStream, stop_at_eq: bool) -> syn::Result<TokenStream> {
+    let mut tokens = TokenStream::new();
+    let mut angle_depth = 0_usize;
+    while !input.is_empty() && !peek_bounds(input) {
+        if angle_depth == 0 && (input.peek(Token![,]) || (stop_at_eq && input.peek(Token![=]))) {
+            break;
+        }
+        let token = input.parse::<TokenTree>()?;
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => angle_depth += 1,
+                '>' => angle_depth = angle_depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        tokens.extend([token]);
+    }
+
+    if tokens.is_empty() {
+        return Err(input.error("expected a type or an expression"));
+    }
+
+    Ok(tokens)
+}
+
+/// Whether the next tokens are `(min ..., max ...)`.
+fn peek_bounds(input: ParseStream) -> bool {
+    if !input.peek(syn::token::Paren) {
+        return false;
+    }
+    let Ok(group) = input.fork().parse::<proc_macro2::Group>() else {
+        return false;
+    };
+    let first = group.stream().into_iter().next();
+    matches!(first, Some(TokenTree::Ident(ident)) if ident == "min" || ident == "max")
+}
+
+#[test]
+fn ui() {
+    crate::run_ui_tests("query_params");
+}
@@ -0,0 +1,439 @@
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    ext::IdentExt, parse::Parse, punctuated::Punctuated, spanned::Spanned, FnArg, Ident, ItemFn,
+    LitInt, LitStr, Token, Type,
+};
+
+use crate::attr_parsing::{
+    parse_assignment_attribute, parse_doc_comment, parse_parenthesized_attribute, second,
+};
+
+const METHODS: &[&str] = &[
+    "get", "post", "put", "delete", "patch", "head", "options", "trace",
+];
+
+pub(crate) fn expand(attr: Attrs, item_fn: ItemFn) -> TokenStream {
+    match expand_route(attr, &item_fn) {
+        Ok(register_fn) => quote! {
+            #item_fn
+            #register_fn
+        },
+        // still emit the function so using it doesn't cause more errors
+        Err(err) => {
+            let err = err.into_compile_error();
+            quote! {
+                #item_fn
+                #err
+            }
+        }
+    }
+}
+
+fn expand_route(attr: Attrs, item_fn: &ItemFn) -> syn::Result<TokenStream> {
+    let Attrs {
+        method,
+        path,
+        state_ty,
+        docs,
+    } = attr;
+
+    if !item_fn.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item_fn.sig.generics,
+            "`#[route]` doesn't support generic functions",
+        ));
+    }
+
+    if let Some(receiver) = item_fn.sig.receiver() {
+        return Err(syn::Error::new_spanned(
+            receiver,
+            "`#[route]` only supports free functions",
+        ));
+    }
+
+    let path = LitStr::new(&convert_path(&path)?, path.span());
+    let docs = expand_docs(docs, item_fn, &path)?;
+
+    let state_ty = match state_ty.map(second) {
+        Some(state_ty) => Some(state_ty),
+        None => {
+            let state_types =
+                crate::infer_state_types(item_fn.sig.inputs.iter().filter_map(|arg| match arg {
+                    FnArg::Typed(pat_type) => Some(&*pat_type.ty),
+                    FnArg::Receiver(_) => None,
+                }))
+                .collect::<HashSet<_>>();
+
+            if state_types.len() > 1 {
+                return Err(syn::Error::new_spanned(
+                    &item_fn.sig.inputs,
+                    "can't infer state type, please add set it explicitly, as in \
+                     `#[route(get, \"/\", state = MyStateType)]`",
+                ));
+            }
+
+            state_types.into_iter().next()
+        }
+    };
+
+    let vis = &item_fn.vis;
+    let handler = &item_fn.sig.ident;
+    let register_fn = register_fn_ident(handler);
+    let method_filter = format_ident!(
+        "{}",
+        method.to_string().to_uppercase(),
+        span = method.span()
+    );
+
+    let (generics, state_ty, where_clause) = match state_ty {
+        Some(state_ty) => (quote! {}, quote! { #state_ty }, quote! {}),
+        None => (
+            quote! { <S> },
+            quote! { S },
+            quote! {
+                where
+                    S: ::std::clone::Clone + ::std::marker::Send + ::std::marker::Sync + 'static,
+            },
+        ),
+    };
+
+    Ok(quote_spanned! {path.span()=>
+        #[doc(hidden)]
+        #vis fn #register_fn #generics(
+            router: ::axum::Router<#state_ty>,
+        ) -> ::axum::Router<#state_ty>
+        #where_clause
+        {
+            router.route(
+                #path,
+                ::axum::routing::#method(#handler)
+                    .docs(::axum::routing::MethodFilter::#method_filter, #docs),
+            )
+        }
+    })
+}
+
+/// Build the `OperationDocs` for the handler from its doc comment and the documentation
+/// attributes.
+fn expand_docs(docs: DocsAttrs, item_fn: &ItemFn, path: &LitStr) -> syn::Result<TokenStream> {
+    let DocsAttrs {
+        summary,
+        description,
+        params,
+        param,
+        responses,
+    } = docs;
+
+    // the first paragraph of the doc comment is the summary, the rest is the description
+    let doc_comment = parse_doc_comment(&item_fn.attrs);
+    let (doc_summary, doc_description) = match &doc_comment {
+        Some(doc) => match doc.split_once("\n\n") {
+            Some((summary, description)) => (
+                Some(summary.replace('\n', " ")),
+                Some(description.trim().to_owned()),
+            ),
+            None => (Some(doc.replace('\n', " ")), None),
+        },
+        None => (None, None),
+    };
+    let summary = summary.map(|(_, summary)| summary.value()).or(doc_summary);
+    let description = description
+        .map(|(_, description)| description.value())
+        .or(doc_description);
+
+    let mut docs = quote! { ::axum::routing::OperationDocs::new() };
+    if let Some(summary) = summary {
+        docs = quote! { #docs.summary(#summary) };
+    }
+    if let Some(description) = description {
+        docs = quote! { #docs.description(#description) };
+    }
+
+    let path_value = path.value();
+    let captures = path_value
+        .split('/')
+        .filter_map(|segment| {
+            segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('*'))
+        })
+        .collect::<Vec<_>>();
+    for capture in &captures {
+        docs = quote! { #docs.parameter(::axum::routing::ParameterDocs::path(#capture)) };
+    }
+
+    for ty in params.into_iter().flat_map(|(_, params)| params.0) {
+        docs = quote_spanned! {ty.span()=> #docs.parameters_from::<#ty>() };
+    }
+
+    for ParamAttr { name, description } in param.into_iter().flat_map(|(_, param)| param.0) {
+        let name = name.unraw().to_string();
+        let constructor = if captures.contains(&name.as_str()) {
+            quote! { path }
+        } else {
+            quote! { query }
+        };
+        docs = quote! {
+            #docs.parameter(
+                ::axum::routing::ParameterDocs::#constructor(#name).description(#description)
+            )
+        };
+    }
+
+    for response in responses {
+        let ResponseAttr {
+            status,
+            description,
+            body,
+        } = response;
+        let code = status.base10_parse::<u16>()?;
+        if !(100..1000).contains(&code) {
+            return Err(syn::Error::new_spanned(
+                status,
+                "status codes must be between 100 and 999",
+            ));
+        }
+        let mut response = quote! {
+            ::axum::routing::ResponseDocs::new(::axum::http::StatusCode::from_u16(#code).unwrap())
+        };
+        if let Some((_, description)) = description {
+            response = quote! { #response.description(#description) };
+        }
+        if let Some((_, body)) = body {
+            response = quote_spanned! {body.span()=> #response.body::<#body>() };
+        }
+        docs = quote! { #docs.response(#response) };
+    }
+
+    Ok(docs)
+}
+
+/// The function generated by `#[route]` that adds the route for `handler` to a router.
+fn register_fn_ident(handler: &syn::Ident) -> syn::Ident {
+    format_ident!("__axum_macros_route_{}", handler, span = handler.span())
+}
+
+/// Convert `{capture}` and `{*wildcard}` to the `:capture` and `*wildcard` syntax used by
+/// `Router::route`.
+fn convert_path(path: &LitStr) -> syn::Result<String> {
+    let value = path.value();
+    if !value.starts_with('/') {
+        return Err(syn::Error::new_spanned(path, "paths must start with a `/`"));
+    }
+
+    value
+        .split('/')
+        .map(|segment| {
+            let Some(capture) = segment
+                .strip_prefix('{')
+                .and_then(|segment| segment.strip_suffix('}'))
+            else {
+                return Ok(segment.to_owned());
+            };
+
+            if capture.is_empty() || capture == "*" {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "captures must have a name, as in `{id}` or `{*rest}`",
+                ));
+            }
+
+            Ok(match capture.strip_prefix('*') {
+                Some(wildcard) => format!("*{wildcard}"),
+                None => format!(":{capture}"),
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()
+        .map(|segments| segments.join("/"))
+}
+
+pub(crate) struct Attrs {
+    method: syn::Ident,
+    path: LitStr,
+    state_ty: Option<(kw::state, Type)>,
+    docs: DocsAttrs,
+}
+
+#[derive(Default)]
+struct DocsAttrs {
+    summary: Option<(kw::summary, LitStr)>,
+    description: Option<(kw::description, LitStr)>,
+    params: Option<(kw::params, Comma<Type>)>,
+    param: Option<(kw::param, Comma<ParamAttr>)>,
+    responses: Vec<ResponseAttr>,
+}
+
+mod kw {
+    syn::custom_keyword!(state);
+    syn::custom_keyword!(summary);
+    syn::custom_keyword!(description);
+    syn::custom_keyword!(params);
+    syn::custom_keyword!(param);
+    syn::custom_keyword!(response);
+    syn::custom_keyword!(status);
+    syn::custom_keyword!(body);
+}
+
+/// A comma separated list of `T`s.
+struct Comma<T>(Punctuated<T, Token![,]>);
+
+impl<T> Parse for Comma<T>
+where
+    T: Parse,
+{
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Punctuated::parse_terminated(input).map(Self)
+    }
+}
+
+/// `name = "description"` in `param(...)`.
+struct ParamAttr {
+    name: Ident,
+    description: LitStr,
+}
+
+impl Parse for ParamAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.call(Ident::parse_any)?;
+        input.parse::<Token![=]>()?;
+        let description = input.parse()?;
+        Ok(Self { name, description })
+    }
+}
+
+/// `response(status = 200, description = "...", body = T)`.
+struct ResponseAttr {
+    status: LitInt,
+    description: Option<(kw::description, LitStr)>,
+    body: Option<(kw::body, Type)>,
+}
+
+impl Parse for ResponseAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<kw::response>()?;
+        let content;
+        let paren = syn::parenthesized!(content in input);
+
+        let mut status = None::<(kw::status, LitInt)>;
+        let mut description = None;
+        let mut body = None;
+
+        while !content.is_empty() {
+            let lh = content.lookahead1();
+            if lh.peek(kw::status) {
+                parse_assignment_attribute(&content, &mut status)?;
+            } else if lh.peek(kw::description) {
+                parse_assignment_attribute(&content, &mut description)?;
+            } else if lh.peek(kw::body) {
+                parse_assignment_attribute(&content, &mut body)?;
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = content.parse::<Token![,]>();
+        }
+
+        let Some((_, status)) = status else {
+            return Err(syn::Error::new(paren.span.join(), "missing `status`"));
+        };
+
+        Ok(Self {
+            status,
+            description,
+            body,
+        })
+    }
+}
+
+impl Parse for Attrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let method = input.parse::<syn::Ident>()?;
+        if !METHODS.iter().any(|known| method == known) {
+            return Err(syn::Error::new_spanned(
+                &method,
+                format!(
+                    "unknown method, expected one of {}",
+                    METHODS
+                        .iter()
+                        .map(|method| format!("`{method}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ));
+        }
+        input.parse::<Token![,]>()?;
+
+        let path = input.parse()?;
+
+        let mut state_ty = None;
+        let mut docs = DocsAttrs::default();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let lh = input.lookahead1();
+            if lh.peek(kw::state) {
+                parse_assignment_attribute(input, &mut state_ty)?;
+            } else if lh.peek(kw::summary) {
+                parse_assignment_attribute(input, &mut docs.summary)?;
+            } else if lh.peek(kw::description) {
+                parse_assignment_attribute(input, &mut docs.description)?;
+            } else if lh.peek(kw::params) {
+                parse_parenthesized_attribute(input, &mut docs.params)?;
+            } else if lh.peek(kw::param) {
+                parse_parenthesized_attribute(input, &mut docs.param)?;
+            } else if lh.peek(kw::response) {
+                docs.responses.push(input.parse()?);
+            } else {
+                return Err(lh.error());
+            }
+        }
+
+        Ok(Self {
+            method,
+            path,
+            state_ty,
+            docs,
+        })
+    }
+}
+
+pub(crate) struct Handlers {
+    handlers: Punctuated<syn::Path, Token![,]>,
+}
+
+impl Parse for Handlers {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            handlers: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+pub(crate) fn expand_collect_routes(Handlers { handlers }: Handlers) -> syn::Result<TokenStream> {
+    let register_fns = handlers.into_iter().map(|mut handler| {
+        let last = handler.segments.last_mut().unwrap();
+        last.ident = register_fn_ident(&last.ident);
+        handler
+    });
+
+    Ok(quote! {
+        {
+            let router = ::axum::Router::new();
+            #(
+                let router = #register_fns(router);
+            )*
+            router
+        }
+    })
+}
+
+#[test]
+fn ui() {
+    crate::run_ui_tests("route");
+}
@@ -0,0 +1,364 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    ext::IdentExt, parse::Parse, spanned::Spanned, GenericArgument, ItemStruct, LitInt, LitStr,
+    PathArguments, Token, Type,
+};
+
+use crate::attr_parsing::{
+    combine_attribute, combine_unary_attribute, parse_assignment_attribute, parse_attrs, second,
+    Combine,
+};
+
+pub(crate) fn expand(item_struct: ItemStruct) -> syn::Result<TokenStream> {
+    let ItemStruct {
+        ident,
+        generics,
+        fields,
+        ..
+    } = &item_struct;
+
+    if !generics.params.is_empty() || generics.where_clause.is_some() {
+        return Err(syn::Error::new_spanned(
+            generics,
+            "`#[derive(TryFromMultipart)]` doesn't support generics",
+        ));
+    }
+
+    let syn::Fields::Named(fields) = fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`#[derive(TryFromMultipart)]` only supports structs with named fields",
+        ));
+    };
+
+    let fields = fields
+        .named
+        .iter()
+        .map(Field::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let declare_vars = fields.iter().map(Field::declare_var);
+    let match_arms = fields.iter().map(Field::match_arm);
+    let init_fields = fields.iter().map(Field::init);
+
+    Ok(quote! {
+        #[::axum::async_trait]
+        #[automatically_derived]
+        impl ::axum_extra::extract::TryFromMultipart for #ident {
+            async fn try_from_multipart(
+                multipart: &mut ::axum_extra::extract::Multipart,
+            ) -> ::std::result::Result<Self, ::axum_extra::extract::TypedMultipartError> {
+                #(#declare_vars)*
+
+                while let ::std::option::Option::Some(field) = multipart.next_field().await? {
+                    let name = field.name().map(::std::borrow::ToOwned::to_owned);
+                    match name.as_deref() {
+                        #(#match_arms)*
+                        _ => {}
+                    }
+                }
+
+                ::std::result::Result::Ok(Self {
+                    #(#init_fields)*
+                })
+            }
+        }
+    })
+}
+
+mod kw {
+    syn::custom_keyword!(rename);
+    syn::custom_keyword!(limit);
+    syn::custom_keyword!(file);
+    syn::custom_keyword!(text);
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<(kw::rename, LitStr)>,
+    limit: Option<(kw::limit, LitInt)>,
+    file: Option<kw::file>,
+    text: Option<kw::text>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut rename = None;
+        let mut limit = None;
+        let mut file = None;
+        let mut text = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::rename) {
+                parse_assignment_attribute(input, &mut rename)?;
+            } else if lh.peek(kw::limit) {
+                parse_assignment_attribute(input, &mut limit)?;
+            } else if lh.peek(kw::file) {
+                combine_unary_attribute(&mut file, Some(input.parse()?))?;
+            } else if lh.peek(kw::text) {
+                combine_unary_attribute(&mut text, Some(input.parse()?))?;
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self {
+            rename,
+            limit,
+            file,
+            text,
+        })
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            rename,
+            limit,
+            file,
+            text,
+        } = other;
+        combine_attribute(&mut self.rename, rename)?;
+        combine_attribute(&mut self.limit, limit)?;
+        combine_unary_attribute(&mut self.file, file)?;
+        combine_unary_attribute(&mut self.text, text)?;
+        Ok(self)
+    }
+}
+
+/// How many times a multipart field may be sent.
+enum Occurs {
+    Once,
+    Optional,
+    Many,
+}
+
+enum Kind {
+    Any,
+    File,
+    Text,
+}
+
+struct Field<'a> {
+    ident: &'a syn::Ident,
+    var: syn::Ident,
+    name: LitStr,
+    /// The type parsed with `FromMultipartField`.
+    ty: &'a Type,
+    occurs: Occurs,
+    kind: Kind,
+    limit: Option<LitInt>,
+}
+
+impl<'a> Field<'a> {
+    fn parse(field: &'a syn::Field) -> syn::Result<Self> {
+        let ident = field.ident.as_ref().unwrap();
+        let FieldAttrs {
+            rename,
+            limit,
+            file,
+            text,
+        } = parse_attrs("multipart", &field.attrs)?;
+
+        let kind = match (file, text) {
+            (Some(_), Some(text)) => {
+                return Err(syn::Error::new_spanned(
+                    text,
+                    "`file` and `text` can't be used together",
+                ))
+            }
+            (Some(_), None) => Kind::File,
+            (None, Some(_)) => Kind::Text,
+            (None, None) => Kind::Any,
+        };
+
+        let limit = limit.map(second);
+        if let Some(limit) = &limit {
+            limit.base10_parse::<usize>()?;
+        }
+
+        let name = rename
+            .map(second)
+            .unwrap_or_else(|| LitStr::new(&ident.unraw().to_string(), ident.span()));
+
+        let (occurs, ty) = match wrapped_type(&field.ty, "Option") {
+            Some(inner) => (Occurs::Optional, inner),
+            None => match wrapped_type(&field.ty, "Vec").filter(|inner| !is_u8(inner)) {
+                Some(inner) => (Occurs::Many, inner),
+                None => (Occurs::Once, &field.ty),
+            },
+        };
+        check_field_type(ty)?;
+
+        Ok(Self {
+            ident,
+            var: format_ident!("__field_{}", ident),
+            name,
+            ty,
+            occurs,
+            kind,
+            limit,
+        })
+    }
+
+    fn declare_var(&self) -> TokenStream {
+        let Self { var, ty, .. } = self;
+        match self.occurs {
+            Occurs::Once | Occurs::Optional => quote! {
+                let mut #var: ::std::option::Option<#ty> = ::std::option::Option::None;
+            },
+            Occurs::Many => quote! {
+                let mut #var: ::std::vec::Vec<#ty> = ::std::vec::Vec::new();
+            },
+        }
+    }
+
+    fn match_arm(&self) -> TokenStream {
+        let Self { var, name, ty, .. } = self;
+
+        let check_kind = match self.kind {
+            Kind::Any => quote! {},
+            Kind::File => quote! {
+                if field.file_name().is_none() {
+                    return ::std::result::Result::Err(
+                        ::axum_extra::extract::TypedMultipartError::ExpectedFile {
+                            field: ::std::borrow::ToOwned::to_owned(#name),
+                        },
+                    );
+                }
+            },
+            Kind::Text => quote! {
+                if field.file_name().is_some() {
+                    return ::std::result::Result::Err(
+                        ::axum_extra::extract::TypedMultipartError::ExpectedText {
+                            field: ::std::borrow::ToOwned::to_owned(#name),
+                        },
+                    );
+                }
+            },
+        };
+
+        let limit = match &self.limit {
+            Some(limit) => quote! { ::std::option::Option::Some(#limit) },
+            None => quote! { ::std::option::Option::None },
+        };
+
+        // spanned so unsupported types are reported at the field
+        let parse = quote_spanned! {ty.span()=>
+            <#ty as ::axum_extra::extract::FromMultipartField>::from_multipart_field(field, #limit)
+                .await?
+        };
+
+        let store = match self.occurs {
+            Occurs::Once | Occurs::Optional => quote! {
+                if #var.replace(value).is_some() {
+                    return ::std::result::Result::Err(
+                        ::axum_extra::extract::TypedMultipartError::DuplicateField {
+                            field: ::std::borrow::ToOwned::to_owned(#name),
+                        },
+                    );
+                }
+            },
+            Occurs::Many => quote! {
+                #var.push(value);
+            },
+        };
+
+        quote! {
+            ::std::option::Option::Some(#name) => {
+                #check_kind
+                let value = #parse;
+                #store
+            }
+        }
+    }
+
+    fn init(&self) -> TokenStream {
+        let Self {
+            ident, var, name, ..
+        } = self;
+        match self.occurs {
+            Occurs::Once => quote! {
+                #ident: #var.ok_or_else(|| {
+                    ::axum_extra::extract::TypedMultipartError::MissingField {
+                        field: ::std::borrow::ToOwned::to_owned(#name),
+                    }
+                })?,
+            },
+            Occurs::Optional | Occurs::Many => quote! {
+                #ident: #var,
+            },
+        }
+    }
+}
+
+/// If `ty` is `wrapper<T>`, return `T`.
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    if args.args.len() != 1 {
+        return None;
+    }
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident("u8"))
+}
+
+/// Reject types that can't implement `FromMultipartField`, with more helpful errors than the
+/// compiler would give.
+fn check_field_type(ty: &Type) -> syn::Result<()> {
+    match ty {
+        Type::Path(_) => {
+            if wrapped_type(ty, "Option").is_some()
+                || wrapped_type(ty, "Vec")
+                    .filter(|inner| !is_u8(inner))
+                    .is_some()
+            {
+                Err(syn::Error::new_spanned(
+                    ty,
+                    "nested `Option`s and `Vec`s aren't supported in `#[derive(TryFromMultipart)]`",
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        Type::Reference(_) => Err(syn::Error::new_spanned(
+            ty,
+            "references aren't supported in `#[derive(TryFromMultipart)]`, use an owned type \
+             such as `String`",
+        )),
+        Type::Group(group) => check_field_type(&group.elem),
+        Type::Paren(paren) => check_field_type(&paren.elem),
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "unsupported field type for `#[derive(TryFromMultipart)]`, expected a type that \
+             implements `FromMultipartField`, or an `Option` or `Vec` of one",
+        )),
+    }
+}
+
+#[test]
+fn ui() {
+    crate::run_ui_tests("try_from_multipart");
+}
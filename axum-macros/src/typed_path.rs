@@ -1,8 +1,15 @@
+use heck::{
+    ToKebabCase, ToLowerCamelCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
+    ToUpperCamelCase,
+};
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned};
-use syn::{parse::Parse, ItemStruct, LitStr, Token};
+use syn::{ext::IdentExt, parse::Parse, spanned::Spanned, ItemStruct, LitStr, Token};
 
-use crate::attr_parsing::{combine_attribute, parse_parenthesized_attribute, second, Combine};
+use crate::attr_parsing::{
+    combine_attribute, parse_assignment_attribute, parse_attrs, parse_parenthesized_attribute,
+    second, Combine,
+};
 
 pub(crate) fn expand(item_struct: ItemStruct) -> syn::Result<TokenStream> {
     let ItemStruct {
@@ -20,7 +27,11 @@ pub(crate) fn expand(item_struct: ItemStruct) -> syn::Result<TokenStream> {
         ));
     }
 
-    let Attrs { path, rejection } = crate::attr_parsing::parse_attrs("typed_path", attrs)?;
+    let Attrs {
+        path,
+        rejection,
+        rename_all,
+    } = parse_attrs("typed_path", attrs)?;
 
     let path = path.ok_or_else(|| {
         syn::Error::new(
@@ -30,34 +41,74 @@ pub(crate) fn expand(item_struct: ItemStruct) -> syn::Result<TokenStream> {
     })?;
 
     let rejection = rejection.map(second);
+    let rename_all = rename_all.map(second).map(RenameAll::parse).transpose()?;
+
+    let has_encoding = fields
+        .iter()
+        .map(|field| parse_attrs::<FieldAttrs>("typed_path", &field.attrs))
+        .collect::<syn::Result<Vec<_>>>()?
+        .iter()
+        .any(|attrs| attrs.encoding.is_some());
 
     match fields {
-        syn::Fields::Named(_) => {
+        syn::Fields::Named(fields) => {
             let segments = parse_path(&path)?;
-            Ok(expand_named_fields(ident, path, &segments, rejection))
+            if rename_all.is_some() || has_encoding {
+                let fields = capture_named_fields(fields, &segments, &path, rename_all)?;
+                Ok(expand_decoded_fields(
+                    ident, path, &segments, &fields, rejection,
+                ))
+            } else {
+                Ok(expand_named_fields(ident, path, &segments, rejection))
+            }
         }
         syn::Fields::Unnamed(fields) => {
+            if let Some(rename_all) = rename_all {
+                return Err(syn::Error::new(
+                    rename_all.span,
+                    "`rename_all` is only supported on structs with named fields",
+                ));
+            }
             let segments = parse_path(&path)?;
-            expand_unnamed_fields(fields, ident, path, &segments, rejection)
+            if has_encoding {
+                let fields = capture_unnamed_fields(fields, &segments)?;
+                Ok(expand_decoded_fields(
+                    ident, path, &segments, &fields, rejection,
+                ))
+            } else {
+                expand_unnamed_fields(fields, ident, path, &segments, rejection)
+            }
+        }
+        syn::Fields::Unit => {
+            if let Some(rename_all) = rename_all {
+                return Err(syn::Error::new(
+                    rename_all.span,
+                    "`rename_all` is only supported on structs with named fields",
+                ));
+            }
+            expand_unit_fields(ident, path, rejection)
         }
-        syn::Fields::Unit => expand_unit_fields(ident, path, rejection),
     }
 }
 
 mod kw {
     syn::custom_keyword!(rejection);
+    syn::custom_keyword!(rename_all);
+    syn::custom_keyword!(encoding);
 }
 
 #[derive(Default)]
 struct Attrs {
     path: Option<LitStr>,
     rejection: Option<(kw::rejection, syn::Path)>,
+    rename_all: Option<(kw::rename_all, LitStr)>,
 }
 
 impl Parse for Attrs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut path = None;
         let mut rejection = None;
+        let mut rename_all = None;
 
         while !input.is_empty() {
             let lh = input.lookahead1();
@@ -65,6 +116,8 @@ impl Parse for Attrs {
                 path = Some(input.parse()?);
             } else if lh.peek(kw::rejection) {
                 parse_parenthesized_attribute(input, &mut rejection)?;
+            } else if lh.peek(kw::rename_all) {
+                parse_assignment_attribute(input, &mut rename_all)?;
             } else {
                 return Err(lh.error());
             }
@@ -72,13 +125,21 @@ impl Parse for Attrs {
             let _ = input.parse::<Token![,]>();
         }
 
-        Ok(Self { path, rejection })
+        Ok(Self {
+            path,
+            rejection,
+            rename_all,
+        })
     }
 }
 
 impl Combine for Attrs {
     fn combine(mut self, other: Self) -> syn::Result<Self> {
-        let Self { path, rejection } = other;
+        let Self {
+            path,
+            rejection,
+            rename_all,
+        } = other;
         if let Some(path) = path {
             if self.path.is_some() {
                 return Err(syn::Error::new_spanned(
@@ -89,10 +150,175 @@ impl Combine for Attrs {
             self.path = Some(path);
         }
         combine_attribute(&mut self.rejection, rejection)?;
+        combine_attribute(&mut self.rename_all, rename_all)?;
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    encoding: Option<(kw::encoding, syn::Path)>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut encoding = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::encoding) {
+                parse_parenthesized_attribute(input, &mut encoding)?;
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self { encoding })
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self { encoding } = other;
+        combine_attribute(&mut self.encoding, encoding)?;
         Ok(self)
     }
 }
 
+/// The case conversion applied to field names by `#[typed_path(rename_all = "...")]`.
+struct RenameAll {
+    convert: fn(&str) -> String,
+    span: Span,
+}
+
+impl RenameAll {
+    fn parse(lit: LitStr) -> syn::Result<Self> {
+        let convert: fn(&str) -> String = match &*lit.value() {
+            "lowercase" => str::to_lowercase,
+            "UPPERCASE" => str::to_uppercase,
+            "PascalCase" => |name| name.to_upper_camel_case(),
+            "camelCase" => |name| name.to_lower_camel_case(),
+            "snake_case" => |name| name.to_snake_case(),
+            "SCREAMING_SNAKE_CASE" => |name| name.to_shouty_snake_case(),
+            "kebab-case" => |name| name.to_kebab_case(),
+            "SCREAMING-KEBAB-CASE" => |name| name.to_shouty_kebab_case(),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "unknown case, expected one of \"lowercase\", \"UPPERCASE\", \
+                     \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \
+                     \"kebab-case\" or \"SCREAMING-KEBAB-CASE\"",
+                ))
+            }
+        };
+
+        Ok(Self {
+            convert,
+            span: lit.span(),
+        })
+    }
+}
+
+/// A field that is decoded from a capture by the generated code, rather than with `Path<Self>`.
+struct CaptureField<'a> {
+    member: syn::Member,
+    ty: &'a syn::Type,
+    capture: String,
+    encoding: Option<syn::Path>,
+}
+
+fn capture_named_fields<'a>(
+    fields: &'a syn::FieldsNamed,
+    segments: &[Segment],
+    path: &LitStr,
+    rename_all: Option<RenameAll>,
+) -> syn::Result<Vec<CaptureField<'a>>> {
+    let fields = fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let name = ident.unraw().to_string();
+            let capture = match &rename_all {
+                Some(rename_all) => (rename_all.convert)(&name),
+                None => name,
+            };
+
+            if !captures(segments).any(|existing| *existing == capture) {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    format!("the path doesn't capture `{capture}` for this field"),
+                ));
+            }
+
+            let FieldAttrs { encoding } = parse_attrs("typed_path", &field.attrs)?;
+            Ok(CaptureField {
+                member: syn::Member::Named(ident.clone()),
+                ty: &field.ty,
+                capture,
+                encoding: encoding.map(second),
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    for capture in captures(segments) {
+        if !fields.iter().any(|field| field.capture == *capture) {
+            return Err(syn::Error::new_spanned(
+                path,
+                format!("no field for the capture `{capture}`"),
+            ));
+        }
+    }
+
+    Ok(fields)
+}
+
+fn capture_unnamed_fields<'a>(
+    fields: &'a syn::FieldsUnnamed,
+    segments: &[Segment],
+) -> syn::Result<Vec<CaptureField<'a>>> {
+    let num_captures = captures(segments).count();
+    let num_fields = fields.unnamed.len();
+    if num_fields != num_captures {
+        return Err(syn::Error::new_spanned(
+            fields,
+            format!(
+                "Mismatch in number of captures and fields. Path has {} but struct has {}",
+                simple_pluralize(num_captures, "capture"),
+                simple_pluralize(num_fields, "field"),
+            ),
+        ));
+    }
+
+    fields
+        .unnamed
+        .iter()
+        .zip(captures(segments))
+        .enumerate()
+        .map(|(index, (field, capture))| {
+            let FieldAttrs { encoding } = parse_attrs("typed_path", &field.attrs)?;
+            Ok(CaptureField {
+                member: syn::Member::Unnamed(syn::Index {
+                    index: index as _,
+                    span: field.span(),
+                }),
+                ty: &field.ty,
+                capture: capture.clone(),
+                encoding: encoding.map(second),
+            })
+        })
+        .collect()
+}
+
+fn captures(segments: &[Segment]) -> impl Iterator<Item = &String> {
+    segments.iter().filter_map(|segment| match segment {
+        Segment::Capture(capture, _) => Some(capture),
+        Segment::Static(_) => None,
+    })
+}
+
 fn expand_named_fields(
     ident: &syn::Ident,
     path: LitStr,
@@ -265,6 +491,137 @@ fn expand_unnamed_fields(
     })
 }
 
+/// Used for paths with `rename_all` or fields with `encoding`, where the captures can't be
+/// deserialized with `Path<Self>`.
+fn expand_decoded_fields(
+    ident: &syn::Ident,
+    path: LitStr,
+    segments: &[Segment],
+    fields: &[CaptureField<'_>],
+    rejection: Option<syn::Path>,
+) -> TokenStream {
+    let bindings = (0..fields.len())
+        .map(|idx| format_ident!("__field{}", idx))
+        .collect::<Vec<_>>();
+    let members = fields.iter().map(|field| &field.member).collect::<Vec<_>>();
+
+    let format_str = segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Capture(_, _) => "{}".to_owned(),
+            Segment::Static(segment) => segment.replace('{', "{{").replace('}', "}}"),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let display_args = captures(segments).map(|capture| {
+        let (field, binding) = fields
+            .iter()
+            .zip(&bindings)
+            .find(|(field, _)| field.capture == *capture)
+            .unwrap();
+        let ty = field.ty;
+        let encode = match &field.encoding {
+            Some(encoding) => quote_spanned! {encoding.span()=>
+                <#encoding as ::axum_extra::routing::SegmentEncoding<#ty>>::encode(#binding)
+            },
+            None => quote! { #binding.to_string() },
+        };
+        quote! {
+            ::axum_extra::__private::utf8_percent_encode(
+                &#encode,
+                ::axum_extra::__private::PATH_SEGMENT,
+            )
+        }
+    });
+
+    let decode_fields = fields.iter().zip(&bindings).map(|(field, binding)| {
+        let ty = field.ty;
+        let capture = &field.capture;
+        let decode = match &field.encoding {
+            Some(encoding) => quote_spanned! {encoding.span()=>
+                <#encoding as ::axum_extra::routing::SegmentEncoding<#ty>>::decode
+            },
+            None => quote_spanned! {ty.span()=>
+                <#ty as ::std::str::FromStr>::from_str
+            },
+        };
+        quote! {
+            let #binding = ::axum_extra::__private::decode_capture(&captures, #capture, #decode)?;
+        }
+    });
+
+    let typed_path_impl = quote_spanned! {path.span()=>
+        #[automatically_derived]
+        impl ::axum_extra::routing::TypedPath for #ident {
+            const PATH: &'static str = #path;
+        }
+    };
+
+    let display_impl = quote_spanned! {path.span()=>
+        #[automatically_derived]
+        impl ::std::fmt::Display for #ident {
+            #[allow(clippy::unnecessary_to_owned)]
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let Self { #(#members: #bindings,)* } = self;
+                write!(f, #format_str, #(#display_args),*)
+            }
+        }
+    };
+
+    let rejection_assoc_type = match &rejection {
+        Some(rejection) => quote! { #rejection },
+        None => quote! { ::axum_extra::routing::TypedPathRejection },
+    };
+    let map_err_rejection = rejection
+        .as_ref()
+        .map(|rejection| {
+            quote! {
+                .map_err(|rejection| {
+                    <#rejection as ::std::convert::From<
+                        ::axum_extra::routing::TypedPathRejection,
+                    >>::from(rejection)
+                })
+            }
+        })
+        .unwrap_or_default();
+
+    let from_request_impl = quote! {
+        #[::axum::async_trait]
+        #[automatically_derived]
+        impl<S> ::axum::extract::FromRequestParts<S> for #ident
+        where
+            S: Send + Sync,
+        {
+            type Rejection = #rejection_assoc_type;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                state: &S,
+            ) -> ::std::result::Result<Self, Self::Rejection> {
+                async {
+                    let ::axum::extract::Path(captures) = <::axum::extract::Path<
+                        ::std::vec::Vec<(::std::string::String, ::std::string::String)>,
+                    > as ::axum::extract::FromRequestParts<S>>::from_request_parts(parts, state)
+                    .await?;
+                    #(#decode_fields)*
+                    ::std::result::Result::Ok::<_, ::axum_extra::routing::TypedPathRejection>(
+                        Self { #(#members: #bindings,)* },
+                    )
+                }
+                .await
+                #map_err_rejection
+            }
+        }
+    };
+
+    quote! {
+        #typed_path_impl
+        #display_impl
+        #from_request_impl
+    }
+}
+
 fn simple_pluralize(count: usize, word: &str) -> String {
     if count == 1 {
         format!("{count} {word}")
@@ -0,0 +1,13 @@
+use axum::extract::State;
+use axum_macros::debug_handler;
+
+#[debug_handler(state = AppState)]
+async fn handler(_: State<InnerState>) {}
+
+#[derive(Clone)]
+struct AppState;
+
+#[derive(Clone)]
+struct InnerState;
+
+fn main() {}
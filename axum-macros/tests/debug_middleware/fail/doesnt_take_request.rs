@@ -0,0 +1,13 @@
+use axum::{
+    debug_middleware,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+#[debug_middleware]
+async fn my_middleware(next: Next) -> Response {
+    let _ = next;
+    ().into_response()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use axum::{debug_middleware, extract::Request, middleware::Next, response::Response};
+
+#[debug_middleware]
+async fn my_middleware(request: Request, next: &mut Next) -> Response {
+    next.clone().run(request).await
+}
+
+fn main() {}
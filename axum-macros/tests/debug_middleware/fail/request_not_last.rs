@@ -0,0 +1,15 @@
+use axum::{
+    debug_middleware,
+    extract::Request,
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+
+#[debug_middleware]
+async fn my_middleware(request: Request, headers: HeaderMap, next: Next) -> Response {
+    let _ = headers;
+    next.run(request).await
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use axum::{
+    debug_middleware,
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
+    routing::get,
+    Router,
+};
+
+#[derive(Clone)]
+struct AppState {}
+
+#[debug_middleware]
+async fn my_middleware(
+    State(_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    next.run(request).await
+}
+
+fn main() {
+    let state = AppState {};
+    let _: Router = Router::new()
+        .route("/", get(|| async {}))
+        .layer(middleware::from_fn_with_state(state.clone(), my_middleware))
+        .with_state(state);
+}
@@ -0,0 +1,9 @@
+use axum_macros::DescribeParameters;
+
+#[derive(DescribeParameters)]
+#[parameters(path, query)]
+struct Params {
+    id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use axum_macros::DescribeParameters;
+
+#[derive(DescribeParameters)]
+struct Params(u32);
+
+fn main() {}
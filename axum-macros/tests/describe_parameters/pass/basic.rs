@@ -0,0 +1,33 @@
+use axum::routing::{DescribeParameters, ParameterLocation};
+use axum_macros::DescribeParameters;
+
+#[derive(DescribeParameters)]
+#[allow(dead_code)]
+struct Pagination {
+    /// The page to return.
+    page: u32,
+    /// The number of items per page.
+    #[parameters(rename = "per-page")]
+    per_page: Option<u32>,
+}
+
+#[derive(DescribeParameters)]
+#[parameters(header)]
+#[allow(dead_code)]
+struct Headers {
+    r#accept: String,
+}
+
+fn main() {
+    let parameters = Pagination::parameters();
+    assert_eq!(parameters[0].name(), "page");
+    assert_eq!(parameters[0].location(), ParameterLocation::Query);
+    assert_eq!(parameters[0].get_description(), Some("The page to return."));
+    assert!(parameters[0].is_required());
+    assert_eq!(parameters[1].name(), "per-page");
+    assert!(!parameters[1].is_required());
+
+    let parameters = Headers::parameters();
+    assert_eq!(parameters[0].name(), "accept");
+    assert_eq!(parameters[0].location(), ParameterLocation::Header);
+}
@@ -0,0 +1,9 @@
+use axum_macros::FromRef;
+
+#[derive(Clone, FromRef)]
+struct AppState {
+    #[from_ref(deref)]
+    config: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use axum_macros::FromRef;
+
+#[derive(Clone, FromRef)]
+struct AppState {
+    #[from_ref(skip, nested(String))]
+    inner: Inner,
+}
+
+#[derive(Clone)]
+struct Inner;
+
+fn main() {}
@@ -0,0 +1,67 @@
+use axum::{
+    extract::{FromRef, State},
+    routing::get,
+    Router,
+};
+use axum_macros::FromRef;
+use std::sync::Arc;
+
+#[derive(Clone, FromRef)]
+struct AppState {
+    #[from_ref(deref, nested(DatabasePool, ApiKey))]
+    inner: Arc<InnerState>,
+    #[from_ref(deref)]
+    config: &'static Config,
+    #[from_ref(nested(Secret))]
+    secrets: Secrets,
+}
+
+#[derive(Clone, FromRef)]
+struct InnerState {
+    pool: DatabasePool,
+    api_key: ApiKey,
+}
+
+#[derive(Clone)]
+struct DatabasePool;
+
+#[derive(Clone)]
+struct ApiKey(String);
+
+#[derive(Clone)]
+struct Config;
+
+#[derive(Clone, FromRef)]
+struct Secrets {
+    secret: Secret,
+}
+
+#[derive(Clone)]
+struct Secret;
+
+async fn handler(
+    State(_): State<Arc<InnerState>>,
+    State(_): State<InnerState>,
+    State(_): State<DatabasePool>,
+    State(_): State<ApiKey>,
+    State(_): State<Config>,
+    State(_): State<&'static Config>,
+    State(_): State<Secret>,
+) {
+}
+
+fn main() {
+    let state = AppState {
+        inner: Arc::new(InnerState {
+            pool: DatabasePool,
+            api_key: ApiKey("key".to_owned()),
+        }),
+        config: Box::leak(Box::new(Config)),
+        secrets: Secrets { secret: Secret },
+    };
+
+    let ApiKey(key) = ApiKey::from_ref(&state);
+    assert_eq!(key, "key");
+
+    let _: Router = Router::new().route("/", get(handler)).with_state(state);
+}
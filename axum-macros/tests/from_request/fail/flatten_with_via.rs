@@ -0,0 +1,12 @@
+use axum_macros::FromRequest;
+
+#[derive(FromRequest)]
+struct Extractor {
+    #[from_request(flatten, via(axum::Extension))]
+    inner: Inner,
+}
+
+#[derive(Clone)]
+struct Inner;
+
+fn main() {}
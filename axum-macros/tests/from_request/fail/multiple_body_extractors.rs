@@ -0,0 +1,10 @@
+use axum::body::Bytes;
+use axum_macros::FromRequest;
+
+#[derive(FromRequest)]
+struct Extractor {
+    bytes: Bytes,
+    string: String,
+}
+
+fn main() {}
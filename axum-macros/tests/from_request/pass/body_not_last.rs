@@ -0,0 +1,31 @@
+use axum::{
+    extract::{FromRequest, Path},
+    http::HeaderMap,
+    response::Response,
+    Json,
+};
+use axum_macros::FromRequest;
+use serde::Deserialize;
+
+#[derive(FromRequest)]
+struct Extractor {
+    // extracted after the other fields
+    payload: Json<Payload>,
+    headers: HeaderMap,
+    path: Path<u32>,
+}
+
+#[derive(FromRequest)]
+struct Via(#[from_request(via(Json))] Payload, HeaderMap);
+
+#[derive(Deserialize)]
+struct Payload {}
+
+fn assert_from_request()
+where
+    Extractor: FromRequest<(), Rejection = Response>,
+    Via: FromRequest<(), Rejection = Response>,
+{
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{FromRequest, FromRequestParts, Path, Query, State},
+    http::{HeaderMap, Method},
+    response::Response,
+    Json,
+};
+use axum_macros::{FromRequest, FromRequestParts};
+use serde::Deserialize;
+
+#[derive(FromRequestParts)]
+struct Common {
+    method: Method,
+    headers: HeaderMap,
+    state: State<AppState>,
+}
+
+#[derive(FromRequest)]
+struct Extractor {
+    #[from_request(flatten)]
+    common: Common,
+    path: Path<u32>,
+    #[from_request(via(Json))]
+    payload: Payload,
+}
+
+#[derive(FromRequestParts)]
+struct PartsExtractor {
+    query: Query<Payload>,
+    #[from_request(flatten)]
+    common: Common,
+}
+
+#[derive(Clone)]
+struct AppState {}
+
+#[derive(Deserialize)]
+struct Payload {}
+
+fn assert_from_request()
+where
+    Extractor: FromRequest<AppState, Rejection = Response>,
+    PartsExtractor: FromRequestParts<AppState, Rejection = Response>,
+{
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use axum_macros::IntoResponse;
+
+#[derive(IntoResponse)]
+enum Error {
+    NotFound,
+}
+
+fn main() {}
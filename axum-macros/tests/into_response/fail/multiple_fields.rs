@@ -0,0 +1,9 @@
+use axum_macros::IntoResponse;
+
+#[derive(IntoResponse)]
+enum Error {
+    #[response(status = 400)]
+    Invalid(String, String),
+}
+
+fn main() {}
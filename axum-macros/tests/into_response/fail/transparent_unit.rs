@@ -0,0 +1,9 @@
+use axum_macros::IntoResponse;
+
+#[derive(IntoResponse)]
+enum Error {
+    #[response(transparent)]
+    NotFound,
+}
+
+fn main() {}
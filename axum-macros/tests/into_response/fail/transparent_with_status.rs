@@ -0,0 +1,10 @@
+use axum::Json;
+use axum_macros::IntoResponse;
+
+#[derive(IntoResponse)]
+enum Error {
+    #[response(transparent, status = 400)]
+    Json(Json<String>),
+}
+
+fn main() {}
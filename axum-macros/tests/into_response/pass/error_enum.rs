@@ -0,0 +1,58 @@
+use axum::{
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_macros::IntoResponse;
+use serde_json::{json, Value};
+
+#[derive(IntoResponse)]
+enum Error {
+    #[response(status = 404, message = "not found")]
+    NotFound,
+    #[response(status = 204)]
+    Empty,
+    #[response(status = 409)]
+    Conflict(String),
+    #[response(status = 422)]
+    Invalid { details: Json<Value> },
+    #[response(status = 500, message = "internal error")]
+    Internal(std::io::Error, &'static str),
+    #[response(transparent)]
+    Json(JsonRejection),
+    #[response(transparent)]
+    Other { response: Response },
+}
+
+#[derive(IntoResponse)]
+enum Never {}
+
+fn main() {
+    let status = |error: Error| error.into_response().status();
+    assert_eq!(status(Error::NotFound), StatusCode::NOT_FOUND);
+    assert_eq!(status(Error::Empty), StatusCode::NO_CONTENT);
+    assert_eq!(
+        status(Error::Conflict("taken".to_owned())),
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        status(Error::Invalid {
+            details: Json(json!({ "field": "name" })),
+        }),
+        StatusCode::UNPROCESSABLE_ENTITY,
+    );
+    assert_eq!(
+        status(Error::Internal(std::io::ErrorKind::Other.into(), "oops")),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    );
+    assert_eq!(
+        status(Error::Other {
+            response: StatusCode::IM_A_TEAPOT.into_response(),
+        }),
+        StatusCode::IM_A_TEAPOT,
+    );
+
+    fn assert_into_response<T: IntoResponse>() {}
+    assert_into_response::<Never>();
+}
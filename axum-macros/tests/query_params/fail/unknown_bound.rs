@@ -0,0 +1,9 @@
+use axum_macros::query_params;
+
+query_params! {
+    struct Params {
+        page: u32 = 1 (max 100, step 2),
+    }
+}
+
+fn main() {}
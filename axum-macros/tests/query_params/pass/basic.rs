@@ -0,0 +1,51 @@
+use axum::{extract::Query, http::Uri, routing::DescribeParameters};
+use axum_macros::query_params;
+
+query_params! {
+    #[derive(Debug)]
+    pub struct ListUsers {
+        /// The page to return.
+        pub page: u32 = 1 (min 1),
+        #[serde(rename = "perPage")]
+        pub per_page: u32 = 20 (min 1, max 100),
+        pub q: Option<String>,
+        pub limit: Option<u64> (max 10),
+        pub sort: String,
+        r#type: Vec<u8> = Vec::new(),
+    }
+}
+
+fn parse(uri: &'static str) -> ListUsers {
+    let Query(params) = Query::<ListUsers>::try_from_uri(&Uri::from_static(uri)).unwrap();
+    params
+}
+
+fn main() {
+    let params = parse("/?sort=name");
+    assert_eq!(params.page, 1);
+    assert_eq!(params.per_page, 20);
+    assert_eq!(params.q, None);
+    assert_eq!(params.limit, None);
+    assert_eq!(params.sort, "name");
+    assert!(params.r#type.is_empty());
+
+    let params = parse("/?sort=name&page=0&perPage=1000&q=bob&limit=1000");
+    assert_eq!(params.page, 1);
+    assert_eq!(params.per_page, 100);
+    assert_eq!(params.q.as_deref(), Some("bob"));
+    assert_eq!(params.limit, Some(10));
+
+    assert!(Query::<ListUsers>::try_from_uri(&Uri::from_static("/")).is_err());
+
+    let parameters = ListUsers::parameters();
+    assert_eq!(parameters[0].name(), "page");
+    assert_eq!(parameters[0].get_description(), Some("The page to return."));
+    assert_eq!(parameters[0].get_default_value(), Some("1"));
+    assert_eq!(parameters[0].get_minimum(), Some("1"));
+    assert!(!parameters[0].is_required());
+    assert_eq!(parameters[1].name(), "perPage");
+    assert_eq!(parameters[1].get_maximum(), Some("100"));
+    assert!(!parameters[2].is_required());
+    assert!(parameters[4].is_required());
+    assert_eq!(parameters[5].name(), "type");
+}
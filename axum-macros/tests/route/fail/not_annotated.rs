@@ -0,0 +1,8 @@
+use axum::Router;
+use axum_macros::collect_routes;
+
+async fn handler() {}
+
+fn main() {
+    let _: Router = collect_routes![handler];
+}
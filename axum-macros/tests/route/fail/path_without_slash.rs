@@ -0,0 +1,6 @@
+use axum_macros::route;
+
+#[route(get, "users")]
+async fn handler() {}
+
+fn main() {}
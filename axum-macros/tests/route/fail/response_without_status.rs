@@ -0,0 +1,6 @@
+use axum_macros::route;
+
+#[route(get, "/", response(description = "Ok"))]
+async fn handler() {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use axum_macros::route;
+
+#[route(fetch, "/")]
+async fn handler() {}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use axum::{
+    extract::{Path, State},
+    Router,
+};
+use axum_macros::{collect_routes, route};
+
+#[route(get, "/users")]
+async fn list_users() {}
+
+#[route(post, "/users")]
+async fn create_user(State(_): State<AppState>) {}
+
+#[route(get, "/users/{id}/files/{*path}")]
+async fn show_file(Path((_id, _path)): Path<(u32, String)>) {}
+
+mod admin {
+    use axum_macros::route;
+
+    #[route(delete, "/admin/users/:id", state = super::AppState)]
+    pub(crate) async fn delete_user() {}
+}
+
+#[derive(Clone)]
+struct AppState {}
+
+fn main() {
+    let _: Router = collect_routes![list_users, create_user, show_file, admin::delete_user]
+        .with_state(AppState {});
+
+    // handlers without `State` work with any state
+    let _: Router<()> = collect_routes![list_users, show_file];
+}
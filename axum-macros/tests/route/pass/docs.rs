@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, Query},
+    http::Method,
+    routing::ParameterLocation,
+    Json, Router,
+};
+use axum_macros::{collect_routes, route, DescribeParameters};
+use serde::Deserialize;
+
+#[derive(Deserialize, DescribeParameters)]
+#[allow(dead_code)]
+struct Pagination {
+    /// The page to return.
+    page: Option<u32>,
+}
+
+/// List the files of a user.
+///
+/// Files are sorted by name.
+#[route(
+    get,
+    "/users/{id}/files",
+    params(Pagination),
+    param(id = "The id of the user"),
+    response(status = 200, description = "The files", body = Json<Vec<String>>),
+    response(status = 404, description = "No such user"),
+)]
+async fn list_files(Path(_id): Path<u32>, Query(_): Query<Pagination>) {}
+
+#[route(post, "/users", summary = "Create a user")]
+async fn create_user() {}
+
+fn main() {
+    let app: Router = collect_routes![list_files, create_user];
+    let routes = app.export_routes();
+
+    let docs = routes.routes()[0].operation(&Method::POST).unwrap();
+    assert_eq!(docs.get_summary(), Some("Create a user"));
+
+    let docs = routes.routes()[1].operation(&Method::GET).unwrap();
+    assert_eq!(docs.get_summary(), Some("List the files of a user."));
+    assert_eq!(docs.get_description(), Some("Files are sorted by name."));
+
+    let parameters = docs.get_parameters();
+    assert_eq!(parameters.len(), 2);
+    assert_eq!(parameters[0].name(), "page");
+    assert_eq!(parameters[0].location(), ParameterLocation::Query);
+    assert_eq!(parameters[1].name(), "id");
+    assert_eq!(parameters[1].location(), ParameterLocation::Path);
+    assert_eq!(parameters[1].get_description(), Some("The id of the user"));
+
+    let responses = docs.get_responses();
+    assert_eq!(responses[0].status(), 200);
+    assert_eq!(responses[1].get_description(), Some("No such user"));
+}
@@ -0,0 +1,9 @@
+use axum_extra::extract::TryFromMultipart;
+
+#[derive(TryFromMultipart)]
+struct Form {
+    #[multipart(file, text)]
+    upload: String,
+}
+
+fn main() {}
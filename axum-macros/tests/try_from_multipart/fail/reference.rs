@@ -0,0 +1,8 @@
+use axum_extra::extract::TryFromMultipart;
+
+#[derive(TryFromMultipart)]
+struct Form {
+    name: &'static str,
+}
+
+fn main() {}
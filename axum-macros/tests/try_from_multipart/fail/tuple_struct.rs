@@ -0,0 +1,6 @@
+use axum_extra::extract::TryFromMultipart;
+
+#[derive(TryFromMultipart)]
+struct Form(String);
+
+fn main() {}
@@ -0,0 +1,34 @@
+use axum::{body::Bytes, routing::post, Router};
+use axum_extra::extract::{TryFromMultipart, TypedMultipart, UploadedFile};
+
+#[derive(TryFromMultipart)]
+struct Form {
+    title: String,
+    #[multipart(rename = "tag")]
+    tags: Vec<String>,
+    description: Option<String>,
+    count: u32,
+    #[multipart(text, limit = 1024)]
+    notes: Bytes,
+    #[multipart(file, limit = 10_485_760)]
+    file: UploadedFile,
+    attachments: Vec<Vec<u8>>,
+    r#type: Option<bool>,
+}
+
+async fn handler(TypedMultipart(form): TypedMultipart<Form>) {
+    let Form {
+        title: _,
+        tags: _,
+        description: _,
+        count: _,
+        notes: _,
+        file: _,
+        attachments: _,
+        r#type: _,
+    } = form;
+}
+
+fn main() {
+    _ = Router::<()>::new().route("/", post(handler));
+}
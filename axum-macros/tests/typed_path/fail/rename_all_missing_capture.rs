@@ -0,0 +1,9 @@
+use axum_macros::TypedPath;
+
+#[derive(TypedPath)]
+#[typed_path("/users/:user_id", rename_all = "camelCase")]
+struct MyPath {
+    user_id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+use axum_extra::routing::{RouterExt, SegmentEncoding, TypedPath, TypedPathRejection};
+
+struct Hex;
+
+impl SegmentEncoding<u32> for Hex {
+    type Error = std::num::ParseIntError;
+
+    fn encode(value: &u32) -> String {
+        format!("{value:x}")
+    }
+
+    fn decode(segment: &str) -> Result<u32, Self::Error> {
+        u32::from_str_radix(segment, 16)
+    }
+}
+
+#[derive(TypedPath)]
+#[typed_path("/users/:userId/teams/:teamId", rename_all = "camelCase")]
+struct UsersTeam {
+    user_id: u32,
+    #[typed_path(encoding(Hex))]
+    team_id: u32,
+}
+
+#[derive(TypedPath)]
+#[typed_path("/users/:user_id/teams/:team_id")]
+struct TupleUsersTeam(#[typed_path(encoding(Hex))] u32, String);
+
+async fn handler(_: UsersTeam) {}
+
+async fn result_handler(_: Result<TupleUsersTeam, TypedPathRejection>) {}
+
+fn main() {
+    _ = axum::Router::<()>::new()
+        .typed_get(handler)
+        .typed_get(result_handler);
+
+    assert_eq!(
+        UsersTeam { user_id: 1, team_id: 255 }.to_string(),
+        "/users/1/teams/ff"
+    );
+    assert_eq!(
+        TupleUsersTeam(255, "a b".to_owned()).to_string(),
+        "/users/ff/teams/a%20b"
+    );
+}
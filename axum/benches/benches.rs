@@ -52,6 +52,33 @@ fn main() {
         .body(r#"{"n": 123, "s": "hi there", "b": false}"#)
         .run(|| Router::new().route("/", post(|_: Json<Payload>| async {})));
 
+    // large enough for parsing to dominate, to compare `Json` with `SimdJson`
+    let large_json: &'static str = Box::leak(
+        serde_json::to_string(&vec![
+            Payload {
+                n: 123,
+                s: "hi there".to_owned(),
+                b: false,
+            };
+            1000
+        ])
+        .unwrap()
+        .into_boxed_str(),
+    );
+
+    benchmark("receive-large-json")
+        .method("post")
+        .headers(&[("content-type", "application/json")])
+        .body(large_json)
+        .run(|| Router::new().route("/", post(|_: Json<Vec<Payload>>| async {})));
+
+    #[cfg(feature = "simd-json")]
+    benchmark("receive-large-simd-json")
+        .method("post")
+        .headers(&[("content-type", "application/json")])
+        .body(large_json)
+        .run(|| Router::new().route("/", post(|_: axum::SimdJson<Vec<Payload>>| async {})));
+
     benchmark("send-json").run(|| {
         Router::new().route(
             "/",
@@ -93,7 +120,7 @@ struct AppState {
     _vec: Vec<String>,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 struct Payload {
     n: u32,
     s: String,
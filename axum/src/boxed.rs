@@ -23,7 +23,7 @@ where
     {
         Self(AxumMutex::new(Box::new(MakeErasedHandler {
             handler,
-            into_route: |handler, state| Route::new(Handler::with_state(handler, state)),
+            into_route: |handler, state| Route::from_handler(handler, state),
         })))
     }
 }
@@ -84,10 +84,13 @@ where
             .get::<T>()
             .ok_or_else(|| {
                 MissingExtension::from_err(format!(
-                    "Extension of type `{}` was not found. Perhaps you forgot to add it? See `axum::Extension`.",
+                    "Extension of type `{}` was not found. Perhaps you forgot to add it with \
+                     `.layer(Extension(...))`, or added the layer before this route. \
+                     See `axum::Extension`.",
                     std::any::type_name::<T>()
                 ))
-            }).cloned()?;
+            })
+            .cloned()?;
 
         Ok(Extension(value))
     }
@@ -118,6 +121,74 @@ where
     }
 }
 
+/// Extractor for extensions that might not be present.
+///
+/// Unlike [`Extension`] this never rejects the request. If no extension of type `T` exists
+/// `OptionalExtension` will contain `None`.
+///
+/// ```rust,no_run
+/// use axum::{
+///     Router,
+///     extract::OptionalExtension,
+///     routing::get,
+/// };
+///
+/// #[derive(Clone, Default)]
+/// struct CurrentUser {
+///     name: String,
+/// }
+///
+/// async fn handler(OptionalExtension(user): OptionalExtension<CurrentUser>) {
+///     // fall back to an anonymous user if no middleware inserted one
+///     let user = user.unwrap_or_default();
+///     // ...
+/// }
+///
+/// let app = Router::new().route("/", get(handler));
+/// # let _: Router = app;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionalExtension<T>(pub Option<T>);
+
+impl<T> OptionalExtension<T> {
+    /// Returns the extension or `T::default()` if it was missing.
+    pub fn unwrap_or_default(self) -> T
+    where
+        T: Default,
+    {
+        self.0.unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for OptionalExtension<T>
+where
+    T: Clone + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(OptionalExtension(req.extensions.get::<T>().cloned()))
+    }
+}
+
+impl<T> std::ops::Deref for OptionalExtension<T> {
+    type Target = Option<T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for OptionalExtension<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 impl<S, T> tower_layer::Layer<S> for Extension<T>
 where
     T: Clone + Send + Sync + 'static,
@@ -163,3 +234,30 @@ where
         self.inner.call(req)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::get, test_helpers::*, Router};
+    use http::StatusCode;
+
+    #[crate::test]
+    async fn optional_extension() {
+        let handler =
+            |OptionalExtension(value): OptionalExtension<u32>| async move { format!("{value:?}") };
+
+        let app = Router::new()
+            .route("/with", get(handler))
+            .layer(Extension(1_u32))
+            .route("/without", get(handler));
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/with").await;
+        assert_eq!(res.text().await, "Some(1)");
+
+        let res = client.get("/without").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "None");
+    }
+}
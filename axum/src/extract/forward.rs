@@ -0,0 +1,186 @@
+use super::{rejection::ForwardRejection, NestedPath};
+use crate::{extract::Request, response::Response, routing::url_params::UrlParams, Router};
+use async_trait::async_trait;
+use axum_core::extract::FromRequestParts;
+use http::request::Parts;
+use std::{fmt, future::Future};
+use tower_service::Service;
+
+/// Extractor for routing a request through the [`Router`] again.
+///
+/// This works like a server-internal forward rather than an HTTP redirect. The request is
+/// dispatched to whatever route matches it, without the client ever knowing. This can be used
+/// to render error pages with existing handlers, to rewrite URLs, or to keep old URLs working
+/// while migrating to new ones.
+///
+/// The request is routed by the outermost `Router`, so paths must include any prefixes the
+/// matched route is nested at.
+///
+/// # Example
+///
+/// ```
+/// use axum::{
+///     Router,
+///     extract::{Forward, Request},
+///     response::Response,
+///     routing::get,
+///     http::Uri,
+/// };
+///
+/// async fn new_users() -> &'static str {
+///     "users"
+/// }
+///
+/// async fn old_users(forward: Forward, mut req: Request) -> Response {
+///     *req.uri_mut() = Uri::from_static("/v2/users");
+///     forward.to(req).await
+/// }
+///
+/// let app = Router::new()
+///     .route("/v2/users", get(new_users))
+///     .route("/users", get(old_users))
+///     .enable_forward();
+/// # let _: Router = app;
+/// ```
+///
+/// Note that forwarding a request to the route it was received on will loop forever.
+///
+/// # When is `Forward` available?
+///
+/// `Forward` is available to all handlers and middleware called by a [`Router`] that has
+/// [`Router::enable_forward`] called on it. Extracting it fails with [`ForwardRejection`]
+/// otherwise, or if a handler or [`MethodRouter`] is called directly without going through a
+/// `Router`.
+///
+/// [`MethodRouter`]: crate::routing::MethodRouter
+#[derive(Clone)]
+pub struct Forward(Router);
+
+impl Forward {
+    pub(crate) fn new(router: Router) -> Self {
+        Self(router)
+    }
+
+    /// Route `req` as if it had been received by the server and return the response.
+    ///
+    /// Routing state from the previous match, such as path parameters and the matched path, is
+    /// removed from `req` first so the request is routed exactly like a new one.
+    pub fn to(&self, mut req: Request) -> impl Future<Output = Response> + Send + 'static {
+        let extensions = req.extensions_mut();
+        extensions.remove::<UrlParams>();
+        extensions.remove::<NestedPath>();
+        #[cfg(feature = "matched-path")]
+        crate::extract::matched_path::remove_matched_path(extensions);
+
+        let future = self.0.clone().call(req);
+        async move {
+            match future.await {
+                Ok(res) => res,
+                Err(err) => match err {},
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Forward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Forward").finish()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Forward
+where
+    S: Send + Sync,
+{
+    type Rejection = ForwardRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<Self>() {
+            Some(forward) => Ok(forward.clone()),
+            None => Err(ForwardRejection),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        extract::{OriginalUri, Path},
+        handler::HandlerWithoutStateExt,
+        routing::get,
+        test_helpers::*,
+    };
+    use http::{StatusCode, Uri};
+
+    #[crate::test]
+    async fn forward_to_other_route() {
+        let app = Router::new()
+            .route(
+                "/users/:id",
+                get(
+                    |Path(id): Path<u32>, OriginalUri(uri): OriginalUri| async move {
+                        format!("user {id} from {uri}")
+                    },
+                ),
+            )
+            .route(
+                "/old/:id",
+                get(|forward: Forward, mut req: Request| async move {
+                    let id = req.uri().path().trim_start_matches("/old/").to_owned();
+                    *req.uri_mut() = format!("/users/{id}").parse::<Uri>().unwrap();
+                    forward.to(req).await
+                }),
+            )
+            .enable_forward();
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/old/1").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "user 1 from /old/1");
+    }
+
+    #[crate::test]
+    async fn forward_from_nested_router() {
+        let api = Router::new().route(
+            "/missing",
+            get(|forward: Forward, mut req: Request| async move {
+                *req.uri_mut() = Uri::from_static("/not-found");
+                forward.to(req).await
+            }),
+        );
+
+        let app = Router::new()
+            .route(
+                "/not-found",
+                get(|| async { (StatusCode::NOT_FOUND, "custom not found") }),
+            )
+            .nest("/api", api)
+            .enable_forward();
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/api/missing").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.text().await, "custom not found");
+    }
+
+    #[crate::test]
+    async fn missing_without_enable_forward() {
+        let app = Router::new().route("/", get(|_: Forward| async {}));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[crate::test]
+    async fn missing_outside_router() {
+        let client = TestClient::new((|_: Forward| async {}).into_service());
+
+        let res = client.get("/").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
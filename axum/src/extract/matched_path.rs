@@ -109,6 +109,11 @@ pub(crate) fn set_matched_path_for_request(
     }
 }
 
+pub(crate) fn remove_matched_path(extensions: &mut http::Extensions) {
+    extensions.remove::<MatchedPath>();
+    extensions.remove::<MatchedNestedPath>();
+}
+
 // a previous `MatchedPath` might exist if we're inside a nested Router
 fn append_nested_matched_path(matched_path: &Arc<str>, extensions: &http::Extensions) -> Arc<str> {
     if let Some(previous) = extensions
@@ -10,6 +10,7 @@ pub mod rejection;
 #[cfg(feature = "ws")]
 pub mod ws;
 
+mod forward;
 mod host;
 pub(crate) mod nested_path;
 mod raw_form;
@@ -21,17 +22,18 @@ mod state;
 pub use axum_core::extract::{DefaultBodyLimit, FromRef, FromRequest, FromRequestParts, Request};
 
 #[cfg(feature = "macros")]
-pub use axum_macros::{FromRef, FromRequest, FromRequestParts};
+pub use axum_macros::{query_params, FromRef, FromRequest, FromRequestParts};
 
 #[doc(inline)]
 #[allow(deprecated)]
 pub use self::{
+    forward::Forward,
     host::Host,
     nested_path::NestedPath,
-    path::{Path, RawPathParams},
+    path::{Path, RawPath, RawPathParams},
     raw_form::RawForm,
     raw_query::RawQuery,
-    state::State,
+    state::{State, StateRef},
 };
 
 #[doc(inline)]
@@ -45,6 +47,9 @@ pub use crate::Json;
 #[doc(no_inline)]
 pub use crate::Extension;
 
+#[doc(inline)]
+pub use crate::extension::OptionalExtension;
+
 #[cfg(feature = "form")]
 #[doc(no_inline)]
 pub use crate::form::Form;
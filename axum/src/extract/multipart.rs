@@ -7,6 +7,7 @@ use crate::body::Bytes;
 use async_trait::async_trait;
 use axum_core::{
     __composite_rejection as composite_rejection, __define_rejection as define_rejection,
+    extract::rejection::RejectionInfo,
     response::{IntoResponse, Response},
     RequestExt,
 };
@@ -280,7 +281,10 @@ impl IntoResponse for MultipartError {
             body_text = body,
             status = self.status(),
         );
-        (self.status(), body).into_response()
+        let mut res = (self.status(), body.clone()).into_response();
+        res.extensions_mut()
+            .insert(RejectionInfo::new::<Self>(self.status(), body));
+        res
     }
 }
 
@@ -1,10 +1,9 @@
 use super::{ErrorKind, PathDeserializationError};
-use crate::util::PercentDecodedStr;
 use serde::{
     de::{self, DeserializeSeed, EnumAccess, Error, MapAccess, SeqAccess, VariantAccess, Visitor},
     forward_to_deserialize_any, Deserializer,
 };
-use std::{any::type_name, sync::Arc};
+use std::{any::type_name, borrow::Cow};
 
 macro_rules! unsupported_type {
     ($trait_fn:ident) => {
@@ -33,7 +32,7 @@ macro_rules! parse_single_value {
 
             let value = self.url_params[0].1.parse().map_err(|_| {
                 PathDeserializationError::new(ErrorKind::ParseError {
-                    value: self.url_params[0].1.as_str().to_owned(),
+                    value: self.url_params[0].1.to_string(),
                     expected_type: $ty,
                 })
             })?;
@@ -43,12 +42,12 @@ macro_rules! parse_single_value {
 }
 
 pub(crate) struct PathDeserializer<'de> {
-    url_params: &'de [(Arc<str>, PercentDecodedStr)],
+    url_params: &'de [(&'de str, Cow<'de, str>)],
 }
 
 impl<'de> PathDeserializer<'de> {
     #[inline]
-    pub(crate) fn new(url_params: &'de [(Arc<str>, PercentDecodedStr)]) -> Self {
+    pub(crate) fn new(url_params: &'de [(&'de str, Cow<'de, str>)]) -> Self {
         PathDeserializer { url_params }
     }
 }
@@ -216,9 +215,9 @@ impl<'de> Deserializer<'de> for PathDeserializer<'de> {
 }
 
 struct MapDeserializer<'de> {
-    params: &'de [(Arc<str>, PercentDecodedStr)],
+    params: &'de [(&'de str, Cow<'de, str>)],
     key: Option<KeyOrIdx<'de>>,
-    value: Option<&'de PercentDecodedStr>,
+    value: Option<&'de str>,
 }
 
 impl<'de> MapAccess<'de> for MapDeserializer<'de> {
@@ -230,7 +229,7 @@ impl<'de> MapAccess<'de> for MapDeserializer<'de> {
     {
         match self.params.split_first() {
             Some(((key, value), tail)) => {
-                self.value = Some(value);
+                self.value = Some(&**value);
                 self.params = tail;
                 self.key = Some(KeyOrIdx::Key(key));
                 seed.deserialize(KeyDeserializer { key }).map(Some)
@@ -300,19 +299,19 @@ macro_rules! parse_value {
                     let kind = match key {
                         KeyOrIdx::Key(key) => ErrorKind::ParseErrorAtKey {
                             key: key.to_owned(),
-                            value: self.value.as_str().to_owned(),
+                            value: self.value.to_owned(),
                             expected_type: $ty,
                         },
                         KeyOrIdx::Idx { idx: index, key: _ } => ErrorKind::ParseErrorAtIndex {
                             index,
-                            value: self.value.as_str().to_owned(),
+                            value: self.value.to_owned(),
                             expected_type: $ty,
                         },
                     };
                     PathDeserializationError::new(kind)
                 } else {
                     PathDeserializationError::new(ErrorKind::ParseError {
-                        value: self.value.as_str().to_owned(),
+                        value: self.value.to_owned(),
                         expected_type: $ty,
                     })
                 }
@@ -325,7 +324,7 @@ macro_rules! parse_value {
 #[derive(Debug)]
 struct ValueDeserializer<'de> {
     key: Option<KeyOrIdx<'de>>,
-    value: &'de PercentDecodedStr,
+    value: &'de str,
 }
 
 impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
@@ -414,7 +413,7 @@ impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
     {
         struct PairDeserializer<'de> {
             key: Option<KeyOrIdx<'de>>,
-            value: Option<&'de PercentDecodedStr>,
+            value: Option<&'de str>,
         }
 
         impl<'de> SeqAccess<'de> for PairDeserializer<'de> {
@@ -576,7 +575,7 @@ impl<'de> VariantAccess<'de> for UnitVariant {
 }
 
 struct SeqDeserializer<'de> {
-    params: &'de [(Arc<str>, PercentDecodedStr)],
+    params: &'de [(&'de str, Cow<'de, str>)],
     idx: usize,
 }
 
@@ -611,6 +610,7 @@ enum KeyOrIdx<'de> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use percent_encoding::percent_decode_str;
     use serde::Deserialize;
     use std::collections::HashMap;
 
@@ -629,15 +629,10 @@ mod tests {
         a: i32,
     }
 
-    fn create_url_params<I, K, V>(values: I) -> Vec<(Arc<str>, PercentDecodedStr)>
-    where
-        I: IntoIterator<Item = (K, V)>,
-        K: AsRef<str>,
-        V: AsRef<str>,
-    {
+    fn create_url_params<'a>(values: Vec<(&'a str, &'a str)>) -> Vec<(&'a str, Cow<'a, str>)> {
         values
             .into_iter()
-            .map(|(k, v)| (Arc::from(k.as_ref()), PercentDecodedStr::new(v).unwrap()))
+            .map(|(k, v)| (k, percent_decode_str(v).decode_utf8().unwrap()))
             .collect()
     }
 
@@ -5,14 +5,14 @@ mod de;
 
 use crate::{
     extract::{rejection::*, FromRequestParts},
-    routing::url_params::UrlParams,
-    util::PercentDecodedStr,
+    routing::url_params::{UrlParam, UrlParams},
 };
 use async_trait::async_trait;
 use axum_core::response::{IntoResponse, Response};
 use http::{request::Parts, StatusCode};
 use serde::de::DeserializeOwned;
-use std::{fmt, sync::Arc};
+use smallvec::SmallVec;
+use std::{borrow::Cow, fmt, sync::Arc};
 
 /// Extractor that will get captures from the URL and parse them using
 /// [`serde`].
@@ -155,8 +155,15 @@ where
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let params = match parts.extensions.get::<UrlParams>() {
-            Some(UrlParams::Params(params)) => params,
-            Some(UrlParams::InvalidUtf8InPathParam { key }) => {
+            Some(params) => params,
+            None => {
+                return Err(MissingPathParams.into());
+            }
+        };
+
+        let params = match params.decoded() {
+            Ok(params) => params,
+            Err(key) => {
                 let err = PathDeserializationError {
                     kind: ErrorKind::InvalidUtf8InPathParam {
                         key: key.to_string(),
@@ -165,16 +172,70 @@ where
                 let err = FailedToDeserializePathParams(err);
                 return Err(err.into());
             }
+        };
+
+        T::deserialize(de::PathDeserializer::new(&params))
+            .map_err(|err| {
+                PathRejection::FailedToDeserializePathParams(FailedToDeserializePathParams(err))
+            })
+            .map(Path)
+    }
+}
+
+/// Extractor that will get captures from the URL and parse them using [`serde`], without
+/// percent decoding them.
+///
+/// This works like [`Path`] except that captures are passed to [`serde`] exactly as they
+/// appear in the request URI. This is useful for routes that embed values which may contain
+/// encoded slashes (`%2F`) or other characters whose encoding matters, since those can't be told
+/// apart from their unencoded counterparts after [`Path`] decodes them. It can also be used to
+/// apply custom decoding rules.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{
+///     extract::RawPath,
+///     routing::get,
+///     Router,
+/// };
+///
+/// // for `GET /files/docs%2Freadme.md` `name` will be `docs%2Freadme.md`
+/// async fn file(RawPath(name): RawPath<String>) {
+///     // ...
+/// }
+///
+/// let app = Router::new().route("/files/:name", get(file));
+/// # let _: Router = app;
+/// ```
+///
+/// [`serde`]: https://crates.io/crates/serde
+#[derive(Debug)]
+pub struct RawPath<T>(pub T);
+
+axum_core::__impl_deref!(RawPath);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for RawPath<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = PathRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let params = match parts.extensions.get::<UrlParams>() {
+            Some(params) => params.raw(),
             None => {
                 return Err(MissingPathParams.into());
             }
         };
 
-        T::deserialize(de::PathDeserializer::new(params))
+        T::deserialize(de::PathDeserializer::new(&params))
             .map_err(|err| {
                 PathRejection::FailedToDeserializePathParams(FailedToDeserializePathParams(err))
             })
-            .map(Path)
+            .map(RawPath)
     }
 }
 
@@ -404,7 +465,10 @@ impl IntoResponse for FailedToDeserializePathParams {
             body_text = body,
             status = self.status(),
         );
-        (self.status(), body).into_response()
+        let mut res = (self.status(), body.clone()).into_response();
+        res.extensions_mut()
+            .insert(RejectionInfo::new::<Self>(self.status(), body));
+        res
     }
 }
 
@@ -424,6 +488,8 @@ impl std::error::Error for FailedToDeserializePathParams {}
 ///
 /// Any percent encoded parameters will be automatically decoded. The decoded parameters must be
 /// valid UTF-8, otherwise `RawPathParams` will fail and return a `400 Bad Request` response.
+/// Parameters that don't need decoding are borrowed from the request path instead of being
+/// copied.
 ///
 /// # Example
 ///
@@ -443,8 +509,12 @@ impl std::error::Error for FailedToDeserializePathParams {}
 /// let app = Router::new().route("/users/:user_id/team/:team_id", get(users_teams_show));
 /// # let _: Router = app;
 /// ```
-#[derive(Debug)]
-pub struct RawPathParams(Vec<(Arc<str>, PercentDecodedStr)>);
+pub struct RawPathParams {
+    params: UrlParams,
+    // the decoded values of the parameters that contained percent encoded characters. All other
+    // values are borrowed from the path
+    decoded: SmallVec<[Option<Box<str>>; 4]>,
+}
 
 #[async_trait]
 impl<S> FromRequestParts<S> for RawPathParams
@@ -455,19 +525,33 @@ where
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let params = match parts.extensions.get::<UrlParams>() {
-            Some(UrlParams::Params(params)) => params,
-            Some(UrlParams::InvalidUtf8InPathParam { key }) => {
-                return Err(InvalidUtf8InPathParam {
-                    key: Arc::clone(key),
-                }
-                .into());
-            }
+            Some(params) => params,
             None => {
                 return Err(MissingPathParams.into());
             }
         };
 
-        Ok(Self(params.clone()))
+        let decoded = params
+            .iter()
+            .map(|param| match param.decoded_value() {
+                Ok(Cow::Borrowed(_)) => Ok(None),
+                Ok(Cow::Owned(value)) => Ok(Some(value.into_boxed_str())),
+                Err(_) => Err(InvalidUtf8InPathParam {
+                    key: Arc::clone(param.key()),
+                }),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            params: params.clone(),
+            decoded,
+        })
+    }
+}
+
+impl fmt::Debug for RawPathParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
     }
 }
 
@@ -483,7 +567,10 @@ impl<'a> IntoIterator for &'a RawPathParams {
     type IntoIter = RawPathParamsIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        RawPathParamsIter(self.0.iter())
+        RawPathParamsIter {
+            params: self.params.iter(),
+            decoded: self.decoded.iter(),
+        }
     }
 }
 
@@ -491,14 +578,21 @@ impl<'a> IntoIterator for &'a RawPathParams {
 ///
 /// Created with [`RawPathParams::iter`].
 #[derive(Debug)]
-pub struct RawPathParamsIter<'a>(std::slice::Iter<'a, (Arc<str>, PercentDecodedStr)>);
+pub struct RawPathParamsIter<'a> {
+    params: std::slice::Iter<'a, UrlParam>,
+    decoded: std::slice::Iter<'a, Option<Box<str>>>,
+}
 
 impl<'a> Iterator for RawPathParamsIter<'a> {
     type Item = (&'a str, &'a str);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (key, value) = self.0.next()?;
-        Some((&**key, value.as_str()))
+        let param = self.params.next()?;
+        let value = match self.decoded.next()? {
+            Some(decoded) => decoded,
+            None => param.raw_value(),
+        };
+        Some((&**param.key(), value))
     }
 }
 
@@ -537,7 +631,10 @@ impl IntoResponse for InvalidUtf8InPathParam {
             body_text = body,
             status = self.status(),
         );
-        (self.status(), body).into_response()
+        let mut res = (self.status(), body.clone()).into_response();
+        res.extensions_mut()
+            .insert(RejectionInfo::new::<Self>(self.status(), body));
+        res
     }
 }
 
@@ -569,6 +666,39 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
     }
 
+    #[crate::test]
+    async fn raw_path() {
+        let app = Router::new().route(
+            "/files/:name",
+            get(
+                |Path(decoded): Path<String>, RawPath(raw): RawPath<String>| async move {
+                    format!("{decoded} {raw}")
+                },
+            ),
+        );
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/files/a%2Fb%20c").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "a/b c a%2Fb%20c");
+
+        // invalid UTF-8 once decoded
+        let res = client.get("/files/%FF").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let app = Router::new().route(
+            "/files/:name",
+            get(|RawPath(raw): RawPath<String>| async move { raw }),
+        );
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/files/%FF").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "%FF");
+    }
+
     #[crate::test]
     async fn extracting_url_params_multiple_times() {
         let app = Router::new().route("/users/:id", get(|_: Path<i32>, _: Path<String>| async {}));
@@ -887,4 +1017,29 @@ mod tests {
         let body = res.text().await;
         assert_eq!(body, "a=foo b=bar c=baz");
     }
+
+    #[crate::test]
+    async fn params_from_nested_routers() {
+        let app = Router::new().nest(
+            "/:a",
+            Router::new().route(
+                "/:b",
+                get(
+                    |params: RawPathParams, RawPath(raw): RawPath<Vec<String>>| async move {
+                        let decoded = params
+                            .iter()
+                            .map(|(key, value)| format!("{key}={value}"))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        format!("{decoded} {}", raw.join(" "))
+                    },
+                ),
+            ),
+        );
+
+        let client = TestClient::new(app);
+        let res = client.get("/one%20two/three").await;
+        let body = res.text().await;
+        assert_eq!(body, "a=one two b=three one%20two three");
+    }
 }
@@ -216,3 +216,13 @@ define_rejection! {
     /// This rejection is used if the matched route wasn't nested.
     pub struct NestedPathRejection;
 }
+
+define_rejection! {
+    #[status = INTERNAL_SERVER_ERROR]
+    #[body = "No router available to forward the request to"]
+    /// Rejection type for [`Forward`](super::Forward).
+    ///
+    /// This rejection is used if the request wasn't received by a `Router` with
+    /// [`Router::enable_forward`](crate::Router::enable_forward) called on it.
+    pub struct ForwardRejection;
+}
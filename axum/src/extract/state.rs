@@ -4,6 +4,7 @@ use http::request::Parts;
 use std::{
     convert::Infallible,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
 /// Extractor for state.
@@ -374,3 +375,108 @@ impl<S> DerefMut for State<S> {
         &mut self.0
     }
 }
+
+/// Extractor for state that is shared behind an [`Arc`] instead of cloned.
+///
+/// [`State<T>`] clones `T` out of the router's state for every request. For states that are
+/// expensive to clone, `StateRef<T>` extracts an `Arc<T>` instead, so each request only
+/// increments a reference count, and derefs to `&T`. `T` itself doesn't need to implement
+/// `Clone`.
+///
+/// `StateRef<T>` can be extracted if `Arc<T>` implements [`FromRef`] for the router's state,
+/// such as when the state is an `Arc<T>`:
+///
+/// ```
+/// use axum::{Router, routing::get, extract::StateRef};
+/// use std::sync::Arc;
+///
+/// // doesn't implement `Clone`
+/// struct AppState {
+///     config: String,
+/// }
+///
+/// async fn handler(state: StateRef<AppState>) -> String {
+///     state.config.clone()
+/// }
+///
+/// let state = Arc::new(AppState {
+///     config: "...".to_owned(),
+/// });
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .with_state(state);
+/// # let _: Router = app;
+/// ```
+///
+/// Substates work the same as with [`State`], by implementing `FromRef` for `Arc<Substate>`:
+///
+/// ```
+/// use axum::{Router, routing::get, extract::{FromRef, StateRef}};
+/// use std::sync::Arc;
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     db: Arc<Database>,
+/// }
+///
+/// struct Database {}
+///
+/// impl FromRef<AppState> for Arc<Database> {
+///     fn from_ref(app_state: &AppState) -> Arc<Database> {
+///         app_state.db.clone()
+///     }
+/// }
+///
+/// async fn handler(db: StateRef<Database>) {
+///     // ...
+/// }
+///
+/// let state = AppState {
+///     db: Arc::new(Database {}),
+/// };
+///
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .with_state(state);
+/// # let _: Router = app;
+/// ```
+///
+/// `FromRef` can't be implemented from one `Arc` to another, since neither type is local to your
+/// crate. So if the router's state is an `Arc<AppState>`, only `StateRef<AppState>` can be
+/// extracted from it, not substates. To extract substates with `StateRef`, use a state type of
+/// your own that holds them in `Arc`s, as in the example above. Such a state is cheap to clone,
+/// so the state itself can be extracted with [`State`].
+#[derive(Debug, Default)]
+pub struct StateRef<T: ?Sized>(pub Arc<T>);
+
+#[async_trait]
+impl<OuterState, T> FromRequestParts<OuterState> for StateRef<T>
+where
+    Arc<T>: FromRef<OuterState>,
+    OuterState: Send + Sync,
+    T: ?Sized,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &OuterState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(Arc::<T>::from_ref(state)))
+    }
+}
+
+impl<T: ?Sized> Clone for StateRef<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: ?Sized> Deref for StateRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -66,7 +66,31 @@
 //! # Read and write concurrently
 //!
 //! If you need to read and write concurrently from a [`WebSocket`] you can use
-//! [`StreamExt::split`]:
+//! [`WebSocket::spawn_split`]. It spawns a task that writes the messages sent through a
+//! [`WebSocketSender`], which can be cloned and moved into other tasks:
+//!
+//! ```rust,no_run
+//! use axum::extract::ws::{Message, WebSocket};
+//!
+//! async fn handle_socket(socket: WebSocket) {
+//!     let (sender, mut receiver) = socket.spawn_split();
+//!
+//!     tokio::spawn({
+//!         let sender = sender.clone();
+//!         async move {
+//!             let _ = sender.send(Message::Text("hello".to_owned())).await;
+//!         }
+//!     });
+//!
+//!     while let Some(Ok(msg)) = receiver.recv().await {
+//!         if sender.send(msg).await.is_err() {
+//!             break;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! For more control, use [`StreamExt::split`]:
 //!
 //! ```rust,no_run
 //! use axum::{Error, extract::ws::{WebSocket, Message}};
@@ -89,29 +113,72 @@
 //! ```
 //!
 //! [`StreamExt::split`]: https://docs.rs/futures/0.3.17/futures/stream/trait.StreamExt.html#method.split
+//!
+//! # Graceful shutdown
+//!
+//! Open WebSockets aren't closed by [`serve`](crate::serve)'s graceful shutdown. Use a
+//! [`WebSocketTracker`] to send close frames to them, and wait for them to finish.
+//!
+//! # HTTP/2
+//!
+//! WebSockets over HTTP/2, from [RFC 8441], are accepted if the `http2` feature is enabled. The
+//! client opens them with an extended `CONNECT` request, on a connection that can be shared with
+//! other requests, instead of upgrading an HTTP/1.1 connection with a `GET` request.
+//!
+//! [`serve`](crate::serve) enables extended `CONNECT` requests. Since
+//! [`get`](crate::routing::get) doesn't match `CONNECT` requests, add the handler for both
+//! methods with [`MethodRouter::connect`](crate::routing::MethodRouter::connect) to accept
+//! WebSockets over both HTTP/1.1 and HTTP/2:
+//!
+//! ```
+//! use axum::{
+//!     extract::ws::{WebSocketUpgrade, WebSocket},
+//!     routing::get,
+//!     response::Response,
+//!     Router,
+//! };
+//!
+//! let app = Router::new().route("/ws", get(handler).connect(handler));
+//!
+//! async fn handler(ws: WebSocketUpgrade) -> Response {
+//!     ws.on_upgrade(handle_socket)
+//! }
+//!
+//! async fn handle_socket(mut socket: WebSocket) {
+//!     // ...
+//! }
+//! # let _: Router = app;
+//! ```
+//!
+//! [RFC 8441]: https://www.rfc-editor.org/rfc/rfc8441
 
 use self::rejection::*;
 use super::FromRequestParts;
-use crate::{body::Bytes, response::Response, Error};
+use crate::{body::Bytes, response::Response, util::AxumMutex, Error};
 use async_trait::async_trait;
 use axum_core::body::Body;
 use futures_util::{
     sink::{Sink, SinkExt},
-    stream::{Stream, StreamExt},
+    stream::{SplitSink, SplitStream, Stream, StreamExt},
 };
 use http::{
     header::{self, HeaderMap, HeaderName, HeaderValue},
     request::Parts,
-    Method, StatusCode,
+    Method, StatusCode, Version,
 };
 use hyper_util::rt::TokioIo;
 use sha1::{Digest, Sha1};
 use std::{
     borrow::Cow,
-    future::Future,
+    collections::{HashMap, VecDeque},
+    future::{poll_fn, Future},
+    io,
     pin::Pin,
-    task::{Context, Poll},
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
+use tokio::time::{Instant, Sleep};
 use tokio_tungstenite::{
     tungstenite::{
         self as ts,
@@ -122,9 +189,11 @@ use tokio_tungstenite::{
 
 /// Extractor for establishing WebSocket connections.
 ///
-/// Note: This extractor requires the request method to be `GET` so it should
-/// always be used with [`get`](crate::routing::get). Requests with other methods will be
-/// rejected.
+/// Note: This extractor requires the request method to be `GET`, or `CONNECT` for HTTP/2, so it
+/// should always be used with [`get`](crate::routing::get). Requests with other methods will be
+/// rejected. HTTP/2 WebSockets use `CONNECT` requests instead, so to support them also add the
+/// handler with [`connect`](crate::routing::connect), as in `get(handler).connect(handler)`. See
+/// the [module docs](self#http2) for more details.
 ///
 /// See the [module docs](self) for an example.
 #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
@@ -132,10 +201,15 @@ pub struct WebSocketUpgrade<F = DefaultOnFailedUpgrade> {
     config: WebSocketConfig,
     /// The chosen protocol sent in the `Sec-WebSocket-Protocol` header of the response.
     protocol: Option<HeaderValue>,
-    sec_websocket_key: HeaderValue,
+    /// `None` if the request is an HTTP/2 extended CONNECT request, which has no key.
+    sec_websocket_key: Option<HeaderValue>,
     on_upgrade: hyper::upgrade::OnUpgrade,
     on_failed_upgrade: F,
     sec_websocket_protocol: Option<HeaderValue>,
+    keepalive: Option<(Duration, Duration)>,
+    rate_limits: RateLimits,
+    tracker: Option<WebSocketTracker>,
+    interceptor: Option<Box<dyn Interceptor>>,
 }
 
 impl<F> std::fmt::Debug for WebSocketUpgrade<F> {
@@ -145,6 +219,10 @@ impl<F> std::fmt::Debug for WebSocketUpgrade<F> {
             .field("protocol", &self.protocol)
             .field("sec_websocket_key", &self.sec_websocket_key)
             .field("sec_websocket_protocol", &self.sec_websocket_protocol)
+            .field("keepalive", &self.keepalive)
+            .field("rate_limits", &self.rate_limits)
+            .field("tracker", &self.tracker)
+            .field("interceptor", &self.interceptor.is_some())
             .finish_non_exhaustive()
     }
 }
@@ -181,12 +259,16 @@ impl<F> WebSocketUpgrade<F> {
     }
 
     /// Set the maximum message size (defaults to 64 megabytes)
+    ///
+    /// Larger messages close the connection with [`close_code::SIZE`].
     pub fn max_message_size(mut self, max: usize) -> Self {
         self.config.max_message_size = Some(max);
         self
     }
 
     /// Set the maximum frame size (defaults to 16 megabytes)
+    ///
+    /// Larger frames close the connection with [`close_code::SIZE`].
     pub fn max_frame_size(mut self, max: usize) -> Self {
         self.config.max_frame_size = Some(max);
         self
@@ -257,6 +339,226 @@ impl<F> WebSocketUpgrade<F> {
         self
     }
 
+    /// Choose the protocol with an async callback.
+    ///
+    /// This is an alternative to [`protocols`](Self::protocols) for when a static list isn't
+    /// enough, such as for versioned protocols or protocols that depend on the state of the app.
+    /// `select` is called with the protocols the client offered in the `Sec-WebSocket-Protocol`
+    /// header, in the order it sent them, and returns the chosen one, if any.
+    ///
+    /// `select` isn't called if the client didn't offer any protocols. A protocol the client
+    /// didn't offer is ignored, since the client would reject the connection.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     extract::{ws::WebSocketUpgrade, State},
+    ///     response::Response,
+    /// };
+    ///
+    /// #[derive(Clone)]
+    /// struct AppState {
+    ///     // ...
+    /// }
+    ///
+    /// impl AppState {
+    ///     async fn max_protocol_version(&self) -> u32 {
+    ///         // ...
+    ///         # 2
+    ///     }
+    /// }
+    ///
+    /// async fn handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ///     ws.select_protocol(|offered| async move {
+    ///         let max = state.max_protocol_version().await;
+    ///         // pick the highest version, such as `chat.v2`, that is supported
+    ///         offered
+    ///             .into_iter()
+    ///             .filter_map(|protocol| {
+    ///                 let version = protocol.strip_prefix("chat.v")?.parse::<u32>().ok()?;
+    ///                 (version <= max).then_some((version, protocol))
+    ///             })
+    ///             .max()
+    ///             .map(|(_, protocol)| protocol)
+    ///     })
+    ///     .await
+    ///     .on_upgrade(|socket| async {
+    ///         // ...
+    ///     })
+    /// }
+    /// ```
+    pub async fn select_protocol<C, Fut, P>(mut self, select: C) -> Self
+    where
+        C: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = Option<P>>,
+        P: Into<Cow<'static, str>>,
+    {
+        let offered = self
+            .sec_websocket_protocol
+            .as_ref()
+            .and_then(|p| p.to_str().ok())
+            .map(|p| {
+                p.split(',')
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if offered.is_empty() {
+            return self;
+        }
+
+        let chosen = select(offered.clone()).await.map(Into::into);
+        self.protocol = chosen
+            .filter(|protocol| offered.iter().any(|offered| offered == protocol))
+            .and_then(|protocol| HeaderValue::from_str(&protocol).ok());
+
+        self
+    }
+
+    /// Send pings to the client and close the connection if it stops responding.
+    ///
+    /// A ping is sent once no message has been received for `interval`. If the client doesn't
+    /// respond, or send any other message, within `timeout` after that, the connection is
+    /// considered dead: [`WebSocket::recv`] returns an error with the
+    /// [`TimedOut`](std::io::ErrorKind::TimedOut) kind, and `None` after that.
+    ///
+    /// Pings are sent, and responses are tracked, while the socket is being read from, so
+    /// something must keep receiving messages, which is generally the case anyway. Clients
+    /// respond to pings automatically, and their responses are received as [`Message::Pong`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     extract::ws::{WebSocketUpgrade, WebSocket},
+    ///     response::Response,
+    /// };
+    /// use std::time::Duration;
+    ///
+    /// async fn handler(ws: WebSocketUpgrade) -> Response {
+    ///     ws.keepalive(Duration::from_secs(30), Duration::from_secs(10))
+    ///         .on_upgrade(handle_socket)
+    /// }
+    ///
+    /// async fn handle_socket(mut socket: WebSocket) {
+    ///     while let Some(Ok(msg)) = socket.recv().await {
+    ///         // ...
+    ///     }
+    ///     // the client disconnected, or stopped responding to pings
+    /// }
+    /// ```
+    pub fn keepalive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keepalive = Some((interval, timeout));
+        self
+    }
+
+    /// Limit how many messages per second the client can send.
+    ///
+    /// Bursts of up to `max` messages are allowed. If the client sends messages faster, the
+    /// connection is closed with [`close_code::POLICY`], and receiving from the socket returns an
+    /// error. Pings, pongs, and close messages are counted as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     extract::ws::WebSocketUpgrade,
+    ///     response::Response,
+    /// };
+    ///
+    /// async fn handler(ws: WebSocketUpgrade) -> Response {
+    ///     ws.max_message_size(64 * 1024)
+    ///         .max_messages_per_second(20)
+    ///         .max_bytes_per_second(256 * 1024)
+    ///         .on_upgrade(|socket| async {
+    ///             // ...
+    ///         })
+    /// }
+    /// ```
+    pub fn max_messages_per_second(mut self, max: u32) -> Self {
+        self.rate_limits.messages_per_second = Some(max);
+        self
+    }
+
+    /// Limit how many bytes per second the client can send.
+    ///
+    /// Bursts of up to `max` bytes are allowed, so messages larger than `max` always exceed the
+    /// limit. If the client sends data faster, the connection is closed with
+    /// [`close_code::POLICY`], and receiving from the socket returns an error.
+    ///
+    /// See [`max_messages_per_second`](Self::max_messages_per_second) for an example.
+    pub fn max_bytes_per_second(mut self, max: u64) -> Self {
+        self.rate_limits.bytes_per_second = Some(max);
+        self
+    }
+
+    /// Track the socket with a [`WebSocketTracker`], so it can be closed during graceful
+    /// shutdown.
+    ///
+    /// See [`WebSocketTracker`] for more details.
+    pub fn track(mut self, tracker: &WebSocketTracker) -> Self {
+        self.tracker = Some(tracker.clone());
+        self
+    }
+
+    /// Observe, change, or drop the messages received and sent by the socket.
+    ///
+    /// The [`Interceptor`] sees every message before the socket returns it, and every message
+    /// sent with the socket before it's sent to the client. This makes it possible to audit
+    /// messages, translate between protocol versions, or answer some messages, such as requests
+    /// to refresh credentials, without involving the code handling the socket.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     extract::ws::{Interceptor, Message, Outbox, WebSocketUpgrade},
+    ///     response::Response,
+    /// };
+    ///
+    /// struct Audit {
+    ///     user: String,
+    /// }
+    ///
+    /// impl Interceptor for Audit {
+    ///     fn on_receive(&mut self, msg: Message, outbox: &mut Outbox) -> Option<Message> {
+    ///         if let Message::Text(text) = &msg {
+    ///             if text == "refresh-token" {
+    ///                 // answer the client directly, the socket doesn't see this message
+    ///                 outbox.send(Message::Text(new_token(&self.user)));
+    ///                 return None;
+    ///             }
+    ///         }
+    ///         println!("received {msg:?} from {}", self.user);
+    ///         Some(msg)
+    ///     }
+    ///
+    ///     fn on_send(&mut self, msg: Message, _outbox: &mut Outbox) -> Option<Message> {
+    ///         println!("sending {msg:?} to {}", self.user);
+    ///         Some(msg)
+    ///     }
+    /// }
+    ///
+    /// async fn handler(ws: WebSocketUpgrade) -> Response {
+    ///     let user = "alice".to_owned();
+    ///     ws.intercept(Audit { user }).on_upgrade(|socket| async {
+    ///         // ...
+    ///     })
+    /// }
+    /// #
+    /// # fn new_token(_: &str) -> String { String::new() }
+    /// ```
+    pub fn intercept<I>(mut self, interceptor: I) -> Self
+    where
+        I: Interceptor,
+    {
+        self.interceptor = Some(Box::new(interceptor));
+        self
+    }
+
     /// Provide a callback to call if upgrading the connection fails.
     ///
     /// The connection upgrade is performed in a background task. If that fails this callback
@@ -292,6 +594,10 @@ impl<F> WebSocketUpgrade<F> {
             on_upgrade: self.on_upgrade,
             on_failed_upgrade: callback,
             sec_websocket_protocol: self.sec_websocket_protocol,
+            keepalive: self.keepalive,
+            rate_limits: self.rate_limits,
+            tracker: self.tracker,
+            interceptor: self.interceptor,
         }
     }
 
@@ -307,6 +613,15 @@ impl<F> WebSocketUpgrade<F> {
         let on_upgrade = self.on_upgrade;
         let config = self.config;
         let on_failed_upgrade = self.on_failed_upgrade;
+        let keepalive = self.keepalive;
+        let rate_limits = self.rate_limits;
+        // track the socket right away, so a shutdown waits for upgrades in progress
+        let tracked = self.tracker.map(WebSocketTracker::register);
+        let intercept = self.interceptor.map(|interceptor| Intercept {
+            interceptor,
+            outbox: Outbox::default(),
+            needs_flush: false,
+        });
 
         let protocol = self.protocol.clone();
 
@@ -326,6 +641,10 @@ impl<F> WebSocketUpgrade<F> {
             let socket = WebSocket {
                 inner: socket,
                 protocol,
+                keepalive: keepalive.map(|(interval, timeout)| Keepalive::new(interval, timeout)),
+                limits: Limits::new(rate_limits),
+                tracked,
+                intercept,
             };
             callback(socket).await;
         });
@@ -335,14 +654,19 @@ impl<F> WebSocketUpgrade<F> {
         #[allow(clippy::declare_interior_mutable_const)]
         const WEBSOCKET: HeaderValue = HeaderValue::from_static("websocket");
 
-        let mut builder = Response::builder()
-            .status(StatusCode::SWITCHING_PROTOCOLS)
-            .header(header::CONNECTION, UPGRADE)
-            .header(header::UPGRADE, WEBSOCKET)
-            .header(
-                header::SEC_WEBSOCKET_ACCEPT,
-                sign(self.sec_websocket_key.as_bytes()),
-            );
+        let mut builder = if let Some(sec_websocket_key) = &self.sec_websocket_key {
+            Response::builder()
+                .status(StatusCode::SWITCHING_PROTOCOLS)
+                .header(header::CONNECTION, UPGRADE)
+                .header(header::UPGRADE, WEBSOCKET)
+                .header(
+                    header::SEC_WEBSOCKET_ACCEPT,
+                    sign(sec_websocket_key.as_bytes()),
+                )
+        } else {
+            // HTTP/2 accepts extended CONNECT requests with a successful response
+            Response::builder().status(StatusCode::OK)
+        };
 
         if let Some(protocol) = self.protocol {
             builder = builder.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
@@ -389,28 +713,48 @@ where
     type Rejection = WebSocketUpgradeRejection;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        if parts.method != Method::GET {
-            return Err(MethodNotGet.into());
-        }
+        let sec_websocket_key = if parts.version <= Version::HTTP_11 {
+            if parts.method != Method::GET {
+                return Err(MethodNotGet.into());
+            }
 
-        if !header_contains(&parts.headers, header::CONNECTION, "upgrade") {
-            return Err(InvalidConnectionHeader.into());
-        }
+            if !header_contains(&parts.headers, header::CONNECTION, "upgrade") {
+                return Err(InvalidConnectionHeader.into());
+            }
 
-        if !header_eq(&parts.headers, header::UPGRADE, "websocket") {
-            return Err(InvalidUpgradeHeader.into());
-        }
+            if !header_eq(&parts.headers, header::UPGRADE, "websocket") {
+                return Err(InvalidUpgradeHeader.into());
+            }
+
+            Some(
+                parts
+                    .headers
+                    .get(header::SEC_WEBSOCKET_KEY)
+                    .ok_or(WebSocketKeyHeaderMissing)?
+                    .clone(),
+            )
+        } else {
+            // HTTP/2 uses extended CONNECT requests, from RFC 8441
+            if parts.method != Method::CONNECT {
+                return Err(MethodNotConnect.into());
+            }
+
+            // without the `http2` feature, HTTP/2 requests aren't received in the first place
+            #[cfg(feature = "http2")]
+            if !matches!(
+                parts.extensions.get::<hyper::ext::Protocol>(),
+                Some(protocol) if protocol.as_str() == "websocket"
+            ) {
+                return Err(InvalidProtocolPseudoheader.into());
+            }
+
+            None
+        };
 
         if !header_eq(&parts.headers, header::SEC_WEBSOCKET_VERSION, "13") {
             return Err(InvalidWebSocketVersionHeader.into());
         }
 
-        let sec_websocket_key = parts
-            .headers
-            .get(header::SEC_WEBSOCKET_KEY)
-            .ok_or(WebSocketKeyHeaderMissing)?
-            .clone();
-
         let on_upgrade = parts
             .extensions
             .remove::<hyper::upgrade::OnUpgrade>()
@@ -425,105 +769,849 @@ where
             on_upgrade,
             sec_websocket_protocol,
             on_failed_upgrade: DefaultOnFailedUpgrade,
+            keepalive: None,
+            rate_limits: RateLimits::default(),
+            tracker: None,
+            interceptor: None,
         })
     }
 }
 
-fn header_eq(headers: &HeaderMap, key: HeaderName, value: &'static str) -> bool {
-    if let Some(header) = headers.get(&key) {
-        header.as_bytes().eq_ignore_ascii_case(value.as_bytes())
-    } else {
-        false
-    }
+fn header_eq(headers: &HeaderMap, key: HeaderName, value: &'static str) -> bool {
+    if let Some(header) = headers.get(&key) {
+        header.as_bytes().eq_ignore_ascii_case(value.as_bytes())
+    } else {
+        false
+    }
+}
+
+fn header_contains(headers: &HeaderMap, key: HeaderName, value: &'static str) -> bool {
+    let header = if let Some(header) = headers.get(&key) {
+        header
+    } else {
+        return false;
+    };
+
+    if let Ok(header) = std::str::from_utf8(header.as_bytes()) {
+        header.to_ascii_lowercase().contains(value)
+    } else {
+        false
+    }
+}
+
+/// A stream of WebSocket messages.
+///
+/// See [the module level documentation](self) for more details.
+#[derive(Debug)]
+pub struct WebSocket {
+    inner: WebSocketStream<TokioIo<hyper::upgrade::Upgraded>>,
+    protocol: Option<HeaderValue>,
+    keepalive: Option<Keepalive>,
+    limits: Limits,
+    tracked: Option<Tracked>,
+    intercept: Option<Intercept>,
+}
+
+impl WebSocket {
+    /// Receive another message.
+    ///
+    /// Returns `None` if the stream has closed.
+    pub async fn recv(&mut self) -> Option<Result<Message, Error>> {
+        self.next().await
+    }
+
+    /// Send a message.
+    pub async fn send(&mut self, msg: Message) -> Result<(), Error> {
+        SinkExt::send(self, msg).await
+    }
+
+    /// Gracefully close this WebSocket.
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.inner.close(None).await.map_err(Error::new)
+    }
+
+    /// Return the selected WebSocket subprotocol, if one has been chosen.
+    pub fn protocol(&self) -> Option<&HeaderValue> {
+        self.protocol.as_ref()
+    }
+
+    /// Split this WebSocket into a [`WebSocketSender`] and a [`WebSocketReceiver`], spawning a
+    /// task that writes the messages sent through the sender.
+    ///
+    /// The sender can be cloned to send messages from several tasks. Once every sender is
+    /// dropped, or a [`Message::Close`] is sent, the task closes the WebSocket. If writing
+    /// fails, the error is returned by the receiver after its last message.
+    ///
+    /// The receiver must be polled to receive the client's close frame and answer pings.
+    pub fn spawn_split(self) -> (WebSocketSender, WebSocketReceiver) {
+        let (sink, stream) = self.split();
+        let (tx, rx) = tokio::sync::mpsc::channel(SPLIT_CAPACITY);
+        let error = Arc::new(AxumMutex::new(None));
+        tokio::spawn(write_split(sink, rx, error.clone()));
+        (
+            WebSocketSender { tx },
+            WebSocketReceiver {
+                inner: stream,
+                error,
+                done: false,
+            },
+        )
+    }
+}
+
+impl Stream for WebSocket {
+    type Item = Result<Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(keepalive) = &mut this.keepalive {
+                if keepalive.state == KeepaliveState::Dead {
+                    return Poll::Ready(None);
+                }
+                if let Err(err) = keepalive.poll(&mut this.inner, cx) {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+            if let Some(tracked) = &mut this.tracked {
+                if let Err(err) = tracked.poll(&mut this.inner, cx) {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+            if let Some(err) = futures_util::ready!(this.limits.poll(&mut this.inner, cx)) {
+                return Poll::Ready(Some(Err(err)));
+            }
+            if this.limits.is_closed() {
+                return Poll::Ready(None);
+            }
+
+            if let Some(intercept) = &mut this.intercept {
+                // keep receiving while the client is slow to receive the outbox
+                if let Poll::Ready(Err(err)) = intercept.poll_outbox(&mut this.inner, cx) {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+
+            match futures_util::ready!(this.inner.poll_next_unpin(cx)) {
+                Some(Ok(msg)) => {
+                    if let Some(keepalive) = &mut this.keepalive {
+                        keepalive.received();
+                    }
+                    if !this.limits.check(&msg) {
+                        continue;
+                    }
+                    let Some(msg) = Message::from_tungstenite(msg) else {
+                        continue;
+                    };
+                    let Some(intercept) = &mut this.intercept else {
+                        return Poll::Ready(Some(Ok(msg)));
+                    };
+                    if let Some(msg) = intercept.interceptor.on_receive(msg, &mut intercept.outbox)
+                    {
+                        // errors are returned by the next operation
+                        let _ = intercept.poll_outbox(&mut this.inner, cx);
+                        return Poll::Ready(Some(Ok(msg)));
+                    }
+                }
+                Some(Err(err @ ts::Error::Capacity(_))) => {
+                    this.limits
+                        .exceeded(close_code::SIZE, "message too big", Error::new(err));
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(Error::new(err)))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Sink<Message> for WebSocket {
+    type Error = Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+        if let Some(intercept) = &mut this.intercept {
+            futures_util::ready!(intercept.poll_outbox(&mut this.inner, cx))?;
+        }
+        Pin::new(&mut this.inner).poll_ready(cx).map_err(Error::new)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        let this = &mut *self;
+        let item = match &mut this.intercept {
+            Some(intercept) => match intercept.interceptor.on_send(item, &mut intercept.outbox) {
+                Some(item) => item,
+                None => return Ok(()),
+            },
+            None => item,
+        };
+        Pin::new(&mut this.inner)
+            .start_send(item.into_tungstenite())
+            .map_err(Error::new)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+        if let Some(intercept) = &mut this.intercept {
+            futures_util::ready!(intercept.poll_outbox(&mut this.inner, cx))?;
+        }
+        Pin::new(&mut this.inner).poll_flush(cx).map_err(Error::new)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+        if let Some(intercept) = &mut this.intercept {
+            futures_util::ready!(intercept.poll_outbox(&mut this.inner, cx))?;
+        }
+        Pin::new(&mut this.inner).poll_close(cx).map_err(Error::new)
+    }
+}
+
+/// How many messages a [`WebSocketSender`] buffers before waiting for them to be written.
+const SPLIT_CAPACITY: usize = 32;
+
+/// Writes the messages sent through [`WebSocketSender`]s, for [`WebSocket::spawn_split`].
+async fn write_split(
+    mut sink: SplitSink<WebSocket, Message>,
+    mut rx: tokio::sync::mpsc::Receiver<Message>,
+    error: Arc<AxumMutex<Option<Error>>>,
+) {
+    let result = async {
+        while let Some(msg) = rx.recv().await {
+            let mut close = matches!(msg, Message::Close(_));
+            sink.feed(msg).await?;
+            // write every queued message before flushing
+            while !close {
+                let Ok(msg) = rx.try_recv() else {
+                    break;
+                };
+                close = matches!(msg, Message::Close(_));
+                sink.feed(msg).await?;
+            }
+            sink.flush().await?;
+            if close {
+                return Ok(());
+            }
+        }
+        // every sender was dropped
+        sink.close().await
+    }
+    .await;
+
+    rx.close();
+    if let Err(err) = result {
+        *error.lock().unwrap() = Some(err);
+    }
+}
+
+/// The sending half of a [`WebSocket`] split with [`WebSocket::spawn_split`].
+///
+/// Cloning the sender is cheap. The WebSocket is closed once every sender is dropped.
+#[derive(Debug, Clone)]
+pub struct WebSocketSender {
+    tx: tokio::sync::mpsc::Sender<Message>,
+}
+
+impl WebSocketSender {
+    /// Send a message.
+    ///
+    /// Waits if the task writing messages has fallen behind. Returns an error if the WebSocket
+    /// has been closed, or writing to it failed.
+    pub async fn send(&self, msg: Message) -> Result<(), Error> {
+        self.tx
+            .send(msg)
+            .await
+            .map_err(|_| Error::new(ts::Error::AlreadyClosed))
+    }
+
+    /// Close the WebSocket, after writing the messages sent before.
+    pub async fn close(&self, frame: Option<CloseFrame<'static>>) -> Result<(), Error> {
+        self.send(Message::Close(frame)).await
+    }
+
+    /// Returns `true` if messages can no longer be sent, because the WebSocket has been closed or
+    /// writing to it failed.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// The receiving half of a [`WebSocket`] split with [`WebSocket::spawn_split`].
+#[derive(Debug)]
+pub struct WebSocketReceiver {
+    inner: SplitStream<WebSocket>,
+    /// Set by the writing task if it fails.
+    error: Arc<AxumMutex<Option<Error>>>,
+    done: bool,
+}
+
+impl WebSocketReceiver {
+    /// Receive another message.
+    ///
+    /// Returns `None` if the stream has closed.
+    pub async fn recv(&mut self) -> Option<Result<Message, Error>> {
+        self.next().await
+    }
+}
+
+impl Stream for WebSocketReceiver {
+    type Item = Result<Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        match futures_util::ready!(self.inner.poll_next_unpin(cx)) {
+            Some(item) => Poll::Ready(Some(item)),
+            None => {
+                self.done = true;
+                Poll::Ready(self.error.lock().unwrap().take().map(Err))
+            }
+        }
+    }
+}
+
+/// Sends pings and detects dead connections for [`WebSocketUpgrade::keepalive`].
+#[derive(Debug)]
+struct Keepalive {
+    interval: Duration,
+    timeout: Duration,
+    /// Fires when the next ping is due, or when the response to the last ping is overdue.
+    sleep: Pin<Box<Sleep>>,
+    state: KeepaliveState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeepaliveState {
+    Idle,
+    SendPing,
+    Flushing,
+    AwaitingPong,
+    Dead,
+}
+
+impl Keepalive {
+    fn new(interval: Duration, timeout: Duration) -> Self {
+        Self {
+            interval,
+            timeout,
+            sleep: Box::pin(tokio::time::sleep(interval)),
+            state: KeepaliveState::Idle,
+        }
+    }
+
+    /// Send a ping if one is due, and check whether the last one was answered in time.
+    fn poll<S>(&mut self, inner: &mut S, cx: &mut Context<'_>) -> Result<(), Error>
+    where
+        S: Sink<ts::Message, Error = ts::Error> + Unpin,
+    {
+        loop {
+            match self.state {
+                KeepaliveState::Idle => {
+                    if self.sleep.as_mut().poll(cx).is_pending() {
+                        return Ok(());
+                    }
+                    self.state = KeepaliveState::SendPing;
+                }
+                KeepaliveState::SendPing => {
+                    match Pin::new(&mut *inner).poll_ready(cx) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => return Err(Error::new(err)),
+                        Poll::Pending => return Ok(()),
+                    }
+                    Pin::new(&mut *inner)
+                        .start_send(ts::Message::Ping(Vec::new()))
+                        .map_err(Error::new)?;
+                    self.sleep.as_mut().reset(Instant::now() + self.timeout);
+                    self.state = KeepaliveState::Flushing;
+                }
+                KeepaliveState::Flushing => match Pin::new(&mut *inner).poll_flush(cx) {
+                    Poll::Ready(Ok(())) => self.state = KeepaliveState::AwaitingPong,
+                    Poll::Ready(Err(err)) => return Err(Error::new(err)),
+                    // a client that doesn't read can't respond either
+                    Poll::Pending => return self.check_timeout(cx),
+                },
+                KeepaliveState::AwaitingPong => return self.check_timeout(cx),
+                KeepaliveState::Dead => return Ok(()),
+            }
+        }
+    }
+
+    fn check_timeout(&mut self, cx: &mut Context<'_>) -> Result<(), Error> {
+        if self.sleep.as_mut().poll(cx).is_pending() {
+            return Ok(());
+        }
+        self.state = KeepaliveState::Dead;
+        Err(Error::new(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "WebSocket client didn't respond to ping",
+        )))
+    }
+
+    /// Any message from the client shows that the connection is alive.
+    fn received(&mut self) {
+        match self.state {
+            KeepaliveState::Idle | KeepaliveState::SendPing | KeepaliveState::AwaitingPong => {
+                self.sleep.as_mut().reset(Instant::now() + self.interval);
+                self.state = KeepaliveState::Idle;
+            }
+            // wait for the ping to be sent, so it doesn't linger in the write buffer
+            KeepaliveState::Flushing | KeepaliveState::Dead => {}
+        }
+    }
+}
+
+/// Observes, changes, or drops the messages of a [`WebSocket`].
+///
+/// See [`WebSocketUpgrade::intercept`] for more details.
+pub trait Interceptor: Send + Sync + 'static {
+    /// Called with each message received from the client, before the socket returns it.
+    ///
+    /// Return `None` to drop the message. Messages sent with `outbox` are sent to the client.
+    ///
+    /// By default the message is returned unchanged.
+    fn on_receive(&mut self, msg: Message, outbox: &mut Outbox) -> Option<Message> {
+        let _ = outbox;
+        Some(msg)
+    }
+
+    /// Called with each message sent with the socket, before it's sent to the client.
+    ///
+    /// Return `None` to drop the message. Messages sent with `outbox` are sent to the client
+    /// after the returned message.
+    ///
+    /// By default the message is returned unchanged.
+    fn on_send(&mut self, msg: Message, outbox: &mut Outbox) -> Option<Message> {
+        let _ = outbox;
+        Some(msg)
+    }
+}
+
+/// Messages sent to the client by an [`Interceptor`].
+///
+/// These messages aren't passed to [`Interceptor::on_send`].
+#[derive(Debug, Default)]
+pub struct Outbox {
+    messages: VecDeque<Message>,
+}
+
+impl Outbox {
+    /// Send a message to the client.
+    pub fn send(&mut self, msg: Message) {
+        self.messages.push_back(msg);
+    }
+}
+
+/// The [`Interceptor`] of a [`WebSocket`] and the messages it sent.
+struct Intercept {
+    interceptor: Box<dyn Interceptor>,
+    outbox: Outbox,
+    needs_flush: bool,
+}
+
+impl Intercept {
+    /// Send and flush the messages in the outbox.
+    fn poll_outbox<S>(&mut self, inner: &mut S, cx: &mut Context<'_>) -> Poll<Result<(), Error>>
+    where
+        S: Sink<ts::Message, Error = ts::Error> + Unpin,
+    {
+        while !self.outbox.messages.is_empty() {
+            futures_util::ready!(Pin::new(&mut *inner).poll_ready(cx)).map_err(Error::new)?;
+            if let Some(msg) = self.outbox.messages.pop_front() {
+                Pin::new(&mut *inner)
+                    .start_send(msg.into_tungstenite())
+                    .map_err(Error::new)?;
+                self.needs_flush = true;
+            }
+        }
+
+        if self.needs_flush {
+            futures_util::ready!(Pin::new(&mut *inner).poll_flush(cx)).map_err(Error::new)?;
+            self.needs_flush = false;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl std::fmt::Debug for Intercept {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Intercept")
+            .field("outbox", &self.outbox)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RateLimits {
+    messages_per_second: Option<u32>,
+    bytes_per_second: Option<u64>,
+}
+
+/// Enforces the limits on what the client sends, and closes the connection if one is exceeded.
+#[derive(Debug)]
+struct Limits {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    state: LimitsState,
+}
+
+#[derive(Debug)]
+enum LimitsState {
+    Open,
+    SendClose(CloseFrame<'static>, Error),
+    Flushing(Error),
+    Closed,
+}
+
+impl Limits {
+    fn new(config: RateLimits) -> Self {
+        Self {
+            messages: config
+                .messages_per_second
+                .map(|max| TokenBucket::new(max.into())),
+            bytes: config.bytes_per_second.map(TokenBucket::new),
+            state: LimitsState::Open,
+        }
+    }
+
+    /// Check a received message against the rate limits. Returns `false` if one was exceeded.
+    fn check(&mut self, msg: &ts::Message) -> bool {
+        let messages_ok = match &mut self.messages {
+            Some(bucket) => bucket.take(1),
+            None => true,
+        };
+        let bytes_ok = match &mut self.bytes {
+            Some(bucket) => bucket.take(msg.len() as u64),
+            None => true,
+        };
+        if messages_ok && bytes_ok {
+            return true;
+        }
+
+        self.exceeded(
+            close_code::POLICY,
+            "rate limit exceeded",
+            Error::new(io::Error::new(
+                io::ErrorKind::Other,
+                "WebSocket client exceeded the rate limit",
+            )),
+        );
+        false
+    }
+
+    fn exceeded(&mut self, code: CloseCode, reason: &'static str, error: Error) {
+        if let LimitsState::Open = self.state {
+            let frame = CloseFrame {
+                code,
+                reason: reason.into(),
+            };
+            self.state = LimitsState::SendClose(frame, error);
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        matches!(self.state, LimitsState::Closed)
+    }
+
+    /// Send the close frame after a limit was exceeded, and then return the error.
+    ///
+    /// Failing to send the close frame is ignored, since the connection is being closed anyway.
+    fn poll<S>(&mut self, inner: &mut S, cx: &mut Context<'_>) -> Poll<Option<Error>>
+    where
+        S: Sink<ts::Message, Error = ts::Error> + Unpin,
+    {
+        loop {
+            match std::mem::replace(&mut self.state, LimitsState::Closed) {
+                LimitsState::Open => {
+                    self.state = LimitsState::Open;
+                    return Poll::Ready(None);
+                }
+                LimitsState::SendClose(frame, error) => {
+                    match Pin::new(&mut *inner).poll_ready(cx) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(_)) => return Poll::Ready(Some(error)),
+                        Poll::Pending => {
+                            self.state = LimitsState::SendClose(frame, error);
+                            return Poll::Pending;
+                        }
+                    }
+                    let msg = Message::Close(Some(frame)).into_tungstenite();
+                    if Pin::new(&mut *inner).start_send(msg).is_err() {
+                        return Poll::Ready(Some(error));
+                    }
+                    self.state = LimitsState::Flushing(error);
+                }
+                LimitsState::Flushing(error) => match Pin::new(&mut *inner).poll_flush(cx) {
+                    Poll::Ready(_) => return Poll::Ready(Some(error)),
+                    Poll::Pending => {
+                        self.state = LimitsState::Flushing(error);
+                        return Poll::Pending;
+                    }
+                },
+                LimitsState::Closed => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Allows bursts of up to `rate` tokens, refilled at `rate` tokens per second.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: u64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last: Instant::now(),
+        }
+    }
+
+    fn take(&mut self, tokens: u64) -> bool {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.rate as f64;
+        self.tokens = (self.tokens + refill).min(self.rate as f64);
+        self.last = now;
+
+        if self.tokens < tokens as f64 {
+            return false;
+        }
+        self.tokens -= tokens as f64;
+        true
+    }
+}
+
+/// Tracks WebSockets so they can be closed during graceful shutdown.
+///
+/// Upgraded connections aren't tracked by [`serve`](crate::serve), so by default open WebSockets
+/// don't delay a graceful shutdown, and are dropped abruptly once the runtime shuts down. Sockets
+/// added with [`WebSocketUpgrade::track`] can instead be closed with
+/// [`close_all`](Self::close_all), which sends a close frame to each of them and waits for them
+/// to finish.
+///
+/// The close frame is sent while the socket is being read from, like the pings of
+/// [`WebSocketUpgrade::keepalive`], so something must keep receiving messages. The socket then
+/// receives the client's [`Message::Close`] followed by `None`, and is considered finished once
+/// it has been dropped.
+///
+/// `WebSocketTracker` is cheap to clone, and clones track the same sockets.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{
+///     extract::{
+///         ws::{close_code, CloseFrame, WebSocket, WebSocketTracker, WebSocketUpgrade},
+///         State,
+///     },
+///     response::Response,
+///     routing::get,
+///     Router,
+/// };
+/// use std::time::Duration;
+///
+/// async fn handler(ws: WebSocketUpgrade, State(tracker): State<WebSocketTracker>) -> Response {
+///     ws.track(&tracker).on_upgrade(handle_socket)
+/// }
+///
+/// async fn handle_socket(mut socket: WebSocket) {
+///     while let Some(Ok(msg)) = socket.recv().await {
+///         // ...
+///     }
+/// }
+///
+/// # async {
+/// let tracker = WebSocketTracker::new();
+/// let app = Router::new()
+///     .route("/ws", get(handler))
+///     .with_state(tracker.clone());
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+/// axum::serve(listener, app)
+///     .with_graceful_shutdown(async move {
+///         shutdown_signal().await;
+///
+///         let frame = CloseFrame {
+///             code: close_code::AWAY,
+///             reason: "server is shutting down".into(),
+///         };
+///         if !tracker.close_all(frame, Duration::from_secs(5)).await {
+///             eprintln!("{} WebSockets didn't close in time", tracker.len());
+///         }
+///     })
+///     .await
+///     .unwrap();
+/// # };
+/// # async fn shutdown_signal() {}
+/// ```
+#[derive(Clone, Default)]
+pub struct WebSocketTracker {
+    state: Arc<AxumMutex<TrackerState>>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    next_id: u64,
+    /// The close frame to send, once [`WebSocketTracker::close_all`] has been called.
+    closing: Option<CloseFrame<'static>>,
+    /// The open sockets, with the waker of the task reading from each of them.
+    sockets: HashMap<u64, Option<Waker>>,
+    /// Wakers of `close_all` calls waiting for the sockets to finish.
+    finished: Vec<Waker>,
 }
 
-fn header_contains(headers: &HeaderMap, key: HeaderName, value: &'static str) -> bool {
-    let header = if let Some(header) = headers.get(&key) {
-        header
-    } else {
-        return false;
-    };
+impl WebSocketTracker {
+    /// Create a new `WebSocketTracker`.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    if let Ok(header) = std::str::from_utf8(header.as_bytes()) {
-        header.to_ascii_lowercase().contains(value)
-    } else {
-        false
+    /// Get the number of open sockets.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().sockets.len()
     }
-}
 
-/// A stream of WebSocket messages.
-///
-/// See [the module level documentation](self) for more details.
-#[derive(Debug)]
-pub struct WebSocket {
-    inner: WebSocketStream<TokioIo<hyper::upgrade::Upgraded>>,
-    protocol: Option<HeaderValue>,
-}
+    /// Returns `true` if there are no open sockets.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-impl WebSocket {
-    /// Receive another message.
+    /// Send `frame` to every tracked socket, and wait for them to finish for at most `timeout`.
     ///
-    /// Returns `None` if the stream has closed.
-    pub async fn recv(&mut self) -> Option<Result<Message, Error>> {
-        self.next().await
+    /// Sockets tracked afterwards are also closed as soon as they are read from.
+    ///
+    /// Returns `false` if some sockets were still open after `timeout`.
+    pub async fn close_all(&self, frame: CloseFrame<'static>, timeout: Duration) -> bool {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.closing = Some(frame);
+            state
+                .sockets
+                .values_mut()
+                .filter_map(Option::take)
+                .collect::<Vec<_>>()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+
+        let finished = poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.sockets.is_empty() {
+                Poll::Ready(())
+            } else {
+                state.finished.push(cx.waker().clone());
+                Poll::Pending
+            }
+        });
+        tokio::time::timeout(timeout, finished).await.is_ok()
     }
 
-    /// Send a message.
-    pub async fn send(&mut self, msg: Message) -> Result<(), Error> {
-        self.inner
-            .send(msg.into_tungstenite())
-            .await
-            .map_err(Error::new)
+    fn register(self) -> Tracked {
+        let id = {
+            let mut state = self.state.lock().unwrap();
+            let id = state.next_id;
+            state.next_id += 1;
+            state.sockets.insert(id, None);
+            id
+        };
+        Tracked {
+            tracker: self,
+            id,
+            state: TrackedState::Open,
+        }
     }
+}
 
-    /// Gracefully close this WebSocket.
-    pub async fn close(mut self) -> Result<(), Error> {
-        self.inner.close(None).await.map_err(Error::new)
+impl std::fmt::Debug for WebSocketTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("WebSocketTracker")
+            .field("sockets", &state.sockets.len())
+            .field("closing", &state.closing)
+            .finish()
     }
+}
 
-    /// Return the selected WebSocket subprotocol, if one has been chosen.
-    pub fn protocol(&self) -> Option<&HeaderValue> {
-        self.protocol.as_ref()
-    }
+/// A socket tracked by a [`WebSocketTracker`], removed from it when dropped.
+#[derive(Debug)]
+struct Tracked {
+    tracker: WebSocketTracker,
+    id: u64,
+    state: TrackedState,
 }
 
-impl Stream for WebSocket {
-    type Item = Result<Message, Error>;
+#[derive(Debug)]
+enum TrackedState {
+    Open,
+    SendClose(CloseFrame<'static>),
+    Flushing,
+    Closed,
+}
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+impl Tracked {
+    /// Send the close frame once the tracker is closing.
+    fn poll<S>(&mut self, inner: &mut S, cx: &mut Context<'_>) -> Result<(), Error>
+    where
+        S: Sink<ts::Message, Error = ts::Error> + Unpin,
+    {
         loop {
-            match futures_util::ready!(self.inner.poll_next_unpin(cx)) {
-                Some(Ok(msg)) => {
-                    if let Some(msg) = Message::from_tungstenite(msg) {
-                        return Poll::Ready(Some(Ok(msg)));
+            match &self.state {
+                TrackedState::Open => {
+                    let mut state = self.tracker.state.lock().unwrap();
+                    match &state.closing {
+                        Some(frame) => self.state = TrackedState::SendClose(frame.clone()),
+                        None => {
+                            if let Some(waker) = state.sockets.get_mut(&self.id) {
+                                *waker = Some(cx.waker().clone());
+                            }
+                            return Ok(());
+                        }
                     }
                 }
-                Some(Err(err)) => return Poll::Ready(Some(Err(Error::new(err)))),
-                None => return Poll::Ready(None),
+                TrackedState::SendClose(frame) => {
+                    match Pin::new(&mut *inner).poll_ready(cx) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => {
+                            self.state = TrackedState::Closed;
+                            return Err(Error::new(err));
+                        }
+                        Poll::Pending => return Ok(()),
+                    }
+                    let msg = Message::Close(Some(frame.clone())).into_tungstenite();
+                    self.state = TrackedState::Flushing;
+                    Pin::new(&mut *inner).start_send(msg).map_err(|err| {
+                        self.state = TrackedState::Closed;
+                        Error::new(err)
+                    })?;
+                }
+                TrackedState::Flushing => match Pin::new(&mut *inner).poll_flush(cx) {
+                    Poll::Ready(Ok(())) => self.state = TrackedState::Closed,
+                    Poll::Ready(Err(err)) => {
+                        self.state = TrackedState::Closed;
+                        return Err(Error::new(err));
+                    }
+                    Poll::Pending => return Ok(()),
+                },
+                TrackedState::Closed => return Ok(()),
             }
         }
     }
 }
 
-impl Sink<Message> for WebSocket {
-    type Error = Error;
-
-    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Pin::new(&mut self.inner).poll_ready(cx).map_err(Error::new)
-    }
-
-    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
-        Pin::new(&mut self.inner)
-            .start_send(item.into_tungstenite())
-            .map_err(Error::new)
-    }
-
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Pin::new(&mut self.inner).poll_flush(cx).map_err(Error::new)
-    }
-
-    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Pin::new(&mut self.inner).poll_close(cx).map_err(Error::new)
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        let wakers = {
+            let mut state = self.tracker.state.lock().unwrap();
+            state.sockets.remove(&self.id);
+            if state.sockets.is_empty() {
+                std::mem::take(&mut state.finished)
+            } else {
+                Vec::new()
+            }
+        };
+        wakers.into_iter().for_each(Waker::wake);
     }
 }
 
@@ -708,6 +1796,15 @@ pub mod rejection {
         pub struct MethodNotGet;
     }
 
+    define_rejection! {
+        #[status = METHOD_NOT_ALLOWED]
+        #[body = "Request method must be `CONNECT`"]
+        /// Rejection type for [`WebSocketUpgrade`](super::WebSocketUpgrade).
+        ///
+        /// This rejection is returned if an HTTP/2 request isn't an extended `CONNECT` request.
+        pub struct MethodNotConnect;
+    }
+
     define_rejection! {
         #[status = BAD_REQUEST]
         #[body = "Connection header did not include 'upgrade'"]
@@ -722,6 +1819,13 @@ pub mod rejection {
         pub struct InvalidUpgradeHeader;
     }
 
+    define_rejection! {
+        #[status = BAD_REQUEST]
+        #[body = "`:protocol` pseudo-header missing or invalid"]
+        /// Rejection type for [`WebSocketUpgrade`](super::WebSocketUpgrade).
+        pub struct InvalidProtocolPseudoheader;
+    }
+
     define_rejection! {
         #[status = BAD_REQUEST]
         #[body = "`Sec-WebSocket-Version` header did not include '13'"]
@@ -757,8 +1861,10 @@ pub mod rejection {
         /// extractor can fail.
         pub enum WebSocketUpgradeRejection {
             MethodNotGet,
+            MethodNotConnect,
             InvalidConnectionHeader,
             InvalidUpgradeHeader,
+            InvalidProtocolPseudoheader,
             InvalidWebSocketVersionHeader,
             WebSocketKeyHeaderMissing,
             ConnectionNotUpgradable,
@@ -830,7 +1936,7 @@ pub mod close_code {
 
 #[cfg(test)]
 mod tests {
-    use std::future::ready;
+    use std::{future::ready, sync::Arc};
 
     use super::*;
     use crate::{routing::get, test_helpers::spawn_service, Router};
@@ -923,4 +2029,386 @@ mod tests {
             tungstenite::Message::Pong("ping".to_owned().into_bytes())
         );
     }
+
+    #[cfg(feature = "http2")]
+    #[crate::test]
+    async fn http2() {
+        use hyper_util::rt::TokioExecutor;
+        use tokio::net::TcpStream;
+
+        let echo = |ws: WebSocketUpgrade| {
+            ready(ws.on_upgrade(|mut socket| async move {
+                while let Some(Ok(msg)) = socket.recv().await {
+                    if socket.send(msg).await.is_err() {
+                        break;
+                    }
+                }
+            }))
+        };
+        let app = Router::new().route("/echo", get(echo).connect(echo));
+        let addr = spawn_service(app);
+
+        let io = TokioIo::new(TcpStream::connect(addr).await.unwrap());
+        let (mut send_request, conn) =
+            hyper::client::conn::http2::Builder::new(TokioExecutor::new())
+                .handshake(io)
+                .await
+                .unwrap();
+        // wait for the server's SETTINGS frame, which enables extended CONNECT
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(conn.is_extended_connect_protocol_enabled());
+        tokio::spawn(conn);
+
+        let req = Request::builder()
+            .method(Method::CONNECT)
+            .extension(hyper::ext::Protocol::from_static("websocket"))
+            .uri(format!("http://{addr}/echo"))
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .body(Body::empty())
+            .unwrap();
+        let res = send_request.send_request(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let upgraded = TokioIo::new(hyper::upgrade::on(res).await.unwrap());
+        let mut socket =
+            WebSocketStream::from_raw_socket(upgraded, protocol::Role::Client, None).await;
+
+        let input = tungstenite::Message::Text("foobar".to_owned());
+        socket.send(input.clone()).await.unwrap();
+        let output = socket.next().await.unwrap().unwrap();
+        assert_eq!(input, output);
+    }
+
+    #[cfg(feature = "http2")]
+    #[crate::test]
+    async fn rejects_http2_get_requests() {
+        let svc = get(|ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>| {
+            let rejection = ws.unwrap_err();
+            assert!(matches!(
+                rejection,
+                WebSocketUpgradeRejection::MethodNotConnect(_)
+            ));
+            std::future::ready(())
+        });
+
+        let req = Request::builder()
+            .version(Version::HTTP_2)
+            .method(Method::GET)
+            .header("sec-websocket-version", "13")
+            .body(Body::empty())
+            .unwrap();
+
+        let res = svc.oneshot(req).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[crate::test]
+    async fn select_protocol() {
+        use tungstenite::client::IntoClientRequest;
+
+        let app = Router::new().route(
+            "/",
+            get(|ws: WebSocketUpgrade| async move {
+                ws.select_protocol(|offered| async move {
+                    assert_eq!(offered, ["chat.v1", "chat.v2", "other"]);
+                    offered.into_iter().rev().find(|p| p.starts_with("chat."))
+                })
+                .await
+                .on_upgrade(|socket| async move {
+                    assert_eq!(socket.protocol().unwrap(), "chat.v2");
+                })
+            }),
+        );
+
+        let addr = spawn_service(app);
+        let mut req = format!("ws://{addr}/").into_client_request().unwrap();
+        req.headers_mut().insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static("chat.v1, chat.v2, other"),
+        );
+        let (_socket, res) = tokio_tungstenite::connect_async(req).await.unwrap();
+
+        assert_eq!(res.headers()[header::SEC_WEBSOCKET_PROTOCOL], "chat.v2");
+    }
+
+    #[crate::test]
+    async fn keepalive() {
+        let app = Router::new().route(
+            "/",
+            get(|ws: WebSocketUpgrade| {
+                ready(
+                    ws.keepalive(Duration::from_millis(20), Duration::from_millis(50))
+                        .on_upgrade(|mut socket| async move {
+                            while let Some(Ok(_)) = socket.recv().await {}
+                        }),
+                )
+            }),
+        );
+
+        let addr = spawn_service(app);
+        let (mut socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+
+        // reading sends the responses to the pings
+        for _ in 0..3 {
+            let msg = socket.next().await.unwrap().unwrap();
+            assert_eq!(msg, tungstenite::Message::Ping(Vec::new()));
+        }
+    }
+
+    #[crate::test]
+    async fn keepalive_closes_dead_connections() {
+        let error_kind = Arc::new(AxumMutex::new(None));
+        let app = Router::new().route(
+            "/",
+            get({
+                let error_kind = error_kind.clone();
+                move |ws: WebSocketUpgrade| {
+                    let error_kind = error_kind.clone();
+                    ready(
+                        ws.keepalive(Duration::from_millis(20), Duration::from_millis(50))
+                            .on_upgrade(|mut socket| async move {
+                                let err = loop {
+                                    match socket.recv().await {
+                                        Some(Ok(_)) => {}
+                                        Some(Err(err)) => break err,
+                                        None => panic!("connection closed without an error"),
+                                    }
+                                };
+                                assert!(socket.recv().await.is_none());
+                                let err = err.into_inner().downcast::<io::Error>().unwrap();
+                                *error_kind.lock().unwrap() = Some(err.kind());
+                            }),
+                    )
+                }
+            }),
+        );
+
+        let addr = spawn_service(app);
+        // never read from the socket, so pings aren't answered
+        let (_socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+
+        for _ in 0..100 {
+            if error_kind.lock().unwrap().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*error_kind.lock().unwrap(), Some(io::ErrorKind::TimedOut));
+    }
+
+    #[crate::test]
+    async fn intercept() {
+        struct Shout;
+
+        impl Interceptor for Shout {
+            fn on_receive(&mut self, msg: Message, outbox: &mut Outbox) -> Option<Message> {
+                match msg {
+                    Message::Text(text) if text == "drop" => None,
+                    Message::Text(text) if text == "ping!" => {
+                        outbox.send(Message::Text("pong!".to_owned()));
+                        None
+                    }
+                    Message::Text(text) => Some(Message::Text(text.to_uppercase())),
+                    msg => Some(msg),
+                }
+            }
+
+            fn on_send(&mut self, msg: Message, _outbox: &mut Outbox) -> Option<Message> {
+                match msg {
+                    Message::Text(text) => Some(Message::Text(format!("out:{text}"))),
+                    msg => Some(msg),
+                }
+            }
+        }
+
+        let app = Router::new().route(
+            "/",
+            get(|ws: WebSocketUpgrade| {
+                ready(ws.intercept(Shout).on_upgrade(|mut socket| async move {
+                    while let Some(Ok(msg)) = socket.recv().await {
+                        if socket.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                }))
+            }),
+        );
+
+        let addr = spawn_service(app);
+        let (mut socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+        for msg in ["drop", "ping!", "hello"] {
+            socket
+                .send(tungstenite::Message::Text(msg.to_owned()))
+                .await
+                .unwrap();
+        }
+
+        let output = socket.next().await.unwrap().unwrap();
+        assert_eq!(output, tungstenite::Message::Text("pong!".to_owned()));
+        let output = socket.next().await.unwrap().unwrap();
+        assert_eq!(output, tungstenite::Message::Text("out:HELLO".to_owned()));
+    }
+
+    #[crate::test]
+    async fn spawn_split() {
+        async fn handle_socket(socket: WebSocket) {
+            let (sender, mut receiver) = socket.spawn_split();
+            sender
+                .send(Message::Text("hello".to_owned()))
+                .await
+                .unwrap();
+
+            if let Some(Ok(msg)) = receiver.recv().await {
+                let sender = sender.clone();
+                tokio::spawn(async move { sender.send(msg).await.unwrap() });
+            }
+            // the socket is closed once every sender is dropped
+            drop(sender);
+            while let Some(Ok(_)) = receiver.recv().await {}
+        }
+
+        let app = Router::new().route(
+            "/",
+            get(|ws: WebSocketUpgrade| ready(ws.on_upgrade(handle_socket))),
+        );
+        let addr = spawn_service(app);
+        let (mut socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+
+        let msg = socket.next().await.unwrap().unwrap();
+        assert_eq!(msg, tungstenite::Message::Text("hello".to_owned()));
+
+        let input = tungstenite::Message::Text("echo".to_owned());
+        socket.send(input.clone()).await.unwrap();
+        assert_eq!(socket.next().await.unwrap().unwrap(), input);
+
+        let msg = socket.next().await.unwrap().unwrap();
+        assert!(matches!(msg, tungstenite::Message::Close(_)), "{msg:?}");
+    }
+
+    async fn close_code_after_sending<F>(configure: F, messages: Vec<tungstenite::Message>) -> u16
+    where
+        F: Fn(WebSocketUpgrade) -> WebSocketUpgrade + Clone + Send + Sync + 'static,
+    {
+        let app = Router::new().route(
+            "/",
+            get(move |ws: WebSocketUpgrade| {
+                ready(configure(ws).on_upgrade(|mut socket| async move {
+                    while let Some(Ok(_)) = socket.recv().await {}
+                }))
+            }),
+        );
+
+        let addr = spawn_service(app);
+        let (mut socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+        for msg in messages {
+            // the server may have closed the connection already
+            let _ = socket.send(msg).await;
+        }
+
+        loop {
+            match socket.next().await {
+                Some(Ok(tungstenite::Message::Close(Some(frame)))) => return frame.code.into(),
+                Some(Ok(_)) => {}
+                other => panic!("expected a close frame, got {other:?}"),
+            }
+        }
+    }
+
+    #[crate::test]
+    async fn max_messages_per_second() {
+        let messages = vec![tungstenite::Message::Text("a".to_owned()); 5];
+        let code = close_code_after_sending(|ws| ws.max_messages_per_second(2), messages).await;
+        assert_eq!(code, close_code::POLICY);
+    }
+
+    #[crate::test]
+    async fn max_bytes_per_second() {
+        let messages = vec![tungstenite::Message::Text("a".repeat(20))];
+        let code = close_code_after_sending(|ws| ws.max_bytes_per_second(10), messages).await;
+        assert_eq!(code, close_code::POLICY);
+    }
+
+    #[crate::test]
+    async fn max_message_size() {
+        let messages = vec![tungstenite::Message::Text("a".repeat(20))];
+        let code = close_code_after_sending(|ws| ws.max_message_size(10), messages).await;
+        assert_eq!(code, close_code::SIZE);
+    }
+
+    fn tracked_app(tracker: &WebSocketTracker, read: bool) -> Router {
+        let tracker = tracker.clone();
+        Router::new().route(
+            "/",
+            get(move |ws: WebSocketUpgrade| {
+                ready(ws.track(&tracker).on_upgrade(move |mut socket| async move {
+                    if read {
+                        while let Some(Ok(_)) = socket.recv().await {}
+                    } else {
+                        tokio::time::sleep(Duration::from_secs(10)).await;
+                    }
+                }))
+            }),
+        )
+    }
+
+    #[crate::test]
+    async fn tracker_closes_sockets() {
+        let tracker = WebSocketTracker::new();
+        let addr = spawn_service(tracked_app(&tracker, true));
+        let (mut socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+        assert_eq!(tracker.len(), 1);
+
+        let frame = CloseFrame {
+            code: close_code::AWAY,
+            reason: "shutting down".into(),
+        };
+        let (closed, msg) = tokio::join!(tracker.close_all(frame, Duration::from_secs(1)), async {
+            let msg = socket.next().await.unwrap().unwrap();
+            // reply to the close frame and wait for the connection to close
+            while let Some(Ok(_)) = socket.next().await {}
+            msg
+        },);
+
+        match msg {
+            tungstenite::Message::Close(Some(frame)) => {
+                assert_eq!(u16::from(frame.code), close_code::AWAY);
+                assert_eq!(frame.reason, "shutting down");
+            }
+            msg => panic!("expected a close frame, got {msg:?}"),
+        }
+        assert!(closed);
+        assert!(tracker.is_empty());
+    }
+
+    #[crate::test]
+    async fn tracker_times_out() {
+        let tracker = WebSocketTracker::new();
+        let addr = spawn_service(tracked_app(&tracker, false));
+        let (_socket, _response) = tokio_tungstenite::connect_async(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+
+        let frame = CloseFrame {
+            code: close_code::AWAY,
+            reason: "shutting down".into(),
+        };
+        // the socket is never read from, so it isn't closed
+        assert!(!tracker.close_all(frame, Duration::from_millis(50)).await);
+        assert_eq!(tracker.len(), 1);
+    }
 }
@@ -29,6 +29,14 @@ use serde::{de::DeserializeOwned, Serialize};
 ///
 /// See [`JsonRejection`] for more details.
 ///
+/// The body is deserialized into owned data, so every string is copied out of the body. To
+/// deserialize into types that borrow from the body instead, such as `&str` or `Cow<str>` fields,
+/// use [`JsonDeserializer`] from [axum-extra], which keeps the body as contiguous [`Bytes`] for as
+/// long as the handler runs.
+///
+/// [`JsonDeserializer`]: https://docs.rs/axum-extra/latest/axum_extra/extract/struct.JsonDeserializer.html
+/// [axum-extra]: https://docs.rs/axum-extra/latest/axum_extra/
+///
 /// # Extractor example
 ///
 /// ```rust,no_run
@@ -209,6 +217,113 @@ where
     }
 }
 
+/// JSON Extractor / Response that uses [`simd-json`] for parsing.
+///
+/// This works like [`Json`], and is rejected with the same [`JsonRejection`]s, but parses the
+/// request body using SIMD instructions. This can be faster for large payloads, so it's useful
+/// for APIs where parsing JSON dominates CPU usage.
+///
+/// simd-json parses in place, so the extractor takes ownership of the request body and parses
+/// it without copying it, unless the body is shared with something else. Errors include the path
+/// to the field that failed to deserialize, just like with `Json`.
+///
+/// simd-json picks the SIMD instructions to use at compile time, so it's only faster when built
+/// for a CPU that supports them, for example with `RUSTFLAGS="-C target-cpu=native"`. Compare it
+/// with `Json` for your payloads, for example with the `receive-large-json` and
+/// `receive-large-simd-json` benchmarks, before switching.
+///
+/// As a response it's serialized like [`Json`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use axum::{routing::post, Router, SimdJson};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+///     password: String,
+/// }
+///
+/// async fn create_user(SimdJson(payload): SimdJson<CreateUser>) {
+///     // payload is a `CreateUser`
+/// }
+///
+/// let app = Router::new().route("/users", post(create_user));
+/// # let _: Router = app;
+/// ```
+///
+/// [`simd-json`]: https://crates.io/crates/simd-json
+#[cfg(feature = "simd-json")]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "simd-json")))]
+#[must_use]
+pub struct SimdJson<T>(pub T);
+
+#[cfg(feature = "simd-json")]
+#[async_trait]
+impl<T, S> FromRequest<S> for SimdJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = JsonRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if json_content_type(req.headers()) {
+            let bytes = Bytes::from_request(req, state).await?;
+            Self::from_buf(Vec::from(bytes))
+        } else {
+            Err(MissingJsonContentType.into())
+        }
+    }
+}
+
+#[cfg(feature = "simd-json")]
+axum_core::__impl_deref!(SimdJson);
+
+#[cfg(feature = "simd-json")]
+impl<T> From<T> for SimdJson<T> {
+    fn from(inner: T) -> Self {
+        Self(inner)
+    }
+}
+
+#[cfg(feature = "simd-json")]
+impl<T> SimdJson<T>
+where
+    T: DeserializeOwned,
+{
+    /// Construct a `SimdJson<T>` from a byte slice.
+    ///
+    /// simd-json parses in place, so the bytes are copied into a mutable buffer first.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, JsonRejection> {
+        Self::from_buf(bytes.to_vec())
+    }
+
+    fn from_buf(mut buf: Vec<u8>) -> Result<Self, JsonRejection> {
+        // this is `simd_json::serde::from_slice` with the deserializer wrapped to track the path
+        // to the field that failed to deserialize. The whole input is parsed in `from_slice`, so
+        // errors after that are data errors.
+        let mut deserializer =
+            simd_json::Deserializer::from_slice(&mut buf).map_err(JsonSyntaxError::from_err)?;
+        serde_path_to_error::deserialize(&mut deserializer)
+            .map(SimdJson)
+            .map_err(|err| JsonDataError::from_err(err).into())
+    }
+}
+
+#[cfg(feature = "simd-json")]
+impl<T> IntoResponse for SimdJson<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,4 +435,39 @@ mod tests {
             "Failed to deserialize the JSON body into the target type: b[0]: missing field `y` at line 1 column 23"
         );
     }
+
+    #[cfg(feature = "simd-json")]
+    #[crate::test]
+    async fn simd_json_extractor() {
+        let app = Router::new().route(
+            "/",
+            post(|SimdJson(input): SimdJson<Foo>| async move { input.a.to_string() }),
+        );
+
+        let client = TestClient::new(app);
+        let res = client
+            .post("/")
+            .json(&json!({ "a": 1, "b": [{ "x": 2, "y": 3 }] }))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "1");
+
+        let res = client.post("/").body("{ \"a\": 1 }").await;
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let res = client
+            .post("/")
+            .body("{")
+            .header("content-type", "application/json")
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client
+            .post("/")
+            .body("{\"a\": 1, \"b\": [{\"x\": 2}]}")
+            .header("content-type", "application/json")
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(res.text().await.contains("b[0]"));
+    }
 }
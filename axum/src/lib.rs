@@ -201,7 +201,9 @@
 //! You should prefer using [`State`] if possible since it's more type safe. The downside is that
 //! it's less dynamic than request extensions.
 //!
-//! See [`State`] for more details about accessing state.
+//! See [`State`] for more details about accessing state, and
+//! [`StateRef`](crate::extract::StateRef) for extracting state that's shared behind an `Arc`
+//! without naming the `Arc`.
 //!
 //! ## Using request extensions
 //!
@@ -330,6 +332,9 @@
 //!
 //! Name | Description | Default?
 //! ---|---|---
+//! `acme` | Enables [`serve::acme`] for automatic certificates from ACME certificate authorities such as Let's Encrypt | No
+//! `h3` | Enables [`serve_h3`] for serving HTTP/3 over QUIC with `quinn` | No
+//! `handoff` | Enables [`serve::handoff`] for restarting without downtime by handing the listening socket to a new process | No
 //! `http1` | Enables hyper's `http1` feature | Yes
 //! `http2` | Enables hyper's `http2` feature | No
 //! `json` | Enables the [`Json`] type and some similar convenience functionality | Yes
@@ -337,16 +342,24 @@
 //! `matched-path` | Enables capturing of every request's router path and the [`MatchedPath`] extractor | Yes
 //! `multipart` | Enables parsing `multipart/form-data` requests with [`Multipart`] | No
 //! `original-uri` | Enables capturing of every request's original URI and the [`OriginalUri`] extractor | Yes
+//! `systemd` | Enables [`serve::systemd`] for systemd socket activation and readiness notifications | No
+//! `tls-rustls` | Enables [`serve_tls`] for serving over TLS with `rustls` | No
 //! `tokio` | Enables `tokio` as a dependency and `axum::serve`, `SSE` and `extract::connect_info` types. | Yes
 //! `tower-log` | Enables `tower`'s `log` feature | Yes
 //! `tracing` | Log rejections from built-in extractors | Yes
 //! `ws` | Enables WebSockets support via [`extract::ws`] | No
 //! `form` | Enables the `Form` extractor | Yes
 //! `query` | Enables the `Query` extractor | Yes
+//! `simd-json` | Enables the `SimdJson` type which parses JSON with `simd-json` | No
 //!
 //! [`MatchedPath`]: crate::extract::MatchedPath
 //! [`Multipart`]: crate::extract::Multipart
 //! [`OriginalUri`]: crate::extract::OriginalUri
+//! [`serve_h3`]: crate::serve_h3
+//! [`serve_tls`]: crate::serve_tls
+//! [`serve::systemd`]: crate::serve::systemd
+//! [`serve::acme`]: crate::serve::acme
+//! [`serve::handoff`]: crate::serve::handoff
 //! [`tower`]: https://crates.io/crates/tower
 //! [`tower-http`]: https://crates.io/crates/tower-http
 //! [`tokio`]: http://crates.io/crates/tokio
@@ -452,6 +465,8 @@ pub use self::extension::Extension;
 #[doc(inline)]
 #[cfg(feature = "json")]
 pub use self::json::Json;
+#[cfg(feature = "simd-json")]
+pub use self::json::SimdJson;
 #[doc(inline)]
 pub use self::routing::Router;
 
@@ -463,11 +478,23 @@ pub use self::form::Form;
 pub use axum_core::{BoxError, Error, RequestExt, RequestPartsExt};
 
 #[cfg(feature = "macros")]
-pub use axum_macros::{debug_handler, debug_middleware};
+pub use axum_macros::{collect_routes, debug_handler, debug_middleware, route};
 
 #[cfg(all(feature = "tokio", any(feature = "http1", feature = "http2")))]
 #[doc(inline)]
 pub use self::serve::serve;
+#[cfg(all(feature = "h3", any(feature = "http1", feature = "http2")))]
+#[doc(inline)]
+pub use self::serve::serve_h3;
+#[cfg(all(feature = "tokio", any(feature = "http1", feature = "http2")))]
+#[doc(inline)]
+pub use self::serve::serve_multi;
+#[cfg(all(feature = "tls-rustls", any(feature = "http1", feature = "http2")))]
+#[doc(inline)]
+pub use self::serve::serve_tls;
+#[cfg(all(unix, feature = "tokio", any(feature = "http1", feature = "http2")))]
+#[doc(inline)]
+pub use self::serve::serve_uds;
 
 pub use self::service_ext::ServiceExt;
 
@@ -69,7 +69,7 @@ use tower_service::Service;
 /// # let _: Router = app;
 /// ```
 ///
-/// Note that to access state you must use either [`map_response_with_state`].
+/// Note that to access state you must use [`map_response_with_state`].
 ///
 /// # Returning any `impl IntoResponse`
 ///
@@ -138,6 +138,54 @@ pub fn map_response<F, T>(f: F) -> MapResponseLayer<F, (), T> {
 ///     .with_state(state);
 /// # let _: axum::Router = app;
 /// ```
+///
+/// # Using the request in the response
+///
+/// Extractors run before the handler, so they see the request as it was received, and their
+/// values are kept until the response is mapped. This can be used to add headers to the
+/// response that depend on both the request and the state, without passing values through
+/// request extensions:
+///
+/// ```rust
+/// use axum::{
+///     Router,
+///     routing::get,
+///     response::{IntoResponse, Response},
+///     middleware::map_response_with_state,
+///     extract::State,
+///     http::HeaderMap,
+/// };
+/// use std::collections::HashMap;
+/// use std::sync::Arc;
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     // the plan of each user, by API key
+///     plans: Arc<HashMap<String, String>>,
+/// }
+///
+/// async fn add_plan_header(
+///     State(state): State<AppState>,
+///     headers: HeaderMap,
+///     response: Response,
+/// ) -> impl IntoResponse {
+///     let plan = headers
+///         .get("x-api-key")
+///         .and_then(|key| key.to_str().ok())
+///         .and_then(|key| state.plans.get(key))
+///         .cloned()
+///         .unwrap_or_else(|| "anonymous".to_owned());
+///     ([("x-plan", plan)], response)
+/// }
+///
+/// let state = AppState { plans: Default::default() };
+///
+/// let app = Router::new()
+///     .route("/", get(|| async { /* ... */ }))
+///     .layer(map_response_with_state(state.clone(), add_plan_header))
+///     .with_state(state);
+/// # let _: axum::Router = app;
+/// ```
 pub fn map_response_with_state<F, S, T>(state: S, f: F) -> MapResponseLayer<F, S, T> {
     MapResponseLayer {
         f,
@@ -361,4 +409,27 @@ mod tests {
 
         assert_eq!(res.headers()["x-foo"], "foo");
     }
+
+    #[crate::test]
+    async fn extractors_and_state() {
+        use crate::{extract::State, http::HeaderMap, routing::get};
+
+        async fn add_header(
+            State(suffix): State<&'static str>,
+            headers: HeaderMap,
+            res: Response,
+        ) -> impl IntoResponse {
+            let user = headers["x-user"].to_str().unwrap().to_owned();
+            ([("x-greeting", format!("hello {user}{suffix}"))], res)
+        }
+
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .layer(map_response_with_state("!", add_header));
+        let client = TestClient::new(app);
+
+        let res = client.get("/").header("x-user", "alice").await;
+
+        assert_eq!(res.headers()["x-greeting"], "hello alice!");
+    }
 }
@@ -24,6 +24,9 @@ pub use axum_core::response::{
     AppendHeaders, ErrorResponse, IntoResponse, IntoResponseParts, Response, ResponseParts, Result,
 };
 
+#[cfg(feature = "macros")]
+pub use axum_macros::IntoResponse;
+
 #[doc(inline)]
 pub use self::redirect::Redirect;
 
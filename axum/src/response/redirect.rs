@@ -1,5 +1,5 @@
 use axum_core::response::{IntoResponse, Response};
-use http::{header::LOCATION, HeaderValue, StatusCode};
+use http::{header::LOCATION, HeaderValue, StatusCode, Uri};
 
 /// Response that redirects the request to another location.
 ///
@@ -67,6 +67,75 @@ impl Redirect {
         Self::with_status_code(StatusCode::PERMANENT_REDIRECT, uri)
     }
 
+    /// Create a new [`Redirect`] that uses a [`303 See Other`][mdn] status code, with `uri`
+    /// resolved relative to `base`.
+    ///
+    /// `uri` is resolved like a link on a page at `base`. For example `"edit"` relative to
+    /// `/users/1` is `/users/edit`, and `"../"` relative to `/users/1/edit` is `/users/`. Absolute
+    /// URIs are used as is.
+    ///
+    /// The resulting location is absolute if `base` is. Use [`OriginalUri`] as the base in nested
+    /// routers, since the request URI has the prefix of the nested router removed. Behind a reverse
+    /// proxy, `ExternalUrl` from [`axum-extra`] reconstructs the URL the client used.
+    ///
+    /// # Panics
+    ///
+    /// If the resolved URI isn't a valid [`HeaderValue`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{
+    ///     extract::OriginalUri,
+    ///     response::Redirect,
+    ///     routing::post,
+    ///     Router,
+    /// };
+    ///
+    /// // redirects `/users/1/delete` to `/users/`
+    /// async fn delete_user(OriginalUri(uri): OriginalUri) -> Redirect {
+    ///     Redirect::to_relative(&uri, "../")
+    /// }
+    ///
+    /// let app = Router::new().route("/users/:id/delete", post(delete_user));
+    /// # let _: Router = app;
+    /// ```
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/303
+    /// [`OriginalUri`]: crate::extract::OriginalUri
+    /// [`axum-extra`]: https://docs.rs/axum-extra
+    pub fn to_relative(base: &Uri, uri: &str) -> Self {
+        Self::with_status_code(StatusCode::SEE_OTHER, &resolve(base, uri))
+    }
+
+    /// Create a new [`Redirect`] that uses a [`307 Temporary Redirect`][mdn] status code, with
+    /// `uri` resolved relative to `base`.
+    ///
+    /// See [`Redirect::to_relative`] for how `uri` is resolved.
+    ///
+    /// # Panics
+    ///
+    /// If the resolved URI isn't a valid [`HeaderValue`].
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/307
+    pub fn temporary_relative(base: &Uri, uri: &str) -> Self {
+        Self::with_status_code(StatusCode::TEMPORARY_REDIRECT, &resolve(base, uri))
+    }
+
+    /// Create a new [`Redirect`] that uses a [`308 Permanent Redirect`][mdn] status code, with
+    /// `uri` resolved relative to `base`.
+    ///
+    /// See [`Redirect::to_relative`] for how `uri` is resolved.
+    ///
+    /// # Panics
+    ///
+    /// If the resolved URI isn't a valid [`HeaderValue`].
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/308
+    pub fn permanent_relative(base: &Uri, uri: &str) -> Self {
+        Self::with_status_code(StatusCode::PERMANENT_REDIRECT, &resolve(base, uri))
+    }
+
     // This is intentionally not public since other kinds of redirects might not
     // use the `Location` header, namely `304 Not Modified`.
     //
@@ -89,3 +158,130 @@ impl IntoResponse for Redirect {
         (self.status_code, [(LOCATION, self.location)]).into_response()
     }
 }
+
+/// Resolve `reference` relative to `base`, as described in [RFC 3986 section 5.2].
+///
+/// [RFC 3986 section 5.2]: https://www.rfc-editor.org/rfc/rfc3986#section-5.2
+fn resolve(base: &Uri, reference: &str) -> String {
+    if has_scheme(reference) {
+        return reference.to_owned();
+    }
+
+    if reference.starts_with("//") {
+        return match base.scheme_str() {
+            Some(scheme) => format!("{scheme}:{reference}"),
+            None => reference.to_owned(),
+        };
+    }
+
+    let origin = match (base.scheme_str(), base.authority()) {
+        (Some(scheme), Some(authority)) => format!("{scheme}://{authority}"),
+        _ => String::new(),
+    };
+    let base_path = match base.path() {
+        "" => "/",
+        path => path,
+    };
+
+    let split = reference.find(['?', '#']).unwrap_or(reference.len());
+    let (path, suffix) = reference.split_at(split);
+
+    if path.is_empty() {
+        // only a query or fragment, which replace those of the base
+        let query = match base.query() {
+            Some(query) if suffix.starts_with('#') => format!("?{query}"),
+            _ => String::new(),
+        };
+        return format!("{origin}{base_path}{query}{suffix}");
+    }
+
+    let path = if path.starts_with('/') {
+        remove_dot_segments(path)
+    } else {
+        let directory = &base_path[..base_path.rfind('/').map_or(0, |idx| idx + 1)];
+        remove_dot_segments(&format!("{directory}{path}"))
+    };
+
+    format!("{origin}{path}{suffix}")
+}
+
+fn has_scheme(reference: &str) -> bool {
+    let Some(end) = reference.find([':', '/', '?', '#']) else {
+        return false;
+    };
+    let scheme = &reference[..end];
+    reference[end..].starts_with(':')
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let mut segments = Vec::new();
+    let mut trailing_slash = false;
+    for segment in path.strip_prefix('/').unwrap_or(path).split('/') {
+        match segment {
+            "." => trailing_slash = true,
+            ".." => {
+                segments.pop();
+                trailing_slash = true;
+            }
+            segment => {
+                segments.push(segment);
+                trailing_slash = false;
+            }
+        }
+    }
+
+    let mut path = format!("/{}", segments.join("/"));
+    if trailing_slash && !segments.is_empty() {
+        path.push('/');
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolving() {
+        let base = Uri::from_static("/users/1/edit?tab=profile");
+        assert_eq!(resolve(&base, "delete"), "/users/1/delete");
+        assert_eq!(resolve(&base, "../"), "/users/");
+        assert_eq!(resolve(&base, "../.."), "/");
+        assert_eq!(resolve(&base, "../../../.."), "/");
+        assert_eq!(resolve(&base, "./?saved=1"), "/users/1/?saved=1");
+        assert_eq!(resolve(&base, "/login?next=/a"), "/login?next=/a");
+        assert_eq!(
+            resolve(&base, "?tab=settings"),
+            "/users/1/edit?tab=settings"
+        );
+        assert_eq!(resolve(&base, "#top"), "/users/1/edit?tab=profile#top");
+        assert_eq!(
+            resolve(&base, "https://example.com/a"),
+            "https://example.com/a"
+        );
+        assert_eq!(resolve(&base, "//example.com/a"), "//example.com/a");
+
+        let base = Uri::from_static("https://example.com/app/users");
+        assert_eq!(resolve(&base, "1"), "https://example.com/app/1");
+        assert_eq!(resolve(&base, "/a/./b/../c"), "https://example.com/a/c");
+        assert_eq!(
+            resolve(&base, "//cdn.example.com/a"),
+            "https://cdn.example.com/a"
+        );
+        assert_eq!(resolve(&base, "a:b"), "a:b");
+
+        let base = Uri::from_static("https://example.com");
+        assert_eq!(resolve(&base, "users"), "https://example.com/users");
+    }
+
+    #[test]
+    fn relative_redirect() {
+        let res = Redirect::permanent_relative(&Uri::from_static("/a/b"), "c").into_response();
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers()[LOCATION], "/a/c");
+    }
+}
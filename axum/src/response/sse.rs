@@ -41,9 +41,13 @@ use futures_util::{
 use http_body::Frame;
 use pin_project_lite::pin_project;
 use std::{
+    cell::RefCell,
+    collections::hash_map::RandomState,
     fmt,
     future::Future,
+    hash::{BuildHasher, Hasher},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -56,6 +60,7 @@ use tokio::time::Sleep;
 pub struct Sse<S> {
     stream: S,
     keep_alive: Option<KeepAlive>,
+    on_disconnect: Option<Arc<dyn Fn() + Send + Sync>>,
 }
 
 impl<S> Sse<S> {
@@ -71,6 +76,7 @@ impl<S> Sse<S> {
         Sse {
             stream,
             keep_alive: None,
+            on_disconnect: None,
         }
     }
 
@@ -81,6 +87,40 @@ impl<S> Sse<S> {
         self.keep_alive = Some(keep_alive);
         self
     }
+
+    /// Call `f` when the client disconnects.
+    ///
+    /// `f` is called when the response body is dropped before the stream of events has ended,
+    /// which happens when the connection to the client is closed. This allows releasing resources
+    /// held for the client without waiting for the stream to produce its next event.
+    ///
+    /// Note that a closed connection is usually only noticed when writing to it, so this should
+    /// be combined with [`Sse::keep_alive`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::response::sse::{Event, KeepAlive, Sse};
+    /// use futures_util::stream::{self, Stream};
+    /// use std::convert::Infallible;
+    ///
+    /// async fn sse_handler() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    ///     let stream = stream::pending();
+    ///
+    ///     Sse::new(stream)
+    ///         .keep_alive(KeepAlive::default())
+    ///         .on_disconnect(|| {
+    ///             // remove the subscriber...
+    ///         })
+    /// }
+    /// ```
+    pub fn on_disconnect<F>(mut self, f: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.on_disconnect = Some(Arc::new(f));
+        self
+    }
 }
 
 impl<S> fmt::Debug for Sse<S> {
@@ -88,6 +128,7 @@ impl<S> fmt::Debug for Sse<S> {
         f.debug_struct("Sse")
             .field("stream", &format_args!("{}", std::any::type_name::<S>()))
             .field("keep_alive", &self.keep_alive)
+            .field("on_disconnect", &self.on_disconnect.is_some())
             .finish()
     }
 }
@@ -106,6 +147,7 @@ where
             Body::new(SseBody {
                 event_stream: SyncWrapper::new(self.stream),
                 keep_alive: self.keep_alive.map(KeepAliveStream::new),
+                on_disconnect: OnDisconnect(self.on_disconnect),
             }),
         )
             .into_response()
@@ -118,6 +160,18 @@ pin_project! {
         event_stream: SyncWrapper<S>,
         #[pin]
         keep_alive: Option<KeepAliveStream>,
+        on_disconnect: OnDisconnect,
+    }
+}
+
+/// Calls the callback when dropped, unless the stream has ended.
+struct OnDisconnect(Option<Arc<dyn Fn() + Send + Sync>>);
+
+impl Drop for OnDisconnect {
+    fn drop(&mut self) {
+        if let Some(f) = self.0.take() {
+            f();
+        }
     }
 }
 
@@ -148,12 +202,54 @@ where
                 }
                 Poll::Ready(Some(Ok(Frame::data(event.finalize()))))
             }
-            Poll::Ready(Some(Err(error))) => Poll::Ready(Some(Err(error))),
-            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Ready(Some(Err(error))) => {
+                this.on_disconnect.0 = None;
+                Poll::Ready(Some(Err(error)))
+            }
+            Poll::Ready(None) => {
+                this.on_disconnect.0 = None;
+                Poll::Ready(None)
+            }
         }
     }
 }
 
+/// Event buffers are split off chunks of this size, so building small events doesn't allocate
+/// for every event.
+const FRAME_BUFFER_CHUNK_SIZE: usize = 4 * 1024;
+
+/// Events that need more room than this when they're created get a buffer of their own, so they
+/// don't use up the shared chunk.
+const MAX_SHARED_EVENT_SIZE: usize = 1024;
+
+/// The least room an event is created with. Most events are smaller than this, so they never
+/// have to grow.
+const MIN_EVENT_SIZE: usize = 128;
+
+thread_local! {
+    static FRAME_BUFFER: RefCell<BytesMut> = RefCell::new(BytesMut::new());
+}
+
+/// Split a buffer with room for `capacity` bytes off the shared frame buffer of this thread.
+///
+/// The space of a chunk is reused once all events that were split off it have been sent and
+/// dropped. Until then the whole chunk stays allocated, so a single event that is kept around,
+/// such as a cloned [`KeepAlive`] event, retains up to [`FRAME_BUFFER_CHUNK_SIZE`] bytes. That's
+/// the price for not allocating every small event on its own.
+fn shared_event_buffer(capacity: usize) -> Option<BytesMut> {
+    FRAME_BUFFER
+        .try_with(|frame| {
+            let mut frame = frame.borrow_mut();
+            if frame.capacity() < capacity {
+                frame.reserve(FRAME_BUFFER_CHUNK_SIZE);
+            }
+            // the frame is always empty, so this hands out spare capacity without writing to it
+            let rest = frame.split_off(capacity);
+            std::mem::replace(&mut *frame, rest)
+        })
+        .ok()
+}
+
 /// Server-sent event
 #[derive(Debug, Default, Clone)]
 #[must_use]
@@ -185,7 +281,11 @@ impl Event {
             panic!("Called `EventBuilder::data` multiple times");
         }
 
-        for line in memchr_split(b'\n', data.as_ref().as_bytes()) {
+        let data = data.as_ref().as_bytes();
+        let lines = memchr::memchr_iter(b'\n', data).count() + 1;
+        self.reserve(data.len() + lines * "data: \n".len());
+
+        for line in memchr_split(b'\n', data) {
             self.field("data", line);
         }
 
@@ -212,6 +312,7 @@ impl Event {
             panic!("Called `EventBuilder::json_data` multiple times");
         }
 
+        self.reserve(MIN_EVENT_SIZE);
         self.buffer.extend_from_slice(b"data: ");
         serde_json::to_writer((&mut self.buffer).writer(), &data).map_err(axum_core::Error::new)?;
         self.buffer.put_u8(b'\n');
@@ -221,6 +322,43 @@ impl Event {
         Ok(self)
     }
 
+    /// Create an event from a typed payload, serialized as unformatted JSON.
+    ///
+    /// The event's name field is set to [`TypedEvent::EVENT`] and the data field to `data`
+    /// serialized as JSON. Newlines in strings are escaped by the JSON serializer so the data
+    /// always fits in a single `data: ` field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::response::sse::{Event, TypedEvent};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct UserJoined {
+    ///     name: String,
+    /// }
+    ///
+    /// impl TypedEvent for UserJoined {
+    ///     const EVENT: &'static str = "user-joined";
+    /// }
+    ///
+    /// let event = Event::typed(&UserJoined { name: "alice".to_owned() }).unwrap();
+    /// ```
+    ///
+    /// Browsers can then listen for it with `.addEventListener("user-joined", ...)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`TypedEvent::EVENT`] contains any newlines or carriage returns.
+    #[cfg(feature = "json")]
+    pub fn typed<T>(data: &T) -> Result<Event, axum_core::Error>
+    where
+        T: TypedEvent,
+    {
+        Event::default().event(T::EVENT).json_data(data)
+    }
+
     /// Set the event's comment field (`:<comment-text>`).
     ///
     /// This field will be ignored by most SSE clients.
@@ -282,6 +420,8 @@ impl Event {
         }
         self.flags.insert(EventFlags::HAS_RETRY);
 
+        // `retry:` and the milliseconds of a `u64` of seconds
+        self.reserve(32);
         self.buffer.extend_from_slice(b"retry:");
 
         let secs = duration.as_secs();
@@ -348,6 +488,7 @@ impl Event {
             None,
             "SSE field value cannot contain newlines or carriage returns",
         );
+        self.reserve(name.len() + value.len() + ": \n".len());
         self.buffer.extend_from_slice(name.as_bytes());
         self.buffer.put_u8(b':');
         self.buffer.put_u8(b' ');
@@ -355,12 +496,46 @@ impl Event {
         self.buffer.put_u8(b'\n');
     }
 
+    /// Reserve room for at least `additional` more bytes.
+    ///
+    /// The first time an event reserves room, it's split off the shared frame buffer if it's
+    /// small enough.
+    fn reserve(&mut self, additional: usize) {
+        // room for the blank line that ends the event
+        let additional = additional + 1;
+        if self.buffer.capacity() - self.buffer.len() >= additional {
+            return;
+        }
+
+        if self.buffer.is_empty() {
+            let capacity = additional.max(MIN_EVENT_SIZE);
+            if capacity <= MAX_SHARED_EVENT_SIZE {
+                if let Some(buffer) = shared_event_buffer(capacity) {
+                    self.buffer = buffer;
+                    return;
+                }
+            }
+        }
+
+        self.buffer.reserve(additional);
+    }
+
     fn finalize(mut self) -> Bytes {
         self.buffer.put_u8(b'\n');
         self.buffer.freeze()
     }
 }
 
+/// A payload that can be sent as a server-sent event with [`Event::typed`].
+#[cfg(feature = "json")]
+pub trait TypedEvent: serde::Serialize {
+    /// The name of the event (`event:<event-name>`).
+    ///
+    /// This corresponds to the `type` parameter given when calling `addEventListener` on an
+    /// `EventSource`.
+    const EVENT: &'static str;
+}
+
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 struct EventFlags(u8);
 
@@ -394,6 +569,7 @@ impl EventFlags {
 pub struct KeepAlive {
     event: Bytes,
     max_interval: Duration,
+    jitter: Duration,
 }
 
 impl KeepAlive {
@@ -402,6 +578,7 @@ impl KeepAlive {
         Self {
             event: Bytes::from_static(b":\n\n"),
             max_interval: Duration::from_secs(15),
+            jitter: Duration::ZERO,
         }
     }
 
@@ -413,6 +590,20 @@ impl KeepAlive {
         self
     }
 
+    /// Shorten each interval between keep-alive messages by a random duration of up to `jitter`.
+    ///
+    /// This spreads out the keep-alive messages of connections that were opened at the same
+    /// time, for example when many clients reconnect after a restart.
+    ///
+    /// The jitter is capped at half the [interval](Self::interval), so keep-alive messages are
+    /// never sent more than twice as often as configured.
+    ///
+    /// Default is no jitter.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
     /// Customize the text of the keep-alive message.
     ///
     /// Default is an empty comment.
@@ -440,6 +631,18 @@ impl KeepAlive {
         self.event = event.finalize();
         self
     }
+
+    fn next_interval(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.max_interval;
+        }
+
+        // `RandomState` is seeded differently every time, which is random enough for jitter
+        let random = RandomState::new().build_hasher().finish();
+        let fraction = (random >> 11) as f64 / (1_u64 << 53) as f64;
+        let jitter = self.jitter.min(self.max_interval / 2);
+        self.max_interval - jitter.mul_f64(fraction)
+    }
 }
 
 impl Default for KeepAlive {
@@ -460,7 +663,7 @@ pin_project! {
 impl KeepAliveStream {
     fn new(keep_alive: KeepAlive) -> Self {
         Self {
-            alive_timer: tokio::time::sleep(keep_alive.max_interval),
+            alive_timer: tokio::time::sleep(keep_alive.next_interval()),
             keep_alive,
         }
     }
@@ -468,7 +671,7 @@ impl KeepAliveStream {
     fn reset(self: Pin<&mut Self>) {
         let this = self.project();
         this.alive_timer
-            .reset(tokio::time::Instant::now() + this.keep_alive.max_interval);
+            .reset(tokio::time::Instant::now() + this.keep_alive.next_interval());
     }
 
     fn poll_event(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Bytes> {
@@ -515,7 +718,12 @@ mod tests {
     use super::*;
     use crate::{routing::get, test_helpers::*, Router};
     use futures_util::stream;
-    use std::{collections::HashMap, convert::Infallible};
+    use http_body_util::BodyExt;
+    use std::{
+        collections::HashMap,
+        convert::Infallible,
+        sync::atomic::{AtomicBool, Ordering},
+    };
     use tokio_stream::StreamExt as _;
 
     #[test]
@@ -570,6 +778,55 @@ mod tests {
         assert!(stream.chunk_text().await.is_none());
     }
 
+    #[test]
+    fn typed_event() {
+        #[derive(serde::Serialize)]
+        struct Message {
+            text: &'static str,
+        }
+
+        impl TypedEvent for Message {
+            const EVENT: &'static str = "message";
+        }
+
+        let event = Event::typed(&Message {
+            text: "multi\nline",
+        })
+        .unwrap();
+        assert_eq!(
+            &*event.finalize(),
+            b"event: message\ndata: {\"text\":\"multi\\nline\"}\n\n"
+        );
+    }
+
+    #[test]
+    fn events_share_frame_buffer() {
+        let events = (0..100)
+            .map(|i| {
+                let data = "x".repeat(i * 20);
+                Event::default()
+                    .id(i.to_string())
+                    .data(format!("{data}\n{i}"))
+            })
+            .collect::<Vec<_>>();
+
+        let frames = events.into_iter().map(Event::finalize).collect::<Vec<_>>();
+        for (i, frame) in frames.iter().enumerate() {
+            let expected = format!("id: {i}\ndata: {}\ndata: {i}\n\n", "x".repeat(i * 20));
+            assert_eq!(frame, expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn shared_event_buffers_are_adjacent() {
+        let first = shared_event_buffer(MIN_EVENT_SIZE).unwrap();
+        let second = shared_event_buffer(MIN_EVENT_SIZE).unwrap();
+        assert!(first.is_empty());
+        assert!(second.is_empty());
+        assert_eq!(first.capacity(), MIN_EVENT_SIZE);
+        assert_eq!(first.as_ptr().wrapping_add(MIN_EVENT_SIZE), second.as_ptr());
+    }
+
     #[tokio::test(start_paused = true)]
     async fn keep_alive() {
         const DELAY: Duration = Duration::from_secs(5);
@@ -648,6 +905,57 @@ mod tests {
         assert!(stream.chunk_text().await.is_none());
     }
 
+    #[test]
+    fn keep_alive_jitter() {
+        assert_eq!(KeepAlive::new().next_interval(), Duration::from_secs(15));
+
+        let keep_alive = KeepAlive::new()
+            .interval(Duration::from_secs(10))
+            .jitter(Duration::from_secs(5));
+        for _ in 0..100 {
+            let interval = keep_alive.next_interval();
+            assert!(interval > Duration::from_secs(5));
+            assert!(interval <= Duration::from_secs(10));
+        }
+    }
+
+    #[test]
+    fn keep_alive_jitter_is_capped() {
+        let keep_alive = KeepAlive::new()
+            .jitter(Duration::from_secs(60))
+            .interval(Duration::from_secs(10));
+        for _ in 0..100 {
+            let interval = keep_alive.next_interval();
+            assert!(interval >= Duration::from_secs(5));
+            assert!(interval <= Duration::from_secs(10));
+        }
+    }
+
+    #[crate::test]
+    async fn on_disconnect() {
+        fn response<S>(stream: S, disconnected: &Arc<AtomicBool>) -> Response
+        where
+            S: Stream<Item = Result<Event, Infallible>> + Send + 'static,
+        {
+            let disconnected = Arc::clone(disconnected);
+            Sse::new(stream)
+                .on_disconnect(move || disconnected.store(true, Ordering::SeqCst))
+                .into_response()
+        }
+
+        let disconnected = Arc::new(AtomicBool::new(false));
+
+        // the stream ends before the body is dropped
+        let res = response(stream::iter(vec![Ok(Event::default())]), &disconnected);
+        res.into_body().collect().await.unwrap();
+        assert!(!disconnected.load(Ordering::SeqCst));
+
+        // the body is dropped while the stream is still running
+        let res = response(stream::pending(), &disconnected);
+        drop(res);
+        assert!(disconnected.load(Ordering::SeqCst));
+    }
+
     fn parse_event(payload: &str) -> HashMap<String, String> {
         let mut fields = HashMap::new();
 
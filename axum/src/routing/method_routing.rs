@@ -7,16 +7,18 @@ use crate::{
     body::{Body, Bytes, HttpBody},
     boxed::BoxedIntoRoute,
     error_handling::{HandleError, HandleErrorLayer},
+    extract::DefaultBodyLimit,
     handler::Handler,
     http::{Method, StatusCode},
     response::Response,
-    routing::{future::RouteFuture, Fallback, MethodFilter, Route},
+    routing::{future::RouteFuture, Fallback, MethodFilter, OperationDocs, Route},
 };
 use axum_core::{extract::Request, response::IntoResponse, BoxError};
 use bytes::BytesMut;
 use std::{
     convert::Infallible,
     fmt,
+    sync::Arc,
     task::{Context, Poll},
 };
 use tower::{service_fn, util::MapResponseLayer};
@@ -278,6 +280,7 @@ macro_rules! chained_handler_fn {
     };
 }
 
+top_level_service_fn!(connect_service, CONNECT);
 top_level_service_fn!(delete_service, DELETE);
 top_level_service_fn!(get_service, GET);
 top_level_service_fn!(head_service, HEAD);
@@ -381,6 +384,7 @@ where
         .skip_allow_header()
 }
 
+top_level_handler_fn!(connect, CONNECT);
 top_level_handler_fn!(delete, DELETE);
 top_level_handler_fn!(get, GET);
 top_level_handler_fn!(head, HEAD);
@@ -497,8 +501,10 @@ pub struct MethodRouter<S = (), E = Infallible> {
     post: MethodEndpoint<S, E>,
     put: MethodEndpoint<S, E>,
     trace: MethodEndpoint<S, E>,
+    connect: MethodEndpoint<S, E>,
     fallback: Fallback<S, E>,
     allow_header: AllowHeader,
+    docs: Vec<(MethodFilter, Arc<OperationDocs>)>,
 }
 
 #[derive(Clone, Debug)]
@@ -538,8 +544,10 @@ impl<S, E> fmt::Debug for MethodRouter<S, E> {
             .field("post", &self.post)
             .field("put", &self.put)
             .field("trace", &self.trace)
+            .field("connect", &self.connect)
             .field("fallback", &self.fallback)
             .field("allow_header", &self.allow_header)
+            .field("docs", &self.docs)
             .finish()
     }
 }
@@ -582,6 +590,7 @@ where
         )
     }
 
+    chained_handler_fn!(connect, CONNECT);
     chained_handler_fn!(delete, DELETE);
     chained_handler_fn!(get, GET);
     chained_handler_fn!(head, HEAD);
@@ -689,8 +698,10 @@ where
             post: MethodEndpoint::None,
             put: MethodEndpoint::None,
             trace: MethodEndpoint::None,
+            connect: MethodEndpoint::None,
             allow_header: AllowHeader::None,
             fallback: Fallback::Default(fallback),
+            docs: Vec::new(),
         }
     }
 
@@ -705,8 +716,10 @@ where
             post: self.post.with_state(&state),
             put: self.put.with_state(&state),
             trace: self.trace.with_state(&state),
+            connect: self.connect.with_state(&state),
             allow_header: self.allow_header,
             fallback: self.fallback.with_state(state),
+            docs: self.docs,
         }
     }
 
@@ -803,6 +816,16 @@ where
             &["TRACE"],
         );
 
+        set_endpoint(
+            "CONNECT",
+            &mut self.connect,
+            &endpoint,
+            filter,
+            MethodFilter::CONNECT,
+            &mut self.allow_header,
+            &["CONNECT"],
+        );
+
         set_endpoint(
             "PUT",
             &mut self.put,
@@ -856,6 +879,7 @@ where
         self
     }
 
+    chained_service_fn!(connect_service, CONNECT);
     chained_service_fn!(delete_service, DELETE);
     chained_service_fn!(get_service, GET);
     chained_service_fn!(head_service, HEAD);
@@ -899,8 +923,10 @@ where
             post: self.post.map(layer_fn.clone()),
             put: self.put.map(layer_fn.clone()),
             trace: self.trace.map(layer_fn.clone()),
+            connect: self.connect.map(layer_fn.clone()),
             fallback: self.fallback.map(layer_fn),
             allow_header: self.allow_header,
+            docs: self.docs,
         }
     }
 
@@ -923,6 +949,7 @@ where
             && self.post.is_none()
             && self.put.is_none()
             && self.trace.is_none()
+            && self.connect.is_none()
         {
             panic!(
                 "Adding a route_layer before any routes is a no-op. \
@@ -943,11 +970,43 @@ where
         self.patch = self.patch.map(layer_fn.clone());
         self.post = self.post.map(layer_fn.clone());
         self.put = self.put.map(layer_fn.clone());
-        self.trace = self.trace.map(layer_fn);
+        self.trace = self.trace.map(layer_fn.clone());
+        self.connect = self.connect.map(layer_fn);
 
         self
     }
 
+    /// Set the [default request body limit](crate::extract::DefaultBodyLimit) for this route.
+    ///
+    /// This is a shorthand for `.layer(DefaultBodyLimit::max(limit))` and like
+    /// [`layer`](Self::layer) it only applies to methods added before calling `body_limit`. The
+    /// limit overrides any limit set by a [`DefaultBodyLimit`](crate::extract::DefaultBodyLimit)
+    /// added to the surrounding [`Router`](crate::Router).
+    ///
+    /// Extractors can read the limit in effect with
+    /// [`RequestExt::body_limit`](crate::RequestExt::body_limit).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::post, body::Bytes};
+    ///
+    /// const MB: usize = 1024 * 1024;
+    ///
+    /// let app = Router::new()
+    ///     .route("/upload", post(|body: Bytes| async {}).body_limit(50 * MB))
+    ///     // this route still has the default limit of 2MB
+    ///     .route("/comment", post(|body: Bytes| async {}));
+    /// # let _: Router = app;
+    /// ```
+    pub fn body_limit(self, limit: usize) -> Self
+    where
+        E: 'static,
+        S: 'static,
+    {
+        self.layer(DefaultBodyLimit::max(limit))
+    }
+
     #[track_caller]
     pub(crate) fn merge_for_path(mut self, path: Option<&str>, other: MethodRouter<S, E>) -> Self {
         // written using inner functions to generate less IR
@@ -984,6 +1043,7 @@ where
         self.post = merge_inner(path, "POST", self.post, other.post);
         self.put = merge_inner(path, "PUT", self.put, other.put);
         self.trace = merge_inner(path, "TRACE", self.trace, other.trace);
+        self.connect = merge_inner(path, "CONNECT", self.connect, other.connect);
 
         self.fallback = self
             .fallback
@@ -992,6 +1052,8 @@ where
 
         self.allow_header = self.allow_header.merge(other.allow_header);
 
+        self.docs.extend(other.docs);
+
         self
     }
 
@@ -1017,11 +1079,69 @@ where
         self.layer(HandleErrorLayer::new(f))
     }
 
+    /// Attach documentation to the endpoints for the methods in `filter`.
+    ///
+    /// The documentation is included in the [`RouteTable`] returned by
+    /// [`Router::export_routes`](crate::Router::export_routes). If several calls cover the same
+    /// method the first one wins.
+    ///
+    /// See [`OperationDocs`] for an example.
+    ///
+    /// [`RouteTable`]: crate::routing::RouteTable
+    pub fn docs(mut self, filter: MethodFilter, docs: OperationDocs) -> Self {
+        self.docs.push((filter, Arc::new(docs)));
+        self
+    }
+
+    /// The documentation attached to the endpoints for `methods`.
+    pub(crate) fn operations(&self, methods: &[Method]) -> Vec<(Method, OperationDocs)> {
+        methods
+            .iter()
+            .filter_map(|method| {
+                let filter = MethodFilter::try_from(method.clone()).ok()?;
+                let (_, docs) = self
+                    .docs
+                    .iter()
+                    .find(|(docs_filter, _)| docs_filter.contains(filter))?;
+                Some((method.clone(), OperationDocs::clone(docs)))
+            })
+            .collect()
+    }
+
     fn skip_allow_header(mut self) -> Self {
         self.allow_header = AllowHeader::Skip;
         self
     }
 
+    /// The methods this router has endpoints for, and whether it accepts any method through a
+    /// custom fallback.
+    pub(crate) fn methods(&self) -> (Vec<Method>, bool) {
+        let mut methods = Vec::new();
+        let endpoints = [
+            (&self.get, Method::GET),
+            (&self.head, Method::HEAD),
+            (&self.delete, Method::DELETE),
+            (&self.options, Method::OPTIONS),
+            (&self.patch, Method::PATCH),
+            (&self.post, Method::POST),
+            (&self.put, Method::PUT),
+            (&self.trace, Method::TRACE),
+            (&self.connect, Method::CONNECT),
+        ];
+        for (endpoint, method) in endpoints {
+            if endpoint.is_some() {
+                methods.push(method);
+            }
+        }
+        // `GET` endpoints also handle `HEAD` requests
+        if self.get.is_some() && self.head.is_none() {
+            methods.insert(1, Method::HEAD);
+        }
+
+        let any = !matches!(self.fallback, Fallback::Default(_));
+        (methods, any)
+    }
+
     pub(crate) fn call_with_state(&self, req: Request, state: S) -> RouteFuture<E> {
         macro_rules! call {
             (
@@ -1034,12 +1154,14 @@ where
                     match $svc {
                         MethodEndpoint::None => {}
                         MethodEndpoint::Route(route) => {
-                            return RouteFuture::from_future(route.clone().oneshot_inner($req))
+                            return route
+                                .oneshot_shared($req)
                                 .strip_body($method == Method::HEAD);
                         }
                         MethodEndpoint::BoxedHandler(handler) => {
-                            let route = handler.clone().into_route(state);
-                            return RouteFuture::from_future(route.clone().oneshot_inner($req))
+                            let mut route = handler.clone().into_route(state);
+                            return route
+                                .oneshot_inner($req)
                                 .strip_body($method == Method::HEAD);
                         }
                     }
@@ -1059,8 +1181,10 @@ where
             post,
             put,
             trace,
+            connect,
             fallback,
             allow_header,
+            docs: _,
         } = self;
 
         call!(req, method, HEAD, head);
@@ -1072,6 +1196,7 @@ where
         call!(req, method, PUT, put);
         call!(req, method, DELETE, delete);
         call!(req, method, TRACE, trace);
+        call!(req, method, CONNECT, connect);
 
         let future = fallback.clone().call_with_state(req, state);
 
@@ -1114,8 +1239,10 @@ impl<S, E> Clone for MethodRouter<S, E> {
             post: self.post.clone(),
             put: self.put.clone(),
             trace: self.trace.clone(),
+            connect: self.connect.clone(),
             fallback: self.fallback.clone(),
             allow_header: self.allow_header.clone(),
+            docs: self.docs.clone(),
         }
     }
 }
@@ -1242,6 +1369,21 @@ const _: () = {
             std::future::ready(Ok(self.clone().with_state(())))
         }
     }
+
+    #[cfg(unix)]
+    impl Service<crate::serve::UdsIncomingStream<'_>> for MethodRouter<()> {
+        type Response = Self;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: crate::serve::UdsIncomingStream<'_>) -> Self::Future {
+            std::future::ready(Ok(self.clone().with_state(())))
+        }
+    }
 };
 
 #[cfg(test)]
@@ -1293,6 +1435,18 @@ mod tests {
         assert!(body.is_empty());
     }
 
+    #[crate::test]
+    async fn connect_handler() {
+        let mut svc = get(ok).connect(created);
+
+        let (status, _, _) = call(Method::CONNECT, &mut svc).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, headers, _) = call(Method::POST, &mut svc).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(headers[ALLOW], "GET,HEAD,CONNECT");
+    }
+
     #[crate::test]
     async fn head_takes_precedence_over_get() {
         let mut svc = MethodRouter::new().head(created).get(ok);
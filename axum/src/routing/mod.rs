@@ -6,13 +6,15 @@ use crate::extract::connect_info::IntoMakeServiceWithConnectInfo;
 use crate::{
     body::{Body, HttpBody},
     boxed::BoxedIntoRoute,
+    extract::Forward,
     handler::Handler,
     util::try_downcast,
 };
 use axum_core::{
-    extract::Request,
+    extract::{rejection::RejectionInfo, Request},
     response::{IntoResponse, Response},
 };
+use http::StatusCode;
 use std::{
     convert::Infallible,
     fmt,
@@ -20,6 +22,7 @@ use std::{
     sync::Arc,
     task::{Context, Poll},
 };
+use tower::util::MapResponseLayer;
 use tower_layer::Layer;
 use tower_service::Service;
 
@@ -29,20 +32,40 @@ pub mod method_routing;
 mod into_make_service;
 mod method_filter;
 mod not_found;
+mod operation_docs;
+mod path_rewrite;
 pub(crate) mod path_router;
 mod route;
+#[cfg(feature = "matched-path")]
+mod route_metrics;
+mod route_table;
 mod strip_prefix;
 pub(crate) mod url_params;
 
 #[cfg(test)]
 mod tests;
 
-pub use self::{into_make_service::IntoMakeService, method_filter::MethodFilter, route::Route};
+pub use self::{
+    into_make_service::IntoMakeService,
+    method_filter::MethodFilter,
+    operation_docs::{
+        DescribeParameters, OperationDocs, ParameterDocs, ParameterLocation, ResponseDocs,
+    },
+    path_rewrite::PathRewrite,
+    route::Route,
+    route_table::{RouteEntry, RouteKind, RouteSnapshot, RouteTable},
+};
+
+#[cfg(feature = "matched-path")]
+pub use self::route_metrics::RouteMetrics;
+
+#[cfg(feature = "macros")]
+pub use axum_macros::DescribeParameters;
 
 pub use self::method_routing::{
-    any, any_service, delete, delete_service, get, get_service, head, head_service, on, on_service,
-    options, options_service, patch, patch_service, post, post_service, put, put_service, trace,
-    trace_service, MethodRouter,
+    any, any_service, connect, connect_service, delete, delete_service, get, get_service, head,
+    head_service, on, on_service, options, options_service, patch, patch_service, post,
+    post_service, put, put_service, trace, trace_service, MethodRouter,
 };
 
 macro_rules! panic_on_err {
@@ -76,6 +99,7 @@ struct RouterInner<S> {
     fallback_router: PathRouter<S, true>,
     default_fallback: bool,
     catch_all_fallback: Fallback<S>,
+    forward: bool,
 }
 
 impl<S> Default for Router<S>
@@ -94,6 +118,7 @@ impl<S> fmt::Debug for Router<S> {
             .field("fallback_router", &self.inner.fallback_router)
             .field("default_fallback", &self.inner.default_fallback)
             .field("catch_all_fallback", &self.inner.catch_all_fallback)
+            .field("forward", &self.inner.forward)
             .finish()
     }
 }
@@ -118,6 +143,7 @@ where
                 fallback_router: PathRouter::new_fallback(),
                 default_fallback: true,
                 catch_all_fallback: Fallback::Default(Route::new(NotFound)),
+                forward: false,
             }),
         }
     }
@@ -150,6 +176,7 @@ where
                 fallback_router: arc.fallback_router.clone(),
                 default_fallback: arc.default_fallback,
                 catch_all_fallback: arc.catch_all_fallback.clone(),
+                forward: arc.forward,
             },
         }
     }
@@ -162,6 +189,60 @@ where
         })
     }
 
+    /// Like [`route`](Self::route), but with an explicit priority used to resolve overlapping
+    /// routes.
+    ///
+    /// Routes added with [`route`](Self::route) must not overlap in ways the router considers
+    /// ambiguous, such as `/:id` and `/:name`, and adding such routes panics. Routes added with a
+    /// priority are allowed to overlap. When more than one of them matches a request the one with
+    /// the highest priority is called. Routes added without a priority have priority `0` and
+    /// overlapping routes with the same priority are tried in the order they were added.
+    ///
+    /// The priority is kept when the router is [merged](Self::merge) or [nested](Self::nest).
+    ///
+    /// Like with [`route`](Self::route), calling this again with the same path adds more methods
+    /// to the route, which requires using the same priority.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{Router, routing::get, extract::Path};
+    ///
+    /// async fn file(Path(name): Path<String>) {}
+    ///
+    /// async fn nested_file(Path(path): Path<String>) {}
+    ///
+    /// // `/files/foo` is handled by `file` and `/files/foo/bar` by `nested_file`
+    /// let app = Router::new()
+    ///     .route("/files/:name", get(file))
+    ///     .route_with_priority("/files/*path", get(nested_file), -1);
+    /// # let _: Router = app;
+    /// ```
+    ///
+    /// Note that the higher priority route is called as soon as its path matches, even if a
+    /// lower priority route would also have matched, regardless of how specific the paths are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path is invalid, for example if it doesn't start with `/`, or if a route
+    /// with the same path was already added with a different priority, or with a service rather
+    /// than a [`MethodRouter`].
+    #[track_caller]
+    pub fn route_with_priority(
+        self,
+        path: &str,
+        method_router: MethodRouter<S>,
+        priority: i32,
+    ) -> Self {
+        self.tap_inner_mut(|this| {
+            panic_on_err!(this.path_router.route_with_priority(
+                path,
+                method_router,
+                Some(priority)
+            ));
+        })
+    }
+
     #[doc = include_str!("../docs/routing/route_service.md")]
     pub fn route_service<T>(self, path: &str, service: T) -> Self
     where
@@ -195,10 +276,12 @@ where
             // requests with an empty path. If we were to inherit the catch-all fallback
             // it would end up matching `/{path}/*` which doesn't match empty paths.
             catch_all_fallback: _,
+            forward,
         } = router.into_inner();
 
         self.tap_inner_mut(|this| {
             panic_on_err!(this.path_router.nest(path, path_router));
+            this.forward |= forward;
 
             if !default_fallback {
                 panic_on_err!(this.fallback_router.nest(path, fallback_router));
@@ -207,15 +290,53 @@ where
     }
 
     /// Like [`nest`](Self::nest), but accepts an arbitrary `Service`.
+    ///
+    /// The nested service sees the request URI with the prefix stripped. Use
+    /// [`nest_service_with_rewrite`](Self::nest_service_with_rewrite) to change that.
     #[track_caller]
     pub fn nest_service<T>(self, path: &str, service: T) -> Self
+    where
+        T: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        T::Response: IntoResponse,
+        T::Future: Send + 'static,
+    {
+        self.nest_service_with_rewrite(path, service, PathRewrite::strip_prefix())
+    }
+
+    /// Like [`nest_service`](Self::nest_service), but with control over the request URI the
+    /// nested service sees.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     Router,
+    ///     routing::{get_service, PathRewrite},
+    ///     extract::Request,
+    /// };
+    ///
+    /// let service = get_service(tower::service_fn(|req: Request| async move {
+    ///     Ok::<_, std::convert::Infallible>(req.uri().to_string())
+    /// }));
+    ///
+    /// let app = Router::new()
+    ///     // `GET /api/users` responds with `/api/users`
+    ///     .nest_service_with_rewrite("/api", service.clone(), PathRewrite::preserve())
+    ///     // `GET /internal/users` responds with `/users`
+    ///     .nest_service_with_rewrite("/internal", service, PathRewrite::strip_prefix());
+    /// # let _: Router = app;
+    /// ```
+    ///
+    /// See [`PathRewrite`] for the available options.
+    #[track_caller]
+    pub fn nest_service_with_rewrite<T>(self, path: &str, service: T, rewrite: PathRewrite) -> Self
     where
         T: Service<Request, Error = Infallible> + Clone + Send + 'static,
         T::Response: IntoResponse,
         T::Future: Send + 'static,
     {
         self.tap_inner_mut(|this| {
-            panic_on_err!(this.path_router.nest_service(path, service));
+            panic_on_err!(this.path_router.nest_service(path, service, rewrite));
         })
     }
 
@@ -234,10 +355,12 @@ where
             fallback_router: mut other_fallback,
             default_fallback,
             catch_all_fallback,
+            forward,
         } = other.into_inner();
 
         self.map_inner(|mut this| {
             panic_on_err!(this.path_router.merge(path_router));
+            this.forward |= forward;
 
             match (this.default_fallback, default_fallback) {
                 // both have the default fallback
@@ -285,9 +408,112 @@ where
             fallback_router: this.fallback_router.layer(layer.clone()),
             default_fallback: this.default_fallback,
             catch_all_fallback: this.catch_all_fallback.map(|route| route.layer(layer)),
+            forward: this.forward,
         })
     }
 
+    /// Report [`RouteMetrics`] for every request handled by the router.
+    ///
+    /// `callback` is called once per request with the matched path pattern, the request method,
+    /// the response status, and the time it took to produce the response. This makes it easy to
+    /// record per-route histograms without writing a middleware that is aware of
+    /// [`MatchedPath`](crate::extract::MatchedPath).
+    ///
+    /// Like [`layer`](Self::layer) this only applies to routes added before calling
+    /// `route_metrics`, and to the fallback.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::get};
+    ///
+    /// let app = Router::new()
+    ///     .route("/users/:id", get(|| async {}))
+    ///     .route_metrics(|metrics| {
+    ///         println!(
+    ///             "{} {} {} {:?}",
+    ///             metrics.method(),
+    ///             metrics.matched_path().unwrap_or("<fallback>"),
+    ///             metrics.status(),
+    ///             metrics.latency(),
+    ///         );
+    ///     });
+    /// # let _: Router = app;
+    /// ```
+    #[cfg(feature = "matched-path")]
+    pub fn route_metrics<F>(self, callback: F) -> Self
+    where
+        F: Fn(&RouteMetrics) + Send + Sync + 'static,
+    {
+        self.layer(route_metrics::RouteMetricsLayer::new(callback))
+    }
+
+    /// Replace the responses of rejected built-in extractors with custom responses.
+    ///
+    /// `handler` is called with the [`RejectionInfo`] of every response produced by a rejection
+    /// of one of axum's built-in extractors, and its return value is sent instead. This makes it
+    /// possible to use a consistent error format, such as [RFC 9457] problem details, without
+    /// wrapping every extractor in a custom one.
+    ///
+    /// The response keeps the status of the rejection, such as `415 Unsupported Media Type`, if
+    /// `handler` returns a response with the default `200 OK` status. Return another status to
+    /// override it.
+    ///
+    /// Like [`layer`](Self::layer) this only applies to routes added before calling
+    /// `rejection_handler`, and to the fallback.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     Router,
+    ///     Json,
+    ///     routing::post,
+    ///     extract::rejection::RejectionInfo,
+    ///     http::header::CONTENT_TYPE,
+    ///     response::IntoResponse,
+    /// };
+    /// use serde_json::{json, Value};
+    ///
+    /// fn problem_details(rejection: RejectionInfo) -> impl IntoResponse {
+    ///     (
+    ///         rejection.status(),
+    ///         [(CONTENT_TYPE, "application/problem+json")],
+    ///         Json(json!({
+    ///             "title": rejection.status().canonical_reason(),
+    ///             "status": rejection.status().as_u16(),
+    ///             "detail": rejection.body_text(),
+    ///         })),
+    ///     )
+    /// }
+    ///
+    /// let app = Router::new()
+    ///     .route("/users", post(|Json(_): Json<Value>| async {}))
+    ///     .rejection_handler(problem_details);
+    /// # let _: Router = app;
+    /// ```
+    ///
+    /// [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457
+    pub fn rejection_handler<F, R>(self, handler: F) -> Self
+    where
+        F: Fn(RejectionInfo) -> R + Clone + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.layer(MapResponseLayer::new(move |mut res: Response| {
+            match res.extensions_mut().remove::<RejectionInfo>() {
+                Some(rejection) => {
+                    let status = rejection.status();
+                    let mut res = handler(rejection).into_response();
+                    if res.status() == StatusCode::OK {
+                        *res.status_mut() = status;
+                    }
+                    res
+                }
+                None => res,
+            }
+        }))
+    }
+
     #[doc = include_str!("../docs/routing/route_layer.md")]
     #[track_caller]
     pub fn route_layer<L>(self, layer: L) -> Self
@@ -303,6 +529,7 @@ where
             fallback_router: this.fallback_router,
             default_fallback: this.default_fallback,
             catch_all_fallback: this.catch_all_fallback,
+            forward: this.forward,
         })
     }
 
@@ -343,6 +570,15 @@ where
         })
     }
 
+    /// Make the [`Forward`] extractor available to handlers and middleware.
+    ///
+    /// This is opt-in since it adds a clone of the router to the extensions of every request.
+    /// Enabling it on a router that is [merged](Self::merge) or [nested](Self::nest) enables it
+    /// on the outer router, which is the one requests are forwarded to.
+    pub fn enable_forward(self) -> Self {
+        self.tap_inner_mut(|this| this.forward = true)
+    }
+
     #[doc = include_str!("../docs/routing/with_state.md")]
     pub fn with_state<S2>(self, state: S) -> Router<S2> {
         self.map_inner(|this| RouterInner {
@@ -350,9 +586,96 @@ where
             fallback_router: this.fallback_router.with_state(state.clone()),
             default_fallback: this.default_fallback,
             catch_all_fallback: this.catch_all_fallback.with_state(state),
+            forward: this.forward,
         })
     }
 
+    /// Export a description of the routes in the router.
+    ///
+    /// The returned [`RouteTable`] can be serialized with [serde] and consumed by tools that
+    /// generate documentation or API clients. Routes from nested routers are included with their
+    /// full paths. Fallbacks are not included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::get};
+    ///
+    /// let app = Router::<()>::new()
+    ///     .route("/users", get(|| async {}).post(|| async {}))
+    ///     .nest("/api", Router::new().route("/status", get(|| async {})));
+    ///
+    /// let routes = app.export_routes();
+    /// let paths = routes.routes().iter().map(|route| route.path()).collect::<Vec<_>>();
+    /// assert_eq!(paths, ["/api/status", "/users"]);
+    ///
+    /// let json = serde_json::to_string_pretty(&routes).unwrap();
+    /// ```
+    ///
+    /// [serde]: https://crates.io/crates/serde
+    pub fn export_routes(&self) -> RouteTable {
+        RouteTable {
+            routes: self.inner.path_router.export(),
+        }
+    }
+
+    /// Take a snapshot of the routes and fallbacks in the router.
+    ///
+    /// The returned [`RouteSnapshot`] only depends on which routes exist, so it is stable
+    /// between runs and well suited for snapshot tests that catch routes being removed or
+    /// changed by accident.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::get};
+    ///
+    /// let app = Router::<()>::new()
+    ///     .route("/users", get(|| async {}).post(|| async {}))
+    ///     .nest(
+    ///         "/api",
+    ///         Router::new()
+    ///             .route("/status", get(|| async {}))
+    ///             .fallback(|| async {}),
+    ///     );
+    ///
+    /// assert_eq!(
+    ///     app.snapshot().to_string(),
+    ///     "\
+    /// GET,HEAD /api/status
+    /// GET,HEAD,POST /users
+    /// FALLBACK / (default)
+    /// FALLBACK /api
+    /// ",
+    /// );
+    /// ```
+    pub fn snapshot(&self) -> RouteSnapshot {
+        let mut routes = self.inner.path_router.export();
+        for route in &mut routes {
+            route.operations.clear();
+        }
+
+        let mut fallbacks = self
+            .inner
+            .fallback_router
+            .export()
+            .into_iter()
+            .filter_map(|route| {
+                let prefix = route.path.strip_suffix(FALLBACK_PARAM_PATH)?;
+                Some(if prefix.is_empty() {
+                    ("/".to_owned(), !self.inner.default_fallback)
+                } else {
+                    // only custom fallbacks are nested
+                    (prefix.to_owned(), true)
+                })
+            })
+            .collect::<Vec<_>>();
+        fallbacks.sort();
+        fallbacks.dedup();
+
+        RouteSnapshot { routes, fallbacks }
+    }
+
     pub(crate) fn call_with_state(&self, req: Request, state: S) -> RouteFuture<Infallible> {
         let (req, state) = match self.inner.path_router.call_with_state(req, state) {
             Ok(future) => return future,
@@ -497,6 +820,21 @@ const _: () = {
             std::future::ready(Ok(self.clone().with_state(())))
         }
     }
+
+    #[cfg(unix)]
+    impl Service<crate::serve::UdsIncomingStream<'_>> for Router<()> {
+        type Response = Self;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: crate::serve::UdsIncomingStream<'_>) -> Self::Future {
+            std::future::ready(Ok(self.clone().with_state(())))
+        }
+    }
 };
 
 impl<B> Service<Request<B>> for Router<()>
@@ -515,7 +853,13 @@ where
 
     #[inline]
     fn call(&mut self, req: Request<B>) -> Self::Future {
-        let req = req.map(Body::new);
+        let mut req = req.map(Body::new);
+
+        // the outermost router is the one requests are forwarded to
+        if self.inner.forward && req.extensions().get::<Forward>().is_none() {
+            req.extensions_mut().insert(Forward::new(self.clone()));
+        }
+
         self.call_with_state(req, ())
     }
 }
@@ -652,12 +996,10 @@ where
 
     fn call_with_state(&mut self, req: Request, state: S) -> RouteFuture<E> {
         match self {
-            Fallback::Default(route) | Fallback::Service(route) => {
-                RouteFuture::from_future(route.oneshot_inner(req))
-            }
+            Fallback::Default(route) | Fallback::Service(route) => route.oneshot_inner(req),
             Fallback::BoxedHandler(handler) => {
                 let mut route = handler.clone().into_route(state);
-                RouteFuture::from_future(route.oneshot_inner(req))
+                route.oneshot_inner(req)
             }
         }
     }
@@ -0,0 +1,378 @@
+use http::StatusCode;
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+/// Documentation for a single operation, that is a method on a route.
+///
+/// Attach it to a [`MethodRouter`] with [`MethodRouter::docs`] and it will be included in
+/// the [`RouteTable`] returned by [`Router::export_routes`], where tools that generate OpenAPI
+/// documents or API clients can pick it up.
+///
+/// Usually this is generated by the `#[route]` attribute from `axum-macros` rather than built
+/// by hand.
+///
+/// # Example
+///
+/// ```
+/// use axum::{
+///     Router,
+///     http::StatusCode,
+///     routing::{get, MethodFilter, OperationDocs, ParameterDocs, ResponseDocs},
+/// };
+///
+/// async fn get_user() {}
+///
+/// let app = Router::<()>::new().route(
+///     "/users/:id",
+///     get(get_user).docs(
+///         MethodFilter::GET,
+///         OperationDocs::new()
+///             .summary("Get a user")
+///             .parameter(ParameterDocs::path("id").description("The id of the user"))
+///             .response(ResponseDocs::new(StatusCode::OK).body::<String>())
+///             .response(ResponseDocs::new(StatusCode::NOT_FOUND).description("No such user")),
+///     ),
+/// );
+///
+/// let routes = app.export_routes();
+/// let docs = routes.routes()[0].operation(&http::Method::GET).unwrap();
+/// assert_eq!(docs.get_summary(), Some("Get a user"));
+/// ```
+///
+/// [`MethodRouter`]: super::MethodRouter
+/// [`MethodRouter::docs`]: super::MethodRouter::docs
+/// [`RouteTable`]: super::RouteTable
+/// [`Router::export_routes`]: super::Router::export_routes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationDocs {
+    summary: Option<String>,
+    description: Option<String>,
+    parameters: Vec<ParameterDocs>,
+    responses: Vec<ResponseDocs>,
+}
+
+impl OperationDocs {
+    /// Create empty operation docs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a short summary of what the operation does.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Set a longer description of the operation.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a parameter.
+    ///
+    /// Adding a parameter with the same name and location as an existing one replaces it.
+    pub fn parameter(mut self, parameter: ParameterDocs) -> Self {
+        self.parameters
+            .retain(|p| p.name != parameter.name || p.location != parameter.location);
+        self.parameters.push(parameter);
+        self
+    }
+
+    /// Add all the parameters described by `T`.
+    pub fn parameters_from<T>(self) -> Self
+    where
+        T: DescribeParameters,
+    {
+        T::parameters().into_iter().fold(self, Self::parameter)
+    }
+
+    /// Add a possible response.
+    pub fn response(mut self, response: ResponseDocs) -> Self {
+        self.responses.retain(|r| r.status != response.status);
+        self.responses.push(response);
+        self
+    }
+
+    /// Get the summary, if any.
+    pub fn get_summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    /// Get the description, if any.
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Get the parameters.
+    pub fn get_parameters(&self) -> &[ParameterDocs] {
+        &self.parameters
+    }
+
+    /// Get the responses.
+    pub fn get_responses(&self) -> &[ResponseDocs] {
+        &self.responses
+    }
+}
+
+impl Serialize for OperationDocs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("OperationDocs", 4)?;
+        state.serialize_field("summary", &self.summary)?;
+        state.serialize_field("description", &self.description)?;
+        state.serialize_field("parameters", &self.parameters)?;
+        state.serialize_field("responses", &self.responses)?;
+        state.end()
+    }
+}
+
+/// Documentation for a parameter of an [`OperationDocs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterDocs {
+    name: String,
+    location: ParameterLocation,
+    description: Option<String>,
+    required: bool,
+    default: Option<String>,
+    minimum: Option<String>,
+    maximum: Option<String>,
+}
+
+impl ParameterDocs {
+    /// Create docs for a parameter.
+    ///
+    /// Path parameters are required, other parameters are optional by default.
+    pub fn new(name: impl Into<String>, location: ParameterLocation) -> Self {
+        Self {
+            name: name.into(),
+            location,
+            description: None,
+            required: location == ParameterLocation::Path,
+            default: None,
+            minimum: None,
+            maximum: None,
+        }
+    }
+
+    /// Create docs for a path parameter.
+    pub fn path(name: impl Into<String>) -> Self {
+        Self::new(name, ParameterLocation::Path)
+    }
+
+    /// Create docs for a query parameter.
+    pub fn query(name: impl Into<String>) -> Self {
+        Self::new(name, ParameterLocation::Query)
+    }
+
+    /// Create docs for a header.
+    pub fn header(name: impl Into<String>) -> Self {
+        Self::new(name, ParameterLocation::Header)
+    }
+
+    /// Set the description of the parameter.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set whether the parameter is required.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Set the value used when the parameter is missing.
+    pub fn default_value(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Set the smallest allowed value.
+    pub fn minimum(mut self, minimum: impl Into<String>) -> Self {
+        self.minimum = Some(minimum.into());
+        self
+    }
+
+    /// Set the largest allowed value.
+    pub fn maximum(mut self, maximum: impl Into<String>) -> Self {
+        self.maximum = Some(maximum.into());
+        self
+    }
+
+    /// Get the name of the parameter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get where the parameter is found.
+    pub fn location(&self) -> ParameterLocation {
+        self.location
+    }
+
+    /// Get the description, if any.
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Get whether the parameter is required.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Get the value used when the parameter is missing, if any.
+    pub fn get_default_value(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Get the smallest allowed value, if any.
+    pub fn get_minimum(&self) -> Option<&str> {
+        self.minimum.as_deref()
+    }
+
+    /// Get the largest allowed value, if any.
+    pub fn get_maximum(&self) -> Option<&str> {
+        self.maximum.as_deref()
+    }
+}
+
+impl Serialize for ParameterDocs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("ParameterDocs", 7)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("in", &self.location)?;
+        state.serialize_field("description", &self.description)?;
+        state.serialize_field("required", &self.required)?;
+        state.serialize_field("default", &self.default)?;
+        state.serialize_field("minimum", &self.minimum)?;
+        state.serialize_field("maximum", &self.maximum)?;
+        state.end()
+    }
+}
+
+/// Where a parameter is found in the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParameterLocation {
+    /// A capture in the path, such as `:id`.
+    Path,
+    /// A query string parameter.
+    Query,
+    /// A request header.
+    Header,
+}
+
+impl Serialize for ParameterLocation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (index, name) = match self {
+            Self::Path => (0, "path"),
+            Self::Query => (1, "query"),
+            Self::Header => (2, "header"),
+        };
+        serializer.serialize_unit_variant("ParameterLocation", index, name)
+    }
+}
+
+/// Documentation for a possible response of an [`OperationDocs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseDocs {
+    status: StatusCode,
+    description: Option<String>,
+    body: Option<String>,
+}
+
+impl ResponseDocs {
+    /// Create docs for a response with the given status.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            description: None,
+            body: None,
+        }
+    }
+
+    /// Set the description of the response.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the type of the response body.
+    ///
+    /// This records the name of the type, as returned by [`std::any::type_name`], which tools
+    /// can map to a schema.
+    pub fn body<T>(mut self) -> Self
+    where
+        T: ?Sized,
+    {
+        self.body = Some(std::any::type_name::<T>().to_owned());
+        self
+    }
+
+    /// Get the status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Get the description, if any.
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Get the name of the type of the response body, if any.
+    pub fn get_body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+}
+
+impl Serialize for ResponseDocs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("ResponseDocs", 3)?;
+        state.serialize_field("status", &self.status.as_u16())?;
+        state.serialize_field("description", &self.description)?;
+        state.serialize_field("body", &self.body)?;
+        state.end()
+    }
+}
+
+/// Types, usually extractors, that can describe the parameters they read from the request.
+///
+/// This can be derived with `#[derive(DescribeParameters)]` from `axum-macros` and is used by
+/// [`OperationDocs::parameters_from`] and the `#[route]` attribute.
+///
+/// # Example
+///
+/// ```
+/// use axum::routing::{DescribeParameters, OperationDocs, ParameterDocs};
+///
+/// struct Pagination {
+///     page: u32,
+///     per_page: u32,
+/// }
+///
+/// impl DescribeParameters for Pagination {
+///     fn parameters() -> Vec<ParameterDocs> {
+///         vec![
+///             ParameterDocs::query("page").description("The page to return"),
+///             ParameterDocs::query("per_page").description("The number of items per page"),
+///         ]
+///     }
+/// }
+///
+/// let docs = OperationDocs::new().parameters_from::<Pagination>();
+/// assert_eq!(docs.get_parameters().len(), 2);
+/// ```
+pub trait DescribeParameters {
+    /// The parameters this type reads from the request.
+    fn parameters() -> Vec<ParameterDocs>;
+}
@@ -0,0 +1,89 @@
+use http::Uri;
+use std::{fmt, sync::Arc};
+
+/// How the request path is rewritten before it reaches a service nested with
+/// [`Router::nest_service_with_rewrite`](super::Router::nest_service_with_rewrite).
+///
+/// The default is [`PathRewrite::strip_prefix`], which is also what
+/// [`Router::nest_service`](super::Router::nest_service) uses.
+#[derive(Clone)]
+pub struct PathRewrite(PathRewriteKind);
+
+#[derive(Clone)]
+pub(super) enum PathRewriteKind {
+    StripPrefix,
+    Preserve,
+    Custom(Arc<dyn Fn(&Uri) -> Uri + Send + Sync>),
+}
+
+impl PathRewrite {
+    /// Remove the prefix the service was nested at.
+    ///
+    /// A service nested at `/api` will see a request to `/api/users` as `/users`.
+    pub fn strip_prefix() -> Self {
+        Self(PathRewriteKind::StripPrefix)
+    }
+
+    /// Leave the request URI untouched.
+    ///
+    /// A service nested at `/api` will see a request to `/api/users` as `/api/users`.
+    pub fn preserve() -> Self {
+        Self(PathRewriteKind::Preserve)
+    }
+
+    /// Rewrite the request URI with a function.
+    ///
+    /// The function receives the original request URI and returns the URI the nested service
+    /// should see.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{
+    ///     Router,
+    ///     routing::{get_service, PathRewrite},
+    ///     http::Uri,
+    /// };
+    ///
+    /// let legacy = get_service(tower::service_fn(|_req| async {
+    ///     Ok::<_, std::convert::Infallible>("legacy")
+    /// }));
+    ///
+    /// // requests to `/v2/users` are seen as `/legacy/users` by the nested service
+    /// let app = Router::new().nest_service_with_rewrite(
+    ///     "/v2",
+    ///     legacy,
+    ///     PathRewrite::custom(|uri: &Uri| {
+    ///         let path = uri.path().trim_start_matches("/v2");
+    ///         format!("/legacy{path}").parse().unwrap()
+    ///     }),
+    /// );
+    /// # let _: Router = app;
+    /// ```
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&Uri) -> Uri + Send + Sync + 'static,
+    {
+        Self(PathRewriteKind::Custom(Arc::new(f)))
+    }
+
+    pub(super) fn into_kind(self) -> PathRewriteKind {
+        self.0
+    }
+}
+
+impl Default for PathRewrite {
+    fn default() -> Self {
+        Self::strip_prefix()
+    }
+}
+
+impl fmt::Debug for PathRewrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            PathRewriteKind::StripPrefix => f.debug_tuple("StripPrefix").finish(),
+            PathRewriteKind::Preserve => f.debug_tuple("Preserve").finish(),
+            PathRewriteKind::Custom(_) => f.debug_tuple("Custom").finish(),
+        }
+    }
+}
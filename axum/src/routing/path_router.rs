@@ -1,13 +1,25 @@
 use crate::extract::{nested_path::SetNestedPath, Request};
 use axum_core::response::IntoResponse;
 use matchit::MatchError;
-use std::{borrow::Cow, collections::HashMap, convert::Infallible, fmt, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    fmt,
+    sync::Arc,
+};
+use tower::util::MapRequestLayer;
 use tower_layer::Layer;
 use tower_service::Service;
 
 use super::{
-    future::RouteFuture, not_found::NotFound, strip_prefix::StripPrefix, url_params, Endpoint,
-    MethodRouter, Route, RouteId, FALLBACK_PARAM_PATH, NEST_TAIL_PARAM,
+    future::RouteFuture,
+    not_found::NotFound,
+    path_rewrite::{PathRewrite, PathRewriteKind},
+    route_table::{RouteEntry, RouteKind},
+    strip_prefix::StripPrefix,
+    url_params, Endpoint, MethodRouter, Route, RouteId, FALLBACK_PARAM_PATH, NEST_TAIL_PARAM,
+    NEST_TAIL_PARAM_CAPTURE,
 };
 
 pub(super) struct PathRouter<S, const IS_FALLBACK: bool> {
@@ -40,6 +52,15 @@ where
         &mut self,
         path: &str,
         method_router: MethodRouter<S>,
+    ) -> Result<(), Cow<'static, str>> {
+        self.route_with_priority(path, method_router, None)
+    }
+
+    pub(super) fn route_with_priority(
+        &mut self,
+        path: &str,
+        method_router: MethodRouter<S>,
+        priority: Option<i32>,
     ) -> Result<(), Cow<'static, str>> {
         fn validate_path(path: &str) -> Result<(), &'static str> {
             if path.is_empty() {
@@ -59,6 +80,16 @@ where
             .get(path)
             .and_then(|route_id| self.routes.get(route_id).map(|svc| (*route_id, svc)))
         {
+            let prev_priority = self.node.priority(route_id).unwrap_or(0);
+            if priority.unwrap_or(0) != prev_priority {
+                return Err(format!(
+                    "Invalid route {path:?}: the route was already added with priority \
+                     {prev_priority}, it cannot be added again with priority {}",
+                    priority.unwrap_or(0),
+                )
+                .into());
+            }
+
             // if we're adding a new `MethodRouter` to a route that already has one just
             // merge them. This makes `.route("/", get(_)).route("/", post(_))` work
             let service = Endpoint::MethodRouter(
@@ -73,7 +104,7 @@ where
         };
 
         let id = self.next_route_id();
-        self.set_node(path, id)?;
+        self.set_node(path, id, priority)?;
         self.routes.insert(id, endpoint);
 
         Ok(())
@@ -96,6 +127,15 @@ where
         &mut self,
         path: &str,
         endpoint: Endpoint<S>,
+    ) -> Result<(), Cow<'static, str>> {
+        self.route_endpoint_with_priority(path, endpoint, None)
+    }
+
+    fn route_endpoint_with_priority(
+        &mut self,
+        path: &str,
+        endpoint: Endpoint<S>,
+        priority: Option<i32>,
     ) -> Result<(), Cow<'static, str>> {
         if path.is_empty() {
             return Err("Paths must start with a `/`. Use \"/\" for root routes".into());
@@ -104,16 +144,16 @@ where
         }
 
         let id = self.next_route_id();
-        self.set_node(path, id)?;
+        self.set_node(path, id, priority)?;
         self.routes.insert(id, endpoint);
 
         Ok(())
     }
 
-    fn set_node(&mut self, path: &str, id: RouteId) -> Result<(), String> {
+    fn set_node(&mut self, path: &str, id: RouteId, priority: Option<i32>) -> Result<(), String> {
         let node = Arc::make_mut(&mut self.node);
 
-        node.insert(path, id)
+        node.insert(path, id, priority)
             .map_err(|err| format!("Invalid route {path:?}: {err}"))
     }
 
@@ -147,9 +187,14 @@ where
                 // one we can ignore is that of `self`.
                 self.replace_endpoint(path, route);
             } else {
+                let priority = node.priority(id);
                 match route {
-                    Endpoint::MethodRouter(method_router) => self.route(path, method_router)?,
-                    Endpoint::Route(route) => self.route_service(path, route)?,
+                    Endpoint::MethodRouter(method_router) => {
+                        self.route_with_priority(path, method_router, priority)?
+                    }
+                    Endpoint::Route(route) => {
+                        self.route_endpoint_with_priority(path, Endpoint::Route(route), priority)?
+                    }
                 }
             }
         }
@@ -177,6 +222,7 @@ where
                 .expect("no path for route id. This is a bug in axum. Please file an issue");
 
             let path = path_for_nested_route(prefix, inner_path);
+            let priority = node.priority(id);
 
             let layer = (
                 StripPrefix::layer(prefix),
@@ -184,10 +230,10 @@ where
             );
             match endpoint.layer(layer) {
                 Endpoint::MethodRouter(method_router) => {
-                    self.route(&path, method_router)?;
+                    self.route_with_priority(&path, method_router, priority)?;
                 }
                 Endpoint::Route(route) => {
-                    self.route_endpoint(&path, Endpoint::Route(route))?;
+                    self.route_endpoint_with_priority(&path, Endpoint::Route(route), priority)?;
                 }
             }
         }
@@ -199,6 +245,7 @@ where
         &mut self,
         path_to_nest_at: &str,
         svc: T,
+        rewrite: PathRewrite,
     ) -> Result<(), Cow<'static, str>>
     where
         T: Service<Request, Error = Infallible> + Clone + Send + 'static,
@@ -214,11 +261,19 @@ where
             format!("{path}/*{NEST_TAIL_PARAM}")
         };
 
-        let layer = (
-            StripPrefix::layer(prefix),
-            SetNestedPath::layer(path_to_nest_at),
-        );
-        let endpoint = Endpoint::Route(Route::new(layer.layer(svc)));
+        let svc = SetNestedPath::layer(path_to_nest_at).layer(svc);
+        let route = match rewrite.into_kind() {
+            PathRewriteKind::StripPrefix => Route::new(StripPrefix::layer(prefix).layer(svc)),
+            PathRewriteKind::Preserve => Route::new(svc),
+            PathRewriteKind::Custom(f) => Route::new(
+                MapRequestLayer::new(move |mut req: Request| {
+                    *req.uri_mut() = f(req.uri());
+                    req
+                })
+                .layer(svc),
+            ),
+        };
+        let endpoint = Endpoint::Route(route);
 
         self.route_endpoint(&path, endpoint.clone())?;
 
@@ -327,9 +382,15 @@ where
             }
         }
 
-        let path = req.uri().path().to_owned();
+        // match against a clone of the path, which only bumps a reference count, so the captured
+        // parameters can point into it
+        let path = match req.uri().path_and_query() {
+            Some(path) if !req.uri().path().is_empty() => path.clone(),
+            // no route matches an empty path, as used by `CONNECT` requests
+            _ => return Err((req, state)),
+        };
 
-        match self.node.at(&path) {
+        match self.node.at(path.path()) {
             Ok(match_) => {
                 let id = *match_.value;
 
@@ -342,7 +403,12 @@ where
                     );
                 }
 
-                url_params::insert_url_params(req.extensions_mut(), match_.params);
+                url_params::insert_url_params(
+                    req.extensions_mut(),
+                    &path,
+                    match_.params,
+                    &self.node.param_keys,
+                );
 
                 let endpoint = self
                     .routes
@@ -378,6 +444,73 @@ where
         }
     }
 
+    pub(super) fn export(&self) -> Vec<RouteEntry> {
+        let nested_service_prefixes = self
+            .node
+            .route_id_to_path
+            .values()
+            .filter_map(|path| path.strip_suffix(NEST_TAIL_PARAM_CAPTURE))
+            .map(|prefix| if prefix.is_empty() { "/" } else { prefix })
+            .collect::<Vec<_>>();
+
+        let mut routes =
+            self.routes
+                .iter()
+                .filter_map(|(id, endpoint)| {
+                    let path = self.node.route_id_to_path.get(id).expect(
+                        "no path for route id. This is a bug in axum. Please file an issue",
+                    );
+                    let priority = self.node.priority(*id);
+
+                    let entry = match endpoint {
+                        Endpoint::MethodRouter(method_router) => {
+                            let (methods, any_method) = method_router.methods();
+                            let operations = method_router.operations(&methods);
+                            RouteEntry {
+                                path: path.to_string(),
+                                kind: RouteKind::Handler,
+                                methods,
+                                any_method,
+                                priority,
+                                operations,
+                            }
+                        }
+                        Endpoint::Route(_) => {
+                            if let Some(prefix) = path.strip_suffix(NEST_TAIL_PARAM_CAPTURE) {
+                                RouteEntry {
+                                    path: if prefix.is_empty() { "/" } else { prefix }.to_owned(),
+                                    kind: RouteKind::NestedService,
+                                    methods: Vec::new(),
+                                    any_method: true,
+                                    priority,
+                                    operations: Vec::new(),
+                                }
+                            } else if nested_service_prefixes.iter().any(|prefix| {
+                                &**path == *prefix || path.strip_suffix('/') == Some(*prefix)
+                            }) {
+                                // nested services are also registered at the prefix itself. Those
+                                // routes are covered by the entry for the wildcard route
+                                return None;
+                            } else {
+                                RouteEntry {
+                                    path: path.to_string(),
+                                    kind: RouteKind::Service,
+                                    methods: Vec::new(),
+                                    any_method: true,
+                                    priority,
+                                    operations: Vec::new(),
+                                }
+                            }
+                        }
+                    };
+                    Some(entry)
+                })
+                .collect::<Vec<_>>();
+
+        routes.sort_by(|a, b| a.path.cmp(&b.path));
+        routes
+    }
+
     fn next_route_id(&mut self) -> RouteId {
         let next_id = self
             .prev_route_id
@@ -419,11 +552,18 @@ impl<S, const IS_FALLBACK: bool> Clone for PathRouter<S, IS_FALLBACK> {
 }
 
 /// Wrapper around `matchit::Router` that supports merging two `Router`s.
+///
+/// Routes added without a priority all live in `inner` and must not conflict. Routes added with
+/// an explicit priority are placed in additional routers, sorted by descending priority, such
+/// that overlapping routes can coexist. `inner` is considered to have priority `0`.
 #[derive(Clone, Default)]
 struct Node {
     inner: matchit::Router<RouteId>,
+    prioritized: Vec<(i32, matchit::Router<RouteId>)>,
     route_id_to_path: HashMap<RouteId, Arc<str>>,
     path_to_route_id: HashMap<Arc<str>, RouteId>,
+    route_id_to_priority: HashMap<RouteId, i32>,
+    param_keys: HashSet<Arc<str>>,
 }
 
 impl Node {
@@ -431,10 +571,22 @@ impl Node {
         &mut self,
         path: impl Into<String>,
         val: RouteId,
+        priority: Option<i32>,
     ) -> Result<(), matchit::InsertError> {
         let path = path.into();
 
-        self.inner.insert(&path, val)?;
+        if let Some(priority) = priority {
+            self.insert_with_priority(&path, val, priority)?;
+            self.route_id_to_priority.insert(val, priority);
+        } else {
+            self.inner.insert(&path, val)?;
+        }
+
+        for key in url_params::param_keys(&path) {
+            if !self.param_keys.contains(key) {
+                self.param_keys.insert(key.into());
+            }
+        }
 
         let shared_path: Arc<str> = path.into();
         self.route_id_to_path.insert(val, shared_path.clone());
@@ -443,11 +595,72 @@ impl Node {
         Ok(())
     }
 
+    fn insert_with_priority(
+        &mut self,
+        path: &str,
+        val: RouteId,
+        priority: i32,
+    ) -> Result<(), matchit::InsertError> {
+        // the prioritized routers accept overlapping routes, but not the same path twice
+        if self.path_to_route_id.contains_key(path) {
+            return Err(matchit::InsertError::Conflict {
+                with: path.to_owned(),
+            });
+        }
+
+        if priority == 0 && self.inner.insert(path, val).is_ok() {
+            return Ok(());
+        }
+
+        for (_, router) in self.prioritized.iter_mut().filter(|(p, _)| *p == priority) {
+            if router.insert(path, val).is_ok() {
+                return Ok(());
+            }
+        }
+
+        // the route conflicts with all routes of the same priority so give it a new router that
+        // is tried after those, meaning registration order decides
+        let mut router = matchit::Router::new();
+        router.insert(path, val)?;
+        let idx = self.prioritized.partition_point(|(p, _)| *p >= priority);
+        self.prioritized.insert(idx, (priority, router));
+
+        Ok(())
+    }
+
+    fn priority(&self, id: RouteId) -> Option<i32> {
+        self.route_id_to_priority.get(&id).copied()
+    }
+
     fn at<'n, 'p>(
         &'n self,
         path: &'p str,
     ) -> Result<matchit::Match<'n, 'p, &'n RouteId>, MatchError> {
-        self.inner.at(path)
+        if self.prioritized.is_empty() {
+            return self.inner.at(path);
+        }
+
+        let split = self.prioritized.partition_point(|(p, _)| *p > 0);
+        let (higher, lower) = self.prioritized.split_at(split);
+
+        for (_, router) in higher {
+            if let Ok(match_) = router.at(path) {
+                return Ok(match_);
+            }
+        }
+
+        let err = match self.inner.at(path) {
+            Ok(match_) => return Ok(match_),
+            Err(err) => err,
+        };
+
+        for (_, router) in lower {
+            if let Ok(match_) = router.at(path) {
+                return Ok(match_);
+            }
+        }
+
+        Err(err)
     }
 }
 
@@ -455,6 +668,7 @@ impl fmt::Debug for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Node")
             .field("paths", &self.route_id_to_path)
+            .field("priorities", &self.route_id_to_priority)
             .finish()
     }
 }
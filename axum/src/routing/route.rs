@@ -1,10 +1,12 @@
 use crate::{
     body::{Body, HttpBody},
+    handler::Handler,
     response::Response,
     util::AxumMutex,
 };
 use axum_core::{extract::Request, response::IntoResponse};
 use bytes::Bytes;
+use futures_util::future::BoxFuture;
 use http::{
     header::{self, CONTENT_LENGTH},
     HeaderMap, HeaderValue,
@@ -14,6 +16,7 @@ use std::{
     convert::Infallible,
     fmt,
     future::Future,
+    marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -28,7 +31,14 @@ use tower_service::Service;
 ///
 /// You normally shouldn't need to care about this type. It's used in
 /// [`Router::layer`](super::Router::layer).
-pub struct Route<E = Infallible>(AxumMutex<BoxCloneService<Request, Response, E>>);
+pub struct Route<E = Infallible>(RouteInner<E>);
+
+enum RouteInner<E> {
+    Service(AxumMutex<BoxCloneService<Request, Response, E>>),
+    // handlers without any layers are called directly, which skips cloning the boxed service and
+    // driving it through `Oneshot` for every request
+    Handler(AxumMutex<Box<dyn ErasedHandler>>),
+}
 
 impl<E> Route<E> {
     pub(crate) fn new<T>(svc: T) -> Self
@@ -37,16 +47,48 @@ impl<E> Route<E> {
         T::Response: IntoResponse + 'static,
         T::Future: Send + 'static,
     {
-        Self(AxumMutex::new(BoxCloneService::new(
+        Self(RouteInner::Service(AxumMutex::new(BoxCloneService::new(
             svc.map_response(IntoResponse::into_response),
-        )))
+        ))))
     }
 
-    pub(crate) fn oneshot_inner(
-        &mut self,
-        req: Request,
-    ) -> Oneshot<BoxCloneService<Request, Response, E>, Request> {
-        self.0.get_mut().unwrap().clone().oneshot(req)
+    pub(crate) fn from_handler<H, T, S>(handler: H, state: S) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+        S: Clone + Send + Sync + 'static,
+    {
+        Self(RouteInner::Handler(AxumMutex::new(Box::new(
+            HandlerWithState {
+                handler,
+                state,
+                _marker: PhantomData,
+            },
+        ))))
+    }
+
+    pub(crate) fn oneshot_inner(&mut self, req: Request) -> RouteFuture<E> {
+        match &mut self.0 {
+            RouteInner::Service(svc) => {
+                RouteFuture::from_future(svc.get_mut().unwrap().clone().oneshot(req))
+            }
+            RouteInner::Handler(handler) => {
+                RouteFuture::from_handler(handler.get_mut().unwrap().call(req))
+            }
+        }
+    }
+
+    /// Call a route that's shared, such as one stored in a `MethodRouter`, without cloning it
+    /// first.
+    pub(crate) fn oneshot_shared(&self, req: Request) -> RouteFuture<E> {
+        match &self.0 {
+            RouteInner::Service(svc) => {
+                RouteFuture::from_future(svc.lock().unwrap().clone().oneshot(req))
+            }
+            RouteInner::Handler(handler) => {
+                RouteFuture::from_handler(handler.lock().unwrap().call(req))
+            }
+        }
     }
 
     pub(crate) fn layer<L, NewError>(self, layer: L) -> Route<NewError>
@@ -72,7 +114,14 @@ impl<E> Route<E> {
 impl<E> Clone for Route<E> {
     #[track_caller]
     fn clone(&self) -> Self {
-        Self(AxumMutex::new(self.0.lock().unwrap().clone()))
+        match &self.0 {
+            RouteInner::Service(svc) => Self(RouteInner::Service(AxumMutex::new(
+                svc.lock().unwrap().clone(),
+            ))),
+            RouteInner::Handler(handler) => Self(RouteInner::Handler(AxumMutex::new(
+                handler.lock().unwrap().clone_box(),
+            ))),
+        }
     }
 }
 
@@ -99,7 +148,38 @@ where
     #[inline]
     fn call(&mut self, req: Request<B>) -> Self::Future {
         let req = req.map(Body::new);
-        RouteFuture::from_future(self.oneshot_inner(req))
+        self.oneshot_inner(req)
+    }
+}
+
+trait ErasedHandler: Send {
+    fn clone_box(&self) -> Box<dyn ErasedHandler>;
+
+    fn call(&self, req: Request) -> BoxFuture<'static, Response>;
+}
+
+struct HandlerWithState<H, T, S> {
+    handler: H,
+    state: S,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<H, T, S> ErasedHandler for HandlerWithState<H, T, S>
+where
+    H: Handler<T, S>,
+    T: 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    fn clone_box(&self) -> Box<dyn ErasedHandler> {
+        Box::new(Self {
+            handler: self.handler.clone(),
+            state: self.state.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn call(&self, req: Request) -> BoxFuture<'static, Response> {
+        Box::pin(self.handler.clone().call(req, self.state.clone()))
     }
 }
 
@@ -123,6 +203,9 @@ pin_project! {
                 Request,
             >,
         },
+        Handler {
+            future: BoxFuture<'static, Response>,
+        },
         Response {
             response: Option<Response>,
         }
@@ -140,6 +223,14 @@ impl<E> RouteFuture<E> {
         }
     }
 
+    fn from_handler(future: BoxFuture<'static, Response>) -> Self {
+        Self {
+            kind: RouteFutureKind::Handler { future },
+            strip_body: false,
+            allow_header: None,
+        }
+    }
+
     pub(crate) fn strip_body(mut self, strip_body: bool) -> Self {
         self.strip_body = strip_body;
         self
@@ -164,6 +255,10 @@ impl<E> Future for RouteFuture<E> {
                 Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
                 Poll::Pending => return Poll::Pending,
             },
+            RouteFutureKindProj::Handler { future } => match future.as_mut().poll(cx) {
+                Poll::Ready(res) => res,
+                Poll::Pending => return Poll::Pending,
+            },
             RouteFutureKindProj::Response { response } => {
                 response.take().expect("future polled after completion")
             }
@@ -250,4 +345,28 @@ mod tests {
         use crate::test_helpers::*;
         assert_send::<Route<()>>();
     }
+
+    #[crate::test]
+    async fn handler_route() {
+        use crate::extract::State;
+        use http_body_util::BodyExt;
+
+        let route = Route::<Infallible>::from_handler(
+            |State(state): State<&'static str>| async move { state },
+            "hello",
+        );
+
+        let res = route
+            .oneshot_shared(Request::new(Body::empty()))
+            .strip_body(true)
+            .await
+            .unwrap();
+        assert_eq!(res.headers()[CONTENT_LENGTH], "5");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty());
+
+        let res = route.oneshot(Request::new(Body::empty())).await.unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello");
+    }
 }
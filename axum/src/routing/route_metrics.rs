@@ -0,0 +1,152 @@
+use crate::extract::{MatchedPath, Request};
+use axum_core::response::Response;
+use futures_util::ready;
+use http::{Method, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Metrics for a single request, reported by [`Router::route_metrics`].
+///
+/// [`Router::route_metrics`]: super::Router::route_metrics
+#[derive(Debug, Clone)]
+pub struct RouteMetrics {
+    matched_path: Option<MatchedPath>,
+    method: Method,
+    status: StatusCode,
+    latency: Duration,
+}
+
+impl RouteMetrics {
+    /// The path pattern of the matched route, such as `/users/:id`.
+    ///
+    /// This is `None` for requests handled by a fallback or a nested service.
+    pub fn matched_path(&self) -> Option<&str> {
+        self.matched_path.as_ref().map(MatchedPath::as_str)
+    }
+
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The time it took to produce the response.
+    ///
+    /// This doesn't include the time it takes to send the response body.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
+
+type Callback = Arc<dyn Fn(&RouteMetrics) + Send + Sync>;
+
+#[derive(Clone)]
+pub(super) struct RouteMetricsLayer {
+    callback: Callback,
+}
+
+impl RouteMetricsLayer {
+    pub(super) fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&RouteMetrics) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl<S> Layer<S> for RouteMetricsLayer {
+    type Service = RouteMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RouteMetricsService {
+            inner,
+            callback: Arc::clone(&self.callback),
+        }
+    }
+}
+
+impl fmt::Debug for RouteMetricsLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteMetricsLayer").finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct RouteMetricsService<S> {
+    inner: S,
+    callback: Callback,
+}
+
+impl<S> Service<Request> for RouteMetricsService<S>
+where
+    S: Service<Request, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = RouteMetricsFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let matched_path = req.extensions().get::<MatchedPath>().cloned();
+        let method = req.method().clone();
+
+        RouteMetricsFuture {
+            inner: self.inner.call(req),
+            state: Some((matched_path, method)),
+            callback: Arc::clone(&self.callback),
+            start: Instant::now(),
+        }
+    }
+}
+
+pin_project! {
+    pub(super) struct RouteMetricsFuture<F> {
+        #[pin]
+        inner: F,
+        state: Option<(Option<MatchedPath>, Method)>,
+        callback: Callback,
+        start: Instant,
+    }
+}
+
+impl<F, E> Future for RouteMetricsFuture<F>
+where
+    F: Future<Output = Result<Response, E>>,
+{
+    type Output = Result<Response, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.inner.poll(cx));
+
+        if let (Ok(res), Some((matched_path, method))) = (&result, this.state.take()) {
+            (this.callback)(&RouteMetrics {
+                matched_path,
+                method,
+                status: res.status(),
+                latency: this.start.elapsed(),
+            });
+        }
+
+        Poll::Ready(result)
+    }
+}
@@ -0,0 +1,325 @@
+use super::OperationDocs;
+use http::Method;
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::{collections::BTreeMap, fmt};
+
+/// A description of the routes in a [`Router`], returned by [`Router::export_routes`].
+///
+/// `RouteTable` implements [`Serialize`] with a stable schema, which makes it suitable for
+/// generating documentation or API clients. As JSON a route table looks like this:
+///
+/// ```json
+/// {
+///   "routes": [
+///     {
+///       "path": "/assets",
+///       "kind": "nested_service",
+///       "methods": [],
+///       "any_method": true,
+///       "priority": null,
+///       "operations": {}
+///     },
+///     {
+///       "path": "/users/:id",
+///       "kind": "handler",
+///       "methods": ["GET", "HEAD", "DELETE"],
+///       "any_method": false,
+///       "priority": null,
+///       "operations": {
+///         "GET": {
+///           "summary": "Get a user",
+///           "description": null,
+///           "parameters": [
+///             {
+///               "name": "id",
+///               "in": "path",
+///               "description": "The id of the user",
+///               "required": true,
+///               "default": null,
+///               "minimum": null,
+///               "maximum": null
+///             }
+///           ],
+///           "responses": [
+///             { "status": 200, "description": null, "body": "my_app::User" }
+///           ]
+///         }
+///       }
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Routes are sorted by path. `operations` contains the [`OperationDocs`] attached with
+/// [`MethodRouter::docs`](super::MethodRouter::docs), keyed by method.
+///
+/// [`Router`]: super::Router
+/// [`Router::export_routes`]: super::Router::export_routes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTable {
+    pub(super) routes: Vec<RouteEntry>,
+}
+
+impl RouteTable {
+    /// The routes in the table.
+    pub fn routes(&self) -> &[RouteEntry] {
+        &self.routes
+    }
+}
+
+impl Serialize for RouteTable {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("RouteTable", 1)?;
+        state.serialize_field("routes", &self.routes)?;
+        state.end()
+    }
+}
+
+/// A stable description of the routes and fallbacks in a [`Router`], returned by
+/// [`Router::snapshot`].
+///
+/// Snapshots are meant for regression tests that catch routes being removed or changed by
+/// accident, for example with [insta]. Unlike [`RouteTable`] they don't include the
+/// [`OperationDocs`], so editing documentation doesn't change the snapshot.
+///
+/// The [`Display`](fmt::Display) implementation prints one line per route, sorted by path,
+/// followed by one line per fallback:
+///
+/// ```text
+/// GET,HEAD /*rest [priority -1]
+/// * /anything
+/// POST /api/status
+/// * /assets (nested service)
+/// GET,HEAD,DELETE /users/:id
+/// FALLBACK / (default)
+/// FALLBACK /api
+/// ```
+///
+/// `*` means the route accepts any method. Fallbacks marked `(default)` respond with
+/// `404 Not Found`.
+///
+/// As JSON a snapshot looks like this:
+///
+/// ```json
+/// {
+///   "routes": [
+///     {
+///       "path": "/users/:id",
+///       "kind": "handler",
+///       "methods": ["GET", "HEAD", "DELETE"],
+///       "any_method": false,
+///       "priority": null
+///     }
+///   ],
+///   "fallbacks": [
+///     { "path": "/", "custom": false }
+///   ]
+/// }
+/// ```
+///
+/// [`Router`]: super::Router
+/// [`Router::snapshot`]: super::Router::snapshot
+/// [insta]: https://crates.io/crates/insta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSnapshot {
+    pub(super) routes: Vec<RouteEntry>,
+    pub(super) fallbacks: Vec<(String, bool)>,
+}
+
+impl RouteSnapshot {
+    /// The routes in the snapshot.
+    ///
+    /// [`RouteEntry::operation`] always returns `None` for these.
+    pub fn routes(&self) -> &[RouteEntry] {
+        &self.routes
+    }
+}
+
+impl fmt::Display for RouteSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for route in &self.routes {
+            let mut methods = route.methods.iter().map(Method::as_str).collect::<Vec<_>>();
+            if route.any_method {
+                methods.push("*");
+            }
+            write!(f, "{} {}", methods.join(","), route.path)?;
+
+            match route.kind {
+                RouteKind::Handler => {}
+                RouteKind::Service => write!(f, " (service)")?,
+                RouteKind::NestedService => write!(f, " (nested service)")?,
+            }
+            if let Some(priority) = route.priority {
+                write!(f, " [priority {priority}]")?;
+            }
+            writeln!(f)?;
+        }
+
+        for (path, custom) in &self.fallbacks {
+            if *custom {
+                writeln!(f, "FALLBACK {path}")?;
+            } else {
+                writeln!(f, "FALLBACK {path} (default)")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for RouteSnapshot {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        struct SnapshotRoute<'a>(&'a RouteEntry);
+
+        impl Serialize for SnapshotRoute<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let route = self.0;
+                let methods = route.methods.iter().map(Method::as_str).collect::<Vec<_>>();
+
+                let mut state = serializer.serialize_struct("RouteEntry", 5)?;
+                state.serialize_field("path", &route.path)?;
+                state.serialize_field("kind", &route.kind)?;
+                state.serialize_field("methods", &methods)?;
+                state.serialize_field("any_method", &route.any_method)?;
+                state.serialize_field("priority", &route.priority)?;
+                state.end()
+            }
+        }
+
+        struct SnapshotFallback<'a>(&'a str, bool);
+
+        impl Serialize for SnapshotFallback<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let mut state = serializer.serialize_struct("Fallback", 2)?;
+                state.serialize_field("path", self.0)?;
+                state.serialize_field("custom", &self.1)?;
+                state.end()
+            }
+        }
+
+        let routes = self.routes.iter().map(SnapshotRoute).collect::<Vec<_>>();
+        let fallbacks = self
+            .fallbacks
+            .iter()
+            .map(|(path, custom)| SnapshotFallback(path, *custom))
+            .collect::<Vec<_>>();
+
+        let mut state = serializer.serialize_struct("RouteSnapshot", 2)?;
+        state.serialize_field("routes", &routes)?;
+        state.serialize_field("fallbacks", &fallbacks)?;
+        state.end()
+    }
+}
+
+/// A single route in a [`RouteTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteEntry {
+    pub(super) path: String,
+    pub(super) kind: RouteKind,
+    pub(super) methods: Vec<Method>,
+    pub(super) any_method: bool,
+    pub(super) priority: Option<i32>,
+    pub(super) operations: Vec<(Method, OperationDocs)>,
+}
+
+impl RouteEntry {
+    /// The path of the route, such as `/users/:id`.
+    ///
+    /// For nested services this is the path the service is nested at.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// What kind of endpoint handles the route.
+    pub fn kind(&self) -> RouteKind {
+        self.kind
+    }
+
+    /// The methods the route has explicit endpoints for.
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// Whether the route accepts requests with any method.
+    ///
+    /// This is the case for routes added with [`any`](super::any), routes with a method
+    /// fallback, and services.
+    pub fn any_method(&self) -> bool {
+        self.any_method
+    }
+
+    /// The priority the route was added with using
+    /// [`Router::route_with_priority`](super::Router::route_with_priority).
+    pub fn priority(&self) -> Option<i32> {
+        self.priority
+    }
+
+    /// The documentation attached to the endpoint for `method`, if any.
+    pub fn operation(&self, method: &Method) -> Option<&OperationDocs> {
+        self.operations
+            .iter()
+            .find(|(operation_method, _)| operation_method == method)
+            .map(|(_, docs)| docs)
+    }
+}
+
+impl Serialize for RouteEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let methods = self.methods.iter().map(Method::as_str).collect::<Vec<_>>();
+        let operations = self
+            .operations
+            .iter()
+            .map(|(method, docs)| (method.as_str(), docs))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut state = serializer.serialize_struct("RouteEntry", 6)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("kind", &self.kind)?;
+        state.serialize_field("methods", &methods)?;
+        state.serialize_field("any_method", &self.any_method)?;
+        state.serialize_field("priority", &self.priority)?;
+        state.serialize_field("operations", &operations)?;
+        state.end()
+    }
+}
+
+/// The kind of endpoint that handles a route in a [`RouteTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RouteKind {
+    /// A [`MethodRouter`](super::MethodRouter), added with [`Router::route`](super::Router::route).
+    Handler,
+    /// A service, added with [`Router::route_service`](super::Router::route_service).
+    Service,
+    /// A nested service, added with [`Router::nest_service`](super::Router::nest_service).
+    NestedService,
+}
+
+impl Serialize for RouteKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (index, name) = match self {
+            Self::Handler => (0, "handler"),
+            Self::Service => (1, "service"),
+            Self::NestedService => (2, "nested_service"),
+        };
+        serializer.serialize_unit_variant("RouteKind", index, name)
+    }
+}
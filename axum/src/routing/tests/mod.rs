@@ -1,19 +1,23 @@
 use crate::{
     body::{Body, Bytes},
     error_handling::HandleErrorLayer,
-    extract::{self, DefaultBodyLimit, FromRef, Path, State},
+    extract::{
+        self, rejection::BytesRejection, DefaultBodyLimit, FromRef, FromRequest, Path, State,
+        StateRef,
+    },
     handler::{Handler, HandlerWithoutStateExt},
     response::{IntoResponse, Response},
     routing::{
-        delete, get, get_service, on, on_service, patch, patch_service,
-        path_router::path_for_nested_route, post, MethodFilter,
+        any, delete, get, get_service, on, on_service, patch, patch_service,
+        path_router::path_for_nested_route, post, MethodFilter, OperationDocs, ParameterDocs,
+        ResponseDocs,
     },
     test_helpers::{
         tracing_helpers::{capture_tracing, TracingEvent},
         *,
     },
-    util::mutex_num_locked,
-    BoxError, Extension, Json, Router, ServiceExt,
+    util::{mutex_num_locked, AxumMutex},
+    BoxError, Extension, Json, RequestExt, Router, ServiceExt,
 };
 use axum_core::extract::Request;
 use futures_util::stream::StreamExt;
@@ -27,7 +31,10 @@ use serde_json::json;
 use std::{
     convert::Infallible,
     future::{ready, IntoFuture, Ready},
-    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
     time::Duration,
 };
@@ -421,6 +428,108 @@ async fn static_and_dynamic_paths() {
     assert_eq!(res.text().await, "static");
 }
 
+#[test]
+#[should_panic(expected = "Invalid route \"/:name\"")]
+fn conflicting_routes_without_priority_panic() {
+    let _: Router = Router::new()
+        .route("/:id", get(|| async {}))
+        .route("/:name", get(|| async {}));
+}
+
+#[crate::test]
+async fn route_priority() {
+    let app = Router::new()
+        .route("/:id", get(|| async { "low" }))
+        .route_with_priority("/:name", get(|| async { "high" }), 1)
+        .route_with_priority("/foo/*rest", get(|| async { "wildcard" }), -1)
+        .route("/foo/:id", get(|| async { "capture" }));
+
+    let client = TestClient::new(app);
+
+    let res = client.get("/bar").await;
+    assert_eq!(res.text().await, "high");
+
+    let res = client.get("/foo/bar").await;
+    assert_eq!(res.text().await, "capture");
+
+    let res = client.get("/foo/bar/baz").await;
+    assert_eq!(res.text().await, "wildcard");
+}
+
+#[crate::test]
+async fn route_priority_falls_back_to_registration_order() {
+    let app = Router::new()
+        .route_with_priority("/:a", get(|| async { "first" }), 0)
+        .route_with_priority("/:b", get(|| async { "second" }), 0);
+
+    let client = TestClient::new(app);
+
+    let res = client.get("/foo").await;
+    assert_eq!(res.text().await, "first");
+}
+
+#[crate::test]
+async fn route_priority_is_kept_when_merging_and_nesting() {
+    let one = Router::new().route("/:id", get(|| async { "one" }));
+    let two = Router::new().route_with_priority("/:name", get(|| async { "two" }), 1);
+
+    let app = Router::new().nest("/api", one.merge(two));
+
+    let client = TestClient::new(app);
+
+    let res = client.get("/api/foo").await;
+    assert_eq!(res.text().await, "two");
+}
+
+#[crate::test]
+async fn route_priority_merges_methods_of_the_same_path() {
+    let app = Router::new()
+        .route("/:id", get(|| async { "low" }))
+        .route_with_priority("/:name", get(|| async { "get" }), 1)
+        .route_with_priority("/:name", post(|| async { "post" }), 1);
+
+    let client = TestClient::new(app);
+
+    let res = client.get("/foo").await;
+    assert_eq!(res.text().await, "get");
+
+    let res = client.post("/foo").await;
+    assert_eq!(res.text().await, "post");
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid route \"/:name\": the route was already added with priority 1, \
+                           it cannot be added again with priority 2"
+)]
+fn route_priority_of_existing_path_panics() {
+    let _: Router = Router::new()
+        .route_with_priority("/:name", get(|| async {}), 1)
+        .route_with_priority("/:name", post(|| async {}), 2);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid route \"/:name\": the route was already added with priority 0, \
+                           it cannot be added again with priority 1"
+)]
+fn route_priority_of_existing_unprioritized_path_panics() {
+    let _: Router = Router::new()
+        .route("/:name", get(|| async {}))
+        .route_with_priority("/:name", post(|| async {}), 1);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid route \"/foo\": insertion failed due to conflict with \
+                           previously registered route: /foo"
+)]
+fn route_priority_of_existing_service_panics() {
+    let _: Router = Router::new()
+        .route_service("/foo", get(|| async {}).into_service())
+        .route_with_priority("/foo", post(|| async {}), 1);
+}
+
 #[crate::test]
 #[should_panic(expected = "Paths must start with a `/`. Use \"/\" for root routes")]
 async fn empty_route() {
@@ -714,6 +823,37 @@ async fn changing_the_default_limit() {
     assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
 }
 
+#[crate::test]
+async fn method_router_body_limit() {
+    let app = Router::new()
+        .route(
+            "/",
+            post(|req: Request| async move {
+                let limit = req.body_limit();
+                let _ = Bytes::from_request(req, &()).await?;
+                Ok::<_, BytesRejection>(format!("{limit:?}"))
+            })
+            .body_limit(10),
+        )
+        .route(
+            "/default",
+            post(|req: Request| async move { format!("{:?}", req.body_limit()) }),
+        )
+        .layer(DefaultBodyLimit::max(5));
+
+    let client = TestClient::new(app);
+
+    let res = client.post("/").body("a".repeat(10)).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await, "Some(10)");
+
+    let res = client.post("/").body("a".repeat(11)).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let res = client.post("/default").await;
+    assert_eq!(res.text().await, "Some(5)");
+}
+
 #[crate::test]
 async fn changing_the_default_limit_differently_on_different_routes() {
     let limit1 = 2;
@@ -952,7 +1092,69 @@ async fn state_isnt_cloned_too_much() {
 
     client.get("/").await;
 
-    assert_eq!(COUNT.load(Ordering::SeqCst), 4);
+    // once to call the handler with its state, and once to extract `State`
+    assert_eq!(COUNT.load(Ordering::SeqCst), 2);
+}
+
+#[crate::test]
+async fn extract_state_ref() {
+    // doesn't implement `Clone`
+    struct AppState {
+        value: i32,
+    }
+
+    let state = Arc::new(AppState { value: 1 });
+
+    let app = Router::new()
+        .route(
+            "/",
+            get(|state: StateRef<AppState>| async move { state.value.to_string() }),
+        )
+        .with_state(Arc::clone(&state));
+    let client = TestClient::new(app);
+
+    let res = client.get("/").await;
+    assert_eq!(res.text().await, "1");
+
+    // the extracted reference has been dropped
+    assert_eq!(Arc::strong_count(&state), 2);
+}
+
+#[crate::test]
+async fn extract_state_ref_substate() {
+    #[derive(Clone)]
+    struct AppState {
+        value: i32,
+        inner: Arc<InnerState>,
+    }
+
+    // doesn't implement `Clone`
+    struct InnerState {
+        value: i32,
+    }
+
+    impl FromRef<AppState> for Arc<InnerState> {
+        fn from_ref(state: &AppState) -> Self {
+            state.inner.clone()
+        }
+    }
+
+    async fn handler(State(outer): State<AppState>, inner: StateRef<InnerState>) {
+        assert_eq!(outer.value, 1);
+        assert_eq!(inner.value, 2);
+    }
+
+    let inner = Arc::new(InnerState { value: 2 });
+    let state = AppState {
+        value: 1,
+        inner: Arc::clone(&inner),
+    };
+
+    let app = Router::new().route("/", get(handler)).with_state(state);
+    let client = TestClient::new(app);
+
+    let res = client.get("/").await;
+    assert_eq!(res.status(), StatusCode::OK);
 }
 
 #[crate::test]
@@ -998,7 +1200,8 @@ async fn logging_rejections() {
                     status: 500,
                     body: "Missing request extension: Extension of \
                         type `core::convert::Infallible` was not found. \
-                        Perhaps you forgot to add it? See `axum::Extension`."
+                        Perhaps you forgot to add it with `.layer(Extension(...))`, \
+                        or added the layer before this route. See `axum::Extension`."
                         .to_owned(),
                     rejection_type: "axum::extract::rejection::MissingExtension".to_owned(),
                 },
@@ -1099,3 +1302,249 @@ async fn locks_mutex_very_little() {
         assert_eq!(num, 1);
     }
 }
+
+#[crate::test]
+async fn route_metrics() {
+    let reported = Arc::new(AxumMutex::new(Vec::new()));
+
+    let app = Router::new()
+        .route("/users/:id", get(|| async {}))
+        .route(
+            "/fail",
+            post(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        )
+        .route_metrics({
+            let reported = reported.clone();
+            move |metrics| {
+                reported.lock().unwrap().push((
+                    metrics.matched_path().map(ToOwned::to_owned),
+                    metrics.method().clone(),
+                    metrics.status(),
+                ));
+            }
+        });
+
+    let client = TestClient::new(app);
+
+    client.get("/users/1").await;
+    client.post("/fail").await;
+    client.get("/not-found").await;
+
+    assert_eq!(
+        *reported.lock().unwrap(),
+        [
+            (Some("/users/:id".to_owned()), Method::GET, StatusCode::OK),
+            (
+                Some("/fail".to_owned()),
+                Method::POST,
+                StatusCode::INTERNAL_SERVER_ERROR
+            ),
+            (None, Method::GET, StatusCode::NOT_FOUND),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn export_routes() {
+    let app = Router::<()>::new()
+        .route("/users/:id", get(|| async {}).delete(|| async {}))
+        .route("/anything", any(|| async {}))
+        .route_with_priority("/*rest", get(|| async {}), -1)
+        .route_service(
+            "/service",
+            service_fn(|_: Request| async { Ok::<_, Infallible>(()) }),
+        )
+        .nest_service(
+            "/assets",
+            service_fn(|_: Request| async { Ok::<_, Infallible>(()) }),
+        )
+        .nest("/api", Router::new().route("/status", post(|| async {})));
+
+    let routes = app.export_routes();
+
+    assert_eq!(
+        serde_json::to_value(&routes).unwrap(),
+        json!({
+            "routes": [
+                { "path": "/*rest", "kind": "handler", "methods": ["GET", "HEAD"], "any_method": false, "priority": -1, "operations": {} },
+                { "path": "/anything", "kind": "handler", "methods": [], "any_method": true, "priority": null, "operations": {} },
+                { "path": "/api/status", "kind": "handler", "methods": ["POST"], "any_method": false, "priority": null, "operations": {} },
+                { "path": "/assets", "kind": "nested_service", "methods": [], "any_method": true, "priority": null, "operations": {} },
+                { "path": "/service", "kind": "service", "methods": [], "any_method": true, "priority": null, "operations": {} },
+                { "path": "/users/:id", "kind": "handler", "methods": ["GET", "HEAD", "DELETE"], "any_method": false, "priority": null, "operations": {} },
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+async fn snapshot() {
+    let app = Router::<()>::new()
+        .route(
+            "/users/:id",
+            get(|| async {}).delete(|| async {}).docs(
+                MethodFilter::GET,
+                OperationDocs::new().summary("Get a user"),
+            ),
+        )
+        .route("/anything", any(|| async {}))
+        .route_with_priority("/*rest", get(|| async {}), -1)
+        .route_service(
+            "/service",
+            service_fn(|_: Request| async { Ok::<_, Infallible>(()) }),
+        )
+        .nest_service(
+            "/assets",
+            service_fn(|_: Request| async { Ok::<_, Infallible>(()) }),
+        )
+        .nest(
+            "/api",
+            Router::new()
+                .route("/status", post(|| async {}))
+                .fallback(|| async {}),
+        )
+        .nest("/admin", Router::new().route("/", get(|| async {})));
+
+    let snapshot = app.snapshot();
+
+    assert_eq!(
+        snapshot.to_string(),
+        "\
+GET,HEAD /*rest [priority -1]
+GET,HEAD /admin
+* /anything
+POST /api/status
+* /assets (nested service)
+* /service (service)
+GET,HEAD,DELETE /users/:id
+FALLBACK / (default)
+FALLBACK /api
+"
+    );
+
+    assert_eq!(
+        serde_json::to_value(&snapshot).unwrap()["fallbacks"],
+        json!([
+            { "path": "/", "custom": false },
+            { "path": "/api", "custom": true },
+        ])
+    );
+    assert_eq!(
+        serde_json::to_value(&snapshot).unwrap()["routes"][6],
+        json!({ "path": "/users/:id", "kind": "handler", "methods": ["GET", "HEAD", "DELETE"], "any_method": false, "priority": null })
+    );
+    assert!(snapshot.routes()[6].operation(&Method::GET).is_none());
+
+    let app = app.fallback(|| async {});
+    assert!(app
+        .snapshot()
+        .to_string()
+        .ends_with("FALLBACK /\nFALLBACK /api\n"));
+}
+
+#[crate::test]
+async fn export_routes_with_docs() {
+    let app = Router::<()>::new()
+        .route(
+            "/users/:id",
+            get(|| async {}).docs(
+                MethodFilter::GET,
+                OperationDocs::new()
+                    .summary("Get a user")
+                    .parameter(ParameterDocs::path("id").description("The id of the user"))
+                    .response(ResponseDocs::new(StatusCode::OK).body::<String>()),
+            ),
+        )
+        .route(
+            "/users/:id",
+            delete(|| async {}).docs(MethodFilter::DELETE, OperationDocs::new()),
+        );
+
+    let routes = app.export_routes();
+    let route = &routes.routes()[0];
+    assert!(route.operation(&Method::HEAD).is_none());
+    assert_eq!(
+        route.operation(&Method::DELETE),
+        Some(&OperationDocs::new())
+    );
+
+    assert_eq!(
+        serde_json::to_value(&routes).unwrap()["routes"][0]["operations"]["GET"],
+        json!({
+            "summary": "Get a user",
+            "description": null,
+            "parameters": [
+                {
+                    "name": "id",
+                    "in": "path",
+                    "description": "The id of the user",
+                    "required": true,
+                    "default": null,
+                    "minimum": null,
+                    "maximum": null,
+                },
+            ],
+            "responses": [
+                { "status": 200, "description": null, "body": "alloc::string::String" },
+            ],
+        })
+    );
+}
+
+#[crate::test]
+async fn rejection_handler() {
+    let app = Router::new()
+        .route("/json", post(|_: Json<serde_json::Value>| async {}))
+        .route("/path/:id", get(|_: Path<u32>| async {}))
+        .route(
+            "/not-a-rejection",
+            get(|| async { (StatusCode::BAD_REQUEST, "custom") }),
+        )
+        .rejection_handler(|rejection: extract::rejection::RejectionInfo| {
+            Json(json!({
+                "status": rejection.status().as_u16(),
+                "type": rejection.rejection_type().rsplit("::").next(),
+                "detail": rejection.body_text(),
+            }))
+        });
+
+    let client = TestClient::new(app);
+
+    let res = client.post("/json").body("{}").await;
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(
+        res.json::<serde_json::Value>().await,
+        json!({
+            "status": 415,
+            "type": "MissingJsonContentType",
+            "detail": "Expected request with `Content-Type: application/json`",
+        })
+    );
+
+    let res = client.get("/path/foo").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json::<serde_json::Value>().await,
+        json!({
+            "status": 400,
+            "type": "FailedToDeserializePathParams",
+            "detail": r#"Invalid URL: Cannot parse `"foo"` to a `u32`"#,
+        })
+    );
+
+    let res = client.get("/not-a-rejection").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.text().await, "custom");
+}
+
+#[crate::test]
+async fn rejection_handler_status() {
+    let app = Router::new()
+        .route("/json", post(|_: Json<serde_json::Value>| async {}))
+        .rejection_handler(|_: extract::rejection::RejectionInfo| StatusCode::IM_A_TEAPOT);
+
+    let client = TestClient::new(app);
+
+    let res = client.post("/json").body("{}").await;
+    assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::routing::PathRewrite;
 use std::collections::HashMap;
 use tower_http::services::ServeDir;
 
@@ -199,6 +200,40 @@ async fn nested_service_sees_stripped_uri() {
     assert_eq!(res.text().await, "/baz");
 }
 
+#[crate::test]
+async fn nested_service_path_rewrite() {
+    let svc = service_fn(|req: Request| async move {
+        let body = Body::from(req.uri().to_string());
+        Ok::<_, Infallible>(Response::new(body))
+    });
+
+    let app = Router::new()
+        .nest_service_with_rewrite("/strip", svc, PathRewrite::strip_prefix())
+        .nest_service_with_rewrite("/preserve", svc, PathRewrite::preserve())
+        .nest_service_with_rewrite(
+            "/custom/:version",
+            svc,
+            PathRewrite::custom(|uri: &Uri| {
+                let path = uri.path().replacen("/custom", "/rewritten", 1);
+                path.parse().unwrap()
+            }),
+        );
+
+    let client = TestClient::new(app);
+
+    let res = client.get("/strip/foo?a=1").await;
+    assert_eq!(res.text().await, "/foo?a=1");
+
+    let res = client.get("/preserve/foo?a=1").await;
+    assert_eq!(res.text().await, "/preserve/foo?a=1");
+
+    let res = client.get("/preserve").await;
+    assert_eq!(res.text().await, "/preserve");
+
+    let res = client.get("/custom/v1/foo").await;
+    assert_eq!(res.text().await, "/rewritten/v1/foo");
+}
+
 #[crate::test]
 async fn nest_static_file_server() {
     let app = Router::new().nest_service("/static", ServeDir::new("."));
@@ -1,47 +1,144 @@
-use crate::util::PercentDecodedStr;
-use http::Extensions;
+use http::{uri::PathAndQuery, Extensions};
 use matchit::Params;
-use std::sync::Arc;
+use percent_encoding::percent_decode_str;
+use smallvec::SmallVec;
+use std::{borrow::Cow, collections::HashSet, ops::Range, str::Utf8Error, sync::Arc};
 
-#[derive(Clone)]
-pub(crate) enum UrlParams {
-    Params(Vec<(Arc<str>, PercentDecodedStr)>),
-    InvalidUtf8InPathParam { key: Arc<str> },
+/// The path parameters captured while routing a request.
+///
+/// Values aren't copied out of the path. Each one is stored as a range into the path it was
+/// matched against, which is cheap to clone, and only percent decoded when it's extracted. Routes
+/// with up to four parameters therefore don't allocate for their captures.
+#[derive(Clone, Debug)]
+pub(crate) struct UrlParams(SmallVec<[UrlParam; 4]>);
+
+#[derive(Clone, Debug)]
+pub(crate) struct UrlParam {
+    key: Arc<str>,
+    path: PathAndQuery,
+    value: Range<usize>,
+}
+
+impl UrlParam {
+    pub(crate) fn key(&self) -> &Arc<str> {
+        &self.key
+    }
+
+    /// The value exactly as it appears in the path, without percent decoding.
+    pub(crate) fn raw_value(&self) -> &str {
+        &self.path.path()[self.value.clone()]
+    }
+
+    /// The percent decoded value, which borrows from the path unless something was decoded.
+    pub(crate) fn decoded_value(&self) -> Result<Cow<'_, str>, Utf8Error> {
+        percent_decode_str(self.raw_value()).decode_utf8()
+    }
 }
 
-pub(super) fn insert_url_params(extensions: &mut Extensions, params: Params) {
-    let current_params = extensions.get_mut();
+impl UrlParams {
+    pub(crate) fn iter(&self) -> std::slice::Iter<'_, UrlParam> {
+        self.0.iter()
+    }
+
+    pub(crate) fn raw(&self) -> SmallVec<[(&str, Cow<'_, str>); 4]> {
+        self.iter()
+            .map(|param| (&*param.key, Cow::Borrowed(param.raw_value())))
+            .collect()
+    }
 
-    if let Some(UrlParams::InvalidUtf8InPathParam { .. }) = current_params {
-        // nothing to do here since an error was stored earlier
-        return;
+    /// Percent decode all values, or return the key of the first value that isn't valid UTF-8
+    /// once decoded.
+    pub(crate) fn decoded(&self) -> Result<SmallVec<[(&str, Cow<'_, str>); 4]>, &Arc<str>> {
+        self.iter()
+            .map(|param| match param.decoded_value() {
+                Ok(value) => Ok((&*param.key, value)),
+                Err(_) => Err(&param.key),
+            })
+            .collect()
     }
+}
 
+/// The names of the parameters in a route path.
+pub(super) fn param_keys(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix(':').or(segment.strip_prefix('*')))
+}
+
+pub(super) fn insert_url_params(
+    extensions: &mut Extensions,
+    path: &PathAndQuery,
+    params: Params<'_, '_>,
+    keys: &HashSet<Arc<str>>,
+) {
+    let matched = path.path();
     let params = params
         .iter()
         .filter(|(key, _)| !key.starts_with(super::NEST_TAIL_PARAM))
         .filter(|(key, _)| !key.starts_with(super::FALLBACK_PARAM))
-        .map(|(k, v)| {
-            if let Some(decoded) = PercentDecodedStr::new(v) {
-                Ok((Arc::from(k), decoded))
-            } else {
-                Err(Arc::from(k))
+        .map(|(key, value)| {
+            // matchit returns the values as slices of the path it matched against
+            let start = (value.as_ptr() as usize)
+                .checked_sub(matched.as_ptr() as usize)
+                .filter(|start| start + value.len() <= matched.len())
+                .expect("path parameter isn't part of the path. This is a bug in axum");
+            UrlParam {
+                key: keys.get(key).cloned().unwrap_or_else(|| Arc::from(key)),
+                path: path.clone(),
+                value: start..start + value.len(),
             }
-        })
-        .collect::<Result<Vec<_>, _>>();
+        });
 
-    match (current_params, params) {
-        (Some(UrlParams::InvalidUtf8InPathParam { .. }), _) => {
-            unreachable!("we check for this state earlier in this method")
-        }
-        (_, Err(invalid_key)) => {
-            extensions.insert(UrlParams::InvalidUtf8InPathParam { key: invalid_key });
-        }
-        (Some(UrlParams::Params(current)), Ok(params)) => {
-            current.extend(params);
-        }
-        (None, Ok(params)) => {
-            extensions.insert(UrlParams::Params(params));
+    match extensions.get_mut::<UrlParams>() {
+        Some(UrlParams(current)) => current.extend(params),
+        None => {
+            extensions.insert(UrlParams(params.collect()));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url_params(path: &'static str, keys: &HashSet<Arc<str>>) -> UrlParams {
+        let mut router = matchit::Router::new();
+        router.insert("/:a/:b", ()).unwrap();
+        let path = PathAndQuery::from_static(path);
+        let match_ = router.at(path.path()).unwrap();
+
+        let mut extensions = Extensions::new();
+        insert_url_params(&mut extensions, &path, match_.params, keys);
+        extensions.remove::<UrlParams>().unwrap()
+    }
+
+    #[test]
+    fn only_escaped_values_are_decoded_into_new_strings() {
+        let params = url_params("/plain/with%20space?a=b", &HashSet::new());
+
+        let decoded = params.decoded().unwrap();
+        assert!(matches!(decoded[0], ("a", Cow::Borrowed("plain"))));
+        assert!(matches!(&decoded[1], ("b", Cow::Owned(value)) if value == "with space"));
+
+        let raw = params.raw();
+        assert!(matches!(raw[1], ("b", Cow::Borrowed("with%20space"))));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let params = url_params("/plain/%FF", &HashSet::new());
+        assert_eq!(&**params.decoded().unwrap_err(), "b");
+    }
+
+    #[test]
+    fn keys_are_shared() {
+        let keys = HashSet::from([Arc::from("a")]);
+        let params = url_params("/1/2", &keys);
+
+        let mut iter = params.iter();
+        assert!(Arc::ptr_eq(
+            iter.next().unwrap().key(),
+            keys.get("a").unwrap()
+        ));
+        assert_eq!(&**iter.next().unwrap().key(), "b");
+    }
+}
@@ -8,25 +8,62 @@ use std::{
     marker::PhantomData,
     net::SocketAddr,
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
     time::Duration,
 };
 
 use axum_core::{body::Body, extract::Request, response::Response};
-use futures_util::{pin_mut, FutureExt};
+use futures_util::{
+    future::BoxFuture,
+    pin_mut,
+    stream::{BoxStream, StreamExt},
+    FutureExt,
+};
+use http::{header, HeaderValue};
 use hyper::body::Incoming;
-use hyper_util::rt::{TokioExecutor, TokioIo};
-#[cfg(any(feature = "http1", feature = "http2"))]
-use hyper_util::server::conn::auto::Builder;
+use hyper_util::rt::TokioIo;
 use pin_project_lite::pin_project;
 use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
     net::{TcpListener, TcpStream},
-    sync::watch,
+    sync::{mpsc, watch, Semaphore},
 };
 use tower::util::{Oneshot, ServiceExt};
 use tower_service::Service;
 
+#[cfg(feature = "acme")]
+pub mod acme;
+#[cfg(all(unix, feature = "handoff"))]
+pub mod handoff;
+#[cfg(feature = "h3")]
+mod http3;
+mod http_config;
+mod multi;
+mod proxy_protocol;
+#[cfg(feature = "tls-rustls")]
+mod sni;
+#[cfg(all(unix, feature = "systemd"))]
+pub mod systemd;
+#[cfg(feature = "tls-rustls")]
+mod tls;
+#[cfg(unix)]
+mod unix;
+
+#[cfg(feature = "h3")]
+pub use self::http3::ServeH3;
+pub use self::http_config::HttpConfig;
+pub use self::multi::{serve_multi, ServeMulti};
+#[cfg(feature = "tls-rustls")]
+pub use self::sni::SniRouter;
+#[cfg(feature = "tls-rustls")]
+pub use self::tls::{RustlsConfig, TlsConnectInfo, TlsInfo};
+#[cfg(unix)]
+pub use self::unix::{serve_uds, ServeUds, UdsConnectInfo, UdsIncomingStream};
+
 /// Serve the service with the supplied listener.
 ///
 /// This method of running a service is intentionally simple and doesn't support any configuration.
@@ -101,18 +138,104 @@ where
     Serve {
         tcp_listener,
         make_service,
-        tcp_nodelay: None,
+        config: ServeConfig::default(),
         _marker: PhantomData,
     }
 }
 
+/// Serve the service with the supplied listener over TLS, using [rustls].
+///
+/// This works like [`serve`], including graceful shutdown and the other options on [`Serve`],
+/// except that every connection starts with a TLS handshake. The certificate can be replaced
+/// while the server is running with [`RustlsConfig::reload_from_pem_file`] and similar methods.
+///
+/// Use [`TlsConnectInfo`] with `into_make_service_with_connect_info` to access the TLS session
+/// of a connection from handlers.
+///
+/// The TLS handshake must be done within 10 seconds. At most 1024 handshakes are done at once,
+/// and no more connections are accepted until one of them is done.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{Router, routing::get, serve::RustlsConfig};
+///
+/// # async {
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// let config = RustlsConfig::from_pem_file("cert.pem", "key.pem").await.unwrap();
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// axum::serve_tls(listener, config, router).await.unwrap();
+/// # };
+/// ```
+///
+/// [rustls]: https://crates.io/crates/rustls
+#[cfg(all(feature = "tls-rustls", any(feature = "http1", feature = "http2")))]
+pub fn serve_tls<M, S>(
+    tcp_listener: TcpListener,
+    config: RustlsConfig,
+    make_service: M,
+) -> Serve<M, S>
+where
+    M: for<'a> Service<IncomingStream<'a>, Error = Infallible, Response = S>,
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    let mut serve = serve(tcp_listener, make_service);
+    serve.config.tls = Some(config);
+    serve
+}
+
+/// Serve the service over HTTP/3, using [quinn] and [h3].
+///
+/// Unlike [`serve`] this takes the service itself, such as a [`Router`], rather than a service
+/// that makes services. The endpoint's TLS configuration must include `h3` in its ALPN
+/// protocols.
+///
+/// Clients only try HTTP/3 after learning that the server supports it, usually from the `Alt-Svc`
+/// header of a response sent over TCP. Use [`Serve::advertise_h3`] to add that header.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{Router, routing::get};
+/// use std::net::SocketAddr;
+///
+/// # async {
+/// # let quinn_server_config: quinn::ServerConfig = todo!();
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// let addr: SocketAddr = "0.0.0.0:443".parse().unwrap();
+/// let endpoint = quinn::Endpoint::server(quinn_server_config, addr).unwrap();
+/// let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+///
+/// tokio::try_join!(
+///     axum::serve_h3(endpoint, router.clone()),
+///     axum::serve(listener, router).advertise_h3(443),
+/// )
+/// .unwrap();
+/// # };
+/// ```
+///
+/// [quinn]: https://crates.io/crates/quinn
+/// [h3]: https://crates.io/crates/h3
+/// [`Router`]: crate::Router
+#[cfg(feature = "h3")]
+pub fn serve_h3<S>(endpoint: quinn::Endpoint, service: S) -> ServeH3<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    ServeH3::new(endpoint, service)
+}
+
 /// Future returned by [`serve`].
 #[cfg(all(feature = "tokio", any(feature = "http1", feature = "http2")))]
 #[must_use = "futures must be awaited or polled"]
 pub struct Serve<M, S> {
     tcp_listener: TcpListener,
     make_service: M,
-    tcp_nodelay: Option<bool>,
+    config: ServeConfig,
     _marker: PhantomData<S>,
 }
 
@@ -147,7 +270,7 @@ impl<M, S> Serve<M, S> {
             tcp_listener: self.tcp_listener,
             make_service: self.make_service,
             signal,
-            tcp_nodelay: self.tcp_nodelay,
+            config: self.config,
             _marker: PhantomData,
         }
     }
@@ -170,11 +293,115 @@ impl<M, S> Serve<M, S> {
     ///     .unwrap();
     /// # };
     /// ```
-    pub fn tcp_nodelay(self, nodelay: bool) -> Self {
-        Self {
-            tcp_nodelay: Some(nodelay),
-            ..self
-        }
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.config.tcp_nodelay = Some(nodelay);
+        self
+    }
+
+    /// Expect every accepted connection to start with a [PROXY protocol] header.
+    ///
+    /// Use this when running behind a TCP load balancer, such as HAProxy or AWS NLB, that
+    /// prepends a PROXY protocol header to every connection. Both version 1 and 2 of the protocol
+    /// are supported. The client address from the header is used as the remote address, so
+    /// [`ConnectInfo<SocketAddr>`] reflects the real client rather than the load balancer.
+    ///
+    /// Connections without a valid header are closed. The header must be sent within 10 seconds.
+    /// At most 1024 headers are read at once, and no more connections are accepted until one of
+    /// them is done.
+    ///
+    /// Only enable this if all connections come from a trusted proxy, since clients can otherwise
+    /// claim any address.
+    ///
+    /// # Example
+    /// ```
+    /// use axum::{Router, routing::get};
+    ///
+    /// # async {
+    /// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+    ///
+    /// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    /// axum::serve(listener, router)
+    ///     .proxy_protocol(true)
+    ///     .await
+    ///     .unwrap();
+    /// # };
+    /// ```
+    ///
+    /// [PROXY protocol]: https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+    /// [`ConnectInfo<SocketAddr>`]: crate::extract::ConnectInfo
+    pub fn proxy_protocol(mut self, proxy_protocol: bool) -> Self {
+        self.config.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// Advertise that HTTP/3 is available on `port` by adding an `Alt-Svc` header to every
+    /// response.
+    ///
+    /// See [`serve_h3`] for serving HTTP/3.
+    #[cfg(feature = "h3")]
+    pub fn advertise_h3(mut self, port: u16) -> Self {
+        self.config.alt_svc = Some(http3::alt_svc(port));
+        self
+    }
+
+    /// Returns a handle to the number of requests currently being handled by this server.
+    ///
+    /// See [`InFlightRequests`] for more details.
+    pub fn in_flight_requests(&self) -> InFlightRequests {
+        self.config.in_flight.clone()
+    }
+
+    /// Set the HTTP/1 and HTTP/2 options used for every accepted connection.
+    ///
+    /// See [`HttpConfig`] for the available options.
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.config.http_config = http_config;
+        self
+    }
+
+    /// Decide whether to serve each accepted connection before any HTTP is parsed.
+    ///
+    /// `filter` is called with the remote address of every connection right after it's
+    /// accepted. Returning `None` closes the connection. Otherwise the returned value is kept
+    /// until the connection closes, so dropping it can be used to count open connections, for
+    /// example to limit connections per IP.
+    ///
+    /// With [`Serve::proxy_protocol`] or TLS enabled, `filter` is called a second time once the
+    /// PROXY protocol header and the TLS ClientHello have been read, with the client address from
+    /// the header and the server name the client sent with SNI, but before the TLS handshake.
+    /// The value returned by that call replaces the first one. See
+    /// [`AcceptedConnection::is_final`].
+    ///
+    /// The filter shouldn't block since it runs on the tasks accepting connections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::get};
+    /// use std::{collections::HashSet, net::IpAddr};
+    ///
+    /// # async {
+    /// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+    ///
+    /// let denylist: HashSet<IpAddr> = ["192.0.2.1".parse().unwrap()].into();
+    ///
+    /// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    /// axum::serve(listener, router)
+    ///     .accept_filter(move |conn| {
+    ///         let ip = conn.remote_addr()?.ip();
+    ///         (!denylist.contains(&ip)).then_some(())
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// # };
+    /// ```
+    pub fn accept_filter<A, G>(mut self, filter: A) -> Self
+    where
+        A: Fn(&AcceptedConnection<'_>) -> Option<G> + Send + Sync + 'static,
+        G: Send + 'static,
+    {
+        self.config.accept_filter = Some(accept_filter(filter));
+        self
     }
 }
 
@@ -187,14 +414,14 @@ where
         let Self {
             tcp_listener,
             make_service,
-            tcp_nodelay,
+            config,
             _marker: _,
         } = self;
 
         f.debug_struct("Serve")
             .field("tcp_listener", tcp_listener)
             .field("make_service", make_service)
-            .field("tcp_nodelay", tcp_nodelay)
+            .field("config", config)
             .finish()
     }
 }
@@ -211,61 +438,22 @@ where
     type IntoFuture = private::ServeFuture;
 
     fn into_future(self) -> Self::IntoFuture {
-        private::ServeFuture(Box::pin(async move {
-            let Self {
-                tcp_listener,
-                mut make_service,
-                tcp_nodelay,
-                _marker: _,
-            } = self;
-
-            loop {
-                let (tcp_stream, remote_addr) = match tcp_accept(&tcp_listener).await {
-                    Some(conn) => conn,
-                    None => continue,
-                };
-
-                if let Some(nodelay) = tcp_nodelay {
-                    if let Err(err) = tcp_stream.set_nodelay(nodelay) {
-                        trace!("failed to set TCP_NODELAY on incoming connection: {err:#}");
-                    }
-                }
-
-                let tcp_stream = TokioIo::new(tcp_stream);
-
-                poll_fn(|cx| make_service.poll_ready(cx))
-                    .await
-                    .unwrap_or_else(|err| match err {});
-
-                let tower_service = make_service
-                    .call(IncomingStream {
-                        tcp_stream: &tcp_stream,
-                        remote_addr,
-                    })
-                    .await
-                    .unwrap_or_else(|err| match err {});
-
-                let hyper_service = TowerToHyperService {
-                    service: tower_service,
-                };
+        let Self {
+            tcp_listener,
+            make_service,
+            config,
+            _marker: _,
+        } = self;
 
-                tokio::spawn(async move {
-                    match Builder::new(TokioExecutor::new())
-                        // upgrades needed for websockets
-                        .serve_connection_with_upgrades(tcp_stream, hyper_service)
-                        .await
-                    {
-                        Ok(()) => {}
-                        Err(_err) => {
-                            // This error only appears when the client doesn't send a request and
-                            // terminate the connection.
-                            //
-                            // If client sends one request then terminate connection whenever, it doesn't
-                            // appear.
-                        }
-                    }
-                });
-            }
+        private::ServeFuture(Box::pin(async move {
+            serve_connections(
+                tcp_incoming(tcp_listener),
+                TcpMakeService(make_service),
+                config,
+                None,
+            )
+            .await;
+            Ok(())
         }))
     }
 }
@@ -277,7 +465,7 @@ pub struct WithGracefulShutdown<M, S, F> {
     tcp_listener: TcpListener,
     make_service: M,
     signal: F,
-    tcp_nodelay: Option<bool>,
+    config: ServeConfig,
     _marker: PhantomData<S>,
 }
 
@@ -305,11 +493,134 @@ impl<M, S, F> WithGracefulShutdown<M, S, F> {
     ///     // ...
     /// }
     /// ```
-    pub fn tcp_nodelay(self, nodelay: bool) -> Self {
-        Self {
-            tcp_nodelay: Some(nodelay),
-            ..self
-        }
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.config.tcp_nodelay = Some(nodelay);
+        self
+    }
+
+    /// Expect every accepted connection to start with a PROXY protocol header.
+    ///
+    /// See [`Serve::proxy_protocol`] for more details.
+    pub fn proxy_protocol(mut self, proxy_protocol: bool) -> Self {
+        self.config.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// Advertise that HTTP/3 is available on `port` by adding an `Alt-Svc` header to every
+    /// response.
+    ///
+    /// See [`Serve::advertise_h3`] for more details.
+    #[cfg(feature = "h3")]
+    pub fn advertise_h3(mut self, port: u16) -> Self {
+        self.config.alt_svc = Some(http3::alt_svc(port));
+        self
+    }
+
+    /// Returns a handle to the number of requests currently being handled by this server.
+    ///
+    /// See [`InFlightRequests`] for more details.
+    pub fn in_flight_requests(&self) -> InFlightRequests {
+        self.config.in_flight.clone()
+    }
+
+    /// Set the HTTP/1 and HTTP/2 options used for every accepted connection.
+    ///
+    /// See [`Serve::http_config`] for more details.
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.config.http_config = http_config;
+        self
+    }
+
+    /// Decide whether to serve each accepted connection before any HTTP is parsed.
+    ///
+    /// See [`Serve::accept_filter`] for more details.
+    pub fn accept_filter<A, G>(mut self, filter: A) -> Self
+    where
+        A: Fn(&AcceptedConnection<'_>) -> Option<G> + Send + Sync + 'static,
+        G: Send + 'static,
+    {
+        self.config.accept_filter = Some(accept_filter(filter));
+        self
+    }
+
+    /// Limit how long to wait for open connections to finish after the shutdown signal.
+    ///
+    /// Connections that are still open when the timeout elapses are closed, even if they are in
+    /// the middle of a request. By default the server waits for all connections to finish.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::get};
+    /// use std::time::Duration;
+    ///
+    /// # async {
+    /// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+    ///
+    /// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    /// axum::serve(listener, router)
+    ///     .with_graceful_shutdown(shutdown_signal())
+    ///     .drain_timeout(Duration::from_secs(30))
+    ///     .await
+    ///     .unwrap();
+    /// # };
+    ///
+    /// async fn shutdown_signal() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.config.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Call `f` when the shutdown signal completes, before waiting for open connections to
+    /// finish.
+    ///
+    /// This can be used to make health checks fail so load balancers stop sending new traffic
+    /// while the server drains.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::{Router, routing::get, http::StatusCode};
+    /// use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+    ///
+    /// # async {
+    /// let draining = Arc::new(AtomicBool::new(false));
+    ///
+    /// let router = Router::new().route(
+    ///     "/health",
+    ///     get({
+    ///         let draining = Arc::clone(&draining);
+    ///         move || async move {
+    ///             if draining.load(Ordering::Relaxed) {
+    ///                 StatusCode::SERVICE_UNAVAILABLE
+    ///             } else {
+    ///                 StatusCode::OK
+    ///             }
+    ///         }
+    ///     }),
+    /// );
+    ///
+    /// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    /// axum::serve(listener, router)
+    ///     .with_graceful_shutdown(shutdown_signal())
+    ///     .on_drain_start(move || draining.store(true, Ordering::Relaxed))
+    ///     .await
+    ///     .unwrap();
+    /// # };
+    ///
+    /// async fn shutdown_signal() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn on_drain_start<C>(mut self, f: C) -> Self
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        self.config.on_drain_start = Some(Box::new(f));
+        self
     }
 }
 
@@ -325,7 +636,7 @@ where
             tcp_listener,
             make_service,
             signal,
-            tcp_nodelay,
+            config,
             _marker: _,
         } = self;
 
@@ -333,7 +644,7 @@ where
             .field("tcp_listener", tcp_listener)
             .field("make_service", make_service)
             .field("signal", signal)
-            .field("tcp_nodelay", tcp_nodelay)
+            .field("config", config)
             .finish()
     }
 }
@@ -353,107 +664,728 @@ where
     fn into_future(self) -> Self::IntoFuture {
         let Self {
             tcp_listener,
-            mut make_service,
+            make_service,
             signal,
-            tcp_nodelay,
+            config,
             _marker: _,
         } = self;
 
-        let (signal_tx, signal_rx) = watch::channel(());
-        let signal_tx = Arc::new(signal_tx);
+        private::ServeFuture(Box::pin(async move {
+            serve_connections(
+                tcp_incoming(tcp_listener),
+                TcpMakeService(make_service),
+                config,
+                Some(Box::pin(signal)),
+            )
+            .await;
+            Ok(())
+        }))
+    }
+}
+
+/// Options for accepting and serving connections, shared by every way of serving.
+#[derive(Default)]
+struct ServeConfig {
+    tcp_nodelay: Option<bool>,
+    proxy_protocol: bool,
+    alt_svc: Option<HeaderValue>,
+    in_flight: InFlightRequests,
+    http_config: HttpConfig,
+    accept_filter: Option<AcceptFilter>,
+    drain_timeout: Option<Duration>,
+    on_drain_start: Option<Box<dyn FnOnce() + Send>>,
+    #[cfg(feature = "tls-rustls")]
+    tls: Option<RustlsConfig>,
+}
+
+impl Debug for ServeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            tcp_nodelay,
+            proxy_protocol,
+            alt_svc,
+            in_flight,
+            http_config,
+            accept_filter,
+            drain_timeout,
+            on_drain_start,
+            #[cfg(feature = "tls-rustls")]
+            tls,
+        } = self;
+
+        let mut f = f.debug_struct("ServeConfig");
+        f.field("tcp_nodelay", tcp_nodelay)
+            .field("proxy_protocol", proxy_protocol)
+            .field("alt_svc", alt_svc)
+            .field("in_flight", in_flight)
+            .field("http_config", http_config)
+            .field("accept_filter", &accept_filter.is_some())
+            .field("drain_timeout", drain_timeout)
+            .field("on_drain_start", &on_drain_start.is_some());
+        #[cfg(feature = "tls-rustls")]
+        f.field("tls", tls);
+        f.finish()
+    }
+}
+
+/// Makes the service that serves an accepted connection.
+///
+/// This is what differs between [`serve`], [`serve_uds`], and [`serve_multi`]. Everything else
+/// is done by [`serve_connections`].
+trait MakeConnectionService: Send + 'static {
+    type Service: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()>;
+
+    fn make_service<'a>(
+        &'a mut self,
+        stream: &'a TokioIo<Stream>,
+        addrs: &'a Addrs,
+    ) -> BoxFuture<'a, Self::Service>;
+}
+
+/// Calls a service that makes services with an [`IncomingStream`].
+struct TcpMakeService<M>(M);
+
+impl<M, S> MakeConnectionService for TcpMakeService<M>
+where
+    M: for<'a> Service<IncomingStream<'a>, Error = Infallible, Response = S> + Send + 'static,
+    for<'a> <M as Service<IncomingStream<'a>>>::Future: Send,
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+{
+    type Service = S;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.0
+            .poll_ready(cx)
+            .map(|result| result.unwrap_or_else(|err| match err {}))
+    }
+
+    fn make_service<'a>(
+        &'a mut self,
+        stream: &'a TokioIo<Stream>,
+        addrs: &'a Addrs,
+    ) -> BoxFuture<'a, S> {
+        let incoming = IncomingStream {
+            stream,
+            // TCP connections always have one
+            remote_addr: addrs
+                .remote_addr()
+                .expect("TCP connection without a remote address"),
+        };
+        Box::pin(
+            self.0
+                .call(incoming)
+                .map(|result| result.unwrap_or_else(|err| match err {})),
+        )
+    }
+}
+
+/// Accept connections from `incoming` and serve them until `signal` completes, then wait for
+/// the open connections to finish.
+///
+/// This is the accept loop of every way of serving, so all of them support the same options.
+async fn serve_connections<M>(
+    incoming: Connections,
+    mut make_service: M,
+    config: ServeConfig,
+    signal: Option<BoxFuture<'static, ()>>,
+) where
+    M: MakeConnectionService,
+    <M::Service as Service<Request>>::Future: Send,
+{
+    let ServeConfig {
+        tcp_nodelay,
+        proxy_protocol,
+        alt_svc,
+        in_flight,
+        http_config,
+        accept_filter,
+        drain_timeout,
+        on_drain_start,
+        #[cfg(feature = "tls-rustls")]
+        tls,
+    } = config;
+
+    let (signal_tx, signal_rx) = watch::channel(());
+    let signal_tx = Arc::new(signal_tx);
+    // kept until the server stops if there is no signal
+    let _signal_rx = match signal {
+        Some(signal) => {
+            tokio::spawn(async move {
+                signal.await;
+                trace!("received graceful shutdown signal. Telling tasks to shutdown");
+                drop(signal_rx);
+            });
+            None
+        }
+        None => Some(signal_rx),
+    };
+
+    let builder = http_config.builder();
+    let (close_tx, close_rx) = watch::channel(());
+    // dropped to close the remaining connections when the drain timeout elapses
+    let (abort_tx, abort_rx) = watch::channel(());
+
+    let mut acceptor = Acceptor::new(
+        incoming,
+        tcp_nodelay,
+        accept_filter,
+        Handshake {
+            proxy_protocol,
+            #[cfg(feature = "tls-rustls")]
+            tls,
+        },
+    );
+
+    loop {
+        let conn = tokio::select! {
+            conn = acceptor.accept() => conn,
+            _ = signal_tx.closed() => {
+                trace!("signal received, not accepting new connections");
+                break;
+            }
+        };
+        let Some(Accepted {
+            stream,
+            addrs,
+            accept_guard,
+        }) = conn
+        else {
+            continue;
+        };
+
+        let stream = TokioIo::new(stream);
+
+        trace!("connection {:?} accepted", addrs.peer);
+
+        poll_fn(|cx| make_service.poll_ready(cx)).await;
+        let tower_service = make_service.make_service(&stream, &addrs).await;
+
+        let hyper_service = TowerToHyperService {
+            service: tower_service,
+            alt_svc: alt_svc.clone(),
+            in_flight: in_flight.clone(),
+        };
+
+        let signal_tx = Arc::clone(&signal_tx);
+
+        let close_rx = close_rx.clone();
+        let mut abort_rx = abort_rx.clone();
+        let builder = builder.clone();
+
         tokio::spawn(async move {
-            signal.await;
-            trace!("received graceful shutdown signal. Telling tasks to shutdown");
-            drop(signal_rx);
-        });
+            // held until the connection is closed
+            let _accept_guard = accept_guard;
 
-        let (close_tx, close_rx) = watch::channel(());
+            let conn = builder.serve_connection_with_upgrades(stream, hyper_service);
+            pin_mut!(conn);
+
+            let signal_closed = signal_tx.closed().fuse();
+            pin_mut!(signal_closed);
 
-        private::ServeFuture(Box::pin(async move {
             loop {
-                let (tcp_stream, remote_addr) = tokio::select! {
-                    conn = tcp_accept(&tcp_listener) => {
-                        match conn {
-                            Some(conn) => conn,
-                            None => continue,
+                tokio::select! {
+                    result = conn.as_mut() => {
+                        if let Err(_err) = result {
+                            trace!("failed to serve connection: {_err:#}");
                         }
-                    }
-                    _ = signal_tx.closed() => {
-                        trace!("signal received, not accepting new connections");
                         break;
                     }
-                };
-
-                if let Some(nodelay) = tcp_nodelay {
-                    if let Err(err) = tcp_stream.set_nodelay(nodelay) {
-                        trace!("failed to set TCP_NODELAY on incoming connection: {err:#}");
+                    _ = &mut signal_closed => {
+                        trace!("signal received in task, starting graceful shutdown");
+                        conn.as_mut().graceful_shutdown();
+                    }
+                    _ = abort_rx.changed() => {
+                        trace!("drain timeout elapsed, closing connection {:?}", addrs.peer);
+                        break;
                     }
                 }
+            }
 
-                let tcp_stream = TokioIo::new(tcp_stream);
+            trace!("connection {:?} closed", addrs.peer);
 
-                trace!("connection {remote_addr} accepted");
+            drop(close_rx);
+        });
+    }
 
-                poll_fn(|cx| make_service.poll_ready(cx))
-                    .await
-                    .unwrap_or_else(|err| match err {});
+    drop(close_rx);
+    drop(abort_rx);
+    drop(acceptor);
 
-                let tower_service = make_service
-                    .call(IncomingStream {
-                        tcp_stream: &tcp_stream,
-                        remote_addr,
-                    })
-                    .await
-                    .unwrap_or_else(|err| match err {});
+    if let Some(on_drain_start) = on_drain_start {
+        on_drain_start();
+    }
 
-                let hyper_service = TowerToHyperService {
-                    service: tower_service,
-                };
+    trace!(
+        "waiting for {} task(s) to finish",
+        close_tx.receiver_count()
+    );
+    match drain_timeout {
+        Some(timeout) => {
+            if tokio::time::timeout(timeout, close_tx.closed())
+                .await
+                .is_err()
+            {
+                trace!(
+                    "drain timeout elapsed, closing {} connection(s)",
+                    close_tx.receiver_count()
+                );
+                drop(abort_tx);
+                close_tx.closed().await;
+            }
+        }
+        None => close_tx.closed().await,
+    }
+}
+
+/// How long a client has to send the PROXY protocol header.
+const PROXY_PROTOCOL_HEADER_TIMEOUT: Duration = Duration::from_secs(10);
 
-                let signal_tx = Arc::clone(&signal_tx);
+/// How long a client has to complete the TLS handshake.
+#[cfg(feature = "tls-rustls")]
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
-                let close_rx = close_rx.clone();
+/// How many PROXY protocol headers and TLS handshakes are read at once. No more connections are
+/// accepted until one of them is done.
+const MAX_CONCURRENT_HANDSHAKES: usize = 1024;
+
+/// Connections accepted by one or more listeners.
+///
+/// `None` is produced when accepting a connection failed and should be retried.
+type Connections = BoxStream<'static, Option<(Stream, Peer)>>;
+
+fn tcp_incoming(tcp_listener: TcpListener) -> Connections {
+    Box::pin(futures_util::stream::unfold(
+        tcp_listener,
+        |tcp_listener| async move {
+            let conn = tcp_accept(&tcp_listener)
+                .await
+                .map(|(tcp_stream, addr)| (Stream::Tcp(tcp_stream), Peer::Tcp(addr)));
+            Some((conn, tcp_listener))
+        },
+    ))
+}
+
+/// The address of the socket on the other end of a connection.
+#[derive(Debug)]
+enum Peer {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(tokio::net::unix::SocketAddr),
+}
+
+/// The addresses of an accepted connection.
+#[derive(Debug)]
+struct Addrs {
+    peer: Peer,
+    /// The client address from the PROXY protocol header.
+    proxied: Option<SocketAddr>,
+}
 
+impl Addrs {
+    /// The address of the client, if it's known.
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.proxied.or(match self.peer {
+            Peer::Tcp(addr) => Some(addr),
+            #[cfg(unix)]
+            Peer::Unix(_) => None,
+        })
+    }
+}
+
+/// A connection that's ready to be served.
+struct Accepted {
+    stream: Stream,
+    addrs: Addrs,
+    accept_guard: Option<Box<dyn Send>>,
+}
+
+/// Accepts connections, reading PROXY protocol headers, doing TLS handshakes, and calling the
+/// accept filter if enabled.
+struct Acceptor {
+    incoming: Connections,
+    tcp_nodelay: Option<bool>,
+    accept_filter: Option<AcceptFilter>,
+    handshake: Handshake,
+    handshakes: Option<Handshakes>,
+}
+
+/// Handshakes running in separate tasks.
+struct Handshakes {
+    permits: Arc<Semaphore>,
+    tx: mpsc::Sender<Accepted>,
+    rx: mpsc::Receiver<Accepted>,
+}
+
+impl Acceptor {
+    fn new(
+        incoming: Connections,
+        tcp_nodelay: Option<bool>,
+        accept_filter: Option<AcceptFilter>,
+        handshake: Handshake,
+    ) -> Self {
+        let handshakes = handshake.is_needed().then(|| {
+            let (tx, rx) = mpsc::channel(1024);
+            Handshakes {
+                permits: Arc::new(Semaphore::new(MAX_CONCURRENT_HANDSHAKES)),
+                tx,
+                rx,
+            }
+        });
+        Self {
+            incoming,
+            tcp_nodelay,
+            accept_filter,
+            handshake,
+            handshakes,
+        }
+    }
+
+    /// Accept the next connection, or return `None` if there isn't one yet.
+    async fn accept(&mut self) -> Option<Accepted> {
+        let Self {
+            incoming,
+            tcp_nodelay,
+            accept_filter,
+            handshake,
+            handshakes,
+        } = self;
+
+        let Some(handshakes) = handshakes else {
+            let (stream, peer) = next_raw(incoming, *tcp_nodelay).await?;
+            let addrs = Addrs {
+                peer,
+                proxied: None,
+            };
+            let accept_guard = filter_connection(accept_filter.as_ref(), &addrs, None, true)?;
+            return Some(Accepted {
+                stream,
+                addrs,
+                accept_guard,
+            });
+        };
+
+        // handshakes are done in separate tasks so slow clients can't block accepting new
+        // connections
+        tokio::select! {
+            conn = async {
+                // stop accepting while too many handshakes are running
+                let permit = Arc::clone(&handshakes.permits)
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed");
+                let conn = next_raw(incoming, *tcp_nodelay).await?;
+                Some((conn, permit))
+            } => {
+                let ((stream, peer), permit) = conn?;
+                let addrs = Addrs {
+                    peer,
+                    proxied: None,
+                };
+                // rejected before the handshake so they don't cost one
+                let accept_guard =
+                    filter_connection(accept_filter.as_ref(), &addrs, None, false)?;
+
+                let handshake = handshake.clone();
+                let accept_filter = accept_filter.clone();
+                let tx = handshakes.tx.clone();
                 tokio::spawn(async move {
-                    let builder = Builder::new(TokioExecutor::new());
-                    let conn = builder.serve_connection_with_upgrades(tcp_stream, hyper_service);
-                    pin_mut!(conn);
-
-                    let signal_closed = signal_tx.closed().fuse();
-                    pin_mut!(signal_closed);
-
-                    loop {
-                        tokio::select! {
-                            result = conn.as_mut() => {
-                                if let Err(_err) = result {
-                                    trace!("failed to serve connection: {_err:#}");
-                                }
-                                break;
-                            }
-                            _ = &mut signal_closed => {
-                                trace!("signal received in task, starting graceful shutdown");
-                                conn.as_mut().graceful_shutdown();
-                            }
-                        }
+                    let conn = handshake
+                        .run(stream, addrs, accept_filter.as_ref(), accept_guard)
+                        .await;
+                    drop(permit);
+                    if let Some(conn) = conn {
+                        let _ = tx.send(conn).await;
                     }
+                });
+                None
+            }
+            conn = handshakes.rx.recv() => conn,
+        }
+    }
+}
 
-                    trace!("connection {remote_addr} closed");
+async fn next_raw(incoming: &mut Connections, tcp_nodelay: Option<bool>) -> Option<(Stream, Peer)> {
+    let Some(conn) = incoming.next().await else {
+        // there are no listeners
+        return std::future::pending().await;
+    };
+    let (stream, peer) = conn?;
 
-                    drop(close_rx);
-                });
+    if let (Some(nodelay), Stream::Tcp(tcp_stream)) = (tcp_nodelay, &stream) {
+        if let Err(_err) = tcp_stream.set_nodelay(nodelay) {
+            trace!("failed to set TCP_NODELAY on incoming connection: {_err:#}");
+        }
+    }
+
+    Some((stream, peer))
+}
+
+/// Call the accept filter, if any.
+///
+/// Returns `None` if the connection is rejected, otherwise the guard to keep until the
+/// connection closes.
+#[allow(clippy::option_option)]
+fn filter_connection(
+    accept_filter: Option<&AcceptFilter>,
+    addrs: &Addrs,
+    server_name: Option<&str>,
+    is_final: bool,
+) -> Option<Option<Box<dyn Send>>> {
+    let Some(accept_filter) = accept_filter else {
+        return Some(None);
+    };
+    let guard = accept_filter(&AcceptedConnection {
+        remote_addr: addrs.remote_addr(),
+        server_name,
+        is_final,
+    });
+    if guard.is_none() {
+        trace!("connection {:?} rejected", addrs.peer);
+    }
+    guard.map(Some)
+}
+
+/// What happens after accepting a connection and before serving it.
+#[derive(Clone)]
+struct Handshake {
+    proxy_protocol: bool,
+    #[cfg(feature = "tls-rustls")]
+    tls: Option<RustlsConfig>,
+}
+
+impl Handshake {
+    fn is_needed(&self) -> bool {
+        #[cfg(feature = "tls-rustls")]
+        if self.tls.is_some() {
+            return true;
+        }
+        self.proxy_protocol
+    }
+
+    async fn run(
+        self,
+        mut stream: Stream,
+        mut addrs: Addrs,
+        accept_filter: Option<&AcceptFilter>,
+        accept_guard: Option<Box<dyn Send>>,
+    ) -> Option<Accepted> {
+        if self.proxy_protocol {
+            let header = tokio::time::timeout(
+                PROXY_PROTOCOL_HEADER_TIMEOUT,
+                proxy_protocol::read_header(&mut stream),
+            )
+            .await;
+            match header {
+                Ok(Ok(addr)) => addrs.proxied = addr,
+                Ok(Err(_err)) => {
+                    trace!(
+                        "invalid PROXY protocol header from {:?}: {_err:#}",
+                        addrs.peer
+                    );
+                    return None;
+                }
+                Err(_) => {
+                    trace!(
+                        "timed out reading PROXY protocol header from {:?}",
+                        addrs.peer
+                    );
+                    return None;
+                }
             }
+        }
 
-            drop(close_rx);
-            drop(tcp_listener);
+        #[cfg(feature = "tls-rustls")]
+        let stream = match (stream, &self.tls) {
+            (Stream::Tcp(tcp_stream), Some(tls)) => {
+                return tls_handshake(tls, tcp_stream, addrs, accept_filter, accept_guard).await;
+            }
+            (stream, _) => stream,
+        };
+
+        // replaces the guard from before the PROXY protocol header was read
+        let new_guard = filter_connection(accept_filter, &addrs, None, true)?;
+        drop(accept_guard);
+        Some(Accepted {
+            stream,
+            addrs,
+            accept_guard: new_guard,
+        })
+    }
+}
 
-            trace!(
-                "waiting for {} task(s) to finish",
-                close_tx.receiver_count()
-            );
-            close_tx.closed().await;
+#[cfg(feature = "tls-rustls")]
+async fn tls_handshake(
+    tls: &RustlsConfig,
+    tcp_stream: TcpStream,
+    addrs: Addrs,
+    accept_filter: Option<&AcceptFilter>,
+    accept_guard: Option<Box<dyn Send>>,
+) -> Option<Accepted> {
+    let deadline = tokio::time::Instant::now() + TLS_HANDSHAKE_TIMEOUT;
+
+    // the ClientHello is read first so the filter can see the server name before the handshake
+    let acceptor = tokio_rustls::LazyConfigAcceptor::new(
+        tokio_rustls::rustls::server::Acceptor::default(),
+        tcp_stream,
+    );
+    let start = match tokio::time::timeout_at(deadline, acceptor).await {
+        Ok(Ok(start)) => start,
+        Ok(Err(_err)) => {
+            trace!("TLS handshake with {:?} failed: {_err:#}", addrs.peer);
+            return None;
+        }
+        Err(_) => {
+            trace!("timed out doing TLS handshake with {:?}", addrs.peer);
+            return None;
+        }
+    };
 
-            Ok(())
-        }))
+    let server_name = start.client_hello().server_name().map(str::to_owned);
+    // replaces the guard from before the ClientHello was read
+    let new_guard = filter_connection(accept_filter, &addrs, server_name.as_deref(), true)?;
+    drop(accept_guard);
+
+    let tls_stream = tokio::time::timeout_at(deadline, start.into_stream(tls.get_inner())).await;
+    match tls_stream {
+        // TLS-ALPN-01 challenges only need the handshake
+        #[cfg(feature = "acme")]
+        Ok(Ok(tls_stream))
+            if tls_stream.get_ref().1.alpn_protocol() == Some(acme::ACME_TLS_ALPN) =>
+        {
+            None
+        }
+        Ok(Ok(tls_stream)) => Some(Accepted {
+            stream: Stream::Tls(Box::new(tls_stream)),
+            addrs,
+            accept_guard: new_guard,
+        }),
+        Ok(Err(_err)) => {
+            trace!("TLS handshake with {:?} failed: {_err:#}", addrs.peer);
+            None
+        }
+        Err(_) => {
+            trace!("timed out doing TLS handshake with {:?}", addrs.peer);
+            None
+        }
+    }
+}
+
+/// An accepted connection.
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(feature = "tls-rustls")]
+    Tls(Box<tokio_rustls::server::TlsStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl Stream {
+    fn tcp_stream(&self) -> Option<&TcpStream> {
+        match self {
+            Self::Tcp(tcp_stream) => Some(tcp_stream),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => Some(tls_stream.get_ref().0),
+            #[cfg(unix)]
+            Self::Unix(_) => None,
+        }
+    }
+
+    #[cfg(feature = "tls-rustls")]
+    fn server_name(&self) -> Option<&str> {
+        match self {
+            Self::Tls(tls_stream) => tls_stream.get_ref().1.server_name(),
+            _ => None,
+        }
+    }
+}
+
+impl Debug for Stream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(tcp_stream) => f.debug_tuple("Tcp").field(tcp_stream).finish(),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => f.debug_tuple("Tls").field(tls_stream.get_ref().0).finish(),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => f.debug_tuple("Unix").field(unix_stream).finish(),
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_read(cx, buf),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => Pin::new(tls_stream).poll_read(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_write(cx, buf),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => Pin::new(tls_stream).poll_write(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_write_vectored(cx, bufs),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => Pin::new(tls_stream).poll_write_vectored(cx, bufs),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Tcp(tcp_stream) => tcp_stream.is_write_vectored(),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => tls_stream.is_write_vectored(),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => unix_stream.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_flush(cx),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => Pin::new(tls_stream).poll_flush(cx),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_shutdown(cx),
+            #[cfg(feature = "tls-rustls")]
+            Self::Tls(tls_stream) => Pin::new(tls_stream).poll_shutdown(cx),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_shutdown(cx),
+        }
     }
 }
 
@@ -518,14 +1450,16 @@ mod private {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 struct TowerToHyperService<S> {
     service: S,
+    alt_svc: Option<HeaderValue>,
+    in_flight: InFlightRequests,
 }
 
 impl<S> hyper::service::Service<Request<Incoming>> for TowerToHyperService<S>
 where
-    S: tower_service::Service<Request> + Clone,
+    S: tower_service::Service<Request, Response = Response> + Clone,
 {
     type Response = S::Response;
     type Error = S::Error;
@@ -535,6 +1469,8 @@ where
         let req = req.map(Body::new);
         TowerToHyperServiceFuture {
             future: self.service.clone().oneshot(req),
+            alt_svc: self.alt_svc.clone(),
+            _in_flight: self.in_flight.start(),
         }
     }
 }
@@ -546,18 +1482,81 @@ pin_project! {
     {
         #[pin]
         future: Oneshot<S, R>,
+        alt_svc: Option<HeaderValue>,
+        _in_flight: InFlightGuard,
     }
 }
 
 impl<S, R> Future for TowerToHyperServiceFuture<S, R>
 where
-    S: tower_service::Service<R>,
+    S: tower_service::Service<R, Response = Response>,
 {
     type Output = Result<S::Response, S::Error>;
 
     #[inline]
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.project().future.poll(cx)
+        let this = self.project();
+        let mut res = std::task::ready!(this.future.poll(cx));
+        if let (Ok(res), Some(alt_svc)) = (&mut res, this.alt_svc.take()) {
+            res.headers_mut().insert(header::ALT_SVC, alt_svc);
+        }
+        Poll::Ready(res)
+    }
+}
+
+/// A handle to the number of requests a server is currently handling.
+///
+/// A request counts as in flight from when it's received until its response has been produced.
+/// Streaming the response body isn't included.
+///
+/// Returned by [`Serve::in_flight_requests`] and [`WithGracefulShutdown::in_flight_requests`].
+/// The handle is cheap to clone and stays valid while the server runs.
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, routing::get};
+///
+/// # async {
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+/// let server = axum::serve(listener, router).with_graceful_shutdown(shutdown_signal());
+///
+/// let in_flight = server.in_flight_requests();
+/// tokio::spawn(async move {
+///     // report the number of requests somewhere
+///     println!("{} request(s) in flight", in_flight.count());
+/// });
+///
+/// server.await.unwrap();
+/// # };
+///
+/// async fn shutdown_signal() {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InFlightRequests(Arc<AtomicUsize>);
+
+impl InFlightRequests {
+    /// Returns the number of requests currently being handled.
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn start(&self) -> InFlightGuard {
+        self.0.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard(self.clone())
+    }
+}
+
+/// Decrements the in flight requests count when dropped.
+struct InFlightGuard(InFlightRequests);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0 .0.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
@@ -568,20 +1567,85 @@ where
 /// [`IntoMakeServiceWithConnectInfo`]: crate::extract::connect_info::IntoMakeServiceWithConnectInfo
 #[derive(Debug)]
 pub struct IncomingStream<'a> {
-    tcp_stream: &'a TokioIo<TcpStream>,
+    stream: &'a TokioIo<Stream>,
     remote_addr: SocketAddr,
 }
 
 impl IncomingStream<'_> {
     /// Returns the local address that this stream is bound to.
     pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
-        self.tcp_stream.inner().local_addr()
+        self.stream
+            .inner()
+            .tcp_stream()
+            .expect("`IncomingStream` is only used for TCP connections")
+            .local_addr()
     }
 
     /// Returns the remote address that this stream is bound to.
     pub fn remote_addr(&self) -> SocketAddr {
         self.remote_addr
     }
+
+    /// Returns information about the TLS session, or `None` if the connection doesn't use TLS.
+    ///
+    /// Connections only use TLS when served with [`serve_tls`].
+    #[cfg(feature = "tls-rustls")]
+    pub fn tls_info(&self) -> Option<TlsInfo> {
+        match self.stream.inner() {
+            Stream::Tls(tls_stream) => Some(TlsInfo::new(tls_stream.get_ref().1)),
+            _ => None,
+        }
+    }
+}
+
+/// A connection that has been accepted but not yet served.
+///
+/// Passed to the filter set with [`Serve::accept_filter`].
+#[derive(Debug)]
+pub struct AcceptedConnection<'a> {
+    remote_addr: Option<SocketAddr>,
+    server_name: Option<&'a str>,
+    is_final: bool,
+}
+
+impl AcceptedConnection<'_> {
+    /// Returns the remote address of the connection.
+    ///
+    /// With [`Serve::proxy_protocol`] enabled, this is the client address from the PROXY protocol
+    /// header. Connections over Unix domain sockets don't have one unless the PROXY protocol
+    /// header provides it.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Returns the server name the client sent with TLS SNI, or `None` if the connection doesn't
+    /// use TLS or the client didn't send one.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name
+    }
+
+    /// Returns whether this is the last time the filter is called for this connection.
+    ///
+    /// With the PROXY protocol or TLS enabled, the filter is first called right after the
+    /// connection is accepted, with the address of the socket and no server name. It's called
+    /// again once the PROXY protocol header and TLS ClientHello have been read. Filters that
+    /// need the client address behind a proxy, or the server name, can allow the connection until
+    /// then.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+}
+
+type AcceptFilter = Arc<dyn Fn(&AcceptedConnection<'_>) -> Option<Box<dyn Send>> + Send + Sync>;
+
+fn accept_filter<A, G>(filter: A) -> AcceptFilter
+where
+    A: Fn(&AcceptedConnection<'_>) -> Option<G> + Send + Sync + 'static,
+    G: Send + 'static,
+{
+    Arc::new(move |conn: &AcceptedConnection<'_>| {
+        filter(conn).map(|guard| Box::new(guard) as Box<dyn Send>)
+    })
 }
 
 #[cfg(test)]
@@ -607,7 +1671,9 @@ mod tests {
         );
         serve(
             TcpListener::bind(addr).await.unwrap(),
-            router.into_make_service_with_connect_info::<SocketAddr>(),
+            router
+                .clone()
+                .into_make_service_with_connect_info::<SocketAddr>(),
         );
 
         // method router
@@ -652,7 +1718,197 @@ mod tests {
         )
         .with_graceful_shutdown(async { /*...*/ })
         .tcp_nodelay(true);
+
+        // proxy protocol
+        serve(
+            TcpListener::bind(addr).await.unwrap(),
+            handler.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .proxy_protocol(true);
+
+        serve(
+            TcpListener::bind(addr).await.unwrap(),
+            handler.into_service(),
+        )
+        .with_graceful_shutdown(async { /*...*/ })
+        .proxy_protocol(true);
+
+        // tls
+        #[cfg(feature = "tls-rustls")]
+        {
+            let config = RustlsConfig::from_pem(
+                include_bytes!("test_helpers/certs/cert.pem"),
+                include_bytes!("test_helpers/certs/key.pem"),
+            )
+            .unwrap();
+
+            serve_tls(
+                TcpListener::bind(addr).await.unwrap(),
+                config.clone(),
+                router.clone(),
+            );
+            serve_tls(
+                TcpListener::bind(addr).await.unwrap(),
+                config.clone(),
+                handler.into_make_service_with_connect_info::<TlsConnectInfo>(),
+            )
+            .with_graceful_shutdown(async { /*...*/ })
+            .proxy_protocol(true);
+
+            let sni = SniRouter::new()
+                .host("example.com", config.clone(), router.clone())
+                .default_host(config, router.clone());
+            serve_tls(
+                TcpListener::bind(addr).await.unwrap(),
+                sni.rustls_config(),
+                sni,
+            );
+        }
+
+        // http config
+        serve(TcpListener::bind(addr).await.unwrap(), router.clone())
+            .http_config(HttpConfig::new());
+        serve(TcpListener::bind(addr).await.unwrap(), router.clone())
+            .with_graceful_shutdown(async { /*...*/ })
+            .http_config(HttpConfig::new());
+
+        // accept filter
+        serve(TcpListener::bind(addr).await.unwrap(), router.clone()).accept_filter(|_| Some(()));
+        serve(TcpListener::bind(addr).await.unwrap(), router.clone())
+            .with_graceful_shutdown(async { /*...*/ })
+            .accept_filter(|conn| conn.server_name().map(|_| ()));
+
+        // unix domain sockets
+        #[cfg(unix)]
+        {
+            serve_uds("/tmp/axum.sock", router.clone());
+            serve_uds("/tmp/axum.sock", get(handler));
+            serve_uds("/tmp/axum.sock", handler.into_make_service());
+            serve_uds(
+                "/tmp/axum.sock",
+                router
+                    .clone()
+                    .into_make_service_with_connect_info::<UdsConnectInfo>(),
+            )
+            .permissions(0o660)
+            .remove_existing(true)
+            .with_graceful_shutdown(async { /*...*/ });
+        }
+
+        // h3
+        #[cfg(feature = "h3")]
+        {
+            serve(TcpListener::bind(addr).await.unwrap(), router.clone()).advertise_h3(443);
+            serve(TcpListener::bind(addr).await.unwrap(), router.clone())
+                .with_graceful_shutdown(async { /*...*/ })
+                .advertise_h3(443);
+
+            let endpoint = quinn::Endpoint::client("0.0.0.0:0".parse().unwrap()).unwrap();
+            serve_h3(endpoint.clone(), router.clone());
+            serve_h3(endpoint, handler.with_state(()));
+        }
     }
 
     async fn handler() {}
+
+    #[crate::test]
+    async fn drain_timeout() {
+        use std::sync::atomic::AtomicBool;
+        use tokio::{io::AsyncWriteExt, sync::oneshot};
+
+        let app = Router::new().route("/", get(std::future::pending::<()>));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let drain_started = Arc::new(AtomicBool::new(false));
+        let server = serve(listener, app)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            })
+            .drain_timeout(Duration::from_millis(100))
+            .on_drain_start({
+                let drain_started = Arc::clone(&drain_started);
+                move || drain_started.store(true, Ordering::SeqCst)
+            });
+        let in_flight = server.in_flight_requests();
+        let server = tokio::spawn(server.into_future());
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+
+        while in_flight.count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(in_flight.count(), 1);
+        assert!(!drain_started.load(Ordering::SeqCst));
+
+        shutdown_tx.send(()).unwrap();
+        // the request never finishes, so this only returns because of the drain timeout
+        server.await.unwrap().unwrap();
+
+        assert!(drain_started.load(Ordering::SeqCst));
+    }
+
+    #[crate::test]
+    async fn accept_filter() {
+        use std::sync::atomic::AtomicBool;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        struct Guard(Arc<AtomicUsize>);
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        let app = Router::new().route("/", get(|| async {}));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let open = Arc::new(AtomicUsize::new(0));
+        let reject = Arc::new(AtomicBool::new(false));
+        tokio::spawn({
+            let open = Arc::clone(&open);
+            let reject = Arc::clone(&reject);
+            serve(listener, app)
+                .accept_filter(move |conn| {
+                    assert!(conn.remote_addr().unwrap().ip().is_loopback());
+                    assert_eq!(conn.server_name(), None);
+                    if reject.load(Ordering::SeqCst) {
+                        return None;
+                    }
+                    open.fetch_add(1, Ordering::SeqCst);
+                    Some(Guard(Arc::clone(&open)))
+                })
+                .into_future()
+        });
+
+        let get_root = || async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            // rejected connections may be closed before the request is written
+            let _ = stream
+                .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+                .await;
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await;
+            response
+        };
+
+        assert!(get_root().await.starts_with("HTTP/1.1 200 OK"));
+        // the guard is dropped once the connection is closed
+        while open.load(Ordering::SeqCst) != 0 {
+            tokio::task::yield_now().await;
+        }
+
+        reject.store(true, Ordering::SeqCst);
+        assert_eq!(get_root().await, "");
+        assert_eq!(open.load(Ordering::SeqCst), 0);
+    }
 }
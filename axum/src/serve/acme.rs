@@ -0,0 +1,654 @@
+//! Automatic certificates from [ACME] certificate authorities such as [Let's Encrypt].
+//!
+//! [`Acme`] obtains a certificate for a set of domains, stores it with an [`AcmeStorage`], and
+//! renews it before it expires. Its [`rustls_config`](Acme::rustls_config) always serves the
+//! current certificate, so it can be used with [`serve_tls`](super::serve_tls) directly.
+//!
+//! The certificate authority checks that the server controls the domains with a [`Challenge`].
+//! For [`Challenge::Http01`] the [`http01_router`](Acme::http01_router) has to be served on port
+//! 80, and for [`Challenge::TlsAlpn01`] the TLS server has to be reachable on port 443.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use axum::{
+//!     Router,
+//!     routing::get,
+//!     serve::acme::{AcmeConfig, DirStorage},
+//! };
+//! use tokio::net::TcpListener;
+//!
+//! # async {
+//! let acme = AcmeConfig::new(["example.com"], DirStorage::new("/var/lib/my-app/acme"))
+//!     .contact("mailto:admin@example.com")
+//!     .build();
+//!
+//! // answer challenges on port 80
+//! let http = TcpListener::bind("0.0.0.0:80").await.unwrap();
+//! let challenges = acme.http01_router();
+//! tokio::spawn(async move { axum::serve(http, challenges).await });
+//!
+//! // obtain and renew the certificate in the background
+//! tokio::spawn(acme.clone().run());
+//!
+//! let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+//!
+//! let https = TcpListener::bind("0.0.0.0:443").await.unwrap();
+//! axum::serve_tls(https, acme.rustls_config(), router).await.unwrap();
+//! # };
+//! ```
+//!
+//! [ACME]: https://www.rfc-editor.org/rfc/rfc8555
+//! [Let's Encrypt]: https://letsencrypt.org
+
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    fmt, io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use http::StatusCode;
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, LetsEncrypt,
+    NewAccount, NewOrder, OrderStatus,
+};
+use rcgen::{Certificate, CertificateParams, CustomExtension, DistinguishedName};
+use tokio_rustls::rustls::{
+    crypto::ring::sign::any_supported_type,
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    ServerConfig,
+};
+
+use super::{tls, RustlsConfig};
+use crate::{extract::Path, routing::get, util::AxumMutex, Router};
+
+/// The ALPN protocol used for [`Challenge::TlsAlpn01`].
+pub(super) const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+
+/// How long to wait before trying again after obtaining a certificate failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How the certificate authority checks that the server controls the domains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Challenge {
+    /// Serve a token over plain HTTP on port 80 with [`Acme::http01_router`].
+    Http01,
+    /// Present a special certificate during a TLS handshake on port 443.
+    ///
+    /// Requires no extra listener, but the config from [`Acme::rustls_config`] has to be used
+    /// on port 443.
+    TlsAlpn01,
+}
+
+/// Storage for the ACME account and certificates, so they survive restarts.
+///
+/// Certificate authorities limit how often certificates can be issued, so a persistent storage
+/// such as [`DirStorage`] should be used in production.
+#[async_trait]
+pub trait AcmeStorage: Send + Sync + 'static {
+    /// Load the value stored under `key`, or `None` if nothing is stored.
+    async fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Store `value` under `key`, replacing any existing value.
+    async fn store(&self, key: &str, value: Vec<u8>) -> io::Result<()>;
+}
+
+/// An [`AcmeStorage`] that keeps each value in a file in a directory.
+///
+/// The directory is created if it doesn't exist. It contains private keys, so it should only be
+/// readable by the server.
+#[derive(Debug, Clone)]
+pub struct DirStorage {
+    dir: PathBuf,
+}
+
+impl DirStorage {
+    /// Store values in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl AcmeStorage for DirStorage {
+    async fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.dir.join(key)).await {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn store(&self, key: &str, value: Vec<u8>) -> io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // write to a temporary file first so a crash can't leave a partial value behind
+        let tmp = self.dir.join(format!("{key}.tmp"));
+        tokio::fs::write(&tmp, value).await?;
+        tokio::fs::rename(&tmp, self.dir.join(key)).await
+    }
+}
+
+/// Configuration for [`Acme`].
+///
+/// Uses the Let's Encrypt production directory and [`Challenge::Http01`] by default.
+pub struct AcmeConfig {
+    domains: Vec<String>,
+    storage: Arc<dyn AcmeStorage>,
+    contact: Vec<String>,
+    directory_url: String,
+    challenge: Challenge,
+    renew_before: Duration,
+}
+
+impl AcmeConfig {
+    /// Obtain a certificate for `domains`, storing it in `storage`.
+    pub fn new<I, D, S>(domains: I, storage: S) -> Self
+    where
+        I: IntoIterator<Item = D>,
+        D: Into<String>,
+        S: AcmeStorage,
+    {
+        Self {
+            domains: domains.into_iter().map(Into::into).collect(),
+            storage: Arc::new(storage),
+            contact: Vec::new(),
+            directory_url: LetsEncrypt::Production.url().to_owned(),
+            challenge: Challenge::Http01,
+            renew_before: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+
+    /// Add a contact URL, such as `mailto:admin@example.com`, to the account.
+    ///
+    /// Certificate authorities use it to warn about expiring certificates and other problems.
+    pub fn contact(mut self, contact: impl Into<String>) -> Self {
+        self.contact.push(contact.into());
+        self
+    }
+
+    /// Use the ACME directory at `url` instead of Let's Encrypt.
+    ///
+    /// Creating an account agrees to the terms of service of the certificate authority.
+    pub fn directory_url(self, url: impl Into<String>) -> Self {
+        Self {
+            directory_url: url.into(),
+            ..self
+        }
+    }
+
+    /// Use the Let's Encrypt staging directory, which has higher rate limits but issues
+    /// certificates that browsers don't trust. Useful for testing the setup.
+    pub fn staging(self) -> Self {
+        self.directory_url(LetsEncrypt::Staging.url())
+    }
+
+    /// Set how to prove control over the domains.
+    pub fn challenge(self, challenge: Challenge) -> Self {
+        Self { challenge, ..self }
+    }
+
+    /// Set how long before the certificate expires to renew it.
+    ///
+    /// Defaults to 30 days.
+    pub fn renew_before(self, renew_before: Duration) -> Self {
+        Self {
+            renew_before,
+            ..self
+        }
+    }
+
+    /// Create the [`Acme`] handle.
+    ///
+    /// No certificate is obtained until [`Acme::run`] is called.
+    pub fn build(self) -> Acme {
+        Acme {
+            inner: Arc::new(Inner {
+                config: self,
+                cert: RwLock::new(None),
+                http01: AxumMutex::new(HashMap::new()),
+                tls_alpn01: AxumMutex::new(HashMap::new()),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for AcmeConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            domains,
+            storage: _,
+            contact,
+            directory_url,
+            challenge,
+            renew_before,
+        } = self;
+
+        f.debug_struct("AcmeConfig")
+            .field("domains", domains)
+            .field("contact", contact)
+            .field("directory_url", directory_url)
+            .field("challenge", challenge)
+            .field("renew_before", renew_before)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Obtains and renews a certificate with ACME.
+///
+/// `Acme` is a cheaply cloneable handle. See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct Acme {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    config: AcmeConfig,
+    cert: RwLock<Option<Arc<CertifiedKey>>>,
+    /// Key authorizations for pending HTTP-01 challenges, by token.
+    http01: AxumMutex<HashMap<String, String>>,
+    /// Certificates for pending TLS-ALPN-01 challenges, by domain.
+    tls_alpn01: AxumMutex<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl Acme {
+    /// Create the [`RustlsConfig`] that serves the current certificate.
+    ///
+    /// TLS handshakes fail until the first certificate has been obtained or loaded from the
+    /// storage.
+    pub fn rustls_config(&self) -> RustlsConfig {
+        let mut config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(AcmeResolver(self.clone())));
+        tls::set_alpn_protocols(&mut config);
+        if self.inner.config.challenge == Challenge::TlsAlpn01 {
+            config.alpn_protocols.push(ACME_TLS_ALPN.to_vec());
+        }
+
+        RustlsConfig::from_config(Arc::new(config))
+    }
+
+    /// A [`Router`] that answers HTTP-01 challenges.
+    ///
+    /// Serve it on port 80, either on its own or merged into another router.
+    pub fn http01_router(&self) -> Router {
+        let acme = self.clone();
+        Router::new().route(
+            "/.well-known/acme-challenge/:token",
+            get(move |Path(token): Path<String>| {
+                let key_authorization = lock(&acme.inner.http01).get(&token).cloned();
+                async move { key_authorization.ok_or(StatusCode::NOT_FOUND) }
+            }),
+        )
+    }
+
+    /// Load the certificate from the storage, or obtain one, and renew it before it expires.
+    ///
+    /// This runs forever, so it is usually spawned as a task. Failures are logged and retried
+    /// after an hour.
+    pub async fn run(self) {
+        loop {
+            let wait = match self.renew_if_needed().await {
+                Ok(not_after) => {
+                    let renew_at = not_after.saturating_sub(self.inner.config.renew_before);
+                    renew_at
+                        .saturating_sub(unix_time())
+                        .max(Duration::from_secs(60))
+                }
+                Err(_err) => {
+                    error!("failed to obtain certificate: {_err}");
+                    RETRY_INTERVAL
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Makes sure there is a certificate that doesn't need renewing yet, and returns when it
+    /// expires.
+    async fn renew_if_needed(&self) -> io::Result<Duration> {
+        let config = &self.inner.config;
+        let key = format!(
+            "{}-{}.pem",
+            storage_prefix(config),
+            config.domains.join("_")
+        );
+
+        if let Some(pem) = config.storage.load(&key).await? {
+            let (cert, not_after) = parse_certificate(&pem)?;
+            if not_after.saturating_sub(unix_time()) > config.renew_before {
+                *self
+                    .inner
+                    .cert
+                    .write()
+                    .unwrap_or_else(|err| err.into_inner()) = Some(cert);
+                return Ok(not_after);
+            }
+        }
+
+        trace!("ordering certificate for {:?}", config.domains);
+        let pem = self.order().await?;
+        let (cert, not_after) = parse_certificate(pem.as_bytes())?;
+        config.storage.store(&key, pem.into_bytes()).await?;
+        *self
+            .inner
+            .cert
+            .write()
+            .unwrap_or_else(|err| err.into_inner()) = Some(cert);
+
+        Ok(not_after)
+    }
+
+    /// Orders a certificate, returning the private key and certificate chain as PEM.
+    async fn order(&self) -> io::Result<String> {
+        let config = &self.inner.config;
+        let account = self.account().await?;
+
+        let identifiers = config
+            .domains
+            .iter()
+            .map(|domain| Identifier::Dns(domain.clone()))
+            .collect::<Vec<_>>();
+        let mut order = account
+            .new_order(&NewOrder {
+                identifiers: &identifiers,
+            })
+            .await
+            .map_err(other)?;
+
+        let challenge_type = match config.challenge {
+            Challenge::Http01 => ChallengeType::Http01,
+            Challenge::TlsAlpn01 => ChallengeType::TlsAlpn01,
+        };
+
+        let mut challenge_urls = Vec::new();
+        for authorization in order.authorizations().await.map_err(other)? {
+            match authorization.status {
+                AuthorizationStatus::Valid => continue,
+                AuthorizationStatus::Pending => {}
+                _ => return Err(other("authorization is not pending")),
+            }
+
+            let challenge = authorization
+                .challenges
+                .iter()
+                .find(|challenge| challenge.r#type == challenge_type)
+                .ok_or_else(|| other("challenge type not offered by the certificate authority"))?;
+            let key_authorization = order.key_authorization(challenge);
+
+            match config.challenge {
+                Challenge::Http01 => {
+                    lock(&self.inner.http01).insert(
+                        challenge.token.clone(),
+                        key_authorization.as_str().to_owned(),
+                    );
+                }
+                Challenge::TlsAlpn01 => {
+                    let Identifier::Dns(domain) = authorization.identifier;
+                    let cert = tls_alpn01_certificate(&domain, key_authorization.digest())?;
+                    lock(&self.inner.tls_alpn01).insert(domain, Arc::new(cert));
+                }
+            }
+            challenge_urls.push(challenge.url.clone());
+        }
+
+        let result = self.complete_order(&mut order, &challenge_urls).await;
+
+        lock(&self.inner.http01).clear();
+        lock(&self.inner.tls_alpn01).clear();
+
+        result
+    }
+
+    async fn complete_order(
+        &self,
+        order: &mut instant_acme::Order,
+        challenge_urls: &[String],
+    ) -> io::Result<String> {
+        for url in challenge_urls {
+            order.set_challenge_ready(url).await.map_err(other)?;
+        }
+
+        let mut delay = Duration::from_secs(1);
+        let mut attempts = 0;
+        loop {
+            tokio::time::sleep(delay).await;
+            match order.refresh().await.map_err(other)?.status {
+                OrderStatus::Ready => break,
+                OrderStatus::Invalid => return Err(other("order is invalid")),
+                _ => {}
+            }
+
+            attempts += 1;
+            if attempts == 10 {
+                return Err(other("timed out waiting for the order to become ready"));
+            }
+            delay = (delay * 2).min(Duration::from_secs(30));
+        }
+
+        let mut params = CertificateParams::new(self.inner.config.domains.clone());
+        params.distinguished_name = DistinguishedName::new();
+        let cert = Certificate::from_params(params).map_err(other)?;
+        let csr = cert.serialize_request_der().map_err(other)?;
+        order.finalize(&csr).await.map_err(other)?;
+
+        let chain = loop {
+            match order.certificate().await.map_err(other)? {
+                Some(chain) => break chain,
+                None => tokio::time::sleep(Duration::from_secs(1)).await,
+            }
+        };
+
+        Ok(format!("{}{chain}", cert.serialize_private_key_pem()))
+    }
+
+    /// Loads the account from the storage, or creates one.
+    async fn account(&self) -> io::Result<Account> {
+        let config = &self.inner.config;
+        let key = format!("{}-account.json", storage_prefix(config));
+
+        if let Some(credentials) = config.storage.load(&key).await? {
+            let credentials =
+                serde_json::from_slice::<AccountCredentials>(&credentials).map_err(other)?;
+            return Account::from_credentials(credentials).await.map_err(other);
+        }
+
+        let contact = config
+            .contact
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let (account, credentials) = Account::create(
+            &NewAccount {
+                contact: &contact,
+                terms_of_service_agreed: true,
+                only_return_existing: false,
+            },
+            &config.directory_url,
+            None,
+        )
+        .await
+        .map_err(other)?;
+
+        let credentials = serde_json::to_vec(&credentials).map_err(other)?;
+        config.storage.store(&key, credentials).await?;
+
+        Ok(account)
+    }
+}
+
+impl fmt::Debug for Acme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Acme")
+            .field("config", &self.inner.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct AcmeResolver(Acme);
+
+impl ResolvesServerCert for AcmeResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let is_challenge = client_hello
+            .alpn()
+            .into_iter()
+            .flatten()
+            .any(|protocol| protocol == ACME_TLS_ALPN);
+        if is_challenge {
+            let domain = client_hello.server_name()?;
+            return lock(&self.0.inner.tls_alpn01).get(domain).cloned();
+        }
+
+        self.0
+            .inner
+            .cert
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+/// Separates the accounts and certificates of different directories, such as staging and
+/// production, in the same storage.
+fn storage_prefix(config: &AcmeConfig) -> String {
+    let url = config.directory_url.trim_start_matches("https://");
+    url.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Parses the private key and certificate chain, returning the certificate and when it expires.
+fn parse_certificate(pem: &[u8]) -> io::Result<(Arc<CertifiedKey>, Duration)> {
+    let certs = rustls_pemfile::certs(&mut &*pem).collect::<Result<Vec<_>, _>>()?;
+    let key =
+        rustls_pemfile::private_key(&mut &*pem)?.ok_or_else(|| invalid("no private key found"))?;
+
+    let leaf = certs
+        .first()
+        .ok_or_else(|| invalid("no certificates found"))?;
+    let (_, leaf) =
+        x509_parser::parse_x509_certificate(leaf).map_err(|_| invalid("invalid certificate"))?;
+    let not_after = Duration::from_secs(leaf.validity().not_after.timestamp().max(0) as u64);
+
+    let key = any_supported_type(&key).map_err(other)?;
+    Ok((Arc::new(CertifiedKey::new(certs, key)), not_after))
+}
+
+/// Creates the self-signed certificate presented for a TLS-ALPN-01 challenge.
+fn tls_alpn01_certificate(domain: &str, digest: impl AsRef<[u8]>) -> io::Result<CertifiedKey> {
+    let mut params = CertificateParams::new(vec![domain.to_owned()]);
+    params.custom_extensions = vec![CustomExtension::new_acme_identifier(digest.as_ref())];
+    let cert = Certificate::from_params(params).map_err(other)?;
+
+    let der = CertificateDer::from(cert.serialize_der().map_err(other)?);
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.serialize_private_key_der()));
+    let key = any_supported_type(&key).map_err(other)?;
+
+    Ok(CertifiedKey::new(vec![der], key))
+}
+
+fn unix_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+fn lock<T>(mutex: &AxumMutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn other<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn StdError + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::IntoFuture;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    const CERT: &[u8] = include_bytes!("../test_helpers/certs/cert.pem");
+    const KEY: &[u8] = include_bytes!("../test_helpers/certs/key.pem");
+
+    #[crate::test]
+    async fn dir_storage() {
+        let dir = std::env::temp_dir().join(format!("axum-acme-{}", std::process::id()));
+        let storage = DirStorage::new(&dir);
+
+        assert_eq!(storage.load("key").await.unwrap(), None);
+        storage.store("key", b"one".to_vec()).await.unwrap();
+        storage.store("key", b"two".to_vec()).await.unwrap();
+        assert_eq!(storage.load("key").await.unwrap(), Some(b"two".to_vec()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_stored_certificate() {
+        let pem = [KEY, CERT].concat();
+        let (cert, not_after) = parse_certificate(&pem).unwrap();
+        assert_eq!(cert.cert.len(), 1);
+        assert!(not_after > Duration::ZERO);
+
+        assert!(parse_certificate(CERT).is_err());
+        assert!(parse_certificate(KEY).is_err());
+    }
+
+    #[test]
+    fn storage_prefix_depends_on_directory() {
+        let storage = DirStorage::new("unused");
+        let production = AcmeConfig::new(["example.com"], storage.clone());
+        let staging = AcmeConfig::new(["example.com"], storage).staging();
+
+        assert_ne!(storage_prefix(&production), storage_prefix(&staging));
+        assert!(!storage_prefix(&production).contains('/'));
+    }
+
+    #[crate::test]
+    async fn http01_router() {
+        let acme = AcmeConfig::new(["example.com"], DirStorage::new("unused")).build();
+        lock(&acme.inner.http01).insert("token".to_owned(), "token.thumbprint".to_owned());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(super::super::serve(listener, acme.http01_router()).into_future());
+
+        let get = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let response = get("/.well-known/acme-challenge/token").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("token.thumbprint"));
+
+        let response = get("/.well-known/acme-challenge/other").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Restarting without downtime by handing the listening socket over to a new process.
+//!
+//! There are two ways for a new process to take over from an old one:
+//!
+//! - Both processes bind the same address with [`bind_reuse_port`]. The kernel spreads new
+//!   connections over both sockets until the old process stops accepting.
+//! - A supervisor, such as systemd or `systemfd`, keeps the socket open and passes it to each new
+//!   process with the `LISTEN_FDS` protocol. No connections are refused while neither process is
+//!   accepting.
+//!
+//! [`listener`] uses an inherited socket if there is one and binds with `SO_REUSEPORT`
+//! otherwise, so it supports both.
+//!
+//! Either way the old process has to stop accepting and finish its in-flight requests, which is
+//! what [`Serve::with_graceful_shutdown`] does. Combine it with
+//! [`WithGracefulShutdown::drain_timeout`] to bound how long that takes.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use axum::{Router, routing::get, serve::handoff};
+//! use std::time::Duration;
+//!
+//! # async {
+//! let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+//!
+//! let listener = handoff::listener("0.0.0.0:3000".parse().unwrap()).unwrap();
+//!
+//! axum::serve(listener, router)
+//!     .with_graceful_shutdown(shutdown_signal())
+//!     .drain_timeout(Duration::from_secs(30))
+//!     .await
+//!     .unwrap();
+//! # };
+//!
+//! async fn shutdown_signal() {
+//!     // wait for the new process to tell this one to stop, for example with SIGTERM
+//! }
+//! ```
+//!
+//! [`Serve::with_graceful_shutdown`]: super::Serve::with_graceful_shutdown
+//! [`WithGracefulShutdown::drain_timeout`]: super::WithGracefulShutdown::drain_timeout
+
+use std::{io, net::SocketAddr};
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::TcpListener;
+
+/// The backlog of sockets created by [`bind_reuse_port`].
+const BACKLOG: i32 = 1024;
+
+/// Bind a TCP listener to `addr` with `SO_REUSEPORT`, so another process can bind the same
+/// address at the same time.
+///
+/// All processes bound to the address have to set `SO_REUSEPORT` and run as the same user.
+/// Connections still waiting in the backlog of a socket when it is closed are reset, so a few
+/// connections can be lost when the old process stops accepting under heavy load.
+///
+/// Must be called from within a tokio runtime.
+pub fn bind_reuse_port(addr: SocketAddr) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(BACKLOG)?;
+    TcpListener::from_std(socket.into())
+}
+
+/// Take the first TCP listener passed to the process with the `LISTEN_FDS` protocol, or bind
+/// `addr` with [`bind_reuse_port`] if there is none.
+///
+/// Must be called from within a tokio runtime.
+pub fn listener(addr: SocketAddr) -> io::Result<TcpListener> {
+    match listenfd::ListenFd::from_env().take_tcp_listener(0)? {
+        Some(listener) => {
+            listener.set_nonblocking(true)?;
+            TcpListener::from_std(listener)
+        }
+        None => bind_reuse_port(addr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::get, Router};
+    use std::future::IntoFuture;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        sync::oneshot,
+    };
+
+    #[crate::test]
+    async fn handoff() {
+        let old = bind_reuse_port("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = old.local_addr().unwrap();
+        let new = listener(addr).unwrap();
+
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        let old = tokio::spawn(
+            super::super::serve(old, Router::new().route("/", get(|| async { "old" })))
+                .with_graceful_shutdown(async {
+                    stop_rx.await.ok();
+                })
+                .into_future(),
+        );
+
+        // the new process is ready, so the old one stops accepting
+        stop_tx.send(()).unwrap();
+        old.await.unwrap().unwrap();
+
+        tokio::spawn(
+            super::super::serve(new, Router::new().route("/", get(|| async { "new" })))
+                .into_future(),
+        );
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("new"));
+    }
+}
@@ -0,0 +1,172 @@
+//! HTTP/3 support for [`serve_h3`](super::serve_h3), using [quinn] and [h3].
+//!
+//! [quinn]: https://crates.io/crates/quinn
+//! [h3]: https://crates.io/crates/h3
+
+use std::{convert::Infallible, fmt, future::IntoFuture, io};
+
+use axum_core::{body::Body, extract::Request, response::Response, BoxError};
+use bytes::{Buf, Bytes};
+use futures_util::stream;
+use http::HeaderValue;
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+use tower_service::Service;
+
+use super::private::ServeFuture;
+
+/// Future returned by [`serve_h3`](super::serve_h3).
+///
+/// Await it to serve connections until the endpoint is closed.
+#[must_use = "futures must be awaited or polled"]
+pub struct ServeH3<S> {
+    endpoint: quinn::Endpoint,
+    service: S,
+}
+
+impl<S> ServeH3<S> {
+    pub(super) fn new(endpoint: quinn::Endpoint, service: S) -> Self {
+        Self { endpoint, service }
+    }
+}
+
+impl<S> fmt::Debug for ServeH3<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { endpoint, service } = self;
+
+        f.debug_struct("ServeH3")
+            .field("endpoint", endpoint)
+            .field("service", service)
+            .finish()
+    }
+}
+
+impl<S> IntoFuture for ServeH3<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Output = io::Result<()>;
+    type IntoFuture = ServeFuture;
+
+    fn into_future(self) -> Self::IntoFuture {
+        ServeFuture(Box::pin(async move {
+            let Self { endpoint, service } = self;
+
+            while let Some(connecting) = endpoint.accept().await {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    if let Err(_err) = serve_connection(connecting, service).await {
+                        trace!("failed to serve HTTP/3 connection: {_err:#}");
+                    }
+                });
+            }
+
+            Ok(())
+        }))
+    }
+}
+
+async fn serve_connection<S>(connecting: quinn::Connecting, service: S) -> Result<(), BoxError>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    let conn = connecting.await?;
+    let mut conn =
+        ::h3::server::Connection::<_, Bytes>::new(::h3_quinn::Connection::new(conn)).await?;
+
+    while let Some((req, stream)) = conn.accept().await? {
+        let service = service.clone();
+        tokio::spawn(async move {
+            if let Err(_err) = serve_request(req, stream, service).await {
+                trace!("failed to serve HTTP/3 request: {_err:#}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn serve_request<S, T>(
+    req: http::Request<()>,
+    stream: ::h3::server::RequestStream<T, Bytes>,
+    service: S,
+) -> Result<(), BoxError>
+where
+    S: Service<Request, Response = Response, Error = Infallible>,
+    T: ::h3::quic::BidiStream<Bytes>,
+    T::SendStream: Send + 'static,
+    T::RecvStream: Send + 'static,
+{
+    let (mut send, recv) = stream.split();
+
+    // the body ends at the first error, which is returned to whoever reads it
+    let body = stream::unfold(Some(recv), |recv| async move {
+        let mut recv = recv?;
+        match recv.recv_data().await {
+            Ok(Some(mut data)) => Some((Ok(data.copy_to_bytes(data.remaining())), Some(recv))),
+            Ok(None) => None,
+            Err(err) => Some((Err(err), None)),
+        }
+    });
+    let req = req.map(|()| Body::from_stream(body));
+
+    let res = service
+        .oneshot(req)
+        .await
+        .unwrap_or_else(|err| match err {});
+    let (parts, mut body) = res.into_parts();
+    send.send_response(Response::from_parts(parts, ())).await?;
+
+    while let Some(frame) = body.frame().await {
+        match frame?.into_data() {
+            Ok(data) => send.send_data(data).await?,
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    send.send_trailers(trailers).await?;
+                }
+            }
+        }
+    }
+    send.finish().await?;
+
+    Ok(())
+}
+
+/// The `Alt-Svc` header value advertising HTTP/3 on `port`.
+pub(super) fn alt_svc(port: u16) -> HeaderValue {
+    HeaderValue::try_from(format!("h3=\":{port}\"; ma=86400")).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::get, Router};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn alt_svc_value() {
+        assert_eq!(alt_svc(443), "h3=\":443\"; ma=86400");
+    }
+
+    #[crate::test]
+    async fn advertise_h3() {
+        let app = Router::new().route("/", get(|| async {}));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            super::super::serve(listener, app)
+                .advertise_h3(4433)
+                .await
+                .unwrap();
+        });
+
+        let res = reqwest::get(format!("http://{addr}")).await.unwrap();
+        assert_eq!(res.headers()["alt-svc"], "h3=\":4433\"; ma=86400");
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use hyper_util::{
+    rt::{TokioExecutor, TokioTimer},
+    server::conn::auto::Builder,
+};
+
+/// HTTP/1 and HTTP/2 connection options for [`Serve::http_config`].
+///
+/// Options that aren't set use hyper's defaults.
+///
+/// # Example
+///
+/// ```
+/// use axum::{Router, routing::get, serve::HttpConfig};
+/// use std::time::Duration;
+///
+/// # async {
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// let config = HttpConfig::new()
+///     .http1_header_read_timeout(Duration::from_secs(5))
+///     .http1_max_buf_size(64 * 1024);
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+/// axum::serve(listener, router).http_config(config).await.unwrap();
+/// # };
+/// ```
+///
+/// [`Serve::http_config`]: super::Serve::http_config
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    #[cfg(feature = "http1")]
+    http1_keep_alive: Option<bool>,
+    #[cfg(feature = "http1")]
+    http1_header_read_timeout: Option<Duration>,
+    #[cfg(feature = "http1")]
+    http1_max_buf_size: Option<usize>,
+    #[cfg(feature = "http2")]
+    http2_initial_stream_window_size: Option<u32>,
+    #[cfg(feature = "http2")]
+    http2_initial_connection_window_size: Option<u32>,
+    #[cfg(feature = "http2")]
+    http2_adaptive_window: Option<bool>,
+    #[cfg(feature = "http2")]
+    http2_max_concurrent_streams: Option<u32>,
+    #[cfg(feature = "http2")]
+    http2_keep_alive_interval: Option<Duration>,
+    #[cfg(feature = "http2")]
+    http2_keep_alive_timeout: Option<Duration>,
+}
+
+impl HttpConfig {
+    /// Create a config with hyper's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to keep HTTP/1 connections open between requests.
+    ///
+    /// Defaults to `true`.
+    #[cfg(feature = "http1")]
+    pub fn http1_keep_alive(mut self, keep_alive: bool) -> Self {
+        self.http1_keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// How long a client has to send the headers of an HTTP/1 request before the connection is
+    /// closed.
+    #[cfg(feature = "http1")]
+    pub fn http1_header_read_timeout(mut self, timeout: Duration) -> Self {
+        self.http1_header_read_timeout = Some(timeout);
+        self
+    }
+
+    /// The maximum size of the HTTP/1 read buffer, which limits the size of request headers.
+    ///
+    /// Must be at least 8192 bytes. Defaults to about 400 KB.
+    #[cfg(feature = "http1")]
+    pub fn http1_max_buf_size(mut self, max: usize) -> Self {
+        self.http1_max_buf_size = Some(max);
+        self
+    }
+
+    /// The initial HTTP/2 flow control window size of each stream, in bytes.
+    #[cfg(feature = "http2")]
+    pub fn http2_initial_stream_window_size(mut self, size: u32) -> Self {
+        self.http2_initial_stream_window_size = Some(size);
+        self
+    }
+
+    /// The initial HTTP/2 flow control window size of each connection, in bytes.
+    #[cfg(feature = "http2")]
+    pub fn http2_initial_connection_window_size(mut self, size: u32) -> Self {
+        self.http2_initial_connection_window_size = Some(size);
+        self
+    }
+
+    /// Whether to size the HTTP/2 flow control windows based on the estimated bandwidth-delay
+    /// product of the connection.
+    ///
+    /// Overrides the initial window sizes when enabled.
+    #[cfg(feature = "http2")]
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.http2_adaptive_window = Some(enabled);
+        self
+    }
+
+    /// The maximum number of concurrent HTTP/2 streams a client can open on one connection.
+    #[cfg(feature = "http2")]
+    pub fn http2_max_concurrent_streams(mut self, max: u32) -> Self {
+        self.http2_max_concurrent_streams = Some(max);
+        self
+    }
+
+    /// How often to send HTTP/2 pings to keep connections alive.
+    ///
+    /// Disabled by default.
+    #[cfg(feature = "http2")]
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// How long to wait for a ping acknowledgement before closing the connection.
+    ///
+    /// Only used with [`HttpConfig::http2_keep_alive_interval`].
+    #[cfg(feature = "http2")]
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    pub(super) fn builder(&self) -> Builder<TokioExecutor> {
+        #[allow(unused_mut)]
+        let mut builder = Builder::new(TokioExecutor::new());
+
+        #[cfg(feature = "http1")]
+        {
+            let mut http1 = builder.http1();
+            if let Some(keep_alive) = self.http1_keep_alive {
+                http1.keep_alive(keep_alive);
+            }
+            if let Some(timeout) = self.http1_header_read_timeout {
+                http1.timer(TokioTimer::new()).header_read_timeout(timeout);
+            }
+            if let Some(max) = self.http1_max_buf_size {
+                http1.max_buf_size(max);
+            }
+        }
+
+        #[cfg(feature = "http2")]
+        {
+            let mut http2 = builder.http2();
+            // extended CONNECT needed for websockets over HTTP/2
+            http2.enable_connect_protocol();
+            if let Some(size) = self.http2_initial_stream_window_size {
+                http2.initial_stream_window_size(size);
+            }
+            if let Some(size) = self.http2_initial_connection_window_size {
+                http2.initial_connection_window_size(size);
+            }
+            if let Some(enabled) = self.http2_adaptive_window {
+                http2.adaptive_window(enabled);
+            }
+            if let Some(max) = self.http2_max_concurrent_streams {
+                http2.max_concurrent_streams(max);
+            }
+            if let Some(interval) = self.http2_keep_alive_interval {
+                http2.timer(TokioTimer::new()).keep_alive_interval(interval);
+            }
+            if let Some(timeout) = self.http2_keep_alive_timeout {
+                http2.keep_alive_timeout(timeout);
+            }
+        }
+
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::get, Router};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    #[crate::test]
+    async fn http1_keep_alive() {
+        let app = Router::new().route("/", get(|| async {}));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            super::super::serve(listener, app)
+                .http_config(HttpConfig::new().http1_keep_alive(false))
+                .await
+                .unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        // the server closes the connection even though the client didn't ask it to
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("connection: close"));
+    }
+}
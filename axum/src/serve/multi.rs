@@ -0,0 +1,420 @@
+//! Serving one service from several listeners with [`serve_multi`].
+
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::{
+    convert::Infallible,
+    fmt,
+    future::{ready, Future, IntoFuture},
+    io,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum_core::{extract::Request, response::Response};
+use futures_util::{future::BoxFuture, stream};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tower_service::Service;
+
+use super::{
+    accept_filter, private::ServeFuture, serve_connections, tcp_incoming, AcceptedConnection,
+    Addrs, HttpConfig, InFlightRequests, MakeConnectionService, ServeConfig, Stream,
+};
+
+/// Serve the service from several listeners at once.
+///
+/// Add listeners with [`ServeMulti::tcp`] and, on Unix, `ServeMulti::uds`. All of them share one
+/// graceful shutdown signal and one [`InFlightRequests`] count.
+///
+/// Unlike [`serve`](super::serve) this takes the service itself, such as a [`Router`], rather
+/// than a service that makes services, since the listeners accept different kinds of
+/// connections.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{Router, routing::get};
+/// use tokio::net::TcpListener;
+///
+/// # async {
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// axum::serve_multi(router)
+///     .tcp(TcpListener::bind("0.0.0.0:80").await.unwrap())
+///     .tcp(TcpListener::bind("[::]:80").await.unwrap())
+///     .with_graceful_shutdown(shutdown_signal())
+///     .await
+///     .unwrap();
+/// # };
+///
+/// async fn shutdown_signal() {
+///     // ...
+/// }
+/// ```
+///
+/// [`Router`]: crate::Router
+pub fn serve_multi<S>(service: S) -> ServeMulti<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    ServeMulti {
+        service,
+        tcp_listeners: Vec::new(),
+        #[cfg(unix)]
+        uds_paths: Vec::new(),
+        signal: None,
+        config: ServeConfig::default(),
+    }
+}
+
+/// Future returned by [`serve_multi`].
+#[must_use = "futures must be awaited or polled"]
+pub struct ServeMulti<S> {
+    service: S,
+    tcp_listeners: Vec<TcpListener>,
+    #[cfg(unix)]
+    uds_paths: Vec<PathBuf>,
+    signal: Option<BoxFuture<'static, ()>>,
+    config: ServeConfig,
+}
+
+impl<S> ServeMulti<S> {
+    /// Accept connections from a TCP listener.
+    pub fn tcp(mut self, listener: TcpListener) -> Self {
+        self.tcp_listeners.push(listener);
+        self
+    }
+
+    /// Accept connections from a Unix domain socket at `path`.
+    ///
+    /// The socket is created when the returned future is first polled, and removed again after a
+    /// graceful shutdown.
+    #[cfg(unix)]
+    pub fn uds(mut self, path: impl AsRef<Path>) -> Self {
+        self.uds_paths.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Stop accepting connections on all listeners when `signal` completes, and wait for the open
+    /// connections to finish.
+    pub fn with_graceful_shutdown<F>(self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            signal: Some(Box::pin(signal)),
+            ..self
+        }
+    }
+
+    /// Returns a handle to the number of requests currently being handled, across all listeners.
+    pub fn in_flight_requests(&self) -> InFlightRequests {
+        self.config.in_flight.clone()
+    }
+
+    /// Set the `TCP_NODELAY` option on connections accepted from TCP listeners.
+    ///
+    /// See [`Serve::tcp_nodelay`].
+    ///
+    /// [`Serve::tcp_nodelay`]: super::Serve::tcp_nodelay
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.config.tcp_nodelay = Some(nodelay);
+        self
+    }
+
+    /// Read a PROXY protocol header at the start of each connection, on all listeners.
+    ///
+    /// See [`Serve::proxy_protocol`].
+    ///
+    /// [`Serve::proxy_protocol`]: super::Serve::proxy_protocol
+    pub fn proxy_protocol(mut self, proxy_protocol: bool) -> Self {
+        self.config.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// Configure the HTTP/1 and HTTP/2 protocol settings of each connection.
+    ///
+    /// See [`Serve::http_config`].
+    ///
+    /// [`Serve::http_config`]: super::Serve::http_config
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.config.http_config = http_config;
+        self
+    }
+
+    /// Decide whether to serve each accepted connection.
+    ///
+    /// See [`Serve::accept_filter`]. For connections over Unix domain sockets,
+    /// [`AcceptedConnection::remote_addr`] is `None` unless the PROXY protocol header provides an
+    /// address.
+    ///
+    /// [`Serve::accept_filter`]: super::Serve::accept_filter
+    pub fn accept_filter<A, G>(mut self, filter: A) -> Self
+    where
+        A: Fn(&AcceptedConnection<'_>) -> Option<G> + Send + Sync + 'static,
+        G: Send + 'static,
+    {
+        self.config.accept_filter = Some(accept_filter(filter));
+        self
+    }
+
+    /// Close the connections that are still open `timeout` after the graceful shutdown signal.
+    ///
+    /// See [`WithGracefulShutdown::drain_timeout`]. Has no effect without
+    /// [`ServeMulti::with_graceful_shutdown`].
+    ///
+    /// [`WithGracefulShutdown::drain_timeout`]: super::WithGracefulShutdown::drain_timeout
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.config.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Call `f` once the server stops accepting connections and starts draining the open ones.
+    ///
+    /// See [`WithGracefulShutdown::on_drain_start`]. Has no effect without
+    /// [`ServeMulti::with_graceful_shutdown`].
+    ///
+    /// [`WithGracefulShutdown::on_drain_start`]: super::WithGracefulShutdown::on_drain_start
+    pub fn on_drain_start<C>(mut self, f: C) -> Self
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        self.config.on_drain_start = Some(Box::new(f));
+        self
+    }
+}
+
+impl<S> fmt::Debug for ServeMulti<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            service,
+            tcp_listeners,
+            #[cfg(unix)]
+            uds_paths,
+            signal,
+            config,
+        } = self;
+
+        let mut f = f.debug_struct("ServeMulti");
+        f.field("service", service)
+            .field("tcp_listeners", tcp_listeners);
+        #[cfg(unix)]
+        f.field("uds_paths", uds_paths);
+        f.field("graceful_shutdown", &signal.is_some())
+            .field("config", config)
+            .finish()
+    }
+}
+
+impl<S> IntoFuture for ServeMulti<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Output = io::Result<()>;
+    type IntoFuture = ServeFuture;
+
+    fn into_future(self) -> Self::IntoFuture {
+        ServeFuture(Box::pin(async move {
+            let Self {
+                service,
+                tcp_listeners,
+                #[cfg(unix)]
+                uds_paths,
+                signal,
+                config,
+            } = self;
+
+            let mut incoming = Vec::new();
+            for listener in tcp_listeners {
+                incoming.push(tcp_incoming(listener));
+            }
+            #[cfg(unix)]
+            for path in &uds_paths {
+                let listener = super::unix::bind(path, None, false)?;
+                incoming.push(super::unix::uds_incoming(listener));
+            }
+
+            serve_connections(
+                Box::pin(stream::select_all(incoming)),
+                CloneService(service),
+                config,
+                signal,
+            )
+            .await;
+
+            #[cfg(unix)]
+            for path in uds_paths {
+                std::fs::remove_file(path)?;
+            }
+
+            Ok(())
+        }))
+    }
+}
+
+/// Serves every connection with a clone of the same service.
+struct CloneService<S>(S);
+
+impl<S> MakeConnectionService for CloneService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+{
+    type Service = S;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<()> {
+        Poll::Ready(())
+    }
+
+    fn make_service<'a>(
+        &'a mut self,
+        _stream: &'a TokioIo<Stream>,
+        _addrs: &'a Addrs,
+    ) -> BoxFuture<'a, S> {
+        Box::pin(ready(self.0.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::get, Router};
+    use std::net::SocketAddr;
+    use tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    async fn get_root(mut stream: impl AsyncRead + AsyncWrite + Unpin) -> String {
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[crate::test]
+    async fn serves_all_listeners() {
+        let app = Router::new().route("/", get(|| async { "ok" }));
+
+        let listener_a = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let listener_b = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addrs: [SocketAddr; 2] = [
+            listener_a.local_addr().unwrap(),
+            listener_b.local_addr().unwrap(),
+        ];
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = serve_multi(app)
+            .tcp(listener_a)
+            .tcp(listener_b)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            });
+        let in_flight = server.in_flight_requests();
+        let server = tokio::spawn(server.into_future());
+
+        for addr in addrs {
+            let response = get_root(TcpStream::connect(addr).await.unwrap()).await;
+            assert!(response.starts_with("HTTP/1.1 200 OK"));
+            assert!(response.ends_with("ok"));
+        }
+        assert_eq!(in_flight.count(), 0);
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+
+        for addr in addrs {
+            assert!(TcpStream::connect(addr).await.is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[crate::test]
+    async fn accept_filter() {
+        let dir =
+            std::env::temp_dir().join(format!("axum-serve-multi-filter-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.sock");
+
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            serve_multi(app)
+                .tcp(listener)
+                .uds(&path)
+                // only TCP connections have a remote address
+                .accept_filter(|conn| conn.remote_addr().map(|_| ()))
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .into_future(),
+        );
+
+        let response = get_root(TcpStream::connect(addr).await.unwrap()).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        let mut stream = loop {
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        // rejected connections may be closed before the request is written
+        let _ = stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await;
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response).await;
+        assert_eq!(response, "");
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[crate::test]
+    async fn serves_uds() {
+        let dir = std::env::temp_dir().join(format!("axum-serve-multi-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.sock");
+
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            serve_multi(app)
+                .uds(&path)
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .into_future(),
+        );
+
+        // wait for the socket to be created
+        let stream = loop {
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        let response = get_root(stream).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
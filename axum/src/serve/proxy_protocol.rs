@@ -0,0 +1,308 @@
+//! Parser for the [PROXY protocol] header sent by load balancers such as HAProxy.
+//!
+//! [PROXY protocol]: https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The longest possible v1 header, including the trailing `\r\n`.
+const V1_MAX_LEN: usize = 107;
+
+/// Read a PROXY protocol header from the start of `stream`.
+///
+/// Returns the address of the client the proxy accepted the connection from, or `None` if the
+/// proxy didn't provide one, for example because it sent a health check (v2 `LOCAL`) or the
+/// protocol was not TCP. Only the header is read, so the stream is left at the start of the
+/// proxied data.
+pub(super) async fn read_header<R>(stream: &mut R) -> io::Result<Option<SocketAddr>>
+where
+    R: AsyncRead + Unpin,
+{
+    // both versions have headers of at least 15 bytes
+    let mut prefix = [0; 8];
+    stream.read_exact(&mut prefix).await?;
+
+    if prefix == V2_SIGNATURE[..8] {
+        read_v2(stream, prefix).await
+    } else if prefix.starts_with(b"PROXY ") {
+        read_v1(stream, prefix).await
+    } else {
+        Err(invalid("missing PROXY protocol header"))
+    }
+}
+
+async fn read_v1<R>(stream: &mut R, prefix: [u8; 8]) -> io::Result<Option<SocketAddr>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut line = prefix.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LEN {
+            return Err(invalid("PROXY protocol v1 header too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2])
+        .map_err(|_| invalid("PROXY protocol v1 header is not valid UTF-8"))?;
+    parse_v1(line)
+}
+
+fn parse_v1(line: &str) -> io::Result<Option<SocketAddr>> {
+    let mut parts = line.split(' ').skip(1);
+
+    let protocol = parts.next().ok_or_else(|| invalid("missing protocol"))?;
+    if protocol == "UNKNOWN" {
+        return Ok(None);
+    }
+    if protocol != "TCP4" && protocol != "TCP6" {
+        return Err(invalid("unsupported PROXY protocol v1 protocol"));
+    }
+
+    let (Some(src_ip), Some(_dst_ip), Some(src_port), Some(_dst_port), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return Err(invalid("malformed PROXY protocol v1 header"));
+    };
+
+    let src_ip = src_ip
+        .parse::<IpAddr>()
+        .map_err(|_| invalid("invalid source address"))?;
+    if src_ip.is_ipv4() != (protocol == "TCP4") {
+        return Err(invalid("source address doesn't match protocol"));
+    }
+    let src_port = src_port
+        .parse::<u16>()
+        .map_err(|_| invalid("invalid source port"))?;
+
+    Ok(Some(SocketAddr::new(src_ip, src_port)))
+}
+
+async fn read_v2<R>(stream: &mut R, prefix: [u8; 8]) -> io::Result<Option<SocketAddr>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut header = [0; 16];
+    header[..8].copy_from_slice(&prefix);
+    stream.read_exact(&mut header[8..]).await?;
+
+    if header[..12] != V2_SIGNATURE {
+        return Err(invalid("invalid PROXY protocol v2 signature"));
+    }
+
+    let version_command = header[12];
+    let family = header[13];
+    let len = u16::from_be_bytes([header[14], header[15]]) as usize;
+
+    let mut addresses = vec![0; len];
+    stream.read_exact(&mut addresses).await?;
+
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported PROXY protocol version"));
+    }
+
+    match version_command & 0x0F {
+        // LOCAL, sent for health checks. Use the address of the proxy
+        0x0 => return Ok(None),
+        // PROXY
+        0x1 => {}
+        _ => return Err(invalid("unsupported PROXY protocol v2 command")),
+    }
+
+    match family {
+        // TCP over IPv4
+        0x11 if len >= 12 => {
+            let ip = Ipv4Addr::new(addresses[0], addresses[1], addresses[2], addresses[3]);
+            let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+            Ok(Some(SocketAddr::new(ip.into(), port)))
+        }
+        // TCP over IPv6
+        0x21 if len >= 36 => {
+            let mut ip = [0; 16];
+            ip.copy_from_slice(&addresses[..16]);
+            let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+            Ok(Some(SocketAddr::new(Ipv6Addr::from(ip).into(), port)))
+        }
+        0x11 | 0x21 => Err(invalid("PROXY protocol v2 address block too short")),
+        // UDP, unix sockets, or unspecified
+        _ => Ok(None),
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(mut input: &[u8]) -> (io::Result<Option<SocketAddr>>, &[u8]) {
+        let result = read_header(&mut input).await;
+        (result, input)
+    }
+
+    #[crate::test]
+    async fn v1() {
+        let (addr, rest) = read(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nGET /").await;
+        assert_eq!(addr.unwrap(), Some("192.168.0.1:56324".parse().unwrap()));
+        assert_eq!(rest, b"GET /");
+
+        let (addr, rest) = read(b"PROXY TCP6 ::1 ::2 1234 80\r\nGET /").await;
+        assert_eq!(addr.unwrap(), Some("[::1]:1234".parse().unwrap()));
+        assert_eq!(rest, b"GET /");
+
+        let (addr, rest) = read(b"PROXY UNKNOWN\r\nGET /").await;
+        assert_eq!(addr.unwrap(), None);
+        assert_eq!(rest, b"GET /");
+
+        let (addr, _) = read(b"PROXY TCP4 ::1 ::2 1234 80\r\n").await;
+        assert!(addr.is_err());
+
+        let (addr, _) = read(b"PROXY TCP4 192.168.0.1\r\n").await;
+        assert!(addr.is_err());
+
+        let (addr, _) = read(b"GET / HTTP/1.1\r\n").await;
+        assert!(addr.is_err());
+
+        let (addr, _) = read(&[b"PROXY TCP4 ".as_slice(), &[b'1'; 200]].concat()).await;
+        assert!(addr.is_err());
+    }
+
+    #[crate::test]
+    async fn v2() {
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend([0x21, 0x11, 0, 12]);
+        input.extend([127, 0, 0, 1, 127, 0, 0, 2]);
+        input.extend(1234_u16.to_be_bytes());
+        input.extend(80_u16.to_be_bytes());
+        input.extend(b"GET /");
+        let (addr, rest) = read(&input).await;
+        assert_eq!(addr.unwrap(), Some("127.0.0.1:1234".parse().unwrap()));
+        assert_eq!(rest, b"GET /");
+
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend([0x21, 0x21, 0, 36]);
+        input.extend(Ipv6Addr::LOCALHOST.octets());
+        input.extend(Ipv6Addr::LOCALHOST.octets());
+        input.extend(1234_u16.to_be_bytes());
+        input.extend(80_u16.to_be_bytes());
+        let (addr, _) = read(&input).await;
+        assert_eq!(addr.unwrap(), Some("[::1]:1234".parse().unwrap()));
+
+        // LOCAL command
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend([0x20, 0x00, 0, 0]);
+        input.extend(b"GET /");
+        let (addr, rest) = read(&input).await;
+        assert_eq!(addr.unwrap(), None);
+        assert_eq!(rest, b"GET /");
+
+        // address block too short
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend([0x21, 0x11, 0, 4]);
+        input.extend([127, 0, 0, 1]);
+        let (addr, _) = read(&input).await;
+        assert!(addr.is_err());
+    }
+
+    #[crate::test]
+    async fn connect_info() {
+        use crate::{extract::ConnectInfo, routing::get, Router};
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::{TcpListener, TcpStream},
+        };
+
+        let app = Router::new().route(
+            "/",
+            get(|ConnectInfo(addr): ConnectInfo<SocketAddr>| async move { addr.to_string() }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            super::super::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .proxy_protocol(true)
+            .await
+            .unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n\
+                  GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("192.168.0.1:56324"));
+    }
+
+    #[crate::test]
+    async fn accept_filter() {
+        use crate::{routing::get, util::AxumMutex, Router};
+        use std::{future::IntoFuture, sync::Arc};
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::{TcpListener, TcpStream},
+        };
+
+        let app = Router::new().route("/", get(|| async {}));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let calls = Arc::new(AxumMutex::new(Vec::new()));
+        tokio::spawn({
+            let calls = Arc::clone(&calls);
+            super::super::serve(listener, app)
+                .proxy_protocol(true)
+                .accept_filter(move |conn| {
+                    let remote_addr = conn.remote_addr().unwrap();
+                    calls.lock().unwrap().push((remote_addr, conn.is_final()));
+                    (remote_addr.ip().to_string() != "192.168.0.2").then_some(())
+                })
+                .into_future()
+        });
+
+        let get_root = |client: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!(
+                "PROXY TCP4 {client} 192.168.0.11 56324 443\r\n\
+                 GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n"
+            );
+            // rejected connections may be closed before the request is written
+            let _ = stream.write_all(request.as_bytes()).await;
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await;
+            response
+        };
+
+        assert!(get_root("192.168.0.1").await.starts_with("HTTP/1.1 200 OK"));
+        {
+            let calls = calls.lock().unwrap();
+            // called with the address of the socket first
+            assert!(calls[0].0.ip().is_loopback());
+            assert!(!calls[0].1);
+            assert_eq!(calls[1], ("192.168.0.1:56324".parse().unwrap(), true));
+        }
+
+        assert_eq!(get_root("192.168.0.2").await, "");
+    }
+}
@@ -0,0 +1,298 @@
+//! Serving several TLS hostnames with [`SniRouter`].
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::{ready, Ready},
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum_core::{
+    extract::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::Either;
+use http::{header, uri::Authority, StatusCode};
+use tokio_rustls::rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    ServerConfig,
+};
+use tower_service::Service;
+
+use super::{tls, IncomingStream, RustlsConfig};
+use crate::{routing::future::RouteFuture, Router};
+
+/// Picks the certificate and [`Router`] for each TLS connection based on the server name the
+/// client sent with [SNI].
+///
+/// Each hostname gets its own [`RustlsConfig`] and `Router`, so one server can host several
+/// applications that are isolated below the HTTP layer. Use [`SniRouter::rustls_config`] as the
+/// config and the `SniRouter` itself as the service for [`serve_tls`](super::serve_tls).
+///
+/// Connections are routed by SNI rather than by the `Host` header, and all requests on a
+/// connection go to the same `Router`. Connections for hostnames that weren't added, or without
+/// SNI, use the [default host](SniRouter::default_host) if there is one and fail the TLS
+/// handshake otherwise.
+///
+/// Requests whose `Host` header, or `:authority` with HTTP/2, names another host than the
+/// connection's server name get a `421 Misdirected Request` response. Clients reuse
+/// connections for all hosts the certificate is valid for, so this tells them to open a new
+/// connection for that host instead.
+///
+/// Only the certificates of the configs are used. Other options, such as client
+/// authentication, are ignored.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{Router, routing::get, serve::{RustlsConfig, SniRouter}};
+///
+/// # async {
+/// let shop = Router::new().route("/", get(|| async { "Welcome to the shop" }));
+/// let blog = Router::new().route("/", get(|| async { "Welcome to the blog" }));
+///
+/// let sni = SniRouter::new()
+///     .host(
+///         "shop.example.com",
+///         RustlsConfig::from_pem_file("shop.pem", "shop.key").await.unwrap(),
+///         shop,
+///     )
+///     .host(
+///         "blog.example.com",
+///         RustlsConfig::from_pem_file("blog.pem", "blog.key").await.unwrap(),
+///         blog,
+///     );
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// axum::serve_tls(listener, sni.rustls_config(), sni).await.unwrap();
+/// # };
+/// ```
+///
+/// [SNI]: https://www.rfc-editor.org/rfc/rfc6066#section-3
+#[derive(Debug, Clone, Default)]
+pub struct SniRouter {
+    hosts: HashMap<String, (RustlsConfig, Router)>,
+    default: Option<(RustlsConfig, Router)>,
+}
+
+impl SniRouter {
+    /// Create an `SniRouter` without any hosts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve connections for `server_name` with the certificate from `config` and `router`.
+    ///
+    /// Server names are matched case-insensitively. Adding the same name again replaces it.
+    pub fn host(mut self, server_name: &str, config: RustlsConfig, router: Router) -> Self {
+        self.hosts
+            .insert(server_name.to_ascii_lowercase(), (config, router));
+        self
+    }
+
+    /// Serve connections for hostnames that weren't added, and connections without SNI, with the
+    /// certificate from `config` and `router`.
+    pub fn default_host(self, config: RustlsConfig, router: Router) -> Self {
+        Self {
+            default: Some((config, router)),
+            ..self
+        }
+    }
+
+    /// Create the [`RustlsConfig`] that picks the certificate for each connection.
+    ///
+    /// The certificates are looked up during each handshake, so reloading the config of a host
+    /// applies to new connections.
+    pub fn rustls_config(&self) -> RustlsConfig {
+        let resolver = SniResolver {
+            hosts: self
+                .hosts
+                .iter()
+                .map(|(name, (config, _))| (name.clone(), config.clone()))
+                .collect(),
+            default: self.default.as_ref().map(|(config, _)| config.clone()),
+        };
+
+        let mut config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(resolver));
+        tls::set_alpn_protocols(&mut config);
+
+        RustlsConfig::from_config(Arc::new(config))
+    }
+
+    fn router(&self, server_name: Option<&str>) -> Router {
+        let host = server_name
+            .and_then(|name| self.hosts.get(&name.to_ascii_lowercase()))
+            .or(self.default.as_ref());
+        let router = match host {
+            Some((_, router)) => router.clone(),
+            // only reachable if the config from `rustls_config` isn't used
+            None => return Router::new().fallback(|| async { StatusCode::MISDIRECTED_REQUEST }),
+        };
+        match server_name {
+            Some(server_name) => Router::new().fallback_service(CheckHost {
+                server_name: server_name.into(),
+                router,
+            }),
+            None => router,
+        }
+    }
+}
+
+impl Service<IncomingStream<'_>> for SniRouter {
+    type Response = Router;
+    type Error = Infallible;
+    type Future = Ready<Result<Router, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: IncomingStream<'_>) -> Self::Future {
+        ready(Ok(self.router(req.stream.inner().server_name())))
+    }
+}
+
+/// Responds with `421 Misdirected Request` to requests for another host than the server name of
+/// the connection.
+#[derive(Clone)]
+struct CheckHost {
+    server_name: Arc<str>,
+    router: Router,
+}
+
+impl Service<Request> for CheckHost {
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Either<Ready<Result<Response, Infallible>>, RouteFuture<Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if self.is_misdirected(&req) {
+            Either::Left(ready(Ok(StatusCode::MISDIRECTED_REQUEST.into_response())))
+        } else {
+            Either::Right(self.router.call(req))
+        }
+    }
+}
+
+impl CheckHost {
+    fn is_misdirected(&self, req: &Request) -> bool {
+        // HTTP/2 requests have the host in the URI, HTTP/1 requests in the `Host` header
+        if let Some(host) = req.uri().host() {
+            return !host.eq_ignore_ascii_case(&self.server_name);
+        }
+        let authority = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Authority>().ok());
+        match authority {
+            Some(authority) => !authority.host().eq_ignore_ascii_case(&self.server_name),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SniResolver {
+    hosts: HashMap<String, RustlsConfig>,
+    default: Option<RustlsConfig>,
+}
+
+impl ResolvesServerCert for SniResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let config = client_hello
+            .server_name()
+            .and_then(|name| self.hosts.get(&name.to_ascii_lowercase()))
+            .or(self.default.as_ref())?;
+        config.get_inner().cert_resolver.resolve(client_hello)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::get;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_rustls::{
+        rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+        TlsConnector,
+    };
+
+    const CERT: &[u8] = include_bytes!("../test_helpers/certs/cert.pem");
+    const KEY: &[u8] = include_bytes!("../test_helpers/certs/key.pem");
+
+    #[crate::test]
+    async fn routes_by_server_name() {
+        let config = RustlsConfig::from_pem(CERT, KEY).unwrap();
+        let sni = SniRouter::new()
+            .host(
+                "LOCALHOST",
+                config.clone(),
+                Router::new().route("/", get(|| async { "localhost" })),
+            )
+            .default_host(
+                config,
+                Router::new().route("/", get(|| async { "default" })),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            super::super::serve_tls(listener, sni.rustls_config(), sni)
+                .await
+                .unwrap();
+        });
+
+        let get_root = |enable_sni: bool, host: &'static str| async move {
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_pemfile::certs(&mut &*CERT) {
+                roots.add(cert.unwrap()).unwrap();
+            }
+            let mut client_config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            client_config.enable_sni = enable_sni;
+            let connector = TlsConnector::from(Arc::new(client_config));
+
+            let tcp_stream = TcpStream::connect(addr).await.unwrap();
+            let mut stream = connector
+                .connect(ServerName::try_from("localhost").unwrap(), tcp_stream)
+                .await
+                .unwrap();
+
+            let request = format!("GET / HTTP/1.1\r\nhost: {host}\r\nconnection: close\r\n\r\n");
+            stream.write_all(request.as_bytes()).await.unwrap();
+            // the connection might be closed without a TLS `close_notify`, which is an error here
+            let mut response = Vec::new();
+            let _ = stream.read_to_end(&mut response).await;
+            String::from_utf8(response).unwrap()
+        };
+
+        let response = get_root(true, "localhost").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("localhost"));
+
+        let response = get_root(false, "localhost").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("default"));
+
+        let response = get_root(true, "LocalHost:443").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("localhost"));
+
+        // the `Host` header must match the server name of the connection
+        let response = get_root(true, "example.com").await;
+        assert!(response.starts_with("HTTP/1.1 421 Misdirected Request"));
+    }
+}
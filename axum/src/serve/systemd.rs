@@ -0,0 +1,151 @@
+//! Support for running under [systemd].
+//!
+//! With [socket activation] systemd binds the sockets and passes them to the service when it
+//! starts, so connections aren't refused while the service restarts. [`ListenFds`] takes those
+//! sockets so they can be used with [`serve`](super::serve), and [`notify_ready`] tells systemd
+//! when the service is ready to handle requests.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use axum::{Router, routing::get, serve::systemd};
+//!
+//! # async {
+//! let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+//!
+//! // use the socket from systemd, or bind one when running outside of systemd
+//! let listener = match systemd::ListenFds::from_env().take_tcp_listener(0).unwrap() {
+//!     Some(listener) => listener,
+//!     None => tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap(),
+//! };
+//!
+//! systemd::notify_ready().unwrap();
+//! axum::serve(listener, router).await.unwrap();
+//! # };
+//! ```
+//!
+//! [systemd]: https://systemd.io
+//! [socket activation]: https://www.freedesktop.org/software/systemd/man/latest/sd_listen_fds.html
+
+use std::{fmt, io};
+
+use tokio::net::{TcpListener, UnixListener};
+
+/// The sockets passed to the process with the `LISTEN_FDS` and `LISTEN_PID` environment
+/// variables.
+///
+/// Sockets are identified by their index, in the order of the `Listen*` options in the systemd
+/// socket unit. Each socket can only be taken once.
+pub struct ListenFds {
+    inner: listenfd::ListenFd,
+}
+
+impl ListenFds {
+    /// Read the sockets passed by systemd from the environment.
+    ///
+    /// No sockets are available if the process wasn't started with socket activation.
+    pub fn from_env() -> Self {
+        Self {
+            inner: listenfd::ListenFd::from_env(),
+        }
+    }
+
+    /// The number of sockets passed to the process, including ones that have been taken.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether no sockets were passed to the process.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take the TCP listener at `idx`.
+    ///
+    /// Returns `None` if there is no socket at `idx` or it has already been taken, and an error if
+    /// the socket isn't a TCP listener. Must be called from within a tokio runtime.
+    pub fn take_tcp_listener(&mut self, idx: usize) -> io::Result<Option<TcpListener>> {
+        self.inner
+            .take_tcp_listener(idx)?
+            .map(|listener| {
+                listener.set_nonblocking(true)?;
+                TcpListener::from_std(listener)
+            })
+            .transpose()
+    }
+
+    /// Take the Unix domain socket listener at `idx`.
+    ///
+    /// Returns `None` if there is no socket at `idx` or it has already been taken, and an error if
+    /// the socket isn't a Unix domain socket listener. Must be called from within a tokio runtime.
+    pub fn take_unix_listener(&mut self, idx: usize) -> io::Result<Option<UnixListener>> {
+        self.inner
+            .take_unix_listener(idx)?
+            .map(|listener| {
+                listener.set_nonblocking(true)?;
+                UnixListener::from_std(listener)
+            })
+            .transpose()
+    }
+}
+
+impl fmt::Debug for ListenFds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListenFds")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Tell systemd that the service has started and is ready to handle requests.
+///
+/// Call this after creating the listeners, for services with `Type=notify`. Does nothing if the
+/// process wasn't started by systemd.
+pub fn notify_ready() -> io::Result<()> {
+    sd_notify::notify(false, &[sd_notify::NotifyState::Ready])
+}
+
+/// Tell systemd that the service is shutting down.
+///
+/// A good place to call this is [`WithGracefulShutdown::on_drain_start`]. Does nothing if the
+/// process wasn't started by systemd.
+///
+/// [`WithGracefulShutdown::on_drain_start`]: super::WithGracefulShutdown::on_drain_start
+pub fn notify_stopping() -> io::Result<()> {
+    sd_notify::notify(false, &[sd_notify::NotifyState::Stopping])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[crate::test]
+    async fn no_listen_fds() {
+        let mut fds = ListenFds::from_env();
+        assert!(fds.is_empty());
+        assert!(fds.take_tcp_listener(0).unwrap().is_none());
+        assert!(fds.take_unix_listener(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn notify() {
+        let dir = std::env::temp_dir().join(format!("axum-systemd-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notify.sock");
+        let socket = UnixDatagram::bind(&path).unwrap();
+
+        std::env::set_var("NOTIFY_SOCKET", &path);
+        notify_ready().unwrap();
+        notify_stopping().unwrap();
+        std::env::remove_var("NOTIFY_SOCKET");
+
+        let mut buf = [0; 64];
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(std::str::from_utf8(&buf[..n]).unwrap().trim(), "READY=1");
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(std::str::from_utf8(&buf[..n]).unwrap().trim(), "STOPPING=1");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,387 @@
+//! TLS support for [`serve_tls`](super::serve_tls), using [rustls].
+//!
+//! [rustls]: https://crates.io/crates/rustls
+
+use std::{
+    fmt, io,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use tokio_rustls::rustls::{
+    pki_types::CertificateDer, server::ServerConnection, ProtocolVersion, ServerConfig,
+};
+
+use super::IncomingStream;
+use crate::extract::connect_info::Connected;
+
+/// The rustls configuration used by [`serve_tls`](super::serve_tls).
+///
+/// `RustlsConfig` is a cheaply cloneable handle. Reloading the configuration through any clone,
+/// for example after a certificate has been renewed, applies to all new connections. Existing
+/// connections keep using the configuration they were established with.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{Router, routing::get, serve::RustlsConfig};
+/// use std::time::Duration;
+///
+/// # async {
+/// let config = RustlsConfig::from_pem_file("cert.pem", "key.pem").await.unwrap();
+///
+/// // reload the certificate once a day
+/// tokio::spawn({
+///     let config = config.clone();
+///     async move {
+///         loop {
+///             tokio::time::sleep(Duration::from_secs(60 * 60 * 24)).await;
+///             if let Err(err) = config.reload_from_pem_file("cert.pem", "key.pem").await {
+///                 eprintln!("failed to reload certificate: {err}");
+///             }
+///         }
+///     }
+/// });
+///
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// axum::serve_tls(listener, config, router).await.unwrap();
+/// # };
+/// ```
+#[derive(Clone)]
+pub struct RustlsConfig {
+    inner: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl RustlsConfig {
+    /// Create a config from a rustls [`ServerConfig`].
+    ///
+    /// Remember to set [`ServerConfig::alpn_protocols`] to `h2` and `http/1.1` to support HTTP/2.
+    pub fn from_config(config: Arc<ServerConfig>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(config)),
+        }
+    }
+
+    /// Create a config from a PEM encoded certificate chain and private key.
+    ///
+    /// ALPN is configured for the HTTP versions enabled with the `http1` and `http2` features.
+    pub fn from_pem(cert: &[u8], key: &[u8]) -> io::Result<Self> {
+        Ok(Self::from_config(config_from_pem(cert, key)?))
+    }
+
+    /// Create a config from files containing a PEM encoded certificate chain and private key.
+    ///
+    /// See [`RustlsConfig::from_pem`] for more details.
+    pub async fn from_pem_file(cert: impl AsRef<Path>, key: impl AsRef<Path>) -> io::Result<Self> {
+        let (cert, key) = read_pem_files(cert.as_ref(), key.as_ref()).await?;
+        Self::from_pem(&cert, &key)
+    }
+
+    /// Get the current rustls [`ServerConfig`].
+    pub fn get_inner(&self) -> Arc<ServerConfig> {
+        Arc::clone(&self.inner.read().unwrap_or_else(|err| err.into_inner()))
+    }
+
+    /// Replace the config with a rustls [`ServerConfig`].
+    pub fn reload_from_config(&self, config: Arc<ServerConfig>) {
+        *self.inner.write().unwrap_or_else(|err| err.into_inner()) = config;
+    }
+
+    /// Replace the config with one created from a PEM encoded certificate chain and private key.
+    ///
+    /// The current config is kept if the certificate or key is invalid.
+    pub fn reload_from_pem(&self, cert: &[u8], key: &[u8]) -> io::Result<()> {
+        self.reload_from_config(config_from_pem(cert, key)?);
+        Ok(())
+    }
+
+    /// Replace the config with one created from files containing a PEM encoded certificate chain
+    /// and private key.
+    ///
+    /// The current config is kept if the files can't be read or are invalid.
+    pub async fn reload_from_pem_file(
+        &self,
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> io::Result<()> {
+        let (cert, key) = read_pem_files(cert.as_ref(), key.as_ref()).await?;
+        self.reload_from_pem(&cert, &key)
+    }
+}
+
+impl fmt::Debug for RustlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RustlsConfig").finish_non_exhaustive()
+    }
+}
+
+async fn read_pem_files(cert: &Path, key: &Path) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let cert = tokio::fs::read(cert).await?;
+    let key = tokio::fs::read(key).await?;
+    Ok((cert, key))
+}
+
+fn config_from_pem(cert: &[u8], key: &[u8]) -> io::Result<Arc<ServerConfig>> {
+    let certs = rustls_pemfile::certs(&mut &*cert).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no certificates found",
+        ));
+    }
+    let key = rustls_pemfile::private_key(&mut &*key)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no private key found"))?;
+
+    let mut config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    set_alpn_protocols(&mut config);
+
+    Ok(Arc::new(config))
+}
+
+/// Offer the HTTP versions enabled with the `http1` and `http2` features with ALPN.
+pub(super) fn set_alpn_protocols(config: &mut ServerConfig) {
+    #[cfg(feature = "http2")]
+    config.alpn_protocols.push(b"h2".to_vec());
+    #[cfg(feature = "http1")]
+    config.alpn_protocols.push(b"http/1.1".to_vec());
+}
+
+/// Information about the TLS session of a connection.
+///
+/// Available from [`IncomingStream::tls_info`] and [`TlsConnectInfo`].
+#[derive(Debug, Clone)]
+pub struct TlsInfo {
+    server_name: Option<String>,
+    alpn_protocol: Option<Vec<u8>>,
+    protocol_version: Option<ProtocolVersion>,
+    peer_certificates: Option<Vec<CertificateDer<'static>>>,
+}
+
+impl TlsInfo {
+    pub(super) fn new(conn: &ServerConnection) -> Self {
+        Self {
+            server_name: conn.server_name().map(ToOwned::to_owned),
+            alpn_protocol: conn.alpn_protocol().map(ToOwned::to_owned),
+            protocol_version: conn.protocol_version(),
+            peer_certificates: conn
+                .peer_certificates()
+                .map(|certs| certs.iter().map(|cert| cert.clone().into_owned()).collect()),
+        }
+    }
+
+    /// The server name the client asked for with SNI, if any.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// The protocol negotiated with ALPN, such as `h2`, if any.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// The TLS version of the connection.
+    pub fn protocol_version(&self) -> Option<ProtocolVersion> {
+        self.protocol_version
+    }
+
+    /// The certificate chain sent by the client, if client authentication is configured.
+    pub fn peer_certificates(&self) -> Option<&[CertificateDer<'static>]> {
+        self.peer_certificates.as_deref()
+    }
+}
+
+/// Connection information for connections accepted by [`serve_tls`](super::serve_tls).
+///
+/// Use it with `into_make_service_with_connect_info` and extract it with
+/// [`ConnectInfo`](crate::extract::ConnectInfo).
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{
+///     Router,
+///     extract::ConnectInfo,
+///     routing::get,
+///     serve::{RustlsConfig, TlsConnectInfo},
+/// };
+///
+/// async fn handler(ConnectInfo(info): ConnectInfo<TlsConnectInfo>) -> String {
+///     let server_name = info.tls().and_then(|tls| tls.server_name());
+///     format!("Hello {}, you asked for {server_name:?}", info.remote_addr())
+/// }
+///
+/// # async {
+/// let config = RustlsConfig::from_pem_file("cert.pem", "key.pem").await.unwrap();
+/// let router = Router::new().route("/", get(handler));
+///
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// axum::serve_tls(
+///     listener,
+///     config,
+///     router.into_make_service_with_connect_info::<TlsConnectInfo>(),
+/// )
+/// .await
+/// .unwrap();
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConnectInfo {
+    remote_addr: SocketAddr,
+    tls: Option<TlsInfo>,
+}
+
+impl TlsConnectInfo {
+    /// The address of the client.
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+
+    /// The TLS session, or `None` if the connection doesn't use TLS.
+    pub fn tls(&self) -> Option<&TlsInfo> {
+        self.tls.as_ref()
+    }
+}
+
+impl Connected<IncomingStream<'_>> for TlsConnectInfo {
+    fn connect_info(target: IncomingStream<'_>) -> Self {
+        Self {
+            remote_addr: target.remote_addr(),
+            tls: target.tls_info(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract::ConnectInfo, routing::get, Router};
+    use std::future::IntoFuture;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_rustls::{
+        rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+        TlsConnector,
+    };
+
+    const CERT: &[u8] = include_bytes!("../test_helpers/certs/cert.pem");
+    const KEY: &[u8] = include_bytes!("../test_helpers/certs/key.pem");
+
+    #[test]
+    fn invalid_pem() {
+        assert!(RustlsConfig::from_pem(b"", KEY).is_err());
+        assert!(RustlsConfig::from_pem(CERT, b"").is_err());
+        assert!(RustlsConfig::from_pem(CERT, CERT).is_err());
+    }
+
+    #[test]
+    fn reload() {
+        let config = RustlsConfig::from_pem(CERT, KEY).unwrap();
+        let clone = config.clone();
+        let before = config.get_inner();
+
+        assert!(clone.reload_from_pem(b"", b"").is_err());
+        assert!(Arc::ptr_eq(&before, &config.get_inner()));
+
+        clone.reload_from_pem(CERT, KEY).unwrap();
+        assert!(!Arc::ptr_eq(&before, &config.get_inner()));
+    }
+
+    #[crate::test]
+    async fn serve_tls() {
+        let config = RustlsConfig::from_pem(CERT, KEY).unwrap();
+
+        let app = Router::new().route(
+            "/",
+            get(
+                |ConnectInfo(info): ConnectInfo<TlsConnectInfo>| async move {
+                    let tls = info.tls().unwrap();
+                    format!("{:?}", tls.server_name())
+                },
+            ),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            super::super::serve_tls(
+                listener,
+                config,
+                app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut &*CERT) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = TlsConnector::from(Arc::new(client_config));
+
+        let tcp_stream = TcpStream::connect(addr).await.unwrap();
+        let mut stream = connector
+            .connect(ServerName::try_from("localhost").unwrap(), tcp_stream)
+            .await
+            .unwrap();
+
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        // the connection might be closed without a TLS `close_notify`, which is an error here
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response).await;
+        let response = String::from_utf8(response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("Some(\"localhost\")"));
+    }
+
+    #[crate::test]
+    async fn accept_filter_sees_server_name_before_handshake() {
+        let config = RustlsConfig::from_pem(CERT, KEY).unwrap();
+
+        let app = Router::new().route("/", get(|| async {}));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            super::super::serve_tls(listener, config, app)
+                .accept_filter(|conn| {
+                    if !conn.is_final() {
+                        assert_eq!(conn.server_name(), None);
+                        return Some(());
+                    }
+                    (conn.server_name() != Some("localhost")).then_some(())
+                })
+                .into_future(),
+        );
+
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut &*CERT) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = TlsConnector::from(Arc::new(client_config));
+
+        // rejected after the ClientHello, so the handshake fails
+        let tcp_stream = TcpStream::connect(addr).await.unwrap();
+        let result = connector
+            .connect(ServerName::try_from("localhost").unwrap(), tcp_stream)
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,487 @@
+//! Serving over Unix domain sockets with [`serve_uds`].
+
+use std::{
+    convert::Infallible,
+    fmt,
+    future::{Future, IntoFuture},
+    io,
+    marker::PhantomData,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::{Path, PathBuf},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum_core::{extract::Request, response::Response};
+use futures_util::{future::BoxFuture, FutureExt};
+use hyper_util::rt::TokioIo;
+use tokio::net::{
+    unix::{SocketAddr, UCred},
+    UnixListener, UnixStream,
+};
+use tower_service::Service;
+
+use super::{
+    accept_filter, is_connection_error, private::ServeFuture, serve_connections,
+    AcceptedConnection, Addrs, Connections, HttpConfig, InFlightRequests, MakeConnectionService,
+    Peer, ServeConfig, Stream,
+};
+use crate::extract::connect_info::Connected;
+
+/// Serve the service on a Unix domain socket at `path`.
+///
+/// This works like [`serve`](super::serve) but for local clients, such as sidecars, that connect
+/// through the filesystem rather than over TCP.
+///
+/// The socket is created when the returned future is first polled, and removed again after a
+/// graceful shutdown.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{Router, routing::get};
+///
+/// # async {
+/// let router = Router::new().route("/", get(|| async { "Hello, World!" }));
+///
+/// axum::serve_uds("/run/my-app.sock", router)
+///     .permissions(0o660)
+///     .remove_existing(true)
+///     .await
+///     .unwrap();
+/// # };
+/// ```
+///
+/// Use [`UdsConnectInfo`] to access the credentials of the connecting process:
+///
+/// ```no_run
+/// use axum::{Router, extract::ConnectInfo, routing::get, serve::UdsConnectInfo};
+///
+/// async fn handler(ConnectInfo(info): ConnectInfo<UdsConnectInfo>) -> String {
+///     format!("Hello {:?}", info.uid())
+/// }
+///
+/// # async {
+/// let router = Router::new().route("/", get(handler));
+///
+/// axum::serve_uds(
+///     "/run/my-app.sock",
+///     router.into_make_service_with_connect_info::<UdsConnectInfo>(),
+/// )
+/// .await
+/// .unwrap();
+/// # };
+/// ```
+pub fn serve_uds<M, S>(path: impl AsRef<Path>, make_service: M) -> ServeUds<M, S>
+where
+    M: for<'a> Service<UdsIncomingStream<'a>, Error = Infallible, Response = S>,
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    ServeUds {
+        path: path.as_ref().to_owned(),
+        make_service,
+        permissions: None,
+        remove_existing: false,
+        signal: None,
+        config: ServeConfig::default(),
+        _marker: PhantomData,
+    }
+}
+
+/// Future returned by [`serve_uds`].
+#[must_use = "futures must be awaited or polled"]
+pub struct ServeUds<M, S> {
+    path: PathBuf,
+    make_service: M,
+    permissions: Option<u32>,
+    remove_existing: bool,
+    signal: Option<BoxFuture<'static, ()>>,
+    config: ServeConfig,
+    _marker: PhantomData<S>,
+}
+
+impl<M, S> ServeUds<M, S> {
+    /// Set the permissions of the socket file, such as `0o660`, after creating it.
+    ///
+    /// Clients need write permission on the socket to connect.
+    pub fn permissions(self, mode: u32) -> Self {
+        Self {
+            permissions: Some(mode),
+            ..self
+        }
+    }
+
+    /// Remove a socket left behind by a previous run before creating the socket.
+    ///
+    /// Only sockets are removed. If something else exists at the path, serving fails as usual.
+    pub fn remove_existing(self, remove_existing: bool) -> Self {
+        Self {
+            remove_existing,
+            ..self
+        }
+    }
+
+    /// Stop accepting connections when `signal` completes, wait for the open connections to
+    /// finish, and remove the socket file.
+    pub fn with_graceful_shutdown<F>(self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            signal: Some(Box::pin(signal)),
+            ..self
+        }
+    }
+
+    /// Read a PROXY protocol header at the start of each connection.
+    ///
+    /// See [`Serve::proxy_protocol`]. The client address from the header is available from
+    /// [`UdsIncomingStream::remote_addr`].
+    ///
+    /// [`Serve::proxy_protocol`]: super::Serve::proxy_protocol
+    pub fn proxy_protocol(mut self, proxy_protocol: bool) -> Self {
+        self.config.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// Returns a handle to the number of requests currently being handled.
+    pub fn in_flight_requests(&self) -> InFlightRequests {
+        self.config.in_flight.clone()
+    }
+
+    /// Configure the HTTP/1 and HTTP/2 protocol settings of each connection.
+    ///
+    /// See [`Serve::http_config`].
+    ///
+    /// [`Serve::http_config`]: super::Serve::http_config
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.config.http_config = http_config;
+        self
+    }
+
+    /// Decide whether to serve each accepted connection.
+    ///
+    /// See [`Serve::accept_filter`]. [`AcceptedConnection::remote_addr`] is `None` unless the PROXY
+    /// protocol header provides an address.
+    ///
+    /// [`Serve::accept_filter`]: super::Serve::accept_filter
+    pub fn accept_filter<A, G>(mut self, filter: A) -> Self
+    where
+        A: Fn(&AcceptedConnection<'_>) -> Option<G> + Send + Sync + 'static,
+        G: Send + 'static,
+    {
+        self.config.accept_filter = Some(accept_filter(filter));
+        self
+    }
+
+    /// Close the connections that are still open `timeout` after the graceful shutdown signal.
+    ///
+    /// See [`WithGracefulShutdown::drain_timeout`]. Has no effect without
+    /// [`ServeUds::with_graceful_shutdown`].
+    ///
+    /// [`WithGracefulShutdown::drain_timeout`]: super::WithGracefulShutdown::drain_timeout
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.config.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Call `f` once the server stops accepting connections and starts draining the open ones.
+    ///
+    /// See [`WithGracefulShutdown::on_drain_start`]. Has no effect without
+    /// [`ServeUds::with_graceful_shutdown`].
+    ///
+    /// [`WithGracefulShutdown::on_drain_start`]: super::WithGracefulShutdown::on_drain_start
+    pub fn on_drain_start<C>(mut self, f: C) -> Self
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        self.config.on_drain_start = Some(Box::new(f));
+        self
+    }
+}
+
+impl<M, S> fmt::Debug for ServeUds<M, S>
+where
+    M: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            path,
+            make_service,
+            permissions,
+            remove_existing,
+            signal,
+            config,
+            _marker: _,
+        } = self;
+
+        f.debug_struct("ServeUds")
+            .field("path", path)
+            .field("make_service", make_service)
+            .field("permissions", permissions)
+            .field("remove_existing", remove_existing)
+            .field("graceful_shutdown", &signal.is_some())
+            .field("config", config)
+            .finish()
+    }
+}
+
+impl<M, S> IntoFuture for ServeUds<M, S>
+where
+    M: for<'a> Service<UdsIncomingStream<'a>, Error = Infallible, Response = S> + Send + 'static,
+    for<'a> <M as Service<UdsIncomingStream<'a>>>::Future: Send,
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Output = io::Result<()>;
+    type IntoFuture = ServeFuture;
+
+    fn into_future(self) -> Self::IntoFuture {
+        ServeFuture(Box::pin(async move {
+            let Self {
+                path,
+                make_service,
+                permissions,
+                remove_existing,
+                signal,
+                config,
+                _marker: _,
+            } = self;
+
+            let listener = bind(&path, permissions, remove_existing)?;
+
+            serve_connections(
+                uds_incoming(listener),
+                UdsMakeService(make_service),
+                config,
+                signal,
+            )
+            .await;
+
+            std::fs::remove_file(&path)
+        }))
+    }
+}
+
+/// Create the socket at `path`.
+pub(super) fn bind(
+    path: &Path,
+    permissions: Option<u32>,
+    remove_existing: bool,
+) -> io::Result<UnixListener> {
+    if remove_existing {
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => {
+                std::fs::remove_file(path)?;
+            }
+            _ => {}
+        }
+    }
+
+    let listener = UnixListener::bind(path)?;
+    if let Some(mode) = permissions {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    Ok(listener)
+}
+
+pub(super) fn uds_incoming(listener: UnixListener) -> Connections {
+    Box::pin(futures_util::stream::unfold(
+        listener,
+        |listener| async move {
+            let conn = uds_accept(&listener)
+                .await
+                .map(|(unix_stream, addr)| (Stream::Unix(unix_stream), Peer::Unix(addr)));
+            Some((conn, listener))
+        },
+    ))
+}
+
+async fn uds_accept(listener: &UnixListener) -> Option<(UnixStream, SocketAddr)> {
+    match listener.accept().await {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            if is_connection_error(&e) {
+                return None;
+            }
+
+            // see `tcp_accept`
+            error!("accept error: {e}");
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            None
+        }
+    }
+}
+
+/// Calls a service that makes services with a [`UdsIncomingStream`].
+struct UdsMakeService<M>(M);
+
+impl<M, S> MakeConnectionService for UdsMakeService<M>
+where
+    M: for<'a> Service<UdsIncomingStream<'a>, Error = Infallible, Response = S> + Send + 'static,
+    for<'a> <M as Service<UdsIncomingStream<'a>>>::Future: Send,
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+{
+    type Service = S;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.0
+            .poll_ready(cx)
+            .map(|result| result.unwrap_or_else(|err| match err {}))
+    }
+
+    fn make_service<'a>(
+        &'a mut self,
+        stream: &'a TokioIo<Stream>,
+        addrs: &'a Addrs,
+    ) -> BoxFuture<'a, S> {
+        let (Stream::Unix(stream), Peer::Unix(peer_addr)) = (stream.inner(), &addrs.peer) else {
+            unreachable!("`serve_uds` only accepts Unix domain socket connections")
+        };
+        let incoming = UdsIncomingStream {
+            stream,
+            peer_addr,
+            remote_addr: addrs.proxied,
+        };
+        Box::pin(
+            self.0
+                .call(incoming)
+                .map(|result| result.unwrap_or_else(|err| match err {})),
+        )
+    }
+}
+
+/// An incoming stream accepted by [`serve_uds`].
+///
+/// Used with [`serve_uds`] and [`IntoMakeServiceWithConnectInfo`].
+///
+/// [`IntoMakeServiceWithConnectInfo`]: crate::extract::connect_info::IntoMakeServiceWithConnectInfo
+#[derive(Debug)]
+pub struct UdsIncomingStream<'a> {
+    stream: &'a UnixStream,
+    peer_addr: &'a SocketAddr,
+    remote_addr: Option<std::net::SocketAddr>,
+}
+
+impl UdsIncomingStream<'_> {
+    /// Returns the address of the connecting socket.
+    ///
+    /// This is usually unnamed since clients rarely bind their sockets to a path.
+    pub fn peer_addr(&self) -> &SocketAddr {
+        self.peer_addr
+    }
+
+    /// Returns the client address from the PROXY protocol header.
+    ///
+    /// This is `None` unless [`ServeUds::proxy_protocol`] is enabled and the header provides an
+    /// address.
+    pub fn remote_addr(&self) -> Option<std::net::SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Returns the credentials of the connecting process.
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        self.stream.peer_cred()
+    }
+}
+
+/// Connection information for connections accepted by [`serve_uds`].
+///
+/// Holds the credentials of the process on the other end of the socket, as reported by the
+/// operating system. See [`serve_uds`] for an example.
+#[derive(Debug, Clone, Copy)]
+pub struct UdsConnectInfo {
+    peer_cred: Option<UCred>,
+}
+
+impl UdsConnectInfo {
+    /// The user ID of the connecting process, if available.
+    pub fn uid(&self) -> Option<u32> {
+        self.peer_cred.map(|cred| cred.uid())
+    }
+
+    /// The group ID of the connecting process, if available.
+    pub fn gid(&self) -> Option<u32> {
+        self.peer_cred.map(|cred| cred.gid())
+    }
+
+    /// The process ID of the connecting process, if available.
+    ///
+    /// Not all platforms report the process ID.
+    pub fn pid(&self) -> Option<i32> {
+        self.peer_cred.and_then(|cred| cred.pid())
+    }
+}
+
+impl Connected<UdsIncomingStream<'_>> for UdsConnectInfo {
+    fn connect_info(target: UdsIncomingStream<'_>) -> Self {
+        Self {
+            peer_cred: target.peer_cred().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract::ConnectInfo, routing::get, Router};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[crate::test]
+    async fn serve_with_connect_info() {
+        let dir = std::env::temp_dir().join(format!("axum-serve-uds-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.sock");
+
+        let app = Router::new().route(
+            "/",
+            get(
+                |ConnectInfo(info): ConnectInfo<UdsConnectInfo>| async move {
+                    format!("uid: {}", info.uid().is_some())
+                },
+            ),
+        );
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            serve_uds(
+                &path,
+                app.into_make_service_with_connect_info::<UdsConnectInfo>(),
+            )
+            .permissions(0o600)
+            .remove_existing(true)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            })
+            .into_future(),
+        );
+
+        // wait for the socket to be created
+        let mut stream = loop {
+            match UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("uid: true"));
+
+        shutdown_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
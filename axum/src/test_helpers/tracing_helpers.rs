@@ -30,7 +30,7 @@ where
             .with_ansi(false)
             .json()
             .flatten_event(false)
-            .with_filter("axum=trace".parse::<Targets>().unwrap()),
+            .with_filter("axum::rejection=trace".parse::<Targets>().unwrap()),
     );
 
     let guard = tracing::subscriber::set_default(subscriber);
@@ -1,36 +1,7 @@
 use pin_project_lite::pin_project;
-use std::{ops::Deref, sync::Arc};
 
 pub(crate) use self::mutex::*;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub(crate) struct PercentDecodedStr(Arc<str>);
-
-impl PercentDecodedStr {
-    pub(crate) fn new<S>(s: S) -> Option<Self>
-    where
-        S: AsRef<str>,
-    {
-        percent_encoding::percent_decode(s.as_ref().as_bytes())
-            .decode_utf8()
-            .ok()
-            .map(|decoded| Self(decoded.as_ref().into()))
-    }
-
-    pub(crate) fn as_str(&self) -> &str {
-        &self.0
-    }
-}
-
-impl Deref for PercentDecodedStr {
-    type Target = str;
-
-    #[inline]
-    fn deref(&self) -> &Self::Target {
-        self.as_str()
-    }
-}
-
 pin_project! {
     #[project = EitherProj]
     pub(crate) enum Either<A, B> {
@@ -117,4 +88,16 @@ mod mutex {
             self.0.lock()
         }
     }
+
+    impl<T: Default> Default for AxumMutex<T> {
+        fn default() -> Self {
+            Self::new(T::default())
+        }
+    }
+
+    impl<T: std::fmt::Debug> std::fmt::Debug for AxumMutex<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.0.fmt(f)
+        }
+    }
 }
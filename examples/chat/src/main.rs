@@ -49,7 +49,10 @@ async fn main() {
 
     let app = Router::new()
         .route("/", get(index))
-        .route("/websocket", get(websocket_handler))
+        .route(
+            "/websocket",
+            get(websocket_handler).connect(websocket_handler),
+        )
         .with_state(app_state);
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
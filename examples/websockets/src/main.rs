@@ -56,7 +56,7 @@ async fn main() {
     // build our application with some routes
     let app = Router::new()
         .fallback_service(ServeDir::new(assets_dir).append_index_html_on_directories(true))
-        .route("/ws", get(ws_handler))
+        .route("/ws", get(ws_handler).connect(ws_handler))
         // logging so we can see whats going on
         .layer(
             TraceLayer::new_for_http()